@@ -9,15 +9,17 @@ use std::{
     hash::{Hash, Hasher},
     ptr::null_mut,
     str::FromStr,
+    sync::Arc,
 };
 
 use rgb_lib::{
-    AssetSchema, Assignment, Error as RgbLibError,
+    AssetSchema, Assignment, Error as RgbLibError, ReceivePolicy,
     keys::WitnessVersion,
     utils::BitcoinNetwork,
     wallet::{
-        Online, OnlineOptions, Recipient, RefreshFilter, RgbWalletOpsOffline, RgbWalletOpsOnline,
-        SinglesigKeys, SyncOptions, Wallet, WalletData,
+        Online, OnlineOptions, ReceiveData, Recipient, RefreshFilter, RgbWalletOpsOffline,
+        RgbWalletOpsOnline, SinglesigKeys, SwapLeg, SyncOptions, VssBackupTarget, Wallet,
+        WalletData,
     },
 };
 
@@ -61,11 +63,25 @@ pub extern "C" fn rgblib_backup(
     backup(wallet, backup_path, password).into()
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_backup_incremental(
+    wallet: &COpaqueStruct,
+    backup_path: *const c_char,
+    password: *const c_char,
+) -> CResult {
+    backup_incremental(wallet, backup_path, password).into()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rgblib_backup_info(wallet: &COpaqueStruct) -> CResultString {
     backup_info(wallet).into()
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_backup_remote(wallet: &COpaqueStruct) -> CResult {
+    backup_remote(wallet).into()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rgblib_burn(
     wallet: &COpaqueStruct,
@@ -94,6 +110,7 @@ pub extern "C" fn rgblib_burn_begin(
     amount: *const c_char,
     fee_rate: *const c_char,
     min_confirmations: *const c_char,
+    coin_selection_opt: *const c_char,
     dry_run: bool,
 ) -> CResultString {
     burn_begin(
@@ -103,6 +120,7 @@ pub extern "C" fn rgblib_burn_begin(
         amount,
         fee_rate,
         min_confirmations,
+        coin_selection_opt,
         dry_run,
     )
     .into()
@@ -122,6 +140,7 @@ pub extern "C" fn rgblib_blind_receive(
     wallet: &COpaqueStruct,
     asset_id_opt: *const c_char,
     assignment: *const c_char,
+    receive_policy: *const c_char,
     expiration_timestamp_opt: *const c_char,
     transport_endpoints: *const c_char,
     min_confirmations: *const c_char,
@@ -130,6 +149,7 @@ pub extern "C" fn rgblib_blind_receive(
         wallet,
         asset_id_opt,
         assignment,
+        receive_policy,
         expiration_timestamp_opt,
         transport_endpoints,
         min_confirmations,
@@ -137,6 +157,14 @@ pub extern "C" fn rgblib_blind_receive(
     .into()
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_create_payment_request(
+    wallet: &COpaqueStruct,
+    receive_data: *const c_char,
+) -> CResultString {
+    create_payment_request(wallet, receive_data).into()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rgblib_create_utxos(
     wallet: &COpaqueStruct,
@@ -188,6 +216,16 @@ pub extern "C" fn rgblib_delete_transfers(
     delete_transfers(wallet, batch_transfer_idx_opt, no_asset_only).into()
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_enable_vss_backup(
+    wallet: &COpaqueStruct,
+    url: *const c_char,
+    token_opt: *const c_char,
+    password: *const c_char,
+) -> CResult {
+    enable_vss_backup(wallet, url, token_opt, password).into()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rgblib_fail_transfers(
     wallet: &COpaqueStruct,
@@ -401,8 +439,29 @@ pub extern "C" fn rgblib_load_wallet(
     data_dir: *const c_char,
     master_fingerprint: *const c_char,
     mnemonic_opt: *const c_char,
+    password_opt: *const c_char,
 ) -> CResult {
-    load_wallet(data_dir, master_fingerprint, mnemonic_opt).into()
+    load_wallet(data_dir, master_fingerprint, mnemonic_opt, password_opt).into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_migrate_to(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    invoices: *const c_char,
+    address: *const c_char,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> CResultString {
+    migrate_to(
+        wallet,
+        online,
+        invoices,
+        address,
+        fee_rate,
+        min_confirmations,
+    )
+    .into()
 }
 
 #[unsafe(no_mangle)]
@@ -410,6 +469,13 @@ pub extern "C" fn rgblib_new_wallet(wallet_data: *const c_char, keys: *const c_c
     new_wallet(wallet_data, keys).into()
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_payment_request_data(
+    payment_request_string: *const c_char,
+) -> CResultString {
+    payment_request_data(payment_request_string).into()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rgblib_refresh(
     wallet: &COpaqueStruct,
@@ -430,6 +496,26 @@ pub extern "C" fn rgblib_restore_backup(
     restore_backup(backup_path, password, target_dir).into()
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_restore_backup_chain(
+    backup_paths: *const c_char,
+    password: *const c_char,
+    target_dir: *const c_char,
+) -> CResult {
+    restore_backup_chain(backup_paths, password, target_dir).into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_restore_backup_vss(
+    url: *const c_char,
+    token_opt: *const c_char,
+    fingerprint: *const c_char,
+    password: *const c_char,
+    target_dir: *const c_char,
+) -> CResult {
+    restore_backup_vss(url, token_opt, fingerprint, password, target_dir).into()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rgblib_restore_keys(
     bitcoin_network: *const c_char,
@@ -439,6 +525,39 @@ pub extern "C" fn rgblib_restore_keys(
     restore_keys(bitcoin_network, mnemonic, witness_version).into()
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_sell_order_create(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    asset_id: *const c_char,
+    assignment: *const c_char,
+    price: *const c_char,
+    expiration_timestamp: *const c_char,
+    min_confirmations: *const c_char,
+) -> CResultString {
+    sell_order_create(
+        wallet,
+        online,
+        asset_id,
+        assignment,
+        price,
+        expiration_timestamp,
+        min_confirmations,
+    )
+    .into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_sell_order_take(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    sell_order: *const c_char,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> CResultString {
+    sell_order_take(wallet, online, sell_order, fee_rate, min_confirmations).into()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rgblib_send(
     wallet: &COpaqueStruct,
@@ -470,6 +589,7 @@ pub extern "C" fn rgblib_send_begin(
     fee_rate: *const c_char,
     min_confirmations: *const c_char,
     expiration_timestamp_opt: *const c_char,
+    coin_selection_opt: *const c_char,
     dry_run: bool,
 ) -> CResultString {
     send_begin(
@@ -480,6 +600,7 @@ pub extern "C" fn rgblib_send_begin(
         fee_rate,
         min_confirmations,
         expiration_timestamp_opt,
+        coin_selection_opt,
         dry_run,
     )
     .into()
@@ -506,6 +627,26 @@ pub extern "C" fn rgblib_send_end(
     send_end(wallet, online, signed_psbt).into()
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_send_payment_request(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    payment_request: *const c_char,
+    donation: bool,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> CResultString {
+    send_payment_request(
+        wallet,
+        online,
+        payment_request,
+        donation,
+        fee_rate,
+        min_confirmations,
+    )
+    .into()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rgblib_sign_psbt(
     wallet: &COpaqueStruct,
@@ -514,6 +655,50 @@ pub extern "C" fn rgblib_sign_psbt(
     sign_psbt(wallet, unsigned_psbt).into()
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_swap_offer_complete(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    swap_offer: *const c_char,
+) -> CResultString {
+    swap_offer_complete(wallet, online, swap_offer).into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_swap_offer_create(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    asset_id: *const c_char,
+    assignment: *const c_char,
+    taker_leg: *const c_char,
+    expiration_timestamp: *const c_char,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> CResultString {
+    swap_offer_create(
+        wallet,
+        online,
+        asset_id,
+        assignment,
+        taker_leg,
+        expiration_timestamp,
+        fee_rate,
+        min_confirmations,
+    )
+    .into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rgblib_swap_offer_take(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    swap_offer: *const c_char,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> CResultString {
+    swap_offer_take(wallet, online, swap_offer, fee_rate, min_confirmations).into()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rgblib_sync(
     wallet: &COpaqueStruct,
@@ -528,6 +713,7 @@ pub extern "C" fn rgblib_witness_receive(
     wallet: &COpaqueStruct,
     asset_id_opt: *const c_char,
     assignment: *const c_char,
+    receive_policy: *const c_char,
     expiration_timestamp_opt: *const c_char,
     transport_endpoints: *const c_char,
     min_confirmations: *const c_char,
@@ -536,6 +722,7 @@ pub extern "C" fn rgblib_witness_receive(
         wallet,
         asset_id_opt,
         assignment,
+        receive_policy,
         expiration_timestamp_opt,
         transport_endpoints,
         min_confirmations,
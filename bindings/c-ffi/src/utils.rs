@@ -179,12 +179,30 @@ pub(crate) fn backup(
     Ok(())
 }
 
+pub(crate) fn backup_incremental(
+    wallet: &COpaqueStruct,
+    backup_path: *const c_char,
+    password: *const c_char,
+) -> Result<(), Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let backup_path = ptr_to_string(backup_path);
+    let password = ptr_to_string(password);
+    wallet.backup_incremental(&backup_path, &password)?;
+    Ok(())
+}
+
 pub(crate) fn backup_info(wallet: &COpaqueStruct) -> Result<String, Error> {
     let wallet = Wallet::from_opaque(wallet)?;
     let res = wallet.backup_info()?;
     Ok(serde_json::to_string(&res)?)
 }
 
+pub(crate) fn backup_remote(wallet: &COpaqueStruct) -> Result<(), Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    wallet.backup_remote()?;
+    Ok(())
+}
+
 pub(crate) fn burn(
     wallet: &COpaqueStruct,
     online: *const c_char,
@@ -210,6 +228,7 @@ pub(crate) fn burn_begin(
     amount: *const c_char,
     fee_rate: *const c_char,
     min_confirmations: *const c_char,
+    coin_selection_opt: *const c_char,
     dry_run: bool,
 ) -> Result<String, Error> {
     let wallet = Wallet::from_opaque(wallet)?;
@@ -218,12 +237,16 @@ pub(crate) fn burn_begin(
     let amount = ptr_to_num(amount)?;
     let fee_rate = ptr_to_num(fee_rate)?;
     let min_confirmations = ptr_to_num(min_confirmations)?;
+    let coin_selection = convert_optional_string(coin_selection_opt)
+        .map(|c| serde_json::from_str(&c))
+        .transpose()?;
     let res = wallet.burn_begin(
         online,
         asset_id,
         amount,
         fee_rate,
         min_confirmations,
+        coin_selection,
         dry_run,
     )?;
     Ok(serde_json::to_string(&res)?)
@@ -245,6 +268,7 @@ pub(crate) fn blind_receive(
     wallet: &COpaqueStruct,
     asset_id_opt: *const c_char,
     assignment: *const c_char,
+    receive_policy: *const c_char,
     expiration_timestamp_opt: *const c_char,
     transport_endpoints: *const c_char,
     min_confirmations: *const c_char,
@@ -254,11 +278,13 @@ pub(crate) fn blind_receive(
         serde_json::from_str(&ptr_to_string(transport_endpoints))?;
     let asset_id = convert_optional_string(asset_id_opt);
     let assignment: Assignment = serde_json::from_str(&ptr_to_string(assignment))?;
+    let receive_policy: ReceivePolicy = serde_json::from_str(&ptr_to_string(receive_policy))?;
     let expiration_timestamp = ptr_to_num(expiration_timestamp_opt)?;
     let min_confirmations = ptr_to_num(min_confirmations)?;
     let res = wallet.blind_receive(
         asset_id,
         assignment,
+        receive_policy,
         expiration_timestamp,
         transport_endpoints,
         min_confirmations,
@@ -266,6 +292,16 @@ pub(crate) fn blind_receive(
     Ok(serde_json::to_string(&res)?)
 }
 
+pub(crate) fn create_payment_request(
+    wallet: &COpaqueStruct,
+    receive_data: *const c_char,
+) -> Result<String, Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let receive_data: Vec<ReceiveData> = serde_json::from_str(&ptr_to_string(receive_data))?;
+    let res = wallet.create_payment_request(receive_data)?;
+    Ok(serde_json::to_string(&res)?)
+}
+
 pub(crate) fn create_utxos(
     wallet: &COpaqueStruct,
     online: *const c_char,
@@ -327,6 +363,20 @@ pub(crate) fn delete_transfers(
     Ok(serde_json::to_string(&res)?)
 }
 
+pub(crate) fn enable_vss_backup(
+    wallet: &COpaqueStruct,
+    url: *const c_char,
+    token_opt: *const c_char,
+    password: *const c_char,
+) -> Result<(), Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let backup_target =
+        VssBackupTarget::new(&ptr_to_string(url), convert_optional_string(token_opt))?;
+    let password = ptr_to_string(password);
+    wallet.enable_remote_backup(Arc::new(backup_target), &password)?;
+    Ok(())
+}
+
 pub(crate) fn fail_transfers(
     wallet: &COpaqueStruct,
     online: *const c_char,
@@ -586,11 +636,36 @@ pub(crate) fn load_wallet(
     data_dir: *const c_char,
     master_fingerprint: *const c_char,
     mnemonic_opt: *const c_char,
+    password_opt: *const c_char,
 ) -> Result<Wallet, Error> {
     let data_dir = ptr_to_string(data_dir);
     let master_fingerprint = ptr_to_string(master_fingerprint);
     let mnemonic = convert_optional_string(mnemonic_opt);
-    Ok(Wallet::load(&data_dir, &master_fingerprint, mnemonic)?)
+    let password = convert_optional_string(password_opt);
+    Ok(Wallet::load(
+        &data_dir,
+        &master_fingerprint,
+        mnemonic,
+        password,
+    )?)
+}
+
+pub(crate) fn migrate_to(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    invoices: *const c_char,
+    address: *const c_char,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> Result<String, Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let online = convert_online(online)?;
+    let invoices: Vec<String> = serde_json::from_str(&ptr_to_string(invoices))?;
+    let address = ptr_to_string(address);
+    let fee_rate = ptr_to_num(fee_rate)?;
+    let min_confirmations = ptr_to_num(min_confirmations)?;
+    let res = wallet.migrate_to(online, invoices, address, fee_rate, min_confirmations)?;
+    Ok(serde_json::to_string(&res)?)
 }
 
 pub(crate) fn new_wallet(wallet_data: *const c_char, keys: *const c_char) -> Result<Wallet, Error> {
@@ -599,6 +674,14 @@ pub(crate) fn new_wallet(wallet_data: *const c_char, keys: *const c_char) -> Res
     Ok(Wallet::new(wallet_data, keys)?)
 }
 
+pub(crate) fn payment_request_data(payment_request_string: *const c_char) -> Result<String, Error> {
+    let payment_request_string = ptr_to_string(payment_request_string);
+    let payment_request = rgb_lib::wallet::PaymentRequest::new(payment_request_string)?;
+    Ok(serde_json::to_string(
+        &payment_request.payment_request_data(),
+    )?)
+}
+
 pub(crate) fn refresh(
     wallet: &COpaqueStruct,
     online: *const c_char,
@@ -626,6 +709,35 @@ pub(crate) fn restore_backup(
     Ok(())
 }
 
+pub(crate) fn restore_backup_chain(
+    backup_paths: *const c_char,
+    password: *const c_char,
+    target_dir: *const c_char,
+) -> Result<(), Error> {
+    let backup_paths: Vec<String> = serde_json::from_str(&ptr_to_string(backup_paths))?;
+    let backup_paths: Vec<&str> = backup_paths.iter().map(String::as_str).collect();
+    let password = ptr_to_string(password);
+    let target_dir = ptr_to_string(target_dir);
+    rgb_lib::wallet::restore_backup_chain(&backup_paths, &password, &target_dir)?;
+    Ok(())
+}
+
+pub(crate) fn restore_backup_vss(
+    url: *const c_char,
+    token_opt: *const c_char,
+    fingerprint: *const c_char,
+    password: *const c_char,
+    target_dir: *const c_char,
+) -> Result<(), Error> {
+    let backup_target =
+        VssBackupTarget::new(&ptr_to_string(url), convert_optional_string(token_opt))?;
+    let fingerprint = ptr_to_string(fingerprint);
+    let password = ptr_to_string(password);
+    let target_dir = ptr_to_string(target_dir);
+    rgb_lib::wallet::restore_backup_remote(&backup_target, &fingerprint, &password, &target_dir)?;
+    Ok(())
+}
+
 pub(crate) fn restore_keys(
     bitcoin_network: *const c_char,
     mnemonic: *const c_char,
@@ -639,6 +751,49 @@ pub(crate) fn restore_keys(
 }
 
 #[allow(clippy::too_many_arguments)]
+pub(crate) fn sell_order_create(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    asset_id: *const c_char,
+    assignment: *const c_char,
+    price: *const c_char,
+    expiration_timestamp: *const c_char,
+    min_confirmations: *const c_char,
+) -> Result<String, Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let online = convert_online(online)?;
+    let asset_id = ptr_to_string(asset_id);
+    let assignment: Assignment = serde_json::from_str(&ptr_to_string(assignment))?;
+    let price = ptr_to_num(price)?;
+    let expiration_timestamp = ptr_to_num(expiration_timestamp)?;
+    let min_confirmations = ptr_to_num(min_confirmations)?;
+    let res = wallet.sell_order_create(
+        online,
+        asset_id,
+        assignment,
+        price,
+        expiration_timestamp,
+        min_confirmations,
+    )?;
+    Ok(serde_json::to_string(&res)?)
+}
+
+pub(crate) fn sell_order_take(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    sell_order: *const c_char,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> Result<String, Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let online = convert_online(online)?;
+    let sell_order = ptr_to_string(sell_order);
+    let fee_rate = ptr_to_num(fee_rate)?;
+    let min_confirmations = ptr_to_num(min_confirmations)?;
+    let res = wallet.sell_order_take(online, sell_order, fee_rate, min_confirmations)?;
+    Ok(serde_json::to_string(&res)?)
+}
+
 pub(crate) fn send(
     wallet: &COpaqueStruct,
     online: *const c_char,
@@ -675,6 +830,7 @@ pub(crate) fn send_begin(
     fee_rate: *const c_char,
     min_confirmations: *const c_char,
     expiration_timestamp_opt: *const c_char,
+    coin_selection_opt: *const c_char,
     dry_run: bool,
 ) -> Result<String, Error> {
     let wallet = Wallet::from_opaque(wallet)?;
@@ -684,6 +840,9 @@ pub(crate) fn send_begin(
     let fee_rate = ptr_to_num(fee_rate)?;
     let min_confirmations = ptr_to_num(min_confirmations)?;
     let expiration_timestamp = ptr_to_num(expiration_timestamp_opt)?;
+    let coin_selection = convert_optional_string(coin_selection_opt)
+        .map(|c| serde_json::from_str(&c))
+        .transpose()?;
     let res = wallet.send_begin(
         online,
         recipient_map,
@@ -691,6 +850,7 @@ pub(crate) fn send_begin(
         fee_rate,
         min_confirmations,
         expiration_timestamp,
+        coin_selection,
         dry_run,
     )?;
     Ok(serde_json::to_string(&res)?)
@@ -725,6 +885,29 @@ pub(crate) fn send_end(
     Ok(serde_json::to_string(&res)?)
 }
 
+pub(crate) fn send_payment_request(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    payment_request: *const c_char,
+    donation: bool,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> Result<String, Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let online = convert_online(online)?;
+    let payment_request = ptr_to_string(payment_request);
+    let fee_rate = ptr_to_num(fee_rate)?;
+    let min_confirmations = ptr_to_num(min_confirmations)?;
+    let res = wallet.send_payment_request(
+        online,
+        payment_request,
+        donation,
+        fee_rate,
+        min_confirmations,
+    )?;
+    Ok(serde_json::to_string(&res)?)
+}
+
 pub(crate) fn sign_psbt(
     wallet: &COpaqueStruct,
     unsigned_psbt: *const c_char,
@@ -734,6 +917,65 @@ pub(crate) fn sign_psbt(
     Ok(wallet.sign_psbt(unsigned_psbt, None)?)
 }
 
+pub(crate) fn swap_offer_complete(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    swap_offer: *const c_char,
+) -> Result<String, Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let online = convert_online(online)?;
+    let swap_offer = ptr_to_string(swap_offer);
+    let res = wallet.swap_offer_complete(online, swap_offer)?;
+    Ok(serde_json::to_string(&res)?)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn swap_offer_create(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    asset_id: *const c_char,
+    assignment: *const c_char,
+    taker_leg: *const c_char,
+    expiration_timestamp: *const c_char,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> Result<String, Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let online = convert_online(online)?;
+    let asset_id = ptr_to_string(asset_id);
+    let assignment: Assignment = serde_json::from_str(&ptr_to_string(assignment))?;
+    let taker_leg: SwapLeg = serde_json::from_str(&ptr_to_string(taker_leg))?;
+    let expiration_timestamp = ptr_to_num(expiration_timestamp)?;
+    let fee_rate = ptr_to_num(fee_rate)?;
+    let min_confirmations = ptr_to_num(min_confirmations)?;
+    let res = wallet.swap_offer_create(
+        online,
+        asset_id,
+        assignment,
+        taker_leg,
+        expiration_timestamp,
+        fee_rate,
+        min_confirmations,
+    )?;
+    Ok(serde_json::to_string(&res)?)
+}
+
+pub(crate) fn swap_offer_take(
+    wallet: &COpaqueStruct,
+    online: *const c_char,
+    swap_offer: *const c_char,
+    fee_rate: *const c_char,
+    min_confirmations: *const c_char,
+) -> Result<String, Error> {
+    let wallet = Wallet::from_opaque(wallet)?;
+    let online = convert_online(online)?;
+    let swap_offer = ptr_to_string(swap_offer);
+    let fee_rate = ptr_to_num(fee_rate)?;
+    let min_confirmations = ptr_to_num(min_confirmations)?;
+    let res = wallet.swap_offer_take(online, swap_offer, fee_rate, min_confirmations)?;
+    Ok(serde_json::to_string(&res)?)
+}
+
 pub(crate) fn sync(
     wallet: &COpaqueStruct,
     online: *const c_char,
@@ -750,6 +992,7 @@ pub(crate) fn witness_receive(
     wallet: &COpaqueStruct,
     asset_id_opt: *const c_char,
     assignment: *const c_char,
+    receive_policy: *const c_char,
     expiration_timestamp_opt: *const c_char,
     transport_endpoints: *const c_char,
     min_confirmations: *const c_char,
@@ -759,11 +1002,13 @@ pub(crate) fn witness_receive(
         serde_json::from_str(&ptr_to_string(transport_endpoints))?;
     let asset_id = convert_optional_string(asset_id_opt);
     let assignment: Assignment = serde_json::from_str(&ptr_to_string(assignment))?;
+    let receive_policy: ReceivePolicy = serde_json::from_str(&ptr_to_string(receive_policy))?;
     let expiration_timestamp = ptr_to_num(expiration_timestamp_opt)?;
     let min_confirmations = ptr_to_num(min_confirmations)?;
     let res = wallet.witness_receive(
         asset_id,
         assignment,
+        receive_policy,
         expiration_timestamp,
         transport_endpoints,
         min_confirmations,
@@ -3,35 +3,44 @@
 use std::{
     collections::HashMap,
     str::FromStr,
-    sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard},
+    sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard},
 };
 
 use rgb_lib::{
-    AssetSchema, Assignment as RgbLibAssignment, CloseMethod, Error as RgbLibError, TransferStatus,
-    TransportType, WalletTransactionType,
+    AssetSchema, Assignment as RgbLibAssignment, CloseMethod, Error as RgbLibError, ReceivePolicy,
+    TransferStatus, TransportType, WalletTransactionType,
     keys::{Keys, WitnessVersion},
     utils::BitcoinNetwork,
     wallet::{
         Address as RgbLibAddress, AssetCFA, AssetIFA, AssetNIA, AssetUDA, Assets,
-        AssignmentsCollection, Balance, BlockTime, BtcBalance, BurnBeginResult, BurnDetails,
-        Cosigner as CosignerData, DatabaseType, EmbeddedMedia, HubInfo, InflateBeginResult,
+        AssignmentsCollection, AutoRefreshOptions, Balance, BlockTime, BtcBalance,
+        BumpFeeBeginResult, BumpFeeDetails, BumpFeeResult, BurnBeginResult, BurnDetails,
+        CbfOptions, CoinSelectionOptions, CoinSelectionStrategy, ConsolidateAllocationsBeginResult,
+        ConsolidateAllocationsDetails, Cosigner as CosignerData,
+        DatabaseType as RgbLibDatabaseType, EmbeddedMedia, HubInfo, InflateBeginResult,
         InflateDetails, InitOperationResult, Invoice as RgbLibInvoice,
         InvoiceData as RgbLibInvoiceData, Media, Metadata, MultisigKeys, MultisigOnlineOptions,
         MultisigVotingStatus as RgbLibMultisigVotingStatus, MultisigWallet as RgbLibMultisigWallet,
-        Online, OnlineOptions, Operation as RgbLibOperation, OperationInfo as RgbLibOperationInfo,
-        OperationResult, Outpoint, PendingVanillaTx, ProofOfReserves, PsbtInputInfo,
-        PsbtInspection, PsbtOutputInfo, ReceiveData, Recipient as RgbLibRecipient,
-        RecipientInfo as RgbLibRecipientInfo, RecipientType, RefreshFilter, RefreshTransferStatus,
-        RefreshedTransfer, RespondToOperation as RgbLibRespondToOperation,
-        RgbAllocation as RgbLibRgbAllocation, RgbInputInfo as RgbLibRgbInputInfo,
-        RgbInspection as RgbLibRgbInspection, RgbOperationInfo as RgbLibRgbOperationInfo,
-        RgbOutputInfo as RgbLibRgbOutputInfo, RgbTransitionInfo as RgbLibRgbTransitionInfo,
-        RgbWalletOpsOffline, RgbWalletOpsOnline, SendBeginResult, SendDetails, SinglesigKeys,
+        NetworkOptions, NetworkPolicy, NetworkService, Online, OnlineOptions,
+        Operation as RgbLibOperation, OperationExpiry as RgbLibOperationExpiry,
+        OperationInfo as RgbLibOperationInfo, OperationResult, Outpoint,
+        PaymentRequest as RgbLibPaymentRequest, PaymentRequestData as RgbLibPaymentRequestData,
+        PendingVanillaTx, ProofOfReserves, PsbtInputInfo, PsbtInspection, PsbtOutputInfo,
+        ReceiveData, Recipient as RgbLibRecipient, RecipientInfo as RgbLibRecipientInfo,
+        RecipientType, RecoveryPolicy, RefreshFilter, RefreshTransferStatus, RefreshedTransfer,
+        RespondToOperation as RgbLibRespondToOperation, RgbAllocation as RgbLibRgbAllocation,
+        RgbInputInfo as RgbLibRgbInputInfo, RgbInspection as RgbLibRgbInspection,
+        RgbOperationInfo as RgbLibRgbOperationInfo, RgbOutputInfo as RgbLibRgbOutputInfo,
+        RgbTransitionInfo as RgbLibRgbTransitionInfo, RgbWalletOpsOffline, RgbWalletOpsOnline,
+        SellOrder as RgbLibSellOrder, SellOrderData as RgbLibSellOrderData, SendBeginResult,
+        SendDetails, ServiceProxy, SinglesigKeys, SwapLeg as RgbLibSwapLeg,
+        SwapOffer as RgbLibSwapOffer, SwapOfferData as RgbLibSwapOfferData, SwapStage,
         SyncKeychain as RgbLibSyncKeychain, SyncOptions as RgbLibSyncOptions, SyncStrategy, Token,
         TokenLight, Transaction, TransactionType, Transfer as RgbLibTransfer, TransferKind,
         TransferTransportEndpoint, TransportEndpoint as RgbLibTransportEndpoint, TypeOfTransition,
-        Unspent as RgbLibUnspent, UserRole, Utxo, Wallet as RgbLibWallet, WalletData,
-        WalletDescriptors, WitnessData,
+        Unspent as RgbLibUnspent, UserRole, Utxo, VssBackupTarget, Wallet as RgbLibWallet,
+        WalletData as RgbLibWalletData, WalletDescriptors, WalletEvent as RgbLibWalletEvent,
+        WalletEventListener as RgbLibWalletEventListener, WitnessData,
     },
 };
 
@@ -58,6 +67,58 @@ impl From<SyncKeychain> for RgbLibSyncKeychain {
         }
     }
 }
+pub enum DatabaseType {
+    Sqlite,
+    Postgres { url: String, schema: String },
+}
+impl From<RgbLibDatabaseType> for DatabaseType {
+    fn from(orig: RgbLibDatabaseType) -> Self {
+        match orig {
+            RgbLibDatabaseType::Sqlite => DatabaseType::Sqlite,
+            RgbLibDatabaseType::Postgres { url, schema } => DatabaseType::Postgres { url, schema },
+        }
+    }
+}
+impl From<DatabaseType> for RgbLibDatabaseType {
+    fn from(orig: DatabaseType) -> Self {
+        match orig {
+            DatabaseType::Sqlite => RgbLibDatabaseType::Sqlite,
+            DatabaseType::Postgres { url, schema } => RgbLibDatabaseType::Postgres { url, schema },
+        }
+    }
+}
+pub struct WalletData {
+    pub data_dir: String,
+    pub bitcoin_network: BitcoinNetwork,
+    pub database_type: DatabaseType,
+    pub max_allocations_per_utxo: u32,
+    pub supported_schemas: Vec<AssetSchema>,
+    pub encryption_password: Option<String>,
+}
+impl From<RgbLibWalletData> for WalletData {
+    fn from(orig: RgbLibWalletData) -> Self {
+        Self {
+            data_dir: orig.data_dir,
+            bitcoin_network: orig.bitcoin_network,
+            database_type: orig.database_type.into(),
+            max_allocations_per_utxo: orig.max_allocations_per_utxo,
+            supported_schemas: orig.supported_schemas,
+            encryption_password: orig.encryption_password,
+        }
+    }
+}
+impl From<WalletData> for RgbLibWalletData {
+    fn from(orig: WalletData) -> Self {
+        Self {
+            data_dir: orig.data_dir,
+            bitcoin_network: orig.bitcoin_network,
+            database_type: orig.database_type.into(),
+            max_allocations_per_utxo: orig.max_allocations_per_utxo,
+            supported_schemas: orig.supported_schemas,
+            encryption_password: orig.encryption_password,
+        }
+    }
+}
 pub struct SyncOptions {
     pub keychain: SyncKeychain,
     pub strategy: SyncStrategy,
@@ -71,6 +132,78 @@ impl From<SyncOptions> for RgbLibSyncOptions {
     }
 }
 
+pub enum WalletEvent {
+    TransferStatusChanged {
+        batch_transfer_idx: i32,
+        status: TransferStatus,
+    },
+    AssetAdded {
+        asset_id: String,
+    },
+    UtxoCreated {
+        outpoint: Outpoint,
+    },
+    UtxoSpent {
+        outpoint: Outpoint,
+    },
+    TransferRefreshFailed {
+        batch_transfer_idx: i32,
+        details: String,
+    },
+    AutoRefreshCompleted {
+        synced: bool,
+        transfers_changed: bool,
+    },
+    AutoRefreshFailed {
+        details: String,
+    },
+}
+impl From<RgbLibWalletEvent> for WalletEvent {
+    fn from(orig: RgbLibWalletEvent) -> Self {
+        match orig {
+            RgbLibWalletEvent::TransferStatusChanged {
+                batch_transfer_idx,
+                status,
+            } => WalletEvent::TransferStatusChanged {
+                batch_transfer_idx,
+                status,
+            },
+            RgbLibWalletEvent::AssetAdded { asset_id } => WalletEvent::AssetAdded { asset_id },
+            RgbLibWalletEvent::UtxoCreated { outpoint } => WalletEvent::UtxoCreated { outpoint },
+            RgbLibWalletEvent::UtxoSpent { outpoint } => WalletEvent::UtxoSpent { outpoint },
+            RgbLibWalletEvent::TransferRefreshFailed {
+                batch_transfer_idx,
+                details,
+            } => WalletEvent::TransferRefreshFailed {
+                batch_transfer_idx,
+                details,
+            },
+            RgbLibWalletEvent::AutoRefreshCompleted {
+                synced,
+                transfers_changed,
+            } => WalletEvent::AutoRefreshCompleted {
+                synced,
+                transfers_changed,
+            },
+            RgbLibWalletEvent::AutoRefreshFailed { details } => {
+                WalletEvent::AutoRefreshFailed { details }
+            }
+        }
+    }
+}
+
+pub trait WalletEventListener: Send + Sync {
+    fn on_event(&self, event: WalletEvent);
+}
+
+// forwards the rgb-lib events to the foreign listener
+struct WalletEventListenerAdapter(Box<dyn WalletEventListener>);
+impl RgbLibWalletEventListener for WalletEventListenerAdapter {
+    fn on_event(&self, event: RgbLibWalletEvent) {
+        self.0.on_event(event.into())
+    }
+}
+
 // temporary solution needed because the Enum attribute doesn't support the Remote one
 pub enum Assignment {
     Fungible { amount: u64 },
@@ -139,6 +272,106 @@ impl From<InvoiceData> for RgbLibInvoiceData {
         }
     }
 }
+pub struct PaymentRequestData {
+    pub payment_request_id: String,
+    pub lines: Vec<InvoiceData>,
+    pub network: BitcoinNetwork,
+    pub expiration_timestamp: Option<u64>,
+    pub transport_endpoints: Vec<String>,
+}
+impl From<RgbLibPaymentRequestData> for PaymentRequestData {
+    fn from(orig: RgbLibPaymentRequestData) -> Self {
+        Self {
+            payment_request_id: orig.payment_request_id,
+            lines: orig.lines.into_iter().map(|l| l.into()).collect(),
+            network: orig.network,
+            expiration_timestamp: orig.expiration_timestamp,
+            transport_endpoints: orig.transport_endpoints,
+        }
+    }
+}
+// temporary solution needed because the Enum attribute doesn't support the Remote one
+pub enum SwapLeg {
+    Asset {
+        asset_id: String,
+        assignment: Assignment,
+    },
+    Bitcoin {
+        amount: u64,
+    },
+}
+impl From<RgbLibSwapLeg> for SwapLeg {
+    fn from(orig: RgbLibSwapLeg) -> Self {
+        match orig {
+            RgbLibSwapLeg::Asset {
+                asset_id,
+                assignment,
+            } => SwapLeg::Asset {
+                asset_id,
+                assignment: assignment.into(),
+            },
+            RgbLibSwapLeg::Bitcoin { amount } => SwapLeg::Bitcoin { amount },
+        }
+    }
+}
+impl From<SwapLeg> for RgbLibSwapLeg {
+    fn from(orig: SwapLeg) -> Self {
+        match orig {
+            SwapLeg::Asset {
+                asset_id,
+                assignment,
+            } => RgbLibSwapLeg::Asset {
+                asset_id,
+                assignment: assignment.into(),
+            },
+            SwapLeg::Bitcoin { amount } => RgbLibSwapLeg::Bitcoin { amount },
+        }
+    }
+}
+pub struct SwapOfferData {
+    pub swap_id: String,
+    pub network: BitcoinNetwork,
+    pub maker_asset_id: String,
+    pub maker_assignment: Assignment,
+    pub taker_leg: SwapLeg,
+    pub expiration_timestamp: u64,
+    pub stage: SwapStage,
+    pub txid: Option<String>,
+}
+impl From<RgbLibSwapOfferData> for SwapOfferData {
+    fn from(orig: RgbLibSwapOfferData) -> Self {
+        Self {
+            swap_id: orig.swap_id,
+            network: orig.network,
+            maker_asset_id: orig.maker_asset_id,
+            maker_assignment: orig.maker_assignment.into(),
+            taker_leg: orig.taker_leg.into(),
+            expiration_timestamp: orig.expiration_timestamp,
+            stage: orig.stage,
+            txid: orig.txid,
+        }
+    }
+}
+pub struct SellOrderData {
+    pub sell_order_id: String,
+    pub network: BitcoinNetwork,
+    pub asset_id: String,
+    pub assignment: Assignment,
+    pub price: u64,
+    pub expiration_timestamp: u64,
+}
+impl From<RgbLibSellOrderData> for SellOrderData {
+    fn from(orig: RgbLibSellOrderData) -> Self {
+        Self {
+            sell_order_id: orig.sell_order_id,
+            network: orig.network,
+            asset_id: orig.asset_id,
+            assignment: orig.assignment.into(),
+            price: orig.price,
+            expiration_timestamp: orig.expiration_timestamp,
+        }
+    }
+}
 pub struct Recipient {
     pub recipient_id: String,
     pub witness_data: Option<WitnessData>,
@@ -802,6 +1035,20 @@ impl From<RespondToOperation> for RgbLibRespondToOperation {
     }
 }
 
+// temporary solution needed because the Enum attribute doesn't support the Remote one
+pub enum OperationExpiry {
+    BlockHeight { height: u32 },
+    Timestamp { timestamp: u64 },
+}
+impl From<OperationExpiry> for RgbLibOperationExpiry {
+    fn from(orig: OperationExpiry) -> Self {
+        match orig {
+            OperationExpiry::BlockHeight { height } => RgbLibOperationExpiry::BlockHeight(height),
+            OperationExpiry::Timestamp { timestamp } => RgbLibOperationExpiry::Timestamp(timestamp),
+        }
+    }
+}
+
 fn generate_keys(bitcoin_network: BitcoinNetwork, witness_version: WitnessVersion) -> Keys {
     rgb_lib::keys::generate_keys(bitcoin_network, witness_version)
 }
@@ -822,6 +1069,26 @@ fn restore_backup(
     rgb_lib::wallet::restore_backup(&backup_path, &password, &data_dir)
 }
 
+fn restore_backup_chain(
+    backup_paths: Vec<String>,
+    password: String,
+    data_dir: String,
+) -> Result<(), RgbLibError> {
+    let backup_paths: Vec<&str> = backup_paths.iter().map(String::as_str).collect();
+    rgb_lib::wallet::restore_backup_chain(&backup_paths, &password, &data_dir)
+}
+
+fn restore_backup_vss(
+    url: String,
+    token: Option<String>,
+    fingerprint: String,
+    password: String,
+    data_dir: String,
+) -> Result<(), RgbLibError> {
+    let backup_target = VssBackupTarget::new(&url, token)?;
+    rgb_lib::wallet::restore_backup_remote(&backup_target, &fingerprint, &password, &data_dir)
+}
+
 struct RecipientInfo {
     recipient_info: RwLock<RgbLibRecipientInfo>,
 }
@@ -932,6 +1199,78 @@ impl Invoice {
     }
 }
 
+struct PaymentRequest {
+    payment_request: RwLock<RgbLibPaymentRequest>,
+}
+
+impl PaymentRequest {
+    fn new(payment_request_string: String) -> Result<Self, RgbLibError> {
+        Ok(PaymentRequest {
+            payment_request: RwLock::new(RgbLibPaymentRequest::new(payment_request_string)?),
+        })
+    }
+
+    fn _get_payment_request(&self) -> RwLockReadGuard<'_, RgbLibPaymentRequest> {
+        self.payment_request.read().expect("payment request")
+    }
+
+    fn payment_request_data(&self) -> PaymentRequestData {
+        self._get_payment_request().payment_request_data().into()
+    }
+
+    fn payment_request_string(&self) -> String {
+        self._get_payment_request().payment_request_string()
+    }
+}
+
+struct SwapOffer {
+    swap_offer: RwLock<RgbLibSwapOffer>,
+}
+
+impl SwapOffer {
+    fn new(swap_offer_string: String) -> Result<Self, RgbLibError> {
+        Ok(SwapOffer {
+            swap_offer: RwLock::new(RgbLibSwapOffer::new(swap_offer_string)?),
+        })
+    }
+
+    fn _get_swap_offer(&self) -> RwLockReadGuard<'_, RgbLibSwapOffer> {
+        self.swap_offer.read().expect("swap offer")
+    }
+
+    fn swap_offer_data(&self) -> SwapOfferData {
+        self._get_swap_offer().swap_offer_data().into()
+    }
+
+    fn swap_offer_string(&self) -> String {
+        self._get_swap_offer().swap_offer_string()
+    }
+}
+
+struct SellOrder {
+    sell_order: RwLock<RgbLibSellOrder>,
+}
+
+impl SellOrder {
+    fn new(sell_order_string: String) -> Result<Self, RgbLibError> {
+        Ok(SellOrder {
+            sell_order: RwLock::new(RgbLibSellOrder::new(sell_order_string)?),
+        })
+    }
+
+    fn _get_sell_order(&self) -> RwLockReadGuard<'_, RgbLibSellOrder> {
+        self.sell_order.read().expect("sell order")
+    }
+
+    fn sell_order_data(&self) -> SellOrderData {
+        self._get_sell_order().sell_order_data().into()
+    }
+
+    fn sell_order_string(&self) -> String {
+        self._get_sell_order().sell_order_string()
+    }
+}
+
 struct Wallet {
     wallet_mutex: Mutex<RgbLibWallet>,
 }
@@ -939,7 +1278,7 @@ struct Wallet {
 impl Wallet {
     fn new(wallet_data: WalletData, keys: SinglesigKeys) -> Result<Self, RgbLibError> {
         Ok(Wallet {
-            wallet_mutex: Mutex::new(RgbLibWallet::new(wallet_data, keys)?),
+            wallet_mutex: Mutex::new(RgbLibWallet::new(wallet_data.into(), keys)?),
         })
     }
 
@@ -947,12 +1286,14 @@ impl Wallet {
         data_dir: String,
         master_fingerprint: String,
         mnemonic: Option<String>,
+        password: Option<String>,
     ) -> Result<Self, RgbLibError> {
         Ok(Wallet {
             wallet_mutex: Mutex::new(RgbLibWallet::load(
                 &data_dir,
                 &master_fingerprint,
                 mnemonic,
+                password,
             )?),
         })
     }
@@ -961,8 +1302,16 @@ impl Wallet {
         self.wallet_mutex.lock().expect("wallet")
     }
 
+    fn set_event_listener(&self, listener: Option<Box<dyn WalletEventListener>>) {
+        self._get_wallet().set_event_listener(
+            listener.map(|l| {
+                Arc::new(WalletEventListenerAdapter(l)) as Arc<dyn RgbLibWalletEventListener>
+            }),
+        )
+    }
+
     fn get_wallet_data(&self) -> WalletData {
-        self._get_wallet().get_wallet_data()
+        self._get_wallet().get_wallet_data().into()
     }
 
     fn get_keys(&self) -> SinglesigKeys {
@@ -991,6 +1340,30 @@ impl Wallet {
         self._get_wallet().backup(&backup_path, &password)
     }
 
+    fn backup_incremental(&self, backup_path: String, password: String) -> Result<(), RgbLibError> {
+        self._get_wallet()
+            .backup_incremental(&backup_path, &password)
+    }
+
+    fn backup_remote(&self) -> Result<(), RgbLibError> {
+        self._get_wallet().backup_remote()
+    }
+
+    fn enable_vss_backup(
+        &self,
+        url: String,
+        token: Option<String>,
+        password: String,
+    ) -> Result<(), RgbLibError> {
+        let backup_target = VssBackupTarget::new(&url, token)?;
+        self._get_wallet()
+            .enable_remote_backup(Arc::new(backup_target), &password)
+    }
+
+    fn disable_remote_backup(&self) {
+        self._get_wallet().disable_remote_backup()
+    }
+
     fn backup_info(&self) -> Result<bool, RgbLibError> {
         self._get_wallet().backup_info()
     }
@@ -999,6 +1372,7 @@ impl Wallet {
         &self,
         asset_id: Option<String>,
         assignment: Assignment,
+        receive_policy: ReceivePolicy,
         expiration_timestamp: u64,
         transport_endpoints: Vec<String>,
         min_confirmations: u8,
@@ -1006,6 +1380,7 @@ impl Wallet {
         self._get_wallet().blind_receive(
             asset_id,
             assignment.into(),
+            receive_policy,
             expiration_timestamp,
             transport_endpoints,
             min_confirmations,
@@ -1016,6 +1391,7 @@ impl Wallet {
         &self,
         asset_id: Option<String>,
         assignment: Assignment,
+        receive_policy: ReceivePolicy,
         expiration_timestamp: u64,
         transport_endpoints: Vec<String>,
         min_confirmations: u8,
@@ -1023,12 +1399,23 @@ impl Wallet {
         self._get_wallet().witness_receive(
             asset_id,
             assignment.into(),
+            receive_policy,
             expiration_timestamp,
             transport_endpoints,
             min_confirmations,
         )
     }
 
+    fn create_payment_request(
+        &self,
+        receive_data: Vec<ReceiveData>,
+    ) -> Result<Arc<PaymentRequest>, RgbLibError> {
+        let payment_request = self._get_wallet().create_payment_request(receive_data)?;
+        Ok(Arc::new(PaymentRequest {
+            payment_request: RwLock::new(payment_request),
+        }))
+    }
+
     fn finalize_psbt(&self, signed_psbt: String) -> Result<String, RgbLibError> {
         self._get_wallet().finalize_psbt(signed_psbt, None)
     }
@@ -1037,6 +1424,10 @@ impl Wallet {
         self._get_wallet().sign_psbt(unsigned_psbt, None)
     }
 
+    fn musig_generate_nonces(&self, unsigned_psbt: String) -> Result<String, RgbLibError> {
+        self._get_wallet().musig_generate_nonces(unsigned_psbt)
+    }
+
     fn create_utxos(
         &self,
         online: Online,
@@ -1140,6 +1531,38 @@ impl Wallet {
         self._get_wallet().go_online(online_options)
     }
 
+    fn stop_auto_refresh(&self) {
+        self._get_wallet().stop_auto_refresh()
+    }
+
+    fn bump_fee(
+        &self,
+        online: Online,
+        batch_transfer_idx: i32,
+        fee_rate: u64,
+    ) -> Result<BumpFeeResult, RgbLibError> {
+        self._get_wallet()
+            .bump_fee(online, batch_transfer_idx, fee_rate)
+    }
+
+    fn bump_fee_begin(
+        &self,
+        online: Online,
+        batch_transfer_idx: i32,
+        fee_rate: u64,
+    ) -> Result<BumpFeeBeginResult, RgbLibError> {
+        self._get_wallet()
+            .bump_fee_begin(online, batch_transfer_idx, fee_rate)
+    }
+
+    fn bump_fee_end(
+        &self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<BumpFeeResult, RgbLibError> {
+        self._get_wallet().bump_fee_end(online, signed_psbt)
+    }
+
     fn burn(
         &self,
         online: Online,
@@ -1159,6 +1582,7 @@ impl Wallet {
         amount: u64,
         fee_rate: u64,
         min_confirmations: u8,
+        coin_selection: Option<CoinSelectionOptions>,
         dry_run: bool,
     ) -> Result<BurnBeginResult, RgbLibError> {
         self._get_wallet().burn_begin(
@@ -1167,6 +1591,7 @@ impl Wallet {
             amount,
             fee_rate,
             min_confirmations,
+            coin_selection,
             dry_run,
         )
     }
@@ -1179,6 +1604,43 @@ impl Wallet {
         self._get_wallet().burn_end(online, signed_psbt)
     }
 
+    fn consolidate_allocations(
+        &self,
+        online: Online,
+        asset_id: Option<String>,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<OperationResult, RgbLibError> {
+        self._get_wallet()
+            .consolidate_allocations(online, asset_id, fee_rate, min_confirmations)
+    }
+
+    fn consolidate_allocations_begin(
+        &self,
+        online: Online,
+        asset_id: Option<String>,
+        fee_rate: u64,
+        min_confirmations: u8,
+        dry_run: bool,
+    ) -> Result<ConsolidateAllocationsBeginResult, RgbLibError> {
+        self._get_wallet().consolidate_allocations_begin(
+            online,
+            asset_id,
+            fee_rate,
+            min_confirmations,
+            dry_run,
+        )
+    }
+
+    fn consolidate_allocations_end(
+        &self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<OperationResult, RgbLibError> {
+        self._get_wallet()
+            .consolidate_allocations_end(online, signed_psbt)
+    }
+
     fn inflate(
         &self,
         online: Online,
@@ -1203,6 +1665,7 @@ impl Wallet {
         inflation_amounts: Vec<u64>,
         fee_rate: u64,
         min_confirmations: u8,
+        coin_selection: Option<CoinSelectionOptions>,
         dry_run: bool,
     ) -> Result<InflateBeginResult, RgbLibError> {
         self._get_wallet().inflate_begin(
@@ -1211,6 +1674,7 @@ impl Wallet {
             inflation_amounts,
             fee_rate,
             min_confirmations,
+            coin_selection,
             dry_run,
         )
     }
@@ -1319,6 +1783,45 @@ impl Wallet {
             .collect())
     }
 
+    fn migrate_to(
+        &self,
+        online: Online,
+        invoices: Vec<String>,
+        address: String,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<OperationResult, RgbLibError> {
+        self._get_wallet()
+            .migrate_to(online, invoices, address, fee_rate, min_confirmations)
+    }
+
+    fn migrate_to_begin(
+        &self,
+        online: Online,
+        invoices: Vec<String>,
+        address: String,
+        fee_rate: u64,
+        min_confirmations: u8,
+        dry_run: bool,
+    ) -> Result<SendBeginResult, RgbLibError> {
+        self._get_wallet().migrate_to_begin(
+            online,
+            invoices,
+            address,
+            fee_rate,
+            min_confirmations,
+            dry_run,
+        )
+    }
+
+    fn migrate_to_end(
+        &self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<OperationResult, RgbLibError> {
+        self._get_wallet().migrate_to_end(online, signed_psbt)
+    }
+
     fn refresh(
         &self,
         online: Online,
@@ -1357,6 +1860,7 @@ impl Wallet {
         fee_rate: u64,
         min_confirmations: u8,
         expiration_timestamp: u64,
+        coin_selection: Option<CoinSelectionOptions>,
         dry_run: bool,
     ) -> Result<SendBeginResult, RgbLibError> {
         self._get_wallet().send_begin(
@@ -1366,6 +1870,7 @@ impl Wallet {
             fee_rate,
             min_confirmations,
             expiration_timestamp,
+            coin_selection,
             dry_run,
         )
     }
@@ -1378,6 +1883,44 @@ impl Wallet {
         self._get_wallet().send_end(online, signed_psbt)
     }
 
+    fn send_payment_request(
+        &self,
+        online: Online,
+        payment_request: String,
+        donation: bool,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<OperationResult, RgbLibError> {
+        self._get_wallet().send_payment_request(
+            online,
+            payment_request,
+            donation,
+            fee_rate,
+            min_confirmations,
+        )
+    }
+
+    fn send_payment_request_begin(
+        &self,
+        online: Online,
+        payment_request: String,
+        donation: bool,
+        fee_rate: u64,
+        min_confirmations: u8,
+        coin_selection: Option<CoinSelectionOptions>,
+        dry_run: bool,
+    ) -> Result<SendBeginResult, RgbLibError> {
+        self._get_wallet().send_payment_request_begin(
+            online,
+            payment_request,
+            donation,
+            fee_rate,
+            min_confirmations,
+            coin_selection,
+            dry_run,
+        )
+    }
+
     fn provide_out_of_band_consignment(
         &self,
         online: Online,
@@ -1400,6 +1943,89 @@ impl Wallet {
             .provide_out_of_band_ack(online, recipient_id)
     }
 
+    fn swap_offer_create(
+        &self,
+        online: Online,
+        asset_id: String,
+        assignment: Assignment,
+        taker_leg: SwapLeg,
+        expiration_timestamp: u64,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<Arc<SwapOffer>, RgbLibError> {
+        let swap_offer = self._get_wallet().swap_offer_create(
+            online,
+            asset_id,
+            assignment.into(),
+            taker_leg.into(),
+            expiration_timestamp,
+            fee_rate,
+            min_confirmations,
+        )?;
+        Ok(Arc::new(SwapOffer {
+            swap_offer: RwLock::new(swap_offer),
+        }))
+    }
+
+    fn swap_offer_take(
+        &self,
+        online: Online,
+        swap_offer: String,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<Arc<SwapOffer>, RgbLibError> {
+        let swap_offer =
+            self._get_wallet()
+                .swap_offer_take(online, swap_offer, fee_rate, min_confirmations)?;
+        Ok(Arc::new(SwapOffer {
+            swap_offer: RwLock::new(swap_offer),
+        }))
+    }
+
+    fn swap_offer_complete(
+        &self,
+        online: Online,
+        swap_offer: String,
+    ) -> Result<Arc<SwapOffer>, RgbLibError> {
+        let swap_offer = self._get_wallet().swap_offer_complete(online, swap_offer)?;
+        Ok(Arc::new(SwapOffer {
+            swap_offer: RwLock::new(swap_offer),
+        }))
+    }
+
+    fn sell_order_create(
+        &self,
+        online: Online,
+        asset_id: String,
+        assignment: Assignment,
+        price: u64,
+        expiration_timestamp: u64,
+        min_confirmations: u8,
+    ) -> Result<Arc<SellOrder>, RgbLibError> {
+        let sell_order = self._get_wallet().sell_order_create(
+            online,
+            asset_id,
+            assignment.into(),
+            price,
+            expiration_timestamp,
+            min_confirmations,
+        )?;
+        Ok(Arc::new(SellOrder {
+            sell_order: RwLock::new(sell_order),
+        }))
+    }
+
+    fn sell_order_take(
+        &self,
+        online: Online,
+        sell_order: String,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<OperationResult, RgbLibError> {
+        self._get_wallet()
+            .sell_order_take(online, sell_order, fee_rate, min_confirmations)
+    }
+
     fn send_btc(
         &self,
         online: Online,
@@ -1429,6 +2055,46 @@ impl Wallet {
         self._get_wallet().send_btc_end(online, signed_psbt)
     }
 
+    fn cpfp(&self, online: Online, txid: String, fee_rate: u64) -> Result<String, RgbLibError> {
+        self._get_wallet().cpfp(online, txid, fee_rate)
+    }
+
+    fn cpfp_begin(
+        &self,
+        online: Online,
+        txid: String,
+        fee_rate: u64,
+        dry_run: bool,
+    ) -> Result<String, RgbLibError> {
+        self._get_wallet()
+            .cpfp_begin(online, txid, fee_rate, dry_run)
+    }
+
+    fn cpfp_transfer(
+        &self,
+        online: Online,
+        batch_transfer_idx: i32,
+        fee_rate: u64,
+    ) -> Result<String, RgbLibError> {
+        self._get_wallet()
+            .cpfp_transfer(online, batch_transfer_idx, fee_rate)
+    }
+
+    fn cpfp_transfer_begin(
+        &self,
+        online: Online,
+        batch_transfer_idx: i32,
+        fee_rate: u64,
+        dry_run: bool,
+    ) -> Result<String, RgbLibError> {
+        self._get_wallet()
+            .cpfp_transfer_begin(online, batch_transfer_idx, fee_rate, dry_run)
+    }
+
+    fn cpfp_end(&self, online: Online, signed_psbt: String) -> Result<String, RgbLibError> {
+        self._get_wallet().cpfp_end(online, signed_psbt)
+    }
+
     fn sync(&self, online: Online, options: SyncOptions) -> Result<(), RgbLibError> {
         self._get_wallet().sync(online, options.into())
     }
@@ -1479,7 +2145,7 @@ struct MultisigWallet {
 impl MultisigWallet {
     fn new(wallet_data: WalletData, keys: MultisigKeys) -> Result<Self, RgbLibError> {
         Ok(MultisigWallet {
-            wallet_mutex: Mutex::new(RgbLibMultisigWallet::new(wallet_data, keys)?),
+            wallet_mutex: Mutex::new(RgbLibMultisigWallet::new(wallet_data.into(), keys)?),
         })
     }
 
@@ -1488,7 +2154,7 @@ impl MultisigWallet {
     }
 
     fn get_wallet_data(&self) -> WalletData {
-        self._get_wallet().get_wallet_data()
+        self._get_wallet().get_wallet_data().into()
     }
 
     fn get_keys(&self) -> MultisigKeys {
@@ -1521,6 +2187,30 @@ impl MultisigWallet {
         self._get_wallet().backup(&backup_path, &password)
     }
 
+    fn backup_incremental(&self, backup_path: String, password: String) -> Result<(), RgbLibError> {
+        self._get_wallet()
+            .backup_incremental(&backup_path, &password)
+    }
+
+    fn backup_remote(&self) -> Result<(), RgbLibError> {
+        self._get_wallet().backup_remote()
+    }
+
+    fn enable_vss_backup(
+        &self,
+        url: String,
+        token: Option<String>,
+        password: String,
+    ) -> Result<(), RgbLibError> {
+        let backup_target = VssBackupTarget::new(&url, token)?;
+        self._get_wallet()
+            .enable_remote_backup(Arc::new(backup_target), &password)
+    }
+
+    fn disable_remote_backup(&self) {
+        self._get_wallet().disable_remote_backup()
+    }
+
     fn backup_info(&self) -> Result<bool, RgbLibError> {
         self._get_wallet().backup_info()
     }
@@ -1575,9 +2265,17 @@ impl MultisigWallet {
         size: Option<u32>,
         fee_rate: u64,
         skip_sync: bool,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, RgbLibError> {
-        self._get_wallet()
-            .create_utxos_init(online, up_to, num, size, fee_rate, skip_sync)
+        self._get_wallet().create_utxos_init(
+            online,
+            up_to,
+            num,
+            size,
+            fee_rate,
+            skip_sync,
+            expiry.map(|e| e.into()),
+        )
     }
 
     fn delete_transfers(
@@ -1629,6 +2327,25 @@ impl MultisigWallet {
         wallet.go_online(online_options, multisig_online_options)
     }
 
+    fn go_online_without_hub(
+        &self,
+        online_options: OnlineOptions,
+        cosigner_mnemonic: Option<String>,
+    ) -> Result<Online, RgbLibError> {
+        let mut wallet = self.wallet_mutex.lock().expect("wallet");
+        wallet.go_online_without_hub(online_options, cosigner_mnemonic)
+    }
+
+    fn stop_auto_refresh(&self) {
+        self._get_wallet().stop_auto_refresh()
+    }
+
+    fn use_recovery_policy(&self, recovery_policy_idx: Option<u8>) -> Result<(), RgbLibError> {
+        Ok(self
+            ._get_wallet()
+            .use_recovery_policy(recovery_policy_idx)?)
+    }
+
     fn hub_info(&self, online: Online) -> Result<HubInfo, RgbLibError> {
         self._get_wallet().hub_info(online)
     }
@@ -1640,9 +2357,16 @@ impl MultisigWallet {
         amount: u64,
         fee_rate: u64,
         min_confirmations: u8,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, RgbLibError> {
-        self._get_wallet()
-            .burn_init(online, asset_id, amount, fee_rate, min_confirmations)
+        self._get_wallet().burn_init(
+            online,
+            asset_id,
+            amount,
+            fee_rate,
+            min_confirmations,
+            expiry.map(|e| e.into()),
+        )
     }
 
     fn inflate_init(
@@ -1652,6 +2376,7 @@ impl MultisigWallet {
         inflation_amounts: Vec<u64>,
         fee_rate: u64,
         min_confirmations: u8,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, RgbLibError> {
         self._get_wallet().inflate_init(
             online,
@@ -1659,6 +2384,7 @@ impl MultisigWallet {
             inflation_amounts,
             fee_rate,
             min_confirmations,
+            expiry.map(|e| e.into()),
         )
     }
 
@@ -1783,6 +2509,7 @@ impl MultisigWallet {
         fee_rate: u64,
         min_confirmations: u8,
         expiration_timestamp: u64,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, RgbLibError> {
         self._get_wallet().send_init(
             online,
@@ -1791,6 +2518,7 @@ impl MultisigWallet {
             fee_rate,
             min_confirmations,
             expiration_timestamp,
+            expiry.map(|e| e.into()),
         )
     }
 
@@ -1801,9 +2529,16 @@ impl MultisigWallet {
         amount: u64,
         fee_rate: u64,
         skip_sync: bool,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, RgbLibError> {
-        self._get_wallet()
-            .send_btc_init(online, address, amount, fee_rate, skip_sync)
+        self._get_wallet().send_btc_init(
+            online,
+            address,
+            amount,
+            fee_rate,
+            skip_sync,
+            expiry.map(|e| e.into()),
+        )
     }
 
     fn sync(&self, online: Online, options: SyncOptions) -> Result<(), RgbLibError> {
@@ -1833,6 +2568,44 @@ impl MultisigWallet {
             .into())
     }
 
+    fn cancel_operation(
+        &self,
+        online: Online,
+        operation_idx: i32,
+    ) -> Result<OperationInfo, RgbLibError> {
+        let mut wallet = self.wallet_mutex.lock().expect("wallet");
+        Ok(wallet.cancel_operation(online, operation_idx)?.into())
+    }
+
+    fn post_musig_nonces(
+        &self,
+        online: Online,
+        operation_idx: i32,
+        psbt: String,
+    ) -> Result<(), RgbLibError> {
+        self._get_wallet()
+            .post_musig_nonces(online, operation_idx, psbt)
+    }
+
+    fn export_operation(
+        &self,
+        online: Online,
+        operation_idx: i32,
+        package_path: String,
+    ) -> Result<(), RgbLibError> {
+        self._get_wallet()
+            .export_operation(online, operation_idx, package_path)
+    }
+
+    fn import_operation(
+        &self,
+        online: Online,
+        package_path: String,
+    ) -> Result<OperationInfo, RgbLibError> {
+        let mut wallet = self.wallet_mutex.lock().expect("wallet");
+        Ok(wallet.import_operation(online, package_path)?.into())
+    }
+
     fn inspect_psbt(&self, psbt: String) -> Result<PsbtInspection, RgbLibError> {
         self._get_wallet().inspect_psbt(psbt)
     }
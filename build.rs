@@ -0,0 +1,8 @@
+use cfg_aliases::cfg_aliases;
+
+fn main() {
+    cfg_aliases! {
+        // any indexer feature, needed to go online
+        online: { any(feature = "electrum", feature = "esplora", feature = "bitcoind", feature = "cbf") },
+    }
+}
@@ -6,6 +6,12 @@ mod m20251105_132121_asset_update;
 mod m20251215_124959_backup_info_update;
 mod m20260414_134758_add_reserved_txo;
 mod m20260625_121819_incoming_rework;
+mod m20261018_093512_batch_transfer_payment_request;
+mod m20261018_141207_transfer_receive_policy;
+mod m20261018_151204_batch_transfer_swap;
+mod m20261018_172341_batch_transfer_sell_order;
+mod m20261018_193417_multisig_operation;
+mod m20261018_201512_backup_info_remote;
 
 pub struct Migrator;
 
@@ -19,6 +25,12 @@ impl MigratorTrait for Migrator {
             Box::new(m20251215_124959_backup_info_update::Migration),
             Box::new(m20260414_134758_add_reserved_txo::Migration),
             Box::new(m20260625_121819_incoming_rework::Migration),
+            Box::new(m20261018_093512_batch_transfer_payment_request::Migration),
+            Box::new(m20261018_141207_transfer_receive_policy::Migration),
+            Box::new(m20261018_151204_batch_transfer_swap::Migration),
+            Box::new(m20261018_172341_batch_transfer_sell_order::Migration),
+            Box::new(m20261018_193417_multisig_operation::Migration),
+            Box::new(m20261018_201512_backup_info_remote::Migration),
         ]
     }
 }
@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BatchTransfer::Table)
+                    .add_column(ColumnDef::new(BatchTransfer::SwapId).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WalletTransaction::Table)
+                    .add_column(
+                        ColumnDef::new(WalletTransaction::Expiration)
+                            .big_integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WalletTransaction::Table)
+                    .drop_column(WalletTransaction::Expiration)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BatchTransfer::Table)
+                    .drop_column(BatchTransfer::SwapId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum BatchTransfer {
+    Table,
+    SwapId,
+}
+
+#[derive(DeriveIden)]
+enum WalletTransaction {
+    Table,
+    Expiration,
+}
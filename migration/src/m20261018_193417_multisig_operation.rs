@@ -0,0 +1,112 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MultisigOperation::Table)
+                    .if_not_exists()
+                    .col(pk_auto(MultisigOperation::Idx))
+                    .col(integer_uniq(MultisigOperation::OperationIdx))
+                    .col(tiny_unsigned(MultisigOperation::State))
+                    .col(string_null(MultisigOperation::Txid))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ReservedTxo::Table)
+                    .add_column(ColumnDef::new(ReservedTxo::OperationIdx).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // TXOs can be reserved by multiple conflicting multisig operations, so uniqueness is only
+        // enforced for the other reservations
+        manager
+            .drop_index(
+                sea_query::Index::drop()
+                    .name("idx-reservedtxo-txid-vout")
+                    .table(ReservedTxo::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                sea_query::Index::create()
+                    .name("idx-reservedtxo-txid-vout-nooperation")
+                    .table(ReservedTxo::Table)
+                    .col(ReservedTxo::Txid)
+                    .col(ReservedTxo::Vout)
+                    .unique()
+                    .and_where(Expr::col(ReservedTxo::OperationIdx).is_null())
+                    .clone(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                sea_query::Index::drop()
+                    .name("idx-reservedtxo-txid-vout-nooperation")
+                    .table(ReservedTxo::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                sea_query::Index::create()
+                    .name("idx-reservedtxo-txid-vout")
+                    .table(ReservedTxo::Table)
+                    .col(ReservedTxo::Txid)
+                    .col(ReservedTxo::Vout)
+                    .unique()
+                    .clone(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ReservedTxo::Table)
+                    .drop_column(ReservedTxo::OperationIdx)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(MultisigOperation::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MultisigOperation {
+    Table,
+    Idx,
+    OperationIdx,
+    State,
+    Txid,
+}
+
+#[derive(DeriveIden)]
+enum ReservedTxo {
+    Table,
+    Txid,
+    Vout,
+    OperationIdx,
+}
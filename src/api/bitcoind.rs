@@ -0,0 +1,647 @@
+use super::*;
+
+/// Scheme of indexer URLs pointing to a bitcoind JSON-RPC server over HTTP.
+pub(crate) const BITCOIND_SCHEME: &str = "bitcoind";
+/// Scheme of indexer URLs pointing to a bitcoind JSON-RPC server over HTTPS.
+pub(crate) const BITCOIND_TLS_SCHEME: &str = "bitcoinds";
+/// Name of the watch-only bitcoind wallet used when the URL doesn't specify one.
+pub(crate) const BITCOIND_DEFAULT_WALLET: &str = "rgb-lib";
+
+// https://github.com/bitcoin/bitcoin/blob/master/src/rpc/protocol.h
+const RPC_INVALID_ADDRESS_OR_KEY: i64 = -5;
+const RPC_WALLET_NOT_FOUND: i64 = -18;
+const RPC_WALLET_ALREADY_LOADED: i64 = -35;
+
+// rescanning the chain after importing new scripts can take a long time
+const RESCAN_TIMEOUT: u64 = 3600;
+// number of blocks below the tip to include in chain updates
+const CHAIN_SUFFIX_LENGTH: u32 = 8;
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: serde_json::Value,
+    error: Option<proxy::JsonRpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RpcTxInfo {
+    hex: String,
+    #[serde(default)]
+    pub(crate) confirmations: i64,
+    blockhash: Option<BlockHash>,
+    blockheight: Option<u32>,
+    blocktime: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcBlockHeader {
+    height: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcFeeEstimate {
+    feerate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcWalletTx {
+    txid: Txid,
+    confirmations: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcListSinceBlock {
+    transactions: Vec<RpcWalletTx>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcDescriptor {
+    desc: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcListDescriptors {
+    descriptors: Vec<RpcDescriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcImportResult {
+    success: bool,
+    error: Option<proxy::JsonRpcError>,
+}
+
+struct WalletTx {
+    tx: Arc<BdkTransaction>,
+    info: RpcTxInfo,
+}
+
+/// Client for a bitcoind node, used as indexer via its JSON-RPC API.
+///
+/// Scripts to be monitored are imported as `raw()` descriptors in a watch-only descriptor wallet
+/// on the node, which is created if missing. Resolving RGB witnesses that don't belong to the
+/// wallet requires the node to run with `-txindex`.
+pub struct BitcoindClient {
+    client: RestClient,
+    base_url: String,
+    wallet_url: String,
+    wallet_name: String,
+    auth: Option<(String, String)>,
+    // timestamp the chain is rescanned from when importing scripts that may have an old history
+    birthday: u64,
+    // scripts already imported in the node wallet, None until the wallet has been loaded
+    imported_spks: Mutex<Option<HashSet<ScriptBuf>>>,
+}
+
+impl BitcoindClient {
+    /// Whether the provided indexer URL uses the `bitcoind://` or `bitcoinds://` scheme.
+    pub(crate) fn is_bitcoind_url(indexer_url: &str) -> bool {
+        Url::parse(indexer_url)
+            .is_ok_and(|u| [BITCOIND_SCHEME, BITCOIND_TLS_SCHEME].contains(&u.scheme()))
+    }
+
+    /// Build a client from a `bitcoind://[user:password@]host:port[/wallet/<name>]?birthday=<ts>`
+    /// URL (or `bitcoinds://` for HTTPS). Return None if the URL doesn't use one of these schemes
+    /// or is invalid.
+    ///
+    /// The required `birthday` (a UNIX timestamp) is the earliest time the wallet may have
+    /// received a TX, the chain is rescanned from there when its scripts are first imported. It
+    /// should be the wallet creation time, as a birthday of 0 rescans the whole chain.
+    pub(crate) fn from_url(indexer_url: &str, socks5_proxy: Option<&Socks5Proxy>) -> Option<Self> {
+        let url = Url::parse(indexer_url).ok()?;
+        let http_scheme = match url.scheme() {
+            BITCOIND_SCHEME => "http",
+            BITCOIND_TLS_SCHEME => "https",
+            _ => return None,
+        };
+        let host = url.host_str()?;
+        let base_url = match url.port() {
+            Some(port) => format!("{http_scheme}://{host}:{port}"),
+            None => format!("{http_scheme}://{host}"),
+        };
+        let wallet_name = url
+            .path()
+            .strip_prefix("/wallet/")
+            .filter(|n| !n.is_empty())
+            .unwrap_or(BITCOIND_DEFAULT_WALLET)
+            .to_string();
+        let auth = if url.username().is_empty() {
+            None
+        } else {
+            Some((
+                url.username().to_string(),
+                url.password().unwrap_or_default().to_string(),
+            ))
+        };
+        let birthday = url
+            .query_pairs()
+            .find(|(k, _)| k == "birthday")?
+            .1
+            .parse::<u64>()
+            .ok()?;
+        let client = build_rest_client(INDEXER_TIMEOUT, INDEXER_TIMEOUT, socks5_proxy).ok()?;
+        Some(Self {
+            client,
+            wallet_url: format!("{base_url}/wallet/{wallet_name}"),
+            base_url,
+            wallet_name,
+            auth,
+            birthday,
+            imported_spks: Mutex::new(None),
+        })
+    }
+
+    fn call_url<T: de::DeserializeOwned>(
+        &self,
+        url: &str,
+        method: &str,
+        params: serde_json::Value,
+        timeout: u64,
+    ) -> Result<T, BitcoindError> {
+        let body = serde_json::json!({
+            "jsonrpc": "1.0",
+            "id": "rgb-lib",
+            "method": method,
+            "params": params,
+        });
+        let mut attempt = 0;
+        let response = loop {
+            let mut request = self
+                .client
+                .post(url)
+                .header(CONTENT_TYPE, JSON)
+                .timeout(Duration::from_secs(timeout))
+                .json(&body);
+            if let Some((user, password)) = &self.auth {
+                request = request.basic_auth(user, Some(password));
+            }
+            // RPC errors are returned with a non-success status, so the body is always parsed
+            match request.send().and_then(|r| r.json::<RpcResponse>()) {
+                Ok(response) => break response,
+                Err(e) if attempt >= INDEXER_RETRIES => return Err(e.into()),
+                Err(_) => attempt += 1,
+            }
+        };
+        if let Some(error) = response.error {
+            return Err(BitcoindError::Rpc {
+                code: error.code,
+                message: error.message,
+            });
+        }
+        serde_json::from_value(response.result)
+            .map_err(|e| BitcoindError::InvalidResponse(e.to_string()))
+    }
+
+    fn call<T: de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, BitcoindError> {
+        self.call_url(&self.base_url, method, params, INDEXER_TIMEOUT)
+    }
+
+    fn call_wallet<T: de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, BitcoindError> {
+        self.call_url(&self.wallet_url, method, params, INDEXER_TIMEOUT)
+    }
+
+    pub(crate) fn get_block_hash(&self, height: u32) -> Result<BlockHash, BitcoindError> {
+        self.call("getblockhash", serde_json::json!([height]))
+    }
+
+    pub(crate) fn get_block_count(&self) -> Result<u32, BitcoindError> {
+        self.call("getblockcount", serde_json::json!([]))
+    }
+
+    pub(crate) fn send_raw_transaction(&self, tx: &BdkTransaction) -> Result<(), BitcoindError> {
+        let tx_hex = bitcoin::consensus::encode::serialize_hex(tx);
+        self.call::<Txid>("sendrawtransaction", serde_json::json!([tx_hex]))?;
+        Ok(())
+    }
+
+    /// Return the estimated fee rate in sat/vB, None if the node cannot estimate it.
+    pub(crate) fn estimate_smart_fee(&self, blocks: u16) -> Result<Option<f64>, BitcoindError> {
+        let estimate: RpcFeeEstimate =
+            self.call("estimatesmartfee", serde_json::json!([blocks]))?; // in BTC/kvB
+        Ok(estimate
+            .feerate
+            .map(|feerate| (feerate * 100_000_000.0) / 1_000.0))
+    }
+
+    /// Return the details of the TX with the provided ID, looking it up in the node wallet if
+    /// the node doesn't know about it (i.e. when running without `-txindex`).
+    pub(crate) fn get_tx_info(&self, txid: &Txid) -> Result<Option<RpcTxInfo>, BitcoindError> {
+        match self.call("getrawtransaction", serde_json::json!([txid, true])) {
+            Ok(info) => return Ok(Some(info)),
+            Err(BitcoindError::Rpc { code, ref message })
+                if code == RPC_INVALID_ADDRESS_OR_KEY && !message.contains("genesis block") => {}
+            Err(e) => return Err(e),
+        }
+        self.ensure_wallet()?;
+        match self.call_wallet("gettransaction", serde_json::json!([txid, true])) {
+            Ok(info) => Ok(Some(info)),
+            Err(BitcoindError::Rpc { code, .. }) if code == RPC_INVALID_ADDRESS_OR_KEY => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn decode_tx(info: &RpcTxInfo) -> Result<BdkTransaction, BitcoindError> {
+        bitcoin::consensus::encode::deserialize_hex(&info.hex)
+            .map_err(|e| BitcoindError::InvalidResponse(e.to_string()))
+    }
+
+    fn get_anchor(&self, info: &RpcTxInfo) -> Result<Option<ConfirmationBlockTime>, BitcoindError> {
+        if info.confirmations <= 0 {
+            return Ok(None);
+        }
+        let (Some(hash), Some(confirmation_time)) = (info.blockhash, info.blocktime) else {
+            return Ok(None);
+        };
+        let height = match info.blockheight {
+            Some(height) => height,
+            None => {
+                let header: RpcBlockHeader =
+                    self.call("getblockheader", serde_json::json!([hash, true]))?;
+                header.height
+            }
+        };
+        Ok(Some(ConfirmationBlockTime {
+            block_id: BlockId { height, hash },
+            confirmation_time,
+        }))
+    }
+
+    fn ensure_wallet(&self) -> Result<(), BitcoindError> {
+        let mut imported_spks = self.imported_spks.lock().unwrap();
+        if imported_spks.is_some() {
+            return Ok(());
+        }
+        match self.call::<serde_json::Value>("loadwallet", serde_json::json!([self.wallet_name])) {
+            Ok(_) => {}
+            Err(BitcoindError::Rpc { code, .. }) if code == RPC_WALLET_ALREADY_LOADED => {}
+            Err(BitcoindError::Rpc { code, .. }) if code == RPC_WALLET_NOT_FOUND => {
+                // watch-only, blank, descriptor wallet
+                self.call::<serde_json::Value>(
+                    "createwallet",
+                    serde_json::json!([self.wallet_name, true, true, "", false, true]),
+                )?;
+            }
+            Err(e) => return Err(e),
+        }
+        let descriptors: RpcListDescriptors =
+            self.call_wallet("listdescriptors", serde_json::json!([]))?;
+        let spks = descriptors
+            .descriptors
+            .iter()
+            .filter_map(|d| {
+                let desc = d.desc.split('#').next()?;
+                let script_hex = desc.strip_prefix("raw(")?.strip_suffix(')')?;
+                ScriptBuf::from_hex(script_hex).ok()
+            })
+            .collect();
+        *imported_spks = Some(spks);
+        Ok(())
+    }
+
+    /// Import the provided scripts in the node wallet, rescanning the chain from the provided
+    /// timestamp if any is new. The first import into an empty node wallet always rescans from
+    /// the wallet birthday.
+    ///
+    /// All the scripts are imported with a single call, so the node rescans the chain only once.
+    fn import_spks(&self, spks: &HashSet<ScriptBuf>, timestamp: u64) -> Result<(), BitcoindError> {
+        self.ensure_wallet()?;
+        let mut imported_spks = self.imported_spks.lock().unwrap();
+        let imported = imported_spks.as_mut().expect("wallet has been loaded");
+        let new_spks: Vec<&ScriptBuf> = spks.iter().filter(|s| !imported.contains(*s)).collect();
+        if new_spks.is_empty() {
+            return Ok(());
+        }
+        let timestamp = if imported.is_empty() {
+            self.birthday
+        } else {
+            timestamp
+        };
+        let requests = new_spks
+            .iter()
+            .map(|spk| {
+                let desc = format!("raw({})", spk.to_hex_string());
+                let checksum = calc_checksum(&desc)
+                    .map_err(|e| BitcoindError::InvalidResponse(e.to_string()))?;
+                Ok(serde_json::json!({
+                    "desc": format!("{desc}#{checksum}"),
+                    "timestamp": timestamp,
+                }))
+            })
+            .collect::<Result<Vec<_>, BitcoindError>>()?;
+        let results: Vec<RpcImportResult> = self.call_url(
+            &self.wallet_url,
+            "importdescriptors",
+            serde_json::json!([requests]),
+            RESCAN_TIMEOUT,
+        )?;
+        if let Some(error) = results.into_iter().find(|r| !r.success) {
+            let (code, message) = error
+                .error
+                .map(|e| (e.code, e.message))
+                .unwrap_or((0, s!("import failed")));
+            return Err(BitcoindError::Rpc { code, message });
+        }
+        imported.extend(new_spks.into_iter().cloned());
+        Ok(())
+    }
+
+    /// Add to the provided map the TXs in the node wallet that are not already there.
+    fn fetch_wallet_txs(
+        &self,
+        wallet_txs: &mut HashMap<Txid, WalletTx>,
+    ) -> Result<(), BitcoindError> {
+        let list: RpcListSinceBlock = self.call_wallet("listsinceblock", serde_json::json!([]))?;
+        for wallet_tx in list.transactions {
+            // negative confirmations mark TXs conflicting with the chain
+            if wallet_tx.confirmations < 0 || wallet_txs.contains_key(&wallet_tx.txid) {
+                continue;
+            }
+            let info: RpcTxInfo =
+                self.call_wallet("gettransaction", serde_json::json!([wallet_tx.txid, true]))?;
+            let tx = Arc::new(Self::decode_tx(&info)?);
+            wallet_txs.insert(wallet_tx.txid, WalletTx { tx, info });
+        }
+        Ok(())
+    }
+
+    /// Build a TX update with the wallet TXs either paying to or spending from the provided
+    /// scripts, as the node wallet may also be monitoring other scripts.
+    fn build_tx_update(
+        &self,
+        wallet_txs: &HashMap<Txid, WalletTx>,
+        spks: &HashSet<ScriptBuf>,
+        start_time: u64,
+    ) -> Result<TxUpdate<ConfirmationBlockTime>, BitcoindError> {
+        let owned_outpoints: HashSet<OutPoint> = wallet_txs
+            .iter()
+            .flat_map(|(txid, wtx)| {
+                wtx.tx
+                    .output
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, o)| spks.contains(&o.script_pubkey))
+                    .map(|(vout, _)| OutPoint::new(*txid, vout as u32))
+            })
+            .collect();
+        let mut tx_update = TxUpdate::default();
+        for wtx in wallet_txs.values() {
+            let pays_to_spks = wtx
+                .tx
+                .output
+                .iter()
+                .any(|o| spks.contains(&o.script_pubkey));
+            let spends_from_spks = wtx
+                .tx
+                .input
+                .iter()
+                .any(|i| owned_outpoints.contains(&i.previous_output));
+            if !pays_to_spks && !spends_from_spks {
+                continue;
+            }
+            self.add_tx(&mut tx_update, wtx.tx.clone(), &wtx.info, start_time)?;
+            // prevouts help calculating fees, only the ones known to the wallet are added
+            for input in &wtx.tx.input {
+                let prev = input.previous_output;
+                if let Some(txout) = wallet_txs
+                    .get(&prev.txid)
+                    .and_then(|p| p.tx.output.get(prev.vout as usize))
+                {
+                    tx_update.txouts.insert(prev, txout.clone());
+                }
+            }
+        }
+        Ok(tx_update)
+    }
+
+    fn add_tx(
+        &self,
+        tx_update: &mut TxUpdate<ConfirmationBlockTime>,
+        tx: Arc<BdkTransaction>,
+        info: &RpcTxInfo,
+        start_time: u64,
+    ) -> Result<(), BitcoindError> {
+        let txid = tx.compute_txid();
+        match self.get_anchor(info)? {
+            Some(anchor) => {
+                tx_update.anchors.insert((anchor, txid));
+            }
+            None => {
+                tx_update.seen_ats.insert((txid, start_time));
+            }
+        }
+        tx_update.txs.push(tx);
+        Ok(())
+    }
+
+    /// Build a chain update connecting to the provided tip, adding a checkpoint for each anchor.
+    fn chain_update(
+        &self,
+        prev_tip: CheckPoint,
+        anchors: &BTreeSet<(ConfirmationBlockTime, Txid)>,
+    ) -> Result<CheckPoint, BitcoindError> {
+        let tip_height = self.get_block_count()?;
+        let mut new_blocks = BTreeMap::new();
+        let mut tip = if tip_height < prev_tip.height() {
+            prev_tip
+        } else {
+            let start_height = tip_height.saturating_sub(CHAIN_SUFFIX_LENGTH - 1);
+            for height in start_height..=tip_height {
+                new_blocks.insert(height, self.get_block_hash(height)?);
+            }
+            // find the point of agreement with the node
+            let mut agreement_cp = None;
+            for cp in prev_tip.iter() {
+                let hash = match new_blocks.get(&cp.height()) {
+                    Some(hash) => *hash,
+                    None => {
+                        let hash = self.get_block_hash(cp.height())?;
+                        new_blocks.insert(cp.height(), hash);
+                        hash
+                    }
+                };
+                if hash == cp.hash() {
+                    agreement_cp = Some(cp);
+                    break;
+                }
+            }
+            let agreement_cp = agreement_cp.ok_or_else(|| {
+                BitcoindError::InvalidResponse(s!("cannot find agreement block with the node"))
+            })?;
+            let agreement_height = agreement_cp.height();
+            agreement_cp
+                .extend(
+                    new_blocks
+                        .iter()
+                        .filter(|(height, _)| **height > agreement_height)
+                        .map(|(&height, &hash)| BlockId { height, hash }),
+                )
+                .expect("extension heights are greater than the agreement height")
+        };
+        for (anchor, _) in anchors {
+            let height = anchor.block_id.height;
+            if tip.get(height).is_none() && height <= tip.height() {
+                let hash = new_blocks
+                    .get(&height)
+                    .copied()
+                    .unwrap_or(anchor.block_id.hash);
+                tip = tip.insert(BlockId { height, hash });
+            }
+        }
+        Ok(tip)
+    }
+
+    pub(crate) fn sync<I>(
+        &self,
+        request: impl Into<SyncRequest<I>>,
+    ) -> Result<SyncResponse, BitcoindError> {
+        let mut request = request.into();
+        let start_time = request.start_time();
+        let chain_tip = request.chain_tip();
+        let spks: HashSet<ScriptBuf> = request
+            .iter_spks_with_expected_txids()
+            .map(|s| s.spk)
+            .collect();
+        let txids: Vec<Txid> = request.iter_txids().collect();
+        // TXs spending the requested outpoints are wallet TXs, so they're found via the scripts
+        request.iter_outpoints().for_each(drop);
+
+        // scripts first seen in a sync have just been derived, so they can only have been paid
+        // recently: the node still rescans the blocks of the last couple of hours before the
+        // provided timestamp
+        self.import_spks(&spks, start_time)?;
+        let mut wallet_txs = HashMap::new();
+        self.fetch_wallet_txs(&mut wallet_txs)?;
+        let mut tx_update = self.build_tx_update(&wallet_txs, &spks, start_time)?;
+        for txid in txids {
+            if tx_update.txs.iter().any(|tx| tx.compute_txid() == txid) {
+                continue;
+            }
+            match self.get_tx_info(&txid)? {
+                Some(info) if info.confirmations >= 0 => {
+                    let tx = Arc::new(Self::decode_tx(&info)?);
+                    self.add_tx(&mut tx_update, tx, &info, start_time)?;
+                }
+                _ => {
+                    tx_update.evicted_ats.insert((txid, start_time));
+                }
+            }
+        }
+        let chain_update = match chain_tip {
+            Some(tip) => Some(self.chain_update(tip, &tx_update.anchors)?),
+            None => None,
+        };
+        Ok(SyncResponse {
+            tx_update,
+            chain_update,
+        })
+    }
+
+    pub(crate) fn full_scan<K: Ord + Clone>(
+        &self,
+        request: impl Into<FullScanRequest<K>>,
+    ) -> Result<FullScanResponse<K>, BitcoindError> {
+        let mut request = request.into();
+        let start_time = request.start_time();
+        let chain_tip = request.chain_tip();
+        let mut spks = HashSet::new();
+        let mut wallet_txs = HashMap::new();
+        let mut last_active_indices = BTreeMap::new();
+        // each round imports the next batch of scripts of all the keychains still being scanned,
+        // so that the node rescans the chain from the wallet birthday once per round
+        let mut keychains = request.keychains();
+        while !keychains.is_empty() {
+            let batches: Vec<(K, Vec<(u32, ScriptBuf)>)> = keychains
+                .drain(..)
+                .map(|keychain| {
+                    let batch = (0..INDEXER_STOP_GAP)
+                        .map_while(|_| request.next_spk(keychain.clone()))
+                        .collect();
+                    (keychain, batch)
+                })
+                .filter(|(_, batch): &(K, Vec<(u32, ScriptBuf)>)| !batch.is_empty())
+                .collect();
+            let round_spks: HashSet<ScriptBuf> = batches
+                .iter()
+                .flat_map(|(_, batch)| batch.iter().map(|(_, spk)| spk.clone()))
+                .collect();
+            self.import_spks(&round_spks, self.birthday)?;
+            self.fetch_wallet_txs(&mut wallet_txs)?;
+            let used_spks: HashSet<&ScriptBuf> = wallet_txs
+                .values()
+                .flat_map(|wtx| wtx.tx.output.iter().map(|o| &o.script_pubkey))
+                .filter(|spk| round_spks.contains(*spk))
+                .collect();
+            for (keychain, batch) in batches {
+                for (index, spk) in &batch {
+                    if used_spks.contains(spk) {
+                        last_active_indices.insert(keychain.clone(), *index);
+                    }
+                }
+                let (last_index, _) = batch.last().expect("batch is not empty");
+                if matches!(
+                    last_active_indices.get(&keychain),
+                    Some(active) if last_index - active < INDEXER_STOP_GAP as u32
+                ) {
+                    keychains.push(keychain);
+                }
+            }
+            spks.extend(round_spks);
+        }
+        let tx_update = self.build_tx_update(&wallet_txs, &spks, start_time)?;
+        let chain_update = match chain_tip {
+            Some(tip) => Some(self.chain_update(tip, &tx_update.anchors)?),
+            None => None,
+        };
+        Ok(FullScanResponse {
+            tx_update,
+            last_active_indices,
+            chain_update,
+        })
+    }
+}
+
+impl ResolveWitness for BitcoindClient {
+    fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
+        // check the node is for the correct network
+        let block_hash = self
+            .get_block_hash(0)
+            .map_err(|e| WitnessResolverError::ResolverIssue(None, e.to_string()))?;
+        let chain_hash = ChainHash::from_genesis_block_hash(block_hash);
+        if chain_net.chain_hash() != chain_hash {
+            return Err(WitnessResolverError::WrongChainNet);
+        }
+        Ok(())
+    }
+
+    fn resolve_witness(&self, txid: RgbTxid) -> Result<WitnessStatus, WitnessResolverError> {
+        let resolver_issue =
+            |e: BitcoindError| WitnessResolverError::ResolverIssue(Some(txid), e.to_string());
+        let Some(info) = self.get_tx_info(&txid).map_err(resolver_issue)? else {
+            return Ok(WitnessStatus::Unresolved);
+        };
+        if info.confirmations < 0 {
+            return Ok(WitnessStatus::Unresolved);
+        }
+        let tx = Self::decode_tx(&info).map_err(resolver_issue)?;
+        let ord = match self.get_anchor(&info).map_err(resolver_issue)? {
+            Some(anchor) => {
+                let height = NonZeroU32::new(anchor.block_id.height)
+                    .ok_or(WitnessResolverError::InvalidResolverData)?;
+                WitnessOrd::Mined(
+                    WitnessPos::bitcoin(height, anchor.confirmation_time as i64)
+                        .ok_or(WitnessResolverError::InvalidResolverData)?,
+                )
+            }
+            None => WitnessOrd::Tentative,
+        };
+        Ok(WitnessStatus::Resolved(tx, ord))
+    }
+}
@@ -0,0 +1,1545 @@
+use super::*;
+
+// https://github.com/bitcoin/bips/blob/master/bip-0157.mediawiki
+const BASIC_FILTER_TYPE: u8 = 0;
+const MAX_FILTERS_PER_REQUEST: u32 = 1000;
+const MAX_FILTER_HEADERS_PER_REQUEST: u32 = 2000;
+const MAX_HEADERS_PER_MESSAGE: usize = 2000;
+
+// number of blocks below the tip to include in chain updates
+const CHAIN_SUFFIX_LENGTH: u32 = 8;
+// number of most recent block hashes included in a block locator before it starts skipping
+const LOCATOR_DENSE_LENGTH: usize = 10;
+
+const USER_AGENT: &str = concat!("/rgb-lib:", env!("CARGO_PKG_VERSION"), "/");
+
+const HEADERS_FILE: &str = "headers";
+const FILTER_HEADERS_FILE: &str = "filter_headers";
+const FILTERS_FILE: &str = "filters";
+const HEADER_SIZE: u64 = 80;
+const FILTER_HEADER_SIZE: u64 = 32;
+
+/// Connection to a P2P peer serving compact block filters.
+struct Peer {
+    reader: bitcoin::io::FromStd<BufReader<TcpStream>>,
+    writer: TcpStream,
+    network: Network,
+}
+
+impl Peer {
+    fn connect(addr: &str, network: Network) -> Result<Self, CbfError> {
+        let socket_addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| CbfError::Peer(format!("cannot resolve address '{addr}'")))?;
+        let timeout = Duration::from_secs(INDEXER_TIMEOUT);
+        let stream = TcpStream::connect_timeout(&socket_addr, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        let mut peer = Self {
+            reader: bitcoin::io::FromStd::new(BufReader::new(stream.try_clone()?)),
+            writer: stream,
+            network,
+        };
+        peer.handshake(socket_addr)?;
+        Ok(peer)
+    }
+
+    fn handshake(&mut self, socket_addr: SocketAddr) -> Result<(), CbfError> {
+        let mut version = VersionMessage::new(
+            ServiceFlags::NONE,
+            now().unix_timestamp(),
+            P2pAddress::new(&socket_addr, ServiceFlags::NONE),
+            P2pAddress::new(&SocketAddr::from(([0, 0, 0, 0], 0)), ServiceFlags::NONE),
+            rand::rng().random_range(0..u64::MAX),
+            USER_AGENT.to_string(),
+            0,
+        );
+        // we're not interested in TX announcements
+        version.relay = false;
+        self.send(NetworkMessage::Version(version))?;
+        let (mut version_received, mut verack_received) = (false, false);
+        while !(version_received && verack_received) {
+            match self.receive()? {
+                NetworkMessage::Version(version) => {
+                    if !version.services.has(ServiceFlags::COMPACT_FILTERS)
+                        || !version.services.has(ServiceFlags::WITNESS)
+                    {
+                        return Err(CbfError::Peer(s!(
+                            "peer doesn't serve compact block filters"
+                        )));
+                    }
+                    self.send(NetworkMessage::Verack)?;
+                    version_received = true;
+                }
+                NetworkMessage::Verack => verack_received = true,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, message: NetworkMessage) -> Result<(), CbfError> {
+        let raw = RawNetworkMessage::new(self.network.magic(), message);
+        self.writer
+            .write_all(&bitcoin::consensus::encode::serialize(&raw))?;
+        Ok(())
+    }
+
+    /// Return the next message from the peer, answering pings in the meantime.
+    fn receive(&mut self) -> Result<NetworkMessage, CbfError> {
+        loop {
+            let raw = RawNetworkMessage::consensus_decode(&mut self.reader)?;
+            if *raw.magic() != self.network.magic() {
+                return Err(CbfError::Peer(s!("peer is on a different network")));
+            }
+            match raw.into_payload() {
+                NetworkMessage::Ping(nonce) => self.send(NetworkMessage::Pong(nonce))?,
+                message => return Ok(message),
+            }
+        }
+    }
+
+    /// Send the provided message and return the first response accepted by the provided function,
+    /// None if the peer didn't answer (e.g. because it doesn't know the requested blocks).
+    fn request<T>(
+        &mut self,
+        message: NetworkMessage,
+        mut accept: impl FnMut(NetworkMessage) -> Option<T>,
+    ) -> Result<Option<T>, CbfError> {
+        self.send(message)?;
+        // messages are processed in order, so the pong marks the end of the answer
+        let nonce = rand::rng().random_range(0..u64::MAX);
+        self.send(NetworkMessage::Ping(nonce))?;
+        loop {
+            match self.receive()? {
+                NetworkMessage::Pong(n) if n == nonce => return Ok(None),
+                message => {
+                    if let Some(response) = accept(message) {
+                        return Ok(Some(response));
+                    }
+                }
+            }
+        }
+    }
+
+    fn get_filter_headers(
+        &mut self,
+        start_height: u32,
+        stop_hash: BlockHash,
+    ) -> Result<Option<CFHeaders>, CbfError> {
+        let message = NetworkMessage::GetCFHeaders(GetCFHeaders {
+            filter_type: BASIC_FILTER_TYPE,
+            start_height,
+            stop_hash,
+        });
+        self.request(message, |message| match message {
+            NetworkMessage::CFHeaders(cfheaders)
+                if cfheaders.filter_type == BASIC_FILTER_TYPE
+                    && cfheaders.stop_hash == stop_hash =>
+            {
+                Some(cfheaders)
+            }
+            _ => None,
+        })
+    }
+
+    fn get_block(&mut self, block_hash: BlockHash) -> Result<Block, CbfError> {
+        self.send(NetworkMessage::GetData(vec![Inventory::WitnessBlock(
+            block_hash,
+        )]))?;
+        loop {
+            match self.receive()? {
+                NetworkMessage::Block(block) if block.block_hash() == block_hash => {
+                    if !block.check_merkle_root() || !block.check_witness_commitment() {
+                        return Err(CbfError::Peer(format!(
+                            "block {block_hash} doesn't match its header"
+                        )));
+                    }
+                    return Ok(block);
+                }
+                NetworkMessage::NotFound(_) => {
+                    return Err(CbfError::Peer(format!("block {block_hash} not found")));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+struct ConfirmedTx {
+    tx: Arc<BdkTransaction>,
+    height: u32,
+}
+
+#[derive(Clone, Copy)]
+struct BlockInfo {
+    hash: BlockHash,
+    time: u32,
+    bits: CompactTarget,
+}
+
+impl BlockInfo {
+    fn new(header: &Header) -> Self {
+        Self {
+            hash: header.block_hash(),
+            time: header.time,
+            bits: header.bits,
+        }
+    }
+
+    fn work(&self) -> Work {
+        Target::from_compact(self.bits).to_work()
+    }
+}
+
+fn chain_work<'a>(blocks: impl Iterator<Item = &'a BlockInfo>) -> Work {
+    blocks.fold(Work::from_be_bytes([0; 32]), |work, block| {
+        work + block.work()
+    })
+}
+
+/// Return the bits a header at the provided height needs to have, following the consensus rules
+/// for difficulty adjustments, given a function returning the blocks of its chain.
+fn required_bits(
+    params: &ConsensusParams,
+    height: u32,
+    time: u32,
+    block_at: impl Fn(u32) -> BlockInfo,
+) -> CompactTarget {
+    let prev = block_at(height - 1);
+    let interval = params.difficulty_adjustment_interval() as u32;
+    if height.is_multiple_of(interval) {
+        let first = block_at(height - interval);
+        // BIP94: testnet4 retargets from the first block of the period, to avoid starting from a
+        // block mined with the minimum difficulty
+        let bits = if params.network == Network::Testnet4 {
+            first.bits
+        } else {
+            prev.bits
+        };
+        let timespan = (prev.time as i64 - first.time as i64).max(0) as u64;
+        return CompactTarget::from_next_work_required(bits, timespan, params);
+    }
+    if params.allow_min_difficulty_blocks {
+        let min_difficulty_bits = params.max_attainable_target.to_compact_lossy();
+        // blocks found after twice the target spacing can be mined with the minimum difficulty
+        if time as u64 > prev.time as u64 + params.pow_target_spacing * 2 {
+            return min_difficulty_bits;
+        }
+        // otherwise the difficulty is the one of the last block not mined with the minimum one
+        let mut height = height - 1;
+        while !height.is_multiple_of(interval) && block_at(height).bits == min_difficulty_bits {
+            height -= 1;
+        }
+        return block_at(height).bits;
+    }
+    prev.bits
+}
+
+/// Block headers, filter headers and block filters downloaded from the peers, stored in the wallet
+/// directory so they don't need to be downloaded again each time the wallet goes online.
+///
+/// Files are only appended to or truncated (on reorgs), so an interrupted write can only leave an
+/// incomplete last record, which is dropped when loading them.
+struct CbfStore {
+    headers: File,
+    filter_headers: File,
+    // filters of the blocks from the start height, each one prefixed by its length, after the
+    // start height itself
+    filters: File,
+    start_height: u32,
+    // offsets of the stored filters in the filters file, followed by the end of the last one
+    filter_offsets: Vec<u64>,
+}
+
+impl CbfStore {
+    fn open(dir: &Path, start_height: u32) -> Result<Self, CbfError> {
+        fs::create_dir_all(dir)?;
+        let open = |name: &str| {
+            fs::OpenOptions::new()
+                .read(true)
+                .append(true)
+                .create(true)
+                .open(dir.join(name))
+        };
+        let mut filters = open(FILTERS_FILE)?;
+        let mut start_height_bytes = [0; 4];
+        if filters.read_exact(&mut start_height_bytes).is_err()
+            || u32::from_le_bytes(start_height_bytes) != start_height
+        {
+            filters.set_len(0)?;
+            filters.write_all(&start_height.to_le_bytes())?;
+        }
+        let filters_len = filters.metadata()?.len();
+        let mut filter_offsets = vec![4];
+        let mut reader = BufReader::new(&filters);
+        reader.seek(SeekFrom::Start(4))?;
+        let mut len_bytes = [0; 4];
+        while reader.read_exact(&mut len_bytes).is_ok() {
+            let filter_len = u32::from_le_bytes(len_bytes);
+            let end = filter_offsets.last().expect("never empty") + 4 + filter_len as u64;
+            if end > filters_len {
+                break;
+            }
+            reader.seek_relative(filter_len as i64)?;
+            filter_offsets.push(end);
+        }
+        Ok(Self {
+            headers: open(HEADERS_FILE)?,
+            filter_headers: open(FILTER_HEADERS_FILE)?,
+            filters,
+            start_height,
+            filter_offsets,
+        })
+    }
+
+    /// Load the stored headers, keeping the ones connected to the provided genesis block.
+    fn load_headers(&mut self, genesis: &Header) -> Result<Vec<BlockInfo>, CbfError> {
+        let mut bytes = vec![];
+        (&self.headers).seek(SeekFrom::Start(0))?;
+        self.headers.read_to_end(&mut bytes)?;
+        let mut headers = vec![];
+        for chunk in bytes.chunks_exact(HEADER_SIZE as usize) {
+            let header: Header = deserialize(chunk)?;
+            let connected = match headers.last() {
+                Some(BlockInfo { hash, .. }) => header.prev_blockhash == *hash,
+                None => header == *genesis,
+            };
+            if !connected {
+                break;
+            }
+            headers.push(BlockInfo::new(&header));
+        }
+        if headers.is_empty() {
+            self.headers.set_len(0)?;
+            self.append_headers(&[*genesis])?;
+            headers.push(BlockInfo::new(genesis));
+        }
+        self.headers.set_len(headers.len() as u64 * HEADER_SIZE)?;
+        Ok(headers)
+    }
+
+    /// Load the stored filter headers, up to the provided number of blocks.
+    fn load_filter_headers(&mut self, blocks: usize) -> Result<Vec<FilterHeader>, CbfError> {
+        let mut bytes = vec![];
+        (&self.filter_headers).seek(SeekFrom::Start(0))?;
+        self.filter_headers.read_to_end(&mut bytes)?;
+        let filter_headers = bytes
+            .chunks_exact(FILTER_HEADER_SIZE as usize)
+            .take(blocks)
+            .map(deserialize)
+            .collect::<Result<Vec<FilterHeader>, _>>()?;
+        self.filter_headers
+            .set_len(filter_headers.len() as u64 * FILTER_HEADER_SIZE)?;
+        self.truncate_filters(filter_headers.len() as u32)?;
+        Ok(filter_headers)
+    }
+
+    fn append_headers(&mut self, headers: &[Header]) -> Result<(), CbfError> {
+        let bytes: Vec<u8> = headers.iter().flat_map(serialize).collect();
+        self.headers.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn append_filter_headers(&mut self, filter_headers: &[FilterHeader]) -> Result<(), CbfError> {
+        let bytes: Vec<u8> = filter_headers.iter().flat_map(serialize).collect();
+        self.filter_headers.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Return the height following the last stored filter.
+    fn filters_end(&self) -> u32 {
+        self.start_height + (self.filter_offsets.len() - 1) as u32
+    }
+
+    fn append_filter(&mut self, filter: &[u8]) -> Result<(), CbfError> {
+        let mut bytes = (filter.len() as u32).to_le_bytes().to_vec();
+        bytes.extend(filter);
+        self.filters.write_all(&bytes)?;
+        let end = self.filter_offsets.last().expect("never empty") + bytes.len() as u64;
+        self.filter_offsets.push(end);
+        Ok(())
+    }
+
+    /// Return the stored filters of the blocks in the provided height range.
+    fn get_filters(&self, from_height: u32, to_height: u32) -> Result<Vec<Vec<u8>>, CbfError> {
+        let from = self.filter_offsets[(from_height - self.start_height) as usize];
+        let to = self.filter_offsets[(to_height - self.start_height) as usize + 1];
+        let mut bytes = vec![0; (to - from) as usize];
+        (&self.filters).seek(SeekFrom::Start(from))?;
+        (&self.filters).read_exact(&mut bytes)?;
+        let mut filters = vec![];
+        let mut rest = bytes.as_slice();
+        while let Some((len_bytes, tail)) = rest.split_first_chunk::<4>() {
+            let (filter, tail) = tail.split_at(u32::from_le_bytes(*len_bytes) as usize);
+            filters.push(filter.to_vec());
+            rest = tail;
+        }
+        Ok(filters)
+    }
+
+    /// Drop the filters from the provided height.
+    fn truncate_filters(&mut self, height: u32) -> Result<(), CbfError> {
+        let kept = height.saturating_sub(self.start_height) as usize + 1;
+        if kept < self.filter_offsets.len() {
+            self.filter_offsets.truncate(kept);
+            self.filters
+                .set_len(*self.filter_offsets.last().expect("never empty"))?;
+        }
+        Ok(())
+    }
+
+    /// Drop the data of the blocks above the provided height.
+    fn truncate(&mut self, height: u32) -> Result<(), CbfError> {
+        let blocks = height as u64 + 1;
+        self.headers.set_len(blocks * HEADER_SIZE)?;
+        let filter_headers_len = self.filter_headers.metadata()?.len();
+        self.filter_headers
+            .set_len(min(filter_headers_len, blocks * FILTER_HEADER_SIZE))?;
+        self.truncate_filters(height + 1)
+    }
+}
+
+struct CbfState {
+    store: CbfStore,
+    // connections to the reachable peers, the first one serves filters and blocks
+    peers: Vec<Peer>,
+    // block headers from genesis to the tip, indexed by height
+    headers: Vec<BlockInfo>,
+    // filter headers of the blocks, indexed by height, downloaded up to the tip after the headers
+    filter_headers: Vec<FilterHeader>,
+    // scripts already checked against the block filters up to the scanned height
+    spks: HashSet<ScriptBuf>,
+    // scripts to be checked against the block filters at the next scan
+    pending_spks: HashSet<ScriptBuf>,
+    scanned_height: Option<u32>,
+    // outputs paying to the scanned scripts, used to detect TXs spending them
+    owned_outpoints: HashSet<OutPoint>,
+    // TXs not paying to wallet scripts (e.g. RGB witnesses) that need to be looked up anyway
+    watched_txids: HashSet<Txid>,
+    confirmed: HashMap<Txid, ConfirmedTx>,
+    // TXs broadcast by this client and not yet found in a block, with the time they were sent
+    unconfirmed: HashMap<Txid, (Arc<BdkTransaction>, u64)>,
+}
+
+impl CbfState {
+    fn load(mut store: CbfStore, network: Network) -> Result<Self, CbfError> {
+        let headers = store.load_headers(&genesis_block(network).header)?;
+        let filter_headers = store.load_filter_headers(headers.len())?;
+        Ok(Self {
+            store,
+            peers: vec![],
+            headers,
+            filter_headers,
+            spks: HashSet::new(),
+            pending_spks: HashSet::new(),
+            scanned_height: None,
+            owned_outpoints: HashSet::new(),
+            watched_txids: HashSet::new(),
+            confirmed: HashMap::new(),
+            unconfirmed: HashMap::new(),
+        })
+    }
+
+    fn tip_height(&self) -> u32 {
+        (self.headers.len() - 1) as u32
+    }
+
+    fn block_hash(&self, height: u32) -> BlockHash {
+        self.headers[height as usize].hash
+    }
+
+    fn locator(&self) -> Vec<BlockHash> {
+        let mut locator = vec![];
+        let mut height = self.tip_height() as usize;
+        let mut step = 1;
+        while height > 0 {
+            locator.push(self.headers[height].hash);
+            if locator.len() >= LOCATOR_DENSE_LENGTH {
+                step *= 2;
+            }
+            height = height.saturating_sub(step);
+        }
+        locator.push(self.headers[0].hash);
+        locator
+    }
+
+    fn extend(&mut self, headers: &[(Header, BlockInfo)]) -> Result<(), CbfError> {
+        let (headers, infos): (Vec<Header>, Vec<BlockInfo>) = headers.iter().copied().unzip();
+        self.store.append_headers(&headers)?;
+        self.headers.extend(infos);
+        Ok(())
+    }
+
+    /// Drop the blocks above the provided height, along with what was found in them.
+    fn rewind(&mut self, height: u32) -> Result<(), CbfError> {
+        self.store.truncate(height)?;
+        self.headers.truncate(height as usize + 1);
+        self.filter_headers.truncate(height as usize + 1);
+        self.confirmed.retain(|_, c| c.height <= height);
+        self.scanned_height = self.scanned_height.map(|h| min(h, height));
+        Ok(())
+    }
+
+    fn prev_filter_header(&self, height: u32) -> FilterHeader {
+        match height.checked_sub(1) {
+            Some(prev_height) => self.filter_headers[prev_height as usize],
+            None => FilterHeader::all_zeros(),
+        }
+    }
+
+    fn add_filter_headers(&mut self, filter_headers: &[FilterHeader]) -> Result<(), CbfError> {
+        self.store.append_filter_headers(filter_headers)?;
+        self.filter_headers.extend(filter_headers);
+        Ok(())
+    }
+
+    fn process_block(&mut self, height: u32, block: &Block, spks: &HashSet<ScriptBuf>) {
+        for tx in &block.txdata {
+            let txid = tx.compute_txid();
+            let pays_to_spks = tx.output.iter().any(|o| spks.contains(&o.script_pubkey));
+            let spends_owned = tx
+                .input
+                .iter()
+                .any(|i| self.owned_outpoints.contains(&i.previous_output));
+            if !pays_to_spks && !spends_owned && !self.watched_txids.contains(&txid) {
+                continue;
+            }
+            for (vout, output) in tx.output.iter().enumerate() {
+                if spks.contains(&output.script_pubkey) {
+                    self.owned_outpoints
+                        .insert(OutPoint::new(txid, vout as u32));
+                }
+            }
+            self.unconfirmed.remove(&txid);
+            self.confirmed.insert(
+                txid,
+                ConfirmedTx {
+                    tx: Arc::new(tx.clone()),
+                    height,
+                },
+            );
+        }
+    }
+
+    /// Return the height of the block including the TX with the provided ID (Some(None) if
+    /// unconfirmed), None if the TX is unknown.
+    fn tx_height(&self, txid: &Txid) -> Option<Option<u32>> {
+        match self.confirmed.get(txid) {
+            Some(confirmed) => Some(Some(confirmed.height)),
+            None => self.unconfirmed.contains_key(txid).then_some(None),
+        }
+    }
+
+    fn known_tx(&self, txid: &Txid) -> Option<Arc<BdkTransaction>> {
+        self.confirmed
+            .get(txid)
+            .map(|c| c.tx.clone())
+            .or_else(|| self.unconfirmed.get(txid).map(|(tx, _)| tx.clone()))
+    }
+
+    fn known_txs(&self) -> impl Iterator<Item = &Arc<BdkTransaction>> {
+        self.confirmed
+            .values()
+            .map(|c| &c.tx)
+            .chain(self.unconfirmed.values().map(|(tx, _)| tx))
+    }
+
+    fn anchor(&self, height: u32) -> ConfirmationBlockTime {
+        let BlockInfo { hash, time, .. } = self.headers[height as usize];
+        ConfirmationBlockTime {
+            block_id: BlockId { height, hash },
+            confirmation_time: time as u64,
+        }
+    }
+
+    fn add_tx(&self, tx_update: &mut TxUpdate<ConfirmationBlockTime>, txid: Txid) -> bool {
+        if let Some(confirmed) = self.confirmed.get(&txid) {
+            tx_update
+                .anchors
+                .insert((self.anchor(confirmed.height), txid));
+            tx_update.txs.push(confirmed.tx.clone());
+        } else if let Some((tx, seen_at)) = self.unconfirmed.get(&txid) {
+            tx_update.seen_ats.insert((txid, *seen_at));
+            tx_update.txs.push(tx.clone());
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Build a TX update with the known TXs either paying to or spending from the provided
+    /// scripts, as other scripts may be monitored too.
+    fn tx_update(&self, spks: &HashSet<ScriptBuf>) -> TxUpdate<ConfirmationBlockTime> {
+        let txs: HashMap<Txid, &Arc<BdkTransaction>> =
+            self.known_txs().map(|tx| (tx.compute_txid(), tx)).collect();
+        let owned_outpoints: HashSet<OutPoint> = txs
+            .iter()
+            .flat_map(|(txid, tx)| {
+                tx.output
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, o)| spks.contains(&o.script_pubkey))
+                    .map(|(vout, _)| OutPoint::new(*txid, vout as u32))
+            })
+            .collect();
+        let mut tx_update = TxUpdate::default();
+        for (txid, tx) in &txs {
+            let pays_to_spks = tx.output.iter().any(|o| spks.contains(&o.script_pubkey));
+            let spends_from_spks = tx
+                .input
+                .iter()
+                .any(|i| owned_outpoints.contains(&i.previous_output));
+            if !pays_to_spks && !spends_from_spks {
+                continue;
+            }
+            self.add_tx(&mut tx_update, *txid);
+            // prevouts help calculating fees, only the known ones are added
+            for input in &tx.input {
+                let prev = input.previous_output;
+                if let Some(txout) = txs
+                    .get(&prev.txid)
+                    .and_then(|p| p.output.get(prev.vout as usize))
+                {
+                    tx_update.txouts.insert(prev, txout.clone());
+                }
+            }
+        }
+        tx_update
+    }
+
+    /// Build a chain update connecting to the provided tip, adding a checkpoint for each anchor.
+    fn chain_update(
+        &self,
+        prev_tip: CheckPoint,
+        anchors: &BTreeSet<(ConfirmationBlockTime, Txid)>,
+    ) -> Result<CheckPoint, CbfError> {
+        let tip_height = self.tip_height();
+        let agreement_cp = prev_tip
+            .iter()
+            .find(|cp| {
+                self.headers
+                    .get(cp.height() as usize)
+                    .is_some_and(|block| block.hash == cp.hash())
+            })
+            .ok_or_else(|| CbfError::Peer(s!("cannot find agreement block with the peer")))?;
+        let start_height = max(
+            agreement_cp.height() + 1,
+            tip_height.saturating_sub(CHAIN_SUFFIX_LENGTH - 1),
+        );
+        let mut tip = agreement_cp
+            .extend((start_height..=tip_height).map(|height| BlockId {
+                height,
+                hash: self.block_hash(height),
+            }))
+            .expect("extension heights are greater than the agreement height");
+        for (anchor, _) in anchors {
+            if tip.get(anchor.block_id.height).is_none() {
+                tip = tip.insert(anchor.block_id);
+            }
+        }
+        Ok(tip)
+    }
+}
+
+/// Light client using compact block filters (BIP157/158), used as indexer.
+///
+/// Block headers are downloaded from all the reachable P2P peers, following the chain with the
+/// most work, then the filter of each block is checked against the wallet scripts, downloading
+/// only the matching blocks, so peers don't learn which scripts belong to the wallet. Filters are
+/// verified against the filter headers, which all the peers serving them need to agree on. Block
+/// headers, filter headers and filters are stored in the wallet directory, scan results are kept
+/// in memory.
+///
+/// Peers don't share their mempool, so unconfirmed TXs are known only if broadcast by this
+/// client, and fee estimation is not available.
+pub struct CbfClient {
+    peers: Vec<String>,
+    network: Network,
+    start_height: u32,
+    state: Mutex<CbfState>,
+}
+
+impl CbfClient {
+    pub(crate) fn new(
+        options: &CbfOptions,
+        network: Network,
+        wallet_dir: &Path,
+    ) -> Result<Self, CbfError> {
+        let store = CbfStore::open(&wallet_dir.join(CBF_DIR), options.start_height)?;
+        Ok(Self {
+            peers: options.peers.clone(),
+            network,
+            start_height: options.start_height,
+            state: Mutex::new(CbfState::load(store, network)?),
+        })
+    }
+
+    /// Run the provided function with up-to-date block and filter headers and the peer serving
+    /// filters and blocks.
+    fn with_peer<T>(
+        &self,
+        f: impl FnOnce(&Self, &mut Peer, &mut CbfState) -> Result<T, CbfError>,
+    ) -> Result<T, CbfError> {
+        let mut state = self.state.lock().expect("lock poisoned");
+        let mut peers = std::mem::take(&mut state.peers);
+        if peers.is_empty() {
+            peers = self.connect();
+        }
+        let res = self
+            .sync_peers(&mut peers, &mut state)
+            .and_then(|_| f(self, &mut peers[0], &mut state));
+        if res.is_ok() {
+            state.peers = peers;
+        }
+        res
+    }
+
+    fn connect(&self) -> Vec<Peer> {
+        self.peers
+            .iter()
+            .filter_map(|addr| Peer::connect(addr, self.network).ok())
+            .collect()
+    }
+
+    /// Sync the block headers with all the peers, then the filter headers, making sure the peers
+    /// serving filters agree on them, and move the first of them to the front. Peers misbehaving
+    /// or no longer reachable are dropped.
+    fn sync_peers(&self, peers: &mut Vec<Peer>, state: &mut CbfState) -> Result<(), CbfError> {
+        let mut last_err = CbfError::NoPeers;
+        peers.retain_mut(|peer| match self.sync_headers(peer, state) {
+            Ok(()) => true,
+            Err(e) => {
+                last_err = e;
+                false
+            }
+        });
+        // peers not knowing the tip (e.g. still syncing) leave the download to the next ones
+        let mut idx = 0;
+        while state.filter_headers.len() < state.headers.len() {
+            let Some(peer) = peers.get_mut(idx) else {
+                return Err(last_err);
+            };
+            match self.sync_filter_headers(peer, state) {
+                Ok(()) => idx += 1,
+                Err(e) => {
+                    peers.remove(idx);
+                    last_err = e;
+                }
+            }
+        }
+        // each filter header commits to the previous ones, so comparing the tip ones is enough
+        let tip_filter_header = state.filter_headers[state.tip_height() as usize];
+        let mut serving_idx = None;
+        let mut idx = 0;
+        while idx < peers.len() {
+            match self.get_tip_filter_header(&mut peers[idx], state) {
+                Ok(Some(filter_header)) if filter_header != tip_filter_header => {
+                    return Err(CbfError::Peer(s!("peers disagree on the block filters")));
+                }
+                Ok(filter_header) => {
+                    if filter_header.is_some() {
+                        serving_idx.get_or_insert(idx);
+                    }
+                    idx += 1;
+                }
+                Err(e) => {
+                    peers.remove(idx);
+                    last_err = e;
+                }
+            }
+        }
+        peers.swap(0, serving_idx.ok_or(last_err)?);
+        Ok(())
+    }
+
+    /// Download the block headers from the peer, switching to its chain if it has more work than
+    /// the current one.
+    fn sync_headers(&self, peer: &mut Peer, state: &mut CbfState) -> Result<(), CbfError> {
+        let params = ConsensusParams::new(self.network);
+        // headers of a competing chain, applied only once fully downloaded if it has more work
+        let mut fork: Vec<(Header, BlockInfo)> = vec![];
+        let mut fork_height = 0;
+        // hash of the last received header, the next request continues from it
+        let mut last_hash = None;
+        loop {
+            let mut locator = state.locator();
+            if let Some(hash) = last_hash {
+                locator.insert(0, hash);
+            }
+            peer.send(NetworkMessage::GetHeaders(GetHeadersMessage::new(
+                locator,
+                BlockHash::all_zeros(),
+            )))?;
+            let headers: Vec<Header> = loop {
+                if let NetworkMessage::Headers(headers) = peer.receive()? {
+                    break headers;
+                }
+            };
+            let (Some(first), Some(last)) = (headers.first(), headers.last()) else {
+                break;
+            };
+            last_hash = Some(last.block_hash());
+            let mut new_headers = headers.as_slice();
+            if fork.is_empty() {
+                fork_height = state
+                    .headers
+                    .iter()
+                    .rposition(|block| block.hash == first.prev_blockhash)
+                    .ok_or_else(|| CbfError::Peer(s!("headers don't connect to the chain")))?
+                    as u32;
+                // peers not ahead of the chain send headers it already includes
+                let known = headers
+                    .iter()
+                    .zip(&state.headers[fork_height as usize + 1..])
+                    .take_while(|(header, block)| header.block_hash() == block.hash)
+                    .count();
+                fork_height += known as u32;
+                new_headers = &headers[known..];
+            }
+            for header in new_headers {
+                let height = fork_height + fork.len() as u32 + 1;
+                let block_at = |h: u32| match h.checked_sub(fork_height + 1) {
+                    Some(idx) => fork[idx as usize].1,
+                    None => state.headers[h as usize],
+                };
+                if header.prev_blockhash != block_at(height - 1).hash
+                    || header.bits != required_bits(&params, height, header.time, block_at)
+                {
+                    return Err(CbfError::Peer(s!("invalid header chain")));
+                }
+                header
+                    .validate_pow(header.target())
+                    .map_err(|_| CbfError::Peer(s!("invalid header proof of work")))?;
+                fork.push((*header, BlockInfo::new(header)));
+            }
+            // extensions of the current chain are applied right away
+            if fork_height == state.tip_height() && !fork.is_empty() {
+                state.extend(&fork)?;
+                fork.clear();
+                fork_height = state.tip_height();
+            }
+            if headers.len() < MAX_HEADERS_PER_MESSAGE {
+                break;
+            }
+        }
+        if !fork.is_empty() {
+            let fork_work = chain_work(fork.iter().map(|(_, block)| block));
+            let current_work = chain_work(state.headers[fork_height as usize + 1..].iter());
+            if fork_work > current_work {
+                state.rewind(fork_height)?;
+                state.extend(&fork)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Download the missing filter headers from the peer, stopping early if it doesn't serve them.
+    fn sync_filter_headers(&self, peer: &mut Peer, state: &mut CbfState) -> Result<(), CbfError> {
+        while state.filter_headers.len() < state.headers.len() {
+            let start_height = state.filter_headers.len() as u32;
+            let stop_height = min(
+                start_height + MAX_FILTER_HEADERS_PER_REQUEST - 1,
+                state.tip_height(),
+            );
+            let Some(cfheaders) =
+                peer.get_filter_headers(start_height, state.block_hash(stop_height))?
+            else {
+                return Ok(());
+            };
+            let prev_filter_header = state.prev_filter_header(start_height);
+            if cfheaders.previous_filter_header != prev_filter_header
+                || cfheaders.filter_hashes.len() != (stop_height - start_height + 1) as usize
+            {
+                return Err(CbfError::Peer(s!("invalid block filter headers")));
+            }
+            let filter_headers: Vec<FilterHeader> = cfheaders
+                .filter_hashes
+                .iter()
+                .scan(prev_filter_header, |filter_header, filter_hash| {
+                    *filter_header = filter_hash.filter_header(filter_header);
+                    Some(*filter_header)
+                })
+                .collect();
+            state.add_filter_headers(&filter_headers)?;
+        }
+        Ok(())
+    }
+
+    /// Return the filter header of the tip block according to the peer, None if the peer doesn't
+    /// serve it.
+    fn get_tip_filter_header(
+        &self,
+        peer: &mut Peer,
+        state: &CbfState,
+    ) -> Result<Option<FilterHeader>, CbfError> {
+        let tip_height = state.tip_height();
+        let Some(cfheaders) = peer.get_filter_headers(tip_height, state.block_hash(tip_height))?
+        else {
+            return Ok(None);
+        };
+        let [filter_hash] = cfheaders.filter_hashes[..] else {
+            return Err(CbfError::Peer(s!("invalid block filter headers")));
+        };
+        Ok(Some(
+            filter_hash.filter_header(&cfheaders.previous_filter_header),
+        ))
+    }
+
+    /// Return the filters of the blocks in the provided height range, downloading the ones not
+    /// stored yet and checking them against their filter headers.
+    fn get_filters(
+        &self,
+        peer: &mut Peer,
+        state: &mut CbfState,
+        start_height: u32,
+        stop_height: u32,
+    ) -> Result<Vec<Vec<u8>>, CbfError> {
+        let filters_end = state.store.filters_end();
+        if stop_height < filters_end {
+            return state.store.get_filters(start_height, stop_height);
+        }
+        peer.send(NetworkMessage::GetCFilters(GetCFilters {
+            filter_type: BASIC_FILTER_TYPE,
+            start_height,
+            stop_hash: state.block_hash(stop_height),
+        }))?;
+        let mut filters = vec![];
+        for height in start_height..=stop_height {
+            let cfilter = loop {
+                if let NetworkMessage::CFilter(cfilter) = peer.receive()? {
+                    break cfilter;
+                }
+            };
+            if cfilter.filter_type != BASIC_FILTER_TYPE
+                || cfilter.block_hash != state.block_hash(height)
+            {
+                return Err(CbfError::Peer(s!("unexpected block filter")));
+            }
+            let filter_header =
+                BlockFilter::new(&cfilter.filter).filter_header(&state.prev_filter_header(height));
+            if filter_header != state.filter_headers[height as usize] {
+                return Err(CbfError::Peer(s!("block filter doesn't match its header")));
+            }
+            if height == state.store.filters_end() {
+                state.store.append_filter(&cfilter.filter)?;
+            }
+            filters.push(cfilter.filter);
+        }
+        Ok(filters)
+    }
+
+    /// Check the block filters against the scripts, finding the TXs that pay to or spend from
+    /// them. Scripts not seen before are checked from the start height, the others only in the
+    /// blocks added since the last scan.
+    fn scan(
+        &self,
+        peer: &mut Peer,
+        state: &mut CbfState,
+        spks: &HashSet<ScriptBuf>,
+    ) -> Result<(), CbfError> {
+        let pending_spks = std::mem::take(&mut state.pending_spks);
+        let new_spks: HashSet<ScriptBuf> = pending_spks
+            .into_iter()
+            .chain(spks.iter().cloned())
+            .filter(|s| !state.spks.contains(s))
+            .collect();
+        let res = self.scan_new_blocks(peer, state, &new_spks);
+        if res.is_err() {
+            state.pending_spks.extend(new_spks);
+        }
+        res
+    }
+
+    fn scan_new_blocks(
+        &self,
+        peer: &mut Peer,
+        state: &mut CbfState,
+        new_spks: &HashSet<ScriptBuf>,
+    ) -> Result<(), CbfError> {
+        let tip_height = state.tip_height();
+        let scanned_height = state.scanned_height;
+        let from_height = match scanned_height {
+            Some(height) if new_spks.is_empty() => height + 1,
+            _ => self.start_height,
+        };
+        let mut all_spks = state.spks.clone();
+        all_spks.extend(new_spks.iter().cloned());
+        let mut batch_start = from_height;
+        while batch_start <= tip_height {
+            let mut batch_end = min(batch_start + MAX_FILTERS_PER_REQUEST - 1, tip_height);
+            // stored filters are read up to the last one, the following ones are downloaded
+            let filters_end = state.store.filters_end();
+            if batch_start < filters_end {
+                batch_end = min(batch_end, filters_end - 1);
+            }
+            let filters = self.get_filters(peer, state, batch_start, batch_end)?;
+            let mut matched_heights = vec![];
+            for (height, filter) in (batch_start..=batch_end).zip(filters) {
+                // already scanned blocks only need to be checked against the new scripts
+                let query = if scanned_height.is_some_and(|h| height <= h) {
+                    new_spks
+                } else {
+                    &all_spks
+                };
+                if query.is_empty() {
+                    continue;
+                }
+                let filter = BlockFilter::new(&filter);
+                let block_hash = state.block_hash(height);
+                if filter.match_any(&block_hash, query.iter().map(|s| s.as_bytes()))? {
+                    matched_heights.push(height);
+                }
+            }
+            for height in matched_heights {
+                let block = peer.get_block(state.block_hash(height))?;
+                state.process_block(height, &block, &all_spks);
+            }
+            batch_start = batch_end + 1;
+        }
+        state.spks = all_spks;
+        state.scanned_height = Some(tip_height);
+        Ok(())
+    }
+
+    pub(crate) fn get_block_hash(&self, height: u32) -> Result<BlockHash, CbfError> {
+        self.with_peer(|_, _, state| {
+            state
+                .headers
+                .get(height as usize)
+                .map(|block| block.hash)
+                .ok_or_else(|| CbfError::Peer(format!("no block at height {height}")))
+        })
+    }
+
+    pub(crate) fn get_tip_height(&self) -> Result<u32, CbfError> {
+        self.with_peer(|_, _, state| Ok(state.tip_height()))
+    }
+
+    pub(crate) fn broadcast(&self, tx: &BdkTransaction) -> Result<(), CbfError> {
+        self.with_peer(|_, peer, state| {
+            // the TX gets no answer, returning confirms it has been received
+            peer.request(NetworkMessage::Tx(tx.clone()), |_| None::<()>)?;
+            state.unconfirmed.insert(
+                tx.compute_txid(),
+                (Arc::new(tx.clone()), now().unix_timestamp() as u64),
+            );
+            Ok(())
+        })
+    }
+
+    /// Look for the provided TX in the next scans, even if it doesn't involve wallet scripts.
+    pub(crate) fn watch_tx(&self, tx: &BdkTransaction) {
+        let mut state = self.state.lock().expect("lock poisoned");
+        let txid = tx.compute_txid();
+        if state.confirmed.contains_key(&txid) || !state.watched_txids.insert(txid) {
+            return;
+        }
+        // OP_RETURN outputs are not included in block filters
+        let spks: Vec<ScriptBuf> = tx
+            .output
+            .iter()
+            .map(|o| o.script_pubkey.clone())
+            .filter(|s| !s.is_op_return() && !state.spks.contains(s))
+            .collect();
+        state.pending_spks.extend(spks);
+    }
+
+    /// Scan the new blocks, then run the provided function with the height of the block including
+    /// the TX with the provided ID (None if unconfirmed). Return None if the TX is unknown.
+    fn with_tx<T>(
+        &self,
+        txid: &Txid,
+        f: impl FnOnce(&CbfState, Option<u32>) -> T,
+    ) -> Result<Option<T>, CbfError> {
+        self.with_peer(|client, peer, state| {
+            client.scan(peer, state, &HashSet::new())?;
+            Ok(state.tx_height(txid).map(|height| f(state, height)))
+        })
+    }
+
+    pub(crate) fn get_tx_confirmations(&self, txid: &Txid) -> Result<Option<u64>, CbfError> {
+        self.with_tx(txid, |state, height| {
+            height.map_or(0, |h| (state.tip_height() + 1).saturating_sub(h) as u64)
+        })
+    }
+
+    pub(crate) fn sync<I>(
+        &self,
+        request: impl Into<SyncRequest<I>>,
+    ) -> Result<SyncResponse, CbfError> {
+        let mut request = request.into();
+        let chain_tip = request.chain_tip();
+        let spks: HashSet<ScriptBuf> = request
+            .iter_spks_with_expected_txids()
+            .map(|s| s.spk)
+            .collect();
+        let txids: Vec<Txid> = request.iter_txids().collect();
+        // TXs spending the requested outpoints are wallet TXs, so they're found via the scripts
+        request.iter_outpoints().for_each(drop);
+
+        self.with_peer(|client, peer, state| {
+            client.scan(peer, state, &spks)?;
+            let mut tx_update = state.tx_update(&spks);
+            // unknown TXs are not evicted, as the mempool of the peers cannot be inspected
+            for txid in txids {
+                if !tx_update.txs.iter().any(|tx| tx.compute_txid() == txid) {
+                    state.add_tx(&mut tx_update, txid);
+                }
+            }
+            let chain_update = match chain_tip {
+                Some(tip) => Some(state.chain_update(tip, &tx_update.anchors)?),
+                None => None,
+            };
+            Ok(SyncResponse {
+                tx_update,
+                chain_update,
+            })
+        })
+    }
+
+    pub(crate) fn full_scan<K: Ord + Clone>(
+        &self,
+        request: impl Into<FullScanRequest<K>>,
+    ) -> Result<FullScanResponse<K>, CbfError> {
+        let mut request = request.into();
+        let chain_tip = request.chain_tip();
+        self.with_peer(|client, peer, state| {
+            let mut spks = HashSet::new();
+            let mut last_active_indices = BTreeMap::new();
+            for keychain in request.keychains() {
+                let mut keychain_spks = request.iter_spks(keychain.clone());
+                loop {
+                    let batch: Vec<(u32, ScriptBuf)> =
+                        keychain_spks.by_ref().take(INDEXER_STOP_GAP).collect();
+                    let Some((last_index, _)) = batch.last().cloned() else {
+                        break;
+                    };
+                    let batch_spks: HashSet<ScriptBuf> =
+                        batch.iter().map(|(_, spk)| spk.clone()).collect();
+                    client.scan(peer, state, &batch_spks)?;
+                    let used_spks: HashSet<&ScriptBuf> = state
+                        .known_txs()
+                        .flat_map(|tx| tx.output.iter().map(|o| &o.script_pubkey))
+                        .filter(|spk| batch_spks.contains(*spk))
+                        .collect();
+                    for (index, spk) in &batch {
+                        if used_spks.contains(spk) {
+                            last_active_indices.insert(keychain.clone(), *index);
+                        }
+                    }
+                    spks.extend(batch_spks);
+                    match last_active_indices.get(&keychain) {
+                        Some(active) if last_index - active < INDEXER_STOP_GAP as u32 => {}
+                        _ => break,
+                    }
+                }
+            }
+            let tx_update = state.tx_update(&spks);
+            let chain_update = match chain_tip {
+                Some(tip) => Some(state.chain_update(tip, &tx_update.anchors)?),
+                None => None,
+            };
+            Ok(FullScanResponse {
+                tx_update,
+                last_active_indices,
+                chain_update,
+            })
+        })
+    }
+}
+
+impl ResolveWitness for CbfClient {
+    fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
+        // the header chain is built on top of the genesis block of the client network
+        let chain_hash =
+            ChainHash::from_genesis_block_hash(genesis_block(self.network).block_hash());
+        if chain_net.chain_hash() != chain_hash {
+            return Err(WitnessResolverError::WrongChainNet);
+        }
+        Ok(())
+    }
+
+    fn resolve_witness(&self, txid: RgbTxid) -> Result<WitnessStatus, WitnessResolverError> {
+        let Some((tx, anchor)) = self
+            .with_tx(&txid, |state, height| {
+                (state.known_tx(&txid), height.map(|h| state.anchor(h)))
+            })
+            .map_err(|e| WitnessResolverError::ResolverIssue(Some(txid), e.to_string()))?
+        else {
+            return Ok(WitnessStatus::Unresolved);
+        };
+        let ord = match anchor {
+            Some(anchor) => {
+                let height = NonZeroU32::new(anchor.block_id.height)
+                    .ok_or(WitnessResolverError::InvalidResolverData)?;
+                WitnessOrd::Mined(
+                    WitnessPos::bitcoin(height, anchor.confirmation_time as i64)
+                        .ok_or(WitnessResolverError::InvalidResolverData)?,
+                )
+            }
+            None => WitnessOrd::Tentative,
+        };
+        let tx = tx.ok_or(WitnessResolverError::InvalidResolverData)?;
+        Ok(WitnessStatus::Resolved((*tx).clone(), ord))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::net::TcpListener;
+
+    use bdk_wallet::bitcoin::{
+        Amount as BtcAmount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxMerkleNode, TxOut,
+        Witness,
+        absolute::LockTime,
+        bip158::{self, FilterHash},
+        block::Version as BlockVersion,
+        p2p::message_filter::CFilter,
+        transaction::Version as TxVersion,
+    };
+
+    #[derive(Clone, Copy, Default, PartialEq)]
+    enum Misbehavior {
+        #[default]
+        None,
+        NoFilters,
+        WrongFilterHeaders,
+        WrongFilters,
+        WrongBlocks,
+    }
+
+    // P2P peer serving the provided chain, which can be replaced to simulate reorgs
+    struct MockPeer {
+        addr: String,
+        chain: Arc<Mutex<Vec<Block>>>,
+    }
+
+    impl MockPeer {
+        fn start(chain: Vec<Block>, misbehavior: Misbehavior) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+            let chain = Arc::new(Mutex::new(chain));
+            let server_chain = chain.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else {
+                        return;
+                    };
+                    let chain = server_chain.clone();
+                    thread::spawn(move || serve(stream, &chain, misbehavior));
+                }
+            });
+            Self { addr, chain }
+        }
+
+        fn set_chain(&self, chain: Vec<Block>) {
+            *self.chain.lock().unwrap() = chain;
+        }
+    }
+
+    fn serve(mut stream: TcpStream, chain: &Mutex<Vec<Block>>, misbehavior: Misbehavior) {
+        let mut reader = bitcoin::io::FromStd::new(BufReader::new(stream.try_clone().unwrap()));
+        // the client closing the connection ends the loop
+        while let Ok(raw) = RawNetworkMessage::consensus_decode(&mut reader) {
+            let chain = chain.lock().unwrap().clone();
+            for message in respond(raw.into_payload(), &chain, misbehavior) {
+                let raw = RawNetworkMessage::new(Network::Regtest.magic(), message);
+                if stream.write_all(&serialize(&raw)).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn respond(
+        message: NetworkMessage,
+        chain: &[Block],
+        misbehavior: Misbehavior,
+    ) -> Vec<NetworkMessage> {
+        let height_of = |hash: &BlockHash| chain.iter().position(|b| b.block_hash() == *hash);
+        match message {
+            NetworkMessage::Version(_) => {
+                let services = if misbehavior == Misbehavior::NoFilters {
+                    ServiceFlags::WITNESS
+                } else {
+                    ServiceFlags::WITNESS | ServiceFlags::COMPACT_FILTERS
+                };
+                let addr = P2pAddress::new(&SocketAddr::from(([0, 0, 0, 0], 0)), services);
+                let version = VersionMessage::new(
+                    services,
+                    0,
+                    addr.clone(),
+                    addr,
+                    0,
+                    s!("/mock/"),
+                    chain.len() as i32 - 1,
+                );
+                vec![NetworkMessage::Version(version), NetworkMessage::Verack]
+            }
+            NetworkMessage::Ping(nonce) => vec![NetworkMessage::Pong(nonce)],
+            NetworkMessage::GetHeaders(request) => {
+                let fork_height = request
+                    .locator_hashes
+                    .iter()
+                    .find_map(height_of)
+                    .unwrap_or_default();
+                let headers = chain[fork_height + 1..]
+                    .iter()
+                    .take(MAX_HEADERS_PER_MESSAGE)
+                    .map(|b| b.header)
+                    .collect();
+                vec![NetworkMessage::Headers(headers)]
+            }
+            NetworkMessage::GetCFHeaders(request) => {
+                let Some(stop_height) = height_of(&request.stop_hash) else {
+                    return vec![];
+                };
+                let start_height = request.start_height as usize;
+                let mut filter_hashes: Vec<FilterHash> = chain[..=stop_height]
+                    .iter()
+                    .map(|b| <FilterHash as Sha256Hash>::hash(&block_filter(b)))
+                    .collect();
+                if misbehavior == Misbehavior::WrongFilterHeaders {
+                    filter_hashes[stop_height] = FilterHash::all_zeros();
+                }
+                let previous_filter_header = filter_hashes[..start_height]
+                    .iter()
+                    .fold(FilterHeader::all_zeros(), |prev, h| h.filter_header(&prev));
+                vec![NetworkMessage::CFHeaders(CFHeaders {
+                    filter_type: BASIC_FILTER_TYPE,
+                    stop_hash: request.stop_hash,
+                    previous_filter_header,
+                    filter_hashes: filter_hashes[start_height..].to_vec(),
+                })]
+            }
+            NetworkMessage::GetCFilters(request) => {
+                let Some(stop_height) = height_of(&request.stop_hash) else {
+                    return vec![];
+                };
+                chain[request.start_height as usize..=stop_height]
+                    .iter()
+                    .map(|b| {
+                        let filter = if misbehavior == Misbehavior::WrongFilters {
+                            // a valid filter matching nothing
+                            vec![0]
+                        } else {
+                            block_filter(b)
+                        };
+                        NetworkMessage::CFilter(CFilter {
+                            filter_type: BASIC_FILTER_TYPE,
+                            block_hash: b.block_hash(),
+                            filter,
+                        })
+                    })
+                    .collect()
+            }
+            NetworkMessage::GetData(inventory) => inventory
+                .into_iter()
+                .map(|inv| match inv {
+                    Inventory::WitnessBlock(hash) if height_of(&hash).is_some() => {
+                        let mut block = chain[height_of(&hash).unwrap()].clone();
+                        if misbehavior == Misbehavior::WrongBlocks {
+                            block.txdata[0].output[0].value = BtcAmount::ZERO;
+                        }
+                        NetworkMessage::Block(block)
+                    }
+                    _ => NetworkMessage::NotFound(vec![inv]),
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn block_filter(block: &Block) -> Vec<u8> {
+        // mock blocks only include a coinbase TX, which spends no scripts
+        BlockFilter::new_script_filter(block, |o| {
+            Err::<ScriptBuf, _>(bip158::Error::UtxoMissing(*o))
+        })
+        .unwrap()
+        .content
+    }
+
+    // Return the provided chain extended with a block for each provided script, whose coinbase TX
+    // pays to it.
+    fn mine(chain: &[Block], scripts: impl IntoIterator<Item = ScriptBuf>) -> Vec<Block> {
+        let mut chain = chain.to_vec();
+        for script_pubkey in scripts {
+            let prev = chain.last().unwrap().header;
+            let height = chain.len() as u32;
+            let coinbase = Transaction {
+                version: TxVersion::TWO,
+                lock_time: LockTime::ZERO,
+                input: vec![TxIn {
+                    previous_output: OutPoint::null(),
+                    script_sig: ScriptBuf::from_bytes(height.to_le_bytes().to_vec()),
+                    sequence: Sequence::MAX,
+                    witness: Witness::new(),
+                }],
+                output: vec![TxOut {
+                    value: BtcAmount::from_sat(5_000_000_000),
+                    script_pubkey,
+                }],
+            };
+            let mut block = Block {
+                header: Header {
+                    version: BlockVersion::TWO,
+                    prev_blockhash: prev.block_hash(),
+                    merkle_root: TxMerkleNode::all_zeros(),
+                    time: prev.time + 1,
+                    bits: prev.bits,
+                    nonce: 0,
+                },
+                txdata: vec![coinbase],
+            };
+            block.header.merkle_root = block.compute_merkle_root().unwrap();
+            while block.header.validate_pow(block.header.target()).is_err() {
+                block.header.nonce += 1;
+            }
+            chain.push(block);
+        }
+        chain
+    }
+
+    fn genesis() -> Vec<Block> {
+        vec![genesis_block(Network::Regtest)]
+    }
+
+    fn script(opcode: u8) -> ScriptBuf {
+        ScriptBuf::from_bytes(vec![opcode])
+    }
+
+    fn client(peers: &[&MockPeer], dir: &TempDir) -> CbfClient {
+        let options = CbfOptions {
+            peers: peers.iter().map(|p| p.addr.clone()).collect(),
+            start_height: 0,
+        };
+        CbfClient::new(&options, Network::Regtest, dir.path()).unwrap()
+    }
+
+    #[test]
+    fn reorg() {
+        let base = mine(&genesis(), [script(0x52), script(0x52)]);
+        let chain = mine(&base, [script(0x51), script(0x52), script(0x52)]);
+        let tx = chain[3].txdata[0].clone();
+        let txid = tx.compute_txid();
+        let peer = MockPeer::start(chain.clone(), Misbehavior::None);
+        let dir = tempfile::tempdir().unwrap();
+        let client = client(&[&peer], &dir);
+
+        // the TX is found in the block paying to the watched script
+        client.watch_tx(&tx);
+        assert_eq!(client.get_tx_confirmations(&txid).unwrap(), Some(3));
+
+        // a fork with less work is ignored
+        peer.set_chain(mine(&base, [script(0x53)]));
+        let result = client.get_tip_height();
+        assert_matches!(result, Err(CbfError::NoPeers));
+        peer.set_chain(chain.clone());
+        assert_eq!(client.get_tip_height().unwrap(), 5);
+        assert_eq!(client.get_tx_confirmations(&txid).unwrap(), Some(3));
+
+        // a fork with more work replaces the blocks, dropping the TXs found in them
+        let fork = mine(&base, std::iter::repeat_n(script(0x53), 4));
+        peer.set_chain(fork.clone());
+        assert_eq!(client.get_tx_confirmations(&txid).unwrap(), None);
+        assert_eq!(client.get_tip_height().unwrap(), 6);
+        assert_eq!(client.get_block_hash(3).unwrap(), fork[3].block_hash());
+
+        // the new chain is the one stored
+        drop(client);
+        let client = self::client(&[&peer], &dir);
+        assert_eq!(client.get_block_hash(3).unwrap(), fork[3].block_hash());
+    }
+
+    #[test]
+    fn invalid_headers() {
+        let chain = mine(&genesis(), std::iter::repeat_n(script(0x52), 3));
+        let honest = MockPeer::start(chain.clone(), Misbehavior::None);
+
+        // header not requiring the expected difficulty
+        let mut invalid_chain = chain.clone();
+        invalid_chain[2].header.bits = CompactTarget::from_consensus(0x1d00ffff);
+        let malicious = MockPeer::start(invalid_chain, Misbehavior::None);
+        let dir = tempfile::tempdir().unwrap();
+        let result = client(&[&malicious], &dir).get_tip_height();
+        assert_matches!(result, Err(CbfError::Peer(ref e)) if e == "invalid header chain");
+
+        // header not meeting its difficulty
+        let mut invalid_chain = chain.clone();
+        let header = &mut invalid_chain[2].header;
+        while header.validate_pow(header.target()).is_ok() {
+            header.nonce += 1;
+        }
+        let malicious = MockPeer::start(invalid_chain, Misbehavior::None);
+        let dir = tempfile::tempdir().unwrap();
+        let result = client(&[&malicious], &dir).get_tip_height();
+        assert_matches!(result, Err(CbfError::Peer(ref e)) if e == "invalid header proof of work");
+
+        // the malicious peer is dropped, the honest one is used
+        let dir = tempfile::tempdir().unwrap();
+        let client = client(&[&malicious, &honest], &dir);
+        assert_eq!(client.get_tip_height().unwrap(), 3);
+        assert_eq!(client.get_block_hash(2).unwrap(), chain[2].block_hash());
+    }
+
+    #[test]
+    fn wrong_filter_headers() {
+        let chain = mine(&genesis(), [script(0x51), script(0x52)]);
+        let honest = MockPeer::start(chain.clone(), Misbehavior::None);
+        let malicious = MockPeer::start(chain.clone(), Misbehavior::WrongFilterHeaders);
+
+        // peers disagreeing on the filter headers make the sync fail, whichever is queried first
+        for peers in [[&honest, &malicious], [&malicious, &honest]] {
+            let dir = tempfile::tempdir().unwrap();
+            let result = client(&peers, &dir).get_tip_height();
+            assert_matches!(
+                result,
+                Err(CbfError::Peer(ref e)) if e == "peers disagree on the block filters"
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_filters() {
+        let chain = mine(&genesis(), [script(0x51), script(0x52)]);
+        let tx = chain[1].txdata[0].clone();
+        let malicious = MockPeer::start(chain, Misbehavior::WrongFilters);
+        let dir = tempfile::tempdir().unwrap();
+        let client = client(&[&malicious], &dir);
+
+        // filters not matching their filter header are rejected instead of hiding the TX
+        client.watch_tx(&tx);
+        let result = client.get_tx_confirmations(&tx.compute_txid());
+        assert_matches!(
+            result,
+            Err(CbfError::Peer(ref e)) if e == "block filter doesn't match its header"
+        );
+    }
+
+    #[test]
+    fn wrong_block() {
+        let chain = mine(&genesis(), [script(0x51)]);
+        let tx = chain[1].txdata[0].clone();
+        let malicious = MockPeer::start(chain.clone(), Misbehavior::WrongBlocks);
+        let dir = tempfile::tempdir().unwrap();
+        let client = client(&[&malicious], &dir);
+
+        // blocks not matching their header are rejected
+        client.watch_tx(&tx);
+        let result = client.get_tx_confirmations(&tx.compute_txid());
+        let expected = format!("block {} doesn't match its header", chain[1].block_hash());
+        assert_matches!(result, Err(CbfError::Peer(ref e)) if *e == expected);
+    }
+
+    #[test]
+    fn no_filters() {
+        let peer = MockPeer::start(genesis(), Misbehavior::NoFilters);
+        let dir = tempfile::tempdir().unwrap();
+        let result = client(&[&peer], &dir).get_tip_height();
+        assert_matches!(result, Err(CbfError::NoPeers));
+    }
+}
@@ -1,10 +1,70 @@
+#[cfg(feature = "bitcoind")]
+pub(crate) mod bitcoind;
+#[cfg(feature = "cbf")]
+pub(crate) mod cbf;
 pub(crate) mod multisig_hub;
 pub(crate) mod proxy;
 pub(crate) mod reject_list;
+pub(crate) mod vss;
 
 use super::*;
 
 const JSON: &str = "application/json";
 const OCTET_STREAM: &str = "application/octet-stream";
-const CONNECT_TIMEOUT: u64 = 10;
-const READ_WRITE_TIMEOUT: u64 = 120;
+
+fn build_rest_client(
+    connect_timeout: u64,
+    timeout: u64,
+    socks5_proxy: Option<&Socks5Proxy>,
+) -> Result<RestClient, reqwest::Error> {
+    let mut builder = RestClient::builder()
+        .connect_timeout(Duration::from_secs(connect_timeout))
+        .timeout(Duration::from_secs(timeout));
+    if let Some(socks5_proxy) = socks5_proxy {
+        builder = builder.proxy(reqwest::Proxy::all(socks5_proxy.url())?);
+    }
+    builder.build()
+}
+
+fn build_policy_client(
+    policy: &NetworkPolicy,
+    socks5_proxy: Option<&Socks5Proxy>,
+) -> Result<RestClient, reqwest::Error> {
+    build_rest_client(
+        policy.connect_timeout_secs,
+        policy.timeout_secs,
+        socks5_proxy,
+    )
+}
+
+/// Send the request built by `request`, retrying on failures as configured by the policy.
+///
+/// Idempotent requests are retried after connection errors, timeouts and server errors, the other
+/// ones only if the connection could not be established.
+fn send_with_retries(
+    policy: &NetworkPolicy,
+    idempotent: bool,
+    request: impl Fn() -> RequestBuilder,
+) -> Result<Response, reqwest::Error> {
+    let mut attempt = 0;
+    loop {
+        let res = request().send();
+        let retry = match &res {
+            Ok(response) => idempotent && response.status().is_server_error(),
+            Err(e) => e.is_connect() || (idempotent && e.is_timeout()),
+        };
+        if !retry || attempt >= policy.max_retries {
+            return res;
+        }
+        thread::sleep(policy.backoff(attempt));
+        attempt += 1;
+    }
+}
+
+/// Build a multipart file part from the provided bytes, so it can be sent more than once.
+fn file_part(bytes: Vec<u8>, file_name: String) -> multipart::Part {
+    multipart::Part::bytes(bytes)
+        .file_name(file_name)
+        .mime_str(OCTET_STREAM)
+        .expect("OCTET_STREAM is a valid MIME type")
+}
@@ -4,6 +4,7 @@ pub(crate) struct MultisigHubClient {
     client: RestClient,
     base_url: String,
     token: String,
+    policy: NetworkPolicy,
 }
 
 pub(crate) enum FileSource {
@@ -51,6 +52,10 @@ pub(crate) enum FileType {
     OperationPsbt,
     ResponsePsbt,
     Fascia,
+    MusigNonces,
+    OperationExpiry,
+    Cancellation,
+    RecoveryPolicy,
 }
 
 impl FileType {
@@ -61,6 +66,10 @@ impl FileType {
             FileType::Media => "file_media",
             FileType::OperationData => "file_operation_data",
             FileType::Fascia => "file_fascia",
+            FileType::MusigNonces => "file_musig_nonces",
+            FileType::OperationExpiry => "file_operation_expiry",
+            FileType::Cancellation => "file_cancellation",
+            FileType::RecoveryPolicy => "file_recovery_policy",
         }
     }
 }
@@ -117,7 +126,7 @@ pub(crate) enum OperationStatus {
     Discarded,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub(crate) enum OperationType {
     CreateUtxos = 1,
     Issuance = 2,
@@ -129,6 +138,12 @@ pub(crate) enum OperationType {
     Burn = 8,
 }
 
+#[cfg(feature = "hub_operation_files")]
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct PostOperationFileRequest {
+    pub(crate) operation_idx: i32,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub(crate) struct PostOperationResponse {
     pub(crate) operation_idx: i32,
@@ -165,15 +180,18 @@ pub(crate) enum UserRoleResponse {
 }
 
 impl MultisigHubClient {
-    pub(crate) fn new(base_url: &str, token: &str) -> Result<Self, Error> {
-        let client = RestClient::builder()
-            .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT))
-            .timeout(Duration::from_secs(READ_WRITE_TIMEOUT))
-            .build()?;
+    pub(crate) fn new(
+        base_url: &str,
+        token: &str,
+        socks5_proxy: Option<&Socks5Proxy>,
+        policy: &NetworkPolicy,
+    ) -> Result<Self, Error> {
+        let client = build_policy_client(policy, socks5_proxy)?;
         Ok(Self {
             client,
             base_url: base_url.to_string(),
             token: token.to_string(),
+            policy: policy.clone(),
         })
     }
 
@@ -200,13 +218,13 @@ impl MultisigHubClient {
     }
 
     pub(crate) fn bump_address_indices(&self, count: u32, internal: bool) -> Result<u32, Error> {
-        let response = self
-            .client
-            .post(format!("{}/bumpaddressindices", self.base_url))
-            .bearer_auth(&self.token)
-            .json(&BumpAddressIndicesRequest { count, internal })
-            .send()
-            .map_err(Self::req_err)?;
+        let response = send_with_retries(&self.policy, false, || {
+            self.client
+                .post(format!("{}/bumpaddressindices", self.base_url))
+                .bearer_auth(&self.token)
+                .json(&BumpAddressIndicesRequest { count, internal })
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -220,12 +238,12 @@ impl MultisigHubClient {
     pub(crate) fn get_current_address_indices(
         &self,
     ) -> Result<GetCurrentAddressIndicesResponse, Error> {
-        let response = self
-            .client
-            .get(format!("{}/getcurrentaddressindices", self.base_url))
-            .bearer_auth(&self.token)
-            .send()
-            .map_err(Self::req_err)?;
+        let response = send_with_retries(&self.policy, true, || {
+            self.client
+                .get(format!("{}/getcurrentaddressindices", self.base_url))
+                .bearer_auth(&self.token)
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -236,15 +254,15 @@ impl MultisigHubClient {
     }
 
     pub(crate) fn get_file(&self, file_id: &str, out_path: impl AsRef<Path>) -> Result<(), Error> {
-        let mut response = self
-            .client
-            .post(format!("{}/getfile", self.base_url))
-            .bearer_auth(&self.token)
-            .json(&GetFileRequest {
-                file_id: file_id.to_string(),
-            })
-            .send()
-            .map_err(Self::req_err)?;
+        let mut response = send_with_retries(&self.policy, true, || {
+            self.client
+                .post(format!("{}/getfile", self.base_url))
+                .bearer_auth(&self.token)
+                .json(&GetFileRequest {
+                    file_id: file_id.to_string(),
+                })
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -260,13 +278,13 @@ impl MultisigHubClient {
         &self,
         operation_idx: i32,
     ) -> Result<Option<OperationResponse>, Error> {
-        let response = self
-            .client
-            .post(format!("{}/getoperationbyidx", self.base_url))
-            .bearer_auth(&self.token)
-            .json(&GetOperationByIdxRequest { operation_idx })
-            .send()
-            .map_err(Self::req_err)?;
+        let response = send_with_retries(&self.policy, true, || {
+            self.client
+                .post(format!("{}/getoperationbyidx", self.base_url))
+                .bearer_auth(&self.token)
+                .json(&GetOperationByIdxRequest { operation_idx })
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -277,12 +295,12 @@ impl MultisigHubClient {
     }
 
     pub(crate) fn info(&self) -> Result<InfoResponse, Error> {
-        let response = self
-            .client
-            .get(format!("{}/info", self.base_url))
-            .bearer_auth(&self.token)
-            .send()
-            .map_err(Self::req_err)?;
+        let response = send_with_retries(&self.policy, true, || {
+            self.client
+                .get(format!("{}/info", self.base_url))
+                .bearer_auth(&self.token)
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -294,13 +312,13 @@ impl MultisigHubClient {
         &self,
         operation_idx: i32,
     ) -> Result<EmptyResponse, Error> {
-        let response = self
-            .client
-            .post(format!("{}/markoperationprocessed", self.base_url))
-            .bearer_auth(&self.token)
-            .json(&MarkOperationProcessedRequest { operation_idx })
-            .send()
-            .map_err(Self::req_err)?;
+        let response = send_with_retries(&self.policy, false, || {
+            self.client
+                .post(format!("{}/markoperationprocessed", self.base_url))
+                .bearer_auth(&self.token)
+                .json(&MarkOperationProcessedRequest { operation_idx })
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -314,30 +332,43 @@ impl MultisigHubClient {
         operation_type: OperationType,
     ) -> Result<PostOperationResponse, Error> {
         let operation_type_bytes = (operation_type as u8).to_le_bytes().to_vec();
-        let operation_type_part = multipart::Part::bytes(operation_type_bytes)
-            .mime_str(OCTET_STREAM)
-            .expect("OCTET_STREAM is a valid MIME type");
-        let mut form = multipart::Form::new().part("operation_type", operation_type_part);
+        // read the files once, the form is rebuilt at each attempt
+        let mut files_data = vec![];
         for (file_type, file_source) in files {
             let field_name = file_type.field_name().to_string();
-            let file_part = match file_source {
-                FileSource::Bytes(bytes) => multipart::Part::bytes(bytes)
-                    .mime_str(OCTET_STREAM)
-                    .expect("OCTET_STREAM is a valid MIME type"),
-                FileSource::Path(path) => multipart::Part::file(path)?
-                    .mime_str(OCTET_STREAM)
-                    .expect("OCTET_STREAM is a valid MIME type"),
+            let file_data = match file_source {
+                FileSource::Bytes(bytes) => (bytes, None),
+                FileSource::Path(path) => {
+                    let file_name = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    (fs::read(path)?, Some(file_name))
+                }
             };
-            form = form.part(field_name, file_part);
+            files_data.push((field_name, file_data));
         }
 
-        let response = self
-            .client
-            .post(format!("{}/postoperation", self.base_url))
-            .bearer_auth(&self.token)
-            .multipart(form)
-            .send()
-            .map_err(Self::req_err)?;
+        let response = send_with_retries(&self.policy, false, || {
+            let operation_type_part = multipart::Part::bytes(operation_type_bytes.clone())
+                .mime_str(OCTET_STREAM)
+                .expect("OCTET_STREAM is a valid MIME type");
+            let mut form = multipart::Form::new().part("operation_type", operation_type_part);
+            for (field_name, (bytes, file_name)) in &files_data {
+                let part = match file_name {
+                    Some(file_name) => file_part(bytes.clone(), file_name.clone()),
+                    None => multipart::Part::bytes(bytes.clone())
+                        .mime_str(OCTET_STREAM)
+                        .expect("OCTET_STREAM is a valid MIME type"),
+                };
+                form = form.part(field_name.clone(), part);
+            }
+            self.client
+                .post(format!("{}/postoperation", self.base_url))
+                .bearer_auth(&self.token)
+                .multipart(form)
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -347,20 +378,63 @@ impl MultisigHubClient {
             .map_err(Self::req_err)
     }
 
+    #[cfg(feature = "hub_operation_files")]
+    pub(crate) fn post_operation_file(
+        &self,
+        operation_idx: i32,
+        file_type: FileType,
+        bytes: Vec<u8>,
+    ) -> Result<EmptyResponse, Error> {
+        let json_payload = serde_json::to_string(&PostOperationFileRequest { operation_idx })
+            .expect("PostOperationFileRequest is serializable");
+
+        let response = send_with_retries(&self.policy, false, || {
+            let file_part = multipart::Part::bytes(bytes.clone())
+                .mime_str(OCTET_STREAM)
+                .expect("OCTET_STREAM is a valid MIME type");
+            let json_part = multipart::Part::text(json_payload.clone())
+                .mime_str(JSON)
+                .expect("JSON is a valid MIME type");
+            let form = multipart::Form::new()
+                .part(file_type.field_name().to_string(), file_part)
+                .part("request", json_part);
+            self.client
+                .post(format!("{}/postoperationfile", self.base_url))
+                .bearer_auth(&self.token)
+                .multipart(form)
+        })
+        .map_err(Self::req_err)?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let res = response.json::<APIErrorBody>().map_err(|e| {
+                // hubs that predate operation files don't know the endpoint
+                if status == StatusCode::NOT_FOUND {
+                    Error::MultisigHubService {
+                        details: s!("the hub doesn't support operation files"),
+                    }
+                } else {
+                    Self::req_err(e)
+                }
+            })?;
+            return Err(Self::map_hub_error(res));
+        }
+        response.json::<EmptyResponse>().map_err(Self::req_err)
+    }
+
     pub(crate) fn get_transfer_status(
         &self,
         batch_transfer_idx: i32,
     ) -> Result<Option<TransferStatusInfo>, Error> {
-        let response = self
-            .client
-            .post(format!("{}/transferstatus", self.base_url))
-            .bearer_auth(&self.token)
-            .json(&TransferStatusRequest {
-                batch_transfer_idx,
-                accept: None,
-            })
-            .send()
-            .map_err(Self::req_err)?;
+        let response = send_with_retries(&self.policy, true, || {
+            self.client
+                .post(format!("{}/transferstatus", self.base_url))
+                .bearer_auth(&self.token)
+                .json(&TransferStatusRequest {
+                    batch_transfer_idx,
+                    accept: None,
+                })
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -376,16 +450,16 @@ impl MultisigHubClient {
         batch_transfer_idx: i32,
         accept: bool,
     ) -> Result<TransferStatusInfo, Error> {
-        let response = self
-            .client
-            .post(format!("{}/transferstatus", self.base_url))
-            .bearer_auth(&self.token)
-            .json(&TransferStatusRequest {
-                batch_transfer_idx,
-                accept: Some(accept),
-            })
-            .send()
-            .map_err(Self::req_err)?;
+        let response = send_with_retries(&self.policy, false, || {
+            self.client
+                .post(format!("{}/transferstatus", self.base_url))
+                .bearer_auth(&self.token)
+                .json(&TransferStatusRequest {
+                    batch_transfer_idx,
+                    accept: Some(accept),
+                })
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -402,39 +476,44 @@ impl MultisigHubClient {
         operation_idx: i32,
         respond_to_operation: RespondToOperation,
     ) -> Result<OperationResponse, Error> {
-        let mut form = multipart::Form::new();
-        let respond_to_operation_request = match respond_to_operation {
+        let (respond_to_operation_request, psbt_bytes) = match respond_to_operation {
             RespondToOperation::Ack(psbt) => {
                 let psbt = Psbt::from_str(&psbt).expect("PSBT already validated by caller");
-                let file_bytes = psbt.serialize();
-                let file_part = multipart::Part::bytes(file_bytes)
-                    .mime_str(OCTET_STREAM)
-                    .expect("OCTET_STREAM is a valid MIME type");
-                form = form.part("file_psbt", file_part);
-                RespondToOperationRequest {
+                let request = RespondToOperationRequest {
                     operation_idx,
                     ack: true,
-                }
+                };
+                (request, Some(psbt.serialize()))
+            }
+            RespondToOperation::Nack => {
+                let request = RespondToOperationRequest {
+                    operation_idx,
+                    ack: false,
+                };
+                (request, None)
             }
-            RespondToOperation::Nack => RespondToOperationRequest {
-                operation_idx,
-                ack: false,
-            },
         };
         let json_payload = serde_json::to_string(&respond_to_operation_request)
             .expect("RespondToOperationRequest is serializable");
-        let json_part = multipart::Part::text(json_payload)
-            .mime_str(JSON)
-            .expect("JSON is a valid MIME type");
-        form = form.part("request", json_part);
-
-        let response = self
-            .client
-            .post(format!("{}/respondtooperation", self.base_url))
-            .bearer_auth(&self.token)
-            .multipart(form)
-            .send()
-            .map_err(Self::req_err)?;
+
+        let response = send_with_retries(&self.policy, false, || {
+            let mut form = multipart::Form::new();
+            if let Some(psbt_bytes) = &psbt_bytes {
+                let file_part = multipart::Part::bytes(psbt_bytes.clone())
+                    .mime_str(OCTET_STREAM)
+                    .expect("OCTET_STREAM is a valid MIME type");
+                form = form.part("file_psbt", file_part);
+            }
+            let json_part = multipart::Part::text(json_payload.clone())
+                .mime_str(JSON)
+                .expect("JSON is a valid MIME type");
+            form = form.part("request", json_part);
+            self.client
+                .post(format!("{}/respondtooperation", self.base_url))
+                .bearer_auth(&self.token)
+                .multipart(form)
+        })
+        .map_err(Self::req_err)?;
         if !response.status().is_success() {
             let res = response.json::<APIErrorBody>().map_err(Self::req_err)?;
             return Err(Self::map_hub_error(res));
@@ -524,13 +603,28 @@ mod tests {
         assert_eq!(FileType::Media.field_name(), "file_media");
         assert_eq!(FileType::OperationData.field_name(), "file_operation_data");
         assert_eq!(FileType::Fascia.field_name(), "file_fascia");
+        assert_eq!(FileType::MusigNonces.field_name(), "file_musig_nonces");
+        assert_eq!(
+            FileType::OperationExpiry.field_name(),
+            "file_operation_expiry"
+        );
+        assert_eq!(FileType::Cancellation.field_name(), "file_cancellation");
+        assert_eq!(
+            FileType::RecoveryPolicy.field_name(),
+            "file_recovery_policy"
+        );
     }
 
     #[test]
     fn bump_address_indices_error() {
         // network error
-        let client: MultisigHubClient =
-            MultisigHubClient::new("http://127.0.0.1:1", "token").unwrap();
+        let client: MultisigHubClient = MultisigHubClient::new(
+            "http://127.0.0.1:1",
+            "token",
+            None,
+            &NetworkPolicy::default(),
+        )
+        .unwrap();
         let result = client.bump_address_indices(1, false).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
 
@@ -542,7 +636,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.bump_address_indices(1, false).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -555,7 +651,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected":"unexpected JSON"}"#)
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.bump_address_indices(1, false).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -569,7 +667,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(serde_json::to_string(&body).unwrap())
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.bump_address_indices(1, false).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -578,8 +678,13 @@ mod tests {
     #[test]
     fn get_current_address_indices_error() {
         // network error
-        let client: MultisigHubClient =
-            MultisigHubClient::new("http://127.0.0.1:1", "token").unwrap();
+        let client: MultisigHubClient = MultisigHubClient::new(
+            "http://127.0.0.1:1",
+            "token",
+            None,
+            &NetworkPolicy::default(),
+        )
+        .unwrap();
         let result = client.get_current_address_indices().unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
 
@@ -591,7 +696,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.get_current_address_indices().unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -605,7 +712,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(serde_json::to_string(&body).unwrap())
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.get_current_address_indices().unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -618,7 +727,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.get_current_address_indices().unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -629,8 +740,13 @@ mod tests {
         let tmp_path = tempfile::NamedTempFile::new().unwrap();
 
         // network error
-        let client: MultisigHubClient =
-            MultisigHubClient::new("http://127.0.0.1:1", "token").unwrap();
+        let client: MultisigHubClient = MultisigHubClient::new(
+            "http://127.0.0.1:1",
+            "token",
+            None,
+            &NetworkPolicy::default(),
+        )
+        .unwrap();
         let result = client.get_file("123", tmp_path.path()).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
 
@@ -643,7 +759,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(serde_json::to_string(&body).unwrap())
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.get_file("123", tmp_path.path()).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -656,7 +774,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected":"unexpected JSON"}"#)
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.get_file("123", tmp_path.path()).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -665,8 +785,13 @@ mod tests {
     #[test]
     fn get_operation_by_idx_error() {
         // network error
-        let client: MultisigHubClient =
-            MultisigHubClient::new("http://127.0.0.1:1", "token").unwrap();
+        let client: MultisigHubClient = MultisigHubClient::new(
+            "http://127.0.0.1:1",
+            "token",
+            None,
+            &NetworkPolicy::default(),
+        )
+        .unwrap();
         let result = client.get_operation_by_idx(1).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
 
@@ -678,7 +803,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.get_operation_by_idx(1).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -691,7 +818,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected":"unexpected JSON"}"#)
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.get_operation_by_idx(1).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -705,7 +834,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(serde_json::to_string(&body).unwrap())
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.get_operation_by_idx(1).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -714,7 +845,13 @@ mod tests {
     #[test]
     fn info_error() {
         // network error
-        let client = MultisigHubClient::new("http://127.0.0.1:1", "token").unwrap();
+        let client = MultisigHubClient::new(
+            "http://127.0.0.1:1",
+            "token",
+            None,
+            &NetworkPolicy::default(),
+        )
+        .unwrap();
         let result = client.info().unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
 
@@ -726,7 +863,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.info().unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -735,7 +874,13 @@ mod tests {
     #[test]
     fn mark_operation_processed_error() {
         // network error
-        let client = MultisigHubClient::new("http://127.0.0.1:1", "token").unwrap();
+        let client = MultisigHubClient::new(
+            "http://127.0.0.1:1",
+            "token",
+            None,
+            &NetworkPolicy::default(),
+        )
+        .unwrap();
         let result = client.mark_operation_processed(1).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
 
@@ -747,7 +892,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client.mark_operation_processed(1).unwrap_err();
         assert_matches!(result, Error::MultisigHubService { .. });
         mock.assert();
@@ -756,8 +903,13 @@ mod tests {
     #[test]
     fn post_operation_error() {
         // network error
-        let client: MultisigHubClient =
-            MultisigHubClient::new("http://127.0.0.1:1", "token").unwrap();
+        let client: MultisigHubClient = MultisigHubClient::new(
+            "http://127.0.0.1:1",
+            "token",
+            None,
+            &NetworkPolicy::default(),
+        )
+        .unwrap();
         let result = client
             .post_operation(vec![], OperationType::CreateUtxos)
             .unwrap_err();
@@ -771,7 +923,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client
             .post_operation(vec![], OperationType::CreateUtxos)
             .unwrap_err();
@@ -779,11 +933,72 @@ mod tests {
         mock.assert();
     }
 
+    #[cfg(feature = "hub_operation_files")]
+    #[test]
+    fn post_operation_file_error() {
+        // network error
+        let client: MultisigHubClient = MultisigHubClient::new(
+            "http://127.0.0.1:1",
+            "token",
+            None,
+            &NetworkPolicy::default(),
+        )
+        .unwrap();
+        let result = client
+            .post_operation_file(1, FileType::MusigNonces, vec![0])
+            .unwrap_err();
+        assert_matches!(result, Error::MultisigHubService { .. });
+
+        // hub error
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/postoperationfile")
+            .with_status(403)
+            .with_header("content-type", JSON)
+            .with_body(
+                serde_json::to_string(&make_api_error(
+                    "CannotRespondToOperation",
+                    "not pending",
+                    403,
+                ))
+                .unwrap(),
+            )
+            .create();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
+        let result = client
+            .post_operation_file(1, FileType::MusigNonces, vec![0])
+            .unwrap_err();
+        assert_matches!(result, Error::MultisigCannotRespondToOperation { details } if details == "not pending");
+        mock.assert();
+
+        // hub without the endpoint
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/postoperationfile")
+            .with_status(404)
+            .create();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
+        let result = client
+            .post_operation_file(1, FileType::MusigNonces, vec![0])
+            .unwrap_err();
+        assert_matches!(result, Error::MultisigHubService { details } if details == "the hub doesn't support operation files");
+        mock.assert();
+    }
+
     #[test]
     fn respond_to_operation_error() {
         // network error
-        let client: MultisigHubClient =
-            MultisigHubClient::new("http://127.0.0.1:1", "token").unwrap();
+        let client: MultisigHubClient = MultisigHubClient::new(
+            "http://127.0.0.1:1",
+            "token",
+            None,
+            &NetworkPolicy::default(),
+        )
+        .unwrap();
         let result = client
             .respond_to_operation(1, RespondToOperation::Nack)
             .unwrap_err();
@@ -797,7 +1012,9 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = MultisigHubClient::new(&server.url(), "test-token").unwrap();
+        let client =
+            MultisigHubClient::new(&server.url(), "test-token", None, &NetworkPolicy::default())
+                .unwrap();
         let result = client
             .respond_to_operation(1, RespondToOperation::Nack)
             .unwrap_err();
@@ -3,6 +3,7 @@ use super::*;
 pub struct ProxyClient {
     client: RestClient,
     base_url: String,
+    policy: NetworkPolicy,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -74,17 +75,28 @@ pub(crate) struct AttachmentIdParam {
 }
 
 impl ProxyClient {
-    pub(crate) fn new(base_url: &str) -> Result<Self, Error> {
-        let client = RestClient::builder()
-            .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT))
-            .timeout(Duration::from_secs(READ_WRITE_TIMEOUT))
-            .build()?;
+    pub(crate) fn new(
+        base_url: &str,
+        socks5_proxy: Option<&Socks5Proxy>,
+        policy: &NetworkPolicy,
+    ) -> Result<Self, Error> {
+        let client = build_policy_client(policy, socks5_proxy)?;
         Ok(Self {
             client,
             base_url: base_url.to_string(),
+            policy: policy.clone(),
         })
     }
 
+    fn read_file<P: AsRef<Path>>(path: P) -> Result<(Vec<u8>, String), Error> {
+        let file_name = path
+            .as_ref()
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Ok((fs::read(path)?, file_name))
+    }
+
     fn req_err(e: impl std::fmt::Display) -> Error {
         Error::Proxy {
             details: e.to_string(),
@@ -98,14 +110,15 @@ impl ProxyClient {
             id: None,
             params: None,
         };
-        self.client
-            .post(&self.base_url)
-            .header(CONTENT_TYPE, JSON)
-            .json(&body)
-            .send()
-            .map_err(Self::req_err)?
-            .json::<JsonRpcResponse<ServerInfoResponse>>()
-            .map_err(Self::req_err)
+        send_with_retries(&self.policy, true, || {
+            self.client
+                .post(&self.base_url)
+                .header(CONTENT_TYPE, JSON)
+                .json(&body)
+        })
+        .map_err(Self::req_err)?
+        .json::<JsonRpcResponse<ServerInfoResponse>>()
+        .map_err(Self::req_err)
     }
 
     pub(crate) fn get_ack(&self, recipient_id: &str) -> Result<JsonRpcResponse<bool>, Error> {
@@ -117,14 +130,15 @@ impl ProxyClient {
                 recipient_id: recipient_id.to_string(),
             }),
         };
-        self.client
-            .post(&self.base_url)
-            .header(CONTENT_TYPE, JSON)
-            .json(&body)
-            .send()
-            .map_err(Self::req_err)?
-            .json::<JsonRpcResponse<bool>>()
-            .map_err(Self::req_err)
+        send_with_retries(&self.policy, true, || {
+            self.client
+                .post(&self.base_url)
+                .header(CONTENT_TYPE, JSON)
+                .json(&body)
+        })
+        .map_err(Self::req_err)?
+        .json::<JsonRpcResponse<bool>>()
+        .map_err(Self::req_err)
     }
 
     pub(crate) fn get_consignment(
@@ -139,14 +153,15 @@ impl ProxyClient {
                 recipient_id: recipient_id.to_string(),
             }),
         };
-        self.client
-            .post(&self.base_url)
-            .header(CONTENT_TYPE, JSON)
-            .json(&body)
-            .send()
-            .map_err(Self::req_err)?
-            .json::<JsonRpcResponse<GetConsignmentResponse>>()
-            .map_err(Self::req_err)
+        send_with_retries(&self.policy, true, || {
+            self.client
+                .post(&self.base_url)
+                .header(CONTENT_TYPE, JSON)
+                .json(&body)
+        })
+        .map_err(Self::req_err)?
+        .json::<JsonRpcResponse<GetConsignmentResponse>>()
+        .map_err(Self::req_err)
     }
 
     pub(crate) fn get_media(&self, attachment_id: &str) -> Result<JsonRpcResponse<String>, Error> {
@@ -158,14 +173,15 @@ impl ProxyClient {
                 attachment_id: attachment_id.to_string(),
             }),
         };
-        self.client
-            .post(&self.base_url)
-            .header(CONTENT_TYPE, JSON)
-            .json(&body)
-            .send()
-            .map_err(Self::req_err)?
-            .json::<JsonRpcResponse<String>>()
-            .map_err(Self::req_err)
+        send_with_retries(&self.policy, true, || {
+            self.client
+                .post(&self.base_url)
+                .header(CONTENT_TYPE, JSON)
+                .json(&body)
+        })
+        .map_err(Self::req_err)?
+        .json::<JsonRpcResponse<String>>()
+        .map_err(Self::req_err)
     }
 
     pub(crate) fn post_ack(
@@ -182,14 +198,16 @@ impl ProxyClient {
                 ack,
             }),
         };
-        self.client
-            .post(&self.base_url)
-            .header(CONTENT_TYPE, JSON)
-            .json(&body)
-            .send()
-            .map_err(Self::req_err)?
-            .json::<JsonRpcResponse<bool>>()
-            .map_err(Self::req_err)
+        // posting the same ACK again is a no-op for the proxy, so the request can be retried
+        send_with_retries(&self.policy, true, || {
+            self.client
+                .post(&self.base_url)
+                .header(CONTENT_TYPE, JSON)
+                .json(&body)
+        })
+        .map_err(Self::req_err)?
+        .json::<JsonRpcResponse<bool>>()
+        .map_err(Self::req_err)
     }
 
     pub(crate) fn post_consignment<P: AsRef<Path>>(
@@ -213,19 +231,21 @@ impl ProxyClient {
             })
             .expect("serializable")
         };
-        let form = multipart::Form::new()
-            .text("method", "consignment.post")
-            .text("jsonrpc", "2.0")
-            .text("id", "null")
-            .text("params", params)
-            .file("file", consignment_path)?;
-        self.client
-            .post(&self.base_url)
-            .multipart(form)
-            .send()
-            .map_err(Self::req_err)?
-            .json::<JsonRpcResponse<bool>>()
-            .map_err(Self::req_err)
+        let (bytes, file_name) = Self::read_file(consignment_path)?;
+        // re-posting the same file for the same recipient ID is a no-op for the proxy (it replies
+        // with a `false` result), so the request can be retried
+        send_with_retries(&self.policy, true, || {
+            let form = multipart::Form::new()
+                .text("method", "consignment.post")
+                .text("jsonrpc", "2.0")
+                .text("id", "null")
+                .text("params", params.clone())
+                .part("file", file_part(bytes.clone(), file_name.clone()));
+            self.client.post(&self.base_url).multipart(form)
+        })
+        .map_err(Self::req_err)?
+        .json::<JsonRpcResponse<bool>>()
+        .map_err(Self::req_err)
     }
 
     pub(crate) fn post_media<P: AsRef<Path>>(
@@ -237,19 +257,20 @@ impl ProxyClient {
             attachment_id: attachment_id.to_string(),
         })
         .expect("serializable");
-        let form = multipart::Form::new()
-            .text("method", "media.post")
-            .text("jsonrpc", "2.0")
-            .text("id", "null")
-            .text("params", params)
-            .file("file", media_path)?;
-        self.client
-            .post(&self.base_url)
-            .multipart(form)
-            .send()
-            .map_err(Self::req_err)?
-            .json::<JsonRpcResponse<bool>>()
-            .map_err(Self::req_err)
+        let (bytes, file_name) = Self::read_file(media_path)?;
+        // media are keyed by their digest, so re-posting the same file is a no-op for the proxy
+        send_with_retries(&self.policy, true, || {
+            let form = multipart::Form::new()
+                .text("method", "media.post")
+                .text("jsonrpc", "2.0")
+                .text("id", "null")
+                .text("params", params.clone())
+                .part("file", file_part(bytes.clone(), file_name.clone()));
+            self.client.post(&self.base_url).multipart(form)
+        })
+        .map_err(Self::req_err)?
+        .json::<JsonRpcResponse<bool>>()
+        .map_err(Self::req_err)
     }
 }
 
@@ -257,10 +278,75 @@ impl ProxyClient {
 mod tests {
     use super::*;
 
+    #[test]
+    fn retry_policy() {
+        // exponential backoff, capped and optionally randomized
+        let policy = NetworkPolicy {
+            initial_backoff_ms: 100,
+            max_backoff_ms: 300,
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(300));
+        assert_eq!(policy.backoff(u8::MAX), Duration::from_millis(300));
+        let policy = NetworkPolicy {
+            jitter: true,
+            ..policy
+        };
+        let backoff = policy.backoff(5);
+        assert!(backoff >= Duration::from_millis(150) && backoff <= Duration::from_millis(300));
+
+        let policy = NetworkPolicy {
+            max_retries: 2,
+            initial_backoff_ms: 1,
+            jitter: false,
+            ..Default::default()
+        };
+
+        // idempotent calls are retried after server errors
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/").with_status(503).expect(3).create();
+        let client = ProxyClient::new(&server.url(), None, &policy).unwrap();
+        let result = client.get_consignment("123").unwrap_err();
+        assert_matches!(result, Error::Proxy { .. });
+        mock.assert();
+
+        // posts are retried as well, since the proxy treats re-posting the same data as a no-op
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/").with_status(503).expect(3).create();
+        let client = ProxyClient::new(&server.url(), None, &policy).unwrap();
+        let result = client.post_ack("123", true).unwrap_err();
+        assert_matches!(result, Error::Proxy { .. });
+        mock.assert();
+
+        // a retried post reporting the file as already uploaded succeeds
+        let tmp_path = tempfile::NamedTempFile::new().unwrap();
+        let mut server = mockito::Server::new();
+        let mock_err = server.mock("POST", "/").with_status(503).expect(1).create();
+        let mock_ok = server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", JSON)
+            .with_body(r#"{"jsonrpc": "2.0", "id": null, "result": false}"#)
+            .expect(1)
+            .create();
+        let client = ProxyClient::new(&server.url(), None, &policy).unwrap();
+        let result = client
+            .post_consignment("123", tmp_path.path(), "123", None)
+            .unwrap();
+        assert_eq!(result.result, Some(false));
+        assert!(result.error.is_none());
+        mock_err.assert();
+        mock_ok.assert();
+    }
+
     #[test]
     fn get_info_error() {
         // network error
-        let client = ProxyClient::new("http://127.0.0.1:1").unwrap();
+        let client =
+            ProxyClient::new("http://127.0.0.1:1", None, &NetworkPolicy::default()).unwrap();
         let result = client.get_info().unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
 
@@ -272,7 +358,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.get_info().unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -285,7 +371,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected": "json"}"#)
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.get_info().unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -294,7 +380,8 @@ mod tests {
     #[test]
     fn get_ack_error() {
         // network error
-        let client = ProxyClient::new("http://127.0.0.1:1").unwrap();
+        let client =
+            ProxyClient::new("http://127.0.0.1:1", None, &NetworkPolicy::default()).unwrap();
         let result = client.get_ack("123").unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
 
@@ -306,7 +393,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.get_ack("123").unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -319,7 +406,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected": "json"}"#)
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.get_ack("123").unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -328,7 +415,8 @@ mod tests {
     #[test]
     fn get_consignment_error() {
         // network error
-        let client = ProxyClient::new("http://127.0.0.1:1").unwrap();
+        let client =
+            ProxyClient::new("http://127.0.0.1:1", None, &NetworkPolicy::default()).unwrap();
         let result = client.get_consignment("123").unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
 
@@ -340,7 +428,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.get_consignment("123").unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -353,7 +441,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected": "json"}"#)
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.get_consignment("123").unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -362,7 +450,8 @@ mod tests {
     #[test]
     fn get_media_error() {
         // network error
-        let client = ProxyClient::new("http://127.0.0.1:1").unwrap();
+        let client =
+            ProxyClient::new("http://127.0.0.1:1", None, &NetworkPolicy::default()).unwrap();
         let result = client.get_media("123").unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
 
@@ -374,7 +463,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.get_media("123").unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -387,7 +476,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected": "json"}"#)
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.get_media("123").unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -396,7 +485,8 @@ mod tests {
     #[test]
     fn post_ack_error() {
         // network error
-        let client = ProxyClient::new("http://127.0.0.1:1").unwrap();
+        let client =
+            ProxyClient::new("http://127.0.0.1:1", None, &NetworkPolicy::default()).unwrap();
         let result = client.post_ack("123", true).unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
 
@@ -408,7 +498,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.post_ack("123", true).unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -421,7 +511,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected": "json"}"#)
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.post_ack("123", true).unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -432,7 +522,8 @@ mod tests {
         let tmp_path = tempfile::NamedTempFile::new().unwrap();
 
         // network error
-        let client = ProxyClient::new("http://127.0.0.1:1").unwrap();
+        let client =
+            ProxyClient::new("http://127.0.0.1:1", None, &NetworkPolicy::default()).unwrap();
         let result = client
             .post_consignment("123", tmp_path.path(), "123", None)
             .unwrap_err();
@@ -446,7 +537,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client
             .post_consignment("123", tmp_path.path(), "123", None)
             .unwrap_err();
@@ -461,7 +552,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected": "json"}"#)
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client
             .post_consignment("123", tmp_path.path(), "123", None)
             .unwrap_err();
@@ -474,7 +565,8 @@ mod tests {
         let tmp_path = tempfile::NamedTempFile::new().unwrap();
 
         // network error
-        let client = ProxyClient::new("http://127.0.0.1:1").unwrap();
+        let client =
+            ProxyClient::new("http://127.0.0.1:1", None, &NetworkPolicy::default()).unwrap();
         let result = client.post_media("123", tmp_path.path()).unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
 
@@ -486,7 +578,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body("not valid json")
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.post_media("123", tmp_path.path()).unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -499,7 +591,7 @@ mod tests {
             .with_header("content-type", JSON)
             .with_body(r#"{"unexpected": "json"}"#)
             .create();
-        let client = ProxyClient::new(&server.url()).unwrap();
+        let client = ProxyClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.post_media("123", tmp_path.path()).unwrap_err();
         assert_matches!(result, Error::Proxy { .. });
         mock.assert();
@@ -3,17 +3,20 @@ use super::*;
 pub struct RejectListClient {
     client: RestClient,
     base_url: String,
+    policy: NetworkPolicy,
 }
 
 impl RejectListClient {
-    pub(crate) fn new(base_url: &str) -> Result<Self, Error> {
-        let client = RestClient::builder()
-            .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT))
-            .timeout(Duration::from_secs(READ_WRITE_TIMEOUT))
-            .build()?;
+    pub(crate) fn new(
+        base_url: &str,
+        socks5_proxy: Option<&Socks5Proxy>,
+        policy: &NetworkPolicy,
+    ) -> Result<Self, Error> {
+        let client = build_policy_client(policy, socks5_proxy)?;
         Ok(Self {
             client,
             base_url: base_url.to_string(),
+            policy: policy.clone(),
         })
     }
 
@@ -24,9 +27,7 @@ impl RejectListClient {
     }
 
     pub(crate) fn get_reject_list(&self) -> Result<String, Error> {
-        self.client
-            .get(&self.base_url)
-            .send()
+        send_with_retries(&self.policy, true, || self.client.get(&self.base_url))
             .map_err(Self::req_err)?
             .text()
             .map_err(Self::req_err)
@@ -40,7 +41,8 @@ mod tests {
     #[test]
     fn get_reject_list_error() {
         // network error
-        let client = RejectListClient::new("http://127.0.0.1:1").unwrap();
+        let client =
+            RejectListClient::new("http://127.0.0.1:1", None, &NetworkPolicy::default()).unwrap();
         let result = client.get_reject_list().unwrap_err();
         assert_matches!(result, Error::RejectListService { .. });
 
@@ -52,7 +54,7 @@ mod tests {
             .with_header("content-length", "100")
             .with_body(&[0xFFu8, 0xFEu8][..])
             .create();
-        let client = RejectListClient::new(&server.url()).unwrap();
+        let client = RejectListClient::new(&server.url(), None, &NetworkPolicy::default()).unwrap();
         let result = client.get_reject_list().unwrap_err();
         assert_matches!(result, Error::RejectListService { .. });
         mock.assert();
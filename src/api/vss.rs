@@ -0,0 +1,237 @@
+use super::*;
+
+/// A [`BackupTarget`] storing the wallet backups in a key/value server exposing a VSS-style JSON
+/// API.
+///
+/// The server is expected to handle the following requests, with values encoded in base64:
+/// - `POST {base_url}/getObject`, with a `{"store_id": <string>, "key": <string>}` body, replying
+///   with a `{"value": {"key": <string>, "version": <number>, "value": <string>}}` body or with a
+///   404 status if no object is stored with the given key
+/// - `POST {base_url}/putObjects`, with a
+///   `{"store_id": <string>, "transaction_items": [{"key": <string>, "version": <number>, "value": <string>}]}`
+///   body, storing each item with its version incremented by one and replying with a 409 status
+///   if an item version doesn't match the one of the stored object (0 if there is none)
+///
+/// When a token is provided, it's sent as a bearer token in every request.
+pub struct VssBackupTarget {
+    client: RestClient,
+    base_url: String,
+    token: Option<String>,
+    policy: NetworkPolicy,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct VssKeyValue {
+    key: String,
+    version: u64,
+    value: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct VssGetObjectRequest {
+    store_id: String,
+    key: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct VssGetObjectResponse {
+    value: VssKeyValue,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct VssPutObjectsRequest {
+    store_id: String,
+    transaction_items: Vec<VssKeyValue>,
+}
+
+impl VssBackupTarget {
+    /// Create a target for the VSS-style server at the provided URL, authenticating with the
+    /// provided token, if any.
+    pub fn new(base_url: &str, token: Option<String>) -> Result<Self, Error> {
+        Self::new_with_network_options(base_url, token, &NetworkOptions::default())
+    }
+
+    /// Create a target like [`VssBackupTarget::new`], connecting to the server as configured by
+    /// the provided [`NetworkOptions`].
+    pub fn new_with_network_options(
+        base_url: &str,
+        token: Option<String>,
+        network_options: &NetworkOptions,
+    ) -> Result<Self, Error> {
+        let socks5_proxy = network_options.socks5_proxy(NetworkService::Vss, None);
+        let policy = &network_options.policy;
+        let client = build_policy_client(policy, socks5_proxy.as_ref())?;
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+            policy: policy.clone(),
+        })
+    }
+
+    fn req_err(e: impl std::fmt::Display) -> Error {
+        Error::RemoteBackup {
+            details: e.to_string(),
+        }
+    }
+
+    fn post<T: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &T,
+        idempotent: bool,
+    ) -> Result<Response, Error> {
+        send_with_retries(&self.policy, idempotent, || {
+            let mut request = self
+                .client
+                .post(format!("{}/{endpoint}", self.base_url))
+                .header(CONTENT_TYPE, JSON)
+                .json(body);
+            if let Some(token) = &self.token {
+                request = request.bearer_auth(token);
+            }
+            request
+        })
+        .map_err(Self::req_err)
+    }
+}
+
+impl BackupTarget for VssBackupTarget {
+    fn get_object(&self, store_id: &str, key: &str) -> Result<Option<VersionedObject>, Error> {
+        let body = VssGetObjectRequest {
+            store_id: store_id.to_string(),
+            key: key.to_string(),
+        };
+        let response = self.post("getObject", &body, true)?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response.error_for_status().map_err(Self::req_err)?;
+        let res = response
+            .json::<VssGetObjectResponse>()
+            .map_err(Self::req_err)?;
+        let value = general_purpose::STANDARD
+            .decode(res.value.value)
+            .map_err(Self::req_err)?;
+        Ok(Some(VersionedObject {
+            key: res.value.key,
+            version: res.value.version,
+            value,
+        }))
+    }
+
+    fn put_object(&self, store_id: &str, object: VersionedObject) -> Result<(), Error> {
+        let body = VssPutObjectsRequest {
+            store_id: store_id.to_string(),
+            transaction_items: vec![VssKeyValue {
+                key: object.key,
+                version: object.version,
+                value: general_purpose::STANDARD.encode(object.value),
+            }],
+        };
+        let response = self.post("putObjects", &body, false)?;
+        if response.status() == StatusCode::CONFLICT {
+            return Err(Error::RemoteBackup {
+                details: format!("version {} is not the stored one", object.version),
+            });
+        }
+        response.error_for_status().map_err(Self::req_err)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_object() {
+        let mut server = mockito::Server::new();
+        let target = VssBackupTarget::new(&server.url(), Some(s!("token"))).unwrap();
+
+        // object stored
+        let mock = server
+            .mock("POST", "/getObject")
+            .match_header("authorization", "Bearer token")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "store_id": "store",
+                "key": "key",
+            })))
+            .with_status(200)
+            .with_body(r#"{"value": {"key": "key", "version": 3, "value": "AQID"}}"#)
+            .create();
+        let object = target.get_object("store", "key").unwrap().unwrap();
+        assert_eq!(
+            object,
+            VersionedObject {
+                key: s!("key"),
+                version: 3,
+                value: vec![1, 2, 3],
+            }
+        );
+        mock.assert();
+        mock.remove();
+
+        // no object stored
+        let mock = server.mock("POST", "/getObject").with_status(404).create();
+        assert!(target.get_object("store", "key").unwrap().is_none());
+        mock.assert();
+    }
+
+    #[test]
+    fn put_object() {
+        let mut server = mockito::Server::new();
+        let target = VssBackupTarget::new(&server.url(), None).unwrap();
+        let object = VersionedObject {
+            key: s!("key"),
+            version: 0,
+            value: vec![1, 2, 3],
+        };
+
+        // success
+        let mock = server
+            .mock("POST", "/putObjects")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "store_id": "store",
+                "transaction_items": [{"key": "key", "version": 0, "value": "AQID"}],
+            })))
+            .with_status(200)
+            .create();
+        target.put_object("store", object.clone()).unwrap();
+        mock.assert();
+        mock.remove();
+
+        // version conflict
+        let mock = server.mock("POST", "/putObjects").with_status(409).create();
+        let result = target.put_object("store", object).unwrap_err();
+        assert_matches!(result, Error::RemoteBackup { .. });
+        mock.assert();
+    }
+
+    #[test]
+    fn request_error() {
+        // network error
+        let target = VssBackupTarget::new("http://127.0.0.1:1", None).unwrap();
+        let result = target.get_object("store", "key").unwrap_err();
+        assert_matches!(result, Error::RemoteBackup { .. });
+
+        // server error
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/getObject").with_status(500).create();
+        let target = VssBackupTarget::new(&server.url(), None).unwrap();
+        let result = target.get_object("store", "key").unwrap_err();
+        assert_matches!(result, Error::RemoteBackup { .. });
+        mock.assert();
+        mock.remove();
+
+        // invalid response
+        let mock = server
+            .mock("POST", "/getObject")
+            .with_status(200)
+            .with_body("not json")
+            .create();
+        let result = target.get_object("store", "key").unwrap_err();
+        assert_matches!(result, Error::RemoteBackup { .. });
+        mock.assert();
+    }
+}
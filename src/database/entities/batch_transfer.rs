@@ -21,8 +21,11 @@ pub struct Model {
     pub created_at: i64,
     pub updated_at: i64,
     pub expiration: Option<i64>,
-    pub min_confirmations: u8,
+    pub min_confirmations: i16,
     pub incoming: bool,
+    pub payment_request_id: Option<String>,
+    pub swap_id: Option<String>,
+    pub sell_order_id: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -35,6 +38,9 @@ pub enum Column {
     Expiration,
     MinConfirmations,
     Incoming,
+    PaymentRequestId,
+    SwapId,
+    SellOrderId,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -66,6 +72,9 @@ impl ColumnTrait for Column {
             Self::Expiration => ColumnType::BigInteger.def().null(),
             Self::MinConfirmations => ColumnType::SmallInteger.def(),
             Self::Incoming => ColumnType::Boolean.def(),
+            Self::PaymentRequestId => ColumnType::String(StringLen::None).def().null(),
+            Self::SwapId => ColumnType::String(StringLen::None).def().null(),
+            Self::SellOrderId => ColumnType::String(StringLen::None).def().null(),
         }
     }
 }
@@ -8,6 +8,8 @@ pub mod backup_info;
 pub mod batch_transfer;
 pub mod coloring;
 pub mod media;
+#[cfg(online)]
+pub mod multisig_operation;
 pub mod pending_witness_script;
 pub mod reserved_txo;
 pub mod token;
@@ -6,6 +6,8 @@ pub use super::backup_info::Entity as BackupInfo;
 pub use super::batch_transfer::Entity as BatchTransfer;
 pub use super::coloring::Entity as Coloring;
 pub use super::media::Entity as Media;
+#[cfg(online)]
+pub use super::multisig_operation::Entity as MultisigOperation;
 pub use super::pending_witness_script::Entity as PendingWitnessScript;
 pub use super::reserved_txo::Entity as ReservedTxo;
 pub use super::token::Entity as Token;
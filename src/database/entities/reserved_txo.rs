@@ -15,8 +15,9 @@ impl EntityName for Entity {
 pub struct Model {
     pub idx: i32,
     pub txid: String,
-    pub vout: u32,
+    pub vout: i64,
     pub reserved_for: Option<i32>,
+    pub operation_idx: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -25,6 +26,7 @@ pub enum Column {
     Txid,
     Vout,
     ReservedFor,
+    OperationIdx,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -52,6 +54,7 @@ impl ColumnTrait for Column {
             Self::Txid => ColumnType::String(StringLen::None).def(),
             Self::Vout => ColumnType::BigInteger.def(),
             Self::ReservedFor => ColumnType::Integer.def().null(),
+            Self::OperationIdx => ColumnType::Integer.def().null(),
         }
     }
 }
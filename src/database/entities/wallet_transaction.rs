@@ -18,6 +18,7 @@ pub struct Model {
     pub idx: i32,
     pub txid: String,
     pub r#type: WalletTransactionType,
+    pub expiration: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -25,6 +26,7 @@ pub enum Column {
     Idx,
     Txid,
     Type,
+    Expiration,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -51,6 +53,7 @@ impl ColumnTrait for Column {
             Self::Idx => ColumnType::Integer.def(),
             Self::Txid => ColumnType::String(StringLen::None).def(),
             Self::Type => ColumnType::SmallInteger.def(),
+            Self::Expiration => ColumnType::BigInteger.def().null(),
         }
     }
 }
@@ -4,7 +4,7 @@ use super::*;
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, EnumIter, DeriveActiveEnum,
 )]
-#[sea_orm(rs_type = "u8", db_type = "TinyUnsigned")]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
 pub enum AssetSchema {
     /// NIA schema
     #[sea_orm(num_value = 1)]
@@ -128,7 +128,7 @@ impl From<AssetSchema> for SchemaId {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
-#[sea_orm(rs_type = "u8", db_type = "TinyUnsigned")]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
 pub enum ColoringType {
     #[sea_orm(num_value = 1)]
     Receive = 1,
@@ -214,9 +214,39 @@ impl Nullable for RecipientTypeFull {
     }
 }
 
+/// The policy applied to the amount received by a pending receive.
+///
+/// The policy is only enforced when the receive requests a specific amount.
+#[derive(
+    Debug, Copy, Clone, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize,
+)]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
+pub enum ReceivePolicy {
+    /// Accept only the exact requested amount
+    #[sea_orm(num_value = 1)]
+    Exact = 1,
+    /// Accept the requested amount or more
+    #[sea_orm(num_value = 2)]
+    AtLeast = 2,
+    /// Accept any amount
+    #[default]
+    #[sea_orm(num_value = 3)]
+    Any = 3,
+}
+
+impl fmt::Display for ReceivePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Exact => write!(f, "exact"),
+            Self::AtLeast => write!(f, "at least"),
+            Self::Any => write!(f, "any"),
+        }
+    }
+}
+
 /// The type of an RGB transport.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
-#[sea_orm(rs_type = "u8", db_type = "TinyUnsigned")]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
 pub enum TransportType {
     /// HTTP(s) JSON-RPC ([specification](https://github.com/RGB-Tools/rgb-http-json-rpc))
     #[sea_orm(num_value = 1)]
@@ -237,7 +267,7 @@ pub enum TransportType {
     Deserialize,
     Serialize,
 )]
-#[sea_orm(rs_type = "u8", db_type = "TinyUnsigned")]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
 pub enum TransferStatus {
     /// Transfer has been initiated (PSBT prepared) but not yet finalized
     #[sea_orm(num_value = 5)]
@@ -282,7 +312,7 @@ impl TransferStatus {
         .contains(self)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn waiting(&self) -> bool {
         [
             TransferStatus::WaitingCounterparty,
@@ -305,7 +335,7 @@ impl TransferStatus {
         self == &TransferStatus::WaitingCounterparty
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn is_fallible(&self) -> bool {
         [
             TransferStatus::Initiated,
@@ -319,7 +349,7 @@ impl TransferStatus {
 
 /// The type of a pending vanilla wallet transaction.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
-#[sea_orm(rs_type = "u8", db_type = "TinyUnsigned")]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
 pub enum WalletTransactionType {
     /// Transaction used to create UTXOs
     #[sea_orm(num_value = 1)]
@@ -330,6 +360,47 @@ pub enum WalletTransactionType {
     /// Transaction used to perform a BTC send
     #[sea_orm(num_value = 3)]
     SendBtc = 3,
+    /// Child transaction used to accelerate a pending transaction (CPFP)
+    #[sea_orm(num_value = 4)]
+    Cpfp = 4,
+    /// Swap offer transaction, reserving the maker's UTXOs until the offer is completed, aborted
+    /// or expires
+    #[sea_orm(num_value = 5)]
+    SwapOffer = 5,
+}
+
+/// The local state of a multisig operation following the last processed one.
+#[cfg(online)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
+pub enum MultisigOperationState {
+    /// Operation waiting for responses, its inputs are reserved
+    #[sea_orm(num_value = 1)]
+    Pending = 1,
+    /// Pending operation whose inputs have been spent, to be discarded
+    #[sea_orm(num_value = 2)]
+    Conflicting = 2,
+    /// Operation processed while a previous one is still pending
+    #[sea_orm(num_value = 3)]
+    Processed = 3,
+    /// Pending operation cancelled by its initiator, to be discarded
+    #[sea_orm(num_value = 4)]
+    Cancelled = 4,
+    /// Pending operation past its expiry, to be discarded
+    #[sea_orm(num_value = 5)]
+    Expired = 5,
+}
+
+#[cfg(online)]
+impl MultisigOperationState {
+    pub(crate) fn discarded(&self) -> bool {
+        matches!(
+            self,
+            MultisigOperationState::Conflicting
+                | MultisigOperationState::Cancelled
+                | MultisigOperationState::Expired
+        )
+    }
 }
 
 /// An RGB assignment.
@@ -345,6 +416,17 @@ pub enum Assignment {
     Any,
 }
 
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fungible(amt) => write!(f, "{amt}"),
+            Self::NonFungible => write!(f, "a non-fungible token"),
+            Self::InflationRight(amt) => write!(f, "{amt} inflation rights"),
+            Self::Any => write!(f, "any assignment"),
+        }
+    }
+}
+
 impl Assignment {
     pub(crate) fn from_opout_and_state(opout: Opout, state: &AllocatedState) -> Self {
         match state {
@@ -357,7 +439,15 @@ impl Assignment {
         }
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    pub(crate) fn requests_amount(&self) -> bool {
+        match self {
+            Self::Fungible(amt) | Self::InflationRight(amt) => *amt > 0,
+            Self::NonFungible => true,
+            Self::Any => false,
+        }
+    }
+
+    #[cfg(online)]
     pub(crate) fn add_to_assignments(&self, assignments: &mut AssignmentsCollection) {
         match self {
             Self::Fungible(amt) => {
@@ -387,7 +477,7 @@ impl Assignment {
         }
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn inflation_amount(&self) -> u64 {
         if let Self::InflationRight(amt) = self {
             *amt
@@ -687,7 +777,7 @@ mod tests {
         assert!(!TransferStatus::Failed.waiting_counterparty());
         assert!(!TransferStatus::WaitingBroadcast.waiting_counterparty());
 
-        #[cfg(any(feature = "electrum", feature = "esplora"))]
+        #[cfg(online)]
         {
             assert!(TransferStatus::WaitingCounterparty.waiting());
             assert!(TransferStatus::WaitingConfirmations.waiting());
@@ -698,4 +788,14 @@ mod tests {
             assert!(!TransferStatus::Failed.waiting());
         }
     }
+
+    #[cfg(online)]
+    #[test]
+    fn test_multisig_operation_state_methods() {
+        assert!(!MultisigOperationState::Pending.discarded());
+        assert!(MultisigOperationState::Conflicting.discarded());
+        assert!(!MultisigOperationState::Processed.discarded());
+        assert!(MultisigOperationState::Cancelled.discarded());
+        assert!(MultisigOperationState::Expired.discarded());
+    }
 }
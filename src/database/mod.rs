@@ -6,11 +6,11 @@ use crate::database::entities::{
     asset, coloring, media, prelude::*, transfer_transport_endpoint, transport_endpoint, txo,
     wallet_transaction,
 };
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 use crate::database::entities::{batch_transfer, pending_witness_script, reserved_txo};
 
 #[derive(Debug, Clone)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct DbAssetTransferData {
     pub(crate) asset_transfer: DbAssetTransfer,
     pub(crate) transfers: Vec<DbTransfer>,
@@ -28,7 +28,7 @@ impl DbBatchTransfer {
             .collect()
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn get_transfers(
         &self,
         asset_transfers: &[DbAssetTransfer],
@@ -52,7 +52,7 @@ impl DbBatchTransfer {
         })
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn get_incoming_transfer(
         &self,
         asset_transfers: &[DbAssetTransfer],
@@ -74,7 +74,7 @@ impl DbBatchTransfer {
         self.status.failed()
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn waiting(&self) -> bool {
         self.status.waiting()
     }
@@ -83,14 +83,14 @@ impl DbBatchTransfer {
         self.status.waiting_confirmations()
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn is_fallible(&self) -> bool {
         self.status.is_fallible()
     }
 }
 
 #[derive(Debug, Clone)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct DbBatchTransferData {
     pub(crate) asset_transfers_data: Vec<DbAssetTransferData>,
 }
@@ -132,7 +132,7 @@ impl DbTransfer {
         (asset_transfer.clone(), batch_transfer.clone())
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn uses_out_of_band_exchange(&self) -> bool {
         self.invoice_string
             .as_deref()
@@ -146,7 +146,7 @@ impl DbTxo {
     pub(crate) fn outpoint(&self) -> Outpoint {
         Outpoint {
             txid: self.txid.to_string(),
-            vout: self.vout,
+            vout: self.vout as u32,
         }
     }
 
@@ -202,7 +202,7 @@ impl From<LocalOutput> for DbTxoActMod {
         DbTxoActMod {
             idx: ActiveValue::NotSet,
             txid: ActiveValue::Set(x.outpoint.txid.to_string()),
-            vout: ActiveValue::Set(x.outpoint.vout),
+            vout: ActiveValue::Set(x.outpoint.vout.into()),
             btc_amount: ActiveValue::Set(x.txout.value.to_sat().to_string()),
             spent: ActiveValue::Set(x.is_spent),
             exists: ActiveValue::Set(true),
@@ -211,24 +211,125 @@ impl From<LocalOutput> for DbTxoActMod {
     }
 }
 
+// Serializes DB transactions across threads, so a background task never interleaves with an
+// operation in progress. Transactions nested on the owning thread are allowed.
+#[derive(Default)]
+struct OperationLock {
+    owner: Mutex<Option<(ThreadId, usize)>>,
+    released: Condvar,
+}
+
+impl OperationLock {
+    fn acquire(self: &Arc<Self>) -> OperationGuard {
+        let current = thread::current().id();
+        let mut owner = self.owner.lock().expect("lock poisoned");
+        loop {
+            match owner.as_mut() {
+                None => {
+                    *owner = Some((current, 1));
+                    break;
+                }
+                Some((thread_id, count)) if *thread_id == current => {
+                    *count += 1;
+                    break;
+                }
+                Some(_) => owner = self.released.wait(owner).expect("lock poisoned"),
+            }
+        }
+        OperationGuard(self.clone())
+    }
+
+    fn is_held_by_current_thread(&self) -> bool {
+        let owner = self.owner.lock().expect("lock poisoned");
+        matches!(owner.as_ref(), Some((thread_id, _)) if *thread_id == thread::current().id())
+    }
+}
+
+pub(crate) struct OperationGuard(Arc<OperationLock>);
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        let mut owner = self.0.owner.lock().expect("lock poisoned");
+        if let Some((_, count)) = owner.as_mut() {
+            *count -= 1;
+            if *count == 0 {
+                *owner = None;
+                self.0.released.notify_all();
+            }
+        }
+    }
+}
+
 pub struct RgbLibDatabase {
     connection: DatabaseConnection,
+    event_listener: RwLock<Option<Arc<dyn WalletEventListener>>>,
+    remote_backup: RwLock<Option<Arc<RemoteBackup>>>,
+    operation_lock: Arc<OperationLock>,
 }
 
 impl RgbLibDatabase {
     pub(crate) fn new(connection: DatabaseConnection) -> Self {
-        Self { connection }
+        Self {
+            connection,
+            event_listener: RwLock::new(None),
+            remote_backup: RwLock::new(None),
+            operation_lock: Arc::new(OperationLock::default()),
+        }
+    }
+
+    pub(crate) fn set_event_listener(&self, listener: Option<Arc<dyn WalletEventListener>>) {
+        *self.event_listener.write().expect("lock poisoned") = listener;
+    }
+
+    pub(crate) fn set_remote_backup(&self, remote_backup: Option<Arc<RemoteBackup>>) {
+        *self.remote_backup.write().expect("lock poisoned") = remote_backup;
+    }
+
+    pub(crate) fn remote_backup(&self) -> Option<Arc<RemoteBackup>> {
+        self.remote_backup.read().expect("lock poisoned").clone()
+    }
+
+    // Keep other threads from operating on the database until the returned guard is dropped.
+    pub(crate) fn lock_operations(&self) -> OperationGuard {
+        self.operation_lock.acquire()
+    }
+
+    // Whether the current thread is in the middle of an operation.
+    pub(crate) fn in_operation(&self) -> bool {
+        self.operation_lock.is_held_by_current_thread()
+    }
+
+    #[cfg(online)]
+    pub(crate) fn dispatch_event(&self, event: WalletEvent) {
+        let listener = self.event_listener.read().expect("lock poisoned").clone();
+        if let Some(listener) = listener {
+            listener.on_event(event);
+        }
     }
 
     pub(crate) fn begin_transaction(&self) -> Result<DbTxn, Error> {
+        let operation_guard = self.operation_lock.acquire();
         Ok(DbTxn {
             txn: Some(block_on(self.connection.begin())?),
+            operation_guard: Some(operation_guard),
+            event_listener: self.event_listener.read().expect("lock poisoned").clone(),
+            events: RefCell::new(vec![]),
+            remote_backup: self.remote_backup(),
+            backup_outdated: Cell::new(false),
         })
     }
 }
 
 pub struct DbTxn {
     txn: Option<DatabaseTransaction>,
+    // events are only delivered once the changes producing them have been committed
+    event_listener: Option<Arc<dyn WalletEventListener>>,
+    events: RefCell<Vec<WalletEvent>>,
+    // the remote backup is pushed once the changes outdating it have been committed
+    remote_backup: Option<Arc<RemoteBackup>>,
+    backup_outdated: Cell<bool>,
+    // released after the DB transaction has been committed or rolled back
+    operation_guard: Option<OperationGuard>,
 }
 
 impl Drop for DbTxn {
@@ -248,7 +349,33 @@ impl DbTxn {
 
     pub(crate) fn commit(mut self) -> Result<(), Error> {
         let txn = self.txn.take().expect("txn already consumed");
-        Ok(block_on(txn.commit())?)
+        block_on(txn.commit())?;
+        if let Some(remote_backup) = &self.remote_backup
+            && self.backup_outdated.get()
+        {
+            remote_backup.mark_pending();
+        }
+        // let other operations proceed while the listener handles the events
+        self.operation_guard.take();
+        if let Some(listener) = &self.event_listener {
+            for event in self.events.take() {
+                listener.on_event(event);
+            }
+        }
+        if let Some(remote_backup) = &self.remote_backup {
+            remote_backup.push_pending();
+        }
+        Ok(())
+    }
+
+    pub(crate) fn mark_backup_outdated(&self) {
+        self.backup_outdated.set(true);
+    }
+
+    pub(crate) fn emit_event(&self, event: WalletEvent) {
+        if self.event_listener.is_some() {
+            self.events.borrow_mut().push(event);
+        }
     }
 
     pub(crate) fn set_asset(&self, asset: DbAssetActMod) -> Result<i32, Error> {
@@ -274,7 +401,14 @@ impl DbTxn {
         mut batch_transfer: DbBatchTransferActMod,
     ) -> Result<i32, Error> {
         batch_transfer.updated_at = batch_transfer.created_at.clone();
+        let status = batch_transfer.status.clone();
         let res = block_on(BatchTransfer::insert(batch_transfer).exec(self.inner()))?;
+        if let ActiveValue::Set(status) = status {
+            self.emit_event(WalletEvent::TransferStatusChanged {
+                batch_transfer_idx: res.last_insert_id,
+                status,
+            });
+        }
         Ok(res.last_insert_id)
     }
 
@@ -288,6 +422,15 @@ impl DbTxn {
         Ok(res.last_insert_id)
     }
 
+    #[cfg(online)]
+    pub(crate) fn set_multisig_operation(
+        &self,
+        multisig_operation: DbMultisigOperationActMod,
+    ) -> Result<i32, Error> {
+        let res = block_on(MultisigOperation::insert(multisig_operation).exec(self.inner()))?;
+        Ok(res.last_insert_id)
+    }
+
     pub(crate) fn set_pending_witness_script(
         &self,
         pending_witness_script: DbPendingWitnessScriptActMod,
@@ -297,7 +440,7 @@ impl DbTxn {
         Ok(res.last_insert_id)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn set_reserved_txos(
         &self,
         reserved_txos: Vec<DbReservedTxoActMod>,
@@ -339,7 +482,7 @@ impl DbTxn {
         Ok(res.last_insert_id)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn set_txo(&self, txo: DbTxoActMod) -> Result<i32, Error> {
         let mut on_conflict =
             sea_query::OnConflict::columns([txo::Column::Txid, txo::Column::Vout]);
@@ -370,7 +513,7 @@ impl DbTxn {
                 // insert skipped due to ON CONFLICT DO NOTHING -> fetch existing record's idx
                 let existing = self.get_txo(&Outpoint {
                     txid: txo.txid.unwrap(),
-                    vout: txo.vout.unwrap(),
+                    vout: txo.vout.unwrap() as u32,
                 })?;
                 match existing {
                     Some(record) => record.idx,
@@ -384,7 +527,7 @@ impl DbTxn {
         Ok(idx)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn set_wallet_transaction(
         &self,
         wallet_transaction: DbWalletTransactionActMod,
@@ -393,7 +536,7 @@ impl DbTxn {
         Ok(res.last_insert_id)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn update_transfer(
         &self,
         transfer: &mut DbTransferActMod,
@@ -403,12 +546,12 @@ impl DbTxn {
         )?)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn update_asset(&self, asset: &mut DbAssetActMod) -> Result<DbAsset, Error> {
         Ok(block_on(Asset::update(asset.clone()).exec(self.inner()))?)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn update_asset_transfer(
         &self,
         asset_transfer: &mut DbAssetTransferActMod,
@@ -427,19 +570,41 @@ impl DbTxn {
         )?)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
     pub(crate) fn update_batch_transfer(
         &self,
         batch_transfer: &mut DbBatchTransferActMod,
     ) -> Result<DbBatchTransfer, Error> {
         let now = now().unix_timestamp();
         batch_transfer.updated_at = ActiveValue::Set(now);
-        Ok(block_on(
-            BatchTransfer::update(batch_transfer.clone()).exec(self.inner()),
-        )?)
+        // the previous status is needed only to notify a change to the event listener
+        let prev_status = match (&batch_transfer.status, &batch_transfer.idx) {
+            (ActiveValue::Set(_), ActiveValue::Set(idx) | ActiveValue::Unchanged(idx))
+                if self.event_listener.is_some() =>
+            {
+                block_on(BatchTransfer::find_by_id(*idx).one(self.inner()))?.map(|bt| bt.status)
+            }
+            _ => None,
+        };
+        let updated = block_on(BatchTransfer::update(batch_transfer.clone()).exec(self.inner()))?;
+        if prev_status.is_some_and(|s| s != updated.status) {
+            self.emit_event(WalletEvent::TransferStatusChanged {
+                batch_transfer_idx: updated.idx,
+                status: updated.status,
+            });
+        }
+        Ok(updated)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
+    pub(crate) fn update_multisig_operation(
+        &self,
+        multisig_operation: DbMultisigOperationActMod,
+    ) -> Result<(), Error> {
+        block_on(MultisigOperation::update(multisig_operation).exec(self.inner()))?;
+        Ok(())
+    }
+
+    #[cfg(online)]
     pub(crate) fn update_transfer_transport_endpoint(
         &self,
         transfer_transport_endpoint: &mut DbTransferTransportEndpointActMod,
@@ -450,7 +615,21 @@ impl DbTxn {
         )?)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
+    pub(crate) fn update_colorings_txo(&self, txo_idx: i32, new_txo_idx: i32) -> Result<(), Error> {
+        block_on(
+            Coloring::update_many()
+                .col_expr(
+                    coloring::Column::TxoIdx,
+                    sea_query::Expr::value(new_txo_idx),
+                )
+                .filter(coloring::Column::TxoIdx.eq(txo_idx))
+                .exec(self.inner()),
+        )?;
+        Ok(())
+    }
+
+    #[cfg(online)]
     pub(crate) fn update_txo(&self, txo: DbTxoActMod) -> Result<(), Error> {
         block_on(Txo::update(txo).exec(self.inner()))?;
         Ok(())
@@ -475,7 +654,13 @@ impl DbTxn {
         Ok(())
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
+    pub(crate) fn del_multisig_operation(&self, idx: i32) -> Result<(), Error> {
+        block_on(MultisigOperation::delete_by_id(idx).exec(self.inner()))?;
+        Ok(())
+    }
+
+    #[cfg(online)]
     pub(crate) fn del_pending_witness_script(&self, script: String) -> Result<(), Error> {
         block_on(
             PendingWitnessScript::delete_many()
@@ -485,7 +670,7 @@ impl DbTxn {
         Ok(())
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn del_reserved_txos(&self, reserved_txos: &[DbReservedTxo]) -> Result<(), Error> {
         let idxs = reserved_txos.iter().map(|r| r.idx).collect::<Vec<_>>();
         block_on(
@@ -506,6 +691,15 @@ impl DbTxn {
         Ok(())
     }
 
+    pub(crate) fn del_expired_wallet_transactions(&self, now: i64) -> Result<(), Error> {
+        block_on(
+            WalletTransaction::delete_many()
+                .filter(wallet_transaction::Column::Expiration.lte(now))
+                .exec(self.inner()),
+        )?;
+        Ok(())
+    }
+
     pub(crate) fn get_asset(&self, asset_id: String) -> Result<Option<DbAsset>, Error> {
         Ok(block_on(
             Asset::find()
@@ -518,7 +712,7 @@ impl DbTxn {
         Ok(block_on(BackupInfo::find().one(self.inner()))?)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn get_batch_transfer_by_txid(
         &self,
         txid: &str,
@@ -530,6 +724,42 @@ impl DbTxn {
         )?)
     }
 
+    #[cfg(online)]
+    pub(crate) fn get_batch_transfers_by_payment_request_id(
+        &self,
+        payment_request_id: &str,
+    ) -> Result<Vec<DbBatchTransfer>, Error> {
+        Ok(block_on(
+            BatchTransfer::find()
+                .filter(batch_transfer::Column::PaymentRequestId.eq(payment_request_id))
+                .all(self.inner()),
+        )?)
+    }
+
+    #[cfg(online)]
+    pub(crate) fn get_batch_transfers_by_swap_id(
+        &self,
+        swap_id: &str,
+    ) -> Result<Vec<DbBatchTransfer>, Error> {
+        Ok(block_on(
+            BatchTransfer::find()
+                .filter(batch_transfer::Column::SwapId.eq(swap_id))
+                .all(self.inner()),
+        )?)
+    }
+
+    #[cfg(online)]
+    pub(crate) fn get_batch_transfers_by_sell_order_id(
+        &self,
+        sell_order_id: &str,
+    ) -> Result<Vec<DbBatchTransfer>, Error> {
+        Ok(block_on(
+            BatchTransfer::find()
+                .filter(batch_transfer::Column::SellOrderId.eq(sell_order_id))
+                .all(self.inner()),
+        )?)
+    }
+
     pub(crate) fn get_media(&self, media_idx: i32) -> Result<Option<DbMedia>, Error> {
         Ok(block_on(Media::find_by_id(media_idx).one(self.inner()))?)
     }
@@ -557,7 +787,7 @@ impl DbTxn {
         Ok(block_on(
             Txo::find()
                 .filter(txo::Column::Txid.eq(outpoint.txid.clone()))
-                .filter(txo::Column::Vout.eq(outpoint.vout))
+                .filter(txo::Column::Vout.eq(i64::from(outpoint.vout)))
                 .one(self.inner()),
         )?)
     }
@@ -607,7 +837,12 @@ impl DbTxn {
         Ok(block_on(Media::find().all(self.inner()))?)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
+    pub(crate) fn iter_multisig_operations(&self) -> Result<Vec<DbMultisigOperation>, Error> {
+        Ok(block_on(MultisigOperation::find().all(self.inner()))?)
+    }
+
+    #[cfg(online)]
     pub(crate) fn iter_pending_witness_scripts(
         &self,
     ) -> Result<Vec<DbPendingWitnessScript>, Error> {
@@ -804,7 +1039,7 @@ impl DbTxn {
         })
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn get_asset_ids(&self) -> Result<Vec<String>, Error> {
         Ok(self.iter_assets()?.into_iter().map(|a| a.id).collect())
     }
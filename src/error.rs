@@ -56,6 +56,27 @@ pub enum Error {
     #[error("Pending vanilla TX cannot be aborted")]
     CannotAbortPendingVanillaTx,
 
+    /// The fee of the requested transfer cannot be bumped
+    #[error("Cannot bump fee: {details}")]
+    CannotBumpFee {
+        /// Error details
+        details: String,
+    },
+
+    /// The requested TX cannot be accelerated with a CPFP child
+    #[error("Cannot CPFP: {details}")]
+    CannotCpfp {
+        /// Error details
+        details: String,
+    },
+
+    /// The wallet allocations cannot be consolidated
+    #[error("Cannot consolidate: {details}")]
+    CannotConsolidate {
+        /// Error details
+        details: String,
+    },
+
     /// Requested batch transfer cannot be deleted
     #[error("Batch transfer cannot be deleted")]
     CannotDeleteBatchTransfer,
@@ -72,6 +93,13 @@ pub enum Error {
     #[error("The given PSBT cannot be finalized")]
     CannotFinalizePsbt,
 
+    /// The wallet cannot be migrated
+    #[error("Cannot migrate: {details}")]
+    CannotMigrate {
+        /// Error details
+        details: String,
+    },
+
     /// Cannot use IFA schema on mainnet
     #[error("Cannot use IFA schema on mainnet")]
     CannotUseIfaOnMainnet,
@@ -160,7 +188,7 @@ pub enum Error {
     InsufficientAllocationSlots,
 
     /// There are not enough assignments of the requested asset to fulfill the request
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     #[error("Insufficient total assignments for asset: {asset_id}")]
     InsufficientAssignments {
         /// Asset ID
@@ -207,6 +235,13 @@ pub enum Error {
         details: String,
     },
 
+    /// The provided backups don't form a valid chain
+    #[error("Invalid backup chain: {details}")]
+    InvalidBackupChain {
+        /// Error details
+        details: String,
+    },
+
     /// Keys derived from the provided data do not match
     #[error("Invalid bitcoin keys")]
     InvalidBitcoinKeys,
@@ -218,6 +253,13 @@ pub enum Error {
         network: String,
     },
 
+    /// The provided coin selection options are invalid
+    #[error("Invalid coin selection: {details}")]
+    InvalidCoinSelection {
+        /// Error details
+        details: String,
+    },
+
     /// The provided coloring info is invalid
     #[error("Invalid coloring info")]
     InvalidColoringInfo {
@@ -318,6 +360,13 @@ pub enum Error {
         details: String,
     },
 
+    /// The provided payment request is invalid
+    #[error("Invalid payment request: {details}")]
+    InvalidPaymentRequest {
+        /// Error details
+        details: String,
+    },
+
     /// The provided asset precision is invalid
     #[error("Invalid precision: {details}")]
     InvalidPrecision {
@@ -332,6 +381,13 @@ pub enum Error {
         version: String,
     },
 
+    /// The provided multisig operation package is invalid
+    #[error("Invalid operation package: {details}")]
+    InvalidOperationPackage {
+        /// Error details
+        details: String,
+    },
+
     /// The provided PSBT is invalid
     #[error("Invalid PSBT: {details}")]
     InvalidPsbt {
@@ -346,6 +402,13 @@ pub enum Error {
         details: String,
     },
 
+    /// The provided receive policy cannot be applied to the receive
+    #[error("Invalid receive policy: {details}")]
+    InvalidReceivePolicy {
+        /// Error details
+        details: String,
+    },
+
     /// The provided recipient data is invalid
     #[error("The provided recipient data is invalid: {details}")]
     InvalidRecipientData {
@@ -361,6 +424,13 @@ pub enum Error {
     #[error("The provided recipient map is invalid")]
     InvalidRecipientMap,
 
+    /// The provided multisig recovery policy is invalid
+    #[error("Invalid recovery policy: {details}")]
+    InvalidRecoveryPolicy {
+        /// Error details
+        details: String,
+    },
+
     /// The provided recipient ID is for a different network than the wallet's one
     #[error("The provided recipient ID is for a different network than the wallet's one")]
     InvalidRecipientNetwork,
@@ -372,6 +442,20 @@ pub enum Error {
         details: String,
     },
 
+    /// The provided sell order is invalid or cannot be filled
+    #[error("Invalid sell order: {details}")]
+    InvalidSellOrder {
+        /// Error details
+        details: String,
+    },
+
+    /// The provided swap offer is invalid or cannot be used at its current stage
+    #[error("Invalid swap offer: {details}")]
+    InvalidSwapOffer {
+        /// Error details
+        details: String,
+    },
+
     /// The provided asset ticker is invalid
     #[error("Invalid ticker: {details}")]
     InvalidTicker {
@@ -422,6 +506,13 @@ pub enum Error {
         txid: String,
     },
 
+    /// The requested API is not available in the multisig coordination mode of the wallet
+    #[error("Unsupported in the current multisig coordination mode: {details}")]
+    MultisigCoordinationMode {
+        /// Error details
+        details: String,
+    },
+
     /// Multisig hub service error
     #[error("Multisig hub service error: {details}")]
     MultisigHubService {
@@ -429,6 +520,13 @@ pub enum Error {
         details: String,
     },
 
+    /// Cannot cancel operation
+    #[error("Cannot cancel operation: {details}")]
+    MultisigCannotCancelOperation {
+        /// Error details
+        details: String,
+    },
+
     /// Cannot mark operation as processed
     #[error("Cannot mark operation as processed: {details}")]
     MultisigCannotMarkOperationProcessed {
@@ -469,6 +567,13 @@ pub enum Error {
     #[error("User is not a cosigner")]
     MultisigUserNotCosigner,
 
+    /// A MuSig2 operation failed
+    #[error("MuSig2 error: {details}")]
+    Musig {
+        /// Error details
+        details: String,
+    },
+
     /// A network error occurred
     #[error("Network error: {details}")]
     Network {
@@ -476,6 +581,10 @@ pub enum Error {
         details: String,
     },
 
+    /// An incremental backup has been requested but no previous backup has been made
+    #[error("No previous backup to base the incremental backup on")]
+    NoBaseBackup,
+
     /// Cannot burn an asset with zero amount
     #[error("Burn request with zero amount")]
     NoBurnAmount,
@@ -504,6 +613,10 @@ pub enum Error {
     #[error("Cannot allocate assets with zero allocations per UTXO")]
     NoMaxAllocationsPerUtxo,
 
+    /// No backup of the wallet has been found in the remote backup target
+    #[error("No remote backup found")]
+    NoRemoteBackup,
+
     /// Cannot create a wallet with no supported schemas
     #[error("Cannot create a wallet with no supported schemas")]
     NoSupportedSchemas,
@@ -524,6 +637,10 @@ pub enum Error {
     #[error("Output below the dust limit")]
     OutputBelowDustLimit,
 
+    /// The wallet is encrypted and no password has been provided
+    #[error("The wallet is encrypted, a password is required")]
+    PasswordRequired,
+
     /// Error contacting the RGB proxy
     #[error("Proxy error: {details}")]
     Proxy {
@@ -538,6 +655,13 @@ pub enum Error {
         details: String,
     },
 
+    /// The amount received doesn't satisfy the receive policy
+    #[error("Receive policy not satisfied: {details}")]
+    ReceivePolicyNotSatisfied {
+        /// Error details
+        details: String,
+    },
+
     /// Provided recipient ID has already been used for another transfer
     #[error("Recipient ID already used")]
     RecipientIDAlreadyUsed,
@@ -553,6 +677,13 @@ pub enum Error {
         details: String,
     },
 
+    /// Error from the remote backup target
+    #[error("Remote backup error: {details}")]
+    RemoteBackup {
+        /// Error details
+        details: String,
+    },
+
     /// Error building a rest client
     #[error("Error building a rest client")]
     RestClientBuild {
@@ -597,6 +728,10 @@ pub enum Error {
         txid: String,
     },
 
+    /// The wallet database can't be included in a backup
+    #[error("Backup is not supported for the wallet database type")]
+    UnsupportedBackupDatabase,
+
     /// The backup version is not supported
     #[error("Backup version not supported")]
     UnsupportedBackupVersion {
@@ -611,6 +746,11 @@ pub enum Error {
         asset_schema: AssetSchema,
     },
 
+    /// The wallet database type doesn't support encryption or rgb-lib has been built without the
+    /// `sqlcipher` feature
+    #[error("Encryption is not supported for the wallet database type")]
+    UnsupportedDatabaseEncryption,
+
     /// The schema doesn't support inflate transitions
     #[error("Inflation not supported")]
     UnsupportedInflation {
@@ -672,8 +812,48 @@ pub enum Error {
     WrongPassword,
 }
 
+#[cfg(feature = "bitcoind")]
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum BitcoindError {
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+
+    #[error("RPC error {code}: {message}")]
+    Rpc { code: i64, message: String },
+
+    #[error("transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+}
+
+#[cfg(feature = "cbf")]
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum CbfError {
+    #[error("encoding error: {0}")]
+    Encode(#[from] bitcoin::consensus::encode::Error),
+
+    #[error("filter error: {0}")]
+    Filter(#[from] bitcoin::bip158::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("no reachable peer serving compact block filters")]
+    NoPeers,
+
+    #[error("peer misbehaved: {0}")]
+    Peer(String),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum IndexerError {
+    #[cfg(feature = "bitcoind")]
+    #[error("Bitcoind error: {0}")]
+    Bitcoind(#[from] BitcoindError),
+
+    #[cfg(feature = "cbf")]
+    #[error("CBF error: {0}")]
+    Cbf(#[from] CbfError),
+
     #[cfg(feature = "electrum")]
     #[error("Electrum error: {0}")]
     Electrum(#[from] ElectrumError),
@@ -681,11 +861,45 @@ pub(crate) enum IndexerError {
     #[cfg(feature = "esplora")]
     #[error("Esplora error: {0}")]
     Esplora(#[from] EsploraError),
+
+    #[cfg(online)]
+    #[error("Indexer quorum not reached: {0}")]
+    Quorum(String),
+}
+
+#[cfg(online)]
+impl IndexerError {
+    /// Whether the error is caused by the indexer being unreachable, rather than by the request.
+    pub(crate) fn is_connection_error(&self) -> bool {
+        match self {
+            #[cfg(feature = "bitcoind")]
+            IndexerError::Bitcoind(e) => matches!(e, BitcoindError::Transport(_)),
+            #[cfg(feature = "cbf")]
+            IndexerError::Cbf(e) => matches!(e, CbfError::Io(_) | CbfError::NoPeers),
+            #[cfg(feature = "electrum")]
+            IndexerError::Electrum(e) => matches!(
+                e,
+                ElectrumError::IOError(_)
+                    | ElectrumError::SharedIOError(_)
+                    | ElectrumError::AllAttemptsErrored(_)
+                    | ElectrumError::CouldNotCreateConnection(_)
+                    | ElectrumError::CouldntLockReader
+                    | ElectrumError::Mpsc
+            ),
+            #[cfg(feature = "esplora")]
+            IndexerError::Esplora(e) => match e {
+                EsploraError::Minreq(_) => true,
+                EsploraError::HttpResponse { status, .. } => *status >= 500,
+                _ => false,
+            },
+            IndexerError::Quorum(_) => false,
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum InternalError {
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     #[error("API error: {0}")]
     Api(#[from] reqwest::Error),
 
@@ -785,7 +999,7 @@ impl From<IndexerError> for Error {
     }
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl From<reqwest::Error> for Error {
     fn from(e: reqwest::Error) -> Self {
         Error::RestClientBuild {
@@ -925,6 +1139,31 @@ impl From<bdk_wallet::LoadWithPersistError<bdk_wallet::FileStoreError>> for Erro
     }
 }
 
+impl From<bdk_wallet::CreateWithPersistError<Error>> for Error {
+    fn from(e: bdk_wallet::CreateWithPersistError<Error>) -> Self {
+        match e {
+            bdk_wallet::CreateWithPersistError::Persist(e) => e,
+            _ => Error::IO {
+                details: e.to_string(),
+            },
+        }
+    }
+}
+
+impl From<bdk_wallet::LoadWithPersistError<Error>> for Error {
+    fn from(e: bdk_wallet::LoadWithPersistError<Error>) -> Self {
+        match e {
+            bdk_wallet::LoadWithPersistError::Persist(e) => e,
+            bdk_wallet::LoadWithPersistError::InvalidChangeSet(
+                bdk_wallet::LoadError::Mismatch(bdk_wallet::LoadMismatch::Genesis { .. }),
+            ) => Error::BitcoinNetworkMismatch,
+            _ => Error::IO {
+                details: e.to_string(),
+            },
+        }
+    }
+}
+
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Self {
         Error::IO {
@@ -53,6 +53,7 @@
 //!         database_type: DatabaseType::Sqlite,
 //!         max_allocations_per_utxo: 5,
 //!         supported_schemas: vec![AssetSchema::Nia],
+//!         encryption_password: None,
 //!     };
 //!     let wallet = Wallet::new(wallet_data, single_sig_keys)?;
 //!
@@ -79,6 +80,7 @@
 //!         database_type: DatabaseType::Sqlite,
 //!         max_allocations_per_utxo: 5,
 //!         supported_schemas: vec![AssetSchema::Nia],
+//!         encryption_password: None,
 //!     };
 //!     let wallet = Wallet::new(wallet_data, SinglesigKeys::from_keys(&keys, None))?;
 //!     drop(wallet);
@@ -87,13 +89,14 @@
 //!         data_dir,
 //!         &keys.master_fingerprint,
 //!         Some(keys.mnemonic.clone()),
+//!         None,
 //!     )?;
 //!
 //!     Ok(())
 //! }
 //! ```
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) mod api;
 pub(crate) mod database;
 pub(crate) mod error;
@@ -116,37 +119,48 @@ pub use rgbstd::{
 
 pub use crate::{
     database::enums::{
-        AssetSchema, Assignment, TransferStatus, TransportType, WalletTransactionType,
+        AssetSchema, Assignment, ReceivePolicy, TransferStatus, TransportType,
+        WalletTransactionType,
     },
     error::Error,
     utils::{BitcoinNetwork, block_on},
 };
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
-use std::{
-    cmp::{Ordering, max, min},
-    collections::{BTreeSet, hash_map::DefaultHasher},
-    hash::Hasher,
-    num::NonZeroU32,
-};
 use std::{
+    cell::{Cell, RefCell},
     collections::{BTreeMap, HashMap, HashSet},
     fmt, fs,
     hash::Hash,
     io::{self, ErrorKind, Read, Write},
+    ops::{Deref, DerefMut},
     panic,
     path::{Path, PathBuf},
     str::FromStr,
-    sync::{Arc, LazyLock},
+    sync::{Arc, Condvar, LazyLock, Mutex, MutexGuard, RwLock, Weak},
+    thread::{self, ThreadId},
     time::Duration,
 };
+#[cfg(online)]
+use std::{
+    cmp::{Ordering, max, min},
+    collections::{BTreeSet, hash_map::DefaultHasher},
+    hash::Hasher,
+    num::NonZeroU32,
+    thread::JoinHandle,
+    time::Instant,
+};
+#[cfg(feature = "cbf")]
+use std::{
+    fs::File,
+    io::{BufReader, Seek, SeekFrom},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+};
 
 use amplify::{
     Bytes32, Wrapper, bmap,
     confinement::{Confined, MediumOrdMap},
     s,
 };
-#[cfg(any(feature = "electrum", feature = "esplora"))]
 use base64::{Engine as _, engine::general_purpose};
 #[cfg(feature = "electrum")]
 use bdk_electrum::{
@@ -160,19 +174,43 @@ use bdk_esplora::{
         BlockingClient as EsploraClient, Builder as EsploraBuilder, Error as EsploraError,
     },
 };
-#[cfg(feature = "esplora")]
+#[cfg(any(feature = "esplora", feature = "bitcoind", feature = "cbf"))]
 use bdk_wallet::bitcoin::Txid;
+#[cfg(feature = "cbf")]
+use bdk_wallet::bitcoin::{
+    Block, CompactTarget, Network, Target, Work,
+    bip158::{BlockFilter, FilterHeader},
+    block::Header,
+    consensus::{
+        Decodable,
+        encode::{deserialize, serialize},
+    },
+    constants::genesis_block,
+    p2p::{
+        Address as P2pAddress, ServiceFlags,
+        message::{NetworkMessage, RawNetworkMessage},
+        message_blockdata::{GetHeadersMessage, Inventory},
+        message_filter::{CFHeaders, GetCFHeaders, GetCFilters},
+        message_network::VersionMessage,
+    },
+    params::Params as ConsensusParams,
+};
+#[cfg(feature = "bitcoind")]
+use bdk_wallet::descriptor::calc_checksum;
 use bdk_wallet::{
     ChangeSet, KeychainKind, LocalOutput, PersistedWallet, SignOptions, Wallet as BdkWallet,
+    WalletPersister,
     bitcoin::{
         Address as BdkAddress, Amount as BdkAmount, BlockHash, Network as BdkNetwork, NetworkKind,
         OutPoint, OutPoint as BdkOutPoint, ScriptBuf, TxOut,
-        bip32::{ChildNumber, DerivationPath, Fingerprint, KeySource, Xpriv, Xpub},
-        hashes::{Hash as Sha256Hash, sha256},
-        psbt::{ExtractTxError, Psbt},
-        secp256k1::Secp256k1,
+        bip32::{ChainCode, ChildNumber, DerivationPath, Fingerprint, KeySource, Xpriv, Xpub},
+        hashes::{Hash as Sha256Hash, HashEngine, sha256},
+        psbt::{ExtractTxError, Input as PsbtInput, Psbt, raw::Key as PsbtRawKey},
+        secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey},
+        sighash::{Prevouts, SighashCache, TapSighashType},
+        taproot::TapTweakHash,
     },
-    chain::{CanonicalizationParams, ChainPosition},
+    chain::{CanonicalizationParams, ChainPosition, Merge},
     descriptor::Segwitv0,
     file_store::Store,
     keys::{
@@ -182,23 +220,45 @@ use bdk_wallet::{
         bip39::{Language, Mnemonic, WordCount},
     },
 };
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 use bdk_wallet::{
     Update,
-    bitcoin::{Transaction as BdkTransaction, blockdata::fee_rate::FeeRate, hashes::HashEngine},
+    bitcoin::{
+        EcdsaSighashType, Transaction as BdkTransaction, Weight,
+        blockdata::fee_rate::FeeRate,
+        secp256k1::{Keypair, Message as SecpMessage, schnorr::Signature as SchnorrSignature},
+        taproot::Signature as TaprootSignature,
+    },
     chain::{
         DescriptorExt,
         spk_client::{FullScanRequest, FullScanResponse, SyncRequest, SyncResponse},
     },
     coin_selection::InsufficientFunds,
 };
+#[cfg(any(feature = "bitcoind", feature = "cbf"))]
+use bdk_wallet::{
+    bitcoin::constants::ChainHash,
+    chain::{BlockId, CheckPoint, ConfirmationBlockTime, TxUpdate},
+};
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305, XNonce, aead::Aead};
 use file_format::FileFormat;
+use musig2::{
+    AggNonce, KeyAggContext as MusigKeyAggContext, PartialSignature, PubNonce, SecNonce,
+    SecNonceBuilder,
+    errors::SigningError,
+    secp::{MaybeScalar, Point, Scalar as MusigScalar},
+    sign_partial,
+};
+#[cfg(online)]
+use musig2::{LiftedSignature, aggregate_partial_signatures, verify_partial};
 use psrgbt::{RgbOutExt, RgbPsbtExt};
+#[cfg(online)]
+use rand::seq::SliceRandom;
 use rand::{RngExt, distr::Alphanumeric};
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 use reqwest::{
-    blocking::{Client as RestClient, multipart},
+    StatusCode,
+    blocking::{Client as RestClient, RequestBuilder, Response, multipart},
     header::CONTENT_TYPE,
 };
 use rgb_lib_migration::{
@@ -207,8 +267,14 @@ use rgb_lib_migration::{
 use rgbinvoice::{
     AddressPayload, Beneficiary, RgbInvoice, RgbInvoiceBuilder, RgbTransport, XChainNet,
 };
+#[cfg(any(feature = "electrum", feature = "esplora"))]
+use rgbstd::indexers::AnyResolver;
 #[cfg(feature = "electrum")]
-use rgbstd::indexers::electrum_blocking::electrum_client::ConfigBuilder;
+use rgbstd::indexers::electrum_blocking::electrum_client::{
+    Config as ElectrumConfig, ConfigBuilder, Socks5Config,
+};
+#[cfg(any(feature = "bitcoind", feature = "cbf"))]
+use rgbstd::vm::WitnessPos;
 use rgbstd::{
     Allocation, Amount, Assign, ChainNet, Genesis, GraphSeal, Identity, KnownTransition, Layer1,
     Operation as _, Opout, OutputSeal, OwnedFraction, Precision, Schema, SecretSeal, TokenIndex,
@@ -232,17 +298,16 @@ use rgbstd::{
         ResolveWitness, Scripts, Status, WitnessOrdProvider, WitnessResolverError, WitnessStatus,
     },
 };
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 use rgbstd::{
-    TransitionType,
+    OpId, TransitionBundle, TransitionType,
     containers::Consignment,
     contract::FilterIncludeAll,
     daggy::Walker,
-    indexers::AnyResolver,
     info::ContractInfo,
     validation::{OpoutsDagData, ValidationConfig, ValidationError, Validity, Warning},
 };
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 use schemata::{CfaWrapper, NiaWrapper, UdaWrapper};
 use schemata::{
     CollectibleFungibleAsset, IfaWrapper, InflatableFungibleAsset, NonInflatableAsset, OS_ASSET,
@@ -250,13 +315,14 @@ use schemata::{
 };
 use scrypt::{Params, phc::Salt, scrypt};
 use sea_orm::{
-    ActiveValue, ColumnTrait, ConnectOptions, Database, DatabaseConnection, DatabaseTransaction,
-    DbErr, DeriveActiveEnum, EntityTrait, EnumIter, IntoActiveValue, JsonValue, QueryFilter,
-    QueryOrder, QueryResult, TransactionTrait, TryGetError, TryGetable, TryIntoModel,
+    ActiveValue, ColumnTrait, ConnectOptions, ConnectionTrait, Database, DatabaseConnection,
+    DatabaseTransaction, DbErr, DeriveActiveEnum, EntityTrait, EnumIter, IntoActiveValue,
+    JsonValue, QueryFilter, QueryOrder, QueryResult, TransactionTrait, TryGetError, TryGetable,
+    TryIntoModel,
 };
 use serde::de::{self, Unexpected, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 use slog::warn;
 use slog::{Drain, Logger, debug, error, info, o};
 use slog_async::AsyncGuard;
@@ -266,7 +332,7 @@ use strict_encoding::{DecodeError, DeserializeError, FieldName};
 use strict_types::StrictDumb;
 use tempfile::TempDir;
 use time::OffsetDateTime;
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 use url::Url;
 use walkdir::WalkDir;
 use zip::{ZipArchive, ZipWriter, write::SimpleFileOptions};
@@ -275,13 +341,23 @@ use zip::{ZipArchive, ZipWriter, write::SimpleFileOptions};
 use crate::utils::INDEXER_BATCH_SIZE;
 #[cfg(feature = "esplora")]
 use crate::utils::INDEXER_PARALLEL_REQUESTS;
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(feature = "bitcoind")]
+use crate::utils::INDEXER_RETRIES;
+#[cfg(any(feature = "bitcoind", feature = "cbf"))]
+use crate::utils::INDEXER_TIMEOUT;
+#[cfg(online)]
 #[cfg(test)]
 use crate::wallet::test::{
     mock_input_unspents, mock_local_version, mock_send_end_crash, mock_vout, skip_build_dag,
     skip_check_fee_rate,
 };
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(feature = "bitcoind")]
+use crate::{api::bitcoind::BitcoindClient, error::BitcoindError};
+#[cfg(feature = "cbf")]
+use crate::{
+    api::cbf::CbfClient, error::CbfError, utils::get_cbf_indexer_and_resolver, wallet::CbfOptions,
+};
+#[cfg(online)]
 use crate::{
     api::{
         multisig_hub::{
@@ -294,17 +370,26 @@ use crate::{
     database::{
         DbData,
         entities::{
+            multisig_operation::{
+                ActiveModel as DbMultisigOperationActMod, Model as DbMultisigOperation,
+            },
             pending_witness_script::Model as DbPendingWitnessScript,
             reserved_txo::ActiveModel as DbReservedTxoActMod,
             wallet_transaction::ActiveModel as DbWalletTransactionActMod,
         },
+        enums::MultisigOperationState,
     },
     error::IndexerError,
     utils::{
-        INDEXER_STOP_GAP, OffchainResolver, check_proxy, get_indexer_and_resolver, hash_file,
+        INDEXER_STOP_GAP, OffchainResolver, check_proxy, get_indexer_and_resolver,
+        get_multi_indexer_and_resolver, load_volatile_rgb_runtime, recipient_id_from_script_buf,
         script_buf_from_recipient_id,
     },
-    wallet::{AssignmentsCollection, Indexer, multisig::RespondToOperation},
+    wallet::{
+        AssignmentsCollection, BackupTarget, Indexer, MultiIndexer, MultiResolver, NetworkOptions,
+        NetworkPolicy, NetworkService, Socks5Proxy, VersionedObject, WitnessResolver,
+        multisig::RespondToOperation,
+    },
 };
 use crate::{
     database::{
@@ -336,15 +421,16 @@ use crate::{
     error::InternalError,
     keys::{Keys, WitnessVersion},
     utils::{
-        ACCOUNT, DumbResolver, KEYCHAIN_BTC, KEYCHAIN_RGB, LOG_FILE, PURPOSE, RgbRuntime,
+        ACCOUNT, CBF_DIR, DumbResolver, KEYCHAIN_BTC, KEYCHAIN_RGB, LOG_FILE, PURPOSE, RgbRuntime,
         adjust_canonicalization, beneficiary_from_script_buf, from_str_or_number_mandatory,
-        from_str_or_number_optional, get_account_xpubs, get_coin_type, get_descriptors,
-        get_descriptors_from_xpubs, hash_bytes, hash_bytes_hex, load_rgb_runtime, now,
-        parse_address_str, setup_logger, str_to_xpub,
+        from_str_or_number_optional, get_account_data, get_account_xpubs, get_coin_type,
+        get_descriptors, get_descriptors_from_xpubs, hash_bytes, hash_bytes_hex, hash_file,
+        load_rgb_runtime, now, parse_address_str, setup_logger, str_to_xpub, write_file_atomically,
     },
     wallet::{
-        Balance, LocalRgbAllocation, LocalUnspent, NUM_KNOWN_SCHEMAS, Outpoint, SCHEMA_ID_CFA,
-        SCHEMA_ID_IFA, SCHEMA_ID_NIA, SCHEMA_ID_UDA, WalletDescriptors,
+        Balance, LocalRgbAllocation, LocalUnspent, NUM_KNOWN_SCHEMAS, Outpoint, RemoteBackup,
+        SCHEMA_ID_CFA, SCHEMA_ID_IFA, SCHEMA_ID_NIA, SCHEMA_ID_UDA, WalletDescriptors, WalletEvent,
+        WalletEventListener,
     },
 };
 #[cfg(test)]
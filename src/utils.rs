@@ -12,6 +12,8 @@ const RGB_RUNTIME_LOCK_FILE: &str = "rgb_runtime.lock";
 
 pub(crate) const RGB_RUNTIME_DIR: &str = "rgb";
 pub(crate) const LOG_FILE: &str = "log";
+// block headers and filters downloaded by the compact block filters client
+pub(crate) const CBF_DIR: &str = "cbf";
 
 pub(crate) const PURPOSE: u8 = 86;
 pub(crate) const COIN_RGB_MAINNET: u32 = 827166;
@@ -20,18 +22,18 @@ pub(crate) const ACCOUNT: u8 = 0;
 pub(crate) const KEYCHAIN_RGB: u8 = 0;
 pub(crate) const KEYCHAIN_BTC: u8 = 0;
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) const INDEXER_STOP_GAP: usize = 20;
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) const INDEXER_TIMEOUT: u64 = 10;
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(any(feature = "electrum", feature = "esplora", feature = "bitcoind"))]
 pub(crate) const INDEXER_RETRIES: u8 = 3;
 #[cfg(feature = "electrum")]
 pub(crate) const INDEXER_BATCH_SIZE: usize = 5;
 #[cfg(feature = "esplora")]
 pub(crate) const INDEXER_PARALLEL_REQUESTS: usize = 5;
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 const PROXY_PROTOCOL_VERSION: &str = "0.2";
 
 #[cfg(test)]
@@ -520,9 +522,13 @@ pub(crate) fn calculate_descriptor_from_xpub(
     Ok(format!("{}({key})", witness_version.descriptor_fn()))
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
-pub(crate) fn check_proxy(proxy_url: &str) -> Result<(), Error> {
-    let proxy_client = ProxyClient::new(proxy_url)?;
+#[cfg(online)]
+pub(crate) fn check_proxy(
+    proxy_url: &str,
+    socks5_proxy: Option<&Socks5Proxy>,
+    policy: &NetworkPolicy,
+) -> Result<(), Error> {
+    let proxy_client = ProxyClient::new(proxy_url, socks5_proxy, policy)?;
     let mut err_details = s!("unable to connect to proxy");
     if let Ok(server_info) = proxy_client.get_info() {
         if let Some(info) = server_info.result {
@@ -543,33 +549,55 @@ pub(crate) fn check_proxy(proxy_url: &str) -> Result<(), Error> {
     })
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
+#[cfg_attr(
+    not(any(feature = "electrum", feature = "esplora", feature = "bitcoind")),
+    allow(unreachable_code, unused_variables)
+)]
 pub(crate) fn get_indexer_and_resolver(
     indexer_url: &str,
     bitcoin_network: BitcoinNetwork,
-) -> Result<(Indexer, AnyResolver), Error> {
+    network_options: &NetworkOptions,
+) -> Result<(Indexer, WitnessResolver), Error> {
+    let socks5_proxy = network_options.socks5_proxy(NetworkService::Indexer, None);
     // detect indexer type
-    let indexer = build_indexer(indexer_url);
+    let indexer = build_indexer(indexer_url, socks5_proxy.as_ref());
     let mut invalid_indexer = true;
     if let Some(ref indexer) = indexer {
         invalid_indexer = indexer.block_hash(0).is_err();
     }
     if invalid_indexer {
-        return Err(Error::InvalidIndexer {
-            details: s!("not a valid electrum nor esplora server"),
-        });
+        #[cfg(feature = "bitcoind")]
+        if BitcoindClient::is_bitcoind_url(indexer_url) {
+            return Err(Error::InvalidIndexer {
+                details: s!("not a valid bitcoind URL (the birthday parameter is required)"),
+            });
+        }
+        let details = if socks5_proxy.is_some() {
+            s!("not a valid electrum server (esplora is not supported with a SOCKS5 proxy)")
+        } else {
+            s!("not a valid electrum nor esplora server")
+        };
+        return Err(Error::InvalidIndexer { details });
     }
     let indexer = indexer.unwrap();
 
-    let resolver = match indexer {
+    let resolver: WitnessResolver = match indexer {
+        #[cfg(feature = "bitcoind")]
+        Indexer::Bitcoind(_) => WitnessResolver::Bitcoind(Box::new(
+            BitcoindClient::from_url(indexer_url, socks5_proxy.as_ref())
+                .expect("from_url uses the same URL as build_indexer which already succeeded"),
+        )),
+        #[cfg(feature = "cbf")]
+        Indexer::Cbf(_) => unreachable!("build_indexer never builds a CBF indexer"),
+        Indexer::Multi(_) => unreachable!("build_indexer never builds a multi indexer"),
         #[cfg(feature = "electrum")]
         Indexer::Electrum(_) => {
-            let electrum_config = ConfigBuilder::new()
-                .retry(INDEXER_RETRIES)
-                .timeout(Some(Duration::from_secs(INDEXER_TIMEOUT)))
-                .build();
-            AnyResolver::electrum_blocking(indexer_url, Some(electrum_config)).expect(
-                "electrum_blocking uses the same config as build_indexer which already succeeded",
+            let electrum_config = electrum_config(socks5_proxy.as_ref());
+            WitnessResolver::Any(
+                AnyResolver::electrum_blocking(indexer_url, Some(electrum_config)).expect(
+                    "electrum_blocking uses the same config as build_indexer which already succeeded",
+                ),
             )
         }
         #[cfg(feature = "esplora")]
@@ -577,8 +605,10 @@ pub(crate) fn get_indexer_and_resolver(
             let esplora_config = EsploraBuilder::new(indexer_url)
                 .max_retries(INDEXER_RETRIES.into())
                 .timeout(INDEXER_TIMEOUT);
-            AnyResolver::esplora_blocking(esplora_config)
-                .expect("esplora_blocking wraps an infallible builder and always returns Ok")
+            WitnessResolver::Any(
+                AnyResolver::esplora_blocking(esplora_config)
+                    .expect("esplora_blocking wraps an infallible builder and always returns Ok"),
+            )
         }
     };
 
@@ -591,22 +621,107 @@ pub(crate) fn get_indexer_and_resolver(
     Ok((indexer, resolver))
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
-pub(crate) fn build_indexer(indexer_url: &str) -> Option<Indexer> {
+#[cfg(online)]
+pub(crate) fn get_multi_indexer_and_resolver(
+    primary: Result<(Indexer, WitnessResolver), Error>,
+    fallback_indexer_urls: &[String],
+    indexer_quorum: Option<u8>,
+    bitcoin_network: BitcoinNetwork,
+    network_options: &NetworkOptions,
+) -> Result<(Indexer, WitnessResolver), Error> {
+    let total = fallback_indexer_urls.len() + 1;
+    if let Some(quorum) = indexer_quorum
+        && (quorum < 2 || quorum as usize > total)
+    {
+        return Err(Error::InvalidIndexer {
+            details: format!(
+                "indexer quorum must be between 2 and the number of indexers ({total})"
+            ),
+        });
+    }
+    // indexers that cannot be reached are skipped, as long as enough of them are left
+    let (mut indexers, mut resolvers, mut errors) = (vec![], vec![], vec![]);
+    let fallbacks = fallback_indexer_urls
+        .iter()
+        .map(|url| get_indexer_and_resolver(url, bitcoin_network, network_options));
+    for res in std::iter::once(primary).chain(fallbacks) {
+        match res {
+            Ok((indexer, resolver)) => {
+                indexers.push(indexer);
+                resolvers.push(resolver);
+            }
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    if indexers.len() < indexer_quorum.unwrap_or(1) as usize {
+        return Err(Error::InvalidIndexer {
+            details: errors.join(", "),
+        });
+    }
+    Ok((
+        Indexer::Multi(Box::new(MultiIndexer::new(indexers, indexer_quorum))),
+        WitnessResolver::Multi(Box::new(MultiResolver::new(resolvers, indexer_quorum))),
+    ))
+}
+
+#[cfg(feature = "cbf")]
+pub(crate) fn get_cbf_indexer_and_resolver(
+    cbf_options: &CbfOptions,
+    bitcoin_network: BitcoinNetwork,
+    network_options: &NetworkOptions,
+    wallet_dir: &Path,
+) -> Result<(Indexer, WitnessResolver), Error> {
+    // P2P connections are not routed through the proxy, refuse to leak the IP
+    if network_options
+        .socks5_proxy(NetworkService::Indexer, None)
+        .is_some()
+    {
+        return Err(Error::InvalidIndexer {
+            details: s!("compact block filters cannot be used with a SOCKS5 proxy"),
+        });
+    }
+    // custom signets use a different P2P magic
+    if bitcoin_network == BitcoinNetwork::SignetCustom {
+        return Err(Error::InvalidIndexer {
+            details: s!("compact block filters are not supported on custom signets"),
+        });
+    }
+    let invalid_indexer = |e: CbfError| Error::InvalidIndexer {
+        details: e.to_string(),
+    };
+    let client = Arc::new(
+        CbfClient::new(cbf_options, bitcoin_network.into(), wallet_dir).map_err(invalid_indexer)?,
+    );
+    // download the block headers, which also checks a peer is reachable
+    client.get_tip_height().map_err(invalid_indexer)?;
+    Ok((Indexer::Cbf(client.clone()), WitnessResolver::Cbf(client)))
+}
+
+#[cfg(online)]
+#[cfg_attr(
+    not(any(feature = "electrum", feature = "esplora", feature = "bitcoind")),
+    allow(unused_variables)
+)]
+pub(crate) fn build_indexer(
+    indexer_url: &str,
+    socks5_proxy: Option<&Socks5Proxy>,
+) -> Option<Indexer> {
+    #[cfg(feature = "bitcoind")]
+    if let Some(client) = BitcoindClient::from_url(indexer_url, socks5_proxy) {
+        return Some(Indexer::Bitcoind(Box::new(client)));
+    }
     #[cfg(feature = "electrum")]
     {
         let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
-        let opts = ConfigBuilder::new()
-            .retry(INDEXER_RETRIES)
-            .timeout(Some(Duration::from_secs(INDEXER_TIMEOUT)))
-            .build();
+        let opts = electrum_config(socks5_proxy);
         if let Ok(client) = ElectrumClient::from_config(indexer_url, opts) {
             let client = BdkElectrumClient::new(client);
             let indexer = Indexer::Electrum(Box::new(client));
             return Some(indexer);
         }
     }
-    if cfg!(feature = "esplora") {
+    // the esplora client only supports HTTP proxies, refuse to leak the IP
+    if cfg!(feature = "esplora") && socks5_proxy.is_none() {
         #[cfg(feature = "esplora")]
         {
             let opts = EsploraBuilder::new(indexer_url)
@@ -620,6 +735,21 @@ pub(crate) fn build_indexer(indexer_url: &str) -> Option<Indexer> {
     None
 }
 
+#[cfg(feature = "electrum")]
+fn electrum_config(socks5_proxy: Option<&Socks5Proxy>) -> ElectrumConfig {
+    let socks5_config = socks5_proxy.map(|p| match &p.credentials {
+        Some((username, password)) => {
+            Socks5Config::with_credentials(&p.address, username.clone(), password.clone())
+        }
+        None => Socks5Config::new(&p.address),
+    });
+    ConfigBuilder::new()
+        .retry(INDEXER_RETRIES)
+        .timeout(Some(Duration::from_secs(INDEXER_TIMEOUT)))
+        .socks5(socks5_config)
+        .build()
+}
+
 pub(crate) fn hash_bytes(data: &[u8]) -> Vec<u8> {
     <sha256::Hash as Sha256Hash>::hash(data)
         .to_byte_array()
@@ -630,7 +760,19 @@ pub(crate) fn hash_bytes_hex(data: &[u8]) -> String {
     hex::encode(hash_bytes(data))
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+/// Replace the file at `path` atomically with the concatenation of the provided `parts`, so a
+/// crash can't leave it half-written.
+pub(crate) fn write_file_atomically(path: &Path, parts: &[&[u8]]) -> Result<(), Error> {
+    let tmp_path = path.with_extension("tmp");
+    let mut tmp_file = fs::File::create(&tmp_path)?;
+    for part in parts {
+        tmp_file.write_all(part)?;
+    }
+    tmp_file.sync_all()?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
 pub(crate) fn hash_file(path: &Path) -> Result<String, Error> {
     let mut file = fs::File::open(path)?;
     let mut engine = sha256::HashEngine::default();
@@ -699,10 +841,12 @@ pub struct RgbRuntime {
     stock: Stock,
     /// The wallet directory, where the lockfile for the runtime is to be held
     wallet_dir: PathBuf,
+    /// Whether changes to the stock are saved when the runtime is dropped
+    persist: bool,
 }
 
 impl RgbRuntime {
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn accept_transfer<R: ResolveWitness>(
         &mut self,
         contract: ValidTransfer,
@@ -739,7 +883,7 @@ impl RgbRuntime {
             .map_err(InternalError::from)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn contracts(&self) -> Result<Vec<ContractInfo>, InternalError> {
         Ok(self
             .stock
@@ -757,7 +901,7 @@ impl RgbRuntime {
             .map_err(InternalError::from)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn contracts_assigning(
         &self,
         outputs: impl IntoIterator<Item = impl Into<OutPoint>>,
@@ -800,7 +944,7 @@ impl RgbRuntime {
             .map_err(InternalError::from)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn contract_schema(
         &self,
         contract_id: ContractId,
@@ -859,7 +1003,7 @@ impl RgbRuntime {
             .map_err(InternalError::from)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn transfer_from_fascia_with_dag(
         &self,
         contract_id: ContractId,
@@ -882,7 +1026,7 @@ impl RgbRuntime {
             .map_err(InternalError::from)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn transition_builder_raw(
         &self,
         contract_id: ContractId,
@@ -893,7 +1037,7 @@ impl RgbRuntime {
             .map_err(InternalError::from)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn update_witnesses<R: ResolveWitness>(
         &mut self,
         resolver: &R,
@@ -917,7 +1061,9 @@ impl RgbRuntime {
 
 impl Drop for RgbRuntime {
     fn drop(&mut self) {
-        self.stock.store().expect("unable to save stock");
+        if self.persist {
+            self.stock.store().expect("unable to save stock");
+        }
         fs::remove_file(self.wallet_dir.join(RGB_RUNTIME_LOCK_FILE))
             .expect("should be able to drop lockfile")
     }
@@ -952,6 +1098,21 @@ fn write_rgb_runtime_lockfile(wallet_dir: &Path) -> Result<(), Error> {
 }
 
 pub(crate) fn load_rgb_runtime<P: AsRef<Path>>(wallet_dir: P) -> Result<RgbRuntime, Error> {
+    load_rgb_runtime_impl(wallet_dir, true)
+}
+
+/// Load an RGB runtime whose stock changes are kept in memory and discarded on drop.
+#[cfg(online)]
+pub(crate) fn load_volatile_rgb_runtime<P: AsRef<Path>>(
+    wallet_dir: P,
+) -> Result<RgbRuntime, Error> {
+    load_rgb_runtime_impl(wallet_dir, false)
+}
+
+fn load_rgb_runtime_impl<P: AsRef<Path>>(
+    wallet_dir: P,
+    persist: bool,
+) -> Result<RgbRuntime, Error> {
     write_rgb_runtime_lockfile(wallet_dir.as_ref())?;
 
     let rgb_dir = wallet_dir.as_ref().join(RGB_RUNTIME_DIR);
@@ -959,7 +1120,7 @@ pub(crate) fn load_rgb_runtime<P: AsRef<Path>>(wallet_dir: P) -> Result<RgbRunti
         fs::create_dir_all(&rgb_dir)?;
     }
     let provider = FsBinStore::new(rgb_dir.clone())?;
-    let stock = Stock::load(provider.clone(), true).or_else(|err| {
+    let stock = Stock::load(provider.clone(), persist).or_else(|err| {
         if err
             .0
             .downcast_ref::<DeserializeError>()
@@ -967,7 +1128,7 @@ pub(crate) fn load_rgb_runtime<P: AsRef<Path>>(wallet_dir: P) -> Result<RgbRunti
             .unwrap_or_default()
         {
             let mut stock = Stock::in_memory();
-            stock.make_persistent(provider, true).expect("unable to save stock");
+            stock.make_persistent(provider, persist).expect("unable to save stock");
             return Ok(stock)
         }
         Err(Error::IO { details: err.to_string() })
@@ -976,30 +1137,35 @@ pub(crate) fn load_rgb_runtime<P: AsRef<Path>>(wallet_dir: P) -> Result<RgbRunti
     Ok(RgbRuntime {
         stock,
         wallet_dir: wallet_dir.as_ref().to_path_buf(),
+        persist,
     })
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct OffchainResolver<'a, 'cons, const TRANSFER: bool> {
     pub(crate) witness_id: RgbTxid,
     pub(crate) consignment: &'cons Consignment<TRANSFER>,
-    pub(crate) fallback: &'a AnyResolver,
+    pub(crate) fallback: &'a WitnessResolver,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl<const TRANSFER: bool> ResolveWitness for OffchainResolver<'_, '_, TRANSFER> {
     fn resolve_witness(&self, witness_id: RgbTxid) -> Result<WitnessStatus, WitnessResolverError> {
+        let witness_tx = self
+            .consignment
+            .bundled_witnesses()
+            .find(|bw| bw.witness_id() == witness_id)
+            .and_then(|p| p.pub_witness.tx().cloned());
+        if let Some(tx) = &witness_tx {
+            self.fallback.watch_tx(tx);
+        }
         if witness_id != self.witness_id {
             return self.fallback.resolve_witness(witness_id);
         }
-        self.consignment
-            .bundled_witnesses()
-            .find(|bw| bw.witness_id() == witness_id)
-            .and_then(|p| p.pub_witness.tx().cloned())
-            .map_or_else(
-                || self.fallback.resolve_witness(witness_id),
-                |tx| Ok(WitnessStatus::Resolved(tx, WitnessOrd::Tentative)),
-            )
+        witness_tx.map_or_else(
+            || self.fallback.resolve_witness(witness_id),
+            |tx| Ok(WitnessStatus::Resolved(tx, WitnessOrd::Tentative)),
+        )
     }
     fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
         self.fallback.check_chain_net(chain_net)
@@ -1110,7 +1276,7 @@ mod tests {
         assert_eq!(result, None);
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     #[test]
     fn test_check_proxy_json_rpc_error() {
         // server returns HTTP 200 with result=null and a JSON-RPC error field
@@ -1123,7 +1289,7 @@ mod tests {
                 r#"{"jsonrpc":"2.0","id":null,"result":null,"error":{"code":-32601,"message":"method not found"}}"#,
             )
             .create();
-        let result = check_proxy(&server.url());
+        let result = check_proxy(&server.url(), None, &NetworkPolicy::default());
         assert_matches!(result, Err(Error::Proxy { details }) if details == "method not found");
         mock.assert();
     }
@@ -1156,7 +1322,7 @@ mod tests {
     #[cfg(all(feature = "electrum", not(feature = "esplora")))]
     #[test]
     fn test_build_indexer_invalid_url_returns_none() {
-        let result = build_indexer("not_a_valid_url");
+        let result = build_indexer("not_a_valid_url", None);
         assert!(result.is_none());
     }
 
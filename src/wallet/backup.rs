@@ -4,13 +4,21 @@ const BACKUP_BUFFER_LEN_ENCRYPT: usize = 239; // 255 max, leaving 16 for the che
 const BACKUP_BUFFER_LEN_DECRYPT: usize = BACKUP_BUFFER_LEN_ENCRYPT + 16;
 const BACKUP_KEY_LENGTH: usize = 32;
 const BACKUP_NONCE_LENGTH: usize = 19;
-const BACKUP_VERSION: u8 = 1;
+const BACKUP_VERSION: u8 = 2;
+// oldest backup version that can still be restored
+const BACKUP_VERSION_MIN: u8 = 1;
+// content hashes of the wallet directory entries, stored in backups to replay deletions and kept in
+// the wallet directory after a backup to base the following incremental one on
+pub(crate) const BACKUP_MANIFEST_FILE: &str = "backup_manifest.json";
+
+// wallet directory entries mapped to the SHA-256 hash of their content (None for directories)
+pub(crate) type BackupManifest = BTreeMap<String, Option<String>>;
 
 pub(crate) struct BackupPaths {
     encrypted: PathBuf,
     pub(crate) backup_pub_data: PathBuf,
     pub(crate) tempdir: TempDir,
-    zip: PathBuf,
+    pub(crate) zip: PathBuf,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -56,6 +64,12 @@ pub(crate) struct BackupPubData {
     salt: String,
     nonce: String,
     pub(crate) version: u8,
+    // backup_info timestamp of this backup, missing in version 1 backups
+    #[serde(default)]
+    timestamp: Option<String>,
+    // timestamp of the backup this one is a delta of, missing in full backups
+    #[serde(default)]
+    base_timestamp: Option<String>,
 }
 
 impl BackupPubData {
@@ -77,21 +91,50 @@ pub trait WalletBackup: WalletCore {
         backup_path: &str,
         password: &str,
         scrypt_params: Option<ScryptParams>,
+    ) -> Result<(), Error> {
+        self.backup_impl(backup_path, password, scrypt_params, false)
+    }
+
+    fn backup_impl(
+        &self,
+        backup_path: &str,
+        password: &str,
+        scrypt_params: Option<ScryptParams>,
+        incremental: bool,
     ) -> Result<(), Error> {
         let txn = self.database().begin_transaction()?;
+        let (base_timestamp, base_manifest) = if incremental {
+            let last_backup_timestamp = txn
+                .get_backup_info()?
+                .map(|i| i.last_backup_timestamp)
+                .filter(|t| t != "0")
+                .ok_or(Error::NoBaseBackup)?;
+            let base_manifest =
+                read_backup_manifest(&self.wallet_dir().join(BACKUP_MANIFEST_FILE))?
+                    .ok_or(Error::NoBaseBackup)?;
+            (Some(last_backup_timestamp), Some(base_manifest))
+        } else {
+            (None, None)
+        };
         let prev_backup_info = self.update_backup_info(&txn, true)?;
+        let timestamp = txn
+            .get_backup_info()?
+            .expect("backup info has just been set")
+            .last_backup_timestamp;
         txn.commit()?;
-        match self.backup_raw(backup_path, password, scrypt_params) {
+        match self.backup_raw(
+            backup_path,
+            password,
+            scrypt_params,
+            timestamp,
+            base_timestamp,
+            base_manifest,
+        ) {
             Ok(()) => Ok(()),
             Err(e) => {
                 error!(self.logger(), "Error during backup: {e:?}");
                 let txn = self.database().begin_transaction()?;
-                if let Some(prev_backup_info) = prev_backup_info {
-                    let mut prev_backup_info: DbBackupInfoActMod = prev_backup_info.into();
-                    txn.update_backup_info(&mut prev_backup_info)?;
-                } else {
-                    txn.del_backup_info()?;
-                }
+                revert_backup_info(&txn, prev_backup_info)?;
                 txn.commit()?;
                 Err(e)
             }
@@ -103,9 +146,15 @@ pub trait WalletBackup: WalletCore {
         backup_path: &str,
         password: &str,
         scrypt_params: Option<ScryptParams>,
+        timestamp: String,
+        base_timestamp: Option<String>,
+        base_manifest: Option<BackupManifest>,
     ) -> Result<(), Error> {
         // setup
         info!(self.logger(), "starting backup...");
+        if !matches!(self.wallet_data().database_type, DatabaseType::Sqlite) {
+            return Err(Error::UnsupportedBackupDatabase);
+        }
         let backup_file = PathBuf::from(&backup_path);
         if backup_file.exists() {
             Err(Error::FileAlreadyExists {
@@ -115,11 +164,7 @@ pub trait WalletBackup: WalletCore {
         let tmp_base_path = get_parent_path(&backup_file)?;
         let files = get_backup_paths(&tmp_base_path)?;
         let scrypt_params = scrypt_params.unwrap_or_default();
-        let salt: String = rand::rng()
-            .sample_iter(&Alphanumeric)
-            .take(24)
-            .map(char::from)
-            .collect();
+        let salt = generate_salt();
         let str_params = serde_json::to_string(&scrypt_params).map_err(InternalError::from)?;
         debug!(
             self.logger(),
@@ -136,6 +181,8 @@ pub trait WalletBackup: WalletCore {
             salt,
             nonce,
             version: BACKUP_VERSION,
+            timestamp: Some(timestamp),
+            base_timestamp,
         };
 
         // create zip archive of wallet data
@@ -145,7 +192,14 @@ pub trait WalletBackup: WalletCore {
             &self.wallet_dir(),
             &files.zip
         );
-        zip_dir(self.wallet_dir(), &files.zip, true, self.logger())?;
+        // incremental backups only include the files whose content changed since their base backup
+        let manifest = zip_dir_with_manifest(
+            self.wallet_dir(),
+            &files.zip,
+            true,
+            Some(base_manifest.as_ref()),
+            self.logger(),
+        )?;
 
         // encrypt the backup file
         debug!(
@@ -170,17 +224,34 @@ pub trait WalletBackup: WalletCore {
             self.logger(),
         )?;
 
+        // keep the manifest of this backup to base the next incremental one on
+        fs::write(
+            self.wallet_dir().join(BACKUP_MANIFEST_FILE),
+            serde_json::to_vec(&manifest).map_err(InternalError::from)?,
+        )?;
+
         info!(self.logger(), "backup completed");
         Ok(())
     }
 
     fn get_backup_info(&self, txn: &DbTxn) -> Result<bool, Error> {
         Ok(if let Some(backup_info) = txn.get_backup_info()? {
+            // either a local or a remote backup covers the last operation
+            let last_backup_timestamp = backup_info
+                .last_backup_timestamp
+                .parse::<i128>()
+                .unwrap()
+                .max(
+                    backup_info
+                        .last_remote_backup_timestamp
+                        .map(|t| t.parse::<i128>().unwrap())
+                        .unwrap_or_default(),
+                );
             backup_info
                 .last_operation_timestamp
                 .parse::<i128>()
                 .unwrap()
-                > backup_info.last_backup_timestamp.parse::<i128>().unwrap()
+                > last_backup_timestamp
         } else {
             false
         })
@@ -192,38 +263,7 @@ pub trait WalletBackup: WalletCore {
         doing_backup: bool,
         last_processed_operation_idx: Option<i32>,
     ) -> Result<Option<DbBackupInfo>, Error> {
-        let now = ActiveValue::Set(now().unix_timestamp_nanos().to_string());
-        let last_processed_operation_idx = if last_processed_operation_idx.is_some() {
-            ActiveValue::Set(last_processed_operation_idx)
-        } else {
-            ActiveValue::NotSet
-        };
-        Ok(if let Some(backup_info) = txn.get_backup_info()? {
-            let prev_backup_info = backup_info.clone();
-            let mut backup_info: DbBackupInfoActMod = backup_info.into();
-            if doing_backup {
-                backup_info.last_backup_timestamp = now;
-            } else {
-                backup_info.last_operation_timestamp = now;
-            }
-            backup_info.last_processed_operation_idx = last_processed_operation_idx;
-            txn.update_backup_info(&mut backup_info)?;
-            Some(prev_backup_info)
-        } else {
-            let (last_backup_timestamp, last_operation_timestamp) = if doing_backup {
-                (now, ActiveValue::Set(s!("0")))
-            } else {
-                (ActiveValue::Set(s!("0")), now)
-            };
-            let backup_info = DbBackupInfoActMod {
-                last_backup_timestamp,
-                last_operation_timestamp,
-                last_processed_operation_idx,
-                ..Default::default()
-            };
-            txn.set_backup_info(backup_info)?;
-            None
-        })
+        record_backup_info(txn, doing_backup, last_processed_operation_idx)
     }
 
     fn update_backup_info(
@@ -235,33 +275,225 @@ pub trait WalletBackup: WalletCore {
     }
 }
 
+// Record the time of an operation or of a backup, returning the previous backup info.
+pub(crate) fn record_backup_info(
+    txn: &DbTxn,
+    doing_backup: bool,
+    last_processed_operation_idx: Option<i32>,
+) -> Result<Option<DbBackupInfo>, Error> {
+    let now = ActiveValue::Set(now().unix_timestamp_nanos().to_string());
+    let last_processed_operation_idx = if last_processed_operation_idx.is_some() {
+        ActiveValue::Set(last_processed_operation_idx)
+    } else {
+        ActiveValue::NotSet
+    };
+    if !doing_backup {
+        txn.mark_backup_outdated();
+    }
+    Ok(if let Some(backup_info) = txn.get_backup_info()? {
+        let prev_backup_info = backup_info.clone();
+        let mut backup_info: DbBackupInfoActMod = backup_info.into();
+        if doing_backup {
+            backup_info.last_backup_timestamp = now;
+        } else {
+            backup_info.last_operation_timestamp = now;
+        }
+        backup_info.last_processed_operation_idx = last_processed_operation_idx;
+        txn.update_backup_info(&mut backup_info)?;
+        Some(prev_backup_info)
+    } else {
+        let (last_backup_timestamp, last_operation_timestamp) = if doing_backup {
+            (now, ActiveValue::Set(s!("0")))
+        } else {
+            (ActiveValue::Set(s!("0")), now)
+        };
+        let backup_info = DbBackupInfoActMod {
+            last_backup_timestamp,
+            last_operation_timestamp,
+            last_processed_operation_idx,
+            ..Default::default()
+        };
+        txn.set_backup_info(backup_info)?;
+        None
+    })
+}
+
+// Record a remote backup push. Its timestamp is kept apart from the local backup one, which
+// incremental backups are based on.
+pub(crate) fn record_remote_backup_info(txn: &DbTxn) -> Result<Option<DbBackupInfo>, Error> {
+    let now = ActiveValue::Set(Some(now().unix_timestamp_nanos().to_string()));
+    Ok(if let Some(backup_info) = txn.get_backup_info()? {
+        let prev_backup_info = backup_info.clone();
+        let mut backup_info: DbBackupInfoActMod = backup_info.into();
+        backup_info.last_remote_backup_timestamp = now;
+        txn.update_backup_info(&mut backup_info)?;
+        Some(prev_backup_info)
+    } else {
+        let backup_info = DbBackupInfoActMod {
+            last_backup_timestamp: ActiveValue::Set(s!("0")),
+            last_operation_timestamp: ActiveValue::Set(s!("0")),
+            last_remote_backup_timestamp: now,
+            ..Default::default()
+        };
+        txn.set_backup_info(backup_info)?;
+        None
+    })
+}
+
+// Put back the backup info returned by record_backup_info after a failed backup.
+pub(crate) fn revert_backup_info(
+    txn: &DbTxn,
+    prev_backup_info: Option<DbBackupInfo>,
+) -> Result<(), Error> {
+    if let Some(prev_backup_info) = prev_backup_info {
+        let mut prev_backup_info: DbBackupInfoActMod = prev_backup_info.into();
+        txn.update_backup_info(&mut prev_backup_info)?;
+    } else {
+        txn.del_backup_info()?;
+    }
+    Ok(())
+}
+
 /// Restore a backup from the given file and password to the provided target directory.
 pub fn restore_backup(backup_path: &str, password: &str, target_dir: &str) -> Result<(), Error> {
+    restore_backup_chain(&[backup_path], password, target_dir)
+}
+
+/// Restore a chain of backups, encrypted with the provided password, to the provided target
+/// directory.
+///
+/// The first backup needs to be a full one, each of the following ones an incremental backup
+/// based on the one preceding it in the chain.
+pub fn restore_backup_chain(
+    backup_paths: &[&str],
+    password: &str,
+    target_dir: &str,
+) -> Result<(), Error> {
     // setup
     fs::create_dir_all(target_dir)?;
     let log_dir = Path::new(&target_dir);
     let log_name = format!("restore_{}", now().unix_timestamp());
     let (logger, _logger_guard) = setup_logger(log_dir, Some(&log_name))?;
     info!(logger, "starting restore...");
+    let target_dir_path = PathBuf::from(&target_dir);
+
+    // check the chain is complete before restoring anything
+    let mut prev_timestamp: Option<String> = None;
+    for (i, backup_path) in backup_paths.iter().enumerate() {
+        let backup_pub_data = read_backup_pub_data(&PathBuf::from(backup_path))?;
+        debug!(logger, "retrieved version: {}", &backup_pub_data.version);
+        if !(BACKUP_VERSION_MIN..=BACKUP_VERSION).contains(&backup_pub_data.version) {
+            return Err(Error::UnsupportedBackupVersion {
+                version: backup_pub_data.version.to_string(),
+            });
+        }
+        match (i, &backup_pub_data.base_timestamp) {
+            (0, Some(_)) => {
+                return Err(Error::InvalidBackupChain {
+                    details: format!("{backup_path} is not a full backup"),
+                });
+            }
+            (0, None) => {}
+            (_, None) => {
+                return Err(Error::InvalidBackupChain {
+                    details: format!("{backup_path} is not an incremental backup"),
+                });
+            }
+            (_, Some(base_timestamp)) => {
+                if prev_timestamp.as_ref() != Some(base_timestamp) {
+                    return Err(Error::InvalidBackupChain {
+                        details: format!("{backup_path} is not based on the previous backup"),
+                    });
+                }
+            }
+        }
+        prev_timestamp = backup_pub_data.timestamp;
+    }
+    let Some((full_backup_path, incremental_backup_paths)) = backup_paths.split_first() else {
+        return Err(Error::InvalidBackupChain {
+            details: s!("no backup provided"),
+        });
+    };
+
+    // restore the full backup
+    let files = decrypt_backup(full_backup_path, password, &logger)?;
+    let fingerprint = get_fingerprint_from_zip(&files.zip)?;
+    let wallet_dir = target_dir_path.join(&fingerprint);
+    if wallet_dir.exists() {
+        return Err(Error::WalletDirAlreadyExists {
+            path: wallet_dir.to_string_lossy().to_string(),
+        });
+    }
+    info!(
+        logger.clone(),
+        "unzipping {:?} to {:?}", &files.zip, &target_dir_path
+    );
+    unzip(&files.zip, &target_dir_path, &logger)?;
+    install_backup_manifest(&target_dir_path, &wallet_dir)?;
+
+    // replay the incremental backups, not leaving a partially restored wallet behind on failure
+    let replay = || -> Result<(), Error> {
+        for backup_path in incremental_backup_paths {
+            let files = decrypt_backup(backup_path, password, &logger)?;
+            if get_fingerprint_from_zip(&files.zip)? != fingerprint {
+                return Err(Error::InvalidBackupChain {
+                    details: format!("{backup_path} belongs to another wallet"),
+                });
+            }
+            info!(
+                logger.clone(),
+                "applying {:?} to {:?}", &files.zip, &target_dir_path
+            );
+            unzip(&files.zip, &target_dir_path, &logger)?;
+            let manifest =
+                install_backup_manifest(&target_dir_path, &wallet_dir)?.ok_or_else(|| {
+                    Error::InvalidBackupChain {
+                        details: format!("{backup_path} has no manifest"),
+                    }
+                })?;
+            remove_deleted_entries(&target_dir_path, &wallet_dir, &manifest, &logger)?;
+        }
+        Ok(())
+    };
+    if let Err(e) = replay() {
+        fs::remove_dir_all(&wallet_dir)?;
+        return Err(e);
+    }
+
+    info!(logger, "restore completed");
+    Ok(())
+}
+
+fn read_backup_pub_data(backup_file: &PathBuf) -> Result<BackupPubData, Error> {
+    let mut archive = get_zip_archive(backup_file)?;
+    let mut pub_data_file =
+        archive
+            .by_name("backup.pub_data")
+            .map_err(|_| Error::InvalidFilePath {
+                file_path: backup_file.to_string_lossy().to_string(),
+            })?;
+    let mut json_pub_data = String::new();
+    pub_data_file.read_to_string(&mut json_pub_data)?;
+    Ok(serde_json::from_str(json_pub_data.as_str()).map_err(InternalError::from)?)
+}
+
+// Unpack the given backup file and decrypt its content to the returned zip path.
+fn decrypt_backup(
+    backup_path: &str,
+    password: &str,
+    logger: &Logger,
+) -> Result<BackupPaths, Error> {
     let backup_file = PathBuf::from(backup_path);
     let tmp_base_path = get_parent_path(&backup_file)?;
     let files = get_backup_paths(&tmp_base_path)?;
-    let target_dir_path = PathBuf::from(&target_dir);
 
     // unpack given zip file and retrieve backup data
     info!(logger, "unzipping {:?}", backup_file);
-    unzip(&backup_file, &PathBuf::from(files.tempdir.path()), &logger)?;
-    let json_pub_data = fs::read_to_string(files.backup_pub_data)?;
+    unzip(&backup_file, &PathBuf::from(files.tempdir.path()), logger)?;
+    let json_pub_data = fs::read_to_string(&files.backup_pub_data)?;
     debug!(logger, "using retrieved backup_pub_data: {}", json_pub_data);
     let backup_pub_data: BackupPubData =
         serde_json::from_str(json_pub_data.as_str()).map_err(InternalError::from)?;
-    let version = backup_pub_data.version;
-    debug!(logger, "retrieved version: {}", &version);
-    if version != BACKUP_VERSION {
-        return Err(Error::UnsupportedBackupVersion {
-            version: version.to_string(),
-        });
-    }
 
     // decrypt backup
     info!(
@@ -270,23 +502,65 @@ pub fn restore_backup(backup_path: &str, password: &str, target_dir: &str) -> Re
     );
     decrypt_file(&files.encrypted, &files.zip, password, &backup_pub_data)?;
 
-    // check the target wallet directory doesn't already exist
-    let fingerprint = get_fingerprint_from_zip(&files.zip)?;
-    let wallet_dir = target_dir_path.join(fingerprint);
-    if wallet_dir.exists() {
-        return Err(Error::WalletDirAlreadyExists {
-            path: wallet_dir.to_string_lossy().to_string(),
-        });
+    Ok(files)
+}
+
+pub(crate) fn read_backup_manifest(manifest_path: &Path) -> Result<Option<BackupManifest>, Error> {
+    if !manifest_path.exists() {
+        return Ok(None);
     }
+    let json_manifest = fs::read_to_string(manifest_path)?;
+    Ok(Some(
+        serde_json::from_str(&json_manifest).map_err(InternalError::from)?,
+    ))
+}
 
-    // restore files
-    info!(
-        logger.clone(),
-        "unzipping {:?} to {:?}", &files.zip, &target_dir_path
-    );
-    unzip(&files.zip, &target_dir_path, &logger)?;
+// Move the manifest extracted from a backup to the restored wallet directory, so the wallet can
+// base incremental backups on it. Backups of version 1 have no manifest.
+fn install_backup_manifest(
+    target_dir: &Path,
+    wallet_dir: &Path,
+) -> Result<Option<BackupManifest>, Error> {
+    let extracted_path = target_dir.join(BACKUP_MANIFEST_FILE);
+    let manifest = read_backup_manifest(&extracted_path)?;
+    if manifest.is_some() {
+        fs::rename(extracted_path, wallet_dir.join(BACKUP_MANIFEST_FILE))?;
+    }
+    Ok(manifest)
+}
 
-    info!(logger, "restore completed");
+// Remove the wallet directory entries an incremental backup doesn't list in its manifest, as they
+// have been deleted after its base backup was taken.
+pub(crate) fn remove_deleted_entries(
+    target_dir: &Path,
+    wallet_dir: &Path,
+    manifest: &BackupManifest,
+    logger: &Logger,
+) -> Result<(), Error> {
+    let cbf_dir = wallet_dir.join(CBF_DIR);
+    let musig_nonces_file = wallet_dir.join(musig::MUSIG_NONCES_FILE);
+    for entry in WalkDir::new(wallet_dir)
+        .contents_first(true)
+        .into_iter()
+        .filter_entry(|e| e.path() != cbf_dir && e.path() != musig_nonces_file)
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if path.ends_with(LOG_FILE) || path.ends_with(BACKUP_MANIFEST_FILE) {
+            continue;
+        }
+        let name = path.strip_prefix(target_dir).map_err(InternalError::from)?;
+        let name_str = name.to_str().ok_or_else(|| InternalError::Unexpected)?;
+        if manifest.contains_key(name_str) {
+            continue;
+        }
+        debug!(logger, "removing deleted entry {path:?}");
+        if entry.file_type().is_dir() {
+            fs::remove_dir_all(path)?;
+        } else {
+            fs::remove_file(path)?;
+        }
+    }
     Ok(())
 }
 
@@ -320,6 +594,19 @@ pub(crate) fn zip_dir(
     keep_last_path_component: bool,
     logger: &Logger,
 ) -> Result<(), Error> {
+    zip_dir_with_manifest(path_in, path_out, keep_last_path_component, None, logger)?;
+    Ok(())
+}
+
+// Archive the content of path_in. When requested, the manifest of the entries is archived as well
+// and returned, skipping the files whose content hash matches the one in the given base manifest.
+pub(crate) fn zip_dir_with_manifest(
+    path_in: &PathBuf,
+    path_out: &PathBuf,
+    keep_last_path_component: bool,
+    base_manifest: Option<Option<&BackupManifest>>,
+    logger: &Logger,
+) -> Result<BackupManifest, Error> {
     // setup
     let writer = fs::File::create(path_out)?;
     let mut zip = ZipWriter::new(writer);
@@ -338,15 +625,34 @@ pub(crate) fn zip_dir(
     } else {
         path_in
     };
-    let entry_iterator = WalkDir::new(path_in).into_iter().filter_map(|e| e.ok());
+    let mut manifest = BackupManifest::new();
+    // downloaded block data can be downloaded again, so it's not archived, and MuSig2 secret
+    // nonces must never be restored, as using one twice leaks the signing key
+    let cbf_dir = path_in.join(CBF_DIR);
+    let musig_nonces_file = path_in.join(musig::MUSIG_NONCES_FILE);
+    let entry_iterator = WalkDir::new(path_in)
+        .into_iter()
+        .filter_entry(|e| e.path() != cbf_dir && e.path() != musig_nonces_file)
+        .filter_map(|e| e.ok());
     for entry in entry_iterator {
         let path = entry.path();
         let name = path.strip_prefix(prefix).map_err(InternalError::from)?;
         let name_str = name.to_str().ok_or_else(|| InternalError::Unexpected)?;
         if path.is_file() {
-            if path.ends_with(LOG_FILE) {
+            if path.ends_with(LOG_FILE) || path.ends_with(BACKUP_MANIFEST_FILE) {
                 continue;
-            }; // skip log file
+            }; // skip log and manifest files
+            if let Some(base_manifest) = base_manifest {
+                let digest = hash_file(path)?;
+                let unchanged = base_manifest
+                    .and_then(|m| m.get(name_str))
+                    .is_some_and(|d| d.as_ref() == Some(&digest));
+                manifest.insert(name_str.to_string(), Some(digest));
+                if unchanged {
+                    debug!(logger, "skipping unchanged file {path:?}");
+                    continue;
+                }
+            }
             debug!(logger, "adding file {path:?} as {name:?}");
             zip.start_file(name_str, options)
                 .map_err(InternalError::from)?;
@@ -360,18 +666,25 @@ pub(crate) fn zip_dir(
                 }
             }
         } else if !name.as_os_str().is_empty() {
+            manifest.insert(name_str.to_string(), None);
             debug!(logger, "adding directory {path:?} as {name:?}");
             zip.add_directory(name_str, options)
                 .map_err(InternalError::from)?;
         }
     }
+    if base_manifest.is_some() {
+        debug!(logger, "adding manifest as {BACKUP_MANIFEST_FILE}");
+        zip.start_file(BACKUP_MANIFEST_FILE, options)
+            .map_err(InternalError::from)?;
+        zip.write_all(&serde_json::to_vec(&manifest).map_err(InternalError::from)?)?;
+    }
 
     // finalize
     let mut file = zip.finish().map_err(InternalError::from)?;
     file.flush()?;
     file.sync_all()?;
 
-    Ok(())
+    Ok(manifest)
 }
 
 fn get_zip_archive(zip_path: &PathBuf) -> Result<ZipArchive<fs::File>, Error> {
@@ -383,10 +696,19 @@ fn get_zip_archive(zip_path: &PathBuf) -> Result<ZipArchive<fs::File>, Error> {
     })
 }
 
-fn get_fingerprint_from_zip(zip_path: &PathBuf) -> Result<String, Error> {
+pub(crate) fn get_fingerprint_from_zip(zip_path: &PathBuf) -> Result<String, Error> {
     let archive = get_zip_archive(zip_path)?;
-    let fingerprint = archive.name_for_index(0).unwrap_or_default();
-    Ok(fingerprint.to_string().replace("/", ""))
+    // the wallet directory is the only top-level directory, next to the backup metadata files
+    let wallet_dirs: HashSet<&str> = archive
+        .file_names()
+        .filter_map(|n| n.split_once('/').map(|(dir, _)| dir))
+        .collect();
+    match wallet_dirs.into_iter().collect::<Vec<_>>()[..] {
+        [fingerprint] => Ok(fingerprint.to_string()),
+        _ => Err(Error::InvalidFilePath {
+            file_path: zip_path.to_string_lossy().to_string(),
+        }),
+    }
 }
 
 pub(crate) fn unzip(zip_path: &PathBuf, path_out: &Path, logger: &Logger) -> Result<(), Error> {
@@ -421,14 +743,34 @@ pub(crate) fn unzip(zip_path: &PathBuf, path_out: &Path, logger: &Logger) -> Res
     Ok(())
 }
 
+pub(crate) fn generate_salt() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(24)
+        .map(char::from)
+        .collect()
+}
+
 fn get_cypher_secrets(password: &str, backup_pub_data: &BackupPubData) -> Result<Key, Error> {
+    derive_key(
+        password,
+        &backup_pub_data.salt,
+        &backup_pub_data.scrypt_params,
+    )
+}
+
+pub(crate) fn derive_key(
+    password: &str,
+    salt: &str,
+    scrypt_params: &ScryptParams,
+) -> Result<Key, Error> {
     // hash password using scrypt with the provided salt
     let password_bytes = password.as_bytes();
-    let salt = Salt::from_b64(&backup_pub_data.salt).map_err(InternalError::from)?;
-    let params = backup_pub_data.scrypt_params.clone().try_into()?;
+    let salt = Salt::from_b64(salt).map_err(InternalError::from)?;
+    let params = scrypt_params.clone().try_into()?;
     let mut hash = [0u8; BACKUP_KEY_LENGTH];
     scrypt(password_bytes, salt.as_ref(), &params, &mut hash).map_err(|_| Error::Internal {
-        details: s!("failed to derive backup encryption key"),
+        details: s!("failed to derive encryption key"),
     })?;
 
     // get key from password hash
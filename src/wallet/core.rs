@@ -4,7 +4,7 @@
 
 use super::*;
 
-const BDK_DB_NAME: &str = "bdk_db";
+pub(crate) const BDK_DB_NAME: &str = "bdk_db";
 
 pub(crate) const NUM_KNOWN_SCHEMAS: usize = 4;
 
@@ -47,7 +47,8 @@ impl WalletManifest {
         Self {
             version: WALLET_MANIFEST_VERSION,
             bitcoin_network: wallet_data.bitcoin_network,
-            database_type: wallet_data.database_type.clone(),
+            // the manifest is stored in plaintext, the database password is supplied again on load
+            database_type: wallet_data.database_type.without_credentials(),
             max_allocations_per_utxo: wallet_data.max_allocations_per_utxo,
             supported_schemas: wallet_data.supported_schemas.clone(),
             account_xpub_vanilla: keys.account_xpub_vanilla.clone(),
@@ -132,6 +133,7 @@ impl WalletManifest {
         self,
         data_dir: String,
         mnemonic: Option<String>,
+        encryption_password: Option<String>,
     ) -> (WalletData, SinglesigKeys) {
         (
             WalletData {
@@ -140,6 +142,7 @@ impl WalletManifest {
                 database_type: self.database_type,
                 max_allocations_per_utxo: self.max_allocations_per_utxo,
                 supported_schemas: self.supported_schemas,
+                encryption_password,
             },
             SinglesigKeys {
                 account_xpub_vanilla: self.account_xpub_vanilla,
@@ -154,7 +157,7 @@ impl WalletManifest {
 }
 
 /// Which keychain contributes SPKs to the sync request.
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SyncKeychain {
     /// Sync the colored keychain
@@ -167,7 +170,7 @@ pub enum SyncKeychain {
     },
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl SyncKeychain {
     fn keychain(&self) -> KeychainKind {
         match self {
@@ -178,7 +181,7 @@ impl SyncKeychain {
 }
 
 /// Strategy used to build the indexer sync request.
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SyncStrategy {
     /// BIP44 stop-gap full scan
@@ -192,7 +195,7 @@ pub enum SyncStrategy {
 }
 
 /// Options driving a single sync invocation.
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SyncOptions {
     /// Which keychain to sync
@@ -201,15 +204,47 @@ pub struct SyncOptions {
     pub strategy: SyncStrategy,
 }
 
+// The BDK wallet together with the store it persists to, shared so a background task can sync
+// the same wallet the user is operating on.
+pub struct BdkState {
+    pub(crate) wallet: PersistedWallet<BdkStore>,
+    pub(crate) database: BdkStore,
+}
+
+impl BdkState {
+    pub(crate) fn persist(&mut self) -> Result<(), Error> {
+        self.wallet.persist(&mut self.database)?;
+        Ok(())
+    }
+}
+
+// Lock on the shared BDK state, dereferencing to the BDK wallet.
+pub struct BdkWalletGuard<'a>(MutexGuard<'a, BdkState>);
+
+impl Deref for BdkWalletGuard<'_> {
+    type Target = PersistedWallet<BdkStore>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0.wallet
+    }
+}
+
+impl DerefMut for BdkWalletGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0.wallet
+    }
+}
+
 pub struct WalletInternals {
     pub(crate) wallet_data: WalletData,
     pub(crate) logger: Logger,
-    pub(crate) _logger_guard: AsyncGuard,
+    pub(crate) _logger_guard: Arc<AsyncGuard>,
     pub(crate) database: Arc<RgbLibDatabase>,
     pub(crate) wallet_dir: PathBuf,
-    pub(crate) bdk_wallet: PersistedWallet<Store<ChangeSet>>,
-    pub(crate) bdk_database: Store<ChangeSet>,
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    pub(crate) bdk: Arc<Mutex<BdkState>>,
+    // serializes the access to the MuSig2 secret nonces stored in the wallet directory
+    pub(crate) musig_nonces_lock: Arc<Mutex<()>>,
+    #[cfg(online)]
     pub(crate) online_data: Option<OnlineData>,
 }
 
@@ -237,18 +272,45 @@ pub(crate) fn setup_rgb<P: AsRef<Path>>(
     Ok(())
 }
 
-pub(crate) fn setup_db<P: AsRef<Path>>(wallet_dir: P) -> Result<RgbLibDatabase, Error> {
-    let db_path = wallet_dir.as_ref().join(RGB_LIB_DB_NAME);
-    let display_db_path = adjust_canonicalization(db_path);
-    let connection_string = format!("sqlite:{display_db_path}?mode=rwc");
-    let mut opt = ConnectOptions::new(connection_string);
+pub(crate) fn setup_db<P: AsRef<Path>>(
+    wallet_dir: P,
+    database_type: &DatabaseType,
+    key: Option<&Key>,
+) -> Result<RgbLibDatabase, Error> {
+    let mut opt = match database_type {
+        DatabaseType::Sqlite => {
+            let db_path = wallet_dir.as_ref().join(RGB_LIB_DB_NAME);
+            let display_db_path = adjust_canonicalization(db_path);
+            let mut opt = ConnectOptions::new(format!("sqlite:{display_db_path}?mode=rwc"));
+            if let Some(key) = key {
+                let pragma_key = sqlcipher_key(key);
+                opt.map_sqlx_sqlite_opts(move |opts| opts.pragma("key", pragma_key.clone()));
+            }
+            opt
+        }
+        DatabaseType::Postgres { url, schema } => {
+            let mut opt = ConnectOptions::new(url);
+            opt.set_schema_search_path(format!("\"{}\"", schema.replace('"', "\"\"")));
+            opt
+        }
+    };
     opt.max_connections(1)
         .min_connections(0)
         .connect_timeout(Duration::from_secs(8))
         .idle_timeout(Duration::from_secs(8))
         .max_lifetime(Duration::from_secs(8));
-    let db_cnn = block_on(Database::connect(opt));
-    let connection = db_cnn?;
+    let connection = block_on(Database::connect(opt))?;
+    if key.is_some() {
+        // SQLCipher only notices a wrong key once the database is read
+        block_on(connection.execute_unprepared("SELECT count(*) FROM sqlite_master"))
+            .map_err(|_| Error::WrongPassword)?;
+    }
+    if let DatabaseType::Postgres { schema, .. } = database_type {
+        block_on(connection.execute_unprepared(&format!(
+            "CREATE SCHEMA IF NOT EXISTS \"{}\"",
+            schema.replace('"', "\"\"")
+        )))?;
+    }
     block_on(Migrator::up(&connection, None))?;
     Ok(RgbLibDatabase::new(connection))
 }
@@ -260,7 +322,8 @@ pub(crate) fn setup_bdk<P: AsRef<Path>>(
     desc_vanilla: String,
     watch_only: bool,
     bdk_network: BdkNetwork,
-) -> Result<(PersistedWallet<Store<ChangeSet>>, Store<ChangeSet>), Error> {
+    key: Option<&Key>,
+) -> Result<BdkState, Error> {
     let chain_net: ChainNet = wallet_data.bitcoin_network.into();
     let mut wallet_params = BdkWallet::load()
         .descriptor(KeychainKind::External, Some(desc_colored.clone()))
@@ -275,15 +338,22 @@ pub(crate) fn setup_bdk<P: AsRef<Path>>(
         BDK_DB_NAME.to_string()
     };
     let bdk_db_path = wallet_dir.as_ref().join(bdk_db_name);
-    let (mut bdk_database, _) =
-        Store::<ChangeSet>::load_or_create(BDK_DB_NAME.as_bytes(), bdk_db_path)?;
+    let mut bdk_database = if let Some(key) = key {
+        BdkStore::Encrypted(Box::new(EncryptedStore::new(bdk_db_path, key)))
+    } else {
+        let (store, _) = Store::<ChangeSet>::load_or_create(BDK_DB_NAME.as_bytes(), bdk_db_path)?;
+        BdkStore::Plain(store)
+    };
     let bdk_wallet = match wallet_params.load_wallet(&mut bdk_database)? {
         Some(wallet) => wallet,
         None => BdkWallet::create(desc_colored, desc_vanilla)
             .network(bdk_network)
             .create_wallet(&mut bdk_database)?,
     };
-    Ok((bdk_wallet, bdk_database))
+    Ok(BdkState {
+        wallet: bdk_wallet,
+        database: bdk_database,
+    })
 }
 
 pub(crate) fn setup_new_wallet(
@@ -315,30 +385,38 @@ pub(crate) fn setup_new_wallet(
     Ok((wallet_dir, logger, logger_guard))
 }
 
+impl WalletInternals {
+    // Internals sharing the same wallet state, used by the background auto-refresh task
+    #[cfg(online)]
+    pub(crate) fn share(&self) -> Self {
+        Self {
+            wallet_data: self.wallet_data.clone(),
+            logger: self.logger.clone(),
+            _logger_guard: self._logger_guard.clone(),
+            database: self.database.clone(),
+            wallet_dir: self.wallet_dir.clone(),
+            bdk: self.bdk.clone(),
+            musig_nonces_lock: self.musig_nonces_lock.clone(),
+            online_data: None,
+        }
+    }
+}
+
 pub trait WalletCore {
     fn internals(&self) -> &WalletInternals;
 
     fn internals_mut(&mut self) -> &mut WalletInternals;
 
-    fn bdk_wallet(&self) -> &PersistedWallet<Store<ChangeSet>> {
-        &self.internals().bdk_wallet
+    fn bdk(&self) -> MutexGuard<'_, BdkState> {
+        self.internals().bdk.lock().expect("lock poisoned")
     }
 
-    fn bdk_wallet_mut(&mut self) -> &mut PersistedWallet<Store<ChangeSet>> {
-        &mut self.internals_mut().bdk_wallet
+    fn bdk_wallet(&self) -> BdkWalletGuard<'_> {
+        BdkWalletGuard(self.bdk())
     }
 
-    fn bdk_wallet_db_mut(
-        &mut self,
-    ) -> (
-        &mut PersistedWallet<Store<ChangeSet>>,
-        &mut Store<ChangeSet>,
-    ) {
-        let internals_mut = self.internals_mut();
-        (
-            &mut internals_mut.bdk_wallet,
-            &mut internals_mut.bdk_database,
-        )
+    fn bdk_wallet_mut(&mut self) -> BdkWalletGuard<'_> {
+        BdkWalletGuard(self.bdk())
     }
 
     fn database(&self) -> &RgbLibDatabase {
@@ -357,27 +435,52 @@ pub trait WalletCore {
         &self.internals().wallet_dir
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn online_data(&self) -> &Option<OnlineData> {
         &self.internals().online_data
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn online_data_mut(&mut self) -> &mut Option<OnlineData> {
         &mut self.internals_mut().online_data
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn indexer(&self) -> &Indexer {
         &self.online_data().as_ref().unwrap().indexer
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn vanilla_sync_lookback(&self) -> u32 {
         self.online_data().as_ref().unwrap().vanilla_sync_lookback
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
+    fn socks5_proxy(
+        &self,
+        service: NetworkService,
+        isolation_key: Option<&str>,
+    ) -> Option<Socks5Proxy> {
+        self.online_data()
+            .as_ref()
+            .unwrap()
+            .network_options
+            .socks5_proxy(service, isolation_key)
+    }
+
+    #[cfg(online)]
+    fn network_policy(&self) -> &NetworkPolicy {
+        &self.online_data().as_ref().unwrap().network_options.policy
+    }
+
+    /// Policy paths, per keychain, to set on the TX builder to select the spending conditions to
+    /// satisfy. Empty when descriptors have a single spending path.
+    #[cfg(online)]
+    fn spending_policy_paths(&self) -> Vec<(KeychainKind, BTreeMap<String, Vec<usize>>)> {
+        vec![]
+    }
+
+    #[cfg(online)]
     fn check_online(&self, online: Online) -> Result<(), Error> {
         if let Some(online_data) = &self.online_data() {
             if online_data.id != online.id {
@@ -391,7 +494,7 @@ pub trait WalletCore {
         Ok(())
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn fast_sync_colored_spks(&self, txn: &DbTxn) -> Result<HashSet<ScriptBuf>, Error> {
         let mut spks: HashSet<ScriptBuf> = HashSet::new();
         for pws in txn.iter_pending_witness_scripts()? {
@@ -400,9 +503,10 @@ pub trait WalletCore {
         Ok(spks)
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn fast_sync_vanilla_spks(&self, lookback: u32) -> HashSet<ScriptBuf> {
-        let spk_index = self.bdk_wallet().spk_index();
+        let bdk_wallet = self.bdk_wallet();
+        let spk_index = bdk_wallet.spk_index();
         let Some(last_revealed) = spk_index.last_revealed_index(KeychainKind::Internal) else {
             return HashSet::new();
         };
@@ -417,12 +521,12 @@ pub trait WalletCore {
             .collect()
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn unconfirmed_colored_spks(&self) -> HashSet<ScriptBuf> {
-        let spk_index = self.bdk_wallet().spk_index();
+        let bdk_wallet = self.bdk_wallet();
+        let spk_index = bdk_wallet.spk_index();
         let mut spks: HashSet<ScriptBuf> = HashSet::new();
-        for tx in self
-            .bdk_wallet()
+        for tx in bdk_wallet
             .transactions()
             .filter(|tx| matches!(tx.chain_position, ChainPosition::Unconfirmed { .. }))
         {
@@ -439,7 +543,7 @@ pub trait WalletCore {
         spks
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn sync_bdk_and_db_txos(
         &mut self,
         txn: &DbTxn,
@@ -452,11 +556,13 @@ pub trait WalletCore {
         let latest_checkpoint = self.bdk_wallet().latest_checkpoint();
         let update: Update = match options.strategy {
             SyncStrategy::FullScan => {
-                let mut iters = self.bdk_wallet().spk_index().all_unbounded_spk_iters();
-                let iter = iters.remove(&kc).expect("keychain must exist");
-                let request = FullScanRequest::builder()
-                    .chain_tip(latest_checkpoint)
-                    .spks_for_keychain(kc, iter);
+                let request = || {
+                    let mut iters = self.bdk_wallet().spk_index().all_unbounded_spk_iters();
+                    let iter = iters.remove(&kc).expect("keychain must exist");
+                    FullScanRequest::builder()
+                        .chain_tip(latest_checkpoint.clone())
+                        .spks_for_keychain(kc, iter)
+                };
                 self.indexer().full_scan(request)?.into()
             }
             SyncStrategy::FullSync => {
@@ -466,9 +572,11 @@ pub trait WalletCore {
                     .revealed_keychain_spks(kc)
                     .map(|(_, spk)| spk)
                     .collect();
-                let request = SyncRequest::builder()
-                    .chain_tip(latest_checkpoint)
-                    .spks(spks);
+                let request = || {
+                    SyncRequest::builder()
+                        .chain_tip(latest_checkpoint.clone())
+                        .spks(spks.clone())
+                };
                 self.indexer().sync(request)?.into()
             }
             SyncStrategy::FastSync => {
@@ -482,19 +590,22 @@ pub trait WalletCore {
                         spks.extend(self.fast_sync_vanilla_spks(lookback));
                     }
                 }
-                let request = SyncRequest::builder()
-                    .chain_tip(latest_checkpoint)
-                    .spks(spks);
+                let request = || {
+                    SyncRequest::builder()
+                        .chain_tip(latest_checkpoint.clone())
+                        .spks(spks.clone())
+                };
                 self.indexer().sync(request)?.into()
             }
         };
-        let (bdk_wallet, bdk_db) = self.bdk_wallet_db_mut();
-        bdk_wallet
+        let mut bdk = self.bdk();
+        bdk.wallet
             .apply_update(update)
             .map_err(|e| Error::FailedBdkSync {
                 details: e.to_string(),
             })?;
-        bdk_wallet.persist(bdk_db)?;
+        bdk.persist()?;
+        drop(bdk);
 
         if matches!(options.keychain, SyncKeychain::Colored) {
             self.update_db_colored_txos_from_bdk(txn, include_spent)?;
@@ -504,7 +615,7 @@ pub trait WalletCore {
         Ok(())
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn update_db_colored_txos_from_bdk(
         &mut self,
         txn: &DbTxn,
@@ -524,13 +635,14 @@ pub trait WalletCore {
             .map(|s| s.script)
             .collect();
 
-        let iter: Box<dyn Iterator<Item = LocalOutput>> = if include_spent {
-            Box::new(self.bdk_wallet().list_output())
+        let outputs: Vec<LocalOutput> = if include_spent {
+            self.bdk_wallet().list_output().collect()
         } else {
-            Box::new(self.bdk_wallet().list_unspent())
+            self.bdk_wallet().list_unspent().collect()
         };
 
-        for new_utxo in iter
+        for new_utxo in outputs
+            .into_iter()
             .filter(|u| u.keychain == KeychainKind::External)
             .filter(|u| !db_outpoints.contains(&u.outpoint.to_string()))
         {
@@ -543,12 +655,15 @@ pub trait WalletCore {
                 }
             }
             txn.set_txo(new_db_utxo.clone())?;
+            txn.emit_event(WalletEvent::UtxoCreated {
+                outpoint: new_utxo.outpoint.into(),
+            });
         }
 
         Ok(())
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn sync_wallet(
         &mut self,
         txn: &DbTxn,
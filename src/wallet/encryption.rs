@@ -0,0 +1,149 @@
+use super::*;
+
+pub(crate) const WALLET_ENCRYPTION_FILE: &str = "encryption.json";
+const ENCRYPTED_STORE_NONCE_LENGTH: usize = 24;
+
+// Public parameters used to derive the key that encrypts the wallet databases, stored in plaintext
+// inside the wallet directory. The password itself is never persisted.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct WalletEncryption {
+    scrypt_params: ScryptParams,
+    salt: String,
+}
+
+impl WalletEncryption {
+    fn path(wallet_dir: &Path) -> PathBuf {
+        wallet_dir.join(WALLET_ENCRYPTION_FILE)
+    }
+
+    // Return the key for the databases of the wallet in wallet_dir, if encryption is enabled,
+    // generating the key parameters when the wallet is first created. The password is taken out
+    // of wallet_data, so the wallet doesn't keep it once the key has been derived.
+    pub(crate) fn setup(
+        wallet_dir: &Path,
+        wallet_data: &mut WalletData,
+    ) -> Result<Option<Key>, Error> {
+        let path = Self::path(wallet_dir);
+        let Some(password) = wallet_data.encryption_password.take() else {
+            if path.exists() {
+                return Err(Error::PasswordRequired);
+            }
+            return Ok(None);
+        };
+        if !cfg!(feature = "sqlcipher")
+            || !matches!(wallet_data.database_type, DatabaseType::Sqlite)
+        {
+            return Err(Error::UnsupportedDatabaseEncryption);
+        }
+        let encryption = if path.exists() {
+            let json = fs::read_to_string(&path)?;
+            serde_json::from_str(&json).map_err(InternalError::from)?
+        } else {
+            // databases already written in plaintext can't be switched to encryption
+            if wallet_dir.join(RGB_LIB_DB_NAME).exists() {
+                return Err(Error::WalletSettingMismatch {
+                    setting: s!("encryption_password"),
+                    expected: s!("None"),
+                    provided: s!("Some"),
+                });
+            }
+            let encryption = WalletEncryption {
+                scrypt_params: ScryptParams::default(),
+                salt: generate_salt(),
+            };
+            let json = serde_json::to_string_pretty(&encryption).map_err(InternalError::from)?;
+            fs::write(&path, json)?;
+            encryption
+        };
+        Ok(Some(derive_key(
+            &password,
+            &encryption.salt,
+            &encryption.scrypt_params,
+        )?))
+    }
+}
+
+// Value for the SQLCipher key pragma, passing the raw key so SQLCipher skips its own derivation.
+pub(crate) fn sqlcipher_key(key: &Key) -> String {
+    format!("\"x'{}'\"", hex::encode(key.as_slice()))
+}
+
+// BDK changeset store kept encrypted on disk. The aggregated changeset is encrypted again as a
+// whole on every persist, which is affordable given the size of a wallet changeset.
+pub struct EncryptedStore {
+    path: PathBuf,
+    aead: XChaCha20Poly1305,
+    changeset: ChangeSet,
+}
+
+impl EncryptedStore {
+    pub(crate) fn new(path: PathBuf, key: &Key) -> Self {
+        Self {
+            path,
+            aead: XChaCha20Poly1305::new(key),
+            changeset: ChangeSet::default(),
+        }
+    }
+
+    fn read(&self) -> Result<ChangeSet, Error> {
+        if !self.path.exists() {
+            return Ok(ChangeSet::default());
+        }
+        let data = fs::read(&self.path)?;
+        if data.len() < ENCRYPTED_STORE_NONCE_LENGTH {
+            return Err(Error::IO {
+                details: format!("truncated BDK store {:?}", self.path),
+            });
+        }
+        let (nonce, ciphertext) = data.split_at(ENCRYPTED_STORE_NONCE_LENGTH);
+        let nonce: [u8; ENCRYPTED_STORE_NONCE_LENGTH] =
+            nonce.try_into().expect("split at the nonce length");
+        let cleartext = self
+            .aead
+            .decrypt(&XNonce::from(nonce), ciphertext)
+            .map_err(|_| Error::WrongPassword)?;
+        Ok(serde_json::from_slice(&cleartext).map_err(InternalError::from)?)
+    }
+
+    fn write(&self) -> Result<(), Error> {
+        let cleartext = serde_json::to_vec(&self.changeset).map_err(InternalError::from)?;
+        let mut nonce = [0u8; ENCRYPTED_STORE_NONCE_LENGTH];
+        rand::rng().fill(&mut nonce[..]);
+        let ciphertext = self
+            .aead
+            .encrypt(&XNonce::from(nonce), cleartext.as_slice())
+            .expect("changeset size is within XChaCha20Poly1305 limits");
+
+        write_file_atomically(&self.path, &[&nonce, &ciphertext])
+    }
+}
+
+// The store the BDK wallet persists its changesets to.
+pub enum BdkStore {
+    Plain(Store<ChangeSet>),
+    Encrypted(Box<EncryptedStore>),
+}
+
+impl WalletPersister for BdkStore {
+    type Error = Error;
+
+    fn initialize(persister: &mut Self) -> Result<ChangeSet, Error> {
+        match persister {
+            BdkStore::Plain(store) => Ok(WalletPersister::initialize(store)?),
+            BdkStore::Encrypted(store) => {
+                store.changeset = store.read()?;
+                Ok(store.changeset.clone())
+            }
+        }
+    }
+
+    fn persist(persister: &mut Self, changeset: &ChangeSet) -> Result<(), Error> {
+        match persister {
+            BdkStore::Plain(store) => Ok(WalletPersister::persist(store, changeset)?),
+            BdkStore::Encrypted(store) => {
+                store.changeset.merge(changeset.clone());
+                store.write()
+            }
+        }
+    }
+}
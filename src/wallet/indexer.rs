@@ -7,28 +7,51 @@ use super::*;
 /// Indexer for a wallet.
 #[non_exhaustive]
 pub enum Indexer {
+    /// Bitcoind indexer
+    #[cfg(feature = "bitcoind")]
+    Bitcoind(Box<BitcoindClient>),
+    /// Compact block filters light client
+    #[cfg(feature = "cbf")]
+    Cbf(Arc<CbfClient>),
     /// Electrum indexer
     #[cfg(feature = "electrum")]
     Electrum(Box<BdkElectrumClient<ElectrumClient>>),
     /// Esplora indexer
     #[cfg(feature = "esplora")]
     Esplora(Box<EsploraClient>),
+    /// Multiple indexers, with failover and optional cross-checking
+    Multi(Box<MultiIndexer>),
 }
 
 impl Indexer {
     pub(crate) fn block_hash(&self, height: usize) -> Result<String, IndexerError> {
         Ok(match self {
+            #[cfg(feature = "bitcoind")]
+            Indexer::Bitcoind(client) => client.get_block_hash(height as u32)?.to_string(),
+            #[cfg(feature = "cbf")]
+            Indexer::Cbf(client) => client.get_block_hash(height as u32)?.to_string(),
             #[cfg(feature = "electrum")]
             Indexer::Electrum(client) => {
                 client.inner.block_header(height)?.block_hash().to_string()
             }
             #[cfg(feature = "esplora")]
             Indexer::Esplora(client) => client.get_block_hash(height as u32)?.to_string(),
+            Indexer::Multi(multi) => multi.block_hash(height)?,
         })
     }
 
     pub(crate) fn broadcast(&self, tx: &BdkTransaction) -> Result<(), IndexerError> {
         match self {
+            #[cfg(feature = "bitcoind")]
+            Indexer::Bitcoind(client) => {
+                client.send_raw_transaction(tx)?;
+                Ok(())
+            }
+            #[cfg(feature = "cbf")]
+            Indexer::Cbf(client) => {
+                client.broadcast(tx)?;
+                Ok(())
+            }
             #[cfg(feature = "electrum")]
             Indexer::Electrum(client) => {
                 client.transaction_broadcast(tx)?;
@@ -39,11 +62,27 @@ impl Indexer {
                 client.broadcast(tx)?;
                 Ok(())
             }
+            Indexer::Multi(multi) => multi.failover(
+                |indexer| indexer.broadcast(tx),
+                IndexerError::is_connection_error,
+            ),
         }
     }
 
+    #[cfg_attr(
+        not(any(feature = "electrum", feature = "esplora", feature = "bitcoind")),
+        allow(unreachable_code, clippy::only_used_in_recursion)
+    )]
     pub(crate) fn fee_estimation(&self, blocks: u16) -> Result<f64, Error> {
         Ok(match self {
+            #[cfg(feature = "bitcoind")]
+            Indexer::Bitcoind(client) => client
+                .estimate_smart_fee(blocks)
+                .map_err(IndexerError::from)?
+                .ok_or(Error::CannotEstimateFees)?,
+            // peers don't share their mempool, so there's no data to estimate fees from
+            #[cfg(feature = "cbf")]
+            Indexer::Cbf(_) => return Err(Error::CannotEstimateFees),
             #[cfg(feature = "electrum")]
             Indexer::Electrum(client) => {
                 let estimate = client
@@ -55,6 +94,9 @@ impl Indexer {
                 }
                 (estimate * 100_000_000.0) / 1_000.0
             }
+            Indexer::Multi(multi) => {
+                multi.failover(|indexer| indexer.fee_estimation(blocks), is_indexer_error)?
+            }
             #[cfg(feature = "esplora")]
             Indexer::Esplora(client) => {
                 let estimate_map = client.get_fee_estimates().map_err(IndexerError::from)?; // in sat/vB
@@ -103,9 +145,22 @@ impl Indexer {
 
     pub(crate) fn full_scan<K: Ord + Clone, R: Into<FullScanRequest<K>>>(
         &self,
-        request: R,
+        request: impl Fn() -> R,
     ) -> Result<FullScanResponse<K>, IndexerError> {
+        if let Indexer::Multi(multi) = self {
+            // going through a trait object keeps the recursion from instantiating new types
+            let request: &dyn Fn() -> R = &request;
+            return multi.failover(
+                |indexer| indexer.full_scan(request),
+                IndexerError::is_connection_error,
+            );
+        }
+        let request = request();
         match self {
+            #[cfg(feature = "bitcoind")]
+            Indexer::Bitcoind(client) => Ok(client.full_scan(request)?),
+            #[cfg(feature = "cbf")]
+            Indexer::Cbf(client) => Ok(client.full_scan(request)?),
             #[cfg(feature = "electrum")]
             Indexer::Electrum(client) => {
                 Ok(client.full_scan(request, INDEXER_STOP_GAP, INDEXER_BATCH_SIZE, true)?)
@@ -114,11 +169,16 @@ impl Indexer {
             Indexer::Esplora(client) => client
                 .full_scan(request, INDEXER_STOP_GAP, INDEXER_PARALLEL_REQUESTS)
                 .map_err(|e| IndexerError::from(*e)),
+            Indexer::Multi(_) => unreachable!("already handled"),
         }
     }
 
     pub(crate) fn get_latest_block_height(&self) -> Result<u32, Error> {
         Ok(match self {
+            #[cfg(feature = "bitcoind")]
+            Indexer::Bitcoind(client) => client.get_block_count().map_err(IndexerError::from)?,
+            #[cfg(feature = "cbf")]
+            Indexer::Cbf(client) => client.get_tip_height().map_err(IndexerError::from)?,
             #[cfg(feature = "electrum")]
             Indexer::Electrum(client) => {
                 let header = client
@@ -131,11 +191,35 @@ impl Indexer {
             }
             #[cfg(feature = "esplora")]
             Indexer::Esplora(client) => client.get_height().map_err(IndexerError::from)?,
+            Indexer::Multi(multi) => multi.failover(
+                |indexer| indexer.get_latest_block_height(),
+                is_indexer_error,
+            )?,
         })
     }
 
     pub(crate) fn get_tx_confirmations(&self, txid: &str) -> Result<Option<u64>, Error> {
         Ok(match self {
+            #[cfg(feature = "bitcoind")]
+            Indexer::Bitcoind(client) => {
+                let txid = Txid::from_str(txid).unwrap();
+                match client.get_tx_info(&txid) {
+                    // TXs with negative confirmations conflict with the chain
+                    Ok(info) => info
+                        .filter(|i| i.confirmations >= 0)
+                        .map(|i| i.confirmations as u64),
+                    Err(BitcoindError::Rpc { ref message, .. })
+                        if message.contains("genesis block coinbase") =>
+                    {
+                        Some(u64::MAX)
+                    }
+                    Err(e) => return Err(IndexerError::from(e).into()),
+                }
+            }
+            #[cfg(feature = "cbf")]
+            Indexer::Cbf(client) => client
+                .get_tx_confirmations(&Txid::from_str(txid).unwrap())
+                .map_err(IndexerError::from)?,
             #[cfg(feature = "electrum")]
             Indexer::Electrum(client) => {
                 let tx_details = match client.inner.raw_call(
@@ -167,6 +251,7 @@ impl Indexer {
                     Some(0)
                 }
             }
+            Indexer::Multi(multi) => multi.get_tx_confirmations(txid)?,
             #[cfg(feature = "esplora")]
             Indexer::Esplora(client) => {
                 let txid = Txid::from_str(txid).unwrap();
@@ -194,33 +279,284 @@ impl Indexer {
         })
     }
 
-    pub(crate) fn populate_tx_cache(
-        &self,
-        #[cfg_attr(feature = "esplora", allow(unused))] bdk_wallet: &PersistedWallet<
-            Store<ChangeSet>,
-        >,
-    ) {
+    #[cfg_attr(not(feature = "electrum"), allow(clippy::only_used_in_recursion))]
+    pub(crate) fn populate_tx_cache(&self, bdk_wallet: &PersistedWallet<BdkStore>) {
         match self {
+            #[cfg(feature = "bitcoind")]
+            Indexer::Bitcoind(_) => {}
+            #[cfg(feature = "cbf")]
+            Indexer::Cbf(_) => {}
             #[cfg(feature = "electrum")]
             Indexer::Electrum(client) => {
                 client.populate_tx_cache(bdk_wallet.tx_graph().full_txs().map(|tx_node| tx_node.tx))
             }
             #[cfg(feature = "esplora")]
             Indexer::Esplora(_) => {}
+            Indexer::Multi(multi) => multi
+                .indexers
+                .iter()
+                .for_each(|indexer| indexer.populate_tx_cache(bdk_wallet)),
         }
     }
 
-    pub(crate) fn sync<I: 'static>(
+    pub(crate) fn sync<I: 'static, R: Into<SyncRequest<I>>>(
         &self,
-        request: impl Into<SyncRequest<I>>,
+        request: impl Fn() -> R,
     ) -> Result<SyncResponse, IndexerError> {
+        if let Indexer::Multi(multi) = self {
+            // going through a trait object keeps the recursion from instantiating new types
+            let request: &dyn Fn() -> R = &request;
+            return multi.failover(
+                |indexer| indexer.sync(request),
+                IndexerError::is_connection_error,
+            );
+        }
+        let request = request();
         match self {
+            #[cfg(feature = "bitcoind")]
+            Indexer::Bitcoind(client) => Ok(client.sync(request)?),
+            #[cfg(feature = "cbf")]
+            Indexer::Cbf(client) => Ok(client.sync(request)?),
             #[cfg(feature = "electrum")]
             Indexer::Electrum(client) => Ok(client.sync(request, INDEXER_BATCH_SIZE, true)?),
             #[cfg(feature = "esplora")]
             Indexer::Esplora(client) => client
                 .sync(request, INDEXER_PARALLEL_REQUESTS)
                 .map_err(|e| IndexerError::from(*e)),
+            Indexer::Multi(_) => unreachable!("already handled"),
+        }
+    }
+}
+
+fn is_indexer_error(e: &Error) -> bool {
+    matches!(e, Error::Indexer { .. })
+}
+
+/// Indexers sorted by priority.
+///
+/// Requests go to the indexer that last answered (the first one initially) and fail over to the
+/// next ones on connection errors. If a quorum is set, TX confirmations and block hashes are
+/// instead requested to all indexers and the answer is accepted only if confirmed by at least
+/// `quorum` of them.
+pub struct MultiIndexer {
+    indexers: Vec<Indexer>,
+    active: Mutex<usize>,
+    quorum: Option<u8>,
+}
+
+impl MultiIndexer {
+    pub(crate) fn new(indexers: Vec<Indexer>, quorum: Option<u8>) -> Self {
+        Self {
+            indexers,
+            active: Mutex::new(0),
+            quorum,
+        }
+    }
+
+    fn failover<T, E>(
+        &self,
+        op: impl Fn(&Indexer) -> Result<T, E>,
+        can_retry: impl Fn(&E) -> bool,
+    ) -> Result<T, E> {
+        let start = *self.active.lock().unwrap();
+        let mut last_err = None;
+        for i in 0..self.indexers.len() {
+            let idx = (start + i) % self.indexers.len();
+            match op(&self.indexers[idx]) {
+                Ok(res) => {
+                    *self.active.lock().unwrap() = idx;
+                    return Ok(res);
+                }
+                Err(e) if can_retry(&e) => last_err = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_err.expect("there should be at least one indexer"))
+    }
+
+    fn quorum_answers<T, E: fmt::Display>(
+        &self,
+        quorum: u8,
+        op: impl Fn(&Indexer) -> Result<T, E>,
+    ) -> Result<Vec<T>, IndexerError> {
+        let (mut answers, mut errors) = (vec![], vec![]);
+        for indexer in &self.indexers {
+            match op(indexer) {
+                Ok(answer) => answers.push(answer),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        if answers.len() < quorum as usize {
+            return Err(IndexerError::Quorum(format!(
+                "only {} indexers answered, errors: {}",
+                answers.len(),
+                errors.join(", ")
+            )));
+        }
+        Ok(answers)
+    }
+
+    fn block_hash(&self, height: usize) -> Result<String, IndexerError> {
+        let Some(quorum) = self.quorum else {
+            return self.failover(
+                |indexer| indexer.block_hash(height),
+                IndexerError::is_connection_error,
+            );
+        };
+        let answers = self.quorum_answers(quorum, |indexer| indexer.block_hash(height))?;
+        answers
+            .iter()
+            .find(|hash| answers.iter().filter(|h| h == hash).count() >= quorum as usize)
+            .cloned()
+            .ok_or_else(|| {
+                IndexerError::Quorum(format!(
+                    "indexers disagree on the hash of the block at height {height}"
+                ))
+            })
+    }
+
+    fn get_tx_confirmations(&self, txid: &str) -> Result<Option<u64>, Error> {
+        let Some(quorum) = self.quorum else {
+            return self.failover(
+                |indexer| indexer.get_tx_confirmations(txid),
+                is_indexer_error,
+            );
+        };
+        let mut answers =
+            self.quorum_answers(quorum, |indexer| indexer.get_tx_confirmations(txid))?;
+        // return the most confirmations reported by at least `quorum` indexers
+        answers.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(answers[quorum as usize - 1])
+    }
+}
+
+/// Witness resolvers sorted by priority, queried like the indexers of a [`MultiIndexer`].
+pub struct MultiResolver {
+    resolvers: Vec<WitnessResolver>,
+    active: Mutex<usize>,
+    quorum: Option<u8>,
+}
+
+impl MultiResolver {
+    pub(crate) fn new(resolvers: Vec<WitnessResolver>, quorum: Option<u8>) -> Self {
+        Self {
+            resolvers,
+            active: Mutex::new(0),
+            quorum,
+        }
+    }
+}
+
+impl ResolveWitness for MultiResolver {
+    fn resolve_witness(&self, witness_id: RgbTxid) -> Result<WitnessStatus, WitnessResolverError> {
+        let Some(quorum) = self.quorum else {
+            let start = *self.active.lock().unwrap();
+            let mut last_err = None;
+            for i in 0..self.resolvers.len() {
+                let idx = (start + i) % self.resolvers.len();
+                match self.resolvers[idx].resolve_witness(witness_id) {
+                    Ok(status) => {
+                        *self.active.lock().unwrap() = idx;
+                        return Ok(status);
+                    }
+                    Err(e @ WitnessResolverError::ResolverIssue(..)) => last_err = Some(e),
+                    Err(e) => return Err(e),
+                }
+            }
+            return Err(last_err.expect("there should be at least one resolver"));
+        };
+        let mut answers: Vec<WitnessStatus> = vec![];
+        let mut errors = vec![];
+        for resolver in &self.resolvers {
+            match resolver.resolve_witness(witness_id) {
+                Ok(status) => answers.push(status),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        if answers.len() < quorum as usize {
+            return Err(WitnessResolverError::ResolverIssue(
+                Some(witness_id),
+                format!(
+                    "quorum not reached, only {} resolvers answered, errors: {}",
+                    answers.len(),
+                    errors.join(", ")
+                ),
+            ));
+        }
+        // return the status (with TX and its position) reported by at least `quorum` resolvers
+        answers
+            .iter()
+            .find(|status| answers.iter().filter(|s| s == status).count() >= quorum as usize)
+            .cloned()
+            .ok_or_else(|| {
+                WitnessResolverError::ResolverIssue(
+                    Some(witness_id),
+                    s!("quorum not reached, resolvers disagree on the witness status"),
+                )
+            })
+    }
+
+    fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
+        self.resolvers
+            .iter()
+            .try_for_each(|resolver| resolver.check_chain_net(chain_net))
+    }
+}
+
+/// Resolver of the witness TXs for an online wallet.
+pub enum WitnessResolver {
+    /// Resolver provided by the RGB library
+    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    Any(AnyResolver),
+    /// Resolver querying a bitcoind node
+    #[cfg(feature = "bitcoind")]
+    Bitcoind(Box<BitcoindClient>),
+    /// Resolver using the compact block filters light client
+    #[cfg(feature = "cbf")]
+    Cbf(Arc<CbfClient>),
+    /// Multiple resolvers, with failover and optional cross-checking
+    Multi(Box<MultiResolver>),
+}
+
+impl WitnessResolver {
+    // let the resolver know about a witness TX, for resolvers that cannot look up TXs by ID
+    #[cfg_attr(not(feature = "cbf"), allow(clippy::only_used_in_recursion))]
+    pub(crate) fn watch_tx(&self, tx: &BdkTransaction) {
+        match self {
+            #[cfg(feature = "cbf")]
+            WitnessResolver::Cbf(client) => client.watch_tx(tx),
+            WitnessResolver::Multi(multi) => multi
+                .resolvers
+                .iter()
+                .for_each(|resolver| resolver.watch_tx(tx)),
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
+}
+
+impl ResolveWitness for WitnessResolver {
+    fn resolve_witness(&self, witness_id: RgbTxid) -> Result<WitnessStatus, WitnessResolverError> {
+        match self {
+            #[cfg(any(feature = "electrum", feature = "esplora"))]
+            WitnessResolver::Any(resolver) => resolver.resolve_witness(witness_id),
+            #[cfg(feature = "bitcoind")]
+            WitnessResolver::Bitcoind(resolver) => resolver.resolve_witness(witness_id),
+            #[cfg(feature = "cbf")]
+            WitnessResolver::Cbf(resolver) => resolver.resolve_witness(witness_id),
+            WitnessResolver::Multi(resolver) => resolver.resolve_witness(witness_id),
+        }
+    }
+
+    fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
+        match self {
+            #[cfg(any(feature = "electrum", feature = "esplora"))]
+            WitnessResolver::Any(resolver) => resolver.check_chain_net(chain_net),
+            #[cfg(feature = "bitcoind")]
+            WitnessResolver::Bitcoind(resolver) => resolver.check_chain_net(chain_net),
+            #[cfg(feature = "cbf")]
+            WitnessResolver::Cbf(resolver) => resolver.check_chain_net(chain_net),
+            WitnessResolver::Multi(resolver) => resolver.check_chain_net(chain_net),
         }
     }
 }
@@ -229,7 +565,7 @@ impl Indexer {
 mod tests {
     use super::*;
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(any(feature = "electrum", feature = "esplora", feature = "bitcoind"))]
     const TEST_TXID: &str = "0000000000000000000000000000000000000000000000000000000000000000";
 
     #[cfg(feature = "electrum")]
@@ -322,6 +658,43 @@ mod tests {
         (url, handle)
     }
 
+    #[cfg(feature = "bitcoind")]
+    fn bitcoind_indexer(url: &str) -> Indexer {
+        let url = format!("{}?birthday=0", url.replacen("http://", "bitcoind://", 1));
+        Indexer::Bitcoind(Box::new(
+            BitcoindClient::from_url(&url, None).expect("bitcoind client"),
+        ))
+    }
+
+    #[cfg(feature = "bitcoind")]
+    fn bitcoind_rpc_mock(
+        server: &mut mockito::Server,
+        path: &str,
+        method: &str,
+        response: &str,
+    ) -> mockito::Mock {
+        let (status, body) = if response.starts_with('{') && response.contains(r#""code""#) {
+            (
+                500,
+                format!(r#"{{"result":null,"error":{response},"id":"rgb-lib"}}"#),
+            )
+        } else {
+            (
+                200,
+                format!(r#"{{"result":{response},"error":null,"id":"rgb-lib"}}"#),
+            )
+        };
+        server
+            .mock("POST", path)
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({ "method": method }),
+            ))
+            .with_status(status)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create()
+    }
+
     #[cfg(feature = "esplora")]
     fn assert_fee_rate_approx(actual: f64, expected: f64) {
         assert!(
@@ -596,6 +969,66 @@ mod tests {
         handle.join().expect("mock electrum thread");
     }
 
+    #[cfg(feature = "electrum")]
+    fn electrum_confirmations_indexer(
+        confirmations: u64,
+    ) -> (Indexer, std::thread::JoinHandle<()>) {
+        let (url, handle) = start_electrum_mock(move |method, req| {
+            assert_eq!(method, "blockchain.transaction.get");
+            electrum_tx_get_result(
+                req,
+                &format!(r#"{{"confirmations":{confirmations},"hex":"00"}}"#),
+            )
+        });
+        (electrum_indexer(&url), handle)
+    }
+
+    #[cfg(feature = "electrum")]
+    #[test]
+    fn test_multi_failover() {
+        let (url, handle_down) = start_electrum_mock(|_, _| String::new());
+        let indexer_down = electrum_indexer(&url);
+        let (indexer_up, handle_up) = electrum_confirmations_indexer(42);
+        let multi = MultiIndexer::new(vec![indexer_down, indexer_up], None);
+
+        let res = multi.get_tx_confirmations(TEST_TXID).unwrap();
+        assert_eq!(res, Some(42));
+        assert_eq!(*multi.active.lock().unwrap(), 1);
+        handle_down.join().expect("mock electrum thread");
+        handle_up.join().expect("mock electrum thread");
+    }
+
+    #[cfg(feature = "electrum")]
+    #[test]
+    fn test_multi_quorum_confirmations() {
+        let (indexers, handles): (Vec<_>, Vec<_>) = [5, 3, 100]
+            .into_iter()
+            .map(electrum_confirmations_indexer)
+            .unzip();
+        let multi = MultiIndexer::new(indexers, Some(2));
+
+        // a single indexer reporting more confirmations is not trusted
+        let res = multi.get_tx_confirmations(TEST_TXID).unwrap();
+        assert_eq!(res, Some(5));
+        for handle in handles {
+            handle.join().expect("mock electrum thread");
+        }
+    }
+
+    #[cfg(feature = "electrum")]
+    #[test]
+    fn test_multi_quorum_not_reached() {
+        let (url, handle_down) = start_electrum_mock(|_, _| String::new());
+        let indexer_down = electrum_indexer(&url);
+        let (indexer_up, handle_up) = electrum_confirmations_indexer(42);
+        let multi = MultiIndexer::new(vec![indexer_down, indexer_up], Some(2));
+
+        let res = multi.get_tx_confirmations(TEST_TXID).unwrap_err();
+        assert_matches!(res, Error::Indexer { details } if details.contains("quorum"));
+        handle_down.join().expect("mock electrum thread");
+        handle_up.join().expect("mock electrum thread");
+    }
+
     #[cfg(feature = "esplora")]
     #[test]
     fn test_esplora_fee_estimation_empty_estimates() {
@@ -775,4 +1208,266 @@ mod tests {
         assert_matches!(res, IndexerError::Esplora { .. });
         mock.assert();
     }
+
+    #[cfg(feature = "bitcoind")]
+    #[test]
+    fn test_bitcoind_fee_estimation_success() {
+        let mut server = mockito::Server::new();
+        let mock = bitcoind_rpc_mock(
+            &mut server,
+            "/",
+            "estimatesmartfee",
+            r#"{"feerate":0.0001,"blocks":6}"#,
+        );
+        let indexer = bitcoind_indexer(&server.url());
+
+        let res = indexer.fee_estimation(6).unwrap();
+        assert_eq!(res, 10.0);
+        mock.assert();
+    }
+
+    #[cfg(feature = "bitcoind")]
+    #[test]
+    fn test_bitcoind_fee_estimation_unavailable() {
+        let mut server = mockito::Server::new();
+        let mock = bitcoind_rpc_mock(
+            &mut server,
+            "/",
+            "estimatesmartfee",
+            r#"{"errors":["Insufficient data or no feerate found"],"blocks":0}"#,
+        );
+        let indexer = bitcoind_indexer(&server.url());
+
+        let res = indexer.fee_estimation(6).unwrap_err();
+        assert_matches!(res, Error::CannotEstimateFees);
+        mock.assert();
+    }
+
+    #[cfg(feature = "bitcoind")]
+    #[test]
+    fn test_bitcoind_get_tx_confirmations_confirmed() {
+        let mut server = mockito::Server::new();
+        let mock = bitcoind_rpc_mock(
+            &mut server,
+            "/",
+            "getrawtransaction",
+            r#"{"hex":"00","confirmations":42}"#,
+        );
+        let indexer = bitcoind_indexer(&server.url());
+
+        let res = indexer.get_tx_confirmations(TEST_TXID).unwrap();
+        assert_eq!(res, Some(42));
+        mock.assert();
+    }
+
+    #[cfg(feature = "bitcoind")]
+    #[test]
+    fn test_bitcoind_get_tx_confirmations_mempool() {
+        let mut server = mockito::Server::new();
+        let mock = bitcoind_rpc_mock(&mut server, "/", "getrawtransaction", r#"{"hex":"00"}"#);
+        let indexer = bitcoind_indexer(&server.url());
+
+        let res = indexer.get_tx_confirmations(TEST_TXID).unwrap();
+        assert_eq!(res, Some(0));
+        mock.assert();
+    }
+
+    #[cfg(feature = "bitcoind")]
+    #[test]
+    fn test_bitcoind_get_tx_confirmations_not_found() {
+        let mut server = mockito::Server::new();
+        let wallet_path = format!("/wallet/{}", crate::api::bitcoind::BITCOIND_DEFAULT_WALLET);
+        let mock_raw = bitcoind_rpc_mock(
+            &mut server,
+            "/",
+            "getrawtransaction",
+            r#"{"code":-5,"message":"No such mempool or blockchain transaction"}"#,
+        );
+        let mock_load = bitcoind_rpc_mock(
+            &mut server,
+            "/",
+            "loadwallet",
+            r#"{"code":-35,"message":"Wallet is already loaded"}"#,
+        );
+        let mock_descriptors = bitcoind_rpc_mock(
+            &mut server,
+            &wallet_path,
+            "listdescriptors",
+            r#"{"wallet_name":"rgb-lib","descriptors":[]}"#,
+        );
+        let mock_wallet_tx = bitcoind_rpc_mock(
+            &mut server,
+            &wallet_path,
+            "gettransaction",
+            r#"{"code":-5,"message":"Invalid or non-wallet transaction id"}"#,
+        );
+        let indexer = bitcoind_indexer(&server.url());
+
+        let res = indexer.get_tx_confirmations(TEST_TXID).unwrap();
+        assert_eq!(res, None);
+        mock_raw.assert();
+        mock_load.assert();
+        mock_descriptors.assert();
+        mock_wallet_tx.assert();
+    }
+
+    #[cfg(feature = "bitcoind")]
+    #[test]
+    fn test_bitcoind_get_tx_confirmations_genesis_coinbase() {
+        let mut server = mockito::Server::new();
+        let mock = bitcoind_rpc_mock(
+            &mut server,
+            "/",
+            "getrawtransaction",
+            r#"{"code":-5,"message":"The genesis block coinbase is not considered an ordinary transaction and cannot be retrieved"}"#,
+        );
+        let indexer = bitcoind_indexer(&server.url());
+
+        let res = indexer.get_tx_confirmations(TEST_TXID).unwrap();
+        assert_eq!(res, Some(u64::MAX));
+        mock.assert();
+    }
+
+    #[cfg(feature = "bitcoind")]
+    #[test]
+    fn test_multi_quorum_witness_disagreement() {
+        let tx_hex = bdk_wallet::bitcoin::consensus::encode::serialize_hex(&BdkTransaction {
+            version: bdk_wallet::bitcoin::transaction::Version::TWO,
+            lock_time: bdk_wallet::bitcoin::locktime::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        });
+        let block_hash = "0".repeat(64);
+        let resolver = |server: &mut mockito::Server, height: u32| {
+            let mock = bitcoind_rpc_mock(
+                server,
+                "/",
+                "getrawtransaction",
+                &format!(
+                    r#"{{"hex":"{tx_hex}","confirmations":1,"blockhash":"{block_hash}","blockheight":{height},"blocktime":1700000000}}"#
+                ),
+            );
+            let url = server.url().replacen("http://", "bitcoind://", 1);
+            let client = BitcoindClient::from_url(&format!("{url}?birthday=0"), None)
+                .expect("bitcoind client");
+            (WitnessResolver::Bitcoind(Box::new(client)), mock)
+        };
+        let witness_id = RgbTxid::from_str(TEST_TXID).unwrap();
+
+        // the only answer backed by a quorum is returned, even if another one is stronger
+        let mut servers: Vec<mockito::Server> = (0..3).map(|_| mockito::Server::new()).collect();
+        let (resolvers, mocks): (Vec<_>, Vec<_>) = servers
+            .iter_mut()
+            .zip([100, 200, 100])
+            .map(|(server, height)| resolver(server, height))
+            .unzip();
+        let multi = MultiResolver::new(resolvers, Some(2));
+        let status = multi.resolve_witness(witness_id).unwrap();
+        let expected_pos =
+            WitnessPos::bitcoin(NonZeroU32::new(100).unwrap(), 1_700_000_000).unwrap();
+        assert_matches!(
+            status,
+            WitnessStatus::Resolved(_, WitnessOrd::Mined(pos)) if pos == expected_pos
+        );
+        mocks.iter().for_each(|m| m.assert());
+
+        // without agreement no answer is trusted
+        let mut servers: Vec<mockito::Server> = (0..3).map(|_| mockito::Server::new()).collect();
+        let (resolvers, _mocks): (Vec<_>, Vec<_>) = servers
+            .iter_mut()
+            .zip([100, 200, 300])
+            .map(|(server, height)| resolver(server, height))
+            .unzip();
+        let multi = MultiResolver::new(resolvers, Some(2));
+        let res = multi.resolve_witness(witness_id).unwrap_err();
+        assert_matches!(
+            res,
+            WitnessResolverError::ResolverIssue(_, details) if details.contains("disagree")
+        );
+    }
+
+    #[cfg(feature = "bitcoind")]
+    #[test]
+    fn test_bitcoind_import_rescan_timestamps() {
+        let mut server = mockito::Server::new();
+        let wallet_path = format!("/wallet/{}", crate::api::bitcoind::BITCOIND_DEFAULT_WALLET);
+        let mock_load = bitcoind_rpc_mock(
+            &mut server,
+            "/",
+            "loadwallet",
+            r#"{"code":-35,"message":"Wallet is already loaded"}"#,
+        );
+        let mock_descriptors = bitcoind_rpc_mock(
+            &mut server,
+            &wallet_path,
+            "listdescriptors",
+            r#"{"wallet_name":"rgb-lib","descriptors":[]}"#,
+        );
+        let mock_since_block = bitcoind_rpc_mock(
+            &mut server,
+            &wallet_path,
+            "listsinceblock",
+            r#"{"transactions":[]}"#,
+        )
+        .expect(2);
+        let import_mock = |server: &mut mockito::Server, timestamp: &str| {
+            server
+                .mock("POST", wallet_path.as_str())
+                .match_body(mockito::Matcher::AllOf(vec![
+                    mockito::Matcher::PartialJson(
+                        serde_json::json!({ "method": "importdescriptors" }),
+                    ),
+                    mockito::Matcher::Regex(format!(r#""timestamp":{timestamp}\b"#)),
+                ]))
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(r#"{"result":[{"success":true}],"error":null,"id":"rgb-lib"}"#)
+                .create()
+        };
+        // the first import rescans from the birthday, the following ones from the sync time
+        let mock_import_birthday = import_mock(&mut server, "1000");
+        let mock_import_recent = import_mock(&mut server, "1700000000");
+        let url = server.url().replacen("http://", "bitcoind://", 1);
+        let client = BitcoindClient::from_url(&format!("{url}?birthday=1000"), None).unwrap();
+
+        let spk_1 = ScriptBuf::new_op_return([1]);
+        let spk_2 = ScriptBuf::new_op_return([2]);
+        client
+            .sync(SyncRequest::builder_at(1_700_000_000).spks([spk_1.clone()]))
+            .unwrap();
+        client
+            .sync(SyncRequest::builder_at(1_700_000_000).spks([spk_1, spk_2]))
+            .unwrap();
+        mock_load.assert();
+        mock_descriptors.assert();
+        mock_since_block.assert();
+        mock_import_birthday.assert();
+        mock_import_recent.assert();
+    }
+
+    #[cfg(feature = "bitcoind")]
+    #[test]
+    fn test_bitcoind_broadcast_failure() {
+        let mut server = mockito::Server::new();
+        let mock = bitcoind_rpc_mock(
+            &mut server,
+            "/",
+            "sendrawtransaction",
+            r#"{"code":-26,"message":"min relay fee not met"}"#,
+        );
+        let indexer = bitcoind_indexer(&server.url());
+
+        let tx = BdkTransaction {
+            version: bdk_wallet::bitcoin::transaction::Version::TWO,
+            lock_time: bdk_wallet::bitcoin::locktime::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        };
+        let res = indexer.broadcast(&tx).unwrap_err();
+        assert_matches!(
+            res,
+            IndexerError::Bitcoind(BitcoindError::Rpc { code: -26, .. })
+        );
+        mock.assert();
+    }
 }
@@ -4,78 +4,105 @@
 
 pub(crate) mod backup;
 pub(crate) mod core;
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+pub(crate) mod encryption;
+#[cfg(online)]
 pub(crate) mod indexer;
 pub(crate) mod multisig;
+pub(crate) mod musig;
 pub(crate) mod objects;
 pub(crate) mod offline;
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) mod online;
+pub(crate) mod remote_backup;
 pub mod rust_only;
 pub(crate) mod singlesig;
 
 #[cfg(test)]
 pub(crate) mod test;
 
-pub use backup::restore_backup;
-pub use multisig::{Cosigner, MultisigKeys, MultisigWallet};
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
+pub use crate::api::vss::VssBackupTarget;
+pub use backup::{restore_backup, restore_backup_chain};
+pub use multisig::{Cosigner, MultisigKeys, MultisigWallet, RecoveryPolicy};
+#[cfg(online)]
 pub use multisig::{
     HubInfo, InitOperationResult, MultisigOnlineOptions, MultisigVotingStatus, Operation,
-    OperationInfo, RespondToOperation, UserRole,
+    OperationExpiry, OperationInfo, RespondToOperation, UserRole,
 };
+#[cfg(feature = "cbf")]
+pub use objects::CbfOptions;
 pub use objects::{
     Address, AssetCFA, AssetIFA, AssetNIA, AssetUDA, Assets, AssignmentsCollection, Balance,
     BlockTime, BtcBalance, DatabaseType, EmbeddedMedia, Invoice, InvoiceData, Media, Metadata,
-    Online, Outpoint, PendingVanillaTx, ProofOfReserves, PsbtInputInfo, PsbtInspection,
-    PsbtOutputInfo, ReceiveData, Recipient, RecipientInfo, RecipientType, RgbAllocation,
-    RgbInputInfo, RgbInspection, RgbOperationInfo, RgbOutputInfo, RgbTransitionInfo, Token,
-    TokenLight, Transaction, TransactionType, Transfer, TransferKind, TransferTransportEndpoint,
-    TransportEndpoint, TypeOfTransition, Unspent, Utxo, WalletData, WalletDescriptors, WitnessData,
+    Online, Outpoint, PaymentRequest, PaymentRequestData, PendingVanillaTx, ProofOfReserves,
+    PsbtInputInfo, PsbtInspection, PsbtOutputInfo, ReceiveData, Recipient, RecipientInfo,
+    RecipientType, RgbAllocation, RgbInputInfo, RgbInspection, RgbOperationInfo, RgbOutputInfo,
+    RgbTransitionInfo, SellOrder, SellOrderData, SwapLeg, SwapOffer, SwapOfferData, SwapStage,
+    Token, TokenLight, Transaction, TransactionType, Transfer, TransferKind,
+    TransferTransportEndpoint, TransportEndpoint, TypeOfTransition, Unspent, Utxo, WalletData,
+    WalletDescriptors, WalletEvent, WalletEventListener, WitnessData,
 };
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub use objects::{
-    BurnBeginResult, BurnDetails, InflateBeginResult, InflateDetails, OnlineOptions,
-    OperationResult, RefreshFilter, RefreshResult, RefreshTransferStatus, RefreshedTransfer,
-    SendBeginResult, SendDetails,
+    AutoRefreshOptions, BumpFeeBeginResult, BumpFeeDetails, BumpFeeResult, BurnBeginResult,
+    BurnDetails, CoinSelectionOptions, CoinSelectionStrategy, ConsolidateAllocationsBeginResult,
+    ConsolidateAllocationsDetails, InflateBeginResult, InflateDetails, NetworkOptions,
+    NetworkPolicy, NetworkService, OnlineOptions, OperationResult, RefreshFilter, RefreshResult,
+    RefreshTransferStatus, RefreshedTransfer, SendBeginResult, SendDetails, ServiceProxy,
 };
 pub use offline::RgbWalletOpsOffline;
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub use online::RgbWalletOpsOnline;
+pub use remote_backup::{BackupTarget, VersionedObject, restore_backup_remote};
 pub use singlesig::{SinglesigKeys, Wallet};
 
-pub(crate) use backup::WalletBackup;
+pub(crate) use backup::{
+    BACKUP_MANIFEST_FILE, BackupManifest, ScryptParams, WalletBackup, derive_key, generate_salt,
+    get_backup_paths, get_fingerprint_from_zip, read_backup_manifest, record_remote_backup_info,
+    remove_deleted_entries, revert_backup_info, unzip, zip_dir_with_manifest,
+};
 pub(crate) use core::{
-    ASSETS_DIR, MEDIA_DIR, NUM_KNOWN_SCHEMAS, WalletCore, WalletInternals, WalletManifest,
-    setup_bdk, setup_db, setup_new_wallet, setup_rgb,
+    ASSETS_DIR, MEDIA_DIR, NUM_KNOWN_SCHEMAS, RGB_LIB_DB_NAME, WalletCore, WalletInternals,
+    WalletManifest, setup_bdk, setup_db, setup_new_wallet, setup_rgb,
 };
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub use core::{SyncKeychain, SyncOptions, SyncStrategy};
-#[cfg(any(feature = "electrum", feature = "esplora"))]
-pub(crate) use indexer::Indexer;
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+pub(crate) use encryption::{BdkStore, EncryptedStore, WalletEncryption, sqlcipher_key};
+#[cfg(online)]
+pub(crate) use indexer::{Indexer, MultiIndexer, MultiResolver, WitnessResolver};
+#[cfg(online)]
 pub(crate) use objects::{
-    AssetInfo, AssetSpend, BeginOperationData, BtcChange, FailTransfersOutcome, LocalRecipient,
-    LocalRecipientData, LocalWitnessData, OnlineData, PrepareRgbPsbtResult,
+    AssetInfo, AssetSpend, BeginOperationData, BtcChange, BtcDrain, FailTransfersOutcome,
+    LocalRecipient, LocalRecipientData, LocalWitnessData, OnlineData, PrepareRgbPsbtResult,
     PrepareTransferPsbtResult, ReceiveMatcher, ReceiveMode, ReceivedConsignmentMeta,
-    RefreshResultTrait, TryFailBatchTransferOutcome,
+    RefreshResultTrait, RgbTransitionsData, SellOrderPayload, Socks5Proxy, SwapOfferPayload,
+    TryFailBatchTransferOutcome,
 };
 pub(crate) use objects::{
     InfoAssetTransfer, InfoBatchTransfer, IssueData, IssuedAssetDetails, LocalAssetData,
     LocalRgbAllocation, LocalTransportEndpoint, LocalUnspent, ReceiveDataInternal, TransferData,
     TransferEndData,
 };
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) use offline::TRANSFER_DATA_FILE;
 pub(crate) use offline::WalletOffline;
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
+pub(crate) use online::AutoRefreshHandle;
+#[cfg(online)]
 pub(crate) use online::WalletOnline;
+pub(crate) use remote_backup::RemoteBackup;
 
 use super::*;
 
 pub(crate) const CONSIGNMENT_FILE: &str = "consignment_out";
 pub(crate) const FASCIA_FILE: &str = "fascia";
 pub(crate) const UNSIGNED_PSBT_FILE: &str = "unsigned.psbt";
+#[cfg(online)]
+pub(crate) const SWAP_OFFER_FILE: &str = "swap_offer";
+
+pub(crate) const PAYMENT_REQUEST_PREFIX: &str = "rgbreq:";
+pub(crate) const SELL_ORDER_PREFIX: &str = "rgbsell:";
+pub(crate) const SWAP_OFFER_PREFIX: &str = "rgbswap:";
 
 pub(crate) const SCHEMA_ID_NIA: &str =
     "rgb:sch:RWhwUfTMpuP2Zfx1~j4nswCANGeJrYOqDcKelaMV4zU#remote-digital-pegasus";
@@ -90,9 +117,9 @@ pub(crate) const RGB_STATE_ASSET_OWNER: &str = "assetOwner";
 pub(crate) const RGB_STATE_INFLATION_ALLOWANCE: &str = "inflationAllowance";
 pub(crate) const RGB_GLOBAL_ISSUED_SUPPLY: &str = "issuedSupply";
 pub(crate) const RGB_GLOBAL_REJECT_LIST_URL: &str = "rejectListUrl";
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) const RGB_METADATA_ALLOWED_INFLATION: &str = "allowedInflation";
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) const RGB_METADATA_BURNED_ASSET: &str = "burnedAsset";
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) const RGB_METADATA_BURNED_INFLATION: &str = "burnedInflation";
@@ -5,6 +5,10 @@
 use super::*;
 
 const HUB_OPS_DIR: &str = "hub_ops";
+#[cfg(online)]
+const OPERATION_PACKAGES_DIR: &str = "operation_packages";
+#[cfg(online)]
+const OPERATION_PACKAGE_VERSION: u8 = 1;
 
 // BIP-341 NUMS H point (0250929b74...) with 32-zero chain code, formatted as a BIP-32 xpub.
 // BIP-388 requires every key in a tr() policy to be a derivable @i/** reference, so a
@@ -96,6 +100,29 @@ pub struct MultisigKeys {
     pub threshold_colored: u8,
     /// Threshold for the vanilla funds
     pub threshold_vanilla: u8,
+    /// Timelocked recovery policies, each adding an alternative spending path to both the colored
+    /// and vanilla descriptors
+    #[serde(default)]
+    pub recovery_policies: Vec<RecoveryPolicy>,
+    /// Whether to use the MuSig2 aggregate of the cosigner keys as taproot internal key, allowing
+    /// key path spends when all cosigners sign (requires n-of-n thresholds)
+    #[serde(default)]
+    pub musig: bool,
+}
+
+/// A timelocked recovery policy for a multisig wallet.
+///
+/// Once `after_blocks` blocks have been mined on top of a UTXO, `threshold` of the policy
+/// cosigners can spend it without the other multisig cosigners.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct RecoveryPolicy {
+    /// Relative timelock, in blocks, after which the policy can be used
+    pub after_blocks: u16,
+    /// Cosigners of the policy (if empty, the multisig group cosigners are used)
+    pub cosigners: Vec<Cosigner>,
+    /// Threshold for the policy cosigners
+    pub threshold: u8,
 }
 
 impl MultisigKeys {
@@ -105,7 +132,34 @@ impl MultisigKeys {
             cosigners,
             threshold_colored,
             threshold_vanilla,
+            recovery_policies: vec![],
+            musig: false,
+        }
+    }
+
+    /// Return the sorted MuSig2 participant keys for the colored or vanilla side.
+    pub(crate) fn musig_participants(
+        &self,
+        bitcoin_network: BitcoinNetwork,
+        rgb: bool,
+    ) -> Result<Vec<PublicKey>, Error> {
+        let secp = Secp256k1::verification_only();
+        let mut participants = Vec::with_capacity(self.cosigners.len());
+        for c in &self.cosigners {
+            let (xpub_str, keychain) = if rgb {
+                (&c.account_xpub_colored, KEYCHAIN_RGB)
+            } else {
+                (
+                    &c.account_xpub_vanilla,
+                    c.vanilla_keychain.unwrap_or(KEYCHAIN_BTC),
+                )
+            };
+            let xpub = str_to_xpub(xpub_str, &bitcoin_network.network_kind())?;
+            let child = ChildNumber::from_normal_idx(keychain as u32).expect("valid keychain");
+            participants.push(xpub.ckd_pub(&secp, child)?.public_key);
         }
+        participants.sort_by_key(|pk| pk.serialize());
+        Ok(participants)
     }
 
     pub(crate) fn build_descriptors(
@@ -172,6 +226,48 @@ impl MultisigKeys {
         colored_keys.sort();
         vanilla_keys.sort();
 
+        let mut colored_recovery_leaves = Vec::with_capacity(self.recovery_policies.len());
+        let mut vanilla_recovery_leaves = Vec::with_capacity(self.recovery_policies.len());
+        for (idx, recovery_policy) in self.recovery_policies.iter().enumerate() {
+            if recovery_policy.after_blocks == 0 {
+                return Err(Error::InvalidRecoveryPolicy {
+                    details: format!("policy {idx} has a 0 blocks timelock"),
+                });
+            }
+            let cosigners = if recovery_policy.cosigners.is_empty() {
+                &self.cosigners
+            } else {
+                &recovery_policy.cosigners
+            };
+            let total = u8::try_from(cosigners.len()).map_err(|_| Error::TooManyCosigners)?;
+            if recovery_policy.threshold == 0 || recovery_policy.threshold > total {
+                return Err(Error::InvalidRecoveryPolicy {
+                    details: format!(
+                        "policy {idx} requires {} signatures but has {total} cosigners",
+                        recovery_policy.threshold
+                    ),
+                });
+            }
+            let mut colored_policy_keys = Vec::with_capacity(cosigners.len());
+            let mut vanilla_policy_keys = Vec::with_capacity(cosigners.len());
+            for c in cosigners {
+                colored_policy_keys.push(key_for(c, rgb_coin, true)?);
+                vanilla_policy_keys.push(key_for(c, btc_coin, false)?);
+            }
+            colored_policy_keys.sort();
+            vanilla_policy_keys.sort();
+            let recovery_leaf = |keys: &[String]| -> String {
+                format!(
+                    "and_v(v:multi_a({},{}),older({}))",
+                    recovery_policy.threshold,
+                    keys.join(","),
+                    recovery_policy.after_blocks
+                )
+            };
+            colored_recovery_leaves.push(recovery_leaf(&colored_policy_keys));
+            vanilla_recovery_leaves.push(recovery_leaf(&vanilla_policy_keys));
+        }
+
         let nums_tpub = match bitcoin_network {
             BitcoinNetwork::Mainnet => NUMS_XPUB_MAINNET,
             _ => NUMS_TPUB_TESTNET,
@@ -180,16 +276,56 @@ impl MultisigKeys {
         // use /0/* (keychain 0) for the NUMS internal key to match the signer
         // keys and to avoid the /** multipath notation, which rust-miniscript
         // v12 does not accept as a raw descriptor string
-        let tr_multi_a_desc = |threshold: u8, keys: &[String]| -> String {
-            format!(
-                "tr({}/0/*,multi_a({},{}))",
-                nums_tpub,
-                threshold,
-                keys.join(",")
-            )
+        // with MuSig2 the internal key is instead derived from the BIP-328 xpub of the aggregate
+        // of the cosigner keychain keys
+        let (colored_internal_key, vanilla_internal_key) = if self.musig {
+            for threshold in [self.threshold_colored, self.threshold_vanilla] {
+                if threshold != total {
+                    return Err(Error::InvalidMultisigThreshold {
+                        required: threshold,
+                        total,
+                    });
+                }
+            }
+            let aggregate_key = |rgb: bool| -> Result<String, Error> {
+                let participants = self.musig_participants(bitcoin_network, rgb)?;
+                let xpub = musig::KeyAggContext::new(&participants)?
+                    .aggregate_xpub(bitcoin_network.network_kind());
+                Ok(format!("{xpub}/*"))
+            };
+            (aggregate_key(true)?, aggregate_key(false)?)
+        } else {
+            (format!("{nums_tpub}/0/*"), format!("{nums_tpub}/0/*"))
         };
-        let colored = tr_multi_a_desc(self.threshold_colored, &colored_keys);
-        let vanilla = tr_multi_a_desc(self.threshold_vanilla, &vanilla_keys);
+        // the regular multisig leaf comes first and recovery leaves are nested to the right
+        // ({multi_a,{r0,{r1,...}}}), so the tapleaves are iterated in policy order
+        let tr_multi_a_desc = |internal_key: &str,
+                               threshold: u8,
+                               keys: &[String],
+                               recovery_leaves: &[String]|
+         -> String {
+            let multi_a_leaf = format!("multi_a({},{})", threshold, keys.join(","));
+            let tree = recovery_leaves
+                .iter()
+                .rev()
+                .cloned()
+                .chain(std::iter::once(multi_a_leaf))
+                .reduce(|tree, leaf| format!("{{{leaf},{tree}}}"))
+                .expect("at least the multi_a leaf");
+            format!("tr({internal_key},{tree})")
+        };
+        let colored = tr_multi_a_desc(
+            &colored_internal_key,
+            self.threshold_colored,
+            &colored_keys,
+            &colored_recovery_leaves,
+        );
+        let vanilla = tr_multi_a_desc(
+            &vanilla_internal_key,
+            self.threshold_vanilla,
+            &vanilla_keys,
+            &vanilla_recovery_leaves,
+        );
 
         Ok(WalletDescriptors { colored, vanilla })
     }
@@ -201,6 +337,10 @@ impl MultisigKeys {
 pub struct MultisigWallet {
     pub(crate) internals: WalletInternals,
     pub(crate) keys: MultisigKeys,
+    #[cfg(online)]
+    pub(crate) auto_refresh: Option<AutoRefreshHandle>,
+    #[cfg(online)]
+    pub(crate) recovery_policy_idx: Option<u8>,
 }
 
 impl WalletCore for MultisigWallet {
@@ -212,67 +352,82 @@ impl WalletCore for MultisigWallet {
         &mut self.internals
     }
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn sync_wallet(
         &mut self,
         txn: &DbTxn,
         options: SyncOptions,
         include_spent: bool,
     ) -> Result<(), Error> {
-        // sync addresses
-        let response = self.hub_client().get_current_address_indices()?;
-        let (bdk_wallet, bdk_database) = self.bdk_wallet_db_mut();
-        let mut persist = false;
-        let mut reveal = |keychain_kind: KeychainKind, index: Option<u32>| {
-            if let Some(hub_index) = index {
-                let local_index = bdk_wallet
-                    .derivation_index(keychain_kind)
-                    .map(|i| i as i64)
-                    .unwrap_or(-1);
-                if local_index < hub_index as i64 {
-                    for _ in local_index..hub_index as i64 {
-                        bdk_wallet.reveal_next_address(keychain_kind);
-                    }
-                    persist = true;
-                }
-            }
-        };
-        reveal(KeychainKind::Internal, response.internal);
-        reveal(KeychainKind::External, response.external);
-        if persist {
-            bdk_wallet.persist(bdk_database)?;
+        // sync addresses (without a hub they're synced when importing operations)
+        if let Ok(hub_client) = self.hub_client() {
+            let response = hub_client.get_current_address_indices()?;
+            self.reveal_addresses_up_to(response.internal, response.external)?;
         }
         // sync UTXOs
         self.sync_bdk_and_db_txos(txn, options, include_spent)
     }
+
+    #[cfg(online)]
+    fn spending_policy_paths(&self) -> Vec<(KeychainKind, BTreeMap<String, Vec<usize>>)> {
+        if self.keys.recovery_policies.is_empty() {
+            return vec![];
+        }
+        // the taproot policy is a 1-of-N between the (unspendable) key path and the tapleaves,
+        // with the multi_a leaf first and the recovery ones following in order
+        let item = match self.recovery_policy_idx {
+            Some(idx) => idx as usize + 2,
+            None => 1,
+        };
+        let bdk_wallet = self.bdk_wallet();
+        [KeychainKind::External, KeychainKind::Internal]
+            .into_iter()
+            .filter_map(|keychain| {
+                let policy = bdk_wallet
+                    .policies(keychain)
+                    .expect("multisig descriptors should have a valid policy")?;
+                Some((keychain, BTreeMap::from([(policy.id, vec![item])])))
+            })
+            .collect()
+    }
 }
 
 impl WalletBackup for MultisigWallet {}
 
 impl WalletOffline for MultisigWallet {
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn get_new_addresses(
         &mut self,
         keychain: KeychainKind,
         count: u32,
     ) -> Result<BdkAddress, Error> {
+        let Ok(hub_client) = self.hub_client() else {
+            // without a hub indexes are shared with cosigners via the operation packages
+            let mut bdk = self.bdk();
+            let first_address = bdk.wallet.reveal_next_address(keychain).address;
+            for _ in 1..count {
+                bdk.wallet.reveal_next_address(keychain);
+            }
+            bdk.persist()?;
+            return Ok(first_address);
+        };
         let is_internal = keychain == KeychainKind::Internal;
-        let start_index = self.hub_client().bump_address_indices(count, is_internal)?;
+        let start_index = hub_client.bump_address_indices(count, is_internal)?;
         let local_index = self.bdk_wallet().derivation_index(keychain).unwrap_or(0);
         let target_index = start_index
             .checked_add(count)
             .expect("address derivation index cannot exceed u32::MAX");
-        let (bdk_wallet, bdk_database) = self.bdk_wallet_db_mut();
+        let mut bdk = self.bdk();
         for _ in local_index..target_index {
-            bdk_wallet.reveal_next_address(keychain);
+            bdk.wallet.reveal_next_address(keychain);
         }
-        let first_address = bdk_wallet.peek_address(keychain, start_index).address;
-        bdk_wallet.persist(bdk_database)?;
+        let first_address = bdk.wallet.peek_address(keychain, start_index).address;
+        bdk.persist()?;
         Ok(first_address)
     }
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl WalletOnline for MultisigWallet {
     fn wallet_specific_consistency_checks(&mut self, _txn: &DbTxn) -> Result<(), Error> {
         Ok(())
@@ -282,26 +437,30 @@ impl WalletOnline for MultisigWallet {
         false
     }
 
+    // without a hub each wallet decides on transfers on its own, as singlesig wallets do
+
     fn get_hub_fail_status(&self, batch_transfer_idx: i32) -> Result<bool, Error> {
-        Ok(self
-            .hub_client()
+        let Ok(hub_client) = self.hub_client() else {
+            return Ok(false);
+        };
+        Ok(hub_client
             .get_transfer_status(batch_transfer_idx)?
             .is_some_and(|s| !s.accepted))
     }
 
     fn set_hub_accept_status(&self, batch_transfer_idx: i32) -> Result<Option<bool>, Error> {
+        let Ok(hub_client) = self.hub_client() else {
+            return Ok(Some(true));
+        };
         if self.check_is_cosigner().is_ok() {
-            match self
-                .hub_client()
-                .set_transfer_status(batch_transfer_idx, true)
-            {
+            match hub_client.set_transfer_status(batch_transfer_idx, true) {
                 Ok(_) => Ok(Some(true)),
                 Err(Error::MultisigTransferStatusMismatch) => Ok(Some(false)),
                 Err(e) => Err(e),
             }
         } else {
             // watch-only must follow the cosigners' decision if registered
-            match self.hub_client().get_transfer_status(batch_transfer_idx)? {
+            match hub_client.get_transfer_status(batch_transfer_idx)? {
                 Some(s) => Ok(Some(s.accepted)),
                 None => Ok(None),
             }
@@ -309,9 +468,10 @@ impl WalletOnline for MultisigWallet {
     }
 
     fn set_hub_fail_status(&self, batch_transfer_idx: i32) -> Result<(), Error> {
-        if self.check_is_cosigner().is_ok() {
-            self.hub_client()
-                .set_transfer_status(batch_transfer_idx, false)?;
+        if let Ok(hub_client) = self.hub_client()
+            && self.check_is_cosigner().is_ok()
+        {
+            hub_client.set_transfer_status(batch_transfer_idx, false)?;
         }
         Ok(())
     }
@@ -321,7 +481,7 @@ impl WalletOnline for MultisigWallet {
 impl RgbWalletOpsOffline for MultisigWallet {}
 
 /// Common online APIs of the wallet.
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl RgbWalletOpsOnline for MultisigWallet {
     fn fail_transfers(
         &mut self,
@@ -352,7 +512,7 @@ impl RgbWalletOpsOnline for MultisigWallet {
 }
 
 /// Multisig-specific options for the [`MultisigWallet::go_online`] method.
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct MultisigOnlineOptions {
@@ -360,11 +520,14 @@ pub struct MultisigOnlineOptions {
     pub hub_url: String,
     /// Authentication token for the multisig hub
     pub hub_token: String,
+    /// Mnemonic of the cosigner the token belongs to, needed to sign the cancellation of
+    /// operations (see [`MultisigWallet::cancel_operation`])
+    pub cosigner_mnemonic: Option<String>,
 }
 
 /// Voting status for multisig operations.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct MultisigVotingStatus {
     /// xPubs of cosigners that ACKed
@@ -378,7 +541,7 @@ pub struct MultisigVotingStatus {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 struct ReceiveMetadata {
     invoice: String,
     min_confirmations: u8,
@@ -388,7 +551,7 @@ struct ReceiveMetadata {
 
 /// Operations for multisig wallets.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub enum Operation {
     // CreateUtxos variants
@@ -564,13 +727,13 @@ pub enum Operation {
 }
 
 #[derive(Debug, Clone)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct FileResponse {
     pub(crate) r#type: FileType,
     pub(crate) filepath: PathBuf,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl InfoBatchTransfer {
     fn extract_from_files(files: &[FileResponse]) -> Result<Self, Error> {
         let transfer_data_file = files
@@ -587,7 +750,7 @@ impl InfoBatchTransfer {
     }
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 fn extract_fascia_path(files: &[FileResponse]) -> Result<String, Error> {
     files
         .iter()
@@ -598,7 +761,7 @@ fn extract_fascia_path(files: &[FileResponse]) -> Result<String, Error> {
         })
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 fn extract_fascia_from_files(files: &[FileResponse]) -> Result<Fascia, Error> {
     let fascia_file = files
         .iter()
@@ -614,10 +777,10 @@ fn extract_fascia_from_files(files: &[FileResponse]) -> Result<Fascia, Error> {
 }
 
 #[derive(Debug, Clone)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct NoDetails;
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) trait OperationHandler {
     type Details: Clone;
 
@@ -646,10 +809,10 @@ pub(crate) trait OperationHandler {
     }
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct CreateUtxosHandler;
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl OperationHandler for CreateUtxosHandler {
     type Details = NoDetails;
 
@@ -683,10 +846,10 @@ impl OperationHandler for CreateUtxosHandler {
     }
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct SendBtcHandler;
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl OperationHandler for SendBtcHandler {
     type Details = NoDetails;
 
@@ -720,10 +883,10 @@ impl OperationHandler for SendBtcHandler {
     }
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct SendRgbHandler;
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl OperationHandler for SendRgbHandler {
     type Details = SendDetails;
 
@@ -780,10 +943,10 @@ impl OperationHandler for SendRgbHandler {
     }
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct InflateHandler;
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl OperationHandler for InflateHandler {
     type Details = InflateDetails;
 
@@ -847,10 +1010,10 @@ impl OperationHandler for InflateHandler {
     }
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) struct BurnHandler;
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl OperationHandler for BurnHandler {
     type Details = BurnDetails;
 
@@ -916,7 +1079,7 @@ impl OperationHandler for BurnHandler {
 
 /// Information about an operation.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct OperationInfo {
     /// Index of the operation
@@ -929,7 +1092,7 @@ pub struct OperationInfo {
 
 /// Response to an operation.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub enum RespondToOperation {
     /// ACK the operation with a signed PSBT
@@ -938,9 +1101,59 @@ pub enum RespondToOperation {
     Nack,
 }
 
+/// Deadline after which a pending operation expires and gets discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg(online)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub enum OperationExpiry {
+    /// Block height from which the operation is expired
+    BlockHeight(u32),
+    /// UTC timestamp from which the operation is expired
+    Timestamp(u64),
+}
+
+// operation expiry as stored in the operation files, independently of the enabled features
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg(online)]
+struct OperationExpiryData {
+    block_height: Option<u32>,
+    timestamp: Option<u64>,
+}
+
+#[cfg(online)]
+impl From<OperationExpiry> for OperationExpiryData {
+    fn from(orig: OperationExpiry) -> Self {
+        match orig {
+            OperationExpiry::BlockHeight(height) => Self {
+                block_height: Some(height),
+                timestamp: None,
+            },
+            OperationExpiry::Timestamp(timestamp) => Self {
+                block_height: None,
+                timestamp: Some(timestamp),
+            },
+        }
+    }
+}
+
+#[cfg(online)]
+impl TryFrom<OperationExpiryData> for OperationExpiry {
+    type Error = Error;
+
+    fn try_from(orig: OperationExpiryData) -> Result<Self, Self::Error> {
+        match (orig.block_height, orig.timestamp) {
+            (Some(height), None) => Ok(OperationExpiry::BlockHeight(height)),
+            (None, Some(timestamp)) => Ok(OperationExpiry::Timestamp(timestamp)),
+            _ => Err(Error::MultisigUnexpectedData {
+                details: s!("invalid operation expiry"),
+            }),
+        }
+    }
+}
+
 /// Result of an operation initialization.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct InitOperationResult {
     /// PSBT of the operation
@@ -951,7 +1164,7 @@ pub struct InitOperationResult {
 
 /// The role of the user on the hub.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub enum UserRole {
     /// A cosigner
@@ -960,7 +1173,7 @@ pub enum UserRole {
     WatchOnly,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl From<UserRoleResponse> for UserRole {
     fn from(orig: UserRoleResponse) -> Self {
         match orig {
@@ -972,7 +1185,7 @@ impl From<UserRoleResponse> for UserRole {
 
 /// Information about the hub.
 #[derive(Debug, Clone, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct HubInfo {
     /// The minimum supported rgb-lib version
@@ -987,7 +1200,7 @@ pub struct HubInfo {
     pub user_role: UserRole,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl From<InfoResponse> for HubInfo {
     fn from(orig: InfoResponse) -> Self {
         Self {
@@ -1000,17 +1213,144 @@ impl From<InfoResponse> for HubInfo {
     }
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 enum PostData {
     BeginOperationData(Box<BeginOperationData>),
     Psbt(Psbt),
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg(online)]
+struct PackageFile {
+    r#type: FileType,
+    posted_by_xpub: String,
+    data: String,
+    signature: String,
+}
+
+#[cfg(online)]
+impl PackageFile {
+    // the file still needs to be signed by the cosigner posting it
+    fn new(r#type: FileType, posted_by_xpub: &str, bytes: &[u8]) -> Self {
+        Self {
+            r#type,
+            posted_by_xpub: posted_by_xpub.to_string(),
+            data: general_purpose::STANDARD.encode(bytes),
+            signature: String::new(),
+        }
+    }
+
+    // message signed by the poster, binding the file to the operation
+    fn signed_hash(&self, operation_hash: &[u8; 32]) -> [u8; 32] {
+        let file_type = serde_json::to_vec(&self.r#type).expect("serializable");
+        musig::tagged_hash(
+            "rgb-lib/operation package file",
+            &[operation_hash, &file_type, self.data.as_bytes()],
+        )
+    }
+
+    fn bytes(&self) -> Result<Vec<u8>, Error> {
+        general_purpose::STANDARD
+            .decode(&self.data)
+            .map_err(|_| Error::InvalidOperationPackage {
+                details: s!("invalid file data"),
+            })
+    }
+
+    // files posted after the operation has been initiated
+    fn is_response(&self) -> bool {
+        matches!(
+            self.r#type,
+            FileType::ResponsePsbt | FileType::MusigNonces | FileType::Cancellation
+        )
+    }
+}
+
+// an operation with its files and the responses collected so far, exchanged between cosigners
+// in place of the hub
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg(online)]
+struct OperationPackage {
+    version: u8,
+    wallet_id: String,
+    operation_idx: i32,
+    operation_type: OperationType,
+    initiator_xpub: String,
+    initiator_signature: String,
+    created_at: i64,
+    internal_index: Option<u32>,
+    external_index: Option<u32>,
+    acked_by: HashSet<String>,
+    // xPub of the cosigners that NACKed, with their signature
+    nacked_by: HashMap<String, String>,
+    files: Vec<PackageFile>,
+}
+
+#[cfg(online)]
+impl OperationPackage {
+    fn read(path: &Path) -> Result<Self, Error> {
+        let file = fs::File::open(path)?;
+        let reader = io::BufReader::new(file);
+        serde_json::from_reader(reader).map_err(|_| Error::InvalidOperationPackage {
+            details: s!("invalid format"),
+        })
+    }
+
+    fn write(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, serde_json::to_vec(self).expect("serializable"))?;
+        Ok(())
+    }
+
+    // hash of the operation data and files, signed by the initiator
+    fn operation_hash(&self) -> [u8; 32] {
+        let operation_files = self
+            .files
+            .iter()
+            .filter(|f| !f.is_response())
+            .map(|f| (f.r#type, &f.data))
+            .collect::<Vec<_>>();
+        let operation = serde_json::to_vec(&(
+            self.version,
+            &self.wallet_id,
+            self.operation_idx,
+            self.operation_type,
+            &self.initiator_xpub,
+            self.created_at,
+            self.internal_index,
+            self.external_index,
+            operation_files,
+        ))
+        .expect("serializable");
+        musig::tagged_hash("rgb-lib/operation package", &[&operation])
+    }
+
+    // message signed by a cosigner NACKing the operation
+    fn nack_hash(&self) -> [u8; 32] {
+        musig::tagged_hash("rgb-lib/operation package nack", &[&self.operation_hash()])
+    }
+
+    fn merge(&mut self, other: OperationPackage) -> Result<(), Error> {
+        if self.operation_hash() != other.operation_hash() {
+            return Err(Error::InvalidOperationPackage {
+                details: format!("conflicting operation {}", self.operation_idx),
+            });
+        }
+        self.acked_by.extend(other.acked_by);
+        self.nacked_by.extend(other.nacked_by);
+        for file in other.files {
+            if !self.files.contains(&file) {
+                self.files.push(file);
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Offline APIs of the wallet
 impl MultisigWallet {
     /// Create a new RGB multisig wallet based on the provided [`WalletData`] and
     /// [`MultisigKeys`].
-    pub fn new(wallet_data: WalletData, keys: MultisigKeys) -> Result<Self, Error> {
+    pub fn new(mut wallet_data: WalletData, keys: MultisigKeys) -> Result<Self, Error> {
         let wdata = wallet_data.clone();
 
         // wallet keys
@@ -1023,36 +1363,44 @@ impl MultisigWallet {
         let (wallet_dir, logger, _logger_guard) = setup_new_wallet(&wallet_data, &fingerprint)?;
         fs::create_dir_all(wallet_dir.join(HUB_OPS_DIR))?;
 
+        // database encryption key
+        let key = WalletEncryption::setup(&wallet_dir, &mut wallet_data)?;
+
         // setup the BDK wallet
-        let (bdk_wallet, bdk_database) = setup_bdk(
+        let bdk = setup_bdk(
             &wdata,
             &wallet_dir,
             descs.colored,
             descs.vanilla,
             true,
             BdkNetwork::from(wdata.bitcoin_network),
+            key.as_ref(),
         )?;
 
         // setup RGB
         setup_rgb(&wallet_dir, wdata.supported_schemas, wdata.bitcoin_network)?;
 
         // setup rgb-lib DB
-        let database = setup_db(&wallet_dir)?;
+        let database = setup_db(&wallet_dir, &wdata.database_type, key.as_ref())?;
 
         info!(logger, "New multisig wallet completed");
         Ok(Self {
             internals: WalletInternals {
                 wallet_data,
                 logger,
-                _logger_guard,
+                _logger_guard: Arc::new(_logger_guard),
                 database: Arc::new(database),
                 wallet_dir,
-                bdk_wallet,
-                bdk_database,
-                #[cfg(any(feature = "electrum", feature = "esplora"))]
+                bdk: Arc::new(Mutex::new(bdk)),
+                musig_nonces_lock: Arc::new(Mutex::new(())),
+                #[cfg(online)]
                 online_data: None,
             },
             keys,
+            #[cfg(online)]
+            auto_refresh: None,
+            #[cfg(online)]
+            recovery_policy_idx: None,
         })
     }
 
@@ -1093,7 +1441,7 @@ impl MultisigWallet {
 }
 
 /// Online APIs of the wallet
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl MultisigWallet {
     fn is_cosigner(&self) -> Result<bool, Error> {
         Ok(matches!(
@@ -1114,13 +1462,143 @@ impl MultisigWallet {
         Ok(())
     }
 
-    pub(crate) fn hub_client(&self) -> &MultisigHubClient {
+    pub(crate) fn hub_client(&self) -> Result<&MultisigHubClient, Error> {
         self.online_data()
             .as_ref()
             .unwrap()
             .hub_client
             .as_ref()
+            .ok_or(Error::MultisigCoordinationMode {
+                details: s!("a hub is required"),
+            })
+    }
+
+    // MuSig2 nonces, cancellations, expiries and recovery policies are exchanged as operation
+    // files, which the hub only serves when built with the `hub_operation_files` feature
+    #[cfg(not(feature = "hub_operation_files"))]
+    fn hub_operation_files_error(file_type: FileType) -> Error {
+        Error::MultisigCoordinationMode {
+            details: format!(
+                "{file_type:?} files are not supported with a hub (needs the `hub_operation_files` feature)"
+            ),
+        }
+    }
+
+    fn check_no_hub(&self) -> Result<(), Error> {
+        if self.hub_client().is_ok() {
+            return Err(Error::MultisigCoordinationMode {
+                details: s!("not available when using a hub"),
+            });
+        }
+        Ok(())
+    }
+
+    fn cosigner_xpub(&self) -> Result<String, Error> {
+        self.online_data()
+            .as_ref()
+            .unwrap()
+            .cosigner_xpub
+            .clone()
+            .ok_or(Error::MultisigUserNotCosigner)
+    }
+
+    // cosigners of the multisig group or of one of its recovery policies
+    fn find_cosigner(&self, xpub: &str) -> Option<&Cosigner> {
+        self.keys
+            .cosigners
+            .iter()
+            .chain(
+                self.keys
+                    .recovery_policies
+                    .iter()
+                    .flat_map(|p| &p.cosigners),
+            )
+            .find(|c| c.account_xpub_colored == xpub)
+    }
+
+    fn is_cosigner_xpub(&self, xpub: &str) -> bool {
+        self.find_cosigner(xpub).is_some()
+    }
+
+    fn get_cosigner_keypair(&self, mnemonic: &str) -> Result<(String, Keypair), Error> {
+        let bitcoin_network = self.bitcoin_network();
+        for witness_version in [WitnessVersion::Taproot, WitnessVersion::SegWitV0] {
+            let (account_xprv, account_xpub, _) =
+                get_account_data(&bitcoin_network, mnemonic, true, witness_version)?;
+            let xpub = account_xpub.to_string();
+            if self.is_cosigner_xpub(&xpub) {
+                return Ok((xpub, account_xprv.to_keypair(&Secp256k1::new())));
+            }
+        }
+        Err(Error::InvalidCosigner {
+            details: s!("mnemonic doesn't belong to any of the wallet cosigners"),
+        })
+    }
+
+    // message signed by the initiator to cancel an operation
+    fn cancellation_hash(&self, operation_idx: i32) -> [u8; 32] {
+        musig::tagged_hash(
+            "rgb-lib/operation cancellation",
+            &[self.wallet_id().as_bytes(), &operation_idx.to_le_bytes()],
+        )
+    }
+
+    fn sign_as_cosigner(&self, msg: [u8; 32]) -> Result<String, Error> {
+        let keypair = self
+            .online_data()
+            .as_ref()
             .unwrap()
+            .cosigner_keypair
+            .ok_or(Error::MultisigUserNotCosigner)?;
+        Ok(Secp256k1::new()
+            .sign_schnorr_no_aux_rand(&SecpMessage::from_digest(msg), &keypair)
+            .to_string())
+    }
+
+    fn verify_cosigner_signature(&self, xpub: &str, msg: [u8; 32], signature: &str) -> bool {
+        let (Ok(xpub), Ok(signature)) =
+            (Xpub::from_str(xpub), SchnorrSignature::from_str(signature))
+        else {
+            return false;
+        };
+        Secp256k1::verification_only()
+            .verify_schnorr(
+                &signature,
+                &SecpMessage::from_digest(msg),
+                &xpub.public_key.x_only_public_key().0,
+            )
+            .is_ok()
+    }
+
+    fn reveal_addresses_up_to(
+        &self,
+        internal_index: Option<u32>,
+        external_index: Option<u32>,
+    ) -> Result<(), Error> {
+        let mut bdk = self.bdk();
+        let mut persist = false;
+        for (keychain_kind, index) in [
+            (KeychainKind::Internal, internal_index),
+            (KeychainKind::External, external_index),
+        ] {
+            if let Some(index) = index {
+                let local_index = bdk
+                    .wallet
+                    .derivation_index(keychain_kind)
+                    .map(|i| i as i64)
+                    .unwrap_or(-1);
+                if local_index < index as i64 {
+                    for _ in local_index..index as i64 {
+                        bdk.wallet.reveal_next_address(keychain_kind);
+                    }
+                    persist = true;
+                }
+            }
+        }
+        if persist {
+            bdk.persist()?;
+        }
+        Ok(())
     }
 
     fn get_hub_ops_dir(&self) -> PathBuf {
@@ -1142,7 +1620,7 @@ impl MultisigWallet {
                 return Ok(media_path);
             }
         }
-        self.hub_client()
+        self.hub_client()?
             .get_file(&file_metadata.file_id, &filepath)?;
         Ok(filepath)
     }
@@ -1216,9 +1694,14 @@ impl MultisigWallet {
         }
 
         // check hub connectivity and configuration
+        let socks5_proxy = online_options
+            .network
+            .socks5_proxy(NetworkService::MultisigHub, None);
         let hub_client = MultisigHubClient::new(
             &multisig_online_options.hub_url,
             &multisig_online_options.hub_token,
+            socks5_proxy.as_ref(),
+            &online_options.network.policy,
         )?;
         let info = hub_client.info()?;
         const RGB_LIB_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1238,29 +1721,182 @@ impl MultisigWallet {
             });
         }
 
+        let cosigner_xpub = match &info.user_role {
+            UserRoleResponse::Cosigner(xpub) => Some(xpub.clone()),
+            UserRoleResponse::WatchOnly => None,
+        };
+        let cosigner_keypair = match &multisig_online_options.cosigner_mnemonic {
+            Some(mnemonic) => {
+                let (xpub, keypair) = self.get_cosigner_keypair(mnemonic)?;
+                if cosigner_xpub.as_ref() != Some(&xpub) {
+                    return Err(Error::InvalidCosigner {
+                        details: s!("mnemonic doesn't belong to the hub user"),
+                    });
+                }
+                Some(keypair)
+            }
+            None => None,
+        };
+        let online = self.go_online_multisig(
+            &online_options,
+            Some((hub_client, &multisig_online_options)),
+            info.user_role.into(),
+            cosigner_xpub,
+            cosigner_keypair,
+        )?;
+
+        info!(self.logger(), "Go online completed");
+        Ok(online)
+    }
+
+    /// Return the existing or freshly generated wallet [`Online`] data, coordinating with the
+    /// other cosigners without a hub.
+    ///
+    /// Operations are then exchanged as files, using the
+    /// [`export_operation`](MultisigWallet::export_operation) and
+    /// [`import_operation`](MultisigWallet::import_operation) methods.
+    ///
+    /// The `cosigner_mnemonic` identifies the user among the cosigners: the
+    /// [`Cosigner::account_xpub_colored`] of one of them needs to derive from it. Its key is used
+    /// to sign the user's contributions to the operation packages (operations, NACKs and posted
+    /// files), which are verified by the other cosigners on import. If it's not provided the
+    /// wallet is watch-only.
+    ///
+    /// See [`OnlineOptions`] for details on the available options.
+    pub fn go_online_without_hub(
+        &mut self,
+        online_options: OnlineOptions,
+        cosigner_mnemonic: Option<String>,
+    ) -> Result<Online, Error> {
+        info!(self.logger(), "Going online without hub...");
+        let (user_role, cosigner_xpub, cosigner_keypair) = match &cosigner_mnemonic {
+            Some(mnemonic) => {
+                let (xpub, keypair) = self.get_cosigner_keypair(mnemonic)?;
+                (UserRole::Cosigner, Some(xpub), Some(keypair))
+            }
+            None => (UserRole::WatchOnly, None, None),
+        };
+        let online = self.go_online_multisig(
+            &online_options,
+            None,
+            user_role,
+            cosigner_xpub,
+            cosigner_keypair,
+        )?;
+        info!(self.logger(), "Go online without hub completed");
+        Ok(online)
+    }
+
+    fn go_online_multisig(
+        &mut self,
+        online_options: &OnlineOptions,
+        hub: Option<(MultisigHubClient, &MultisigOnlineOptions)>,
+        user_role: UserRole,
+        cosigner_xpub: Option<String>,
+        cosigner_keypair: Option<Keypair>,
+    ) -> Result<Online, Error> {
         // shared go online logic
-        let online = self.go_online_impl(&online_options)?;
+        self.auto_refresh = None;
+        let online = self.go_online_impl(online_options)?;
 
         // set multisig-specific OnlineData fields
-        self.online_data_mut().as_mut().unwrap().hub_client = Some(hub_client);
-        self.online_data_mut().as_mut().unwrap().user_role = Some(info.user_role.into());
+        let (hub_client, multisig_online_options) = hub.unzip();
+        let online_data = self.online_data_mut().as_mut().unwrap();
+        online_data.hub_client = hub_client;
+        online_data.user_role = Some(user_role);
+        online_data.cosigner_xpub = cosigner_xpub;
+        online_data.cosigner_keypair = cosigner_keypair;
+
+        // start the background task, with its own indexer and hub connections
+        if let Some(auto_refresh_options) = online_options.auto_refresh {
+            let mut wallet = MultisigWallet {
+                internals: self.internals.share(),
+                keys: self.keys.clone(),
+                auto_refresh: None,
+                recovery_policy_idx: None,
+            };
+            let (wallet_online, mut online_data) = wallet.get_online_data(online_options)?;
+            if let Some(multisig_online_options) = multisig_online_options {
+                let socks5_proxy = online_options
+                    .network
+                    .socks5_proxy(NetworkService::MultisigHub, None);
+                online_data.hub_client = Some(MultisigHubClient::new(
+                    &multisig_online_options.hub_url,
+                    &multisig_online_options.hub_token,
+                    socks5_proxy.as_ref(),
+                    &online_options.network.policy,
+                )?);
+            }
+            let self_online_data = self.online_data().as_ref().unwrap();
+            online_data.user_role = self_online_data.user_role.clone();
+            online_data.cosigner_xpub = self_online_data.cosigner_xpub.clone();
+            online_data.cosigner_keypair = self_online_data.cosigner_keypair;
+            *wallet.online_data_mut() = Some(online_data);
+            self.auto_refresh = Some(AutoRefreshHandle::start(
+                wallet,
+                wallet_online,
+                auto_refresh_options,
+            ));
+        }
 
-        info!(self.logger(), "Go online completed");
         Ok(online)
     }
 
+    /// Stop the background auto-refresh task started by [`go_online`](MultisigWallet::go_online),
+    /// if any.
+    ///
+    /// This waits for a run in progress to complete.
+    pub fn stop_auto_refresh(&mut self) {
+        info!(self.logger(), "Stopping auto-refresh...");
+        self.auto_refresh = None;
+        info!(self.logger(), "Stop auto-refresh completed");
+    }
+
+    /// Select the spending path for the transactions initiated by this wallet.
+    ///
+    /// With `Some(idx)` new operations spend via the recovery policy at index `idx` of
+    /// [`MultisigKeys::recovery_policies`], which is only possible for UTXOs whose timelock has
+    /// expired. With `None` (the default) the regular multisig path is used.
+    ///
+    /// The selected policy is part of the operation data: without a hub, cosigners approve the
+    /// operation once `threshold` of the policy cosigners have ACKed it and only the policy
+    /// cosigners can respond to it. Note: the hub still approves operations based on its
+    /// configured threshold, so it needs to be set accordingly for recovery operations to
+    /// complete, and initiating recovery operations with a hub requires the
+    /// `hub_operation_files` feature.
+    pub fn use_recovery_policy(&mut self, recovery_policy_idx: Option<u8>) -> Result<(), Error> {
+        if let Some(idx) = recovery_policy_idx
+            && idx as usize >= self.keys.recovery_policies.len()
+        {
+            return Err(Error::InvalidRecoveryPolicy {
+                details: format!("unknown policy {idx}"),
+            });
+        }
+        self.recovery_policy_idx = recovery_policy_idx;
+        Ok(())
+    }
+
     /// Get information about the hub.
     pub fn hub_info(&self, online: Online) -> Result<HubInfo, Error> {
         info!(self.logger(), "Hub info...");
         self.check_online(online)?;
-        let info = self.hub_client().info()?.into();
+        let info = self.hub_client()?.info()?.into();
         info!(self.logger(), "Hub info completed");
         Ok(info)
     }
 
     fn mark_operation_as_processed(&self, txn: &DbTxn, operation_idx: i32) -> Result<(), Error> {
-        if self.is_cosigner()?
-            && let Err(e) = self.hub_client().mark_operation_processed(operation_idx)
+        let local_op = txn
+            .iter_multisig_operations()?
+            .into_iter()
+            .find(|o| o.operation_idx == operation_idx);
+        // operations with conflicting inputs, cancelled or expired are discarded locally while
+        // still pending on the hub
+        let discarded_locally = local_op.as_ref().is_some_and(|o| o.state.discarded());
+        if !discarded_locally
+            && self.is_cosigner()?
+            && let Ok(hub_client) = self.hub_client()
+            && let Err(e) = hub_client.mark_operation_processed(operation_idx)
         {
             // ignore to enable multiple instances and restore from old backups
             if !matches!(&e, Error::MultisigCannotMarkOperationProcessed { details: d }
@@ -1269,51 +1905,278 @@ impl MultisigWallet {
                 return Err(e);
             }
         }
-        self.update_backup_info_with_op_idx(txn, false, Some(operation_idx))?;
+        if let Some(local_op) = local_op {
+            self.release_operation_txos(txn, operation_idx)?;
+            txn.del_multisig_operation(local_op.idx)?;
+        }
+
+        // the last processed operation can only move past operations that have been processed
+        let last_processed = self.get_local_last_processed_operation_idx_impl(txn)?;
+        if operation_idx == last_processed + 1 {
+            let processed: HashMap<i32, i32> = txn
+                .iter_multisig_operations()?
+                .into_iter()
+                .filter(|o| o.state == MultisigOperationState::Processed)
+                .map(|o| (o.operation_idx, o.idx))
+                .collect();
+            let mut last_processed = operation_idx;
+            while let Some(idx) = processed.get(&(last_processed + 1)) {
+                txn.del_multisig_operation(*idx)?;
+                last_processed += 1;
+            }
+            self.update_backup_info_with_op_idx(txn, false, Some(last_processed))?;
+        } else {
+            if operation_idx > last_processed {
+                txn.set_multisig_operation(DbMultisigOperationActMod {
+                    operation_idx: ActiveValue::Set(operation_idx),
+                    state: ActiveValue::Set(MultisigOperationState::Processed),
+                    ..Default::default()
+                })?;
+            }
+            self.update_backup_info(txn, false)?;
+        }
         Ok(())
     }
 
-    fn upload_and_process_issuance<T: IssuedAssetDetails>(
+    fn get_local_operations(
         &self,
         txn: &DbTxn,
-        issue_data: &IssueData,
-        mut additional_files: Vec<(FileType, FileSource)>,
-    ) -> Result<T, Error> {
-        let mut files = vec![(
-            FileType::Consignment,
-            FileSource::Path(issue_data.contract_path.clone()),
-        )];
-        files.append(&mut additional_files);
-        let response = self
-            .hub_client()
-            .post_operation(files, OperationType::Issuance)?;
-        let mut runtime = self.rgb_runtime()?;
-        let asset = self.import_and_save_contract(txn, issue_data, &mut runtime)?;
-        self.mark_operation_as_processed(txn, response.operation_idx)?;
-        T::from_issuance(txn, self, &asset, issue_data)
+    ) -> Result<HashMap<i32, DbMultisigOperation>, Error> {
+        Ok(txn
+            .iter_multisig_operations()?
+            .into_iter()
+            .map(|o| (o.operation_idx, o))
+            .collect())
     }
 
-    /// Issue a new RGB NIA asset with the provided `ticker`, `name`, `precision` and `amounts`,
-    /// post the issuance to the hub and return the asset.
-    ///
-    /// At least 1 amount needs to be provided and the sum of all amounts cannot exceed the maximum
-    /// `u64` value.
-    ///
-    /// If `amounts` contains more than 1 element, each one will be issued as a separate allocation
-    /// for the same asset (on a separate UTXO that needs to be already available).
-    pub fn issue_asset_nia(
+    fn get_next_new_operation_idx(&self, txn: &DbTxn) -> Result<i32, Error> {
+        let last_processed = self.get_local_last_processed_operation_idx_impl(txn)?;
+        Ok(txn
+            .iter_multisig_operations()?
+            .into_iter()
+            .map(|o| o.operation_idx)
+            .fold(last_processed, max)
+            .checked_add(1)
+            .expect("operation index cannot exceed i32::MAX"))
+    }
+
+    fn get_spending_txids(&self) -> HashMap<BdkOutPoint, String> {
+        self.bdk_wallet()
+            .transactions()
+            .flat_map(|t| {
+                let txid = t.tx_node.txid.to_string();
+                t.tx_node
+                    .tx
+                    .input
+                    .iter()
+                    .map(|i| (i.previous_output, txid.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    // Start tracking a pending operation, reserving its inputs so they cannot be spent by other
+    // operations, unless some of them have already been spent.
+    fn track_operation(
         &self,
-        online: Online,
-        ticker: String,
-        name: String,
-        precision: u8,
-        amounts: Vec<u64>,
-    ) -> Result<AssetNIA, Error> {
-        info!(self.logger(), "Issuing NIA...");
-        self.check_online(online)?;
-        self.check_is_cosigner()?;
-        let txn = self.database().begin_transaction()?;
-        let issue_data = self.create_nia_contract(&txn, ticker, name, precision, amounts)?;
+        txn: &DbTxn,
+        operation_idx: i32,
+        psbt: &Psbt,
+    ) -> Result<MultisigOperationState, Error> {
+        if let Some(local_op) = self.get_local_operations(txn)?.remove(&operation_idx) {
+            return Ok(local_op.state);
+        }
+        let txid = psbt.unsigned_tx.compute_txid().to_string();
+        let inputs: Vec<BdkOutPoint> = psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .map(|i| i.previous_output)
+            .collect();
+        let spending_txids = self.get_spending_txids();
+        let conflicting = inputs
+            .iter()
+            .any(|i| spending_txids.get(i).is_some_and(|t| *t != txid));
+        let state = if conflicting {
+            MultisigOperationState::Conflicting
+        } else {
+            MultisigOperationState::Pending
+        };
+        txn.set_multisig_operation(DbMultisigOperationActMod {
+            operation_idx: ActiveValue::Set(operation_idx),
+            state: ActiveValue::Set(state),
+            txid: ActiveValue::Set(Some(txid)),
+            ..Default::default()
+        })?;
+        if state == MultisigOperationState::Pending {
+            let reservations: Vec<DbReservedTxoActMod> = inputs
+                .iter()
+                .map(|i| DbReservedTxoActMod {
+                    txid: ActiveValue::Set(i.txid.to_string()),
+                    vout: ActiveValue::Set(i.vout.into()),
+                    operation_idx: ActiveValue::Set(Some(operation_idx)),
+                    ..Default::default()
+                })
+                .collect();
+            txn.set_reserved_txos(reservations)?;
+        }
+        Ok(state)
+    }
+
+    fn release_operation_txos(&self, txn: &DbTxn, operation_idx: i32) -> Result<(), Error> {
+        let reservations: Vec<DbReservedTxo> = txn
+            .iter_reserved_txos()?
+            .into_iter()
+            .filter(|r| r.operation_idx == Some(operation_idx))
+            .collect();
+        txn.del_reserved_txos(&reservations)
+    }
+
+    // Mark as conflicting the pending operations with inputs spent by other transactions,
+    // releasing their reserved TXOs. They will be handled as discarded when processed.
+    fn discard_conflicting_operations(
+        &self,
+        txn: &DbTxn,
+        spending_txids: &HashMap<BdkOutPoint, String>,
+    ) -> Result<(), Error> {
+        let reservations = txn.iter_reserved_txos()?;
+        for local_op in txn.iter_multisig_operations()? {
+            if local_op.state != MultisigOperationState::Pending {
+                continue;
+            }
+            let conflicting = reservations
+                .iter()
+                .filter(|r| r.operation_idx == Some(local_op.operation_idx))
+                .filter_map(|r| spending_txids.get(&BdkOutPoint::from(r.clone())))
+                .any(|t| Some(t) != local_op.txid.as_ref());
+            if !conflicting {
+                continue;
+            }
+            debug!(
+                self.logger(),
+                "Discarding operation {} with spent inputs", local_op.operation_idx
+            );
+            self.discard_operation(txn, local_op, MultisigOperationState::Conflicting)?;
+        }
+        Ok(())
+    }
+
+    // Mark a pending operation as discarded locally, with the given state, and release its
+    // reserved TXOs.
+    fn discard_operation(
+        &self,
+        txn: &DbTxn,
+        local_op: DbMultisigOperation,
+        state: MultisigOperationState,
+    ) -> Result<(), Error> {
+        self.release_operation_txos(txn, local_op.operation_idx)?;
+        let mut local_op: DbMultisigOperationActMod = local_op.into();
+        local_op.state = ActiveValue::Set(state);
+        txn.update_multisig_operation(local_op)
+    }
+
+    fn is_operation_expired(&self, expiry: &OperationExpiry) -> bool {
+        match expiry {
+            OperationExpiry::BlockHeight(height) => {
+                self.bdk_wallet().latest_checkpoint().height() >= *height
+            }
+            OperationExpiry::Timestamp(timestamp) => {
+                now().unix_timestamp() >= i64::try_from(*timestamp).unwrap_or(i64::MAX)
+            }
+        }
+    }
+
+    fn get_operation_expiry(
+        &self,
+        op: &OperationResponse,
+    ) -> Result<Option<OperationExpiry>, Error> {
+        let Some(expiry_file) = op
+            .files
+            .iter()
+            .find(|f| f.r#type == FileType::OperationExpiry)
+        else {
+            return Ok(None);
+        };
+        let expiry_path = self.get_or_download_file(expiry_file)?;
+        let expiry_data: OperationExpiryData = serde_json::from_slice(&fs::read(expiry_path)?)
+            .map_err(|_| Error::MultisigUnexpectedData {
+                details: s!("invalid operation expiry"),
+            })?;
+        Ok(Some(expiry_data.try_into()?))
+    }
+
+    // Check whether a pending operation has been cancelled by its initiator or has expired,
+    // returning the state it should be discarded with.
+    fn check_operation_withdrawn(
+        &self,
+        op: &OperationResponse,
+    ) -> Result<Option<MultisigOperationState>, Error> {
+        // the cancellation needs to be signed by the initiator
+        let cancellation_hash = self.cancellation_hash(op.operation_idx);
+        for file in op
+            .files
+            .iter()
+            .filter(|f| f.r#type == FileType::Cancellation && f.posted_by_xpub == op.initiator_xpub)
+        {
+            let cancellation_path = self.get_or_download_file(file)?;
+            let signature = fs::read(cancellation_path)?;
+            if str::from_utf8(&signature).is_ok_and(|signature| {
+                self.verify_cosigner_signature(&op.initiator_xpub, cancellation_hash, signature)
+            }) {
+                return Ok(Some(MultisigOperationState::Cancelled));
+            }
+            warn!(
+                self.logger(),
+                "Ignoring invalid cancellation of operation {}", op.operation_idx
+            );
+        }
+        if let Some(expiry) = self.get_operation_expiry(op)?
+            && self.is_operation_expired(&expiry)
+        {
+            return Ok(Some(MultisigOperationState::Expired));
+        }
+        Ok(None)
+    }
+
+    fn upload_and_process_issuance<T: IssuedAssetDetails>(
+        &self,
+        txn: &DbTxn,
+        issue_data: &IssueData,
+        mut additional_files: Vec<(FileType, FileSource)>,
+    ) -> Result<T, Error> {
+        let mut files = vec![(
+            FileType::Consignment,
+            FileSource::Path(issue_data.contract_path.clone()),
+        )];
+        files.append(&mut additional_files);
+        let operation_idx = self.publish_operation(txn, files, OperationType::Issuance)?;
+        let mut runtime = self.rgb_runtime()?;
+        let asset = self.import_and_save_contract(txn, issue_data, &mut runtime)?;
+        self.mark_operation_as_processed(txn, operation_idx)?;
+        T::from_issuance(txn, self, &asset, issue_data)
+    }
+
+    /// Issue a new RGB NIA asset with the provided `ticker`, `name`, `precision` and `amounts`,
+    /// post the issuance to the hub and return the asset.
+    ///
+    /// At least 1 amount needs to be provided and the sum of all amounts cannot exceed the maximum
+    /// `u64` value.
+    ///
+    /// If `amounts` contains more than 1 element, each one will be issued as a separate allocation
+    /// for the same asset (on a separate UTXO that needs to be already available).
+    pub fn issue_asset_nia(
+        &self,
+        online: Online,
+        ticker: String,
+        name: String,
+        precision: u8,
+        amounts: Vec<u64>,
+    ) -> Result<AssetNIA, Error> {
+        info!(self.logger(), "Issuing NIA...");
+        self.check_online(online)?;
+        self.check_is_cosigner()?;
+        let txn = self.database().begin_transaction()?;
+        let issue_data = self.create_nia_contract(&txn, ticker, name, precision, amounts)?;
         let res = self.upload_and_process_issuance(&txn, &issue_data, vec![])?;
         txn.commit()?;
         info!(self.logger(), "Issue asset NIA completed");
@@ -1492,15 +2355,19 @@ impl MultisigWallet {
         };
         let metadata_json = serde_json::to_vec(&receive_metadata).expect("serializable");
         let files = vec![(FileType::OperationData, FileSource::Bytes(metadata_json))];
-        let response = self.hub_client().post_operation(files, operation_type)?;
+        let operation_idx = self.publish_operation(&txn, files, operation_type)?;
 
         // store transfer
-        let batch_transfer_idx =
-            self.store_receive_transfer(&txn, &receive_data_internal, min_confirmations)?;
+        let batch_transfer_idx = self.store_receive_transfer(
+            &txn,
+            &receive_data_internal,
+            ReceivePolicy::Any,
+            min_confirmations,
+        )?;
 
         self.update_backup_info(&txn, false)?;
 
-        self.mark_operation_as_processed(&txn, response.operation_idx)?;
+        self.mark_operation_as_processed(&txn, operation_idx)?;
 
         txn.commit()?;
 
@@ -1791,8 +2658,12 @@ impl MultisigWallet {
             recipient_type_full,
             script_pubkey,
         };
-        let batch_transfer_idx =
-            self.store_receive_transfer(txn, &receive_data_internal, min_confirmations)?;
+        let batch_transfer_idx = self.store_receive_transfer(
+            txn,
+            &receive_data_internal,
+            ReceivePolicy::Any,
+            min_confirmations,
+        )?;
 
         Ok(ReceiveData {
             invoice: receive_metadata.invoice,
@@ -1808,10 +2679,31 @@ impl MultisigWallet {
     /// local index) from the hub.
     /// If the operation is found, it is processed and the operation info is returned.
     /// If the operation isn't found (i.e. the cosigner is already in sync), None is returned.
+    ///
+    /// Multiple operations can be pending at the same time: the inputs of each pending operation
+    /// are reserved so they won't be used by new operations. Pending operations are returned until
+    /// the cosigner responds to them, after which they're skipped until their status changes.
+    /// Pending operations with inputs spent by another operation are discarded.
+    ///
+    /// When coordinating without a hub, the next operation is looked up among the ones
+    /// initiated or imported locally.
     pub fn sync_with_hub(&mut self, online: Online) -> Result<Option<OperationInfo>, Error> {
         info!(self.logger(), "Syncing with hub...");
         self.check_online(online)?;
+        let operation_info = self.process_next_operation()?;
+        info!(self.logger(), "Sync with hub completed");
+        Ok(operation_info)
+    }
 
+    fn process_next_operation(&mut self) -> Result<Option<OperationInfo>, Error> {
+        self.sync_for_operations()?;
+        let Some(op) = self.get_next_operation()? else {
+            return Ok(None);
+        };
+        Ok(Some(self.process_and_refresh(op)?))
+    }
+
+    fn sync_for_operations(&mut self) -> Result<(), Error> {
         let txn = self.database().begin_transaction()?;
 
         // make sure the wallet is synced and transfers are up-to-date
@@ -1836,15 +2728,114 @@ impl MultisigWallet {
         self.refresh_impl(&txn, None, vec![], true)?;
         self.refresh_impl(&txn, None, vec![], true)?;
 
-        let op_idx = self.get_local_last_processed_operation_idx_impl(&txn)?;
+        // pending operations with inputs that have been spent cannot be completed anymore
+        let spending_txids = self.get_spending_txids();
+        self.discard_conflicting_operations(&txn, &spending_txids)?;
         txn.commit()?;
-        let next_op_idx = op_idx
-            .checked_add(1)
-            .expect("operation index cannot exceed i32::MAX");
-        let Some(op) = self.hub_client().get_operation_by_idx(next_op_idx)? else {
+        Ok(())
+    }
+
+    // Get the first operation that needs to be handled, skipping the pending ones that don't need
+    // a response from this cosigner, as they don't block the following ones.
+    fn get_next_operation(&self) -> Result<Option<OperationResponse>, Error> {
+        let txn = self.database().begin_transaction()?;
+        let mut op_idx = self.get_local_last_processed_operation_idx_impl(&txn)?;
+        let local_ops = self.get_local_operations(&txn)?;
+        txn.commit()?;
+        let is_cosigner = self.is_cosigner()?;
+        loop {
+            op_idx = op_idx
+                .checked_add(1)
+                .expect("operation index cannot exceed i32::MAX");
+            let local_state = local_ops.get(&op_idx).map(|o| o.state);
+            if local_state == Some(MultisigOperationState::Processed) {
+                continue;
+            }
+            let Some(op) = self.load_operation(op_idx, local_state)? else {
+                if local_state.is_some() {
+                    return Err(Error::MultisigOperationNotFound {
+                        operation_idx: op_idx,
+                    });
+                }
+                return Ok(None);
+            };
+            let skip = local_state == Some(MultisigOperationState::Pending)
+                && op.status == OperationStatus::Pending
+                && (!is_cosigner || op.my_response.is_some());
+            if !skip {
+                return Ok(Some(op));
+            }
+        }
+    }
+
+    // Get an operation, starting to track it if it's a new pending one. Operations discarded
+    // locally because of conflicting inputs, cancellation or expiry are returned as discarded,
+    // unless the hub approved them before the cancellation or expiry.
+    fn load_operation(
+        &self,
+        operation_idx: i32,
+        local_state: Option<MultisigOperationState>,
+    ) -> Result<Option<OperationResponse>, Error> {
+        let Some(mut op) = self.get_operation_by_idx(operation_idx)? else {
             return Ok(None);
         };
+        let mut local_state = match local_state {
+            None if op.status == OperationStatus::Pending => {
+                let psbt = self.get_operation_psbt(&op)?;
+                let txn = self.database().begin_transaction()?;
+                let state = self.track_operation(&txn, operation_idx, &psbt)?;
+                txn.commit()?;
+                Some(state)
+            }
+            local_state => local_state,
+        };
+        if local_state == Some(MultisigOperationState::Pending)
+            && op.status == OperationStatus::Pending
+            && let Some(state) = self.check_operation_withdrawn(&op)?
+        {
+            debug!(
+                self.logger(),
+                "Discarding operation {} as {:?}", operation_idx, state
+            );
+            let txn = self.database().begin_transaction()?;
+            let local_op = self
+                .get_local_operations(&txn)?
+                .remove(&operation_idx)
+                .expect("operation is tracked");
+            self.discard_operation(&txn, local_op, state)?;
+            txn.commit()?;
+            local_state = Some(state);
+        }
+        // cancellation and expiry only apply while the operation is pending on the hub: if it got
+        // approved in the meantime it needs to be completed, so its TXOs are tracked again
+        if op.status == OperationStatus::Approved
+            && matches!(
+                local_state,
+                Some(MultisigOperationState::Cancelled | MultisigOperationState::Expired)
+            )
+        {
+            debug!(
+                self.logger(),
+                "Restoring operation {} approved on the hub", operation_idx
+            );
+            let psbt = self.get_operation_psbt(&op)?;
+            let txn = self.database().begin_transaction()?;
+            let local_op = self
+                .get_local_operations(&txn)?
+                .remove(&operation_idx)
+                .expect("operation is tracked");
+            txn.del_multisig_operation(local_op.idx)?;
+            let state = self.track_operation(&txn, operation_idx, &psbt)?;
+            txn.commit()?;
+            local_state = Some(state);
+        }
+        if local_state.is_some_and(|s| s.discarded()) {
+            op.status = OperationStatus::Discarded;
+        }
+        Ok(Some(op))
+    }
 
+    fn process_and_refresh(&mut self, op: OperationResponse) -> Result<OperationInfo, Error> {
         let operation = self.process_operation(&op)?;
 
         // refresh when needed
@@ -1884,12 +2875,11 @@ impl MultisigWallet {
         let txn = self.database().begin_transaction()?;
         self.update_backup_info(&txn, false)?;
         txn.commit()?;
-        info!(self.logger(), "Sync with hub completed");
-        Ok(Some(OperationInfo {
+        Ok(OperationInfo {
             operation_idx: op.operation_idx,
             initiator_xpub: op.initiator_xpub,
             operation,
-        }))
+        })
     }
 
     pub(crate) fn read_psbt_from_file(path: &Path) -> Result<Psbt, Error> {
@@ -1907,7 +2897,12 @@ impl MultisigWallet {
             .ok_or(Error::MultisigUnexpectedData {
                 details: s!("PSBT not found"),
             })?;
-        let psbt = Self::read_psbt_from_file(&psbt_file.filepath)?;
+        let mut psbt = Self::read_psbt_from_file(&psbt_file.filepath)?;
+        // include the MuSig2 nonces posted so far
+        for file in files.iter().filter(|f| f.r#type == FileType::MusigNonces) {
+            psbt.combine(Self::read_psbt_from_file(&file.filepath)?)
+                .map_err(|_| Error::CannotCombinePsbts)?;
+        }
         Ok(psbt.to_string())
     }
 
@@ -1925,9 +2920,9 @@ impl MultisigWallet {
         })
     }
 
+    // a single PSBT is enough when the threshold of the spending policy is 1
     fn combine_psbts_from_files(files: &[FileResponse]) -> Result<Psbt, Error> {
         let mut combined_psbt: Option<Psbt> = None;
-        let mut combined = false;
         for file in files {
             if matches!(file.r#type, FileType::ResponsePsbt) {
                 let psbt = Self::read_psbt_from_file(&file.filepath)?;
@@ -1935,18 +2930,14 @@ impl MultisigWallet {
                     combined_psbt
                         .combine(psbt.clone())
                         .map_err(|_| Error::CannotCombinePsbts)?;
-                    combined = true;
                 } else {
                     combined_psbt = Some(psbt);
                 }
             }
         }
-        if !combined {
-            return Err(Error::MultisigUnexpectedData {
-                details: s!("insufficient PSBTs supplied"),
-            });
-        }
-        Ok(combined_psbt.expect("should exist"))
+        combined_psbt.ok_or(Error::MultisigUnexpectedData {
+            details: s!("insufficient PSBTs supplied"),
+        })
     }
 
     fn handle_operation<H: OperationHandler>(
@@ -1967,6 +2958,8 @@ impl MultisigWallet {
             }
             (OperationStatus::Approved, _) => {
                 let mut combined_psbt = Self::combine_psbts_from_files(files)?;
+                // spend via the key path the inputs with all MuSig2 partial signatures
+                musig::aggregate_partial_sigs(&mut combined_psbt)?;
                 self.finalize_psbt_impl(&mut combined_psbt, None)?;
                 let txid = combined_psbt.unsigned_tx.compute_txid().to_string();
                 H::reconstruct_transfer_directory(self, &txid, files)?;
@@ -1974,6 +2967,14 @@ impl MultisigWallet {
                 let txid = H::finalize_and_execute(&txn, self, &combined_psbt)?;
                 self.update_backup_info(&txn, false)?;
                 self.mark_operation_as_processed(&txn, op.operation_idx)?;
+                // other pending operations spending the same inputs cannot be completed anymore
+                let spending_txids = combined_psbt
+                    .unsigned_tx
+                    .input
+                    .iter()
+                    .map(|i| (i.previous_output, txid.clone()))
+                    .collect();
+                self.discard_conflicting_operations(&txn, &spending_txids)?;
                 txn.commit()?;
                 let status = Self::build_voting_status(op, op.my_response)?;
                 Ok(H::completed(txid, details, status))
@@ -2047,18 +3048,12 @@ impl MultisigWallet {
         self.check_is_cosigner()?;
 
         // check we can respond to operation
-        let op = self
-            .hub_client()
-            .get_operation_by_idx(operation_idx)?
-            .ok_or(Error::MultisigOperationNotFound { operation_idx })?;
-        if op.status != OperationStatus::Pending {
+        let op = self.get_respondable_operation(operation_idx)?;
+        let recovery_policy_idx = self.get_operation_recovery_policy(&op)?;
+        let (cosigners, _) = self.operation_spending_policy(op.operation_type, recovery_policy_idx);
+        if !cosigners.contains(&self.cosigner_xpub()?) {
             return Err(Error::MultisigCannotRespondToOperation {
-                details: s!("not pending"),
-            });
-        }
-        if op.my_response.is_some() {
-            return Err(Error::MultisigCannotRespondToOperation {
-                details: s!("already responded"),
+                details: s!("not a cosigner of the operation spending policy"),
             });
         }
 
@@ -2073,33 +3068,25 @@ impl MultisigWallet {
             }
 
             // check PSBT is the one from the operation we are responding
-            let psbt_file = op
-                .files
-                .iter()
-                .find(|f| f.r#type == FileType::OperationPsbt);
-            let Some(psbt_file) = psbt_file else {
-                return Err(Error::MultisigUnexpectedData {
-                    details: s!("operation should have a PSBT"),
-                });
-            };
-            let op_psbt_path = self.get_or_download_file(psbt_file)?;
-            let op_psbt = Self::read_psbt_from_file(&op_psbt_path)?;
-            if op_psbt.unsigned_tx.compute_txid() != psbt.unsigned_tx.compute_txid() {
-                return Err(Error::InvalidPsbt {
-                    details: s!("PSBT unrelated to operation"),
-                });
-            }
+            self.check_operation_psbt(&op, &psbt)?;
         }
 
         // send response to hub
-        let operation_response = self
-            .hub_client()
-            .respond_to_operation(operation_idx, respond_to_operation)?;
+        let operation_response = match self.hub_client() {
+            Ok(hub_client) => {
+                hub_client.respond_to_operation(operation_idx, respond_to_operation)?
+            }
+            Err(_) => self.respond_to_local_operation(operation_idx, respond_to_operation)?,
+        };
 
         // process operation
         let operation = self.process_operation(&operation_response)?;
 
         let txn = self.database().begin_transaction()?;
+        if operation_response.status == OperationStatus::Pending {
+            let psbt = self.get_operation_psbt(&operation_response)?;
+            self.track_operation(&txn, operation_idx, &psbt)?;
+        }
         self.update_backup_info(&txn, false)?;
         txn.commit()?;
         info!(self.logger(), "Responding to operation...");
@@ -2110,13 +3097,675 @@ impl MultisigWallet {
         })
     }
 
+    fn get_respondable_operation(&self, operation_idx: i32) -> Result<OperationResponse, Error> {
+        let op = self
+            .get_operation_by_idx(operation_idx)?
+            .ok_or(Error::MultisigOperationNotFound { operation_idx })?;
+        // operations already processed or discarded locally might still be pending on the hub
+        let txn = self.database().begin_transaction()?;
+        let last_processed = self.get_local_last_processed_operation_idx_impl(&txn)?;
+        let local_state = self
+            .get_local_operations(&txn)?
+            .remove(&operation_idx)
+            .map(|o| o.state);
+        txn.commit()?;
+        if op.status != OperationStatus::Pending
+            || operation_idx <= last_processed
+            || local_state.is_some_and(|s| s != MultisigOperationState::Pending)
+        {
+            return Err(Error::MultisigCannotRespondToOperation {
+                details: s!("not pending"),
+            });
+        }
+        if op.my_response.is_some() {
+            return Err(Error::MultisigCannotRespondToOperation {
+                details: s!("already responded"),
+            });
+        }
+        match self.check_operation_withdrawn(&op)? {
+            Some(MultisigOperationState::Cancelled) => {
+                return Err(Error::MultisigCannotRespondToOperation {
+                    details: s!("cancelled"),
+                });
+            }
+            Some(MultisigOperationState::Expired) => {
+                return Err(Error::MultisigCannotRespondToOperation {
+                    details: s!("expired"),
+                });
+            }
+            _ => {}
+        }
+        Ok(op)
+    }
+
+    fn get_operation_psbt(&self, op: &OperationResponse) -> Result<Psbt, Error> {
+        let psbt_file = op
+            .files
+            .iter()
+            .find(|f| f.r#type == FileType::OperationPsbt);
+        let Some(psbt_file) = psbt_file else {
+            return Err(Error::MultisigUnexpectedData {
+                details: s!("operation should have a PSBT"),
+            });
+        };
+        let op_psbt_path = self.get_or_download_file(psbt_file)?;
+        Self::read_psbt_from_file(&op_psbt_path)
+    }
+
+    fn check_operation_psbt(&self, op: &OperationResponse, psbt: &Psbt) -> Result<(), Error> {
+        let op_psbt = self.get_operation_psbt(op)?;
+        if op_psbt.unsigned_tx.compute_txid() != psbt.unsigned_tx.compute_txid() {
+            return Err(Error::InvalidPsbt {
+                details: s!("PSBT unrelated to operation"),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn get_operation_by_idx(
+        &self,
+        operation_idx: i32,
+    ) -> Result<Option<OperationResponse>, Error> {
+        if let Ok(hub_client) = self.hub_client() {
+            return hub_client.get_operation_by_idx(operation_idx);
+        }
+        self.get_local_operation_package(operation_idx)?
+            .map(|package| self.operation_response_from_package(&package))
+            .transpose()
+    }
+
+    fn wallet_id(&self) -> String {
+        let descs = self.get_descriptors();
+        hash_bytes_hex(format!("{}|{}", descs.colored, descs.vanilla).as_bytes())
+    }
+
+    fn get_operation_package_path(&self, operation_idx: i32) -> PathBuf {
+        self.get_wallet_dir()
+            .join(OPERATION_PACKAGES_DIR)
+            .join(operation_idx.to_string())
+    }
+
+    fn get_local_operation_package(
+        &self,
+        operation_idx: i32,
+    ) -> Result<Option<OperationPackage>, Error> {
+        let package_path = self.get_operation_package_path(operation_idx);
+        if !package_path.exists() {
+            return Ok(None);
+        }
+        OperationPackage::read(&package_path).map(Some)
+    }
+
+    fn save_operation_package(&self, package: &OperationPackage) -> Result<(), Error> {
+        fs::create_dir_all(self.get_wallet_dir().join(OPERATION_PACKAGES_DIR))?;
+        package.write(&self.get_operation_package_path(package.operation_idx))
+    }
+
+    // xPubs of the cosigners that can approve an operation, with the required threshold,
+    // depending on the spending policy selected by the initiator
+    fn operation_spending_policy(
+        &self,
+        operation_type: OperationType,
+        recovery_policy_idx: Option<u8>,
+    ) -> (HashSet<String>, u8) {
+        let (cosigners, threshold) = match recovery_policy_idx {
+            Some(idx) => {
+                let recovery_policy = &self.keys.recovery_policies[idx as usize];
+                let cosigners = if recovery_policy.cosigners.is_empty() {
+                    &self.keys.cosigners
+                } else {
+                    &recovery_policy.cosigners
+                };
+                (cosigners, recovery_policy.threshold)
+            }
+            None => {
+                let threshold = match operation_type {
+                    OperationType::CreateUtxos | OperationType::SendBtc => {
+                        self.keys.threshold_vanilla
+                    }
+                    _ => self.keys.threshold_colored.max(self.keys.threshold_vanilla),
+                };
+                (&self.keys.cosigners, threshold)
+            }
+        };
+        let xpubs = cosigners
+            .iter()
+            .map(|c| c.account_xpub_colored.clone())
+            .collect();
+        (xpubs, threshold)
+    }
+
+    fn parse_recovery_policy(&self, bytes: &[u8]) -> Result<u8, Error> {
+        let recovery_policy_idx: u8 =
+            serde_json::from_slice(bytes).map_err(|_| Error::MultisigUnexpectedData {
+                details: s!("invalid recovery policy"),
+            })?;
+        if recovery_policy_idx as usize >= self.keys.recovery_policies.len() {
+            return Err(Error::InvalidRecoveryPolicy {
+                details: format!("unknown policy {recovery_policy_idx}"),
+            });
+        }
+        Ok(recovery_policy_idx)
+    }
+
+    fn get_operation_recovery_policy(&self, op: &OperationResponse) -> Result<Option<u8>, Error> {
+        let Some(recovery_policy_file) = op
+            .files
+            .iter()
+            .find(|f| f.r#type == FileType::RecoveryPolicy)
+        else {
+            return Ok(None);
+        };
+        let recovery_policy_path = self.get_or_download_file(recovery_policy_file)?;
+        self.parse_recovery_policy(&fs::read(recovery_policy_path)?)
+            .map(Some)
+    }
+
+    fn get_package_recovery_policy(&self, package: &OperationPackage) -> Result<Option<u8>, Error> {
+        package
+            .files
+            .iter()
+            .find(|f| f.r#type == FileType::RecoveryPolicy)
+            .map(|f| self.parse_recovery_policy(&f.bytes()?))
+            .transpose()
+            .map_err(|e| Error::InvalidOperationPackage {
+                details: e.to_string(),
+            })
+    }
+
+    fn operation_response_from_package(
+        &self,
+        package: &OperationPackage,
+    ) -> Result<OperationResponse, Error> {
+        // cache the files as if they were downloaded from the hub
+        let mut files = vec![];
+        for file in &package.files {
+            let bytes = file.bytes()?;
+            let file_id = hash_bytes_hex(&bytes);
+            let filepath = self.get_cached_file_path(&file_id);
+            if !filepath.exists() {
+                fs::write(&filepath, &bytes)?;
+            }
+            files.push(FileMetadata {
+                file_id,
+                r#type: file.r#type,
+                posted_by_xpub: file.posted_by_xpub.clone(),
+                size_bytes: bytes.len() as u64,
+            });
+        }
+        let recovery_policy_idx = self.get_package_recovery_policy(package)?;
+        let (cosigners, threshold) =
+            self.operation_spending_policy(package.operation_type, recovery_policy_idx);
+        let max_nacks = cosigners.len() - threshold as usize;
+        let status = match package.operation_type {
+            OperationType::Issuance
+            | OperationType::BlindReceive
+            | OperationType::WitnessReceive => OperationStatus::Approved,
+            _ if package.acked_by.len() >= threshold as usize => OperationStatus::Approved,
+            _ if package.nacked_by.len() > max_nacks => OperationStatus::Discarded,
+            _ => OperationStatus::Pending,
+        };
+        let my_response = self.cosigner_xpub().ok().and_then(|xpub| {
+            if package.acked_by.contains(&xpub) {
+                Some(true)
+            } else if package.nacked_by.contains_key(&xpub) {
+                Some(false)
+            } else {
+                None
+            }
+        });
+        Ok(OperationResponse {
+            operation_idx: package.operation_idx,
+            initiator_xpub: package.initiator_xpub.clone(),
+            created_at: package.created_at,
+            operation_type: package.operation_type,
+            status,
+            acked_by: package.acked_by.clone(),
+            nacked_by: package.nacked_by.keys().cloned().collect(),
+            threshold: Some(threshold),
+            my_response,
+            processed_at: None,
+            files,
+        })
+    }
+
+    fn check_response_psbt(&self, op_psbt: &Psbt, psbt: &Psbt, xpub: &str) -> Result<(), Error> {
+        if op_psbt.unsigned_tx.compute_txid() != psbt.unsigned_tx.compute_txid() {
+            return Err(Error::InvalidPsbt {
+                details: s!("PSBT unrelated to operation"),
+            });
+        }
+        let cosigner = self
+            .find_cosigner(xpub)
+            .ok_or(Error::MultisigUserNotCosigner)?;
+        let fingerprint = Fingerprint::from_str(&cosigner.master_fingerprint)
+            .expect("validated at wallet creation");
+        let signed_by_cosigner = psbt.inputs.iter().any(|input| {
+            input.tap_script_sigs.keys().any(|(key, _)| {
+                input
+                    .tap_key_origins
+                    .get(key)
+                    .is_some_and(|(_, (fp, _))| *fp == fingerprint)
+            })
+        });
+        if !signed_by_cosigner {
+            return Err(Error::InvalidPsbt {
+                details: format!("PSBT not signed by cosigner '{xpub}'"),
+            });
+        }
+        Ok(())
+    }
+
+    fn check_operation_package(&self, package: &OperationPackage) -> Result<(), Error> {
+        let invalid = |details: String| Error::InvalidOperationPackage { details };
+        if package.version != OPERATION_PACKAGE_VERSION {
+            return Err(invalid(format!("unsupported version {}", package.version)));
+        }
+        if package.wallet_id != self.wallet_id() {
+            return Err(invalid(s!("package belongs to a different wallet")));
+        }
+        let mut xpubs = [&package.initiator_xpub]
+            .into_iter()
+            .chain(&package.acked_by)
+            .chain(package.nacked_by.keys())
+            .chain(package.files.iter().map(|f| &f.posted_by_xpub));
+        if let Some(xpub) = xpubs.find(|x| !self.is_cosigner_xpub(x)) {
+            return Err(invalid(format!("unknown cosigner '{xpub}'")));
+        }
+        if let Some(xpub) = package
+            .acked_by
+            .iter()
+            .find(|x| package.nacked_by.contains_key(*x))
+        {
+            return Err(invalid(format!("cosigner '{xpub}' both ACKed and NACKed")));
+        }
+        let recovery_policy_idx = self.get_package_recovery_policy(package)?;
+        let (cosigners, _) =
+            self.operation_spending_policy(package.operation_type, recovery_policy_idx);
+        if let Some(xpub) = package
+            .acked_by
+            .iter()
+            .chain(package.nacked_by.keys())
+            .find(|x| !cosigners.contains(*x))
+        {
+            return Err(invalid(format!(
+                "cosigner '{xpub}' not part of the operation spending policy"
+            )));
+        }
+
+        // each contribution needs to be signed by the cosigner it's attributed to
+        let operation_hash = package.operation_hash();
+        if !self.verify_cosigner_signature(
+            &package.initiator_xpub,
+            operation_hash,
+            &package.initiator_signature,
+        ) {
+            return Err(invalid(s!("invalid initiator signature")));
+        }
+        let nack_hash = package.nack_hash();
+        for (xpub, signature) in &package.nacked_by {
+            if !self.verify_cosigner_signature(xpub, nack_hash, signature) {
+                return Err(invalid(format!(
+                    "invalid NACK signature of cosigner '{xpub}'"
+                )));
+            }
+        }
+        for file in &package.files {
+            if !file.is_response() && file.posted_by_xpub != package.initiator_xpub {
+                return Err(invalid(s!("operation file not posted by the initiator")));
+            }
+            if !self.verify_cosigner_signature(
+                &file.posted_by_xpub,
+                file.signed_hash(&operation_hash),
+                &file.signature,
+            ) {
+                return Err(invalid(format!(
+                    "invalid file signature of cosigner '{}'",
+                    file.posted_by_xpub
+                )));
+            }
+        }
+
+        // ACKs are authenticated by the cosigner signatures on the response PSBTs
+        if package.acked_by.is_empty() {
+            return Ok(());
+        }
+        let read_psbt = |file: &PackageFile| {
+            Psbt::deserialize(&file.bytes()?).map_err(|_| invalid(s!("invalid PSBT")))
+        };
+        let op_psbt = package
+            .files
+            .iter()
+            .find(|f| f.r#type == FileType::OperationPsbt)
+            .ok_or(invalid(s!("operation PSBT not found")))?;
+        let op_psbt = read_psbt(op_psbt)?;
+        for xpub in &package.acked_by {
+            let mut responses = package
+                .files
+                .iter()
+                .filter(|f| f.r#type == FileType::ResponsePsbt && &f.posted_by_xpub == xpub)
+                .peekable();
+            if responses.peek().is_none() {
+                return Err(invalid(format!("missing PSBT of cosigner '{xpub}'")));
+            }
+            for response in responses {
+                self.check_response_psbt(&op_psbt, &read_psbt(response)?, xpub)?;
+            }
+        }
+        Ok(())
+    }
+
+    // add a file to the package, signed by this cosigner
+    fn add_package_file(
+        &self,
+        package: &mut OperationPackage,
+        r#type: FileType,
+        bytes: &[u8],
+    ) -> Result<(), Error> {
+        let mut file = PackageFile::new(r#type, &self.cosigner_xpub()?, bytes);
+        file.signature = self.sign_as_cosigner(file.signed_hash(&package.operation_hash()))?;
+        package.files.push(file);
+        Ok(())
+    }
+
+    fn respond_to_local_operation(
+        &self,
+        operation_idx: i32,
+        respond_to_operation: RespondToOperation,
+    ) -> Result<OperationResponse, Error> {
+        let xpub = self.cosigner_xpub()?;
+        let mut package = self
+            .get_local_operation_package(operation_idx)?
+            .ok_or(Error::MultisigOperationNotFound { operation_idx })?;
+        match respond_to_operation {
+            RespondToOperation::Ack(psbt) => {
+                let psbt = Psbt::from_str(&psbt)?;
+                self.add_package_file(&mut package, FileType::ResponsePsbt, &psbt.serialize())?;
+                package.acked_by.insert(xpub);
+            }
+            RespondToOperation::Nack => {
+                let signature = self.sign_as_cosigner(package.nack_hash())?;
+                package.nacked_by.insert(xpub, signature);
+            }
+        }
+        self.check_operation_package(&package)?;
+        self.save_operation_package(&package)?;
+        self.operation_response_from_package(&package)
+    }
+
+    /// Cancel the pending operation with index `operation_idx`, initiated by this cosigner, and
+    /// return the corresponding [`OperationInfo`].
+    ///
+    /// The operation is marked as discarded and its reserved UTXOs are released. Other cosigners
+    /// will do the same when they next find the operation while calling
+    /// [`sync_with_hub`](MultisigWallet::sync_with_hub) (or, without a hub, when importing the
+    /// package exported after the cancellation).
+    ///
+    /// The cancellation is a signature of the initiator, which the other cosigners verify before
+    /// discarding the operation. When using a hub, this requires the
+    /// [`MultisigOnlineOptions::cosigner_mnemonic`] to be provided and the `hub_operation_files`
+    /// feature, otherwise a [`Error::MultisigCoordinationMode`] error is returned.
+    pub fn cancel_operation(
+        &mut self,
+        online: Online,
+        operation_idx: i32,
+    ) -> Result<OperationInfo, Error> {
+        info!(self.logger(), "Cancelling operation {}...", operation_idx);
+        self.check_online(online)?;
+        self.check_is_cosigner()?;
+
+        // check we can cancel the operation
+        let op = self
+            .get_operation_by_idx(operation_idx)?
+            .ok_or(Error::MultisigOperationNotFound { operation_idx })?;
+        if op.initiator_xpub != self.cosigner_xpub()? {
+            return Err(Error::MultisigCannotCancelOperation {
+                details: s!("not the initiator"),
+            });
+        }
+        let txn = self.database().begin_transaction()?;
+        let last_processed = self.get_local_last_processed_operation_idx_impl(&txn)?;
+        let local_state = self
+            .get_local_operations(&txn)?
+            .remove(&operation_idx)
+            .map(|o| o.state);
+        txn.commit()?;
+        if op.status != OperationStatus::Pending
+            || operation_idx <= last_processed
+            || local_state.is_some_and(|s| s != MultisigOperationState::Pending)
+        {
+            return Err(Error::MultisigCannotCancelOperation {
+                details: s!("not pending"),
+            });
+        }
+
+        // post cancellation, signed by the initiator
+        if self
+            .online_data()
+            .as_ref()
+            .unwrap()
+            .cosigner_keypair
+            .is_none()
+        {
+            return Err(Error::MultisigCannotCancelOperation {
+                details: s!("the cosigner mnemonic is needed to sign the cancellation"),
+            });
+        }
+        let cancellation_bytes = self
+            .sign_as_cosigner(self.cancellation_hash(operation_idx))?
+            .into_bytes();
+        match self.hub_client() {
+            #[cfg(feature = "hub_operation_files")]
+            Ok(hub_client) => {
+                hub_client.post_operation_file(
+                    operation_idx,
+                    FileType::Cancellation,
+                    cancellation_bytes,
+                )?;
+            }
+            #[cfg(not(feature = "hub_operation_files"))]
+            Ok(_) => return Err(Self::hub_operation_files_error(FileType::Cancellation)),
+            Err(_) => {
+                let mut package = self
+                    .get_local_operation_package(operation_idx)?
+                    .ok_or(Error::MultisigOperationNotFound { operation_idx })?;
+                self.add_package_file(&mut package, FileType::Cancellation, &cancellation_bytes)?;
+                self.save_operation_package(&package)?;
+            }
+        }
+
+        // process operation, which is now discarded
+        let op = self
+            .load_operation(operation_idx, local_state)?
+            .ok_or(Error::MultisigOperationNotFound { operation_idx })?;
+        let operation_info = self.process_and_refresh(op)?;
+        info!(self.logger(), "Cancel operation completed");
+        Ok(operation_info)
+    }
+
+    /// Export the operation with index `operation_idx`, along with the responses collected so far,
+    /// to a package file at `package_path`, to be imported by the other cosigners with
+    /// [`import_operation`](MultisigWallet::import_operation).
+    ///
+    /// The package is self-contained: it includes the operation data, PSBT, fascia,
+    /// consignments and media, plus the signed PSBTs of the cosigners that ACKed the operation.
+    ///
+    /// Only available when coordinating without a hub (see
+    /// [`go_online_without_hub`](MultisigWallet::go_online_without_hub)).
+    pub fn export_operation(
+        &self,
+        online: Online,
+        operation_idx: i32,
+        package_path: String,
+    ) -> Result<(), Error> {
+        info!(self.logger(), "Exporting operation {}...", operation_idx);
+        self.check_online(online)?;
+        self.check_no_hub()?;
+        let package = self
+            .get_local_operation_package(operation_idx)?
+            .ok_or(Error::MultisigOperationNotFound { operation_idx })?;
+        package.write(Path::new(&package_path))?;
+        info!(self.logger(), "Export operation completed");
+        Ok(())
+    }
+
+    /// Import an operation package exported by a cosigner with
+    /// [`export_operation`](MultisigWallet::export_operation), process the operation and return
+    /// the corresponding [`OperationInfo`].
+    ///
+    /// Responses already known locally are merged with the ones in the package. Each contribution
+    /// needs to be signed by the cosigner it's attributed to and ACKs are only accepted if the
+    /// attached PSBT is signed by the responding cosigner.
+    ///
+    /// New operations need to be imported in order: the package must be for an operation that is
+    /// still pending locally or for the one following the last known operation. Once the threshold
+    /// is reached the cosigner completing it finalizes the operation and exports the package again
+    /// so the others can process it.
+    ///
+    /// Only available when coordinating without a hub (see
+    /// [`go_online_without_hub`](MultisigWallet::go_online_without_hub)).
+    pub fn import_operation(
+        &mut self,
+        online: Online,
+        package_path: String,
+    ) -> Result<OperationInfo, Error> {
+        info!(self.logger(), "Importing operation...");
+        self.check_online(online)?;
+        self.check_no_hub()?;
+
+        let mut package = OperationPackage::read(Path::new(&package_path))?;
+        self.check_operation_package(&package)?;
+
+        // check the package is for a known pending operation or for the next one
+        let txn = self.database().begin_transaction()?;
+        let last_processed = self.get_local_last_processed_operation_idx_impl(&txn)?;
+        let local_state = self
+            .get_local_operations(&txn)?
+            .remove(&package.operation_idx)
+            .map(|o| o.state);
+        let next_op_idx = self.get_next_new_operation_idx(&txn)?;
+        txn.commit()?;
+        if package.operation_idx <= last_processed
+            || local_state == Some(MultisigOperationState::Processed)
+        {
+            return Err(Error::InvalidOperationPackage {
+                details: format!("operation {} already processed", package.operation_idx),
+            });
+        }
+        if package.operation_idx > next_op_idx {
+            return Err(Error::InvalidOperationPackage {
+                details: format!("operation {next_op_idx} needs to be imported first"),
+            });
+        }
+
+        // merge responses with the local ones and save the result
+        if let Some(local_package) = self.get_local_operation_package(package.operation_idx)? {
+            package.merge(local_package)?;
+            self.check_operation_package(&package)?;
+        }
+        self.save_operation_package(&package)?;
+        self.reveal_addresses_up_to(package.internal_index, package.external_index)?;
+
+        // the operation might get discarded while syncing
+        self.sync_for_operations()?;
+        let txn = self.database().begin_transaction()?;
+        let local_state = self
+            .get_local_operations(&txn)?
+            .remove(&package.operation_idx)
+            .map(|o| o.state);
+        txn.commit()?;
+        let op = self
+            .load_operation(package.operation_idx, local_state)?
+            .expect("operation package has just been saved");
+        let operation_info = self.process_and_refresh(op)?;
+        info!(self.logger(), "Import operation completed");
+        Ok(operation_info)
+    }
+
+    /// Post to the hub the MuSig2 nonces for the operation with index `operation_idx`, provided
+    /// as a PSBT obtained with [`Wallet::musig_generate_nonces`]. Without a hub, the nonces are
+    /// added to the operation package instead.
+    ///
+    /// Once all cosigners have posted their nonces, the PSBT returned by
+    /// [`sync_with_hub`](MultisigWallet::sync_with_hub) for the operation includes them all and
+    /// signing it adds the MuSig2 partial signatures, allowing the transaction to be spent via
+    /// the taproot key path. Otherwise the transaction can only be spent via the script path,
+    /// which for an n-of-n wallet still needs all cosigners to sign.
+    ///
+    /// Posting to a hub needs one supporting operation files (rgb-multisig-hub 0.1.1 doesn't) and
+    /// the `hub_operation_files` feature, otherwise a [`Error::MultisigCoordinationMode`] error is
+    /// returned.
+    pub fn post_musig_nonces(
+        &self,
+        online: Online,
+        operation_idx: i32,
+        psbt: String,
+    ) -> Result<(), Error> {
+        info!(self.logger(), "Posting MuSig2 nonces...");
+        self.check_online(online)?;
+        self.check_is_cosigner()?;
+        if !self.keys.musig {
+            return Err(Error::Musig {
+                details: s!("the wallet doesn't use MuSig2"),
+            });
+        }
+        let psbt = Psbt::from_str(&psbt)?;
+        let op = self.get_respondable_operation(operation_idx)?;
+        self.check_operation_psbt(&op, &psbt)?;
+        match self.hub_client() {
+            #[cfg(feature = "hub_operation_files")]
+            Ok(hub_client) => {
+                hub_client.post_operation_file(
+                    operation_idx,
+                    FileType::MusigNonces,
+                    psbt.serialize(),
+                )?;
+            }
+            #[cfg(not(feature = "hub_operation_files"))]
+            Ok(_) => return Err(Self::hub_operation_files_error(FileType::MusigNonces)),
+            Err(_) => {
+                let mut package = self
+                    .get_local_operation_package(operation_idx)?
+                    .ok_or(Error::MultisigOperationNotFound { operation_idx })?;
+                self.add_package_file(&mut package, FileType::MusigNonces, &psbt.serialize())?;
+                self.save_operation_package(&package)?;
+            }
+        }
+        info!(self.logger(), "Post MuSig2 nonces completed");
+        Ok(())
+    }
+
     fn post_operation(
         &self,
+        txn: &DbTxn,
         operation_type: OperationType,
         post_data: PostData,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, Error> {
         // collect operation files
         let mut files = vec![];
+        if let Some(expiry) = expiry {
+            if self.is_operation_expired(&expiry) {
+                return Err(Error::InvalidExpiration);
+            }
+            let expiry_bytes =
+                serde_json::to_vec(&OperationExpiryData::from(expiry)).expect("serializable");
+            files.push((FileType::OperationExpiry, FileSource::Bytes(expiry_bytes)));
+        }
+        if let Some(recovery_policy_idx) = self.recovery_policy_idx {
+            let recovery_policy_bytes =
+                serde_json::to_vec(&recovery_policy_idx).expect("serializable");
+            files.push((
+                FileType::RecoveryPolicy,
+                FileSource::Bytes(recovery_policy_bytes),
+            ));
+        }
+        #[cfg(not(feature = "hub_operation_files"))]
+        if let Some((file_type, _)) = files.first()
+            && self.hub_client().is_ok()
+        {
+            return Err(Self::hub_operation_files_error(*file_type));
+        }
         let psbt = match post_data {
             PostData::Psbt(psbt) => psbt,
             PostData::BeginOperationData(begin_operation_data) => {
@@ -2132,17 +3781,84 @@ impl MultisigWallet {
                 begin_operation_data.psbt
             }
         };
+        let mut psbt = psbt;
+        if self.keys.musig {
+            let bitcoin_network = self.bitcoin_network();
+            musig::add_participants(
+                &mut psbt,
+                &[
+                    self.keys.musig_participants(bitcoin_network, true)?,
+                    self.keys.musig_participants(bitcoin_network, false)?,
+                ],
+            )?;
+        }
         files.push((FileType::OperationPsbt, FileSource::Bytes(psbt.serialize())));
 
         // post operation and its files
-        let response = self.hub_client().post_operation(files, operation_type)?;
+        let operation_idx = self.publish_operation(txn, files, operation_type)?;
+
+        // reserve the operation inputs until it's processed
+        self.track_operation(txn, operation_idx, &psbt)?;
 
         Ok(InitOperationResult {
             psbt: psbt.to_string(),
-            operation_idx: response.operation_idx,
+            operation_idx,
         })
     }
 
+    fn publish_operation(
+        &self,
+        txn: &DbTxn,
+        files: Vec<(FileType, FileSource)>,
+        operation_type: OperationType,
+    ) -> Result<i32, Error> {
+        if let Ok(hub_client) = self.hub_client() {
+            return Ok(hub_client
+                .post_operation(files, operation_type)?
+                .operation_idx);
+        }
+
+        // without a hub, operations are numbered locally and saved as packages to be exported
+        let operation_idx = self.get_next_new_operation_idx(txn)?;
+        if self.get_local_operation_package(operation_idx)?.is_some() {
+            return Err(Error::MultisigOperationInProgress);
+        }
+        let initiator_xpub = self.cosigner_xpub()?;
+        let mut package_files = vec![];
+        for (file_type, file_source) in files {
+            let bytes = match file_source {
+                FileSource::Bytes(bytes) => bytes,
+                FileSource::Path(path) => fs::read(path)?,
+            };
+            package_files.push(PackageFile::new(file_type, &initiator_xpub, &bytes));
+        }
+        let bdk_wallet = self.bdk_wallet();
+        let internal_index = bdk_wallet.derivation_index(KeychainKind::Internal);
+        let external_index = bdk_wallet.derivation_index(KeychainKind::External);
+        drop(bdk_wallet);
+        let mut package = OperationPackage {
+            version: OPERATION_PACKAGE_VERSION,
+            wallet_id: self.wallet_id(),
+            operation_idx,
+            operation_type,
+            initiator_xpub,
+            initiator_signature: String::new(),
+            created_at: now().unix_timestamp(),
+            internal_index,
+            external_index,
+            acked_by: HashSet::new(),
+            nacked_by: HashMap::new(),
+            files: package_files,
+        };
+        let operation_hash = package.operation_hash();
+        package.initiator_signature = self.sign_as_cosigner(operation_hash)?;
+        for file in &mut package.files {
+            file.signature = self.sign_as_cosigner(file.signed_hash(&operation_hash))?;
+        }
+        self.save_operation_package(&package)?;
+        Ok(operation_idx)
+    }
+
     /// Prepare the PSBT to create new UTXOs to hold RGB allocations with the provided `fee_rate`
     /// (in sat/vB) and post the operation to the hub.
     ///
@@ -2159,6 +3875,10 @@ impl MultisigWallet {
     /// UTXOs, the number is decremented by one until it is possible to complete the operation. If
     /// the number reaches zero, an error is returned.
     ///
+    /// An optional `expiry` can be provided: once it's reached, the operation, if still pending,
+    /// gets discarded by cosigners when syncing. When using a hub, this requires the
+    /// `hub_operation_files` feature.
+    ///
     /// Returns a PSBT ready to be signed and the operation index on the hub.
     pub fn create_utxos_init(
         &mut self,
@@ -2168,6 +3888,7 @@ impl MultisigWallet {
         size: Option<u32>,
         fee_rate: u64,
         skip_sync: bool,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, Error> {
         info!(self.logger(), "Initiate creating UTXOs...");
         self.check_online(online)?;
@@ -2175,7 +3896,12 @@ impl MultisigWallet {
         let txn = self.database().begin_transaction()?;
         let psbt =
             self.create_utxos_begin_impl(&txn, up_to, num, size, fee_rate, skip_sync, true)?;
-        let res = self.post_operation(OperationType::CreateUtxos, PostData::Psbt(psbt))?;
+        let res = self.post_operation(
+            &txn,
+            OperationType::CreateUtxos,
+            PostData::Psbt(psbt),
+            expiry,
+        )?;
         txn.commit()?;
         info!(self.logger(), "Initiate creating UTXOs completed");
         Ok(res)
@@ -2185,6 +3911,10 @@ impl MultisigWallet {
     /// wallet to the specified Bitcoin `address` with the specified `fee_rate` (in sat/vB) and post
     /// the operation to the hub.
     ///
+    /// An optional `expiry` can be provided: once it's reached, the operation, if still pending,
+    /// gets discarded by cosigners when syncing. When using a hub, this requires the
+    /// `hub_operation_files` feature.
+    ///
     /// Returns a PSBT ready to be signed and the operation index on the hub.
     pub fn send_btc_init(
         &mut self,
@@ -2193,13 +3923,15 @@ impl MultisigWallet {
         amount: u64,
         fee_rate: u64,
         skip_sync: bool,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, Error> {
         info!(self.logger(), "Initiate sending BTC...");
         self.check_online(online)?;
         self.check_is_cosigner()?;
         let txn = self.database().begin_transaction()?;
         let psbt = self.send_btc_begin_impl(&txn, address, amount, fee_rate, skip_sync, true)?;
-        let res = self.post_operation(OperationType::SendBtc, PostData::Psbt(psbt))?;
+        let res =
+            self.post_operation(&txn, OperationType::SendBtc, PostData::Psbt(psbt), expiry)?;
         txn.commit()?;
         info!(self.logger(), "Initiate sending BTC completed");
         Ok(res)
@@ -2231,6 +3963,10 @@ impl MultisigWallet {
     /// received. In case of a batch transfer, set it to the minimum (earliest) expiration across
     /// the recipients' invoices.
     ///
+    /// An optional `expiry` can be provided: once it's reached, the operation, if still pending,
+    /// gets discarded by cosigners when syncing. When using a hub, this requires the
+    /// `hub_operation_files` feature.
+    ///
     /// Returns a PSBT ready to be signed and the operation index on the hub.
     pub fn send_init(
         &mut self,
@@ -2240,6 +3976,7 @@ impl MultisigWallet {
         fee_rate: u64,
         min_confirmations: u8,
         expiration_timestamp: u64,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, Error> {
         info!(self.logger(), "Initiate sending...");
         self.check_online(online)?;
@@ -2252,11 +3989,15 @@ impl MultisigWallet {
             fee_rate,
             min_confirmations,
             Some(expiration_timestamp as i64),
+            CoinSelectionOptions::default(),
+            None,
             true,
         )?;
         let res = self.post_operation(
+            &txn,
             OperationType::SendRgb,
             PostData::BeginOperationData(Box::new(data)),
+            expiry,
         )?;
         txn.commit()?;
         info!(self.logger(), "Initiate sending completed");
@@ -2274,6 +4015,10 @@ impl MultisigWallet {
     /// the transaction anchoring the transfer for it to be considered final and move (while
     /// refreshing) to the [`TransferStatus::Settled`] status.
     ///
+    /// An optional `expiry` can be provided: once it's reached, the operation, if still pending,
+    /// gets discarded by cosigners when syncing. When using a hub, this requires the
+    /// `hub_operation_files` feature.
+    ///
     /// Returns a PSBT ready to be signed and the operation index on the hub.
     pub fn inflate_init(
         &mut self,
@@ -2282,6 +4027,7 @@ impl MultisigWallet {
         inflation_amounts: Vec<u64>,
         fee_rate: u64,
         min_confirmations: u8,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, Error> {
         info!(self.logger(), "Initiate inflating...");
         self.check_online(online)?;
@@ -2293,11 +4039,14 @@ impl MultisigWallet {
             inflation_amounts,
             fee_rate,
             min_confirmations,
+            CoinSelectionOptions::default(),
             true,
         )?;
         let res = self.post_operation(
+            &txn,
             OperationType::Inflation,
             PostData::BeginOperationData(Box::new(data)),
+            expiry,
         )?;
         txn.commit()?;
         info!(self.logger(), "Initiate inflating completed");
@@ -2311,6 +4060,10 @@ impl MultisigWallet {
     /// the transaction anchoring the transfer for it to be considered final and move (while
     /// refreshing) to the [`TransferStatus::Settled`] status.
     ///
+    /// An optional `expiry` can be provided: once it's reached, the operation, if still pending,
+    /// gets discarded by cosigners when syncing. When using a hub, this requires the
+    /// `hub_operation_files` feature.
+    ///
     /// Returns a PSBT ready to be signed and the operation index on the hub.
     pub fn burn_init(
         &mut self,
@@ -2319,16 +4072,26 @@ impl MultisigWallet {
         amount: u64,
         fee_rate: u64,
         min_confirmations: u8,
+        expiry: Option<OperationExpiry>,
     ) -> Result<InitOperationResult, Error> {
         info!(self.logger(), "Initiate burning amount: {}...", amount);
         self.check_online(online)?;
         self.check_is_cosigner()?;
         let txn = self.database().begin_transaction()?;
-        let data =
-            self.burn_begin_impl(&txn, asset_id, amount, fee_rate, min_confirmations, true)?;
+        let data = self.burn_begin_impl(
+            &txn,
+            asset_id,
+            amount,
+            fee_rate,
+            min_confirmations,
+            CoinSelectionOptions::default(),
+            true,
+        )?;
         let res = self.post_operation(
+            &txn,
             OperationType::Burn,
             PostData::BeginOperationData(Box::new(data)),
+            expiry,
         )?;
         txn.commit()?;
         info!(self.logger(), "Initiate burning completed");
@@ -0,0 +1,920 @@
+//! MuSig2 module.
+//!
+//! This module wraps the BIP-327 (MuSig2) implementation of the `musig2` crate to spend multisig
+//! UTXOs via the taproot key path and implements the PSBT plumbing (using the BIP-373 input field
+//! types) to exchange the public nonces and partial signatures between cosigners.
+
+use super::*;
+
+// BIP-328 chain code for the xpub of an aggregate key
+const AGGREGATE_CHAIN_CODE: [u8; 32] = [
+    0x86, 0x80, 0x87, 0xca, 0x02, 0xa6, 0xf9, 0x74, 0xc4, 0x59, 0x89, 0x24, 0xc3, 0x6b, 0x57, 0x76,
+    0x2d, 0x32, 0xcb, 0x45, 0x71, 0x71, 0x67, 0xe3, 0x00, 0x62, 0x2c, 0x71, 0x67, 0xe3, 0x89, 0x65,
+];
+
+// BIP-373 PSBT input field types
+const PSBT_IN_MUSIG2_PARTICIPANT_PUBKEYS: u8 = 0x1a;
+const PSBT_IN_MUSIG2_PUB_NONCE: u8 = 0x1b;
+pub(crate) const PSBT_IN_MUSIG2_PARTIAL_SIG: u8 = 0x1c;
+
+const PUBKEY_LEN: usize = 33;
+pub(crate) const PUB_NONCE_LEN: usize = 2 * PUBKEY_LEN;
+const SEC_NONCE_LEN: usize = 2 * 32 + PUBKEY_LEN;
+
+pub(crate) const MUSIG_NONCES_FILE: &str = "musig_nonces";
+const NONCE_STORE_NONCE_LENGTH: usize = 24;
+
+pub(crate) fn tagged_hash(tag: &str, data: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = hash_bytes(tag.as_bytes());
+    let mut engine = sha256::HashEngine::default();
+    engine.input(&tag_hash);
+    engine.input(&tag_hash);
+    for d in data {
+        engine.input(d);
+    }
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+// the musig2 crate uses its own curve types, conversions go through the serialized forms
+
+fn to_point(pubkey: &PublicKey) -> Point {
+    Point::from_slice(&pubkey.serialize()).expect("valid public key")
+}
+
+fn from_point(point: Point) -> PublicKey {
+    PublicKey::from_slice(&point.serialize()).expect("valid point")
+}
+
+fn to_scalar(secret_key: &SecretKey) -> MusigScalar {
+    MusigScalar::from_slice(&secret_key.secret_bytes()).expect("valid secret key")
+}
+
+fn musig_err(details: impl Into<String>) -> Error {
+    Error::Musig {
+        details: details.into(),
+    }
+}
+
+/// BIP-327 key aggregation context.
+#[derive(Clone, Debug)]
+pub(crate) struct KeyAggContext {
+    inner: MusigKeyAggContext,
+}
+
+impl KeyAggContext {
+    /// Aggregate the given keys (in the given order, callers sort them beforehand).
+    pub(crate) fn new(pubkeys: &[PublicKey]) -> Result<Self, Error> {
+        if pubkeys.is_empty() {
+            return Err(musig_err("no keys to aggregate"));
+        }
+        let inner = MusigKeyAggContext::new(pubkeys.iter().map(to_point))
+            .map_err(|_| musig_err("aggregate key is infinite"))?;
+        Ok(Self { inner })
+    }
+
+    /// The (possibly tweaked) aggregate key.
+    pub(crate) fn aggregate_key(&self) -> PublicKey {
+        from_point(self.inner.aggregated_pubkey())
+    }
+
+    /// The xpub for the untweaked aggregate key, as defined by BIP-328.
+    pub(crate) fn aggregate_xpub(&self, network: NetworkKind) -> Xpub {
+        Xpub {
+            network,
+            depth: 0,
+            parent_fingerprint: Fingerprint::default(),
+            child_number: ChildNumber::from_normal_idx(0).expect("valid"),
+            public_key: from_point(self.inner.aggregated_pubkey_untweaked()),
+            chain_code: ChainCode::from(AGGREGATE_CHAIN_CODE),
+        }
+    }
+
+    /// Apply a plain (BIP-32) or x-only (taproot) tweak to the aggregate key.
+    pub(crate) fn apply_tweak(&mut self, tweak: Scalar, xonly: bool) -> Result<(), Error> {
+        let tweak = MaybeScalar::from_slice(&tweak.to_be_bytes()).expect("valid scalar");
+        self.inner = self
+            .inner
+            .clone()
+            .with_tweak(tweak, xonly)
+            .map_err(|_| musig_err("tweaked key is infinite"))?;
+        Ok(())
+    }
+}
+
+/// Generate a nonce pair for signing `msg`, returning the secret nonce and the serialized public
+/// nonce.
+pub(crate) fn nonce_gen(
+    secret_key: &SecretKey,
+    aggregate_key: &PublicKey,
+    msg: &[u8; 32],
+) -> (SecNonce, [u8; PUB_NONCE_LEN]) {
+    let sec_nonce =
+        SecNonceBuilder::from_seckey(rand::rng().random::<[u8; 32]>(), to_scalar(secret_key))
+            .with_aggregated_pubkey(to_point(aggregate_key))
+            .with_message(msg)
+            .build();
+    let pub_nonce = sec_nonce.public_nonce().serialize();
+    (sec_nonce, pub_nonce)
+}
+
+fn parse_pub_nonces(pub_nonces: &[[u8; PUB_NONCE_LEN]]) -> Result<Vec<PubNonce>, Error> {
+    pub_nonces
+        .iter()
+        .map(|pub_nonce| {
+            PubNonce::from_bytes(pub_nonce).map_err(|_| musig_err("invalid public nonce"))
+        })
+        .collect()
+}
+
+/// Produce a partial signature, consuming the secret nonce.
+fn partial_sign(
+    sec_nonce: SecNonce,
+    secret_key: &SecretKey,
+    ctx: &KeyAggContext,
+    agg_nonce: &AggNonce,
+    msg: &[u8; 32],
+) -> Result<[u8; 32], Error> {
+    let partial_sig: PartialSignature =
+        sign_partial(&ctx.inner, to_scalar(secret_key), sec_nonce, agg_nonce, msg).map_err(
+            |e| match e {
+                SigningError::UnknownKey => musig_err("signer is not a participant"),
+                SigningError::SecNoncePubkeyMismatch => {
+                    musig_err("secret nonce for a different key")
+                }
+                SigningError::SelfVerifyFail => musig_err("invalid partial signature"),
+            },
+        )?;
+    Ok(partial_sig.serialize())
+}
+
+#[cfg(online)]
+/// Verify the partial signatures of the given participants, so a cosigner producing an invalid one
+/// is detected before aggregating them.
+fn verify_partial_sigs(
+    ctx: &KeyAggContext,
+    agg_nonce: &AggNonce,
+    msg: &[u8; 32],
+    participants: &[PublicKey],
+    pub_nonces: &[PubNonce],
+    partial_sigs: &[[u8; 32]],
+) -> Result<Vec<PartialSignature>, Error> {
+    participants
+        .iter()
+        .zip(pub_nonces)
+        .zip(partial_sigs)
+        .map(|((participant, pub_nonce), partial_sig)| {
+            let invalid = || musig_err(format!("invalid partial signature from {participant}"));
+            let partial_sig = PartialSignature::from_slice(partial_sig).map_err(|_| invalid())?;
+            verify_partial(
+                &ctx.inner,
+                partial_sig,
+                agg_nonce,
+                to_point(participant),
+                pub_nonce,
+                msg,
+            )
+            .map_err(|_| invalid())?;
+            Ok(partial_sig)
+        })
+        .collect()
+}
+
+#[cfg(online)]
+/// Aggregate the partial signatures into a BIP-340 signature.
+fn partial_sig_agg(
+    ctx: &KeyAggContext,
+    agg_nonce: &AggNonce,
+    msg: &[u8; 32],
+    partial_sigs: &[PartialSignature],
+) -> Result<SchnorrSignature, Error> {
+    let signature: LiftedSignature =
+        aggregate_partial_signatures(&ctx.inner, agg_nonce, partial_sigs.iter().copied(), msg)
+            .map_err(|_| musig_err("invalid signature"))?;
+    SchnorrSignature::from_slice(&signature.serialize()).map_err(|_| musig_err("invalid signature"))
+}
+
+/// Secret nonces waiting for their partial signature, kept in the wallet directory so they survive
+/// restarts. The file is encrypted with a key derived from the participant secret keys and it's
+/// excluded from backups, as restoring a nonce that has already been used could leak the key.
+pub(crate) struct SecNonceStore {
+    path: PathBuf,
+    aead: XChaCha20Poly1305,
+}
+
+impl SecNonceStore {
+    pub(crate) fn new(wallet_dir: &Path, secret_keys: &[SecretKey]) -> Self {
+        let secret_bytes: Vec<[u8; 32]> = secret_keys.iter().map(|k| k.secret_bytes()).collect();
+        let key = tagged_hash(
+            "rgb-lib/musig nonces",
+            &secret_bytes.iter().map(|b| &b[..]).collect::<Vec<_>>(),
+        );
+        let key = Key::try_from(&key[..]).expect("hash length matches XChaCha20 key size");
+        Self {
+            path: wallet_dir.join(MUSIG_NONCES_FILE),
+            aead: XChaCha20Poly1305::new(&key),
+        }
+    }
+
+    fn read(&self) -> Result<Vec<([u8; PUB_NONCE_LEN], SecNonce)>, Error> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        let invalid = || musig_err("cannot read the stored secret nonces");
+        let data = fs::read(&self.path)?;
+        if data.len() < NONCE_STORE_NONCE_LENGTH {
+            return Err(invalid());
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_STORE_NONCE_LENGTH);
+        let nonce: [u8; NONCE_STORE_NONCE_LENGTH] =
+            nonce.try_into().expect("split at the nonce length");
+        let cleartext = self
+            .aead
+            .decrypt(&XNonce::from(nonce), ciphertext)
+            .map_err(|_| invalid())?;
+        let entry_len = PUB_NONCE_LEN + SEC_NONCE_LEN;
+        if !cleartext.len().is_multiple_of(entry_len) {
+            return Err(invalid());
+        }
+        cleartext
+            .chunks(entry_len)
+            .map(|entry| {
+                let (pub_nonce, sec_nonce) = entry.split_at(PUB_NONCE_LEN);
+                Ok((
+                    pub_nonce
+                        .try_into()
+                        .expect("split at the public nonce length"),
+                    SecNonce::from_bytes(sec_nonce).map_err(|_| invalid())?,
+                ))
+            })
+            .collect()
+    }
+
+    fn write(&self, sec_nonces: &[([u8; PUB_NONCE_LEN], SecNonce)]) -> Result<(), Error> {
+        let mut cleartext = Vec::with_capacity(sec_nonces.len() * (PUB_NONCE_LEN + SEC_NONCE_LEN));
+        for (pub_nonce, sec_nonce) in sec_nonces {
+            cleartext.extend(pub_nonce);
+            cleartext.extend(sec_nonce.serialize());
+        }
+        let mut nonce = [0u8; NONCE_STORE_NONCE_LENGTH];
+        rand::rng().fill(&mut nonce[..]);
+        let ciphertext = self
+            .aead
+            .encrypt(&XNonce::from(nonce), cleartext.as_slice())
+            .expect("nonces size is within XChaCha20Poly1305 limits");
+
+        write_file_atomically(&self.path, &[&nonce, &ciphertext])
+    }
+
+    /// Store new secret nonces.
+    pub(crate) fn add(
+        &self,
+        sec_nonces: Vec<([u8; PUB_NONCE_LEN], SecNonce)>,
+    ) -> Result<(), Error> {
+        let mut stored = self.read()?;
+        stored.extend(sec_nonces);
+        self.write(&stored)
+    }
+
+    /// Remove the secret nonce for the given public nonce and return it. The file is updated
+    /// before returning, so the nonce can't be used twice.
+    pub(crate) fn take(&self, pub_nonce: &[u8; PUB_NONCE_LEN]) -> Result<Option<SecNonce>, Error> {
+        let mut stored = self.read()?;
+        let Some(pos) = stored.iter().position(|(p, _)| p == pub_nonce) else {
+            return Ok(None);
+        };
+        let (_, sec_nonce) = stored.swap_remove(pos);
+        self.write(&stored)?;
+        Ok(Some(sec_nonce))
+    }
+}
+
+// PSBT plumbing
+
+struct InputSession {
+    aggregate_key: PublicKey,
+    participants: Vec<PublicKey>,
+    ctx: KeyAggContext,
+    msg: [u8; 32],
+}
+
+impl InputSession {
+    fn field_key(&self, type_value: u8, participant: &PublicKey) -> PsbtRawKey {
+        let mut key = participant.serialize().to_vec();
+        key.extend(self.aggregate_key.serialize());
+        PsbtRawKey { type_value, key }
+    }
+
+    fn pub_nonces(&self, input: &PsbtInput) -> Option<Vec<[u8; PUB_NONCE_LEN]>> {
+        self.participants
+            .iter()
+            .map(|p| {
+                input
+                    .unknown
+                    .get(&self.field_key(PSBT_IN_MUSIG2_PUB_NONCE, p))
+                    .and_then(|v| v.as_slice().try_into().ok())
+            })
+            .collect()
+    }
+
+    #[cfg(online)]
+    fn partial_sigs(&self, input: &PsbtInput) -> Option<Vec<[u8; 32]>> {
+        self.participants
+            .iter()
+            .map(|p| {
+                input
+                    .unknown
+                    .get(&self.field_key(PSBT_IN_MUSIG2_PARTIAL_SIG, p))
+                    .and_then(|v| v.as_slice().try_into().ok())
+            })
+            .collect()
+    }
+}
+
+fn participants_field(input: &PsbtInput) -> Option<(&PsbtRawKey, &Vec<u8>)> {
+    input
+        .unknown
+        .iter()
+        .find(|(k, _)| k.type_value == PSBT_IN_MUSIG2_PARTICIPANT_PUBKEYS)
+}
+
+/// Whether the PSBT has inputs to be spent via the MuSig2 key path.
+pub(crate) fn has_musig_inputs(psbt: &Psbt) -> bool {
+    psbt.inputs.iter().any(|i| participants_field(i).is_some())
+}
+
+fn input_session(psbt: &Psbt, input_idx: usize) -> Result<Option<InputSession>, Error> {
+    let input = &psbt.inputs[input_idx];
+    let Some((key, value)) = participants_field(input) else {
+        return Ok(None);
+    };
+    let invalid = |details: &str| musig_err(format!("input {input_idx}: {details}"));
+    let aggregate_key =
+        PublicKey::from_slice(&key.key).map_err(|_| invalid("invalid aggregate key"))?;
+    if value.is_empty() || value.len() % PUBKEY_LEN != 0 {
+        return Err(invalid("invalid participant keys"));
+    }
+    let participants = value
+        .chunks(PUBKEY_LEN)
+        .map(PublicKey::from_slice)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid("invalid participant keys"))?;
+    let mut ctx = KeyAggContext::new(&participants)?;
+    if ctx.aggregate_key() != aggregate_key {
+        return Err(invalid("participant keys don't match the aggregate key"));
+    }
+
+    // BIP-32 derivation from the aggregate xpub to the taproot internal key
+    let internal_key = input
+        .tap_internal_key
+        .ok_or_else(|| invalid("missing taproot internal key"))?;
+    let (_, (fingerprint, path)) = input
+        .tap_key_origins
+        .get(&internal_key)
+        .ok_or_else(|| invalid("missing internal key origin"))?;
+    let secp = Secp256k1::verification_only();
+    let mut xpub = ctx.aggregate_xpub(NetworkKind::Test);
+    if &xpub.fingerprint() != fingerprint {
+        return Err(invalid("internal key not derived from the aggregate key"));
+    }
+    for child in path {
+        let (tweak, _) = xpub
+            .ckd_pub_tweak(*child)
+            .map_err(|_| invalid("invalid derivation"))?;
+        ctx.apply_tweak(Scalar::from(tweak), false)?;
+        xpub = xpub
+            .ckd_pub(&secp, *child)
+            .map_err(|_| invalid("invalid derivation"))?;
+    }
+    if ctx.aggregate_key().x_only_public_key().0 != internal_key {
+        return Err(invalid("internal key not derived from the aggregate key"));
+    }
+
+    // taproot tweak
+    let tap_tweak = TapTweakHash::from_key_and_tweak(internal_key, input.tap_merkle_root);
+    ctx.apply_tweak(tap_tweak.to_scalar(), true)?;
+
+    // key spend sighash
+    let prevouts = psbt
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| {
+            input
+                .witness_utxo
+                .clone()
+                .ok_or_else(|| musig_err(format!("input {i}: missing witness UTXO")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let msg = SighashCache::new(&psbt.unsigned_tx)
+        .taproot_key_spend_signature_hash(
+            input_idx,
+            &Prevouts::All(&prevouts),
+            TapSighashType::Default,
+        )
+        .map_err(|e| invalid(&e.to_string()))?
+        .to_byte_array();
+
+    Ok(Some(InputSession {
+        aggregate_key,
+        participants,
+        ctx,
+        msg,
+    }))
+}
+
+#[cfg(online)]
+/// Add the participant keys to the inputs spending from the given aggregate keys.
+pub(crate) fn add_participants(
+    psbt: &mut Psbt,
+    aggregates: &[Vec<PublicKey>],
+) -> Result<(), Error> {
+    for participants in aggregates {
+        let ctx = KeyAggContext::new(participants)?;
+        let fingerprint = ctx.aggregate_xpub(NetworkKind::Test).fingerprint();
+        for input in psbt.inputs.iter_mut() {
+            let Some(internal_key) = input.tap_internal_key else {
+                continue;
+            };
+            if !matches!(input.tap_key_origins.get(&internal_key), Some((_, (fp, _))) if *fp == fingerprint)
+            {
+                continue;
+            }
+            input.unknown.insert(
+                PsbtRawKey {
+                    type_value: PSBT_IN_MUSIG2_PARTICIPANT_PUBKEYS,
+                    key: ctx.aggregate_key().serialize().to_vec(),
+                },
+                participants.iter().flat_map(|p| p.serialize()).collect(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Add public nonces for the inputs where the given keys participate, returning the secret nonces
+/// (indexed by their public nonce) that will be needed to produce the partial signatures.
+pub(crate) fn add_pub_nonces(
+    psbt: &mut Psbt,
+    secret_keys: &[SecretKey],
+) -> Result<Vec<([u8; PUB_NONCE_LEN], SecNonce)>, Error> {
+    let secp = Secp256k1::new();
+    let mut sec_nonces = vec![];
+    for input_idx in 0..psbt.inputs.len() {
+        let Some(session) = input_session(psbt, input_idx)? else {
+            continue;
+        };
+        for secret_key in secret_keys {
+            let pubkey = secret_key.public_key(&secp);
+            if !session.participants.contains(&pubkey) {
+                continue;
+            }
+            let field_key = session.field_key(PSBT_IN_MUSIG2_PUB_NONCE, &pubkey);
+            if psbt.inputs[input_idx].unknown.contains_key(&field_key) {
+                continue;
+            }
+            let (sec_nonce, pub_nonce) =
+                nonce_gen(secret_key, &session.ctx.aggregate_key(), &session.msg);
+            psbt.inputs[input_idx]
+                .unknown
+                .insert(field_key, pub_nonce.to_vec());
+            sec_nonces.push((pub_nonce, sec_nonce));
+        }
+    }
+    Ok(sec_nonces)
+}
+
+/// Add partial signatures for the inputs where the given keys participate and all public nonces
+/// are available, using the secret nonces provided by `take_sec_nonce`.
+pub(crate) fn add_partial_sigs(
+    psbt: &mut Psbt,
+    secret_keys: &[SecretKey],
+    mut take_sec_nonce: impl FnMut(&[u8; PUB_NONCE_LEN]) -> Result<Option<SecNonce>, Error>,
+) -> Result<(), Error> {
+    let secp = Secp256k1::new();
+    for input_idx in 0..psbt.inputs.len() {
+        let Some(session) = input_session(psbt, input_idx)? else {
+            continue;
+        };
+        let input = &psbt.inputs[input_idx];
+        let Some(pub_nonces) = session.pub_nonces(input) else {
+            continue;
+        };
+        let agg_nonce = AggNonce::sum(parse_pub_nonces(&pub_nonces)?);
+        for secret_key in secret_keys {
+            let pubkey = secret_key.public_key(&secp);
+            let Some(pos) = session.participants.iter().position(|p| p == &pubkey) else {
+                continue;
+            };
+            let field_key = session.field_key(PSBT_IN_MUSIG2_PARTIAL_SIG, &pubkey);
+            if psbt.inputs[input_idx].unknown.contains_key(&field_key) {
+                continue;
+            }
+            let Some(sec_nonce) = take_sec_nonce(&pub_nonces[pos])? else {
+                continue;
+            };
+            let partial_sig = partial_sign(
+                sec_nonce,
+                secret_key,
+                &session.ctx,
+                &agg_nonce,
+                &session.msg,
+            )?;
+            psbt.inputs[input_idx]
+                .unknown
+                .insert(field_key, partial_sig.to_vec());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(online)]
+/// Aggregate the partial signatures of the inputs that have all of them into a taproot key spend
+/// signature. Each partial signature is verified first: inputs with missing or invalid partial
+/// signatures are left untouched, so they can still be finalized via the script path if it has
+/// been signed by enough cosigners.
+pub(crate) fn aggregate_partial_sigs(psbt: &mut Psbt) -> Result<(), Error> {
+    for input_idx in 0..psbt.inputs.len() {
+        let Some(session) = input_session(psbt, input_idx)? else {
+            continue;
+        };
+        let input = &psbt.inputs[input_idx];
+        if input.tap_key_sig.is_some() {
+            continue;
+        }
+        let (Some(pub_nonces), Some(partial_sigs)) =
+            (session.pub_nonces(input), session.partial_sigs(input))
+        else {
+            continue;
+        };
+        let Ok(signature) = parse_pub_nonces(&pub_nonces).and_then(|pub_nonces| {
+            let agg_nonce = AggNonce::sum(&pub_nonces);
+            let partial_sigs = verify_partial_sigs(
+                &session.ctx,
+                &agg_nonce,
+                &session.msg,
+                &session.participants,
+                &pub_nonces,
+                &partial_sigs,
+            )?;
+            partial_sig_agg(&session.ctx, &agg_nonce, &session.msg, &partial_sigs)
+        }) else {
+            continue;
+        };
+        psbt.inputs[input_idx].tap_key_sig = Some(TaprootSignature {
+            signature,
+            sighash_type: TapSighashType::Default,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::bitcoin::{
+        Amount as BtcAmount, Transaction, TxIn, absolute::LockTime,
+        transaction::Version as TxVersion,
+    };
+
+    // test vectors from BIP-327
+
+    fn bytes<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    fn pubkey(hex_str: &str) -> Option<PublicKey> {
+        PublicKey::from_slice(&hex::decode(hex_str).unwrap()).ok()
+    }
+
+    fn pubkeys(keys: &[&str], indices: &[usize]) -> Vec<PublicKey> {
+        indices.iter().map(|i| pubkey(keys[*i]).unwrap()).collect()
+    }
+
+    fn tweaked_ctx(keys: &[PublicKey], tweaks: &[(&str, bool)]) -> Result<KeyAggContext, Error> {
+        let mut ctx = KeyAggContext::new(keys)?;
+        for (tweak, xonly) in tweaks {
+            let tweak = Scalar::from_be_bytes(bytes(tweak)).map_err(|_| musig_err("bad tweak"))?;
+            ctx.apply_tweak(tweak, *xonly)?;
+        }
+        Ok(ctx)
+    }
+
+    fn secret_keys() -> Vec<SecretKey> {
+        (1..=3u8)
+            .map(|i| SecretKey::from_slice(&[i; 32]).unwrap())
+            .collect()
+    }
+
+    fn sorted_pubkeys(secret_keys: &[SecretKey]) -> Vec<PublicKey> {
+        let secp = Secp256k1::new();
+        let mut pubkeys: Vec<PublicKey> =
+            secret_keys.iter().map(|sk| sk.public_key(&secp)).collect();
+        pubkeys.sort_by_key(|pk| pk.serialize());
+        pubkeys
+    }
+
+    #[test]
+    fn key_agg_vectors() {
+        let keys = [
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "03dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+            "023590a94e768f8e1815c2f24b4d80a8e3149316c3518ce7b7ad338368d038ca66",
+            "020000000000000000000000000000000000000000000000000000000000000005",
+            "02fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc30",
+            "04f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "03935f972da013f80ae011890fa89b67a27b7be6ccb24d3274d18b2d4067f261a9",
+        ];
+        let tweaks = [
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+            "252e4bd67410a76cdf933d30eaa1608214037f1b105a013eccd3c5c184a6110b",
+        ];
+        for (indices, expected) in [
+            (
+                &[0, 1, 2][..],
+                "90539eede565f5d054f32cc0c220126889ed1e5d193baf15aef344fe59d4610c",
+            ),
+            (
+                &[2, 1, 0],
+                "6204de8b083426dc6eaf9502d27024d53fc826bf7d2012148a0575435df54b2b",
+            ),
+            (
+                &[0, 0, 0],
+                "b436e3bad62b8cd409969a224731c193d051162d8c5ae8b109306127da3aa935",
+            ),
+            (
+                &[0, 0, 1, 1],
+                "69bc22bfa5d106306e48a20679de1d7389386124d07571d0d872686028c26a3e",
+            ),
+        ] {
+            let ctx = KeyAggContext::new(&pubkeys(&keys, indices)).unwrap();
+            assert_eq!(
+                ctx.aggregate_key().x_only_public_key().0.serialize(),
+                bytes(expected)
+            );
+        }
+
+        // invalid public keys
+        for key in &keys[3..6] {
+            assert!(pubkey(key).is_none());
+        }
+        // no keys
+        let result = KeyAggContext::new(&[]);
+        assert_matches!(result, Err(Error::Musig { details: d }) if d == "no keys to aggregate");
+        // tweak out of range
+        let result = tweaked_ctx(&pubkeys(&keys, &[0, 1]), &[(tweaks[0], true)]);
+        assert_matches!(result, Err(Error::Musig { details: d }) if d == "bad tweak");
+        // tweak resulting in the point at infinity
+        let result = tweaked_ctx(&pubkeys(&keys, &[6]), &[(tweaks[1], false)]);
+        assert_matches!(result, Err(Error::Musig { details: d }) if d == "tweaked key is infinite");
+    }
+
+    #[test]
+    fn tweak_vectors() {
+        let secret_key = SecretKey::from_slice(&bytes::<32>(
+            "7fb9e0e687ada1eebf7ecfe2f21e73ebdb51a7d450948dfe8d76d7f2d1007671",
+        ))
+        .unwrap();
+        let sec_nonce_hex = "508b81a611f100a6b2b6b29656590898af488bcf2e1f55cf22e5cfb84421fe61fa27fd49b1d50085b481285e1ca205d55c82cc1b31ff5cd54a489829355901f703935f972da013f80ae011890fa89b67a27b7be6ccb24d3274d18b2d4067f261a9";
+        let agg_nonce = AggNonce::from_hex("028465fcf0bbdbcf443aabcce533d42b4b5a10966ac09a49655e8c42daab8fcd61037496a3cc86926d452cafcfd55d25972ca1675d549310de296bff42f72eeea8c9").unwrap();
+        let msg = bytes("f95466d086770e689964664219266fe5ed215c92ae20bab5c9d79addddf3c0cf");
+        let keys = [
+            "03935f972da013f80ae011890fa89b67a27b7be6ccb24d3274d18b2d4067f261a9",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "02dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+        ];
+        let keys = pubkeys(&keys, &[1, 2, 0]);
+        let tweaks = [
+            "e8f791ff9225a2af0102afff4a9a723d9612a682a25ebe79802b263cdfcd83bb",
+            "ae2ea797cc0fe72ac5b97b97f3c6957d7e4199a167a58eb08bcaffda70ac0455",
+            "f52ecbc565b3d8bea2dfd5b75a4f457e54369809322e4120831626f290fa87e0",
+            "1969ad73cc177fa0b4fced6df1f7bf9907e665fde9ba196a74fed0a3cf5aef9d",
+        ];
+        for (applied, expected) in [
+            (
+                &[(0, true)][..],
+                "e28a5c66e61e178c2ba19db77b6cf9f7e2f0f56c17918cd13135e60cc848fe91",
+            ),
+            (
+                &[(0, false)],
+                "38b0767798252f21bf5702c48028b095428320f73a4b14db1e25de58543d2d2d",
+            ),
+            (
+                &[(0, false), (1, true)],
+                "408a0a21c4a0f5dacaf9646ad6eb6fecd7f7a11f03ed1f48dfff2185bc2c2408",
+            ),
+            (
+                &[(0, false), (1, false), (2, true), (3, true)],
+                "45abd206e61e3df2ec9e264a6fec8292141a633c28586388235541f9ade75435",
+            ),
+            (
+                &[(0, true), (1, false), (2, true), (3, false)],
+                "b255fdcac27b40c7ce7848e2d3b7bf5ea0ed756da81565ac804ccca3e1d5d239",
+            ),
+        ] {
+            let applied: Vec<(&str, bool)> =
+                applied.iter().map(|(i, x)| (tweaks[*i], *x)).collect();
+            let ctx = tweaked_ctx(&keys, &applied).unwrap();
+            let sec_nonce = SecNonce::from_hex(sec_nonce_hex).unwrap();
+            let partial_sig = partial_sign(sec_nonce, &secret_key, &ctx, &agg_nonce, &msg).unwrap();
+            assert_eq!(partial_sig, bytes(expected));
+        }
+
+        // the signer key is not among the participants
+        let ctx = KeyAggContext::new(&keys[..2]).unwrap();
+        let sec_nonce = SecNonce::from_hex(sec_nonce_hex).unwrap();
+        let result = partial_sign(sec_nonce, &secret_key, &ctx, &agg_nonce, &msg);
+        assert_matches!(result, Err(Error::Musig { details: d }) if d == "signer is not a participant");
+        // the secret nonce belongs to another participant
+        let other_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let other_pubkey = other_key.public_key(&Secp256k1::new());
+        let ctx = KeyAggContext::new(&[keys.clone(), vec![other_pubkey]].concat()).unwrap();
+        let sec_nonce = SecNonce::from_hex(sec_nonce_hex).unwrap();
+        let result = partial_sign(sec_nonce, &other_key, &ctx, &agg_nonce, &msg);
+        assert_matches!(result, Err(Error::Musig { details: d }) if d == "secret nonce for a different key");
+    }
+
+    #[cfg(online)]
+    #[test]
+    fn sign_and_aggregate() {
+        let secp = Secp256k1::new();
+        let secret_keys = secret_keys();
+        let pubkeys = sorted_pubkeys(&secret_keys);
+
+        let mut ctx = KeyAggContext::new(&pubkeys).unwrap();
+        let xpub = ctx.aggregate_xpub(NetworkKind::Test);
+        let child = ChildNumber::from_normal_idx(7).unwrap();
+        let (tweak, _) = xpub.ckd_pub_tweak(child).unwrap();
+        ctx.apply_tweak(Scalar::from(tweak), false).unwrap();
+        assert_eq!(
+            ctx.aggregate_key(),
+            xpub.ckd_pub(&secp, child).unwrap().public_key
+        );
+        // the aggregate xpub stays the untweaked one
+        assert_eq!(ctx.aggregate_xpub(NetworkKind::Test), xpub);
+        let internal_key = ctx.aggregate_key().x_only_public_key().0;
+        let tap_tweak = TapTweakHash::from_key_and_tweak(internal_key, None);
+        ctx.apply_tweak(tap_tweak.to_scalar(), true).unwrap();
+
+        let msg = [42; 32];
+        let nonces: Vec<(SecNonce, [u8; PUB_NONCE_LEN])> = secret_keys
+            .iter()
+            .map(|sk| nonce_gen(sk, &ctx.aggregate_key(), &msg))
+            .collect();
+        let pub_nonces: Vec<[u8; PUB_NONCE_LEN]> = nonces.iter().map(|(_, pn)| *pn).collect();
+        let pub_nonces = parse_pub_nonces(&pub_nonces).unwrap();
+        let agg_nonce = AggNonce::sum(&pub_nonces);
+        let signers: Vec<PublicKey> = secret_keys.iter().map(|sk| sk.public_key(&secp)).collect();
+        let mut partial_sigs: Vec<[u8; 32]> = nonces
+            .into_iter()
+            .zip(&secret_keys)
+            .map(|((sec_nonce, _), sk)| {
+                partial_sign(sec_nonce, sk, &ctx, &agg_nonce, &msg).unwrap()
+            })
+            .collect();
+        let verified =
+            verify_partial_sigs(&ctx, &agg_nonce, &msg, &signers, &pub_nonces, &partial_sigs)
+                .unwrap();
+        let signature = partial_sig_agg(&ctx, &agg_nonce, &msg, &verified).unwrap();
+
+        let (output_key, _) = internal_key
+            .add_tweak(&secp, &tap_tweak.to_scalar())
+            .unwrap();
+        assert_eq!(output_key, ctx.aggregate_key().x_only_public_key().0);
+        secp.verify_schnorr(&signature, &SecpMessage::from_digest(msg), &output_key)
+            .unwrap();
+
+        // a missing partial signature doesn't produce a valid signature
+        let result = partial_sig_agg(&ctx, &agg_nonce, &msg, &verified[..2]);
+        assert_matches!(result, Err(Error::Musig { details: d }) if d == "invalid signature");
+
+        // an invalid partial signature is detected and attributed to its signer
+        partial_sigs[1][31] ^= 1;
+        let result =
+            verify_partial_sigs(&ctx, &agg_nonce, &msg, &signers, &pub_nonces, &partial_sigs);
+        let expected = format!("invalid partial signature from {}", signers[1]);
+        assert_matches!(result, Err(Error::Musig { details: d }) if d == expected);
+        // as is a partial signature that isn't a valid scalar
+        partial_sigs[1] = [0xff; 32];
+        let result =
+            verify_partial_sigs(&ctx, &agg_nonce, &msg, &signers, &pub_nonces, &partial_sigs);
+        assert_matches!(result, Err(Error::Musig { details: d }) if d == expected);
+    }
+
+    #[cfg(online)]
+    #[test]
+    fn psbt_key_spend() {
+        let secp = Secp256k1::new();
+        let secret_keys = secret_keys();
+        let pubkeys = sorted_pubkeys(&secret_keys);
+
+        // a PSBT spending a taproot output with a MuSig2 internal key, derived at /0
+        let ctx = KeyAggContext::new(&pubkeys).unwrap();
+        let xpub = ctx.aggregate_xpub(NetworkKind::Test);
+        let path = DerivationPath::from(vec![ChildNumber::from_normal_idx(0).unwrap()]);
+        let internal_key = xpub
+            .derive_pub(&secp, &path)
+            .unwrap()
+            .public_key
+            .x_only_public_key()
+            .0;
+        let prevout = TxOut {
+            value: BtcAmount::from_sat(10_000),
+            script_pubkey: ScriptBuf::new_p2tr(&secp, internal_key, None),
+        };
+        let tx = Transaction {
+            version: TxVersion::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![TxOut {
+                value: BtcAmount::from_sat(9_000),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(prevout.clone());
+        psbt.inputs[0].tap_internal_key = Some(internal_key);
+        psbt.inputs[0]
+            .tap_key_origins
+            .insert(internal_key, (vec![], (xpub.fingerprint(), path)));
+        add_participants(&mut psbt, &[pubkeys]).unwrap();
+        assert!(has_musig_inputs(&psbt));
+
+        // each cosigner adds its nonce, then its partial signature
+        let mut sec_nonces = vec![];
+        for secret_key in &secret_keys {
+            sec_nonces.extend(add_pub_nonces(&mut psbt, &[*secret_key]).unwrap());
+        }
+        assert_eq!(sec_nonces.len(), 3);
+        let mut take_sec_nonce = |pub_nonce: &[u8; PUB_NONCE_LEN]| {
+            let pos = sec_nonces.iter().position(|(p, _)| p == pub_nonce);
+            Ok(pos.map(|pos| sec_nonces.swap_remove(pos).1))
+        };
+        add_partial_sigs(&mut psbt, &secret_keys, &mut take_sec_nonce).unwrap();
+        let partial_sig_key = |psbt: &Psbt| {
+            psbt.inputs[0]
+                .unknown
+                .keys()
+                .find(|k| k.type_value == PSBT_IN_MUSIG2_PARTIAL_SIG)
+                .cloned()
+                .unwrap()
+        };
+
+        // a tampered partial signature leaves the input unsigned
+        let mut tampered = psbt.clone();
+        let key = partial_sig_key(&tampered);
+        tampered.inputs[0].unknown.get_mut(&key).unwrap()[31] ^= 1;
+        aggregate_partial_sigs(&mut tampered).unwrap();
+        assert!(tampered.inputs[0].tap_key_sig.is_none());
+
+        // the valid partial signatures aggregate into a valid key spend signature
+        aggregate_partial_sigs(&mut psbt).unwrap();
+        let tap_key_sig = psbt.inputs[0].tap_key_sig.unwrap();
+        let msg = SighashCache::new(&psbt.unsigned_tx)
+            .taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&[prevout]),
+                TapSighashType::Default,
+            )
+            .unwrap();
+        let (output_key, _) = internal_key
+            .add_tweak(
+                &secp,
+                &TapTweakHash::from_key_and_tweak(internal_key, None).to_scalar(),
+            )
+            .unwrap();
+        secp.verify_schnorr(
+            &tap_key_sig.signature,
+            &SecpMessage::from_digest(msg.to_byte_array()),
+            &output_key,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn sec_nonce_store() {
+        let secp = Secp256k1::new();
+        let wallet_dir = tempfile::tempdir().unwrap();
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let pubkey = secret_key.public_key(&secp);
+        let (sec_nonce, pub_nonce) = nonce_gen(&secret_key, &pubkey, &[0; 32]);
+        let serialized = sec_nonce.serialize();
+        let store = SecNonceStore::new(wallet_dir.path(), &[secret_key]);
+        store.add(vec![(pub_nonce, sec_nonce)]).unwrap();
+
+        // nonces are stored encrypted
+        let data = fs::read(wallet_dir.path().join(MUSIG_NONCES_FILE)).unwrap();
+        assert!(!data.windows(32).any(|w| w == &serialized[..32]));
+
+        // a new store for the same keys (e.g. after a restart) finds them, only once
+        let store = SecNonceStore::new(wallet_dir.path(), &[secret_key]);
+        let taken = store.take(&pub_nonce).unwrap().unwrap();
+        assert_eq!(taken.serialize(), serialized);
+        assert!(store.take(&pub_nonce).unwrap().is_none());
+
+        // a store with different keys cannot read them
+        let other_key = SecretKey::from_slice(&[2; 32]).unwrap();
+        let other_store = SecNonceStore::new(wallet_dir.path(), &[other_key]);
+        let result = other_store.take(&pub_nonce).map(|n| n.is_some());
+        assert_matches!(result, Err(Error::Musig { details: d }) if d == "cannot read the stored secret nonces");
+    }
+}
@@ -13,10 +13,49 @@ use super::*;
 pub enum DatabaseType {
     /// A SQLite database
     Sqlite,
+    /// A PostgreSQL database
+    ///
+    /// The wallet tables are created inside the provided schema, which is created if missing, so
+    /// several wallets can share the same database.
+    ///
+    /// The URL is persisted in plaintext inside the wallet directory, with the password stripped.
+    /// When the URL has no password, it's read from the `PGPASSWORD` environment variable or from
+    /// the password file (`~/.pgpass` or the one set with the `PGPASSFILE` environment variable)
+    /// every time the database is opened, so this is how the password needs to be supplied to
+    /// [`Wallet::load`].
+    Postgres {
+        /// Connection URL (e.g. `postgres://user@localhost:5432/rgb`)
+        url: String,
+        /// Schema where the wallet tables are stored
+        schema: String,
+    },
+}
+
+impl DatabaseType {
+    // Return the database type with the password removed from the URL, as it can be persisted or
+    // logged. URLs that can't be parsed are rejected when connecting, before being persisted.
+    pub(crate) fn without_credentials(&self) -> Self {
+        match self {
+            Self::Sqlite => Self::Sqlite,
+            Self::Postgres { url, schema } => {
+                let url = match url::Url::parse(url) {
+                    Ok(mut parsed) if parsed.password().is_some() => {
+                        let _ = parsed.set_password(None);
+                        parsed.to_string()
+                    }
+                    _ => url.clone(),
+                };
+                Self::Postgres {
+                    url,
+                    schema: schema.clone(),
+                }
+            }
+        }
+    }
 }
 
 /// Data that defines a [`Wallet`].
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct WalletData {
     /// Directory where the wallet directory is stored
@@ -31,6 +70,33 @@ pub struct WalletData {
     /// List of schemas the wallet should support (when issuing, sending and receiving). Empty list
     /// means the wallet should support all the schemas rgb-lib supports.
     pub supported_schemas: Vec<AssetSchema>,
+    /// Password used to encrypt the wallet databases at rest, `None` to keep them in plaintext.
+    ///
+    /// Encryption requires the `sqlcipher` feature, is only supported with
+    /// [`DatabaseType::Sqlite`] and can only be enabled when the wallet is created, after which the same password is needed to open it. Consignments
+    /// and media files are not encrypted.
+    ///
+    /// The password is never serialized nor printed and the wallet drops it as soon as the
+    /// database key has been derived, so it's always `None` in the data returned by
+    /// [`get_wallet_data`](crate::wallet::RgbWalletOpsOffline::get_wallet_data).
+    #[serde(default, skip_serializing)]
+    pub encryption_password: Option<String>,
+}
+
+impl fmt::Debug for WalletData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalletData")
+            .field("data_dir", &self.data_dir)
+            .field("bitcoin_network", &self.bitcoin_network)
+            .field("database_type", &self.database_type.without_credentials())
+            .field("max_allocations_per_utxo", &self.max_allocations_per_utxo)
+            .field("supported_schemas", &self.supported_schemas)
+            .field(
+                "encryption_password",
+                &self.encryption_password.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
 }
 
 /// Descriptors for an RGB wallet.
@@ -58,19 +124,22 @@ pub struct Online {
     pub id: u64,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub struct OnlineData {
     pub(crate) id: u64,
     pub(crate) indexer_url: String,
     pub(crate) indexer: Indexer,
-    pub(crate) resolver: AnyResolver,
+    pub(crate) resolver: WitnessResolver,
+    pub(crate) network_options: NetworkOptions,
     pub(crate) hub_client: Option<MultisigHubClient>,
     pub(crate) user_role: Option<UserRole>,
+    pub(crate) cosigner_xpub: Option<String>,
+    pub(crate) cosigner_keypair: Option<Keypair>,
     pub(crate) vanilla_sync_lookback: u32,
 }
 
 /// Options for the [`Wallet::go_online`] and [`MultisigWallet::go_online`] methods.
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct OnlineOptions {
@@ -88,6 +157,269 @@ pub struct OnlineOptions {
     /// Number of addresses before the last used (or last revealed if none) address to sync when
     /// doing an automatic FastSync for the vanilla keychain
     pub vanilla_sync_lookback: u32,
+    /// Options to run a background task that keeps the wallet synced and its transfers refreshed,
+    /// `None` to disable it.
+    ///
+    /// See [`AutoRefreshOptions`] for details.
+    #[serde(default)]
+    pub auto_refresh: Option<AutoRefreshOptions>,
+    /// URLs of additional indexers, in order of priority, to fail over to when the previous ones
+    /// are unreachable
+    #[serde(default)]
+    pub fallback_indexer_urls: Vec<String>,
+    /// Number of indexers that need to agree on TX confirmations, block hashes and witness TXs,
+    /// `None` to trust the first reachable indexer.
+    ///
+    /// When set, it needs to be between 2 and the total number of indexers. A transfer is then
+    /// settled, and a consignment validated, only with data confirmed by `indexer_quorum`
+    /// indexers, so a single lying indexer cannot trick the wallet.
+    #[serde(default)]
+    pub indexer_quorum: Option<u8>,
+    /// Network configuration for all the clients used while online
+    #[serde(default)]
+    pub network: NetworkOptions,
+    /// Options to use a compact block filters light client in place of an indexer, `None` to use
+    /// the indexer at `indexer_url`.
+    ///
+    /// See [`CbfOptions`] for details.
+    #[cfg(feature = "cbf")]
+    #[serde(default)]
+    pub cbf: Option<CbfOptions>,
+}
+
+#[cfg(online)]
+impl OnlineOptions {
+    // identifies the source of blockchain data, to detect when it changes
+    pub(crate) fn indexer_id(&self) -> String {
+        #[cfg(feature = "cbf")]
+        let mut indexer_id = match &self.cbf {
+            Some(cbf) => format!("cbf:{}@{}", cbf.peers.join(","), cbf.start_height),
+            None => self.indexer_url.clone(),
+        };
+        #[cfg(not(feature = "cbf"))]
+        let mut indexer_id = self.indexer_url.clone();
+        for url in &self.fallback_indexer_urls {
+            indexer_id.push_str(&format!(",{url}"));
+        }
+        if let Some(quorum) = self.indexer_quorum {
+            indexer_id.push_str(&format!("#{quorum}"));
+        }
+        indexer_id
+    }
+}
+
+/// Options for the compact block filters (BIP157/158) light client.
+///
+/// The client connects to all the reachable peers in `peers`, which need to serve compact block
+/// filters (e.g. a bitcoind node run with `-blockfilterindex=1 -peerblockfilters=1`). Block headers
+/// are downloaded from all of them, following the valid chain with the most work, then the filter
+/// of each block is checked against the wallet scripts, downloading only the matching blocks, so
+/// peers don't learn which scripts belong to the wallet. Filters are checked against their filter
+/// headers and the operation fails if the peers serving them disagree, so configuring more peers,
+/// run by different parties, protects against a peer hiding TXs. TXs are broadcast to the first
+/// peer serving filters.
+///
+/// Block headers, filter headers and filters are stored in the wallet directory, which backups
+/// don't include, while scan results are kept in memory. Peers don't share their mempool, so
+/// incoming TXs are detected only once they've been mined and fee estimation is not available.
+///
+/// Blocks below `start_height` are never scanned, so it should precede both the wallet creation
+/// and the TXs in the history of the assets the wallet will receive, which need to be looked up
+/// when validating consignments.
+#[cfg(feature = "cbf")]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct CbfOptions {
+    /// P2P addresses (`host:port`) of the peers to connect to, tried in order
+    pub peers: Vec<String>,
+    /// Height of the first block to scan
+    pub start_height: u32,
+}
+
+/// Options for the background auto-refresh task.
+///
+/// While online, the task periodically runs a [`SyncStrategy::FastSync`] of both keychains and a
+/// refresh of all pending transfers. It waits for any wallet operation in progress to complete
+/// before starting, so it never interleaves with another operation, and it's stopped when the
+/// wallet goes online again, when [`stop_auto_refresh`](crate::Wallet::stop_auto_refresh) is
+/// called or when the wallet is dropped.
+///
+/// The outcome of each run is reported to the registered [`WalletEventListener`] via the
+/// [`WalletEvent::AutoRefreshCompleted`] and [`WalletEvent::AutoRefreshFailed`] events. After a
+/// failure the next run is delayed, doubling the interval at each consecutive failure up to
+/// `max_backoff_secs`.
+///
+/// Intervals are clamped to at least 1 second.
+#[cfg(online)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct AutoRefreshOptions {
+    /// Seconds between wallet syncs
+    pub sync_interval_secs: u64,
+    /// Seconds between transfer refreshes
+    pub refresh_interval_secs: u64,
+    /// Maximum seconds to wait before retrying after consecutive failures
+    pub max_backoff_secs: u64,
+}
+
+#[cfg(online)]
+/// A network service the wallet connects to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum NetworkService {
+    /// Indexers (Electrum, Esplora or bitcoind)
+    Indexer,
+    /// RGB proxy servers, used to exchange consignments and media
+    Proxy,
+    /// Reject list servers, used to check asset allocations
+    RejectList,
+    /// Multisig hub
+    MultisigHub,
+    /// VSS-style backup servers
+    Vss,
+}
+
+#[cfg(online)]
+/// SOCKS5 proxy to use for a specific [`NetworkService`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct ServiceProxy {
+    /// The service this setting applies to
+    pub service: NetworkService,
+    /// Address (`host:port`) of the SOCKS5 proxy to use for the service, `None` to connect
+    /// directly
+    pub socks5_proxy: Option<String>,
+}
+
+#[cfg(online)]
+/// Network configuration for the clients used by an online wallet.
+///
+/// When a SOCKS5 proxy (e.g. a Tor client at `127.0.0.1:9050`) is set, every connection to
+/// indexers, RGB proxy servers, reject list servers and the multisig hub goes through it, with
+/// host names resolved by the proxy, so the services don't learn the user's IP address.
+///
+/// With `stream_isolation`, the connections to the RGB proxy servers made for each transfer
+/// authenticate to the SOCKS5 proxy with different credentials, which makes Tor use a separate
+/// circuit for each transfer, so transfers cannot be linked by the exit node or the RGB proxy.
+///
+/// The compact block filters light client and the esplora client don't support SOCKS5 proxies, so
+/// they cannot be used when a proxy is set for [`NetworkService::Indexer`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct NetworkOptions {
+    /// Address (`host:port`) of the SOCKS5 proxy to use for all services, `None` to connect
+    /// directly
+    pub socks5_proxy: Option<String>,
+    /// Per-service overrides of `socks5_proxy`
+    #[serde(default)]
+    pub service_proxies: Vec<ServiceProxy>,
+    /// Whether to isolate the connections made for each transfer
+    #[serde(default)]
+    pub stream_isolation: bool,
+    /// Timeouts and retry policy for the HTTP clients
+    #[serde(default)]
+    pub policy: NetworkPolicy,
+}
+
+#[cfg(online)]
+impl NetworkOptions {
+    /// Return the SOCKS5 proxy to use for the provided service, if any, with credentials derived
+    /// from the provided isolation key if stream isolation is enabled.
+    pub(crate) fn socks5_proxy(
+        &self,
+        service: NetworkService,
+        isolation_key: Option<&str>,
+    ) -> Option<Socks5Proxy> {
+        let address = match self.service_proxies.iter().find(|p| p.service == service) {
+            Some(service_proxy) => service_proxy.socks5_proxy.clone(),
+            None => self.socks5_proxy.clone(),
+        }?;
+        let credentials = isolation_key
+            .filter(|_| self.stream_isolation)
+            .map(|key| (s!("rgb-lib"), hash_bytes_hex(key.as_bytes())));
+        Some(Socks5Proxy {
+            address,
+            credentials,
+        })
+    }
+}
+
+#[cfg(online)]
+/// Timeouts and retry policy for the clients of RGB proxy servers, reject list servers, the
+/// multisig hub and VSS servers.
+///
+/// A failed request is retried up to `max_retries` times, waiting between attempts a delay that
+/// starts at `initial_backoff_ms` and doubles at each attempt, up to `max_backoff_ms`. With
+/// `jitter`, each delay is randomly shortened by up to a half, so clients don't retry in lockstep.
+///
+/// Requests that only read data, or that the server handles as a no-op when received twice (like
+/// the proxy posts), are retried after connection errors, timeouts and server errors. Other
+/// requests changing data on the server are only retried if the connection could not be
+/// established, so the server never receives them twice.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct NetworkPolicy {
+    /// Seconds to wait for a connection to be established
+    pub connect_timeout_secs: u64,
+    /// Seconds to wait for a request to complete
+    pub timeout_secs: u64,
+    /// Maximum number of retries of a failed request
+    pub max_retries: u8,
+    /// Milliseconds to wait before the first retry
+    pub initial_backoff_ms: u64,
+    /// Maximum milliseconds to wait between retries
+    pub max_backoff_ms: u64,
+    /// Whether to randomize the delay between retries
+    pub jitter: bool,
+}
+
+#[cfg(online)]
+impl Default for NetworkPolicy {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            timeout_secs: 120,
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 10_000,
+            jitter: true,
+        }
+    }
+}
+
+#[cfg(online)]
+impl NetworkPolicy {
+    /// Return the delay to wait before the provided retry attempt (starting from 0).
+    pub(crate) fn backoff(&self, attempt: u8) -> Duration {
+        let backoff = self
+            .initial_backoff_ms
+            .saturating_mul(1 << attempt.min(32))
+            .min(self.max_backoff_ms);
+        let backoff = if self.jitter {
+            rand::rng().random_range(backoff / 2..=backoff)
+        } else {
+            backoff
+        };
+        Duration::from_millis(backoff)
+    }
+}
+
+#[cfg(online)]
+pub struct Socks5Proxy {
+    pub(crate) address: String,
+    pub(crate) credentials: Option<(String, String)>,
+}
+
+#[cfg(online)]
+impl Socks5Proxy {
+    /// Return the proxy URL, with host names resolved by the proxy.
+    pub(crate) fn url(&self) -> String {
+        match &self.credentials {
+            Some((username, password)) => {
+                format!("socks5h://{username}:{password}@{}", self.address)
+            }
+            None => format!("socks5h://{}", self.address),
+        }
+    }
 }
 
 // ────────────────────────────────────────────────────────────
@@ -123,7 +455,7 @@ impl From<DbTxo> for Outpoint {
     fn from(x: DbTxo) -> Outpoint {
         Outpoint {
             txid: x.txid,
-            vout: x.vout,
+            vout: x.vout as u32,
         }
     }
 }
@@ -442,7 +774,7 @@ impl AssetNIA {
             ticker: asset.ticker.clone().unwrap(),
             name: asset.name.clone(),
             details: asset.details.clone(),
-            precision: asset.precision,
+            precision: asset.precision as u8,
             issued_supply: initial_supply,
             timestamp: asset.timestamp,
             added_at: asset.added_at,
@@ -515,7 +847,7 @@ impl AssetUDA {
             details: asset.details.clone(),
             ticker: asset.ticker.clone().unwrap(),
             name: asset.name.clone(),
-            precision: asset.precision,
+            precision: asset.precision as u8,
             timestamp: asset.timestamp,
             added_at: asset.added_at,
             balance,
@@ -585,7 +917,7 @@ impl AssetCFA {
             asset_id: asset.id.clone(),
             name: asset.name.clone(),
             details: asset.details.clone(),
-            precision: asset.precision,
+            precision: asset.precision as u8,
             issued_supply: initial_supply,
             timestamp: asset.timestamp,
             added_at: asset.added_at,
@@ -671,7 +1003,7 @@ impl AssetIFA {
             ticker: asset.ticker.clone().unwrap(),
             name: asset.name.clone(),
             details: asset.details.clone(),
-            precision: asset.precision,
+            precision: asset.precision as u8,
             initial_supply,
             max_supply,
             known_circulating_supply,
@@ -784,7 +1116,7 @@ pub struct LocalAssetData {
 pub struct IssueData {
     pub(crate) asset_data: LocalAssetData,
     pub(crate) valid_contract: ValidContract,
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) contract_path: PathBuf,
     pub(crate) issue_utxos: HashMap<i32, Vec<Assignment>>,
 }
@@ -805,7 +1137,7 @@ pub struct AssignmentsCollection {
     pub inflation: u64,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl AssignmentsCollection {
     fn add_fungible(&mut self, amt: u64) {
         self.fungible = self
@@ -898,7 +1230,7 @@ pub enum TypeOfTransition {
 }
 
 impl TypeOfTransition {
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn type_name(&self) -> &'static str {
         match self {
             Self::Inflate => "inflate",
@@ -1158,6 +1490,347 @@ pub struct InvoiceData {
     pub unknown_query_params: HashMap<String, String>,
 }
 
+/// An RGB payment request, bundling the invoices for multiple asset/assignment pairs so that they
+/// can be paid with a single batch transfer.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct PaymentRequest {
+    /// The payment request string
+    payment_request_string: String,
+    /// The data of the payment request
+    pub(crate) payment_request_data: PaymentRequestData,
+}
+
+impl PaymentRequest {
+    /// Parse the provided `payment_request_string`.
+    /// Throws an error if the provided string is not a valid payment request.
+    pub fn new(payment_request_string: String) -> Result<Self, Error> {
+        let invoices = payment_request_string
+            .strip_prefix(PAYMENT_REQUEST_PREFIX)
+            .and_then(|r| general_purpose::URL_SAFE_NO_PAD.decode(r).ok())
+            .and_then(|b| serde_json::from_slice::<Vec<String>>(&b).ok())
+            .ok_or_else(|| Error::InvalidPaymentRequest {
+                details: s!("invalid encoding"),
+            })?;
+        Ok(PaymentRequest {
+            payment_request_data: PaymentRequestData::from_invoices(&invoices)?,
+            payment_request_string,
+        })
+    }
+
+    pub(crate) fn from_invoices(invoices: Vec<String>) -> Result<Self, Error> {
+        let payment_request_data = PaymentRequestData::from_invoices(&invoices)?;
+        let encoded = serde_json::to_vec(&invoices).map_err(InternalError::from)?;
+        Ok(PaymentRequest {
+            payment_request_string: format!(
+                "{PAYMENT_REQUEST_PREFIX}{}",
+                general_purpose::URL_SAFE_NO_PAD.encode(encoded)
+            ),
+            payment_request_data,
+        })
+    }
+
+    /// Return the data associated with this [`PaymentRequest`].
+    pub fn payment_request_data(&self) -> PaymentRequestData {
+        self.payment_request_data.clone()
+    }
+
+    /// Return the string associated with this [`PaymentRequest`].
+    pub fn payment_request_string(&self) -> String {
+        self.payment_request_string.clone()
+    }
+}
+
+/// The data of an RGB payment request.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct PaymentRequestData {
+    /// ID of the payment request
+    pub payment_request_id: String,
+    /// Requested lines, one for each bundled invoice
+    pub lines: Vec<InvoiceData>,
+    /// Bitcoin network
+    pub network: BitcoinNetwork,
+    /// Payment request expiration (the earliest one of the bundled invoices)
+    pub expiration_timestamp: Option<u64>,
+    /// Transport endpoints, shared by all the bundled invoices
+    pub transport_endpoints: Vec<String>,
+}
+
+impl PaymentRequestData {
+    fn from_invoices(invoices: &[String]) -> Result<Self, Error> {
+        let mut lines: Vec<InvoiceData> = vec![];
+        for invoice in invoices {
+            let invoice_data = Invoice::new(invoice.clone())?.invoice_data;
+            let Some(asset_id) = &invoice_data.asset_id else {
+                return Err(Error::InvalidPaymentRequest {
+                    details: s!("missing asset ID"),
+                });
+            };
+            if !invoice_data.assignment.requests_amount() {
+                return Err(Error::InvalidPaymentRequest {
+                    details: format!("missing amount for asset {asset_id}"),
+                });
+            }
+            if lines
+                .iter()
+                .any(|l| l.recipient_id == invoice_data.recipient_id)
+            {
+                return Err(Error::InvalidPaymentRequest {
+                    details: s!("duplicate recipient ID"),
+                });
+            }
+            if let Some(first) = lines.first() {
+                if first.network != invoice_data.network {
+                    return Err(Error::InvalidPaymentRequest {
+                        details: s!("invoices for different networks"),
+                    });
+                }
+                if first.transport_endpoints != invoice_data.transport_endpoints {
+                    return Err(Error::InvalidPaymentRequest {
+                        details: s!("invoices with different transport endpoints"),
+                    });
+                }
+            }
+            lines.push(invoice_data);
+        }
+        let Some(first) = lines.first() else {
+            return Err(Error::InvalidPaymentRequest {
+                details: s!("no invoices"),
+            });
+        };
+        let recipient_ids: Vec<&str> = lines.iter().map(|l| l.recipient_id.as_str()).collect();
+        Ok(PaymentRequestData {
+            payment_request_id: hash_bytes_hex(recipient_ids.join(",").as_bytes()),
+            network: first.network,
+            expiration_timestamp: lines.iter().filter_map(|l| l.expiration_timestamp).min(),
+            transport_endpoints: first.transport_endpoints.clone(),
+            lines,
+        })
+    }
+}
+
+/// The leg of an atomic swap that the taker pays to the maker.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub enum SwapLeg {
+    /// An RGB asset
+    Asset {
+        /// ID of the asset
+        asset_id: String,
+        /// RGB assignment to be paid
+        assignment: Assignment,
+    },
+    /// Bitcoins
+    Bitcoin {
+        /// Amount in sats
+        amount: u64,
+    },
+}
+
+/// The stage of an atomic swap offer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SwapStage {
+    /// Created by the maker, waiting for a taker
+    Offered,
+    /// Completed by the taker with its side of the swap, waiting for the maker's signatures
+    Taken,
+    /// Signed by the maker, waiting for the taker's signatures and broadcast
+    Signed,
+    /// Signed and broadcast by the taker
+    Broadcast,
+}
+
+/// An atomic swap offer, exchanging an RGB asset of the maker with an RGB asset or bitcoins of the
+/// taker in a single transaction.
+///
+/// The offer is passed back and forth between the maker and the taker, each step moving it to the
+/// next [`SwapStage`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct SwapOffer {
+    /// The swap offer string
+    swap_offer_string: String,
+    /// The data of the swap offer
+    pub(crate) swap_offer_data: SwapOfferData,
+    /// The data needed to progress the swap
+    #[cfg(online)]
+    #[serde(skip)]
+    pub(crate) payload: SwapOfferPayload,
+}
+
+impl SwapOffer {
+    /// Parse the provided `swap_offer_string`.
+    /// Throws an error if the provided string is not a valid swap offer.
+    pub fn new(swap_offer_string: String) -> Result<Self, Error> {
+        let content = swap_offer_string
+            .strip_prefix(SWAP_OFFER_PREFIX)
+            .and_then(|r| general_purpose::URL_SAFE_NO_PAD.decode(r).ok())
+            .and_then(|b| serde_json::from_slice::<SwapOfferContent>(&b).ok())
+            .ok_or_else(|| Error::InvalidSwapOffer {
+                details: s!("invalid encoding"),
+            })?;
+        Ok(SwapOffer {
+            swap_offer_string,
+            swap_offer_data: content.data,
+            #[cfg(online)]
+            payload: content.payload,
+        })
+    }
+
+    #[cfg(online)]
+    pub(crate) fn from_parts(
+        swap_offer_data: SwapOfferData,
+        payload: SwapOfferPayload,
+    ) -> Result<Self, Error> {
+        let content = SwapOfferContent {
+            data: swap_offer_data,
+            payload,
+        };
+        let encoded = serde_json::to_vec(&content).map_err(InternalError::from)?;
+        Ok(SwapOffer {
+            swap_offer_string: format!(
+                "{SWAP_OFFER_PREFIX}{}",
+                general_purpose::URL_SAFE_NO_PAD.encode(encoded)
+            ),
+            swap_offer_data: content.data,
+            payload: content.payload,
+        })
+    }
+
+    /// Return the data associated with this [`SwapOffer`].
+    pub fn swap_offer_data(&self) -> SwapOfferData {
+        self.swap_offer_data.clone()
+    }
+
+    /// Return the string associated with this [`SwapOffer`].
+    pub fn swap_offer_string(&self) -> String {
+        self.swap_offer_string.clone()
+    }
+}
+
+/// The data of an atomic swap offer.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct SwapOfferData {
+    /// ID of the swap
+    pub swap_id: String,
+    /// Bitcoin network
+    pub network: BitcoinNetwork,
+    /// ID of the asset offered by the maker
+    pub maker_asset_id: String,
+    /// RGB assignment offered by the maker
+    pub maker_assignment: Assignment,
+    /// What the taker pays in exchange
+    pub taker_leg: SwapLeg,
+    /// Swap offer expiration
+    pub expiration_timestamp: u64,
+    /// Current stage of the swap
+    pub stage: SwapStage,
+    /// ID of the swap transaction, known once the offer has been taken
+    pub txid: Option<String>,
+}
+
+#[cfg(online)]
+impl SwapOfferData {
+    // whether the other offer has the same terms, regardless of the stage it's at
+    pub(crate) fn same_terms(&self, other: &SwapOfferData) -> bool {
+        self.swap_id == other.swap_id
+            && self.network == other.network
+            && self.maker_asset_id == other.maker_asset_id
+            && self.maker_assignment == other.maker_assignment
+            && self.taker_leg == other.taker_leg
+            && self.expiration_timestamp == other.expiration_timestamp
+    }
+}
+
+/// A sell order, offering an RGB asset of the seller to any buyer paying the requested price in
+/// bitcoins.
+///
+/// The seller input is signed with `SIGHASH_SINGLE|ANYONECANPAY`, so the buyer can complete the
+/// transaction alone, adding the inputs that pay the price and the output receiving the asset. The
+/// signature doesn't cover the RGB commitment, so the seller input holds only the sold assignment.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct SellOrder {
+    /// The sell order string
+    sell_order_string: String,
+    /// The data of the sell order
+    pub(crate) sell_order_data: SellOrderData,
+    /// The data needed to fill the sell order
+    #[cfg(online)]
+    #[serde(skip)]
+    pub(crate) payload: SellOrderPayload,
+}
+
+impl SellOrder {
+    /// Parse the provided `sell_order_string`.
+    /// Throws an error if the provided string is not a valid sell order.
+    pub fn new(sell_order_string: String) -> Result<Self, Error> {
+        let content = sell_order_string
+            .strip_prefix(SELL_ORDER_PREFIX)
+            .and_then(|r| general_purpose::URL_SAFE_NO_PAD.decode(r).ok())
+            .and_then(|b| serde_json::from_slice::<SellOrderContent>(&b).ok())
+            .ok_or_else(|| Error::InvalidSellOrder {
+                details: s!("invalid encoding"),
+            })?;
+        Ok(SellOrder {
+            sell_order_string,
+            sell_order_data: content.data,
+            #[cfg(online)]
+            payload: content.payload,
+        })
+    }
+
+    #[cfg(online)]
+    pub(crate) fn from_parts(
+        sell_order_data: SellOrderData,
+        payload: SellOrderPayload,
+    ) -> Result<Self, Error> {
+        let content = SellOrderContent {
+            data: sell_order_data,
+            payload,
+        };
+        let encoded = serde_json::to_vec(&content).map_err(InternalError::from)?;
+        Ok(SellOrder {
+            sell_order_string: format!(
+                "{SELL_ORDER_PREFIX}{}",
+                general_purpose::URL_SAFE_NO_PAD.encode(encoded)
+            ),
+            sell_order_data: content.data,
+            payload: content.payload,
+        })
+    }
+
+    /// Return the data associated with this [`SellOrder`].
+    pub fn sell_order_data(&self) -> SellOrderData {
+        self.sell_order_data.clone()
+    }
+
+    /// Return the string associated with this [`SellOrder`].
+    pub fn sell_order_string(&self) -> String {
+        self.sell_order_string.clone()
+    }
+}
+
+/// The data of a sell order.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct SellOrderData {
+    /// ID of the sell order
+    pub sell_order_id: String,
+    /// Bitcoin network
+    pub network: BitcoinNetwork,
+    /// ID of the asset being sold
+    pub asset_id: String,
+    /// RGB assignment being sold
+    pub assignment: Assignment,
+    /// Price in sats
+    pub price: u64,
+    /// Sell order expiration
+    pub expiration_timestamp: u64,
+}
+
 /// An RGB transport endpoint.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
@@ -1215,6 +1888,70 @@ pub enum TransferKind {
     Inflation,
     /// A burn transfer
     Burn,
+    /// A transfer moving the wallet's own allocations onto fewer UTXOs
+    Consolidation,
+    /// A leg of an atomic swap, either outgoing or incoming
+    Swap,
+}
+
+/// An event emitted when the wallet state changes.
+///
+/// Events are delivered to the registered [`WalletEventListener`] only after the changes that
+/// produced them have been persisted.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub enum WalletEvent {
+    /// A batch transfer has been created or its status has changed
+    TransferStatusChanged {
+        /// Batch transfer idx
+        batch_transfer_idx: i32,
+        /// The new transfer status
+        status: TransferStatus,
+    },
+    /// An asset has been seen for the first time
+    AssetAdded {
+        /// Asset ID
+        asset_id: String,
+    },
+    /// A colored UTXO has been created
+    UtxoCreated {
+        /// UTXO outpoint
+        outpoint: Outpoint,
+    },
+    /// A colored UTXO has been spent
+    UtxoSpent {
+        /// UTXO outpoint
+        outpoint: Outpoint,
+    },
+    /// The refresh of a batch transfer has failed
+    TransferRefreshFailed {
+        /// Batch transfer idx
+        batch_transfer_idx: i32,
+        /// Failure details
+        details: String,
+    },
+    /// A run of the background auto-refresh task has completed
+    AutoRefreshCompleted {
+        /// Whether the wallet has been synced
+        synced: bool,
+        /// Whether any transfer has changed
+        transfers_changed: bool,
+    },
+    /// A run of the background auto-refresh task has failed
+    AutoRefreshFailed {
+        /// Failure details
+        details: String,
+    },
+}
+
+/// A listener receiving the [`WalletEvent`]s emitted by a wallet.
+///
+/// Events are delivered synchronously, from the thread running the wallet operation that produced
+/// them (the background task's thread for auto-refresh), so implementations should return
+/// quickly.
+pub trait WalletEventListener: Send + Sync {
+    /// Handle a wallet event
+    fn on_event(&self, event: WalletEvent);
 }
 
 #[derive(Debug, Clone)]
@@ -1477,6 +2214,8 @@ pub enum TransactionType {
     CreateUtxos,
     /// Transaction used to perform a BTC send
     SendBtc,
+    /// Child transaction used to accelerate a pending transaction (CPFP)
+    Cpfp,
     /// Incoming transaction
     Incoming,
 }
@@ -1628,9 +2367,52 @@ pub struct RgbInspection {
 // Send, inflate, burn & refresh operations
 // ────────────────────────────────────────────────────────────
 
+/// The result of a bump fee begin operation.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg(online)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct BumpFeeBeginResult {
+    /// PSBT to inspect and sign
+    pub psbt: String,
+    /// Batch transfer idx
+    pub batch_transfer_idx: i32,
+    /// Operation details
+    pub details: BumpFeeDetails,
+}
+
+/// Details for bump fee operations.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg(online)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct BumpFeeDetails {
+    /// Path to fascia file for inspection
+    pub fascia_path: String,
+    /// ID of the transaction being replaced
+    pub replaced_txid: String,
+    /// Entropy used for the merkle tree construction operation
+    pub entropy: u64,
+}
+
+/// The result of a bump fee operation.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg(online)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct BumpFeeResult {
+    /// ID of the replacement transaction
+    pub txid: String,
+    /// ID of the replaced transaction
+    pub replaced_txid: String,
+    /// Batch transfer idx
+    pub batch_transfer_idx: i32,
+    /// Entropy used for the merkle tree construction operation
+    pub entropy: u64,
+    /// Recipient IDs that need to receive the updated consignment out-of-band
+    pub recipient_ids_to_notify: Vec<String>,
+}
+
 /// The result of a burn begin operation.
 #[derive(Debug, Clone, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct BurnBeginResult {
     /// PSBT to inspect and sign
@@ -1643,7 +2425,7 @@ pub struct BurnBeginResult {
 
 /// Details for burn operations.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct BurnDetails {
     /// Path to fascia file for inspection
@@ -1654,9 +2436,71 @@ pub struct BurnDetails {
     pub entropy: u64,
 }
 
+/// The result of a consolidate allocations begin operation.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg(online)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct ConsolidateAllocationsBeginResult {
+    /// PSBT to inspect and sign
+    pub psbt: String,
+    /// Batch transfer idx, None when `dry_run: true`
+    pub batch_transfer_idx: Option<i32>,
+    /// Operation details
+    pub details: ConsolidateAllocationsDetails,
+}
+
+/// Details for consolidate allocations operations.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg(online)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct ConsolidateAllocationsDetails {
+    /// Path to fascia file for inspection
+    pub fascia_path: String,
+    /// IDs of the assets whose allocations get consolidated
+    pub asset_ids: Vec<String>,
+    /// Minimum confirmations for the operation
+    pub min_confirmations: u8,
+    /// Entropy used for the merkle tree construction operation
+    pub entropy: u64,
+}
+
+/// Strategy used to select the UTXOs hosting the RGB allocations to be spent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg(online)]
+pub enum CoinSelectionStrategy {
+    /// Select UTXOs with the smallest allocation amounts first
+    #[default]
+    SmallestFirst,
+    /// Select UTXOs with the largest allocation amounts first
+    LargestFirst,
+    /// Select the smallest UTXO covering the whole amount if there's one, otherwise select UTXOs
+    /// with the largest allocation amounts first
+    MinimizeInputs,
+    /// Select only UTXOs hosting no allocations of other assets (unless explicitly included),
+    /// smallest allocation amounts first, failing with
+    /// [`Error::InsufficientAssignments`](crate::Error::InsufficientAssignments) if they don't
+    /// cover the needed amount
+    AvoidOtherAssets,
+    /// Select UTXOs in random order
+    Random,
+}
+
+/// Options for the selection of the UTXOs hosting the RGB allocations to be spent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg(online)]
+#[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
+pub struct CoinSelectionOptions {
+    /// Strategy used to select UTXOs
+    pub strategy: CoinSelectionStrategy,
+    /// Outpoints that must be spent, regardless of the strategy
+    pub included_outpoints: Vec<Outpoint>,
+    /// Outpoints that must not be spent
+    pub excluded_outpoints: Vec<Outpoint>,
+}
+
 /// The result of an inflate begin operation.
 #[derive(Debug, Clone, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct InflateBeginResult {
     /// PSBT to inspect and sign
@@ -1669,7 +2513,7 @@ pub struct InflateBeginResult {
 
 /// Details for inflate operations.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct InflateDetails {
     /// Path to fascia file for inspection
@@ -1682,7 +2526,7 @@ pub struct InflateDetails {
 
 /// The result of a send begin operation.
 #[derive(Debug, Clone, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct SendBeginResult {
     /// PSBT to inspect and sign
@@ -1695,7 +2539,7 @@ pub struct SendBeginResult {
 
 /// Details for send operations.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct SendDetails {
     /// Path to fascia file for inspection
@@ -1710,7 +2554,7 @@ pub struct SendDetails {
 
 /// The result of an operation.
 #[derive(Debug, Clone, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct OperationResult {
     /// ID of the transaction
@@ -1723,7 +2567,7 @@ pub struct OperationResult {
 
 /// The pending status of a [`Transfer`] (eligible for refresh).
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub enum RefreshTransferStatus {
     /// Waiting for the counterparty to take action
     WaitingCounterparty,
@@ -1735,7 +2579,7 @@ pub enum RefreshTransferStatus {
     WaitingConfirmations,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl TryFrom<TransferStatus> for RefreshTransferStatus {
     type Error = &'static str;
 
@@ -1752,7 +2596,7 @@ impl TryFrom<TransferStatus> for RefreshTransferStatus {
 
 /// A transfer refresh filter.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct RefreshFilter {
     /// Transfer status
@@ -1763,7 +2607,7 @@ pub struct RefreshFilter {
 
 /// A refreshed transfer
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[cfg_attr(feature = "camel_case", serde(rename_all = "camelCase"))]
 pub struct RefreshedTransfer {
     /// The updated transfer status, if it has changed
@@ -1773,15 +2617,15 @@ pub struct RefreshedTransfer {
 }
 
 /// The result of a refresh operation
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub type RefreshResult = HashMap<i32, RefreshedTransfer>;
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) trait RefreshResultTrait {
     fn transfers_changed(&self) -> bool;
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl RefreshResultTrait for RefreshResult {
     fn transfers_changed(&self) -> bool {
         self.values().any(|rt| rt.updated_status.is_some())
@@ -1824,7 +2668,7 @@ pub enum LocalRecipientData {
 }
 
 impl LocalRecipientData {
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn vout(&self) -> Option<u32> {
         match &self {
             LocalRecipientData::Blind(_) => None,
@@ -1886,6 +2730,12 @@ pub struct BtcChange {
     pub amount: u64,
 }
 
+#[cfg(online)]
+pub struct BtcDrain {
+    pub outpoints: Vec<BdkOutPoint>,
+    pub script_pubkey: ScriptBuf,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct InfoAssetTransfer {
     pub asset_info: AssetInfo,
@@ -1898,6 +2748,8 @@ pub struct InfoAssetTransfer {
     pub main_transition: TypeOfTransition,
     pub beneficiaries_blinded: Vec<SecretSeal>,
     pub beneficiaries_witness: Vec<ExplicitSeal<RgbTxid>>,
+    #[serde(default)]
+    pub consolidation: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -1911,11 +2763,55 @@ pub struct InfoBatchTransfer {
     pub created_at: i64,
     pub entropy: u64,
     pub transfers: BTreeMap<String, InfoAssetTransfer>,
+    #[serde(default)]
+    pub replaced_txid: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SwapOfferPayload {
+    /// The swap PSBT
+    pub psbt: String,
+    /// Output receiving the maker's asset
+    pub taker_vout: u32,
+    /// Output receiving the taker's asset, if the taker pays with an asset
+    pub maker_vout: Option<u32>,
+    /// Recipient ID of the maker, if the taker pays with an asset
+    pub maker_recipient_id: Option<String>,
+    /// Address of the maker, if the taker pays with bitcoins
+    pub maker_address: Option<String>,
+    /// Recipient ID of the taker
+    pub taker_recipient_id: Option<String>,
+    /// Serialized fascia of the swap transaction
+    pub fascia: Option<String>,
+    /// Base64-encoded consignment of the taker's asset
+    pub taker_consignment: Option<String>,
+    /// Base64-encoded consignment of the maker's asset
+    pub maker_consignment: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SwapOfferContent {
+    data: SwapOfferData,
+    payload: SwapOfferPayload,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SellOrderPayload {
+    /// The sell PSBT, with the seller input signed
+    pub psbt: String,
+    /// Base64-encoded consignment of the sold asset
+    pub consignment: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SellOrderContent {
+    data: SellOrderData,
+    payload: SellOrderPayload,
 }
 
 pub type TransferEndData = (String, PathBuf, InfoBatchTransfer, Fascia);
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub struct BeginOperationData {
     pub psbt: Psbt,
     pub transfer_dir: PathBuf,
@@ -1923,51 +2819,59 @@ pub struct BeginOperationData {
     pub batch_transfer_idx: Option<i32>,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
+pub struct RgbTransitionsData {
+    pub asset_beneficiaries: BTreeMap<String, Vec<(BuilderSeal<GraphSeal>, String)>>,
+    pub extra_allocations: HashMap<String, HashMap<OutPoint, Vec<Assignment>>>,
+    pub input_opouts: HashMap<ContractId, HashMap<Opout, AllocatedState>>,
+    pub change_utxo_option: Option<DbTxo>,
+}
+
+#[cfg(online)]
 pub enum PrepareRgbPsbtResult {
     Retry,
     Success(Box<BeginOperationData>),
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub enum PrepareTransferPsbtResult {
     Retry,
     Success(Box<BeginOperationData>),
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ReceivedConsignmentMeta {
     pub txid: String,
     pub vout: Option<u32>,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub enum TryFailBatchTransferOutcome {
     Failed,
     Refreshed,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub struct FailTransfersOutcome {
     pub transfers_changed: bool,
     pub cannot_fail: bool,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub enum ReceiveMode {
     Proxy { proxy_url: String },
     OutOfBand { media_file_paths: Vec<String> },
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub(crate) enum ReceiveMatcher {
     Blind(SecretSeal),
     Witness(ScriptBuf),
 }
 
 impl DbTransfer {
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub(crate) fn receive_matcher(&self) -> Result<ReceiveMatcher, Error> {
         let recipient_id = self
             .recipient_id
@@ -1996,13 +2900,13 @@ impl DbTransfer {
 }
 
 #[cfg(test)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 mod tests {
     use super::*;
     use sea_orm::Iterable;
 
     #[test]
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     fn refresh_transfer_status_matches_waiting() {
         for status in TransferStatus::iter() {
             assert_eq!(
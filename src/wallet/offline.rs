@@ -87,7 +87,9 @@ pub trait WalletOffline: WalletBackup {
     fn filter_unspents(&self, keychain: KeychainKind) -> impl Iterator<Item = LocalOutput> + '_ {
         self.bdk_wallet()
             .list_unspent()
-            .filter(move |u| u.keychain == keychain)
+            .filter(|u| u.keychain == keychain)
+            .collect::<Vec<_>>()
+            .into_iter()
     }
 
     fn internal_unspents(&self) -> impl Iterator<Item = LocalOutput> + '_ {
@@ -97,7 +99,9 @@ pub trait WalletOffline: WalletBackup {
     fn filter_outputs(&self, keychain: KeychainKind) -> impl Iterator<Item = LocalOutput> + '_ {
         self.bdk_wallet()
             .list_output()
-            .filter(move |u| u.keychain == keychain)
+            .filter(|u| u.keychain == keychain)
+            .collect::<Vec<_>>()
+            .into_iter()
     }
 
     fn get_available_allocations<T>(
@@ -153,8 +157,15 @@ pub trait WalletOffline: WalletBackup {
             None => rgb_allocations.as_deref().unwrap(),
         };
 
+        // also exclude UTXOs that will be spent by pending transactions
+        let mut exclude_utxos = exclude_utxos.to_vec();
+        exclude_utxos.extend(
+            self.get_reserved_outpoints(txn)?
+                .into_iter()
+                .map(Outpoint::from),
+        );
         let mut allocatable =
-            self.get_available_allocations(unspents, exclude_utxos, max_allocations)?;
+            self.get_available_allocations(unspents, &exclude_utxos, max_allocations)?;
         allocatable.sort_by_key(|t| t.rgb_allocations.len() + t.pending_blinded as usize);
         match allocatable.first() {
             Some(mut selected) => {
@@ -502,7 +513,7 @@ pub trait WalletOffline: WalletBackup {
         Ok(IssueData {
             asset_data,
             valid_contract,
-            #[cfg(any(feature = "electrum", feature = "esplora"))]
+            #[cfg(online)]
             contract_path: _contract_path,
             issue_utxos,
         })
@@ -649,7 +660,7 @@ pub trait WalletOffline: WalletBackup {
         Ok(IssueData {
             asset_data,
             valid_contract,
-            #[cfg(any(feature = "electrum", feature = "esplora"))]
+            #[cfg(online)]
             contract_path: _contract_path,
             issue_utxos,
         })
@@ -760,7 +771,7 @@ pub trait WalletOffline: WalletBackup {
         Ok(IssueData {
             asset_data,
             valid_contract,
-            #[cfg(any(feature = "electrum", feature = "esplora"))]
+            #[cfg(online)]
             contract_path: _contract_path,
             issue_utxos,
         })
@@ -897,7 +908,7 @@ pub trait WalletOffline: WalletBackup {
         Ok(IssueData {
             asset_data,
             valid_contract,
-            #[cfg(any(feature = "electrum", feature = "esplora"))]
+            #[cfg(online)]
             contract_path: _contract_path,
             issue_utxos,
         })
@@ -1059,17 +1070,31 @@ pub trait WalletOffline: WalletBackup {
         })
     }
 
+    fn check_receive_policy(
+        &self,
+        assignment: &Assignment,
+        receive_policy: ReceivePolicy,
+    ) -> Result<(), Error> {
+        if receive_policy != ReceivePolicy::Any && !assignment.requests_amount() {
+            return Err(Error::InvalidReceivePolicy {
+                details: s!("the receive doesn't request an amount"),
+            });
+        }
+        Ok(())
+    }
+
     fn store_receive_transfer(
         &self,
         txn: &DbTxn,
         receive_data_internal: &ReceiveDataInternal,
+        receive_policy: ReceivePolicy,
         min_confirmations: u8,
     ) -> Result<i32, Error> {
         let batch_transfer = DbBatchTransferActMod {
             status: ActiveValue::Set(TransferStatus::WaitingCounterparty),
             expiration: ActiveValue::Set(Some(receive_data_internal.expiration_timestamp)),
             created_at: ActiveValue::Set(receive_data_internal.created_at),
-            min_confirmations: ActiveValue::Set(min_confirmations),
+            min_confirmations: ActiveValue::Set(min_confirmations.into()),
             incoming: ActiveValue::Set(true),
             ..Default::default()
         };
@@ -1091,6 +1116,7 @@ pub trait WalletOffline: WalletBackup {
                 receive_data_internal.recipient_type_full.clone(),
             )),
             invoice_string: ActiveValue::Set(Some(receive_data_internal.invoice_string.clone())),
+            receive_policy: ActiveValue::Set(Some(receive_policy)),
             ..Default::default()
         };
         let transfer_idx = txn.set_transfer(transfer)?;
@@ -1121,6 +1147,77 @@ pub trait WalletOffline: WalletBackup {
         Ok(batch_transfer_idx)
     }
 
+    fn create_payment_request_impl(
+        &self,
+        txn: &DbTxn,
+        receive_data: Vec<ReceiveData>,
+    ) -> Result<PaymentRequest, Error> {
+        let payment_request = PaymentRequest::from_invoices(
+            receive_data.iter().map(|r| r.invoice.clone()).collect(),
+        )?;
+        let payment_request_data = &payment_request.payment_request_data;
+        if payment_request_data.network != self.bitcoin_network() {
+            return Err(Error::InvalidPaymentRequest {
+                details: s!("invoices for a different network"),
+            });
+        }
+
+        // every line needs to be a pending receive of this wallet, not bundled yet
+        let db_data = txn.get_db_data(false)?;
+        let mut batch_transfers = vec![];
+        for (rcv_data, line) in receive_data.iter().zip(&payment_request_data.lines) {
+            let batch_transfer = db_data
+                .batch_transfers
+                .iter()
+                .find(|bt| bt.idx == rcv_data.batch_transfer_idx)
+                .filter(|bt| {
+                    bt.incoming
+                        && bt.status == TransferStatus::WaitingCounterparty
+                        && bt.txid.is_none()
+                })
+                .ok_or_else(|| Error::InvalidPaymentRequest {
+                    details: format!(
+                        "batch transfer {} is not a pending receive",
+                        rcv_data.batch_transfer_idx
+                    ),
+                })?;
+            let asset_transfer_idxs: Vec<i32> = batch_transfer
+                .get_asset_transfers(&db_data.asset_transfers)
+                .iter()
+                .map(|at| at.idx)
+                .collect();
+            if !db_data.transfers.iter().any(|t| {
+                asset_transfer_idxs.contains(&t.asset_transfer_idx)
+                    && t.recipient_id.as_ref() == Some(&line.recipient_id)
+            }) {
+                return Err(Error::InvalidPaymentRequest {
+                    details: format!(
+                        "batch transfer {} doesn't match the invoice",
+                        batch_transfer.idx
+                    ),
+                });
+            }
+            if batch_transfer.payment_request_id.is_some() {
+                return Err(Error::InvalidPaymentRequest {
+                    details: format!(
+                        "batch transfer {} already belongs to a payment request",
+                        batch_transfer.idx
+                    ),
+                });
+            }
+            batch_transfers.push(batch_transfer.clone());
+        }
+
+        for batch_transfer in batch_transfers {
+            let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.into();
+            updated_batch_transfer.payment_request_id =
+                ActiveValue::Set(Some(payment_request_data.payment_request_id.clone()));
+            txn.update_batch_transfer(&mut updated_batch_transfer)?;
+        }
+
+        Ok(payment_request)
+    }
+
     fn finalize_psbt_impl(
         &self,
         signed_psbt: &mut Psbt,
@@ -1232,9 +1329,9 @@ pub trait WalletOffline: WalletBackup {
         keychain: KeychainKind,
         _count: u32,
     ) -> Result<BdkAddress, Error> {
-        let (bdk_wallet, bdk_db) = self.bdk_wallet_db_mut();
-        let address = bdk_wallet.reveal_next_address(keychain).address;
-        bdk_wallet.persist(bdk_db)?;
+        let mut bdk = self.bdk();
+        let address = bdk.wallet.reveal_next_address(keychain).address;
+        bdk.persist()?;
         Ok(address)
     }
 
@@ -1303,7 +1400,7 @@ pub trait WalletOffline: WalletBackup {
             known_circulating_supply,
             timestamp: asset.timestamp,
             name: asset.name,
-            precision: asset.precision,
+            precision: asset.precision as u8,
             ticker: asset.ticker,
             details: asset.details,
             token,
@@ -1324,7 +1421,7 @@ pub trait WalletOffline: WalletBackup {
         txn.set_token_media(DbTokenMediaActMod {
             token_idx: ActiveValue::Set(token_idx),
             media_idx: ActiveValue::Set(media_idx),
-            attachment_id: ActiveValue::Set(attachment_id),
+            attachment_id: ActiveValue::Set(attachment_id.map(i16::from)),
             ..Default::default()
         })?;
 
@@ -1350,18 +1447,21 @@ pub trait WalletOffline: WalletBackup {
                 asset_data.known_circulating_supply.map(|s| s.to_string()),
             ),
             name: ActiveValue::Set(asset_data.name.clone()),
-            precision: ActiveValue::Set(asset_data.precision),
+            precision: ActiveValue::Set(asset_data.precision.into()),
             ticker: ActiveValue::Set(asset_data.ticker.clone()),
             timestamp: ActiveValue::Set(asset_data.timestamp),
             reject_list_url: ActiveValue::Set(asset_data.reject_list_url.clone()),
         };
         let idx = txn.set_asset(db_asset.clone())?;
         db_asset.idx = ActiveValue::Set(idx);
+        txn.emit_event(WalletEvent::AssetAdded {
+            asset_id: asset_data.asset_id.clone(),
+        });
 
         if let Some(ref token) = asset_data.token {
             let db_token = DbTokenActMod {
                 asset_idx: ActiveValue::Set(idx),
-                index: ActiveValue::Set(token.index),
+                index: ActiveValue::Set(token.index.into()),
                 ticker: ActiveValue::Set(token.ticker.clone()),
                 name: ActiveValue::Set(token.name.clone()),
                 details: ActiveValue::Set(token.details.clone()),
@@ -1412,14 +1512,14 @@ pub trait WalletOffline: WalletBackup {
                     let db_media = medias.iter().find(|m| m.idx == tm.media_idx).unwrap();
                     let media_tkn = Media::from_db_media(db_media, &media_dir);
                     if let Some(attachment_id) = tm.attachment_id {
-                        attachments.insert(attachment_id, media_tkn);
+                        attachments.insert(attachment_id as u8, media_tkn);
                     } else {
                         media = Some(media_tkn);
                     }
                 });
 
             Some(TokenLight {
-                index: db_token.index,
+                index: db_token.index as u32,
                 ticker: db_token.ticker.clone(),
                 name: db_token.name.clone(),
                 details: db_token.details.clone(),
@@ -1456,10 +1556,11 @@ pub trait WalletOffline: WalletBackup {
     }
 
     fn get_btc_balance_for_keychain(&self, keychain: KeychainKind) -> Result<Balance, Error> {
-        let chain = self.bdk_wallet().local_chain();
-        let chain_tip = self.bdk_wallet().latest_checkpoint().block_id();
         let outpoints = self.filter_unspents(keychain).map(|lo| ((), lo.outpoint));
-        let balance = self.bdk_wallet().as_ref().balance(
+        let bdk_wallet = self.bdk_wallet();
+        let chain = bdk_wallet.local_chain();
+        let chain_tip = bdk_wallet.latest_checkpoint().block_id();
+        let balance = bdk_wallet.as_ref().balance(
             chain,
             chain_tip,
             CanonicalizationParams::default(),
@@ -1667,7 +1768,9 @@ pub trait WalletOffline: WalletBackup {
         Ok(local_asset_data)
     }
 
-    fn get_reserved_vanilla_outpoints(&self, txn: &DbTxn) -> Result<Vec<BdkOutPoint>, Error> {
+    fn get_reserved_outpoints(&self, txn: &DbTxn) -> Result<Vec<BdkOutPoint>, Error> {
+        // expired swap offers don't reserve UTXOs anymore
+        txn.del_expired_wallet_transactions(now().unix_timestamp())?;
         Ok(txn
             .iter_reserved_txos()?
             .into_iter()
@@ -1681,7 +1784,7 @@ pub trait WalletOffline: WalletBackup {
             .into_iter()
             .map(BdkOutPoint::from)
             .collect();
-        outpoints.extend(self.get_reserved_vanilla_outpoints(txn)?);
+        outpoints.extend(self.get_reserved_outpoints(txn)?);
         Ok(outpoints)
     }
 
@@ -1812,27 +1915,15 @@ pub trait WalletOffline: WalletBackup {
 
     fn sync_if_requested(
         &mut self,
-        #[cfg_attr(
-            not(any(feature = "electrum", feature = "esplora")),
-            allow(unused_variables)
-        )]
-        txn: &DbTxn,
-        #[cfg_attr(
-            not(any(feature = "electrum", feature = "esplora")),
-            allow(unused_variables)
-        )]
-        online: Option<Online>,
+        #[cfg_attr(not(online), allow(unused_variables))] txn: &DbTxn,
+        #[cfg_attr(not(online), allow(unused_variables))] online: Option<Online>,
         skip_sync: bool,
-        #[cfg_attr(
-            not(any(feature = "electrum", feature = "esplora")),
-            allow(unused_variables)
-        )]
-        keychain: KeychainKind,
+        #[cfg_attr(not(online), allow(unused_variables))] keychain: KeychainKind,
     ) -> Result<(), Error> {
         if !skip_sync {
-            #[cfg(not(any(feature = "electrum", feature = "esplora")))]
+            #[cfg(not(online))]
             return Err(Error::Offline);
-            #[cfg(any(feature = "electrum", feature = "esplora"))]
+            #[cfg(online)]
             {
                 if let Some(online) = online {
                     self.check_online(online)?;
@@ -1870,12 +1961,16 @@ pub trait WalletOffline: WalletBackup {
         let mut create_utxos_txids = vec![];
         let mut drain_txids = vec![];
         let mut send_btc_txids = vec![];
+        let mut cpfp_txids = vec![];
         let wallet_transactions = txn.iter_wallet_transactions()?;
         for tx in wallet_transactions {
             match tx.r#type {
                 WalletTransactionType::CreateUtxos => create_utxos_txids.push(tx.txid),
                 WalletTransactionType::Drain => drain_txids.push(tx.txid),
                 WalletTransactionType::SendBtc => send_btc_txids.push(tx.txid),
+                WalletTransactionType::Cpfp => cpfp_txids.push(tx.txid),
+                // swap offer TXs are never broadcast as they are
+                WalletTransactionType::SwapOffer => {}
             }
         }
         let rgb_send_txids: Vec<String> = txn
@@ -1883,8 +1978,8 @@ pub trait WalletOffline: WalletBackup {
             .into_iter()
             .filter_map(|t| t.txid)
             .collect();
-        Ok(self
-            .bdk_wallet()
+        let bdk_wallet = self.bdk_wallet();
+        Ok(bdk_wallet
             .transactions_sort_by(|tx1, tx2| tx2.chain_position.cmp(&tx1.chain_position))
             .into_iter()
             .map(|t| {
@@ -1897,6 +1992,8 @@ pub trait WalletOffline: WalletBackup {
                     TransactionType::RgbSend
                 } else if send_btc_txids.contains(&txid) {
                     TransactionType::SendBtc
+                } else if cpfp_txids.contains(&txid) {
+                    TransactionType::Cpfp
                 } else {
                     TransactionType::Incoming
                 };
@@ -1907,8 +2004,8 @@ pub trait WalletOffline: WalletBackup {
                     }),
                     _ => None,
                 };
-                let (sent, received) = self.bdk_wallet().sent_and_received(&t.tx_node);
-                let fee = self.bdk_wallet().calculate_fee(&t.tx_node).unwrap();
+                let (sent, received) = bdk_wallet.sent_and_received(&t.tx_node);
+                let fee = bdk_wallet.calculate_fee(&t.tx_node).unwrap();
                 Transaction {
                     transaction_type,
                     txid,
@@ -1966,7 +2063,10 @@ pub trait WalletOffline: WalletBackup {
             .map(|c| c.assignment)
             .collect();
 
-        let kind = if batch_transfer.incoming {
+        let kind = if batch_transfer.swap_id.is_some() {
+            // both legs of a swap are reported as such, their direction is the batch transfer one
+            TransferKind::Swap
+        } else if batch_transfer.incoming {
             if filtered_coloring.clone().count() > 0
                 && filtered_coloring
                     .clone()
@@ -1988,6 +2088,9 @@ pub trait WalletOffline: WalletBackup {
         {
             // inflation transfer is outgoing and connected to issue colorings
             TransferKind::Inflation
+        } else if transfer.recipient_type.is_some() {
+            // consolidation is the only other outgoing transfer with a recipient type
+            TransferKind::Consolidation
         } else {
             TransferKind::Send
         };
@@ -2010,7 +2113,9 @@ pub trait WalletOffline: WalletBackup {
                 transfer_txos
                     .clone()
                     .into_iter()
-                    .filter(|t| received_txo_idx.contains(&t.idx) && t.vout == vout.unwrap())
+                    .filter(|t| {
+                        received_txo_idx.contains(&t.idx) && t.vout == i64::from(vout.unwrap())
+                    })
                     .map(|t| t.outpoint())
                     .collect::<Vec<Outpoint>>()
                     .first()
@@ -2020,7 +2125,12 @@ pub trait WalletOffline: WalletBackup {
         };
         let change_utxo = match kind {
             TransferKind::ReceiveBlind | TransferKind::ReceiveWitness => None,
-            TransferKind::Send | TransferKind::Inflation | TransferKind::Burn => {
+            TransferKind::Swap if batch_transfer.incoming => None,
+            TransferKind::Send
+            | TransferKind::Swap
+            | TransferKind::Inflation
+            | TransferKind::Burn
+            | TransferKind::Consolidation => {
                 let change_txo_idx: Vec<i32> = filtered_coloring
                     .filter(|c| c.r#type == ColoringType::Change)
                     .map(|c| c.txo_idx)
@@ -2037,12 +2147,25 @@ pub trait WalletOffline: WalletBackup {
         };
 
         let consignment_path = match (&kind, batch_transfer.status) {
-            (TransferKind::Send | TransferKind::Inflation | TransferKind::Burn, _) => {
-                Some(self.send_consignment_path(
-                    &asset_transfer.asset_id.clone().unwrap(),
-                    &batch_transfer.txid.clone().unwrap(),
-                ))
+            (TransferKind::Swap, TransferStatus::WaitingCounterparty)
+                if batch_transfer.incoming =>
+            {
+                None
+            }
+            (TransferKind::Swap, _) if batch_transfer.incoming => {
+                Some(self.get_receive_consignment_path(&transfer.recipient_id.clone().unwrap()))
             }
+            (
+                TransferKind::Send
+                | TransferKind::Swap
+                | TransferKind::Inflation
+                | TransferKind::Burn
+                | TransferKind::Consolidation,
+                _,
+            ) => Some(self.send_consignment_path(
+                &asset_transfer.asset_id.clone().unwrap(),
+                &batch_transfer.txid.clone().unwrap(),
+            )),
             (
                 TransferKind::ReceiveBlind | TransferKind::ReceiveWitness,
                 TransferStatus::WaitingCounterparty,
@@ -2057,7 +2180,12 @@ pub trait WalletOffline: WalletBackup {
         .map(|p| p.to_string_lossy().to_string());
 
         let psbt_path = match &kind {
-            TransferKind::Send | TransferKind::Inflation | TransferKind::Burn => batch_transfer
+            TransferKind::Swap if batch_transfer.incoming => None,
+            TransferKind::Send
+            | TransferKind::Swap
+            | TransferKind::Inflation
+            | TransferKind::Burn
+            | TransferKind::Consolidation => batch_transfer
                 .txid
                 .as_ref()
                 .map(|txid| self.get_transfer_dir(txid).join(UNSIGNED_PSBT_FILE))
@@ -2188,7 +2316,8 @@ pub trait WalletOffline: WalletBackup {
                 .for_each(|u| u.rgb_allocations.retain(|a| a.settled));
         }
 
-        let spk_index = self.bdk_wallet().spk_index();
+        let bdk_wallet = self.bdk_wallet();
+        let spk_index = bdk_wallet.spk_index();
         for unspent in unspents.iter_mut() {
             if let Some(((KeychainKind::External, derivation_index), _)) =
                 spk_index.txout(BdkOutPoint::from(unspent.utxo.outpoint.clone()))
@@ -2196,6 +2325,7 @@ pub trait WalletOffline: WalletBackup {
                 unspent.utxo.derivation_index = Some(derivation_index);
             }
         }
+        drop(bdk_wallet);
         let mut internal_unspents: Vec<Unspent> =
             self.internal_unspents().map(Unspent::from).collect();
 
@@ -2589,6 +2719,10 @@ pub trait WalletOffline: WalletBackup {
             .map_err(InternalError::from)?
             .compute_txid()
             .to_string();
+        self.get_transfer_dir_data(txid)
+    }
+
+    fn get_transfer_dir_data(&self, txid: String) -> Result<TransferEndData, Error> {
         let transfer_dir = self.get_transfer_dir(&txid);
         if !transfer_dir.exists() {
             return Err(Error::UnknownTransfer { txid });
@@ -2812,6 +2946,76 @@ pub trait RgbWalletOpsOffline: WalletOffline + WalletBackup {
         Ok(())
     }
 
+    /// Create an incremental backup of the wallet as a file with the provided name and encrypted
+    /// with the provided password.
+    ///
+    /// The backup only includes the wallet files whose content changed since the previous (full or
+    /// incremental) backup, so it needs to be restored with [`restore_backup_chain`] after all the backups it
+    /// builds upon. A previous backup is required.
+    fn backup_incremental(&self, backup_path: &str, password: &str) -> Result<(), Error> {
+        info!(self.logger(), "Backing up incrementally...");
+        self.backup_impl(backup_path, password, None, true)?;
+        info!(self.logger(), "Incremental backup completed");
+        Ok(())
+    }
+
+    /// Push encrypted backups of the wallet to the provided [`BackupTarget`], encrypting them
+    /// with the provided password, replacing any previously set target.
+    ///
+    /// A full backup is pushed right away, then every operation that changes the wallet data
+    /// pushes an incremental one, only including the files changed since the previous push. A
+    /// full backup is pushed again after 20 incremental ones or a failed push. As the operations
+    /// themselves succeed, failed automatic pushes are only logged and
+    /// [`backup_info`](RgbWalletOpsOffline::backup_info) keeps reporting that a backup is
+    /// required. If the stored backup has been replaced by someone else, a push fails and the next
+    /// one overwrites it.
+    ///
+    /// The backup can be restored with [`restore_backup_remote`], providing the wallet master
+    /// fingerprint. Remote backups need to be enabled again every time the wallet is loaded.
+    fn enable_remote_backup(
+        &self,
+        backup_target: Arc<dyn BackupTarget>,
+        password: &str,
+    ) -> Result<(), Error> {
+        info!(self.logger(), "Enabling remote backup...");
+        if !matches!(self.wallet_data().database_type, DatabaseType::Sqlite) {
+            return Err(Error::UnsupportedBackupDatabase);
+        }
+        let remote_backup = Arc::new(RemoteBackup::new(
+            backup_target,
+            &self.internals().database,
+            self.wallet_dir(),
+            self.logger(),
+            password,
+        )?);
+        remote_backup.push()?;
+        self.database().set_remote_backup(Some(remote_backup));
+        info!(self.logger(), "Enable remote backup completed");
+        Ok(())
+    }
+
+    /// Stop pushing backups of the wallet to the remote target.
+    fn disable_remote_backup(&self) {
+        info!(self.logger(), "Disabling remote backup...");
+        self.database().set_remote_backup(None);
+        info!(self.logger(), "Disable remote backup completed");
+    }
+
+    /// Push a backup of the wallet to the remote target set with
+    /// [`enable_remote_backup`](RgbWalletOpsOffline::enable_remote_backup).
+    fn backup_remote(&self) -> Result<(), Error> {
+        info!(self.logger(), "Pushing remote backup...");
+        let remote_backup = self
+            .database()
+            .remote_backup()
+            .ok_or_else(|| Error::RemoteBackup {
+                details: s!("remote backup not enabled"),
+            })?;
+        remote_backup.push()?;
+        info!(self.logger(), "Push remote backup completed");
+        Ok(())
+    }
+
     /// Return whether the wallet requires to perform a backup.
     fn backup_info(&self) -> Result<bool, Error> {
         info!(self.logger(), "Getting backup info...");
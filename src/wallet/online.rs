@@ -17,8 +17,121 @@ pub(crate) const UTXO_NUM: u8 = 5;
 pub(crate) const MIN_BLOCK_ESTIMATION: u16 = 1;
 pub(crate) const MAX_BLOCK_ESTIMATION: u16 = 1008;
 
+// Caps the backoff exponent so the delay computation can't overflow
+const AUTO_REFRESH_MAX_BACKOFF_EXP: u32 = 16;
+
+// Handle to the background auto-refresh task, stopping it when dropped
+pub(crate) struct AutoRefreshHandle {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AutoRefreshHandle {
+    // Start the task on a dedicated thread, which takes ownership of the provided wallet. The
+    // wallet is expected to share its state with the one the user operates on.
+    pub(crate) fn start<W>(wallet: W, online: Online, options: AutoRefreshOptions) -> Self
+    where
+        W: WalletOnline + Send + 'static,
+    {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_stop = stop.clone();
+        let thread = thread::spawn(move || auto_refresh_loop(wallet, online, options, thread_stop));
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    pub(crate) fn stop(&mut self) {
+        let (stopped, cvar) = &*self.stop;
+        *stopped.lock().expect("lock poisoned") = true;
+        cvar.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for AutoRefreshHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn auto_refresh_loop<W: WalletOnline>(
+    mut wallet: W,
+    online: Online,
+    options: AutoRefreshOptions,
+    stop: Arc<(Mutex<bool>, Condvar)>,
+) {
+    let sync_interval = Duration::from_secs(max(options.sync_interval_secs, 1));
+    let refresh_interval = Duration::from_secs(max(options.refresh_interval_secs, 1));
+    let max_backoff = Duration::from_secs(max(options.max_backoff_secs, 1));
+    let start = Instant::now();
+    let mut next_sync = start + sync_interval;
+    let mut next_refresh = start + refresh_interval;
+    let mut failures: u32 = 0;
+    loop {
+        // sleep until the next scheduled run, returning as soon as the task gets stopped
+        let next_run = min(next_sync, next_refresh);
+        let (stopped, cvar) = &*stop;
+        let mut stopped = stopped.lock().expect("lock poisoned");
+        loop {
+            if *stopped {
+                return;
+            }
+            let now = Instant::now();
+            if now >= next_run {
+                break;
+            }
+            stopped = cvar
+                .wait_timeout(stopped, next_run - now)
+                .expect("lock poisoned")
+                .0;
+        }
+        drop(stopped);
+
+        let now = Instant::now();
+        let sync = now >= next_sync;
+        let refresh = now >= next_refresh;
+        match wallet.auto_refresh_run(online, sync, refresh) {
+            Ok(transfers_changed) => {
+                failures = 0;
+                if sync {
+                    next_sync = now + sync_interval;
+                }
+                if refresh {
+                    next_refresh = now + refresh_interval;
+                }
+                wallet
+                    .database()
+                    .dispatch_event(WalletEvent::AutoRefreshCompleted {
+                        synced: sync,
+                        transfers_changed,
+                    });
+            }
+            Err(e) => {
+                failures = failures.saturating_add(1);
+                let factor = 2u32.pow(min(failures, AUTO_REFRESH_MAX_BACKOFF_EXP));
+                if sync {
+                    next_sync = now + min(sync_interval.saturating_mul(factor), max_backoff);
+                }
+                if refresh {
+                    next_refresh = now + min(refresh_interval.saturating_mul(factor), max_backoff);
+                }
+                error!(wallet.logger(), "Auto-refresh failed: {e}");
+                wallet
+                    .database()
+                    .dispatch_event(WalletEvent::AutoRefreshFailed {
+                        details: e.to_string(),
+                    });
+            }
+        }
+    }
+}
+
 pub trait WalletOnline: WalletOffline {
-    fn blockchain_resolver(&self) -> &AnyResolver {
+    fn blockchain_resolver(&self) -> &WitnessResolver {
         &self.online_data().as_ref().unwrap().resolver
     }
 
@@ -44,6 +157,47 @@ pub trait WalletOnline: WalletOffline {
         self.sync_bdk_and_db_txos(txn, options, false)
     }
 
+    fn auto_refresh_run(
+        &mut self,
+        online: Online,
+        sync: bool,
+        refresh: bool,
+    ) -> Result<bool, Error> {
+        debug!(
+            self.logger(),
+            "Auto-refreshing (sync: {sync}, refresh: {refresh})..."
+        );
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        if sync {
+            for keychain in [
+                SyncKeychain::Colored,
+                SyncKeychain::Vanilla {
+                    lookback: self.vanilla_sync_lookback(),
+                },
+            ] {
+                self.sync_impl(
+                    &txn,
+                    SyncOptions {
+                        keychain,
+                        strategy: SyncStrategy::FastSync,
+                    },
+                )?;
+            }
+        }
+        let mut transfers_changed = false;
+        if refresh {
+            let res = self.refresh_impl(&txn, None, vec![], false)?;
+            transfers_changed = res.transfers_changed();
+            if transfers_changed {
+                self.update_backup_info(&txn, false)?;
+            }
+        }
+        txn.commit()?;
+        debug!(self.logger(), "Auto-refresh completed");
+        Ok(transfers_changed)
+    }
+
     fn broadcast_tx(&self, tx: BdkTransaction) -> Result<BdkTransaction, Error> {
         let txid = tx.compute_txid().to_string();
         let indexer = self.indexer();
@@ -54,6 +208,21 @@ pub trait WalletOnline: WalletOffline {
             }
             Err(e) => {
                 match e {
+                    #[cfg(feature = "bitcoind")]
+                    IndexerError::Bitcoind(BitcoindError::Rpc { ref message, .. }) => {
+                        if message.contains("min relay fee not met")
+                            || message.contains("mempool min fee not met")
+                        {
+                            return Err(Error::MinFeeNotMet { txid: txid.clone() });
+                        } else if message.contains("Fee exceeds maximum configured") {
+                            return Err(Error::MaxFeeExceeded { txid: txid.clone() });
+                        }
+                    }
+                    #[cfg(feature = "bitcoind")]
+                    IndexerError::Bitcoind(_) => {}
+                    #[cfg(feature = "cbf")]
+                    IndexerError::Cbf(_) => {}
+                    IndexerError::Quorum(_) => {}
                     #[cfg(feature = "electrum")]
                     IndexerError::Electrum(ref e) => {
                         let err_str = e.to_string();
@@ -97,25 +266,36 @@ pub trait WalletOnline: WalletOffline {
         // apply the broadcast TX into BDK directly so its outputs are immediately visible
         // (revealed change SPKs match without needing a wallet sync)
         let seen_at = now().unix_timestamp() as u64;
-        let (bdk_wallet, bdk_db) = self.bdk_wallet_db_mut();
-        bdk_wallet.apply_unconfirmed_txs([(tx.clone(), seen_at)]);
-        bdk_wallet.persist(bdk_db)?;
+        let mut bdk = self.bdk();
+        bdk.wallet.apply_unconfirmed_txs([(tx.clone(), seen_at)]);
+        bdk.persist()?;
+        drop(bdk);
 
         // promote any newly-known colored UTXOs (e.g. the change output) from
         // exists=false to exists=true in the rgb_lib DB
         self.update_db_colored_txos_from_bdk(txn, false)?;
 
-        for input in tx.clone().input {
+        self.set_inputs_spent(txn, &tx)?;
+
+        Ok(tx)
+    }
+
+    fn set_inputs_spent(&self, txn: &DbTxn, tx: &BdkTransaction) -> Result<(), Error> {
+        for input in &tx.input {
             let txid = input.previous_output.txid.to_string();
             let vout = input.previous_output.vout;
             if let Some(db_txo) = txn.get_txo(&Outpoint { txid, vout })? {
+                if !db_txo.spent {
+                    txn.emit_event(WalletEvent::UtxoSpent {
+                        outpoint: db_txo.outpoint(),
+                    });
+                }
                 let mut db_txo: DbTxoActMod = db_txo.into();
                 db_txo.spent = ActiveValue::Set(true);
                 txn.update_txo(db_txo)?;
             }
         }
-
-        Ok(tx)
+        Ok(())
     }
 
     fn reserve_vanilla_txos(
@@ -123,11 +303,22 @@ pub trait WalletOnline: WalletOffline {
         txn: &DbTxn,
         psbt: &Psbt,
         r#type: WalletTransactionType,
+    ) -> Result<(), Error> {
+        self.reserve_txos_until(txn, psbt, r#type, None)
+    }
+
+    fn reserve_txos_until(
+        &self,
+        txn: &DbTxn,
+        psbt: &Psbt,
+        r#type: WalletTransactionType,
+        expiration: Option<i64>,
     ) -> Result<(), Error> {
         let txid = psbt.unsigned_tx.compute_txid().to_string();
         let wt_idx = txn.set_wallet_transaction(DbWalletTransactionActMod {
             txid: ActiveValue::Set(txid),
             r#type: ActiveValue::Set(r#type),
+            expiration: ActiveValue::Set(expiration),
             ..Default::default()
         })?;
         let reservations: Vec<DbReservedTxoActMod> = psbt
@@ -136,7 +327,7 @@ pub trait WalletOnline: WalletOffline {
             .iter()
             .map(|i| DbReservedTxoActMod {
                 txid: ActiveValue::Set(i.previous_output.txid.to_string()),
-                vout: ActiveValue::Set(i.previous_output.vout),
+                vout: ActiveValue::Set(i.previous_output.vout.into()),
                 reserved_for: ActiveValue::Set(Some(wt_idx)),
                 ..Default::default()
             })
@@ -186,7 +377,12 @@ pub trait WalletOnline: WalletOffline {
         size: u32,
         fee_rate: FeeRate,
     ) -> Result<Psbt, bdk_wallet::error::CreateTxError> {
-        let mut tx_builder = self.bdk_wallet_mut().build_tx();
+        let policy_paths = self.spending_policy_paths();
+        let mut bdk_wallet = self.bdk_wallet_mut();
+        let mut tx_builder = bdk_wallet.build_tx();
+        for (keychain, policy_path) in policy_paths {
+            tx_builder.policy_path(policy_path, keychain);
+        }
         tx_builder
             .add_utxos(inputs)
             .map_err(|_| bdk_wallet::error::CreateTxError::UnknownUtxo)?
@@ -228,7 +424,14 @@ pub trait WalletOnline: WalletOffline {
 
         let mut utxos_to_create = num.unwrap_or(UTXO_NUM);
         if up_to {
-            let allocatable = self.get_available_allocations(unspents, &[], None)?.len();
+            let reserved: Vec<Outpoint> = self
+                .get_reserved_outpoints(txn)?
+                .into_iter()
+                .map(Outpoint::from)
+                .collect();
+            let allocatable = self
+                .get_available_allocations(unspents, &reserved, None)?
+                .len();
             // compare in usize since the count of allocatable UTXOs can exceed u8::MAX
             if allocatable >= utxos_to_create as usize {
                 return Err(Error::AllocationsAlreadyAvailable);
@@ -242,10 +445,8 @@ pub trait WalletOnline: WalletOffline {
             "Will try to create {} UTXOs", utxos_to_create
         );
 
-        let reserved: HashSet<BdkOutPoint> = self
-            .get_reserved_vanilla_outpoints(txn)?
-            .into_iter()
-            .collect();
+        let reserved: HashSet<BdkOutPoint> =
+            self.get_reserved_outpoints(txn)?.into_iter().collect();
         let (inputs, usable_btc_amount) = self.internal_unspents().fold(
             (Vec::new(), 0u64),
             |(mut inputs, usable_btc_amount), u| {
@@ -363,7 +564,12 @@ pub trait WalletOnline: WalletOffline {
 
         let script_pubkey = self.get_script_pubkey(&address)?;
 
-        let mut tx_builder = self.bdk_wallet_mut().build_tx();
+        let policy_paths = self.spending_policy_paths();
+        let mut bdk_wallet = self.bdk_wallet_mut();
+        let mut tx_builder = bdk_wallet.build_tx();
+        for (keychain, policy_path) in policy_paths {
+            tx_builder.policy_path(policy_path, keychain);
+        }
         tx_builder
             .drain_wallet()
             .drain_to(script_pubkey)
@@ -384,6 +590,7 @@ pub trait WalletOnline: WalletOffline {
                 details: e.to_string(),
             },
         })?;
+        drop(bdk_wallet);
 
         if !dry_run {
             self.reserve_vanilla_txos(txn, &psbt, WalletTransactionType::Drain)?;
@@ -440,6 +647,9 @@ pub trait WalletOnline: WalletOffline {
                 Err(Error::MinFeeNotMet { txid: _ }) | Err(Error::MaxFeeExceeded { txid: _ }) => {
                     Ok(None)
                 }
+                Err(Error::ReceivePolicyNotSatisfied { .. }) => {
+                    return Ok(TryFailBatchTransferOutcome::Refreshed);
+                }
                 Err(e) => Err(e),
                 Ok(v) => Ok(v),
             }?;
@@ -589,17 +799,52 @@ pub trait WalletOnline: WalletOffline {
         let id = now().unix_timestamp_nanos() as u64;
         let online = Online { id };
 
-        let (indexer, resolver) =
-            get_indexer_and_resolver(&online_options.indexer_url, self.bitcoin_network())?;
-        indexer.populate_tx_cache(self.bdk_wallet());
+        let network_options = &online_options.network;
+        #[cfg(feature = "cbf")]
+        let primary = match &online_options.cbf {
+            Some(cbf_options) => get_cbf_indexer_and_resolver(
+                cbf_options,
+                self.bitcoin_network(),
+                network_options,
+                self.wallet_dir(),
+            ),
+            None => get_indexer_and_resolver(
+                &online_options.indexer_url,
+                self.bitcoin_network(),
+                network_options,
+            ),
+        };
+        #[cfg(not(feature = "cbf"))]
+        let primary = get_indexer_and_resolver(
+            &online_options.indexer_url,
+            self.bitcoin_network(),
+            network_options,
+        );
+        let (indexer, resolver) = if online_options.fallback_indexer_urls.is_empty()
+            && online_options.indexer_quorum.is_none()
+        {
+            primary?
+        } else {
+            get_multi_indexer_and_resolver(
+                primary,
+                &online_options.fallback_indexer_urls,
+                online_options.indexer_quorum,
+                self.bitcoin_network(),
+                network_options,
+            )?
+        };
+        indexer.populate_tx_cache(&self.bdk_wallet());
 
         let online_data = OnlineData {
             id: online.id,
-            indexer_url: online_options.indexer_url.to_string(),
+            indexer_url: online_options.indexer_id(),
             indexer,
             resolver,
+            network_options: network_options.clone(),
             hub_client: None,
             user_role: None,
+            cosigner_xpub: None,
+            cosigner_keypair: None,
             vanilla_sync_lookback: online_options.vanilla_sync_lookback,
         };
 
@@ -607,13 +852,18 @@ pub trait WalletOnline: WalletOffline {
     }
 
     fn go_online_impl(&mut self, online_options: &OnlineOptions) -> Result<Online, Error> {
-        let indexer_url = &online_options.indexer_url;
+        let indexer_url = online_options.indexer_id();
         let online = if let Some(online_data) = self.online_data().as_ref() {
             let online = Online { id: online_data.id };
-            if online_data.indexer_url != *indexer_url {
+            if online_data.indexer_url != indexer_url
+                || online_data.network_options != online_options.network
+            {
                 let (online, online_data) = self.get_online_data(online_options)?;
                 *self.online_data_mut() = Some(online_data);
-                info!(self.logger(), "Went online with new indexer URL");
+                info!(
+                    self.logger(),
+                    "Went online with new indexer URL or network options"
+                );
                 online
             } else {
                 self.check_online(online)?;
@@ -654,7 +904,9 @@ pub trait WalletOnline: WalletOffline {
         );
 
         if let ReceiveMode::Proxy { proxy_url } = mode {
-            let proxy_client = ProxyClient::new(proxy_url)?;
+            let socks5_proxy = self.socks5_proxy(NetworkService::Proxy, Some(&recipient_id));
+            let proxy_client =
+                ProxyClient::new(proxy_url, socks5_proxy.as_ref(), self.network_policy())?;
             match proxy_client.post_ack(&recipient_id, false) {
                 Ok(r) => {
                     debug!(self.logger(), "Consignment NACK response: {:?}", r);
@@ -677,6 +929,7 @@ pub trait WalletOnline: WalletOffline {
         &self,
         attachments: Vec<Attachment>,
         mode: &ReceiveMode,
+        recipient_id: &str,
     ) -> Result<bool, Error> {
         let provided_map: HashMap<String, String> = match mode {
             ReceiveMode::OutOfBand { media_file_paths } => {
@@ -700,7 +953,9 @@ pub trait WalletOnline: WalletOffline {
             }
             let file_bytes = match mode {
                 ReceiveMode::Proxy { proxy_url } => {
-                    let proxy_client = ProxyClient::new(proxy_url)?;
+                    let socks5_proxy = self.socks5_proxy(NetworkService::Proxy, Some(recipient_id));
+                    let proxy_client =
+                        ProxyClient::new(proxy_url, socks5_proxy.as_ref(), self.network_policy())?;
                     let media_res = proxy_client.get_media(&digest)?;
                     #[cfg(test)]
                     debug!(self.logger(), "Media GET response: {:?}", media_res);
@@ -761,7 +1016,9 @@ pub trait WalletOnline: WalletOffline {
         proxy_url: &str,
         recipient_id: String,
     ) -> Result<GetConsignmentResponse, Error> {
-        let proxy_client = ProxyClient::new(proxy_url)?;
+        let socks5_proxy = self.socks5_proxy(NetworkService::Proxy, Some(&recipient_id));
+        let proxy_client =
+            ProxyClient::new(proxy_url, socks5_proxy.as_ref(), self.network_policy())?;
         let consignment_res = proxy_client.get_consignment(&recipient_id);
         if consignment_res.is_err() || consignment_res.as_ref().unwrap().result.as_ref().is_none() {
             debug!(
@@ -863,7 +1120,12 @@ pub trait WalletOnline: WalletOffline {
         &self,
         reject_list_url: &str,
     ) -> Result<(HashSet<Opout>, HashSet<Opout>), Error> {
-        let reject_list_client = RejectListClient::new(reject_list_url)?;
+        let socks5_proxy = self.socks5_proxy(NetworkService::RejectList, None);
+        let reject_list_client = RejectListClient::new(
+            reject_list_url,
+            socks5_proxy.as_ref(),
+            self.network_policy(),
+        )?;
         let list = reject_list_client.get_reject_list()?;
         let reject_list = list.trim();
         let mut opout_map = HashMap::with_capacity(reject_list.lines().count());
@@ -986,6 +1248,30 @@ pub trait WalletOnline: WalletOffline {
             .cloned()
     }
 
+    // a payment request is satisfied when all of its lines have been paid by the same TX: return
+    // Some(true) if so, Some(false) if it cannot be satisfied anymore, None if still waiting
+    fn payment_request_status(
+        &self,
+        txn: &DbTxn,
+        batch_transfer: &DbBatchTransfer,
+        payment_request_id: &str,
+        txid: &str,
+    ) -> Result<Option<bool>, Error> {
+        let mut all_paid = true;
+        for line in txn.get_batch_transfers_by_payment_request_id(payment_request_id)? {
+            if line.idx == batch_transfer.idx {
+                continue;
+            }
+            match (line.status, &line.txid) {
+                (TransferStatus::Failed, _) => return Ok(Some(false)),
+                (_, Some(line_txid)) if line_txid != txid => return Ok(Some(false)),
+                (_, None) => all_paid = false,
+                _ => {}
+            }
+        }
+        Ok(all_paid.then_some(true))
+    }
+
     fn ack_consignment(
         &self,
         txn: &DbTxn,
@@ -997,6 +1283,38 @@ pub trait WalletOnline: WalletOffline {
     ) -> Result<Option<DbBatchTransfer>, Error> {
         debug!(self.logger(), "ACKing consignment...");
 
+        if let Some(payment_request_id) = &batch_transfer.payment_request_id
+            && matches!(
+                batch_transfer.status,
+                TransferStatus::WaitingCounterparty | TransferStatus::WaitingSafeHeight
+            )
+        {
+            let txid = updated_batch_transfer
+                .txid
+                .clone()
+                .unwrap()
+                .expect("validated batch_transfer must have a txid");
+            match self.payment_request_status(txn, batch_transfer, payment_request_id, &txid)? {
+                Some(true) => {}
+                Some(false) => {
+                    error!(self.logger(), "The payment request cannot be satisfied");
+                    return self.refuse_consignment(
+                        txn,
+                        mode,
+                        recipient_id,
+                        updated_batch_transfer,
+                    );
+                }
+                None => {
+                    debug!(self.logger(), "Waiting for the other payment request lines");
+                    if batch_transfer.txid.is_none() {
+                        txn.update_batch_transfer(updated_batch_transfer)?;
+                    }
+                    return Ok(None);
+                }
+            }
+        }
+
         match self.set_hub_accept_status(batch_transfer.idx)? {
             Some(true) => {}
             Some(false) => return Ok(Some(self.fail_batch_transfer(txn, batch_transfer)?)),
@@ -1004,7 +1322,9 @@ pub trait WalletOnline: WalletOffline {
         }
 
         if let ReceiveMode::Proxy { proxy_url } = mode {
-            let proxy_client = ProxyClient::new(proxy_url)?;
+            let socks5_proxy = self.socks5_proxy(NetworkService::Proxy, Some(&recipient_id));
+            let proxy_client =
+                ProxyClient::new(proxy_url, socks5_proxy.as_ref(), self.network_policy())?;
             match proxy_client.post_ack(&recipient_id, true) {
                 Ok(r) => {
                     debug!(self.logger(), "Consignment ACK response: {:?}", r);
@@ -1053,6 +1373,21 @@ pub trait WalletOnline: WalletOffline {
             .expect("transfer should have a recipient ID");
         debug!(self.logger(), "Recipient ID: {recipient_id}");
 
+        // a validated payment request line waits for the other lines before being ACKed
+        if batch_transfer.txid.is_some() {
+            let consignment_path = self.get_receive_consignment_path(&recipient_id);
+            let valid_consignment_path = self.get_receive_valid_consignment_path(&consignment_path);
+            let valid_consignment =
+                ValidTransfer::load_file(&valid_consignment_path).map_err(InternalError::from)?;
+            return self.ack_validated_consignment(
+                txn,
+                batch_transfer,
+                &transfer,
+                recipient_id,
+                &valid_consignment,
+            );
+        }
+
         if transfer.uses_out_of_band_exchange() {
             debug!(self.logger(), "Skipping consignment exchange out-of-band");
             return Ok(None);
@@ -1221,7 +1556,8 @@ pub trait WalletOnline: WalletOffline {
 
         // validate consignment
         debug!(self.logger(), "Validating consignment...");
-        let safe_height = NonZeroU32::new(self.safe_height(batch_transfer.min_confirmations)?);
+        let safe_height =
+            NonZeroU32::new(self.safe_height(batch_transfer.min_confirmations as u8)?);
         let validation_config = ValidationConfig {
             chain_net: self.chain_net(),
             trusted_typesystem: asset_schema.types(),
@@ -1320,6 +1656,44 @@ pub trait WalletOnline: WalletOffline {
             return self.refuse_consignment(txn, &mode, recipient_id, updated_batch_transfer);
         };
 
+        // check the received amount against the receive policy, payment request lines need to
+        // receive (at least) what they requested
+        let receive_policy = match transfer.receive_policy.unwrap_or_default() {
+            ReceivePolicy::Any if batch_transfer.payment_request_id.is_some() => {
+                ReceivePolicy::AtLeast
+            }
+            receive_policy => receive_policy,
+        };
+        if let Some(requested) = &transfer.requested_assignment
+            && requested.requests_amount()
+        {
+            let mut needed = AssignmentsCollection::default();
+            requested.add_to_assignments(&mut needed);
+            let mut received = AssignmentsCollection::default();
+            receiving
+                .values()
+                .for_each(|a| a.add_to_assignments(&mut received));
+            let satisfied = match receive_policy {
+                ReceivePolicy::Exact => received == needed,
+                ReceivePolicy::AtLeast => received.enough(&needed),
+                ReceivePolicy::Any => true,
+            };
+            if !satisfied {
+                let received = receiving
+                    .values()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let details = format!(
+                    "received {received} but {requested} was requested ({receive_policy} policy)"
+                );
+                error!(self.logger(), "Refusing consignment: {details}");
+                // the transfer has failed, the error only reports why
+                self.refuse_consignment(txn, &mode, recipient_id, updated_batch_transfer)?;
+                return Err(Error::ReceivePolicyNotSatisfied { details });
+            }
+        }
+
         if asset_schema == AssetSchema::Ifa {
             let url = if let Ok(ass) = txn.check_asset_exists(asset_id.clone()) {
                 ass.reject_list_url
@@ -1368,7 +1742,7 @@ pub trait WalletOnline: WalletOffline {
                 let valid_contract = valid_consignment.clone().into_valid_contract();
 
                 let attachments = self.extract_attachments(&valid_contract, asset_schema);
-                if !self.fetch_and_save_attachments(attachments, &mode)? {
+                if !self.fetch_and_save_attachments(attachments, &mode, &recipient_id)? {
                     return self.refuse_consignment(
                         txn,
                         &mode,
@@ -1419,7 +1793,7 @@ pub trait WalletOnline: WalletOffline {
                 txn.update_transfer(&mut updated_transfer)?;
                 let db_utxo = DbTxoActMod {
                     txid: ActiveValue::Set(txid.clone()),
-                    vout: ActiveValue::Set(vout.unwrap()),
+                    vout: ActiveValue::Set(vout.unwrap().into()),
                     btc_amount: ActiveValue::Set(s!("0")),
                     spent: ActiveValue::Set(false),
                     exists: ActiveValue::Set(false),
@@ -1488,14 +1862,29 @@ pub trait WalletOnline: WalletOffline {
             });
         };
 
+        let witness_txid = ab.witness_id().to_string();
         let mut matches = vec![];
         for batch_transfer in &db_data.batch_transfers {
-            // skip non-incoming transfers and transfers that are not waiting for a counterparty
-            if !batch_transfer.incoming
-                || batch_transfer.status != TransferStatus::WaitingCounterparty
-            {
+            // skip non-incoming transfers
+            if !batch_transfer.incoming {
                 continue;
             }
+            // a transfer already waiting for its TX can be updated if the sender replaced it
+            let replaced_txid = if matches!(
+                batch_transfer.status,
+                TransferStatus::WaitingBroadcast | TransferStatus::WaitingConfirmations
+            ) {
+                match &batch_transfer.txid {
+                    Some(txid) if *txid != witness_txid => Some(txid.clone()),
+                    _ => continue,
+                }
+            } else if batch_transfer.status == TransferStatus::WaitingCounterparty
+                && batch_transfer.txid.is_none()
+            {
+                None
+            } else {
+                continue;
+            };
             let (asset_transfer, transfer) = batch_transfer
                 .get_incoming_transfer(&db_data.asset_transfers, &db_data.transfers)?;
             // skip transfers bound to a different asset
@@ -1506,8 +1895,8 @@ pub trait WalletOnline: WalletOffline {
             {
                 continue;
             }
-            // skip transfers that are not set up for out-of-band exchange
-            if !transfer.uses_out_of_band_exchange() {
+            // skip transfers that are not set up for out-of-band exchange, unless replaced
+            if replaced_txid.is_none() && !transfer.uses_out_of_band_exchange() {
                 continue;
             }
 
@@ -1541,12 +1930,22 @@ pub trait WalletOnline: WalletOffline {
                 }
             };
             if let Some((txid, vout)) = matched {
+                // skip transfers whose original TX has already been confirmed
+                if let Some(replaced_txid) = &replaced_txid
+                    && self
+                        .indexer()
+                        .get_tx_confirmations(replaced_txid)?
+                        .is_some_and(|c| c > 0)
+                {
+                    continue;
+                }
                 matches.push((
                     batch_transfer.clone(),
                     asset_transfer.clone(),
                     transfer.clone(),
                     txid,
                     vout,
+                    replaced_txid,
                 ));
             }
         }
@@ -1559,7 +1958,7 @@ pub trait WalletOnline: WalletOffline {
         // a single consignment (for one asset) can pay more than one of this wallet's pending
         // invoices (e.g. a sender batched a send to two of them), so process every matched receive
         let mut results: RefreshResult = HashMap::new();
-        for (batch_transfer, asset_transfer, transfer, txid, vout) in matches {
+        for (batch_transfer, asset_transfer, transfer, txid, vout, replaced_txid) in matches {
             let recipient_id = transfer
                 .recipient_id
                 .clone()
@@ -1574,13 +1973,37 @@ pub trait WalletOnline: WalletOffline {
             let consignment_path = self.get_receive_consignment_path(&recipient_id);
             let transfer_dir = consignment_path.parent().unwrap();
             fs::create_dir_all(transfer_dir)?;
+            let previous_consignment = if replaced_txid.is_some() {
+                Some(fs::read(&consignment_path)?)
+            } else {
+                None
+            };
             fs::copy(consignment_path_in, &consignment_path)?;
 
+            // drop what was received with the replaced TX, it will be re-added upon validation
+            if let Some(replaced_txid) = &replaced_txid {
+                debug!(
+                    self.logger(),
+                    "Replacing TX {replaced_txid} for recipient ID: {recipient_id}"
+                );
+                txn.del_coloring(asset_transfer.idx)?;
+                if let Some(RecipientTypeFull::Witness {
+                    vout: Some(old_vout),
+                }) = transfer.recipient_type
+                    && let Some(old_txo) = txn.get_txo(&Outpoint {
+                        txid: replaced_txid.clone(),
+                        vout: old_vout,
+                    })?
+                {
+                    txn.del_txo(old_txo.idx)?;
+                }
+            }
+
             let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
             let mode = ReceiveMode::OutOfBand {
                 media_file_paths: media_file_paths.clone(),
             };
-            let updated = self.validate_received_consignment(
+            let (updated_status, failure) = match self.validate_received_consignment(
                 txn,
                 &batch_transfer,
                 &asset_transfer,
@@ -1591,13 +2014,29 @@ pub trait WalletOnline: WalletOffline {
                 vout,
                 mode,
                 &mut updated_batch_transfer,
-            )?;
+            ) {
+                Ok(updated) => (updated.map(|t| t.status), None),
+                Err(e @ Error::ReceivePolicyNotSatisfied { .. }) => {
+                    (Some(TransferStatus::Failed), Some(e))
+                }
+                Err(e) => return Err(e),
+            };
+
+            // an invalid replacement must not fail a transfer whose original TX is still valid
+            if let Some(previous_consignment) = previous_consignment
+                && updated_status == Some(TransferStatus::Failed)
+            {
+                fs::write(&consignment_path, previous_consignment)?;
+                return Err(Error::CannotProvideOutOfBandConsignment {
+                    details: s!("the provided replacement consignment is invalid"),
+                });
+            }
 
             results.insert(
                 batch_transfer.idx,
                 RefreshedTransfer {
-                    updated_status: updated.map(|t| t.status),
-                    failure: None,
+                    updated_status,
+                    failure,
                 },
             );
         }
@@ -1631,7 +2070,7 @@ pub trait WalletOnline: WalletOffline {
         let unsafe_txids = Self::collect_unsafe_history_txids(&validation_status.warnings, txid);
 
         if !unsafe_txids.is_empty() {
-            let safe_height = self.safe_height(batch_transfer.min_confirmations)?;
+            let safe_height = self.safe_height(batch_transfer.min_confirmations as u8)?;
             for txid in unsafe_txids {
                 let Some(tx_height) = self.tx_height(txid)? else {
                     return Ok(None);
@@ -1642,6 +2081,28 @@ pub trait WalletOnline: WalletOffline {
             }
         }
 
+        self.ack_validated_consignment(
+            txn,
+            batch_transfer,
+            &transfer,
+            recipient_id,
+            &valid_consignment,
+        )
+    }
+
+    // ACK a consignment that has already been validated and saved
+    fn ack_validated_consignment(
+        &self,
+        txn: &DbTxn,
+        batch_transfer: &DbBatchTransfer,
+        transfer: &DbTransfer,
+        recipient_id: String,
+        valid_consignment: &ValidTransfer,
+    ) -> Result<Option<DbBatchTransfer>, Error> {
+        let txid = batch_transfer
+            .txid
+            .as_deref()
+            .expect("validated batch_transfer must have a txid");
         let witness_id = RgbTxid::from_str(txid).expect("batch transfer txid should be valid");
         // the terminal (paying) bundle should be the last one in the consignment
         let signed_tx = valid_consignment
@@ -1707,7 +2168,9 @@ pub trait WalletOnline: WalletOffline {
                     .clone()
                     .expect("transfer should have a recipient ID");
                 debug!(self.logger(), "Recipient ID: {recipient_id}");
-                let proxy_client = ProxyClient::new(&proxy_url)?;
+                let socks5_proxy = self.socks5_proxy(NetworkService::Proxy, Some(&recipient_id));
+                let proxy_client =
+                    ProxyClient::new(&proxy_url, socks5_proxy.as_ref(), self.network_policy())?;
                 let ack_res = proxy_client.get_ack(&recipient_id)?;
                 debug!(
                     self.logger(),
@@ -1910,6 +2373,37 @@ pub trait WalletOnline: WalletOffline {
         )
     }
 
+    // the CBF client only finds TXs it knows about, so let it watch the witness TX of the
+    // consignment (which might not touch any wallet script, e.g. for blinded receives)
+    #[cfg(feature = "cbf")]
+    fn watch_incoming_witness(
+        &self,
+        batch_transfer: &DbBatchTransfer,
+        db_data: &DbData,
+        txid: &str,
+    ) -> Result<(), Error> {
+        let (_, transfer) =
+            batch_transfer.get_incoming_transfer(&db_data.asset_transfers, &db_data.transfers)?;
+        let recipient_id = transfer
+            .recipient_id
+            .expect("transfer should have a recipient ID");
+        let consignment_path = self.get_receive_consignment_path(&recipient_id);
+        let valid_consignment_path = self.get_receive_valid_consignment_path(&consignment_path);
+        if !valid_consignment_path.exists() {
+            return Ok(());
+        }
+        let valid_consignment =
+            ValidTransfer::load_file(&valid_consignment_path).map_err(InternalError::from)?;
+        if let Some(tx) = valid_consignment
+            .bundled_witnesses()
+            .find(|bw| bw.witness_id().to_string() == txid)
+            .and_then(|bw| bw.pub_witness.tx())
+        {
+            self.blockchain_resolver().watch_tx(tx);
+        }
+        Ok(())
+    }
+
     fn wait_confirmations(
         &mut self,
         txn: &DbTxn,
@@ -1919,6 +2413,22 @@ pub trait WalletOnline: WalletOffline {
         skip_sync: bool,
     ) -> Result<Option<DbBatchTransfer>, Error> {
         debug!(self.logger(), "Waiting confirmations...");
+        let filled_batch_transfer;
+        let batch_transfer = if !incoming
+            && batch_transfer.status == TransferStatus::WaitingBroadcast
+            && batch_transfer.sell_order_id.is_some()
+        {
+            match self.find_sell_order_fill(txn, batch_transfer)? {
+                Some(updated) if updated.failed() => return Ok(Some(updated)),
+                Some(updated) => {
+                    filled_batch_transfer = updated;
+                    &filled_batch_transfer
+                }
+                None => return Ok(None),
+            }
+        } else {
+            batch_transfer
+        };
         let txid = batch_transfer
             .txid
             .clone()
@@ -1927,6 +2437,10 @@ pub trait WalletOnline: WalletOffline {
             self.logger(),
             "Getting details of transaction with ID '{}'...", txid
         );
+        #[cfg(feature = "cbf")]
+        if incoming && matches!(self.indexer(), Indexer::Cbf(_)) {
+            self.watch_incoming_witness(batch_transfer, db_data, &txid)?;
+        }
         let confirmations = self.indexer().get_tx_confirmations(&txid)?;
         debug!(self.logger(), "Confirmations: {:?}", confirmations);
 
@@ -1934,6 +2448,14 @@ pub trait WalletOnline: WalletOffline {
             return Ok(None);
         };
 
+        // the swap maker learns about the swap TX broadcast (by the taker) only from the indexer
+        if !incoming
+            && batch_transfer.status == TransferStatus::WaitingBroadcast
+            && batch_transfer.swap_id.is_some()
+        {
+            self.finalize_swap_maker_send(txn, &txid)?;
+        }
+
         if confirmations < batch_transfer.min_confirmations as u64 {
             if batch_transfer.status == TransferStatus::WaitingBroadcast {
                 let mut updated_batch_transfer: DbBatchTransferActMod =
@@ -2047,19 +2569,21 @@ pub trait WalletOnline: WalletOffline {
                 return Ok(None);
             }
         }
-        match transfer.status {
+        let updated_transfer = match transfer.status {
             TransferStatus::WaitingCounterparty => {
                 if self.get_hub_fail_status(transfer.idx)? {
-                    return Ok(Some(self.fail_batch_transfer(txn, transfer)?));
+                    Some(self.fail_batch_transfer(txn, transfer)?)
+                } else {
+                    self.wait_counterparty(txn, transfer, db_data, incoming)?
                 }
-                self.wait_counterparty(txn, transfer, db_data, incoming)
             }
-            TransferStatus::WaitingSafeHeight => self.wait_safe_height(txn, transfer, db_data),
+            TransferStatus::WaitingSafeHeight => self.wait_safe_height(txn, transfer, db_data)?,
             TransferStatus::WaitingBroadcast | TransferStatus::WaitingConfirmations => {
-                self.wait_confirmations(txn, transfer, db_data, incoming, skip_sync)
+                self.wait_confirmations(txn, transfer, db_data, incoming, skip_sync)?
             }
-            _ => Ok(None),
-        }
+            _ => None,
+        };
+        Ok(updated_transfer)
     }
 
     fn refresh_impl(
@@ -2090,7 +2614,18 @@ pub trait WalletOnline: WalletOffline {
             let mut updated_status = None;
             match self.refresh_transfer(txn, transfer, &db_data, &filter, skip_sync) {
                 Ok(Some(updated_transfer)) => updated_status = Some(updated_transfer.status),
-                Err(e) => failure = Some(e),
+                // the consignment has been refused, so the transfer has failed
+                Err(e @ Error::ReceivePolicyNotSatisfied { .. }) => {
+                    updated_status = Some(TransferStatus::Failed);
+                    failure = Some(e);
+                }
+                Err(e) => {
+                    txn.emit_event(WalletEvent::TransferRefreshFailed {
+                        batch_transfer_idx: transfer.idx,
+                        details: e.to_string(),
+                    });
+                    failure = Some(e)
+                }
                 _ => {}
             }
             refresh_result.insert(
@@ -2105,11 +2640,51 @@ pub trait WalletOnline: WalletOffline {
         Ok(refresh_result)
     }
 
+    fn apply_coin_selection(
+        &self,
+        input_unspents: Vec<LocalUnspent>,
+        coin_selection: &CoinSelectionOptions,
+        asset_ids: &[String],
+    ) -> Result<Vec<LocalUnspent>, Error> {
+        for outpoint in &coin_selection.included_outpoints {
+            if coin_selection.excluded_outpoints.contains(outpoint) {
+                return Err(Error::InvalidCoinSelection {
+                    details: format!("outpoint {outpoint} is both included and excluded"),
+                });
+            }
+            let Some(unspent) = input_unspents
+                .iter()
+                .find(|u| &u.utxo.outpoint() == outpoint)
+            else {
+                return Err(Error::InvalidCoinSelection {
+                    details: format!("outpoint {outpoint} is not available to be spent"),
+                });
+            };
+            if !unspent.rgb_allocations.iter().any(|a| {
+                a.status.settled() && a.asset_id.as_ref().is_some_and(|id| asset_ids.contains(id))
+            }) {
+                return Err(Error::InvalidCoinSelection {
+                    details: format!("outpoint {outpoint} hosts no spendable allocations"),
+                });
+            }
+        }
+        Ok(input_unspents
+            .into_iter()
+            .filter(|u| {
+                !coin_selection
+                    .excluded_outpoints
+                    .contains(&u.utxo.outpoint())
+            })
+            .collect())
+    }
+
     fn select_rgb_inputs(
         &self,
         asset_id: String,
+        batch_asset_ids: &[String],
         assignments_needed: &AssignmentsCollection,
         unspents: Vec<LocalUnspent>,
+        coin_selection: &CoinSelectionOptions,
     ) -> Result<AssetSpend, Error> {
         // sort unspents by the sum of main amounts
         fn cmp_localunspent_allocation_sum(a: &LocalUnspent, b: &LocalUnspent) -> Ordering {
@@ -2156,9 +2731,57 @@ pub trait WalletOnline: WalletOffline {
             mut_unspents.sort_by(cmp_localunspent_allocation_sum);
         }
 
+        // apply the requested strategy to the smallest-first ordering
+        match coin_selection.strategy {
+            CoinSelectionStrategy::SmallestFirst => {}
+            CoinSelectionStrategy::LargestFirst => mut_unspents.reverse(),
+            CoinSelectionStrategy::MinimizeInputs => {
+                if let Some(pos) = mut_unspents.iter().position(|u| {
+                    let mut assignments = AssignmentsCollection::default();
+                    u.rgb_allocations
+                        .iter()
+                        .filter(|a| a.asset_id.as_ref() == Some(&asset_id) && a.status.settled())
+                        .for_each(|a| a.assignment.add_to_assignments(&mut assignments));
+                    assignments.enough(assignments_needed)
+                }) {
+                    let unspent = mut_unspents.remove(pos);
+                    mut_unspents.insert(0, unspent);
+                } else {
+                    mut_unspents.reverse();
+                }
+            }
+            CoinSelectionStrategy::AvoidOtherAssets => {
+                // assets sent in the same batch are not considered as other assets
+                mut_unspents.retain(|u| {
+                    coin_selection
+                        .included_outpoints
+                        .contains(&u.utxo.outpoint())
+                        || !u.rgb_allocations.iter().any(|a| {
+                            !a.status.failed()
+                                && a.asset_id.as_ref() != Some(&asset_id)
+                                && a.asset_id
+                                    .as_ref()
+                                    .is_none_or(|id| !batch_asset_ids.contains(id))
+                        })
+                });
+            }
+            CoinSelectionStrategy::Random => mut_unspents.shuffle(&mut rand::rng()),
+        }
+
+        // included outpoints always come first
+        mut_unspents.sort_by_key(|u| {
+            !coin_selection
+                .included_outpoints
+                .contains(&u.utxo.outpoint())
+        });
+
         let mut assignments_collected = AssignmentsCollection::default();
         let mut input_btc_amt: u64 = 0;
         for unspent in mut_unspents {
+            let included = coin_selection
+                .included_outpoints
+                .contains(&unspent.utxo.outpoint());
+
             // get spendable allocations for the required asset
             let asset_allocations: Vec<LocalRgbAllocation> = unspent
                 .rgb_allocations
@@ -2194,8 +2817,8 @@ pub trait WalletOnline: WalletOffline {
             {
                 needed = true;
             }
-            // skip UTXOs with no needed allocations
-            if !needed {
+            // skip UTXOs with no needed allocations, unless they have been included
+            if !needed && !included {
                 continue;
             }
 
@@ -2214,8 +2837,8 @@ pub trait WalletOnline: WalletOffline {
                 .checked_add(utxo_btc_amt)
                 .expect("total input BTC value cannot exceed u64::MAX");
 
-            // stop as soon as we have the needed assignments
-            if assignments_collected.enough(assignments_needed) {
+            // stop as soon as we have the needed assignments and all included UTXOs
+            if assignments_collected.enough(assignments_needed) && !included {
                 break;
             }
         }
@@ -2242,11 +2865,24 @@ pub trait WalletOnline: WalletOffline {
         input_outpoints: HashSet<BdkOutPoint>,
         witness_recipients: &Vec<(ScriptBuf, u64)>,
         fee_rate: FeeRate,
+        btc_drain: Option<&BtcDrain>,
+        op_return: bool,
     ) -> Result<(Psbt, Option<BtcChange>), Error> {
-        let change_addr = self.get_new_address()?.script_pubkey();
-        let mut builder = self.bdk_wallet_mut().build_tx();
+        // when draining, the remaining bitcoins leave the wallet so there's no BTC change
+        let change_addr = match btc_drain {
+            Some(btc_drain) => btc_drain.script_pubkey.clone(),
+            None => self.get_new_address()?.script_pubkey(),
+        };
+        let policy_paths = self.spending_policy_paths();
+        let mut bdk_wallet = self.bdk_wallet_mut();
+        let mut builder = bdk_wallet.build_tx();
+        for (keychain, policy_path) in policy_paths {
+            builder.policy_path(policy_path, keychain);
+        }
+        if op_return {
+            builder.add_data(&[0; 32]);
+        }
         builder
-            .add_data(&[0; 32])
             .add_utxos(&input_outpoints.into_iter().collect::<Vec<_>>())
             .map_err(InternalError::from)?
             .manually_selected_only()
@@ -2272,7 +2908,11 @@ pub trait WalletOnline: WalletOffline {
                 details: e.to_string(),
             },
         })?;
+        drop(bdk_wallet);
 
+        if btc_drain.is_some() {
+            return Ok((psbt, None));
+        }
         let btc_change = psbt
             .unsigned_tx
             .output
@@ -2293,11 +2933,20 @@ pub trait WalletOnline: WalletOffline {
         all_inputs: &mut HashSet<BdkOutPoint>,
         witness_recipients: &Vec<(ScriptBuf, u64)>,
         fee_rate: FeeRate,
+        btc_drain: Option<&BtcDrain>,
+        op_return: bool,
     ) -> Result<(Psbt, Option<BtcChange>), Error> {
         Ok(loop {
-            break match self.prepare_psbt(all_inputs.clone(), witness_recipients, fee_rate) {
+            break match self.prepare_psbt(
+                all_inputs.clone(),
+                witness_recipients,
+                fee_rate,
+                btc_drain,
+                op_return,
+            ) {
                 Ok(res) => res,
-                Err(Error::InsufficientBitcoins { .. }) => {
+                // when draining all UTXOs are already spent, there's nothing more to add
+                Err(Error::InsufficientBitcoins { .. }) if btc_drain.is_none() => {
                     let used_txos: Vec<Outpoint> =
                         all_inputs.clone().into_iter().map(|o| o.into()).collect();
                     let mut free_utxos = self.get_available_allocations(
@@ -2419,20 +3068,16 @@ pub trait WalletOnline: WalletOffline {
         Ok(to_reject)
     }
 
-    fn prepare_rgb_psbt(
+    fn add_rgb_transitions(
         &self,
         txn: &DbTxn,
         psbt: &mut Psbt,
         transfer_info_map: &mut BTreeMap<String, InfoAssetTransfer>,
-        transfer_dir: PathBuf,
-        donation: bool,
-        unspents: Vec<LocalUnspent>,
+        unspents: &[LocalUnspent],
         runtime: &mut RgbRuntime,
-        min_confirmations: u8,
-        expiration_timestamp: Option<i64>,
-        btc_change: Option<BtcChange>,
-        rejected: &mut HashSet<Opout>,
-    ) -> Result<PrepareRgbPsbtResult, Error> {
+        btc_change: &Option<BtcChange>,
+        rejected: &HashSet<Opout>,
+    ) -> Result<RgbTransitionsData, Error> {
         let mut change_utxo_option = None;
 
         let prev_outputs = psbt
@@ -2556,10 +3201,10 @@ pub trait WalletOnline: WalletOffline {
                 transfer_info.change = change.clone();
                 let seal = self.get_change_seal(
                     txn,
-                    &btc_change,
+                    btc_change,
                     &mut change_utxo_option,
                     &input_outpoints,
-                    unspents.as_slice(),
+                    unspents,
                 )?;
                 if change.fungible > 0 {
                     asset_transition_builder = asset_transition_builder.add_fungible_state(
@@ -2636,10 +3281,10 @@ pub trait WalletOnline: WalletOffline {
                 let assignment = Assignment::from_opout_and_state(opout, &state);
                 let seal = self.get_change_seal(
                     txn,
-                    &btc_change,
+                    btc_change,
                     &mut change_utxo_option,
                     &input_outpoints,
-                    unspents.as_slice(),
+                    unspents,
                 )?;
                 extra_builder = extra_builder
                     .add_input(opout, state.clone())?
@@ -2660,6 +3305,52 @@ pub trait WalletOnline: WalletOffline {
             }
         }
 
+        for (cid, transitions) in &all_transitions {
+            for transition in transitions {
+                for opout in transition.inputs() {
+                    psbt.set_rgb_contract_consumer(*cid, opout, transition.id())
+                        .map_err(InternalError::from)?;
+                }
+            }
+        }
+
+        Ok(RgbTransitionsData {
+            asset_beneficiaries,
+            extra_allocations,
+            input_opouts,
+            change_utxo_option,
+        })
+    }
+
+    fn prepare_rgb_psbt(
+        &self,
+        txn: &DbTxn,
+        psbt: &mut Psbt,
+        transfer_info_map: &mut BTreeMap<String, InfoAssetTransfer>,
+        transfer_dir: PathBuf,
+        donation: bool,
+        unspents: Vec<LocalUnspent>,
+        runtime: &mut RgbRuntime,
+        min_confirmations: u8,
+        expiration_timestamp: Option<i64>,
+        btc_change: Option<BtcChange>,
+        rejected: &mut HashSet<Opout>,
+    ) -> Result<PrepareRgbPsbtResult, Error> {
+        let RgbTransitionsData {
+            asset_beneficiaries,
+            extra_allocations,
+            input_opouts,
+            change_utxo_option,
+        } = self.add_rgb_transitions(
+            txn,
+            psbt,
+            transfer_info_map,
+            &unspents,
+            runtime,
+            &btc_change,
+            rejected,
+        )?;
+
         let opreturn_index = psbt
             .unsigned_tx
             .output
@@ -2676,15 +3367,6 @@ pub trait WalletOnline: WalletOffline {
             .set_mpc_entropy(entropy)
             .map_err(InternalError::from)?;
 
-        for (cid, transitions) in &all_transitions {
-            for transition in transitions {
-                for opout in transition.inputs() {
-                    psbt.set_rgb_contract_consumer(*cid, opout, transition.id())
-                        .map_err(InternalError::from)?;
-                }
-            }
-        }
-
         psbt.set_rgb_close_method(CloseMethod::OpretFirst);
         let fascia = psbt.rgb_commit().map_err(InternalError::from)?;
         fs::create_dir_all(&transfer_dir)?;
@@ -2776,6 +3458,7 @@ pub trait WalletOnline: WalletOffline {
             created_at,
             entropy,
             transfers: transfer_info_map.clone(),
+            replaced_txid: None,
         };
         let serialized_info =
             serde_json::to_string(&info_batch_transfer).map_err(InternalError::from)?;
@@ -2815,10 +3498,16 @@ pub trait WalletOnline: WalletOffline {
                 details: format!("proxy error: {}", err.message),
             });
         }
-        if consignment_res.result.is_none() {
-            return Err(Error::InvalidTransportEndpoint {
-                details: s!("invalid result"),
-            });
+        match consignment_res.result {
+            // a `false` result means the same consignment had already been uploaded (e.g. by a
+            // previous attempt whose reply got lost), which is fine
+            Some(false) => debug!(self.logger(), "Consignment already posted"),
+            Some(true) => {}
+            None => {
+                return Err(Error::InvalidTransportEndpoint {
+                    details: s!("invalid result"),
+                });
+            }
         }
 
         Ok(())
@@ -2861,7 +3550,9 @@ pub trait WalletOnline: WalletOffline {
                 let vout = mock_vout(recipient.local_recipient_data.vout());
                 #[cfg(not(test))]
                 let vout = recipient.local_recipient_data.vout();
-                let proxy_client = ProxyClient::new(&proxy_url)?;
+                let socks5_proxy = self.socks5_proxy(NetworkService::Proxy, Some(recipient_id));
+                let proxy_client =
+                    ProxyClient::new(&proxy_url, socks5_proxy.as_ref(), self.network_policy())?;
                 match self.post_consignment_to_proxy(
                     &proxy_client,
                     recipient_id.clone(),
@@ -2876,14 +3567,26 @@ pub trait WalletOnline: WalletOffline {
                     Ok(()) => {}
                 }
 
+                let mut media_posted = true;
                 for media in &medias {
                     let digest = media.get_digest();
-                    let media_res = proxy_client.post_media(&digest, &media.file_path)?;
+                    let media_res = match proxy_client.post_media(&digest, &media.file_path) {
+                        Ok(media_res) => media_res,
+                        Err(Error::Proxy { details }) => {
+                            warn!(self.logger(), "Failed to post attachment: {details}");
+                            media_posted = false;
+                            break;
+                        }
+                        Err(e) => return Err(e),
+                    };
                     debug!(self.logger(), "Attachment POST response: {:?}", media_res);
                     if let Some(_err) = media_res.error {
                         Err(InternalError::Unexpected)?;
                     }
                 }
+                if !media_posted {
+                    continue;
+                }
 
                 transport_endpoint.used = true;
                 found_valid = true;
@@ -2916,7 +3619,7 @@ pub trait WalletOnline: WalletOffline {
                 None => {
                     let db_utxo = DbTxoActMod {
                         txid: ActiveValue::Set(txid.to_string()),
-                        vout: ActiveValue::Set(btc_change.vout),
+                        vout: ActiveValue::Set(btc_change.vout.into()),
                         btc_amount: ActiveValue::Set(btc_change.amount.to_string()),
                         spent: ActiveValue::Set(false),
                         exists: ActiveValue::Set(false),
@@ -2937,6 +3640,35 @@ pub trait WalletOnline: WalletOffline {
         Ok(idx)
     }
 
+    fn get_or_save_witness_txo(
+        &self,
+        txn: &DbTxn,
+        txid: &str,
+        local_witness_data: &LocalWitnessData,
+    ) -> Result<i32, Error> {
+        let vout = local_witness_data.vout;
+        Ok(
+            match txn.get_txo(&Outpoint {
+                txid: txid.to_string(),
+                vout,
+            })? {
+                Some(txo) => txo.idx,
+                None => {
+                    let db_utxo = DbTxoActMod {
+                        txid: ActiveValue::Set(txid.to_string()),
+                        vout: ActiveValue::Set(vout.into()),
+                        btc_amount: ActiveValue::Set(local_witness_data.amount_sat.to_string()),
+                        spent: ActiveValue::Set(false),
+                        exists: ActiveValue::Set(false),
+                        pending_witness: ActiveValue::Set(false),
+                        ..Default::default()
+                    };
+                    txn.set_txo(db_utxo)?
+                }
+            },
+        )
+    }
+
     fn save_transfers(
         &mut self,
         txn: &DbTxn,
@@ -2949,7 +3681,7 @@ pub trait WalletOnline: WalletOffline {
             status: ActiveValue::Set(status),
             expiration: ActiveValue::Set(info_contents.expiration_timestamp),
             created_at: ActiveValue::Set(info_contents.created_at),
-            min_confirmations: ActiveValue::Set(info_contents.min_confirmations),
+            min_confirmations: ActiveValue::Set(info_contents.min_confirmations.into()),
             incoming: ActiveValue::Set(false),
             ..Default::default()
         };
@@ -3042,26 +3774,8 @@ pub trait WalletOnline: WalletOffline {
                             unreachable!("inflation uses witness recipients")
                         };
                         let vout = local_witness_data.vout;
-                        let txo_idx = match txn.get_txo(&Outpoint {
-                            txid: txid.clone(),
-                            vout,
-                        })? {
-                            Some(txo) => txo.idx,
-                            None => {
-                                let db_utxo = DbTxoActMod {
-                                    txid: ActiveValue::Set(txid.clone()),
-                                    vout: ActiveValue::Set(vout),
-                                    btc_amount: ActiveValue::Set(
-                                        local_witness_data.amount_sat.to_string(),
-                                    ),
-                                    spent: ActiveValue::Set(false),
-                                    exists: ActiveValue::Set(false),
-                                    pending_witness: ActiveValue::Set(false),
-                                    ..Default::default()
-                                };
-                                txn.set_txo(db_utxo)?
-                            }
-                        };
+                        let txo_idx =
+                            self.get_or_save_witness_txo(txn, &txid, &local_witness_data)?;
                         let db_coloring = DbColoringActMod {
                             txo_idx: ActiveValue::Set(txo_idx),
                             asset_transfer_idx: ActiveValue::Set(asset_transfer_idx),
@@ -3081,6 +3795,31 @@ pub trait WalletOnline: WalletOffline {
                         None,
                         Assignment::Fungible(transfer_info.original_assignments_needed.fungible),
                     ),
+                    TypeOfTransition::Transfer if transfer_info.consolidation => {
+                        let local_witness_data = if let LocalRecipientData::Witness(lwd) =
+                            recipient.local_recipient_data
+                        {
+                            lwd
+                        } else {
+                            unreachable!("consolidation uses witness recipients")
+                        };
+                        let vout = local_witness_data.vout;
+                        let txo_idx =
+                            self.get_or_save_witness_txo(txn, &txid, &local_witness_data)?;
+                        let db_coloring = DbColoringActMod {
+                            txo_idx: ActiveValue::Set(txo_idx),
+                            asset_transfer_idx: ActiveValue::Set(asset_transfer_idx),
+                            r#type: ActiveValue::Set(ColoringType::Receive),
+                            assignment: ActiveValue::Set(recipient.assignment.clone()),
+                            ..Default::default()
+                        };
+                        txn.set_coloring(db_coloring)?;
+                        (
+                            Some(recipient.recipient_id.clone()),
+                            Some(RecipientTypeFull::Witness { vout: Some(vout) }),
+                            recipient.assignment,
+                        )
+                    }
                     TypeOfTransition::Transfer => (
                         Some(recipient.recipient_id.clone()),
                         None,
@@ -3224,7 +3963,7 @@ pub trait WalletOnline: WalletOffline {
         let mut input_unspents = unspents.to_vec();
         // consider the following UTXOs unspendable:
         // - incoming and pending
-        // - outgoing and in initiated or waiting counterparty status
+        // - outgoing and in initiated, waiting counterparty or waiting broadcast status
         // - pending incoming witness
         // - pending incoming blinded
         // - inexistent
@@ -3233,7 +3972,10 @@ pub trait WalletOnline: WalletOffline {
                 .iter()
                 .any(|a| a.incoming && a.status.pending()))
                 && !(u.rgb_allocations.iter().any(|a| {
-                    !a.incoming && (a.status.initiated() || a.status.waiting_counterparty())
+                    !a.incoming
+                        && (a.status.initiated()
+                            || a.status.waiting_counterparty()
+                            || a.status == TransferStatus::WaitingBroadcast)
                 }))
                 && !u.utxo.pending_witness
                 && u.pending_blinded == 0
@@ -3242,13 +3984,10 @@ pub trait WalletOnline: WalletOffline {
         Ok(input_unspents)
     }
 
-    fn get_transfer_begin_data(
-        &mut self,
+    fn get_transfer_unspents(
+        &self,
         txn: &DbTxn,
-        fee_rate: u64,
-    ) -> Result<(FeeRate, Vec<LocalUnspent>, Vec<LocalUnspent>, RgbRuntime), Error> {
-        let fee_rate_checked = self.check_fee_rate(fee_rate)?;
-
+    ) -> Result<(Vec<LocalUnspent>, Vec<LocalUnspent>), Error> {
         let db_data = txn.get_db_data(false)?;
 
         let utxos = txn.get_unspent_txos(db_data.txos)?;
@@ -3262,9 +4001,25 @@ pub trait WalletOnline: WalletOffline {
         )?;
 
         #[cfg(test)]
-        let input_unspents = mock_input_unspents(self, &unspents);
+        let mut input_unspents = mock_input_unspents(self, &unspents);
         #[cfg(not(test))]
-        let input_unspents = self.get_input_unspents(&unspents)?;
+        let mut input_unspents = self.get_input_unspents(&unspents)?;
+        // UTXOs reserved by pending transactions cannot be spent
+        let reserved: HashSet<BdkOutPoint> =
+            self.get_reserved_outpoints(txn)?.into_iter().collect();
+        input_unspents.retain(|u| !reserved.contains(&u.utxo.clone().into()));
+
+        Ok((unspents, input_unspents))
+    }
+
+    fn get_transfer_begin_data(
+        &mut self,
+        txn: &DbTxn,
+        fee_rate: u64,
+    ) -> Result<(FeeRate, Vec<LocalUnspent>, Vec<LocalUnspent>, RgbRuntime), Error> {
+        let fee_rate_checked = self.check_fee_rate(fee_rate)?;
+
+        let (unspents, input_unspents) = self.get_transfer_unspents(txn)?;
 
         let runtime = self.rgb_runtime()?;
 
@@ -3301,6 +4056,7 @@ pub trait WalletOnline: WalletOffline {
         expiration_timestamp: Option<i64>,
         runtime: &mut RgbRuntime,
         rejected: &mut HashSet<Opout>,
+        btc_drain: Option<&BtcDrain>,
         dry_run: bool,
     ) -> Result<PrepareTransferPsbtResult, Error> {
         // prepare BDK PSBT
@@ -3313,11 +4069,16 @@ pub trait WalletOnline: WalletOffline {
                     .map(|o| o.clone().into())
             })
             .collect();
+        if let Some(btc_drain) = btc_drain {
+            all_inputs.extend(btc_drain.outpoints.iter().cloned());
+        }
         let (mut psbt, btc_change) = self.try_prepare_psbt(
             input_unspents,
             &mut all_inputs,
             witness_recipients,
             fee_rate_checked,
+            btc_drain,
+            true,
         )?;
         psbt.unsigned_tx.output[0].script_pubkey = ScriptBuf::new_op_return([]);
 
@@ -3396,6 +4157,8 @@ pub trait WalletOnline: WalletOffline {
         fee_rate: u64,
         min_confirmations: u8,
         expiration_timestamp: Option<i64>,
+        coin_selection: CoinSelectionOptions,
+        drain_to: Option<ScriptBuf>,
         dry_run: bool,
     ) -> Result<BeginOperationData, Error> {
         if recipient_map.is_empty() || recipient_map.values().any(|v| v.is_empty()) {
@@ -3404,6 +4167,30 @@ pub trait WalletOnline: WalletOffline {
 
         let (fee_rate_checked, unspents, input_unspents, mut runtime) =
             self.get_transfer_begin_data(txn, fee_rate)?;
+        let asset_ids: Vec<String> = recipient_map.keys().cloned().collect();
+        let input_unspents =
+            self.apply_coin_selection(input_unspents, &coin_selection, &asset_ids)?;
+
+        // when draining, all spendable colored and vanilla UTXOs are spent
+        let btc_drain = match drain_to {
+            Some(script_pubkey) => {
+                let reserved = self.get_reserved_outpoints(txn)?;
+                let mut outpoints: Vec<BdkOutPoint> = input_unspents
+                    .iter()
+                    .map(|u| u.utxo.outpoint().into())
+                    .collect();
+                outpoints.extend(
+                    self.internal_unspents()
+                        .map(|u| u.outpoint)
+                        .filter(|o| !reserved.contains(o)),
+                );
+                Some(BtcDrain {
+                    outpoints,
+                    script_pubkey,
+                })
+            }
+            None => None,
+        };
 
         let chainnet: ChainNet = self.bitcoin_network().into();
         let mut witness_recipients: Vec<(ScriptBuf, u64)> = vec![];
@@ -3459,7 +4246,15 @@ pub trait WalletOnline: WalletOffline {
                             used: false,
                             usable: false,
                         };
-                        if check_proxy(&transport_endpoint.endpoint).is_ok() {
+                        let socks5_proxy =
+                            self.socks5_proxy(NetworkService::Proxy, Some(&recipient.recipient_id));
+                        if check_proxy(
+                            &transport_endpoint.endpoint,
+                            socks5_proxy.as_ref(),
+                            self.network_policy(),
+                        )
+                        .is_ok()
+                        {
                             local_transport_endpoint.usable = true;
                             found_valid = true;
                         }
@@ -3560,8 +4355,10 @@ pub trait WalletOnline: WalletOffline {
                 // if no more assignments this returns an error that makes the loop stop
                 let asset_spend = self.select_rgb_inputs(
                     asset_id.clone(),
+                    &asset_ids,
                     &assignments_needed,
                     input_unspents.clone(),
+                    &coin_selection,
                 )?;
 
                 let transfer_info = InfoAssetTransfer {
@@ -3575,6 +4372,7 @@ pub trait WalletOnline: WalletOffline {
                     main_transition,
                     beneficiaries_blinded: vec![],
                     beneficiaries_witness: vec![],
+                    consolidation: false,
                 };
                 transfer_info_map.insert(asset_id.clone(), transfer_info);
             }
@@ -3592,10 +4390,18 @@ pub trait WalletOnline: WalletOffline {
                 expiration_timestamp,
                 &mut runtime,
                 &mut rejected,
+                btc_drain.as_ref(),
                 dry_run,
             )? {
                 PrepareTransferPsbtResult::Retry => continue,
                 PrepareTransferPsbtResult::Success(begin_operation_data) => {
+                    if btc_drain.is_some() && !dry_run {
+                        self.reserve_vanilla_txos(
+                            txn,
+                            &begin_operation_data.psbt,
+                            WalletTransactionType::Drain,
+                        )?;
+                    }
                     break *begin_operation_data;
                 }
             }
@@ -3691,368 +4497,3250 @@ pub trait WalletOnline: WalletOffline {
         })
     }
 
-    fn send_btc_begin_impl(
+    fn migrate_to_begin_impl(
         &mut self,
         txn: &DbTxn,
+        invoices: Vec<String>,
         address: String,
-        amount: u64,
         fee_rate: u64,
-        skip_sync: bool,
+        min_confirmations: u8,
         dry_run: bool,
-    ) -> Result<Psbt, Error> {
-        let fee_rate_checked = self.check_fee_rate(fee_rate)?;
+    ) -> Result<BeginOperationData, Error> {
+        self.check_fee_rate(fee_rate)?;
+        let script_pubkey = self.get_script_pubkey(&address)?;
 
-        if !skip_sync {
-            self.sync_wallet(
-                txn,
-                SyncOptions {
-                    keychain: SyncKeychain::Vanilla {
-                        lookback: self.vanilla_sync_lookback(),
-                    },
-                    strategy: SyncStrategy::FastSync,
+        self.sync_wallet(
+            txn,
+            SyncOptions {
+                keychain: SyncKeychain::Colored,
+                strategy: SyncStrategy::FastSync,
+            },
+            false,
+        )?;
+        self.sync_wallet(
+            txn,
+            SyncOptions {
+                keychain: SyncKeychain::Vanilla {
+                    lookback: self.vanilla_sync_lookback(),
                 },
-                false,
-            )?;
-        }
-
-        let script_pubkey = self.get_script_pubkey(&address)?;
+                strategy: SyncStrategy::FastSync,
+            },
+            false,
+        )?;
 
-        let unspendable = self.get_unspendable_bdk_outpoints(txn)?;
+        // collect the spendable allocations of each asset
+        let (_, input_unspents) = self.get_transfer_unspents(txn)?;
+        let mut assets_assignments: BTreeMap<String, AssignmentsCollection> = BTreeMap::new();
+        for allocation in input_unspents.iter().flat_map(|u| &u.rgb_allocations) {
+            if let Some(asset_id) = &allocation.asset_id
+                && allocation.status.settled()
+            {
+                allocation
+                    .assignment
+                    .add_to_assignments(assets_assignments.entry(asset_id.clone()).or_default());
+            }
+        }
+        if assets_assignments.is_empty() {
+            return Err(Error::CannotMigrate {
+                details: s!("no spendable allocations to migrate"),
+            });
+        }
 
-        let mut tx_builder = self.bdk_wallet_mut().build_tx();
-        tx_builder
-            .unspendable(unspendable)
-            .add_recipient(script_pubkey, BdkAmount::from_sat(amount))
-            .fee_rate(fee_rate_checked);
-        let psbt = tx_builder.finish().map_err(|e| match e {
-            bdk_wallet::error::CreateTxError::CoinSelection(InsufficientFunds {
-                needed,
-                available,
-            }) => Error::InsufficientBitcoins {
-                needed: needed.to_sat(),
-                available: available.to_sat(),
-            },
-            bdk_wallet::error::CreateTxError::OutputBelowDustLimit(_) => {
-                Error::OutputBelowDustLimit
+        // map each asset to the invoices receiving its main assignments and inflation rights
+        let mut assets_invoices: HashMap<String, (Option<InvoiceData>, Option<InvoiceData>)> =
+            HashMap::new();
+        for invoice in invoices {
+            let invoice_data = Invoice::new(invoice)?.invoice_data;
+            let Some(asset_id) = invoice_data.asset_id.clone() else {
+                return Err(Error::InvalidInvoice {
+                    details: s!("missing asset ID"),
+                });
+            };
+            let entry = assets_invoices.entry(asset_id.clone()).or_default();
+            let slot = if matches!(invoice_data.assignment, Assignment::InflationRight(_)) {
+                &mut entry.1
+            } else {
+                &mut entry.0
+            };
+            if slot.replace(invoice_data).is_some() {
+                return Err(Error::InvalidInvoice {
+                    details: format!("multiple invoices for asset {asset_id}"),
+                });
             }
-            _ => Error::Internal {
-                details: e.to_string(),
-            },
-        })?;
+        }
 
-        if !dry_run {
-            self.reserve_vanilla_txos(txn, &psbt, WalletTransactionType::SendBtc)?;
+        // invoices for assets with nothing to migrate are ignored
+        let mut recipient_map: HashMap<String, Vec<Recipient>> = HashMap::new();
+        let mut expiration_timestamp: Option<i64> = None;
+        for (asset_id, assignments) in assets_assignments {
+            let (main_invoice, inflation_invoice) =
+                assets_invoices.remove(&asset_id).unwrap_or_default();
+            let asset = txn.check_asset_exists(asset_id.clone())?;
+            let main_assignment = match asset.schema {
+                AssetSchema::Uda if assignments.non_fungible => Some(Assignment::NonFungible),
+                AssetSchema::Nia | AssetSchema::Cfa | AssetSchema::Ifa
+                    if assignments.fungible > 0 =>
+                {
+                    Some(Assignment::Fungible(assignments.fungible))
+                }
+                _ => None,
+            };
+            let inflation_assignment = (assignments.inflation > 0)
+                .then_some(Assignment::InflationRight(assignments.inflation));
+            for (assignment, invoice_data, what) in [
+                (main_assignment, main_invoice, "assets"),
+                (inflation_assignment, inflation_invoice, "inflation rights"),
+            ] {
+                let Some(assignment) = assignment else {
+                    continue;
+                };
+                let Some(invoice_data) = invoice_data else {
+                    return Err(Error::CannotMigrate {
+                        details: format!("missing invoice for the {what} of asset {asset_id}"),
+                    });
+                };
+                // invoices are expected to leave the amount open, as all of it gets migrated
+                let amount = |a: &Assignment| match a {
+                    Assignment::Fungible(amt) | Assignment::InflationRight(amt) => *amt,
+                    _ => 0,
+                };
+                let requested = amount(&invoice_data.assignment);
+                if requested != 0 && requested != amount(&assignment) {
+                    return Err(Error::InvalidInvoice {
+                        details: format!(
+                            "invoice for asset {asset_id} requests a different amount"
+                        ),
+                    });
+                }
+                let witness_data = Self::default_witness_data(&invoice_data.recipient_id)?;
+                if let Some(expiration) = invoice_data.expiration_timestamp {
+                    let expiration = expiration as i64;
+                    expiration_timestamp =
+                        Some(expiration_timestamp.map_or(expiration, |e| min(e, expiration)));
+                }
+                recipient_map
+                    .entry(asset_id.clone())
+                    .or_default()
+                    .push(Recipient {
+                        recipient_id: invoice_data.recipient_id,
+                        witness_data,
+                        assignment,
+                        transport_endpoints: invoice_data.transport_endpoints,
+                    });
+            }
         }
 
-        Ok(psbt)
+        self.send_begin_impl(
+            txn,
+            recipient_map,
+            true,
+            fee_rate,
+            min_confirmations,
+            expiration_timestamp,
+            CoinSelectionOptions::default(),
+            Some(script_pubkey),
+            dry_run,
+        )
     }
 
-    fn send_btc_end_impl(&mut self, txn: &DbTxn, signed_psbt: &Psbt) -> Result<String, Error> {
-        let tx = self.broadcast_psbt(txn, signed_psbt)?;
-        self.finalize_vanilla_wallet_transaction(txn, signed_psbt, WalletTransactionType::SendBtc)?;
-        Ok(tx.compute_txid().to_string())
+    // witness recipients are sent the minimum non-dust amount for their script
+    fn default_witness_data(recipient_id: &str) -> Result<Option<WitnessData>, Error> {
+        let xchainnet_beneficiary = XChainNet::<Beneficiary>::from_str(recipient_id)
+            .map_err(|_| Error::InvalidRecipientID)?;
+        Ok(match xchainnet_beneficiary.into_inner() {
+            Beneficiary::BlindedSeal(_) => None,
+            Beneficiary::WitnessVout(pay_2_vout, _) => Some(WitnessData {
+                amount_sat: pay_2_vout.to_script().minimal_non_dust().to_sat(),
+                blinding: None,
+            }),
+        })
     }
 
-    fn inflate_begin_impl(
+    fn migrate_to_end_impl(
         &mut self,
         txn: &DbTxn,
-        asset_id: String,
-        inflation_amounts: Vec<u64>,
+        signed_psbt: &Psbt,
+    ) -> Result<OperationResult, Error> {
+        let res = self.send_end_impl(txn, signed_psbt)?;
+        self.finalize_vanilla_wallet_transaction(txn, signed_psbt, WalletTransactionType::Drain)?;
+        Ok(res)
+    }
+
+    fn send_payment_request_begin_impl(
+        &mut self,
+        txn: &DbTxn,
+        payment_request: String,
+        donation: bool,
         fee_rate: u64,
         min_confirmations: u8,
+        coin_selection: CoinSelectionOptions,
         dry_run: bool,
     ) -> Result<BeginOperationData, Error> {
-        let asset = txn.check_asset_exists(asset_id.clone())?;
-        let schema = asset.schema;
-        self.check_schema_support(&schema)?;
-        if !SCHEMAS_SUPPORTING_INFLATION.contains(&schema) {
-            return Err(Error::UnsupportedInflation {
-                asset_schema: schema,
+        let payment_request_data = PaymentRequest::new(payment_request)?.payment_request_data;
+        if payment_request_data.network != self.bitcoin_network() {
+            return Err(Error::InvalidPaymentRequest {
+                details: s!("payment request for a different network"),
             });
         }
 
-        let known_circulating_supply = asset
-            .known_circulating_supply
-            .unwrap()
-            .parse::<u64>()
-            .unwrap();
-        let inflation =
-            self.get_total_inflation_amount(&inflation_amounts, known_circulating_supply)?;
-        if inflation == 0 {
-            return Err(Error::NoInflationAmounts);
+        // all lines are paid with a single batch transfer
+        let mut recipient_map: HashMap<String, Vec<Recipient>> = HashMap::new();
+        for line in payment_request_data.lines {
+            let asset_id = line
+                .asset_id
+                .expect("payment request lines have an asset ID");
+            recipient_map.entry(asset_id).or_default().push(Recipient {
+                witness_data: Self::default_witness_data(&line.recipient_id)?,
+                recipient_id: line.recipient_id,
+                assignment: line.assignment,
+                transport_endpoints: line.transport_endpoints,
+            });
         }
 
-        let (fee_rate_checked, unspents, input_unspents, mut runtime) =
-            self.get_transfer_begin_data(txn, fee_rate)?;
+        self.send_begin_impl(
+            txn,
+            recipient_map,
+            donation,
+            fee_rate,
+            min_confirmations,
+            payment_request_data.expiration_timestamp.map(|e| e as i64),
+            coin_selection,
+            None,
+            dry_run,
+        )
+    }
 
-        let assignments_needed = AssignmentsCollection {
-            inflation,
-            ..Default::default()
+    fn bump_fee_begin_impl(
+        &mut self,
+        txn: &DbTxn,
+        batch_transfer_idx: i32,
+        fee_rate: u64,
+    ) -> Result<BeginOperationData, Error> {
+        let fee_rate_checked = self.check_fee_rate(fee_rate)?;
+
+        let batch_transfers = txn.iter_batch_transfers()?;
+        let batch_transfer =
+            txn.get_batch_transfer_or_fail(batch_transfer_idx, &batch_transfers)?;
+        if batch_transfer.incoming {
+            return Err(Error::CannotBumpFee {
+                details: s!("the transfer is incoming"),
+            });
+        }
+        if batch_transfer.status != TransferStatus::WaitingConfirmations {
+            return Err(Error::CannotBumpFee {
+                details: s!("the transfer is not waiting for confirmations"),
+            });
+        }
+        let Some(replaced_txid) = batch_transfer.txid.clone() else {
+            return Err(Error::CannotBumpFee {
+                details: s!("the transfer has no TX"),
+            });
         };
-        let asset_spend = self.select_rgb_inputs(
-            asset_id.clone(),
-            &assignments_needed,
-            input_unspents.clone(),
+        if self
+            .indexer()
+            .get_tx_confirmations(&replaced_txid)?
+            .is_some_and(|c| c > 0)
+        {
+            return Err(Error::CannotBumpFee {
+                details: s!("the transfer TX is already confirmed"),
+            });
+        }
+
+        self.sync_wallet(
+            txn,
+            SyncOptions {
+                keychain: SyncKeychain::Vanilla {
+                    lookback: self.vanilla_sync_lookback(),
+                },
+                strategy: SyncStrategy::FastSync,
+            },
+            false,
         )?;
 
-        let chainnet: ChainNet = self.bitcoin_network().into();
-        let amount_sat = asset_spend.input_btc_amt / inflation_amounts.len() as u64;
-        let dust = self
-            .bdk_wallet()
-            .public_descriptor(KeychainKind::External)
-            .dust_value()
-            .to_sat();
-        let amount_sat = max(amount_sat, dust);
-        let mut local_recipients = vec![];
-        let mut witness_recipients: Vec<(ScriptBuf, u64)> = vec![];
-        for (idx, amt) in inflation_amounts.iter().enumerate() {
-            let script_pubkey = self
-                .get_new_addresses(KeychainKind::External, 1)?
-                .script_pubkey();
-            let beneficiary = beneficiary_from_script_buf(script_pubkey.clone());
-            let beneficiary = XChainNet::with(chainnet, beneficiary);
-            let recipient_id = beneficiary.to_string();
-            witness_recipients.push((script_pubkey, amount_sat));
-            let vout = idx as u32 + 1; // start from 1 because of OP_RETURN
-            local_recipients.push(LocalRecipient {
-                recipient_id,
-                local_recipient_data: LocalRecipientData::Witness(LocalWitnessData {
-                    amount_sat,
-                    blinding: None,
-                    vout,
-                }),
-                assignment: Assignment::Fungible(*amt),
-                transport_endpoints: vec![],
-            })
+        let (_, _, mut info_contents, fascia) =
+            self.get_transfer_dir_data(replaced_txid.clone())?;
+
+        // build the replacement TX, keeping the original outputs and only adding vanilla inputs
+        let unspendable = self.get_unspendable_bdk_outpoints(txn)?;
+        let bdk_txid = bdk_wallet::bitcoin::Txid::from_str(&replaced_txid)
+            .expect("batch transfer txid should be valid");
+        let policy_paths = self.spending_policy_paths();
+        let mut bdk_wallet = self.bdk_wallet_mut();
+        let mut tx_builder =
+            bdk_wallet
+                .build_fee_bump(bdk_txid)
+                .map_err(|e| Error::CannotBumpFee {
+                    details: e.to_string(),
+                })?;
+        for (keychain, policy_path) in policy_paths {
+            tx_builder.policy_path(policy_path, keychain);
         }
+        tx_builder
+            .unspendable(unspendable)
+            .fee_rate(fee_rate_checked)
+            .ordering(bdk_wallet::tx_builder::TxOrdering::Untouched);
+        let mut psbt = tx_builder.finish().map_err(|e| match e {
+            bdk_wallet::error::CreateTxError::CoinSelection(InsufficientFunds {
+                needed,
+                available,
+            }) => Error::InsufficientBitcoins {
+                needed: needed.to_sat(),
+                available: available.to_sat(),
+            },
+            bdk_wallet::error::CreateTxError::FeeRateTooLow { required } => Error::InvalidFeeRate {
+                details: format!(
+                    "value under the minimum required for replacement ({})",
+                    required.to_sat_per_vb_ceil()
+                ),
+            },
+            bdk_wallet::error::CreateTxError::FeeTooLow { required } => Error::InvalidFeeRate {
+                details: format!(
+                    "absolute fee under the minimum required for replacement ({} sat)",
+                    required.to_sat()
+                ),
+            },
+            bdk_wallet::error::CreateTxError::OutputBelowDustLimit(_) => {
+                Error::OutputBelowDustLimit
+            }
+            _ => Error::Internal {
+                details: e.to_string(),
+            },
+        })?;
+        let replaced_outputs = bdk_wallet
+            .get_tx(bdk_txid)
+            .map(|t| t.tx_node.tx.output.clone())
+            .ok_or_else(|| Error::CannotBumpFee {
+                details: s!("the transfer TX is unknown to the wallet"),
+            })?;
+        drop(bdk_wallet);
 
-        let contract_id = ContractId::from_str(&asset_id).expect("invalid contract ID");
-        let asset_info = AssetInfo {
-            contract_id,
-            reject_list_url: asset.reject_list_url,
-        };
-        let transfer_info = InfoAssetTransfer {
-            asset_info,
-            recipients: local_recipients.clone(),
-            asset_spend: asset_spend.clone(),
-            change: AssignmentsCollection::default(),
-            original_assignments_needed: assignments_needed.clone(),
-            assignments_needed,
-            assignments_spent: HashMap::new(),
-            main_transition: TypeOfTransition::Inflate,
-            beneficiaries_blinded: vec![],
-            beneficiaries_witness: vec![],
-        };
-        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> =
-            BTreeMap::from([(asset_id.clone(), transfer_info)]);
+        // the RGB seals reference outputs by index, so they must stay untouched
+        let colored_vouts = info_contents
+            .btc_change
+            .iter()
+            .map(|c| c.vout)
+            .chain(
+                info_contents
+                    .transfers
+                    .values()
+                    .flat_map(|t| t.beneficiaries_witness.iter().map(|s| s.vout.into_u32())),
+            )
+            .collect::<HashSet<u32>>();
+        for vout in colored_vouts {
+            let replaced = replaced_outputs.get(vout as usize);
+            let replacement = psbt.unsigned_tx.output.get(vout as usize);
+            match (replaced, replacement) {
+                (Some(old), Some(new)) if old.script_pubkey == new.script_pubkey => {}
+                _ => {
+                    return Err(Error::CannotBumpFee {
+                        details: format!("output {vout} would not be kept by the replacement TX"),
+                    });
+                }
+            }
+        }
+        if let Some(btc_change) = info_contents.btc_change.as_mut() {
+            btc_change.amount = psbt.unsigned_tx.output[btc_change.vout as usize]
+                .value
+                .to_sat();
+        }
 
-        let receive_ids: Vec<String> = local_recipients
+        // commit the same RGB transitions to the replacement TX
+        let Some(opreturn_index) = psbt
+            .unsigned_tx
+            .output
             .iter()
-            .map(|lr| lr.recipient_id.clone())
-            .collect();
-        let transfer_dir = self.setup_transfer_directory(receive_ids)?;
+            .position(|o| o.script_pubkey.is_op_return())
+        else {
+            return Err(Error::CannotBumpFee {
+                details: s!("the transfer TX has no OP_RETURN output"),
+            });
+        };
+        psbt.unsigned_tx.output[opreturn_index].script_pubkey = ScriptBuf::new_op_return([]);
+        for (_, bundle) in fascia.into_bundles() {
+            for known_transition in bundle.known_transitions {
+                psbt.push_rgb_transition(known_transition.transition)
+                    .map_err(InternalError::from)?;
+            }
+        }
+        let opreturn_output =
+            psbt.outputs
+                .get_mut(opreturn_index)
+                .ok_or_else(|| Error::CannotBumpFee {
+                    details: s!("the PSBT has no data for the OP_RETURN output"),
+                })?;
+        opreturn_output.set_opret_host();
+        let entropy = rand::rng().random_range(0..u64::MAX);
+        opreturn_output
+            .set_mpc_entropy(entropy)
+            .map_err(InternalError::from)?;
+        psbt.set_rgb_close_method(CloseMethod::OpretFirst);
+        let fascia = psbt.rgb_commit().map_err(InternalError::from)?;
 
-        let mut rejected = HashSet::new();
-        Ok(
-            match self.prepare_transfer_psbt(
-                txn,
-                &mut transfer_info_map,
-                transfer_dir.clone(),
-                false,
-                unspents,
-                &input_unspents,
-                &witness_recipients,
-                fee_rate_checked,
-                min_confirmations,
-                None,
-                &mut runtime,
-                &mut rejected,
-                dry_run,
-            )? {
-                PrepareTransferPsbtResult::Retry => {
-                    unreachable!("inflate transition has no retry logic")
-                }
-                PrepareTransferPsbtResult::Success(begin_operation_data) => *begin_operation_data,
-            },
-        )
+        // the witness seals now point to the replacement TX
+        let witness_txid = psbt.get_txid();
+        for transfer_info in info_contents.transfers.values_mut() {
+            for seal in transfer_info.beneficiaries_witness.iter_mut() {
+                *seal = ExplicitSeal::with(witness_txid, seal.vout);
+            }
+        }
+        info_contents.entropy = entropy;
+        info_contents.replaced_txid = Some(replaced_txid);
+
+        // save replacement data to a new transfer directory
+        let transfer_dir = self.get_transfer_dir(&witness_txid.to_string());
+        if transfer_dir.exists() {
+            fs::remove_dir_all(&transfer_dir)?;
+        }
+        fs::create_dir_all(&transfer_dir)?;
+        let serialized_fascia = serde_json::to_string(&fascia).map_err(InternalError::from)?;
+        fs::write(transfer_dir.join(FASCIA_FILE), serialized_fascia)?;
+        let serialized_info = serde_json::to_string(&info_contents).map_err(InternalError::from)?;
+        fs::write(transfer_dir.join(TRANSFER_DATA_FILE), serialized_info)?;
+        fs::write(transfer_dir.join(UNSIGNED_PSBT_FILE), psbt.to_string())?;
+
+        Ok(BeginOperationData {
+            psbt,
+            transfer_dir,
+            info_batch_transfer: info_contents,
+            batch_transfer_idx: Some(batch_transfer_idx),
+        })
     }
 
-    fn inflate_end_impl(
+    fn bump_fee_end_impl(
         &mut self,
         txn: &DbTxn,
         signed_psbt: &Psbt,
-    ) -> Result<OperationResult, Error> {
-        let (txid, _transfer_dir, info_contents, fascia) =
+    ) -> Result<BumpFeeResult, Error> {
+        let (txid, transfer_dir, info_contents, mut fascia) =
             self.get_transfer_end_data(signed_psbt)?;
+        let Some(replaced_txid) = info_contents.replaced_txid.clone() else {
+            return Err(Error::CannotBumpFee {
+                details: s!("the provided PSBT doesn't replace a transfer TX"),
+            });
+        };
+        let Some(batch_transfer) = txn
+            .get_batch_transfer_by_txid(&replaced_txid)?
+            .filter(|bt| !bt.incoming && bt.status == TransferStatus::WaitingConfirmations)
+        else {
+            return Err(Error::CannotBumpFee {
+                details: s!("the replaced transfer TX is no longer pending"),
+            });
+        };
 
-        let batch_transfer_idx = self.finalize_transfer_end(
-            txn,
-            txid.clone(),
-            signed_psbt,
-            &info_contents,
-            TransferStatus::WaitingConfirmations,
-            fascia,
-            false,
-        )?;
+        // embed the signed transaction in the consignment so the recipients can find the
+        // replacement TX without waiting for it to be mined
+        let tx = signed_psbt
+            .clone()
+            .extract_tx()
+            .map_err(InternalError::from)?;
+        fascia.update_pub_witness(PubWitness::with(tx));
 
-        let (asset_id, transfer_info) = info_contents.transfers.into_iter().next().unwrap();
-        let inflation = transfer_info.original_assignments_needed.inflation;
-        let db_asset = txn.get_asset(asset_id).unwrap().unwrap();
-        let known_circulating_supply = db_asset
-            .known_circulating_supply
-            .as_ref()
-            .unwrap()
-            .parse::<u64>()
-            .expect("DB should contain a valid known circulating supply");
-        let updated_known_circulating_supply = known_circulating_supply
-            .checked_add(inflation)
-            .expect("known circulating supply plus inflation cannot exceed u64::MAX");
-        let mut updated_asset: DbAssetActMod = db_asset.into();
-        updated_asset.known_circulating_supply =
-            ActiveValue::Set(Some(updated_known_circulating_supply.to_string()));
-        txn.update_asset(&mut updated_asset)?;
+        self.gen_consignments(&fascia, &info_contents.transfers, &transfer_dir)?;
 
-        Ok(OperationResult {
+        let psbt_out = transfer_dir.join(SIGNED_PSBT_FILE);
+        fs::write(psbt_out, signed_psbt.to_string())?;
+
+        // move the TXOs created by the replaced TX to the replacement one, so the broadcast can
+        // mark them as existing
+        for txo in txn
+            .iter_txos()?
+            .into_iter()
+            .filter(|t| t.txid == replaced_txid)
+        {
+            let mut updated_txo: DbTxoActMod = txo.into();
+            updated_txo.txid = ActiveValue::Set(txid.clone());
+            updated_txo.exists = ActiveValue::Set(false);
+            txn.update_txo(updated_txo)?;
+        }
+
+        let mut runtime = self.rgb_runtime()?;
+        self.broadcast_and_update_rgb(txn, &mut runtime, signed_psbt, fascia)?;
+
+        let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
+        updated_batch_transfer.txid = ActiveValue::Set(Some(txid.clone()));
+        txn.update_batch_transfer(&mut updated_batch_transfer)?;
+
+        // the replaced transfer data is not needed anymore
+        let replaced_transfer_dir = self.get_transfer_dir(&replaced_txid);
+        if replaced_transfer_dir.exists() {
+            fs::remove_dir_all(replaced_transfer_dir)?;
+        }
+
+        // the RGB proxy server refuses to change an already posted consignment, so all the
+        // recipients need to receive the updated one out-of-band
+        let recipient_ids_to_notify = info_contents
+            .transfers
+            .values()
+            .filter(|t| t.main_transition == TypeOfTransition::Transfer)
+            .flat_map(|t| t.recipients.iter().map(|r| r.recipient_id.clone()))
+            .collect();
+
+        Ok(BumpFeeResult {
             txid,
-            batch_transfer_idx,
+            replaced_txid,
+            batch_transfer_idx: batch_transfer.idx,
             entropy: info_contents.entropy,
+            recipient_ids_to_notify,
         })
     }
 
-    fn burn_begin_impl(
+    fn send_btc_begin_impl(
         &mut self,
         txn: &DbTxn,
-        asset_id: String,
+        address: String,
         amount: u64,
         fee_rate: u64,
-        min_confirmations: u8,
+        skip_sync: bool,
         dry_run: bool,
-    ) -> Result<BeginOperationData, Error> {
-        let asset = txn.check_asset_exists(asset_id.clone())?;
-        let schema = asset.schema;
-        self.check_schema_support(&schema)?;
-        if !SCHEMAS_SUPPORTING_BURN.contains(&schema) {
-            return Err(Error::UnsupportedBurn {
-                asset_schema: schema,
-            });
-        }
-
-        if amount == 0 {
-            return Err(Error::NoBurnAmount);
-        }
-
-        let (fee_rate_checked, unspents, input_unspents, mut runtime) =
-            self.get_transfer_begin_data(txn, fee_rate)?;
+    ) -> Result<Psbt, Error> {
+        let fee_rate_checked = self.check_fee_rate(fee_rate)?;
 
-        let assignments_needed = AssignmentsCollection {
-            fungible: amount,
+        if !skip_sync {
+            self.sync_wallet(
+                txn,
+                SyncOptions {
+                    keychain: SyncKeychain::Vanilla {
+                        lookback: self.vanilla_sync_lookback(),
+                    },
+                    strategy: SyncStrategy::FastSync,
+                },
+                false,
+            )?;
+        }
+
+        let script_pubkey = self.get_script_pubkey(&address)?;
+
+        let unspendable = self.get_unspendable_bdk_outpoints(txn)?;
+
+        let policy_paths = self.spending_policy_paths();
+        let mut bdk_wallet = self.bdk_wallet_mut();
+        let mut tx_builder = bdk_wallet.build_tx();
+        for (keychain, policy_path) in policy_paths {
+            tx_builder.policy_path(policy_path, keychain);
+        }
+        tx_builder
+            .unspendable(unspendable)
+            .add_recipient(script_pubkey, BdkAmount::from_sat(amount))
+            .fee_rate(fee_rate_checked);
+        let psbt = tx_builder.finish().map_err(|e| match e {
+            bdk_wallet::error::CreateTxError::CoinSelection(InsufficientFunds {
+                needed,
+                available,
+            }) => Error::InsufficientBitcoins {
+                needed: needed.to_sat(),
+                available: available.to_sat(),
+            },
+            bdk_wallet::error::CreateTxError::OutputBelowDustLimit(_) => {
+                Error::OutputBelowDustLimit
+            }
+            _ => Error::Internal {
+                details: e.to_string(),
+            },
+        })?;
+        drop(bdk_wallet);
+
+        if !dry_run {
+            self.reserve_vanilla_txos(txn, &psbt, WalletTransactionType::SendBtc)?;
+        }
+
+        Ok(psbt)
+    }
+
+    fn send_btc_end_impl(&mut self, txn: &DbTxn, signed_psbt: &Psbt) -> Result<String, Error> {
+        let tx = self.broadcast_psbt(txn, signed_psbt)?;
+        self.finalize_vanilla_wallet_transaction(txn, signed_psbt, WalletTransactionType::SendBtc)?;
+        Ok(tx.compute_txid().to_string())
+    }
+
+    fn build_cpfp_psbt(
+        &mut self,
+        outpoint: BdkOutPoint,
+        unspendable: Vec<BdkOutPoint>,
+        change_script: ScriptBuf,
+        fee_rate: FeeRate,
+        fee_absolute: Option<BdkAmount>,
+    ) -> Result<Psbt, Error> {
+        let policy_paths = self.spending_policy_paths();
+        let mut bdk_wallet = self.bdk_wallet_mut();
+        let mut tx_builder = bdk_wallet.build_tx();
+        for (keychain, policy_path) in policy_paths {
+            tx_builder.policy_path(policy_path, keychain);
+        }
+        tx_builder
+            .add_utxo(outpoint)
+            .map_err(|e| Error::CannotCpfp {
+                details: e.to_string(),
+            })?
+            .unspendable(unspendable)
+            .drain_to(change_script);
+        if let Some(fee) = fee_absolute {
+            tx_builder.fee_absolute(fee);
+        } else {
+            tx_builder.fee_rate(fee_rate);
+        }
+        tx_builder.finish().map_err(|e| match e {
+            bdk_wallet::error::CreateTxError::CoinSelection(InsufficientFunds {
+                needed,
+                available,
+            }) => Error::InsufficientBitcoins {
+                needed: needed.to_sat(),
+                available: available.to_sat(),
+            },
+            bdk_wallet::error::CreateTxError::OutputBelowDustLimit(_) => {
+                Error::OutputBelowDustLimit
+            }
+            _ => Error::Internal {
+                details: e.to_string(),
+            },
+        })
+    }
+
+    fn accept_unsettled_incoming(
+        &self,
+        txn: &DbTxn,
+        runtime: &mut RgbRuntime,
+        outpoints: &[Outpoint],
+    ) -> Result<(), Error> {
+        let db_data = txn.get_db_data(false)?;
+        let txo_idxs: HashSet<i32> = db_data
+            .txos
+            .iter()
+            .filter(|t| outpoints.contains(&t.outpoint()))
+            .map(|t| t.idx)
+            .collect();
+        for coloring in db_data
+            .colorings
+            .iter()
+            .filter(|c| txo_idxs.contains(&c.txo_idx))
+        {
+            let asset_transfer = db_data
+                .asset_transfers
+                .iter()
+                .find(|at| at.idx == coloring.asset_transfer_idx)
+                .ok_or(InternalError::Unexpected)?;
+            let batch_transfer = db_data
+                .batch_transfers
+                .iter()
+                .find(|bt| bt.idx == asset_transfer.batch_transfer_idx)
+                .ok_or(InternalError::Unexpected)?;
+            if !batch_transfer.incoming || !batch_transfer.waiting_confirmations() {
+                continue;
+            }
+            let (_, transfer) = batch_transfer
+                .get_incoming_transfer(&db_data.asset_transfers, &db_data.transfers)?;
+            let recipient_id = transfer.recipient_id.ok_or(InternalError::Unexpected)?;
+            let consignment_path = self.get_receive_consignment_path(&recipient_id);
+            let valid_consignment_path = self.get_receive_valid_consignment_path(&consignment_path);
+            let valid_consignment =
+                ValidTransfer::load_file(&valid_consignment_path).map_err(InternalError::from)?;
+            runtime.accept_transfer(valid_consignment, self.blockchain_resolver())?;
+        }
+        Ok(())
+    }
+
+    fn cpfp_colored_begin(
+        &mut self,
+        txn: &DbTxn,
+        anchor: LocalUnspent,
+        unspents: Vec<LocalUnspent>,
+        input_unspents: Vec<LocalUnspent>,
+        fee_rate: FeeRate,
+        parent_deficit: BdkAmount,
+        dry_run: bool,
+    ) -> Result<Psbt, Error> {
+        // allocations can be re-assigned only once the transfer that produced them has been
+        // broadcast and, if incoming, its consignment has been validated
+        let allocations: Vec<&LocalRgbAllocation> = anchor
+            .rgb_allocations
+            .iter()
+            .filter(|a| !a.status.failed())
+            .collect();
+        if allocations.is_empty()
+            || allocations
+                .iter()
+                .any(|a| !a.status.waiting_confirmations() && !a.status.settled())
+        {
+            return Err(Error::CannotCpfp {
+                details: s!("the TX output holding RGB allocations cannot be spent yet"),
+            });
+        }
+
+        // incoming allocations are known to the RGB runtime only once settled, so the validated
+        // consignments are accepted in a volatile runtime, leaving the stock untouched until the
+        // child TX gets broadcast
+        let mut runtime = load_volatile_rgb_runtime(self.wallet_dir())?;
+        self.accept_unsettled_incoming(txn, &mut runtime, &[anchor.utxo.outpoint()])?;
+
+        let mut assets: BTreeMap<String, AssignmentsCollection> = BTreeMap::new();
+        for allocation in allocations {
+            if let Some(asset_id) = &allocation.asset_id {
+                allocation
+                    .assignment
+                    .add_to_assignments(assets.entry(asset_id.clone()).or_default());
+            }
+        }
+
+        // all the allocations are re-assigned to a new UTXO, like in a consolidation
+        let anchor_outpoint = anchor.utxo.outpoint();
+        let anchor_btc_amt =
+            anchor
+                .utxo
+                .btc_amount
+                .parse::<u64>()
+                .map_err(|e| Error::Internal {
+                    details: e.to_string(),
+                })?;
+        let dust = self
+            .bdk_wallet()
+            .public_descriptor(KeychainKind::External)
+            .dust_value()
+            .to_sat();
+        let amount_sat = max(anchor_btc_amt, dust);
+        let script_pubkey = self
+            .get_new_addresses(KeychainKind::External, 1)?
+            .script_pubkey();
+        let chainnet: ChainNet = self.bitcoin_network().into();
+        let beneficiary = beneficiary_from_script_buf(script_pubkey.clone());
+        let recipient_id = XChainNet::with(chainnet, beneficiary).to_string();
+        let witness_recipients = vec![(script_pubkey, amount_sat)];
+        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> = BTreeMap::new();
+        for (asset_id, assignments_collected) in assets {
+            // inflation rights are moved to the change as extra allocations
+            let assignment = if assignments_collected.fungible > 0 {
+                Assignment::Fungible(assignments_collected.fungible)
+            } else if assignments_collected.non_fungible {
+                Assignment::NonFungible
+            } else {
+                continue;
+            };
+            let asset = txn.check_asset_exists(asset_id.clone())?;
+            self.check_schema_support(&asset.schema)?;
+            let assignments_needed = AssignmentsCollection {
+                fungible: assignments_collected.fungible,
+                non_fungible: assignments_collected.non_fungible,
+                ..Default::default()
+            };
+            let local_recipient = LocalRecipient {
+                recipient_id: recipient_id.clone(),
+                local_recipient_data: LocalRecipientData::Witness(LocalWitnessData {
+                    amount_sat,
+                    blinding: None,
+                    vout: 1, // 0 is the OP_RETURN
+                }),
+                assignment,
+                transport_endpoints: vec![],
+            };
+            let contract_id = ContractId::from_str(&asset.id).expect("invalid contract ID");
+            let transfer_info = InfoAssetTransfer {
+                asset_info: AssetInfo {
+                    contract_id,
+                    reject_list_url: asset.reject_list_url,
+                },
+                recipients: vec![local_recipient],
+                asset_spend: AssetSpend {
+                    input_outpoints: vec![anchor_outpoint.clone()],
+                    assignments_collected,
+                    input_btc_amt: anchor_btc_amt,
+                },
+                change: AssignmentsCollection::default(),
+                original_assignments_needed: assignments_needed.clone(),
+                assignments_needed,
+                assignments_spent: HashMap::new(),
+                main_transition: TypeOfTransition::Transfer,
+                beneficiaries_blinded: vec![],
+                beneficiaries_witness: vec![],
+                consolidation: true,
+            };
+            transfer_info_map.insert(asset_id, transfer_info);
+        }
+        if transfer_info_map.is_empty() {
+            return Err(Error::CannotCpfp {
+                details: s!("the TX output holds no transferable RGB allocations"),
+            });
+        }
+
+        // the child pays for its own weight at the requested rate plus the parent's deficit, so
+        // its fee rate is raised proportionally
+        let mut all_inputs = HashSet::from([anchor_outpoint.into()]);
+        let (psbt, _) = self.try_prepare_psbt(
+            &input_unspents,
+            &mut all_inputs,
+            &witness_recipients,
+            fee_rate,
+            None,
+            true,
+        )?;
+        let child_fee = psbt
+            .fee()
+            .map_err(|e| Error::Internal {
+                details: e.to_string(),
+            })?
+            .to_sat();
+        let Some(package_fee_rate) = fee_rate
+            .to_sat_per_kwu()
+            .checked_mul(child_fee + parent_deficit.to_sat())
+            .map(|f| FeeRate::from_sat_per_kwu(f.div_ceil(child_fee.max(1))))
+        else {
+            return Err(Error::InvalidFeeRate {
+                details: s!("value overflows"),
+            });
+        };
+
+        let transfer_dir = self.setup_transfer_directory(vec![recipient_id])?;
+        let mut rejected = HashSet::new();
+        match self.prepare_transfer_psbt(
+            txn,
+            &mut transfer_info_map,
+            transfer_dir,
+            false,
+            unspents,
+            &input_unspents,
+            &witness_recipients,
+            package_fee_rate,
+            1,
+            None,
+            &mut runtime,
+            &mut rejected,
+            None,
+            dry_run,
+        )? {
+            PrepareTransferPsbtResult::Retry => Err(Error::CannotCpfp {
+                details: s!("some allocations are on the reject list"),
+            }),
+            PrepareTransferPsbtResult::Success(begin_operation_data) => {
+                Ok(begin_operation_data.psbt)
+            }
+        }
+    }
+
+    fn cpfp_begin_impl(
+        &mut self,
+        txn: &DbTxn,
+        txid: String,
+        fee_rate: u64,
+        dry_run: bool,
+    ) -> Result<Psbt, Error> {
+        let fee_rate_checked = self.check_fee_rate(fee_rate)?;
+
+        self.sync_wallet(
+            txn,
+            SyncOptions {
+                keychain: SyncKeychain::Colored,
+                strategy: SyncStrategy::FastSync,
+            },
+            false,
+        )?;
+        self.sync_wallet(
+            txn,
+            SyncOptions {
+                keychain: SyncKeychain::Vanilla {
+                    lookback: self.vanilla_sync_lookback(),
+                },
+                strategy: SyncStrategy::FastSync,
+            },
+            false,
+        )?;
+
+        let parent_txid =
+            bdk_wallet::bitcoin::Txid::from_str(&txid).map_err(|_| Error::InvalidTxid)?;
+        let bdk_wallet = self.bdk_wallet();
+        let Some(wallet_tx) = bdk_wallet.get_tx(parent_txid) else {
+            return Err(Error::CannotCpfp {
+                details: s!("the TX is unknown to the wallet"),
+            });
+        };
+        if wallet_tx.chain_position.is_confirmed() {
+            return Err(Error::CannotCpfp {
+                details: s!("the TX is already confirmed"),
+            });
+        }
+        let parent_tx = wallet_tx.tx_node.tx.clone();
+        drop(bdk_wallet);
+
+        // spending an output that holds RGB allocations with a vanilla TX would destroy them, so
+        // outputs without allocations (e.g. BTC change) are preferred as anchor of the child TX,
+        // otherwise a colored child re-assigning the allocations is built
+        let (unspents, input_unspents) = self.get_transfer_unspents(txn)?;
+        let reserved = self.get_reserved_outpoints(txn)?;
+        let mut anchor: Option<(BdkOutPoint, BdkAmount)> = None;
+        let mut colored_anchor: Option<LocalUnspent> = None;
+        for (vout, output) in parent_tx.output.iter().enumerate() {
+            let outpoint = BdkOutPoint::new(parent_txid, vout as u32);
+            if self.bdk_wallet().get_utxo(outpoint).is_none() || reserved.contains(&outpoint) {
+                continue;
+            }
+            if let Some(unspent) = unspents
+                .iter()
+                .find(|u| BdkOutPoint::from(u.utxo.outpoint()) == outpoint)
+                && (unspent.utxo.pending_witness
+                    || unspent.rgb_allocations.iter().any(|a| !a.status.failed()))
+            {
+                if colored_anchor.is_none() {
+                    colored_anchor = Some(unspent.clone());
+                }
+                continue;
+            }
+            if anchor.is_none_or(|(_, value)| output.value > value) {
+                anchor = Some((outpoint, output.value));
+            }
+        }
+
+        let parent_fee =
+            self.bdk_wallet()
+                .calculate_fee(&parent_tx)
+                .map_err(|e| Error::Internal {
+                    details: e.to_string(),
+                })?;
+        let parent_target_fee = fee_rate_checked
+            .fee_wu(parent_tx.weight())
+            .expect("fee should not overflow");
+        if parent_fee >= parent_target_fee {
+            return Err(Error::CannotCpfp {
+                details: s!("the TX already pays the requested fee rate"),
+            });
+        }
+        let parent_deficit = parent_target_fee - parent_fee;
+
+        let anchor_outpoint = match (anchor, colored_anchor) {
+            (Some((anchor_outpoint, _)), _) => anchor_outpoint,
+            (None, Some(colored_anchor)) => {
+                return self.cpfp_colored_begin(
+                    txn,
+                    colored_anchor,
+                    unspents,
+                    input_unspents,
+                    fee_rate_checked,
+                    parent_deficit,
+                    dry_run,
+                );
+            }
+            (None, None) => {
+                return Err(Error::CannotCpfp {
+                    details: s!("the TX has no spendable output"),
+                });
+            }
+        };
+
+        // the child pays for its own weight at the requested rate plus the parent's deficit
+        let unspendable = self.get_unspendable_bdk_outpoints(txn)?;
+        let change_script = self
+            .get_new_addresses(KeychainKind::Internal, 1)?
+            .script_pubkey();
+        let psbt = self.build_cpfp_psbt(
+            anchor_outpoint,
+            unspendable.clone(),
+            change_script.clone(),
+            fee_rate_checked,
+            None,
+        )?;
+        let child_fee = psbt.fee().map_err(|e| Error::Internal {
+            details: e.to_string(),
+        })?;
+        let psbt = self.build_cpfp_psbt(
+            anchor_outpoint,
+            unspendable,
+            change_script,
+            fee_rate_checked,
+            Some(child_fee + parent_deficit),
+        )?;
+
+        if !dry_run {
+            self.reserve_vanilla_txos(txn, &psbt, WalletTransactionType::Cpfp)?;
+        }
+
+        Ok(psbt)
+    }
+
+    fn get_cpfp_transfer_txid(
+        &self,
+        txn: &DbTxn,
+        batch_transfer_idx: i32,
+    ) -> Result<String, Error> {
+        let batch_transfers = txn.iter_batch_transfers()?;
+        let batch_transfer =
+            txn.get_batch_transfer_or_fail(batch_transfer_idx, &batch_transfers)?;
+        batch_transfer
+            .txid
+            .clone()
+            .ok_or_else(|| Error::CannotCpfp {
+                details: s!("the transfer has no TX"),
+            })
+    }
+
+    fn cpfp_end_impl(&mut self, txn: &DbTxn, signed_psbt: &Psbt) -> Result<String, Error> {
+        // a colored child has its transfer data saved like any other transfer
+        let txid = signed_psbt.unsigned_tx.compute_txid().to_string();
+        if self.get_transfer_dir(&txid).exists() {
+            let (txid, _, info_contents, fascia) = self.get_transfer_end_data(signed_psbt)?;
+            // the unsettled incoming allocations being spent enter the stock only now that the
+            // child TX has been broadcast
+            let mut runtime = self.rgb_runtime()?;
+            self.broadcast_psbt(txn, signed_psbt)?;
+            let input_outpoints: Vec<Outpoint> = info_contents
+                .transfers
+                .values()
+                .flat_map(|t| t.asset_spend.input_outpoints.clone())
+                .collect();
+            self.accept_unsettled_incoming(txn, &mut runtime, &input_outpoints)?;
+            runtime.consume_fascia(fascia, None)?;
+            drop(runtime);
+            self.update_or_save_transfers(
+                txn,
+                txid.clone(),
+                &info_contents,
+                TransferStatus::WaitingConfirmations,
+                false,
+            )?;
+            return Ok(txid);
+        }
+        let tx = self.broadcast_psbt(txn, signed_psbt)?;
+        self.finalize_vanilla_wallet_transaction(txn, signed_psbt, WalletTransactionType::Cpfp)?;
+        Ok(tx.compute_txid().to_string())
+    }
+
+    fn inflate_begin_impl(
+        &mut self,
+        txn: &DbTxn,
+        asset_id: String,
+        inflation_amounts: Vec<u64>,
+        fee_rate: u64,
+        min_confirmations: u8,
+        coin_selection: CoinSelectionOptions,
+        dry_run: bool,
+    ) -> Result<BeginOperationData, Error> {
+        let asset = txn.check_asset_exists(asset_id.clone())?;
+        let schema = asset.schema;
+        self.check_schema_support(&schema)?;
+        if !SCHEMAS_SUPPORTING_INFLATION.contains(&schema) {
+            return Err(Error::UnsupportedInflation {
+                asset_schema: schema,
+            });
+        }
+
+        let known_circulating_supply = asset
+            .known_circulating_supply
+            .unwrap()
+            .parse::<u64>()
+            .unwrap();
+        let inflation =
+            self.get_total_inflation_amount(&inflation_amounts, known_circulating_supply)?;
+        if inflation == 0 {
+            return Err(Error::NoInflationAmounts);
+        }
+
+        let (fee_rate_checked, unspents, input_unspents, mut runtime) =
+            self.get_transfer_begin_data(txn, fee_rate)?;
+        let input_unspents = self.apply_coin_selection(
+            input_unspents,
+            &coin_selection,
+            std::slice::from_ref(&asset_id),
+        )?;
+
+        let assignments_needed = AssignmentsCollection {
+            inflation,
+            ..Default::default()
+        };
+        let asset_spend = self.select_rgb_inputs(
+            asset_id.clone(),
+            std::slice::from_ref(&asset_id),
+            &assignments_needed,
+            input_unspents.clone(),
+            &coin_selection,
+        )?;
+
+        let chainnet: ChainNet = self.bitcoin_network().into();
+        let amount_sat = asset_spend.input_btc_amt / inflation_amounts.len() as u64;
+        let dust = self
+            .bdk_wallet()
+            .public_descriptor(KeychainKind::External)
+            .dust_value()
+            .to_sat();
+        let amount_sat = max(amount_sat, dust);
+        let mut local_recipients = vec![];
+        let mut witness_recipients: Vec<(ScriptBuf, u64)> = vec![];
+        for (idx, amt) in inflation_amounts.iter().enumerate() {
+            let script_pubkey = self
+                .get_new_addresses(KeychainKind::External, 1)?
+                .script_pubkey();
+            let beneficiary = beneficiary_from_script_buf(script_pubkey.clone());
+            let beneficiary = XChainNet::with(chainnet, beneficiary);
+            let recipient_id = beneficiary.to_string();
+            witness_recipients.push((script_pubkey, amount_sat));
+            let vout = idx as u32 + 1; // start from 1 because of OP_RETURN
+            local_recipients.push(LocalRecipient {
+                recipient_id,
+                local_recipient_data: LocalRecipientData::Witness(LocalWitnessData {
+                    amount_sat,
+                    blinding: None,
+                    vout,
+                }),
+                assignment: Assignment::Fungible(*amt),
+                transport_endpoints: vec![],
+            })
+        }
+
+        let contract_id = ContractId::from_str(&asset_id).expect("invalid contract ID");
+        let asset_info = AssetInfo {
+            contract_id,
+            reject_list_url: asset.reject_list_url,
+        };
+        let transfer_info = InfoAssetTransfer {
+            asset_info,
+            recipients: local_recipients.clone(),
+            asset_spend: asset_spend.clone(),
+            change: AssignmentsCollection::default(),
+            original_assignments_needed: assignments_needed.clone(),
+            assignments_needed,
+            assignments_spent: HashMap::new(),
+            main_transition: TypeOfTransition::Inflate,
+            beneficiaries_blinded: vec![],
+            beneficiaries_witness: vec![],
+            consolidation: false,
+        };
+        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> =
+            BTreeMap::from([(asset_id.clone(), transfer_info)]);
+
+        let receive_ids: Vec<String> = local_recipients
+            .iter()
+            .map(|lr| lr.recipient_id.clone())
+            .collect();
+        let transfer_dir = self.setup_transfer_directory(receive_ids)?;
+
+        let mut rejected = HashSet::new();
+        Ok(
+            match self.prepare_transfer_psbt(
+                txn,
+                &mut transfer_info_map,
+                transfer_dir.clone(),
+                false,
+                unspents,
+                &input_unspents,
+                &witness_recipients,
+                fee_rate_checked,
+                min_confirmations,
+                None,
+                &mut runtime,
+                &mut rejected,
+                None,
+                dry_run,
+            )? {
+                PrepareTransferPsbtResult::Retry => {
+                    unreachable!("inflate transition has no retry logic")
+                }
+                PrepareTransferPsbtResult::Success(begin_operation_data) => *begin_operation_data,
+            },
+        )
+    }
+
+    fn inflate_end_impl(
+        &mut self,
+        txn: &DbTxn,
+        signed_psbt: &Psbt,
+    ) -> Result<OperationResult, Error> {
+        let (txid, _transfer_dir, info_contents, fascia) =
+            self.get_transfer_end_data(signed_psbt)?;
+
+        let batch_transfer_idx = self.finalize_transfer_end(
+            txn,
+            txid.clone(),
+            signed_psbt,
+            &info_contents,
+            TransferStatus::WaitingConfirmations,
+            fascia,
+            false,
+        )?;
+
+        let (asset_id, transfer_info) = info_contents.transfers.into_iter().next().unwrap();
+        let inflation = transfer_info.original_assignments_needed.inflation;
+        let db_asset = txn.get_asset(asset_id).unwrap().unwrap();
+        let known_circulating_supply = db_asset
+            .known_circulating_supply
+            .as_ref()
+            .unwrap()
+            .parse::<u64>()
+            .expect("DB should contain a valid known circulating supply");
+        let updated_known_circulating_supply = known_circulating_supply
+            .checked_add(inflation)
+            .expect("known circulating supply plus inflation cannot exceed u64::MAX");
+        let mut updated_asset: DbAssetActMod = db_asset.into();
+        updated_asset.known_circulating_supply =
+            ActiveValue::Set(Some(updated_known_circulating_supply.to_string()));
+        txn.update_asset(&mut updated_asset)?;
+
+        Ok(OperationResult {
+            txid,
+            batch_transfer_idx,
+            entropy: info_contents.entropy,
+        })
+    }
+
+    fn burn_begin_impl(
+        &mut self,
+        txn: &DbTxn,
+        asset_id: String,
+        amount: u64,
+        fee_rate: u64,
+        min_confirmations: u8,
+        coin_selection: CoinSelectionOptions,
+        dry_run: bool,
+    ) -> Result<BeginOperationData, Error> {
+        let asset = txn.check_asset_exists(asset_id.clone())?;
+        let schema = asset.schema;
+        self.check_schema_support(&schema)?;
+        if !SCHEMAS_SUPPORTING_BURN.contains(&schema) {
+            return Err(Error::UnsupportedBurn {
+                asset_schema: schema,
+            });
+        }
+
+        if amount == 0 {
+            return Err(Error::NoBurnAmount);
+        }
+
+        let (fee_rate_checked, unspents, input_unspents, mut runtime) =
+            self.get_transfer_begin_data(txn, fee_rate)?;
+        let input_unspents = self.apply_coin_selection(
+            input_unspents,
+            &coin_selection,
+            std::slice::from_ref(&asset_id),
+        )?;
+
+        let assignments_needed = AssignmentsCollection {
+            fungible: amount,
             ..Default::default()
         };
         let asset_spend = self.select_rgb_inputs(
             asset_id.clone(),
+            std::slice::from_ref(&asset_id),
+            &assignments_needed,
+            input_unspents.clone(),
+            &coin_selection,
+        )?;
+
+        let chainnet: ChainNet = self.bitcoin_network().into();
+        let script_pubkey = self
+            .get_new_addresses(KeychainKind::External, 1)?
+            .script_pubkey();
+        let dust = self
+            .bdk_wallet()
+            .public_descriptor(KeychainKind::External)
+            .dust_value()
+            .to_sat();
+        let witness_recipients: Vec<(ScriptBuf, u64)> = vec![(script_pubkey.clone(), dust)];
+        let beneficiary = beneficiary_from_script_buf(script_pubkey.clone());
+        let beneficiary = XChainNet::with(chainnet, beneficiary);
+        let recipient_id = beneficiary.to_string();
+        let local_recipients = vec![LocalRecipient {
+            recipient_id,
+            local_recipient_data: LocalRecipientData::Witness(LocalWitnessData {
+                amount_sat: dust,
+                blinding: None,
+                vout: 1,
+            }),
+            assignment: Assignment::Fungible(0),
+            transport_endpoints: vec![],
+        }];
+
+        let contract_id = ContractId::from_str(&asset_id).expect("invalid contract ID");
+        let asset_info = AssetInfo {
+            contract_id,
+            reject_list_url: asset.reject_list_url,
+        };
+        let transfer_info = InfoAssetTransfer {
+            asset_info,
+            recipients: local_recipients.clone(),
+            asset_spend: asset_spend.clone(),
+            change: AssignmentsCollection::default(),
+            original_assignments_needed: assignments_needed.clone(),
+            assignments_needed,
+            assignments_spent: HashMap::new(),
+            main_transition: TypeOfTransition::Burn,
+            beneficiaries_blinded: vec![],
+            beneficiaries_witness: vec![],
+            consolidation: false,
+        };
+        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> =
+            BTreeMap::from([(asset_id.clone(), transfer_info)]);
+
+        let receive_ids: Vec<String> = local_recipients
+            .iter()
+            .map(|lr| lr.recipient_id.clone())
+            .collect();
+        let transfer_dir = self.setup_transfer_directory(receive_ids)?;
+
+        let mut rejected = HashSet::new();
+        Ok(
+            match self.prepare_transfer_psbt(
+                txn,
+                &mut transfer_info_map,
+                transfer_dir.clone(),
+                false,
+                unspents,
+                &input_unspents,
+                &witness_recipients,
+                fee_rate_checked,
+                min_confirmations,
+                None,
+                &mut runtime,
+                &mut rejected,
+                None,
+                dry_run,
+            )? {
+                PrepareTransferPsbtResult::Retry => {
+                    unreachable!("burn transition has no retry logic")
+                }
+                PrepareTransferPsbtResult::Success(begin_operation_data) => *begin_operation_data,
+            },
+        )
+    }
+
+    fn burn_end_impl(&mut self, txn: &DbTxn, signed_psbt: &Psbt) -> Result<OperationResult, Error> {
+        let (txid, _transfer_dir, info_contents, fascia) =
+            self.get_transfer_end_data(signed_psbt)?;
+
+        let batch_transfer_idx = self.finalize_transfer_end(
+            txn,
+            txid.clone(),
+            signed_psbt,
+            &info_contents,
+            TransferStatus::WaitingConfirmations,
+            fascia,
+            false,
+        )?;
+
+        Ok(OperationResult {
+            txid,
+            batch_transfer_idx,
+            entropy: info_contents.entropy,
+        })
+    }
+
+    fn consolidate_allocations_begin_impl(
+        &mut self,
+        txn: &DbTxn,
+        asset_id: Option<String>,
+        fee_rate: u64,
+        min_confirmations: u8,
+        dry_run: bool,
+    ) -> Result<BeginOperationData, Error> {
+        if let Some(asset_id) = &asset_id {
+            let asset = txn.check_asset_exists(asset_id.clone())?;
+            self.check_schema_support(&asset.schema)?;
+        }
+
+        let (fee_rate_checked, unspents, input_unspents, mut runtime) =
+            self.get_transfer_begin_data(txn, fee_rate)?;
+
+        // count the spendable fungible allocations of each asset
+        let mut allocation_counts: BTreeMap<String, usize> = BTreeMap::new();
+        for allocation in input_unspents.iter().flat_map(|u| &u.rgb_allocations) {
+            if let Some(id) = &allocation.asset_id
+                && allocation.status.settled()
+                && matches!(allocation.assignment, Assignment::Fungible(_))
+                && asset_id.as_ref().is_none_or(|a| a == id)
+            {
+                *allocation_counts.entry(id.clone()).or_default() += 1;
+            }
+        }
+        // only assets with more than one allocation can be consolidated
+        let asset_ids: Vec<String> = allocation_counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(id, _)| id)
+            .collect();
+        if asset_ids.is_empty() {
+            return Err(Error::CannotConsolidate {
+                details: s!("no asset has more than one spendable allocation"),
+            });
+        }
+
+        let mut assets_data = vec![];
+        let mut input_outpoints: HashSet<Outpoint> = HashSet::new();
+        for asset_id in &asset_ids {
+            let asset = txn.check_asset_exists(asset_id.clone())?;
+            self.check_schema_support(&asset.schema)?;
+            let fungible: u64 = input_unspents
+                .iter()
+                .flat_map(|u| &u.rgb_allocations)
+                .filter(|a| a.asset_id.as_ref() == Some(asset_id) && a.status.settled())
+                .map(|a| match a.assignment {
+                    Assignment::Fungible(amt) => amt,
+                    _ => 0,
+                })
+                .sum();
+            let assignments_needed = AssignmentsCollection {
+                fungible,
+                ..Default::default()
+            };
+            let asset_spend = self.select_rgb_inputs(
+                asset_id.clone(),
+                &asset_ids,
+                &assignments_needed,
+                input_unspents.clone(),
+                &CoinSelectionOptions::default(),
+            )?;
+            input_outpoints.extend(asset_spend.input_outpoints.iter().cloned());
+            assets_data.push((asset, assignments_needed, asset_spend));
+        }
+
+        // each output receives up to max_allocations_per_utxo consolidated allocations
+        let allocations_per_output = self.max_allocations_per_utxo() as usize;
+        let num_outputs = assets_data.len().div_ceil(allocations_per_output);
+        let input_btc_amt: u64 = input_unspents
+            .iter()
+            .filter(|u| input_outpoints.contains(&u.utxo.outpoint()))
+            .map(|u| {
+                u.utxo
+                    .btc_amount
+                    .parse::<u64>()
+                    .expect("DB should contain a valid BTC amount")
+            })
+            .sum();
+        let dust = self
+            .bdk_wallet()
+            .public_descriptor(KeychainKind::External)
+            .dust_value()
+            .to_sat();
+        let amount_sat = max(input_btc_amt / num_outputs as u64, dust);
+
+        let chainnet: ChainNet = self.bitcoin_network().into();
+        let mut witness_recipients: Vec<(ScriptBuf, u64)> = vec![];
+        let mut receive_ids: Vec<String> = vec![];
+        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> = BTreeMap::new();
+        for (idx, chunk) in assets_data.chunks(allocations_per_output).enumerate() {
+            let script_pubkey = self
+                .get_new_addresses(KeychainKind::External, 1)?
+                .script_pubkey();
+            let beneficiary = beneficiary_from_script_buf(script_pubkey.clone());
+            let beneficiary = XChainNet::with(chainnet, beneficiary);
+            let recipient_id = beneficiary.to_string();
+            witness_recipients.push((script_pubkey, amount_sat));
+            receive_ids.push(recipient_id.clone());
+            let vout = idx as u32 + 1; // start from 1 because of OP_RETURN
+            for (asset, assignments_needed, asset_spend) in chunk {
+                let local_recipient = LocalRecipient {
+                    recipient_id: recipient_id.clone(),
+                    local_recipient_data: LocalRecipientData::Witness(LocalWitnessData {
+                        amount_sat,
+                        blinding: None,
+                        vout,
+                    }),
+                    assignment: Assignment::Fungible(assignments_needed.fungible),
+                    transport_endpoints: vec![],
+                };
+                let contract_id = ContractId::from_str(&asset.id).expect("invalid contract ID");
+                let asset_info = AssetInfo {
+                    contract_id,
+                    reject_list_url: asset.reject_list_url.clone(),
+                };
+                let transfer_info = InfoAssetTransfer {
+                    asset_info,
+                    recipients: vec![local_recipient],
+                    asset_spend: asset_spend.clone(),
+                    change: AssignmentsCollection::default(),
+                    original_assignments_needed: assignments_needed.clone(),
+                    assignments_needed: assignments_needed.clone(),
+                    assignments_spent: HashMap::new(),
+                    main_transition: TypeOfTransition::Transfer,
+                    beneficiaries_blinded: vec![],
+                    beneficiaries_witness: vec![],
+                    consolidation: true,
+                };
+                transfer_info_map.insert(asset.id.clone(), transfer_info);
+            }
+        }
+
+        let transfer_dir = self.setup_transfer_directory(receive_ids)?;
+
+        let mut rejected = HashSet::new();
+        Ok(
+            match self.prepare_transfer_psbt(
+                txn,
+                &mut transfer_info_map,
+                transfer_dir.clone(),
+                false,
+                unspents,
+                &input_unspents,
+                &witness_recipients,
+                fee_rate_checked,
+                min_confirmations,
+                None,
+                &mut runtime,
+                &mut rejected,
+                None,
+                dry_run,
+            )? {
+                PrepareTransferPsbtResult::Retry => {
+                    return Err(Error::CannotConsolidate {
+                        details: s!("some allocations are on the reject list"),
+                    });
+                }
+                PrepareTransferPsbtResult::Success(begin_operation_data) => *begin_operation_data,
+            },
+        )
+    }
+
+    fn consolidate_allocations_end_impl(
+        &mut self,
+        txn: &DbTxn,
+        signed_psbt: &Psbt,
+    ) -> Result<OperationResult, Error> {
+        let (txid, _transfer_dir, info_contents, fascia) =
+            self.get_transfer_end_data(signed_psbt)?;
+
+        let batch_transfer_idx = self.finalize_transfer_end(
+            txn,
+            txid.clone(),
+            signed_psbt,
+            &info_contents,
+            TransferStatus::WaitingConfirmations,
+            fascia,
+            false,
+        )?;
+
+        Ok(OperationResult {
+            txid,
+            batch_transfer_idx,
+            entropy: info_contents.entropy,
+        })
+    }
+
+    fn check_swap_assignment(
+        assignment: &Assignment,
+        schema: Option<AssetSchema>,
+    ) -> Result<(), Error> {
+        match (assignment, schema) {
+            (Assignment::Fungible(0), _) => Err(Error::InvalidAmountZero),
+            (
+                Assignment::Fungible(_),
+                Some(AssetSchema::Nia | AssetSchema::Cfa | AssetSchema::Ifa) | None,
+            )
+            | (Assignment::NonFungible, Some(AssetSchema::Uda) | None) => Ok(()),
+            _ => Err(Error::InvalidAssignment),
+        }
+    }
+
+    fn check_swap_offer(&self, swap_offer_data: &SwapOfferData) -> Result<(), Error> {
+        if swap_offer_data.network != self.bitcoin_network() {
+            return Err(Error::InvalidSwapOffer {
+                details: s!("swap offer for a different network"),
+            });
+        }
+        if swap_offer_data.expiration_timestamp as i64 <= now().unix_timestamp() {
+            return Err(Error::InvalidSwapOffer {
+                details: s!("the swap offer has expired"),
+            });
+        }
+        Ok(())
+    }
+
+    // each contract needs to be spent by a single party, so UTXOs also holding the counterparty's
+    // assets are never selected as swap inputs
+    fn get_swap_input_unspents(
+        input_unspents: Vec<LocalUnspent>,
+        counterparty_asset_ids: &HashSet<String>,
+    ) -> Vec<LocalUnspent> {
+        input_unspents
+            .into_iter()
+            .filter(|u| {
+                !u.rgb_allocations.iter().any(|a| {
+                    a.asset_id
+                        .as_ref()
+                        .is_some_and(|aid| counterparty_asset_ids.contains(aid))
+                })
+            })
+            .collect()
+    }
+
+    fn create_counterparty_receive(
+        &mut self,
+        txn: &DbTxn,
+        asset_id: &str,
+        assignment: &Assignment,
+        expiration_timestamp: u64,
+        min_confirmations: u8,
+    ) -> Result<(ReceiveDataInternal, DbBatchTransfer), Error> {
+        // an unknown asset is imported upon validation of the counterparty's consignment
+        let known_asset = txn.check_asset_exists(asset_id.to_string()).is_ok();
+        let receive_assignment = match assignment {
+            Assignment::NonFungible if !known_asset => Assignment::Any,
+            _ => assignment.clone(),
+        };
+        let receive_data_internal = self.create_receive_data(
+            txn,
+            known_asset.then(|| asset_id.to_string()),
+            receive_assignment,
+            expiration_timestamp as i64,
+            vec![],
+            RecipientType::Witness,
+        )?;
+        let batch_transfer_idx = self.store_receive_transfer(
+            txn,
+            &receive_data_internal,
+            ReceivePolicy::Exact,
+            min_confirmations,
+        )?;
+
+        // the counterparty needs to pay exactly what has been agreed
+        let db_data = txn.get_db_data(false)?;
+        let batch_transfer =
+            txn.get_batch_transfer_or_fail(batch_transfer_idx, &db_data.batch_transfers)?;
+        let (_, transfer) =
+            batch_transfer.get_incoming_transfer(&db_data.asset_transfers, &db_data.transfers)?;
+        let mut updated_transfer: DbTransferActMod = transfer.into();
+        updated_transfer.requested_assignment = ActiveValue::Set(Some(assignment.clone()));
+        txn.update_transfer(&mut updated_transfer)?;
+
+        Ok((receive_data_internal, batch_transfer))
+    }
+
+    fn set_swap_id(
+        &self,
+        txn: &DbTxn,
+        batch_transfer: DbBatchTransfer,
+        swap_id: &str,
+    ) -> Result<(), Error> {
+        let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.into();
+        updated_batch_transfer.swap_id = ActiveValue::Set(Some(swap_id.to_string()));
+        txn.update_batch_transfer(&mut updated_batch_transfer)?;
+        Ok(())
+    }
+
+    fn get_swap_batch_transfer(
+        &self,
+        txn: &DbTxn,
+        swap_id: &str,
+        incoming: bool,
+    ) -> Result<DbBatchTransfer, Error> {
+        txn.get_batch_transfers_by_swap_id(swap_id)?
+            .into_iter()
+            .find(|bt| bt.incoming == incoming)
+            .ok_or_else(|| Error::InvalidSwapOffer {
+                details: s!("cannot find the transfers of the swap"),
+            })
+    }
+
+    fn set_swap_transfer_status(
+        &self,
+        txn: &DbTxn,
+        swap_id: &str,
+        incoming: bool,
+        status: TransferStatus,
+    ) -> Result<(), Error> {
+        let batch_transfer = self.get_swap_batch_transfer(txn, swap_id, incoming)?;
+        self.set_batch_transfer_status(txn, batch_transfer, status)
+    }
+
+    fn set_batch_transfer_status(
+        &self,
+        txn: &DbTxn,
+        batch_transfer: DbBatchTransfer,
+        status: TransferStatus,
+    ) -> Result<(), Error> {
+        if batch_transfer.status == status {
+            return Ok(());
+        }
+        let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
+        updated_batch_transfer.status = ActiveValue::Set(status);
+        txn.update_batch_transfer(&mut updated_batch_transfer)?;
+        Ok(())
+    }
+
+    fn read_swap_offer(&self, transfer_dir: &Path) -> Result<SwapOffer, Error> {
+        let swap_offer_path = transfer_dir.join(SWAP_OFFER_FILE);
+        if !swap_offer_path.exists() {
+            return Err(Error::InvalidSwapOffer {
+                details: s!("unknown swap offer"),
+            });
+        }
+        SwapOffer::new(fs::read_to_string(swap_offer_path)?)
+    }
+
+    fn write_swap_offer(&self, transfer_dir: &Path, swap_offer: &SwapOffer) -> Result<(), Error> {
+        fs::write(
+            transfer_dir.join(SWAP_OFFER_FILE),
+            swap_offer.swap_offer_string(),
+        )?;
+        Ok(())
+    }
+
+    fn read_send_consignment(&self, transfer_dir: &Path, asset_id: &str) -> Result<String, Error> {
+        let asset_transfer_dir = self.get_asset_transfer_dir(transfer_dir, asset_id);
+        let consignment = fs::read(self.get_send_consignment_path_impl(asset_transfer_dir))?;
+        Ok(general_purpose::STANDARD.encode(consignment))
+    }
+
+    // the swap fascia also contains the counterparty's bundles, which spend allocations unknown to
+    // this wallet, so only the bundles of the contracts spent by this wallet are consumed
+    fn consume_swap_fascia(&self, fascia: Fascia, info: &InfoBatchTransfer) -> Result<(), Error> {
+        let own_contracts: HashSet<&String> = info
+            .transfers
+            .keys()
+            .chain(info.extra_allocations.keys())
+            .collect();
+        let seal_witness = fascia.seal_witness().clone();
+        let bundles: BTreeMap<ContractId, TransitionBundle> = fascia
+            .into_bundles()
+            .into_iter()
+            .filter(|(cid, _)| own_contracts.contains(&cid.to_string()))
+            .collect();
+        if bundles.is_empty() {
+            return Ok(());
+        }
+        let fascia = Fascia::new(
+            seal_witness,
+            Confined::try_from(bundles).map_err(InternalError::from)?,
+        );
+        self.rgb_runtime()?.consume_fascia(fascia, None)?;
+        Ok(())
+    }
+
+    fn finalize_swap_maker_send(&self, txn: &DbTxn, txid: &str) -> Result<(), Error> {
+        let (_, transfer_dir, info_contents, fascia) =
+            self.get_transfer_dir_data(txid.to_string())?;
+        self.consume_swap_fascia(fascia, &info_contents)?;
+        let psbt_str = fs::read_to_string(transfer_dir.join(UNSIGNED_PSBT_FILE))?;
+        let psbt = Psbt::from_str(&psbt_str)?;
+        self.set_inputs_spent(txn, &psbt.unsigned_tx)
+    }
+
+    fn swap_offer_create_impl(
+        &mut self,
+        txn: &DbTxn,
+        asset_id: String,
+        assignment: Assignment,
+        taker_leg: SwapLeg,
+        expiration_timestamp: u64,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<SwapOffer, Error> {
+        if expiration_timestamp as i64 <= now().unix_timestamp() {
+            return Err(Error::InvalidExpiration);
+        }
+        let asset = txn.check_asset_exists(asset_id.clone())?;
+        self.check_schema_support(&asset.schema)?;
+        Self::check_swap_assignment(&assignment, Some(asset.schema))?;
+        let mut counterparty_asset_ids = HashSet::new();
+        match &taker_leg {
+            SwapLeg::Asset {
+                asset_id: taker_asset_id,
+                assignment: taker_assignment,
+            } => {
+                if *taker_asset_id == asset_id {
+                    return Err(Error::InvalidSwapOffer {
+                        details: s!("the two legs of the swap need to be different assets"),
+                    });
+                }
+                if ContractId::from_str(taker_asset_id).is_err() {
+                    return Err(Error::InvalidSwapOffer {
+                        details: format!("invalid asset ID '{taker_asset_id}'"),
+                    });
+                }
+                let taker_schema = txn
+                    .check_asset_exists(taker_asset_id.clone())
+                    .ok()
+                    .map(|a| a.schema);
+                Self::check_swap_assignment(taker_assignment, taker_schema)?;
+                counterparty_asset_ids.insert(taker_asset_id.clone());
+            }
+            SwapLeg::Bitcoin { amount } => {
+                if *amount == 0 {
+                    return Err(Error::InvalidAmountZero);
+                }
+            }
+        }
+
+        // prepare what the taker needs to pay
+        let mut receive_batch_transfer = None;
+        let mut witness_recipients: Vec<(ScriptBuf, u64)> = vec![];
+        let (maker_vout, maker_recipient_id, maker_address) = match &taker_leg {
+            SwapLeg::Asset {
+                asset_id: taker_asset_id,
+                assignment: taker_assignment,
+            } => {
+                let (receive_data_internal, batch_transfer) = self.create_counterparty_receive(
+                    txn,
+                    taker_asset_id,
+                    taker_assignment,
+                    expiration_timestamp,
+                    min_confirmations,
+                )?;
+                receive_batch_transfer = Some(batch_transfer);
+                let script_pubkey = receive_data_internal
+                    .script_pubkey
+                    .expect("witness receive should have a script pubkey");
+                let amount_sat = script_pubkey.minimal_non_dust().to_sat();
+                witness_recipients.push((script_pubkey, amount_sat));
+                // output 0 is the OP_RETURN
+                (Some(1), Some(receive_data_internal.recipient_id), None)
+            }
+            SwapLeg::Bitcoin { .. } => {
+                let address = self.get_new_addresses(KeychainKind::Internal, 1)?;
+                (None, None, Some(address.to_string()))
+            }
+        };
+
+        let (fee_rate_checked, unspents, input_unspents, mut runtime) =
+            self.get_transfer_begin_data(txn, fee_rate)?;
+        let input_unspents = Self::get_swap_input_unspents(input_unspents, &counterparty_asset_ids);
+        let mut assignments_needed = AssignmentsCollection::default();
+        assignment.add_to_assignments(&mut assignments_needed);
+        let asset_spend = self.select_rgb_inputs(
+            asset_id.clone(),
+            std::slice::from_ref(&asset_id),
+            &assignments_needed,
+            input_unspents.clone(),
+            &CoinSelectionOptions::default(),
+        )?;
+
+        let mut all_inputs: HashSet<BdkOutPoint> = asset_spend
+            .input_outpoints
+            .iter()
+            .map(|o| o.clone().into())
+            .collect();
+        let (mut psbt, btc_change) = self.try_prepare_psbt(
+            &input_unspents,
+            &mut all_inputs,
+            &witness_recipients,
+            fee_rate_checked,
+            None,
+            true,
+        )?;
+        psbt.unsigned_tx.output[0].script_pubkey = ScriptBuf::new_op_return([]);
+
+        // the outputs of the taker will be appended after the ones of the maker
+        let taker_vout = psbt.unsigned_tx.output.len() as u32;
+        let contract_id = ContractId::from_str(&asset_id).expect("invalid contract ID");
+        let mut transfer_info_map = BTreeMap::new();
+        transfer_info_map.insert(
+            asset_id.clone(),
+            InfoAssetTransfer {
+                asset_info: AssetInfo {
+                    contract_id,
+                    reject_list_url: asset.reject_list_url,
+                },
+                recipients: vec![LocalRecipient {
+                    // the recipient ID of the taker is known only once the offer is taken
+                    recipient_id: s!(""),
+                    local_recipient_data: LocalRecipientData::Witness(LocalWitnessData {
+                        amount_sat: 0,
+                        blinding: None,
+                        vout: taker_vout,
+                    }),
+                    assignment: assignment.clone(),
+                    transport_endpoints: vec![],
+                }],
+                asset_spend,
+                change: AssignmentsCollection::default(),
+                original_assignments_needed: assignments_needed.clone(),
+                assignments_needed,
+                assignments_spent: HashMap::new(),
+                main_transition: TypeOfTransition::Transfer,
+                beneficiaries_blinded: vec![],
+                beneficiaries_witness: vec![],
+                consolidation: false,
+            },
+        );
+        let RgbTransitionsData {
+            extra_allocations,
+            change_utxo_option,
+            ..
+        } = self.add_rgb_transitions(
+            txn,
+            &mut psbt,
+            &mut transfer_info_map,
+            &unspents,
+            &mut runtime,
+            &btc_change,
+            &HashSet::new(),
+        )?;
+        drop(runtime);
+
+        // the maker's UTXOs stay reserved until the offer is completed, aborted or expires
+        self.reserve_txos_until(
+            txn,
+            &psbt,
+            WalletTransactionType::SwapOffer,
+            Some(expiration_timestamp as i64),
+        )?;
+
+        let psbt_string = psbt.to_string();
+        let swap_id = hash_bytes_hex(psbt_string.as_bytes());
+        if let Some(batch_transfer) = receive_batch_transfer {
+            self.set_swap_id(txn, batch_transfer, &swap_id)?;
+        }
+
+        let info_batch_transfer = InfoBatchTransfer {
+            btc_change,
+            change_utxo_outpoint: change_utxo_option.as_ref().map(|utxo| utxo.outpoint()),
+            extra_allocations,
+            donation: false,
+            min_confirmations,
+            expiration_timestamp: Some(expiration_timestamp as i64),
+            created_at: now().unix_timestamp(),
+            entropy: 0,
+            transfers: transfer_info_map,
+            replaced_txid: None,
+        };
+        let swap_offer = SwapOffer::from_parts(
+            SwapOfferData {
+                swap_id: swap_id.clone(),
+                network: self.bitcoin_network(),
+                maker_asset_id: asset_id,
+                maker_assignment: assignment,
+                taker_leg,
+                expiration_timestamp,
+                stage: SwapStage::Offered,
+                txid: None,
+            },
+            SwapOfferPayload {
+                psbt: psbt_string,
+                taker_vout,
+                maker_vout,
+                maker_recipient_id,
+                maker_address,
+                ..Default::default()
+            },
+        )?;
+
+        // save swap data to file (for swap completion)
+        let transfer_dir = self.get_transfer_dir(&swap_id);
+        fs::create_dir_all(&transfer_dir)?;
+        let serialized_info =
+            serde_json::to_string(&info_batch_transfer).map_err(InternalError::from)?;
+        fs::write(transfer_dir.join(TRANSFER_DATA_FILE), serialized_info)?;
+        self.write_swap_offer(&transfer_dir, &swap_offer)?;
+
+        Ok(swap_offer)
+    }
+
+    fn swap_offer_take_impl(
+        &mut self,
+        txn: &DbTxn,
+        swap_offer: String,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<SwapOffer, Error> {
+        let swap_offer = SwapOffer::new(swap_offer)?;
+        let swap_offer_data = swap_offer.swap_offer_data.clone();
+        let payload = swap_offer.payload;
+        self.check_swap_offer(&swap_offer_data)?;
+        if swap_offer_data.stage != SwapStage::Offered {
+            return Err(Error::InvalidSwapOffer {
+                details: s!("the swap offer has already been taken"),
+            });
+        }
+        let mut psbt = Psbt::from_str(&payload.psbt)?;
+        let maker_outputs = payload.taker_vout;
+        if psbt.unsigned_tx.output.len() as u32 != maker_outputs
+            || !psbt.unsigned_tx.output[0].script_pubkey.is_op_return()
+        {
+            return Err(Error::InvalidSwapOffer {
+                details: s!("unexpected outputs in the swap PSBT"),
+            });
+        }
+        let maker_contracts = psbt.rgb_contract_ids().map_err(InternalError::from)?;
+        if !maker_contracts
+            .iter()
+            .any(|cid| cid.to_string() == swap_offer_data.maker_asset_id)
+        {
+            return Err(Error::InvalidSwapOffer {
+                details: s!("the swap PSBT doesn't spend the offered asset"),
+            });
+        }
+        let counterparty_asset_ids: HashSet<String> =
+            maker_contracts.iter().map(|cid| cid.to_string()).collect();
+        let swap_id = swap_offer_data.swap_id.clone();
+
+        // prepare what the maker needs to pay
+        let (receive_data_internal, receive_batch_transfer) = self.create_counterparty_receive(
+            txn,
+            &swap_offer_data.maker_asset_id,
+            &swap_offer_data.maker_assignment,
+            swap_offer_data.expiration_timestamp,
+            min_confirmations,
+        )?;
+        self.set_swap_id(txn, receive_batch_transfer, &swap_id)?;
+        let taker_script = receive_data_internal
+            .script_pubkey
+            .expect("witness receive should have a script pubkey");
+        let taker_amount = taker_script.minimal_non_dust().to_sat();
+
+        // prepare the taker's part of the swap
+        let (partial_psbt, info_batch_transfer, runtime) = match &swap_offer_data.taker_leg {
+            SwapLeg::Asset {
+                asset_id,
+                assignment,
+            } => {
+                let (Some(maker_vout), Some(maker_recipient_id)) = (
+                    payload.maker_vout.filter(|v| *v > 0 && *v < maker_outputs),
+                    payload.maker_recipient_id.clone(),
+                ) else {
+                    return Err(Error::InvalidSwapOffer {
+                        details: s!("missing maker recipient"),
+                    });
+                };
+                let asset = txn.check_asset_exists(asset_id.clone())?;
+                self.check_schema_support(&asset.schema)?;
+                Self::check_swap_assignment(assignment, Some(asset.schema))?;
+
+                let (fee_rate_checked, unspents, input_unspents, mut runtime) =
+                    self.get_transfer_begin_data(txn, fee_rate)?;
+                let input_unspents =
+                    Self::get_swap_input_unspents(input_unspents, &counterparty_asset_ids);
+                let mut assignments_needed = AssignmentsCollection::default();
+                assignment.add_to_assignments(&mut assignments_needed);
+                let asset_spend = self.select_rgb_inputs(
+                    asset_id.clone(),
+                    std::slice::from_ref(asset_id),
+                    &assignments_needed,
+                    input_unspents.clone(),
+                    &CoinSelectionOptions::default(),
+                )?;
+                let mut all_inputs: HashSet<BdkOutPoint> = asset_spend
+                    .input_outpoints
+                    .iter()
+                    .map(|o| o.clone().into())
+                    .collect();
+                let (mut partial_psbt, btc_change) = self.try_prepare_psbt(
+                    &input_unspents,
+                    &mut all_inputs,
+                    &vec![(taker_script, taker_amount)],
+                    fee_rate_checked,
+                    None,
+                    false,
+                )?;
+                let btc_change = btc_change.map(|c| BtcChange {
+                    vout: c.vout + maker_outputs,
+                    amount: c.amount,
+                });
+
+                let contract_id = ContractId::from_str(asset_id).expect("invalid contract ID");
+                let mut transfer_info_map = BTreeMap::new();
+                transfer_info_map.insert(
+                    asset_id.clone(),
+                    InfoAssetTransfer {
+                        asset_info: AssetInfo {
+                            contract_id,
+                            reject_list_url: asset.reject_list_url,
+                        },
+                        recipients: vec![LocalRecipient {
+                            recipient_id: maker_recipient_id,
+                            local_recipient_data: LocalRecipientData::Witness(LocalWitnessData {
+                                amount_sat: psbt.unsigned_tx.output[maker_vout as usize]
+                                    .value
+                                    .to_sat(),
+                                blinding: None,
+                                vout: maker_vout,
+                            }),
+                            assignment: assignment.clone(),
+                            transport_endpoints: vec![],
+                        }],
+                        asset_spend,
+                        change: AssignmentsCollection::default(),
+                        original_assignments_needed: assignments_needed.clone(),
+                        assignments_needed,
+                        assignments_spent: HashMap::new(),
+                        main_transition: TypeOfTransition::Transfer,
+                        beneficiaries_blinded: vec![],
+                        beneficiaries_witness: vec![],
+                        consolidation: false,
+                    },
+                );
+                let RgbTransitionsData {
+                    extra_allocations,
+                    change_utxo_option,
+                    ..
+                } = self.add_rgb_transitions(
+                    txn,
+                    &mut partial_psbt,
+                    &mut transfer_info_map,
+                    &unspents,
+                    &mut runtime,
+                    &btc_change,
+                    &HashSet::new(),
+                )?;
+                let info_batch_transfer = InfoBatchTransfer {
+                    btc_change,
+                    change_utxo_outpoint: change_utxo_option.as_ref().map(|utxo| utxo.outpoint()),
+                    extra_allocations,
+                    donation: false,
+                    min_confirmations,
+                    expiration_timestamp: Some(swap_offer_data.expiration_timestamp as i64),
+                    created_at: now().unix_timestamp(),
+                    entropy: 0,
+                    transfers: transfer_info_map,
+                    replaced_txid: None,
+                };
+                (partial_psbt, Some(info_batch_transfer), runtime)
+            }
+            SwapLeg::Bitcoin { amount } => {
+                let Some(maker_address) = &payload.maker_address else {
+                    return Err(Error::InvalidSwapOffer {
+                        details: s!("missing maker address"),
+                    });
+                };
+                let maker_script = self.get_script_pubkey(maker_address)?;
+                let fee_rate_checked = self.check_fee_rate(fee_rate)?;
+                self.sync_wallet(
+                    txn,
+                    SyncOptions {
+                        keychain: SyncKeychain::Vanilla {
+                            lookback: self.vanilla_sync_lookback(),
+                        },
+                        strategy: SyncStrategy::FastSync,
+                    },
+                    false,
+                )?;
+                let unspendable = self.get_unspendable_bdk_outpoints(txn)?;
+                let policy_paths = self.spending_policy_paths();
+                let mut bdk_wallet = self.bdk_wallet_mut();
+                let mut tx_builder = bdk_wallet.build_tx();
+                for (keychain, policy_path) in policy_paths {
+                    tx_builder.policy_path(policy_path, keychain);
+                }
+                tx_builder
+                    .unspendable(unspendable)
+                    .add_recipient(taker_script, BdkAmount::from_sat(taker_amount))
+                    .add_recipient(maker_script, BdkAmount::from_sat(*amount))
+                    .fee_rate(fee_rate_checked)
+                    .ordering(bdk_wallet::tx_builder::TxOrdering::Untouched);
+                let partial_psbt = tx_builder.finish().map_err(|e| match e {
+                    bdk_wallet::error::CreateTxError::CoinSelection(InsufficientFunds {
+                        needed,
+                        available,
+                    }) => Error::InsufficientBitcoins {
+                        needed: needed.to_sat(),
+                        available: available.to_sat(),
+                    },
+                    bdk_wallet::error::CreateTxError::OutputBelowDustLimit(_) => {
+                        Error::OutputBelowDustLimit
+                    }
+                    _ => Error::Internal {
+                        details: e.to_string(),
+                    },
+                })?;
+                drop(bdk_wallet);
+                (partial_psbt, None, self.rgb_runtime()?)
+            }
+        };
+        drop(runtime);
+
+        // merge the taker's part into the maker's PSBT
+        psbt.unsigned_tx
+            .input
+            .extend(partial_psbt.unsigned_tx.input.clone());
+        psbt.inputs.extend(partial_psbt.inputs.clone());
+        psbt.unsigned_tx
+            .output
+            .extend(partial_psbt.unsigned_tx.output.clone());
+        psbt.outputs.extend(partial_psbt.outputs.clone());
+        for cid in partial_psbt
+            .rgb_contract_ids()
+            .map_err(InternalError::from)?
+        {
+            if maker_contracts.contains(&cid) {
+                return Err(Error::InvalidSwapOffer {
+                    details: format!("contract {cid} is spent by both parties"),
+                });
+            }
+            let opids: BTreeSet<OpId> = partial_psbt
+                .rgb_contract_consumers(cid)
+                .map_err(InternalError::from)?
+                .into_values()
+                .collect();
+            for opid in opids {
+                let transition = partial_psbt
+                    .rgb_transition(opid)
+                    .map_err(InternalError::from)?
+                    .expect("consumed transition should be in the PSBT");
+                psbt.push_rgb_transition(transition)
+                    .map_err(InternalError::from)?;
+            }
+        }
+
+        // commit to the transitions of both parties
+        let opreturn_output = psbt.outputs.get_mut(0).unwrap();
+        opreturn_output.set_opret_host();
+        let entropy = rand::rng().random_range(0..u64::MAX);
+        opreturn_output
+            .set_mpc_entropy(entropy)
+            .map_err(InternalError::from)?;
+        psbt.set_rgb_close_method(CloseMethod::OpretFirst);
+        let fascia = psbt.rgb_commit().map_err(InternalError::from)?;
+        let witness_txid = psbt.get_txid();
+        let txid = witness_txid.to_string();
+
+        let transfer_dir = self.get_transfer_dir(&txid);
+        fs::create_dir_all(&transfer_dir)?;
+        let serialized_fascia = serde_json::to_string(&fascia).map_err(InternalError::from)?;
+        fs::write(transfer_dir.join(FASCIA_FILE), &serialized_fascia)?;
+        fs::write(transfer_dir.join(UNSIGNED_PSBT_FILE), psbt.to_string())?;
+
+        let taker_consignment = if let Some(mut info_batch_transfer) = info_batch_transfer {
+            let transfer_info = info_batch_transfer
+                .transfers
+                .values_mut()
+                .next()
+                .expect("the taker pays a single asset");
+            let maker_vout = transfer_info.recipients[0]
+                .local_recipient_data
+                .vout()
+                .expect("witness recipient");
+            transfer_info.beneficiaries_witness =
+                vec![ExplicitSeal::with(witness_txid, maker_vout)];
+            info_batch_transfer.entropy = entropy;
+            let serialized_info =
+                serde_json::to_string(&info_batch_transfer).map_err(InternalError::from)?;
+            fs::write(transfer_dir.join(TRANSFER_DATA_FILE), serialized_info)?;
+
+            self.gen_consignments(&fascia, &info_batch_transfer.transfers, &transfer_dir)?;
+            let asset_id = info_batch_transfer.transfers.keys().next().unwrap();
+            let consignment = self.read_send_consignment(&transfer_dir, asset_id)?;
+
+            // save transfer to DB with Initiated status to reserve the UTXOs
+            let batch_transfer_idx = self.save_transfers(
+                txn,
+                txid.clone(),
+                &info_batch_transfer,
+                TransferStatus::Initiated,
+            )?;
+            let db_data = txn.get_db_data(false)?;
+            let batch_transfer =
+                txn.get_batch_transfer_or_fail(batch_transfer_idx, &db_data.batch_transfers)?;
+            self.set_swap_id(txn, batch_transfer, &swap_id)?;
+            Some(consignment)
+        } else {
+            self.reserve_vanilla_txos(txn, &psbt, WalletTransactionType::SendBtc)?;
+            None
+        };
+
+        let swap_offer = SwapOffer::from_parts(
+            SwapOfferData {
+                stage: SwapStage::Taken,
+                txid: Some(txid),
+                ..swap_offer_data
+            },
+            SwapOfferPayload {
+                psbt: psbt.to_string(),
+                taker_recipient_id: Some(receive_data_internal.recipient_id),
+                fascia: Some(serialized_fascia),
+                taker_consignment,
+                ..payload
+            },
+        )?;
+        self.write_swap_offer(&transfer_dir, &swap_offer)?;
+
+        Ok(swap_offer)
+    }
+
+    fn validate_swap_consignment(
+        &self,
+        txn: &DbTxn,
+        swap_id: &str,
+        consignment: Option<&String>,
+        txid: &str,
+        vout: u32,
+    ) -> Result<(), Error> {
+        let invalid_consignment = || Error::InvalidSwapOffer {
+            details: s!("the counterparty consignment is invalid"),
+        };
+        let consignment = consignment
+            .and_then(|c| general_purpose::STANDARD.decode(c).ok())
+            .ok_or_else(invalid_consignment)?;
+        let batch_transfer = self.get_swap_batch_transfer(txn, swap_id, true)?;
+        if batch_transfer.status != TransferStatus::WaitingCounterparty {
+            return Err(Error::InvalidSwapOffer {
+                details: s!("the swap is not pending anymore"),
+            });
+        }
+        if !self.validate_unsigned_consignment(txn, &batch_transfer, &consignment, txid, vout)? {
+            return Err(invalid_consignment());
+        }
+        Ok(())
+    }
+
+    // validate the counterparty consignment paying the incoming witness transfer of the provided
+    // batch transfer, returning whether it's valid; the paying TX is not signed yet
+    fn validate_unsigned_consignment(
+        &self,
+        txn: &DbTxn,
+        batch_transfer: &DbBatchTransfer,
+        consignment: &[u8],
+        txid: &str,
+        vout: u32,
+    ) -> Result<bool, Error> {
+        let db_data = txn.get_db_data(false)?;
+        let (asset_transfer, transfer) =
+            batch_transfer.get_incoming_transfer(&db_data.asset_transfers, &db_data.transfers)?;
+        let recipient_id = transfer
+            .recipient_id
+            .clone()
+            .expect("transfer should have a recipient ID");
+        let consignment_path = self.get_receive_consignment_path(&recipient_id);
+        fs::create_dir_all(consignment_path.parent().unwrap())?;
+        fs::write(&consignment_path, consignment)?;
+
+        let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
+        match self.validate_received_consignment(
+            txn,
+            batch_transfer,
+            &asset_transfer,
+            &transfer,
+            recipient_id,
+            &consignment_path,
+            txid.to_string(),
+            Some(vout),
+            ReceiveMode::OutOfBand {
+                media_file_paths: vec![],
+            },
+            &mut updated_batch_transfer,
+        ) {
+            Ok(updated) => {
+                Ok(updated.is_some_and(|t| t.status == TransferStatus::WaitingBroadcast))
+            }
+            Err(Error::ReceivePolicyNotSatisfied { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn swap_offer_complete_begin_impl(
+        &mut self,
+        txn: &DbTxn,
+        swap_offer: String,
+    ) -> Result<(SwapOffer, Psbt), Error> {
+        let swap_offer = SwapOffer::new(swap_offer)?;
+        let swap_offer_data = &swap_offer.swap_offer_data;
+        let payload = &swap_offer.payload;
+        self.check_swap_offer(swap_offer_data)?;
+        let mut psbt = Psbt::from_str(&payload.psbt)?;
+        let txid = psbt.get_txid().to_string();
+        if swap_offer_data.txid.as_ref() != Some(&txid) {
+            return Err(Error::InvalidSwapOffer {
+                details: s!("the swap TXID doesn't match the swap PSBT"),
+            });
+        }
+
+        match swap_offer_data.stage {
+            SwapStage::Taken => {
+                // maker: check the taker has completed the offer without altering it
+                let stored =
+                    self.read_swap_offer(&self.get_transfer_dir(&swap_offer_data.swap_id))?;
+                if !stored.swap_offer_data.same_terms(swap_offer_data)
+                    || stored.swap_offer_data.stage != SwapStage::Offered
+                    || stored.payload.taker_vout != payload.taker_vout
+                {
+                    return Err(Error::InvalidSwapOffer {
+                        details: s!("the swap offer terms have been changed"),
+                    });
+                }
+                let skeleton = Psbt::from_str(&stored.payload.psbt)?;
+                let (maker_inputs, maker_outputs) = (
+                    skeleton.unsigned_tx.input.len(),
+                    skeleton.unsigned_tx.output.len(),
+                );
+                let unsigned_tx = &psbt.unsigned_tx;
+                if unsigned_tx.version != skeleton.unsigned_tx.version
+                    || unsigned_tx.lock_time != skeleton.unsigned_tx.lock_time
+                    || unsigned_tx.input.len() < maker_inputs
+                    || unsigned_tx.output.len() <= maker_outputs
+                    || unsigned_tx.input[..maker_inputs]
+                        .iter()
+                        .zip(&skeleton.unsigned_tx.input)
+                        .any(|(i, s)| {
+                            i.previous_output != s.previous_output || i.sequence != s.sequence
+                        })
+                    || !unsigned_tx.output[0].script_pubkey.is_op_return()
+                    || unsigned_tx.output[1..maker_outputs] != skeleton.unsigned_tx.output[1..]
+                {
+                    return Err(Error::InvalidSwapOffer {
+                        details: s!("the maker's inputs or outputs have been changed"),
+                    });
+                }
+                // sign only what the maker has prepared
+                psbt.inputs[..maker_inputs].clone_from_slice(&skeleton.inputs);
+
+                let skeleton_contracts =
+                    skeleton.rgb_contract_ids().map_err(InternalError::from)?;
+                for cid in &skeleton_contracts {
+                    let consumers = skeleton
+                        .rgb_contract_consumers(*cid)
+                        .map_err(InternalError::from)?;
+                    if psbt
+                        .rgb_contract_consumers(*cid)
+                        .map_err(InternalError::from)?
+                        != consumers
+                    {
+                        return Err(Error::InvalidSwapOffer {
+                            details: s!("the maker's transitions have been changed"),
+                        });
+                    }
+                    for opid in consumers.values() {
+                        if psbt.rgb_transition(*opid).map_err(InternalError::from)?
+                            != skeleton
+                                .rgb_transition(*opid)
+                                .map_err(InternalError::from)?
+                        {
+                            return Err(Error::InvalidSwapOffer {
+                                details: s!("the maker's transitions have been changed"),
+                            });
+                        }
+                    }
+                }
+                let fascia: Fascia = payload
+                    .fascia
+                    .as_ref()
+                    .and_then(|f| serde_json::from_str(f).ok())
+                    .ok_or_else(|| Error::InvalidSwapOffer {
+                        details: s!("invalid swap fascia"),
+                    })?;
+                let bundles = psbt.rgb_bundles().map_err(InternalError::from)?;
+                if fascia.witness_id().to_string() != txid
+                    || skeleton_contracts
+                        .iter()
+                        .any(|cid| fascia.bundles().get(cid) != bundles.get(cid))
+                {
+                    return Err(Error::InvalidSwapOffer {
+                        details: s!("the swap fascia doesn't match the swap PSBT"),
+                    });
+                }
+
+                // release the maker's inputs, which get reserved by the outgoing transfer once
+                // the offer is completed
+                let skeleton_txid = skeleton.unsigned_tx.compute_txid().to_string();
+                match txn.get_wallet_transaction_with_reserved_txos_by_txid(&skeleton_txid)? {
+                    Some((wt, _)) if wt.r#type == WalletTransactionType::SwapOffer => {
+                        txn.del_wallet_transaction(wt.idx)?;
+                    }
+                    _ => {
+                        return Err(Error::InvalidSwapOffer {
+                            details: s!("the swap offer has been aborted"),
+                        });
+                    }
+                }
+
+                // check the maker's inputs are still available and carry no new allocations
+                let (_, input_unspents) = self.get_transfer_unspents(txn)?;
+                let available: HashSet<BdkOutPoint> = input_unspents
+                    .iter()
+                    .map(|u| u.utxo.outpoint().into())
+                    .collect();
+                let prev_outputs: HashSet<OutPoint> = skeleton
+                    .unsigned_tx
+                    .input
+                    .iter()
+                    .map(|i| i.previous_output)
+                    .collect();
+                if prev_outputs.iter().any(|o| !available.contains(o)) {
+                    return Err(Error::InvalidSwapOffer {
+                        details: s!("the maker's inputs are not available anymore"),
+                    });
+                }
+                let runtime = self.rgb_runtime()?;
+                for cid in runtime.contracts_assigning(prev_outputs.clone())? {
+                    let consumers = skeleton
+                        .rgb_contract_consumers(cid)
+                        .map_err(InternalError::from)?;
+                    if runtime
+                        .contract_assignments_for(cid, prev_outputs.clone())?
+                        .values()
+                        .flat_map(|m| m.keys())
+                        .any(|opout| !consumers.contains_key(opout))
+                    {
+                        return Err(Error::InvalidSwapOffer {
+                            details: s!("the maker's inputs have received new allocations"),
+                        });
+                    }
+                }
+                drop(runtime);
+
+                // check what the taker pays
+                match &swap_offer_data.taker_leg {
+                    SwapLeg::Asset { .. } => {
+                        let maker_vout =
+                            payload.maker_vout.ok_or_else(|| Error::InvalidSwapOffer {
+                                details: s!("missing maker recipient"),
+                            })?;
+                        self.validate_swap_consignment(
+                            txn,
+                            &swap_offer_data.swap_id,
+                            payload.taker_consignment.as_ref(),
+                            &txid,
+                            maker_vout,
+                        )?;
+                    }
+                    SwapLeg::Bitcoin { amount } => {
+                        let maker_address = stored
+                            .payload
+                            .maker_address
+                            .as_ref()
+                            .expect("BTC swap offer should have a maker address");
+                        let maker_script = self.get_script_pubkey(maker_address)?;
+                        if !psbt.unsigned_tx.output[maker_outputs..]
+                            .iter()
+                            .any(|o| o.script_pubkey == maker_script && o.value.to_sat() >= *amount)
+                        {
+                            return Err(Error::InvalidSwapOffer {
+                                details: s!("the taker doesn't pay the requested amount"),
+                            });
+                        }
+                    }
+                }
+            }
+            SwapStage::Signed => {
+                // taker: check the maker has signed the swap TX without altering it
+                let stored = self.read_swap_offer(&self.get_transfer_dir(&txid))?;
+                if !stored.swap_offer_data.same_terms(swap_offer_data)
+                    || stored.swap_offer_data.stage != SwapStage::Taken
+                {
+                    return Err(Error::InvalidSwapOffer {
+                        details: s!("the swap offer terms have been changed"),
+                    });
+                }
+                let taken_psbt = Psbt::from_str(&stored.payload.psbt)?;
+                if psbt.unsigned_tx != taken_psbt.unsigned_tx {
+                    return Err(Error::InvalidSwapOffer {
+                        details: s!("the swap TX has been changed"),
+                    });
+                }
+                self.validate_swap_consignment(
+                    txn,
+                    &swap_offer_data.swap_id,
+                    payload.maker_consignment.as_ref(),
+                    &txid,
+                    stored.payload.taker_vout,
+                )?;
+            }
+            SwapStage::Offered => {
+                return Err(Error::InvalidSwapOffer {
+                    details: s!("the swap offer needs to be taken first"),
+                });
+            }
+            SwapStage::Broadcast => {
+                return Err(Error::InvalidSwapOffer {
+                    details: s!("the swap has already been completed"),
+                });
+            }
+        }
+
+        Ok((swap_offer, psbt))
+    }
+
+    fn swap_offer_complete_end_impl(
+        &mut self,
+        txn: &DbTxn,
+        swap_offer: SwapOffer,
+        signed_psbt: &Psbt,
+    ) -> Result<SwapOffer, Error> {
+        let swap_offer_data = swap_offer.swap_offer_data;
+        let payload = swap_offer.payload;
+        let swap_id = swap_offer_data.swap_id.clone();
+        let witness_txid = signed_psbt.get_txid();
+        let txid = witness_txid.to_string();
+        let transfer_dir = self.get_transfer_dir(&txid);
+
+        if swap_offer_data.stage == SwapStage::Taken {
+            // maker: save the outgoing transfer and hand the signed PSBT over to the taker
+            fs::rename(self.get_transfer_dir(&swap_id), &transfer_dir)?;
+            let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
+            let mut info_contents: InfoBatchTransfer =
+                serde_json::from_str(&fs::read_to_string(&info_file)?)
+                    .map_err(InternalError::from)?;
+            info_contents.entropy =
+                signed_psbt.outputs[0]
+                    .mpc_entropy()
+                    .ok_or_else(|| Error::InvalidSwapOffer {
+                        details: s!("missing MPC entropy"),
+                    })?;
+            let taker_recipient_id =
+                payload
+                    .taker_recipient_id
+                    .clone()
+                    .ok_or_else(|| Error::InvalidSwapOffer {
+                        details: s!("missing taker recipient"),
+                    })?;
+            let taker_vout = payload.taker_vout;
+            let taker_amount = signed_psbt.unsigned_tx.output[taker_vout as usize]
+                .value
+                .to_sat();
+            let transfer_info = info_contents
+                .transfers
+                .get_mut(&swap_offer_data.maker_asset_id)
+                .expect("the maker pays the offered asset");
+            for recipient in transfer_info.recipients.iter_mut() {
+                recipient.recipient_id = taker_recipient_id.clone();
+                if let LocalRecipientData::Witness(witness_data) =
+                    &mut recipient.local_recipient_data
+                {
+                    witness_data.amount_sat = taker_amount;
+                }
+            }
+            transfer_info.beneficiaries_witness =
+                vec![ExplicitSeal::with(witness_txid, taker_vout)];
+            let serialized_info =
+                serde_json::to_string(&info_contents).map_err(InternalError::from)?;
+            fs::write(&info_file, serialized_info)?;
+            let fascia_str = payload.fascia.clone().expect("checked in begin");
+            fs::write(transfer_dir.join(FASCIA_FILE), &fascia_str)?;
+            fs::write(transfer_dir.join(UNSIGNED_PSBT_FILE), payload.psbt.clone())?;
+            fs::write(transfer_dir.join(SIGNED_PSBT_FILE), signed_psbt.to_string())?;
+            let fascia: Fascia = serde_json::from_str(&fascia_str).map_err(InternalError::from)?;
+
+            self.gen_consignments(&fascia, &info_contents.transfers, &transfer_dir)?;
+            let maker_consignment =
+                self.read_send_consignment(&transfer_dir, &swap_offer_data.maker_asset_id)?;
+
+            // the maker's UTXOs stay reserved until the taker broadcasts the swap TX
+            let batch_transfer_idx =
+                self.save_transfers(txn, txid, &info_contents, TransferStatus::WaitingBroadcast)?;
+            let db_data = txn.get_db_data(false)?;
+            let batch_transfer =
+                txn.get_batch_transfer_or_fail(batch_transfer_idx, &db_data.batch_transfers)?;
+            self.set_swap_id(txn, batch_transfer, &swap_id)?;
+
+            let swap_offer = SwapOffer::from_parts(
+                SwapOfferData {
+                    stage: SwapStage::Signed,
+                    ..swap_offer_data
+                },
+                SwapOfferPayload {
+                    psbt: signed_psbt.to_string(),
+                    maker_consignment: Some(maker_consignment),
+                    ..payload
+                },
+            )?;
+            self.write_swap_offer(&transfer_dir, &swap_offer)?;
+            return Ok(swap_offer);
+        }
+
+        // taker: broadcast the swap TX
+        let tx = self.broadcast_psbt(txn, signed_psbt)?;
+        fs::write(transfer_dir.join(SIGNED_PSBT_FILE), signed_psbt.to_string())?;
+        match &swap_offer_data.taker_leg {
+            SwapLeg::Asset { .. } => {
+                let (_, _, info_contents, fascia) = self.get_transfer_dir_data(txid)?;
+                self.consume_swap_fascia(fascia, &info_contents)?;
+                self.set_swap_transfer_status(
+                    txn,
+                    &swap_id,
+                    false,
+                    TransferStatus::WaitingConfirmations,
+                )?;
+            }
+            SwapLeg::Bitcoin { .. } => {
+                self.finalize_vanilla_wallet_transaction(
+                    txn,
+                    signed_psbt,
+                    WalletTransactionType::SendBtc,
+                )?;
+            }
+        }
+        self.set_swap_transfer_status(txn, &swap_id, true, TransferStatus::WaitingConfirmations)?;
+
+        let swap_offer = SwapOffer::from_parts(
+            SwapOfferData {
+                stage: SwapStage::Broadcast,
+                txid: Some(tx.compute_txid().to_string()),
+                ..swap_offer_data
+            },
+            SwapOfferPayload {
+                psbt: signed_psbt.to_string(),
+                ..payload
+            },
+        )?;
+        self.write_swap_offer(&transfer_dir, &swap_offer)?;
+        Ok(swap_offer)
+    }
+
+    fn check_sell_order(&self, sell_order_data: &SellOrderData) -> Result<(), Error> {
+        if sell_order_data.network != self.bitcoin_network() {
+            return Err(Error::InvalidSellOrder {
+                details: s!("sell order for a different network"),
+            });
+        }
+        if sell_order_data.expiration_timestamp as i64 <= now().unix_timestamp() {
+            return Err(Error::InvalidSellOrder {
+                details: s!("the sell order has expired"),
+            });
+        }
+        Ok(())
+    }
+
+    fn set_sell_order_id(
+        &self,
+        txn: &DbTxn,
+        batch_transfer: DbBatchTransfer,
+        sell_order_id: &str,
+    ) -> Result<DbBatchTransfer, Error> {
+        let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.into();
+        updated_batch_transfer.sell_order_id = ActiveValue::Set(Some(sell_order_id.to_string()));
+        txn.update_batch_transfer(&mut updated_batch_transfer)
+    }
+
+    // the buyer of a sell order is unknown until the order is filled
+    fn set_sell_order_recipient_id(
+        &self,
+        txn: &DbTxn,
+        batch_transfer: &DbBatchTransfer,
+        recipient_id: Option<String>,
+    ) -> Result<(), Error> {
+        let db_data = txn.get_db_data(false)?;
+        let batch_transfer_data =
+            batch_transfer.get_transfers(&db_data.asset_transfers, &db_data.transfers)?;
+        for asset_transfer_data in batch_transfer_data.asset_transfers_data {
+            for transfer in asset_transfer_data.transfers {
+                let mut updated_transfer: DbTransferActMod = transfer.into();
+                updated_transfer.recipient_id = ActiveValue::Set(recipient_id.clone());
+                txn.update_transfer(&mut updated_transfer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn sell_order_create_begin_impl(
+        &mut self,
+        txn: &DbTxn,
+        asset_id: String,
+        assignment: Assignment,
+        price: u64,
+        expiration_timestamp: u64,
+        min_confirmations: u8,
+    ) -> Result<(SellOrder, Psbt), Error> {
+        if expiration_timestamp as i64 <= now().unix_timestamp() {
+            return Err(Error::InvalidExpiration);
+        }
+        if price == 0 {
+            return Err(Error::InvalidAmountZero);
+        }
+        let asset = txn.check_asset_exists(asset_id.clone())?;
+        self.check_schema_support(&asset.schema)?;
+        Self::check_swap_assignment(&assignment, Some(asset.schema))?;
+
+        let (unspents, input_unspents) = self.get_transfer_unspents(txn)?;
+        let mut assignments_needed = AssignmentsCollection::default();
+        assignment.add_to_assignments(&mut assignments_needed);
+        // the seller signature doesn't cover the OP_RETURN commitment, which the buyer could
+        // replace to move anything the seller UTXO holds, so the UTXO needs to hold exactly the
+        // sold assignment
+        let input_unspents: Vec<LocalUnspent> = input_unspents
+            .into_iter()
+            .filter(|u| {
+                let mut held = AssignmentsCollection::default();
+                for allocation in u.rgb_allocations.iter().filter(|a| !a.status.failed()) {
+                    if allocation.asset_id.as_ref() != Some(&asset_id) {
+                        return false;
+                    }
+                    allocation.assignment.add_to_assignments(&mut held);
+                }
+                u.pending_blinded == 0 && held == assignments_needed
+            })
+            .collect();
+        if input_unspents.is_empty() {
+            return Err(Error::InvalidSellOrder {
+                details: s!("no UTXO holds exactly the sold assignment"),
+            });
+        }
+        let asset_spend = self.select_rgb_inputs(
+            asset_id.clone(),
+            std::slice::from_ref(&asset_id),
             &assignments_needed,
-            input_unspents.clone(),
+            input_unspents,
+            &CoinSelectionOptions::default(),
         )?;
+        // SIGHASH_SINGLE binds the seller input to the output at the same index, so the sold
+        // assignment needs to be held by a single UTXO
+        if asset_spend.input_outpoints.len() != 1 {
+            return Err(Error::InvalidSellOrder {
+                details: s!("the sold assignment needs to be held by a single UTXO"),
+            });
+        }
+        let input_outpoint: BdkOutPoint = asset_spend.input_outpoints[0].clone().into();
+
+        // the buyer pays all fees, so the seller output gets back the input amount plus the price
+        let (mut psbt, btc_change) = self.prepare_psbt(
+            HashSet::from([input_outpoint]),
+            &vec![],
+            FeeRate::ZERO,
+            None,
+            true,
+        )?;
+        let btc_change = btc_change.ok_or(Error::OutputBelowDustLimit)?;
+        // move the seller output first, to match the seller input, and the OP_RETURN second
+        psbt.unsigned_tx.output.swap(0, btc_change.vout as usize);
+        psbt.outputs.swap(0, btc_change.vout as usize);
+        psbt.unsigned_tx.output[1].script_pubkey = ScriptBuf::new_op_return([]);
+        let seller_amount = btc_change.amount + price;
+        psbt.unsigned_tx.output[0].value = BdkAmount::from_sat(seller_amount);
+        let btc_change = Some(BtcChange {
+            vout: 0,
+            amount: seller_amount,
+        });
 
-        let chainnet: ChainNet = self.bitcoin_network().into();
-        let script_pubkey = self
-            .get_new_addresses(KeychainKind::External, 1)?
-            .script_pubkey();
-        let dust = self
-            .bdk_wallet()
-            .public_descriptor(KeychainKind::External)
-            .dust_value()
-            .to_sat();
-        let witness_recipients: Vec<(ScriptBuf, u64)> = vec![(script_pubkey.clone(), dust)];
-        let beneficiary = beneficiary_from_script_buf(script_pubkey.clone());
-        let beneficiary = XChainNet::with(chainnet, beneficiary);
-        let recipient_id = beneficiary.to_string();
-        let local_recipients = vec![LocalRecipient {
-            recipient_id,
-            local_recipient_data: LocalRecipientData::Witness(LocalWitnessData {
-                amount_sat: dust,
-                blinding: None,
-                vout: 1,
-            }),
-            assignment: Assignment::Fungible(0),
-            transport_endpoints: vec![],
-        }];
-
+        // the output of the buyer will be appended after the ones of the seller
+        let buyer_vout = psbt.unsigned_tx.output.len() as u32;
         let contract_id = ContractId::from_str(&asset_id).expect("invalid contract ID");
-        let asset_info = AssetInfo {
-            contract_id,
-            reject_list_url: asset.reject_list_url,
-        };
-        let transfer_info = InfoAssetTransfer {
-            asset_info,
-            recipients: local_recipients.clone(),
-            asset_spend: asset_spend.clone(),
-            change: AssignmentsCollection::default(),
-            original_assignments_needed: assignments_needed.clone(),
-            assignments_needed,
-            assignments_spent: HashMap::new(),
-            main_transition: TypeOfTransition::Burn,
-            beneficiaries_blinded: vec![],
-            beneficiaries_witness: vec![],
+        let mut transfer_info_map = BTreeMap::new();
+        transfer_info_map.insert(
+            asset_id.clone(),
+            InfoAssetTransfer {
+                asset_info: AssetInfo {
+                    contract_id,
+                    reject_list_url: asset.reject_list_url,
+                },
+                recipients: vec![LocalRecipient {
+                    // the recipient ID of the buyer is known only once the order is filled
+                    recipient_id: s!(""),
+                    local_recipient_data: LocalRecipientData::Witness(LocalWitnessData {
+                        amount_sat: 0,
+                        blinding: None,
+                        vout: buyer_vout,
+                    }),
+                    assignment: assignment.clone(),
+                    transport_endpoints: vec![],
+                }],
+                asset_spend,
+                change: AssignmentsCollection::default(),
+                original_assignments_needed: assignments_needed.clone(),
+                assignments_needed,
+                assignments_spent: HashMap::new(),
+                main_transition: TypeOfTransition::Transfer,
+                beneficiaries_blinded: vec![],
+                beneficiaries_witness: vec![],
+                consolidation: false,
+            },
+        );
+        let mut runtime = self.rgb_runtime()?;
+        let RgbTransitionsData {
+            extra_allocations,
+            change_utxo_option,
+            ..
+        } = self.add_rgb_transitions(
+            txn,
+            &mut psbt,
+            &mut transfer_info_map,
+            &unspents,
+            &mut runtime,
+            &btc_change,
+            &HashSet::new(),
+        )?;
+        drop(runtime);
+
+        let opreturn_output = psbt.outputs.get_mut(1).unwrap();
+        opreturn_output.set_opret_host();
+        let entropy = rand::rng().random_range(0..u64::MAX);
+        opreturn_output
+            .set_mpc_entropy(entropy)
+            .map_err(InternalError::from)?;
+        psbt.set_rgb_close_method(CloseMethod::OpretFirst);
+        let fascia = psbt.rgb_commit().map_err(InternalError::from)?;
+        let witness_txid = psbt.get_txid();
+        let partial_txid = witness_txid.to_string();
+        let sell_order_id = hash_bytes_hex(psbt.to_string().as_bytes());
+
+        // the consignment is prepared for the partial TX, the buyer will update its witness
+        transfer_info_map
+            .get_mut(&asset_id)
+            .expect("the seller pays the sold asset")
+            .beneficiaries_witness = vec![ExplicitSeal::with(witness_txid, buyer_vout)];
+        let info_batch_transfer = InfoBatchTransfer {
+            btc_change,
+            change_utxo_outpoint: change_utxo_option.as_ref().map(|utxo| utxo.outpoint()),
+            extra_allocations,
+            donation: false,
+            min_confirmations,
+            expiration_timestamp: Some(expiration_timestamp as i64),
+            created_at: now().unix_timestamp(),
+            entropy,
+            transfers: transfer_info_map,
+            replaced_txid: None,
         };
-        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> =
-            BTreeMap::from([(asset_id.clone(), transfer_info)]);
+        let transfer_dir = self.get_transfer_dir(&partial_txid);
+        fs::create_dir_all(&transfer_dir)?;
+        let serialized_info =
+            serde_json::to_string(&info_batch_transfer).map_err(InternalError::from)?;
+        fs::write(transfer_dir.join(TRANSFER_DATA_FILE), serialized_info)?;
+        let serialized_fascia = serde_json::to_string(&fascia).map_err(InternalError::from)?;
+        fs::write(transfer_dir.join(FASCIA_FILE), serialized_fascia)?;
+        fs::write(transfer_dir.join(UNSIGNED_PSBT_FILE), psbt.to_string())?;
+        self.gen_consignments(&fascia, &info_batch_transfer.transfers, &transfer_dir)?;
+        let consignment = self.read_send_consignment(&transfer_dir, &asset_id)?;
 
-        let receive_ids: Vec<String> = local_recipients
-            .iter()
-            .map(|lr| lr.recipient_id.clone())
-            .collect();
-        let transfer_dir = self.setup_transfer_directory(receive_ids)?;
+        // the seller UTXO stays reserved until the buyer broadcasts the sell TX
+        let batch_transfer_idx = self.save_transfers(
+            txn,
+            partial_txid,
+            &info_batch_transfer,
+            TransferStatus::WaitingBroadcast,
+        )?;
+        let db_data = txn.get_db_data(false)?;
+        let batch_transfer =
+            txn.get_batch_transfer_or_fail(batch_transfer_idx, &db_data.batch_transfers)?;
+        let batch_transfer = self.set_sell_order_id(txn, batch_transfer, &sell_order_id)?;
+        self.set_sell_order_recipient_id(txn, &batch_transfer, None)?;
+
+        psbt.inputs[0].sighash_type = Some(EcdsaSighashType::SinglePlusAnyoneCanPay.into());
+        let sell_order = SellOrder::from_parts(
+            SellOrderData {
+                sell_order_id,
+                network: self.bitcoin_network(),
+                asset_id,
+                assignment,
+                price,
+                expiration_timestamp,
+            },
+            SellOrderPayload {
+                psbt: s!(""),
+                consignment,
+            },
+        )?;
+        Ok((sell_order, psbt))
+    }
 
-        let mut rejected = HashSet::new();
-        Ok(
-            match self.prepare_transfer_psbt(
-                txn,
-                &mut transfer_info_map,
-                transfer_dir.clone(),
-                false,
-                unspents,
-                &input_unspents,
-                &witness_recipients,
-                fee_rate_checked,
-                min_confirmations,
-                None,
-                &mut runtime,
-                &mut rejected,
-                dry_run,
-            )? {
-                PrepareTransferPsbtResult::Retry => {
-                    unreachable!("burn transition has no retry logic")
-                }
-                PrepareTransferPsbtResult::Success(begin_operation_data) => *begin_operation_data,
+    fn sell_order_create_end_impl(
+        &self,
+        sell_order: SellOrder,
+        signed_psbt: &Psbt,
+    ) -> Result<SellOrder, Error> {
+        let seller_input = &signed_psbt.inputs[0];
+        if seller_input.final_script_witness.is_none() && seller_input.final_script_sig.is_none() {
+            return Err(Error::InvalidSellOrder {
+                details: s!("the seller input could not be signed"),
+            });
+        }
+        SellOrder::from_parts(
+            sell_order.sell_order_data,
+            SellOrderPayload {
+                psbt: signed_psbt.to_string(),
+                ..sell_order.payload
             },
         )
     }
 
-    fn burn_end_impl(&mut self, txn: &DbTxn, signed_psbt: &Psbt) -> Result<OperationResult, Error> {
-        let (txid, _transfer_dir, info_contents, fascia) =
-            self.get_transfer_end_data(signed_psbt)?;
+    fn sell_order_take_begin_impl(
+        &mut self,
+        txn: &DbTxn,
+        sell_order: String,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<(DbBatchTransfer, Psbt), Error> {
+        let sell_order = SellOrder::new(sell_order)?;
+        let sell_order_data = sell_order.sell_order_data;
+        let payload = sell_order.payload;
+        self.check_sell_order(&sell_order_data)?;
+        if !txn
+            .get_batch_transfers_by_sell_order_id(&sell_order_data.sell_order_id)?
+            .is_empty()
+        {
+            return Err(Error::InvalidSellOrder {
+                details: s!("the sell order is already known to this wallet"),
+            });
+        }
+        let asset_schema = txn
+            .check_asset_exists(sell_order_data.asset_id.clone())
+            .ok()
+            .map(|a| a.schema);
+        Self::check_swap_assignment(&sell_order_data.assignment, asset_schema)?;
+
+        // check the seller has signed a single input and committed to the sold asset
+        let seller_psbt = Psbt::from_str(&payload.psbt)?;
+        let seller_tx = &seller_psbt.unsigned_tx;
+        let invalid_psbt = || Error::InvalidSellOrder {
+            details: s!("unexpected inputs or outputs in the sell PSBT"),
+        };
+        if seller_tx.input.len() != 1
+            || seller_tx.output.len() != 2
+            || !seller_tx.output[1].script_pubkey.is_op_return()
+        {
+            return Err(invalid_psbt());
+        }
+        let seller_outpoint = seller_tx.input[0].previous_output;
+        let seller_input = seller_psbt.inputs[0].clone();
+        let satisfaction_weight = match (
+            &seller_input.final_script_witness,
+            &seller_input.final_script_sig,
+        ) {
+            (None, None) => {
+                return Err(Error::InvalidSellOrder {
+                    details: s!("the seller input is not signed"),
+                });
+            }
+            (witness, script_sig) => Weight::from_wu(
+                (witness.as_ref().map_or(0, |w| w.size())
+                    + script_sig.as_ref().map_or(0, |s| s.len() * 4)) as u64,
+            ),
+        };
+        let seller_prevout = match (&seller_input.witness_utxo, &seller_input.non_witness_utxo) {
+            (_, Some(prev_tx)) => prev_tx.output.get(seller_outpoint.vout as usize).cloned(),
+            (Some(txout), None) => Some(txout.clone()),
+            (None, None) => None,
+        }
+        .ok_or_else(invalid_psbt)?;
+        let seller_output = seller_tx.output[0].clone();
+        if seller_output
+            .value
+            .to_sat()
+            .checked_sub(seller_prevout.value.to_sat())
+            != Some(sell_order_data.price)
+        {
+            return Err(Error::InvalidSellOrder {
+                details: s!("the sell PSBT doesn't match the sell order price"),
+            });
+        }
+        if !seller_psbt
+            .rgb_contract_ids()
+            .map_err(InternalError::from)?
+            .iter()
+            .any(|cid| cid.to_string() == sell_order_data.asset_id)
+        {
+            return Err(Error::InvalidSellOrder {
+                details: s!("the sell PSBT doesn't spend the sold asset"),
+            });
+        }
 
-        let batch_transfer_idx = self.finalize_transfer_end(
+        // prepare what the seller needs to pay
+        let (receive_data_internal, receive_batch_transfer) = self.create_counterparty_receive(
             txn,
-            txid.clone(),
-            signed_psbt,
-            &info_contents,
-            TransferStatus::WaitingConfirmations,
-            fascia,
+            &sell_order_data.asset_id,
+            &sell_order_data.assignment,
+            sell_order_data.expiration_timestamp,
+            min_confirmations,
+        )?;
+        let receive_batch_transfer =
+            self.set_sell_order_id(txn, receive_batch_transfer, &sell_order_data.sell_order_id)?;
+        let buyer_script = receive_data_internal
+            .script_pubkey
+            .expect("witness receive should have a script pubkey");
+        let buyer_amount = buyer_script.minimal_non_dust().to_sat();
+
+        // complete the sell TX, keeping what the seller has signed untouched
+        let fee_rate_checked = self.check_fee_rate(fee_rate)?;
+        self.sync_wallet(
+            txn,
+            SyncOptions {
+                keychain: SyncKeychain::Vanilla {
+                    lookback: self.vanilla_sync_lookback(),
+                },
+                strategy: SyncStrategy::FastSync,
+            },
             false,
         )?;
+        let unspendable = self.get_unspendable_bdk_outpoints(txn)?;
+        let policy_paths = self.spending_policy_paths();
+        let mut bdk_wallet = self.bdk_wallet_mut();
+        let mut tx_builder = bdk_wallet.build_tx();
+        for (keychain, policy_path) in policy_paths {
+            tx_builder.policy_path(policy_path, keychain);
+        }
+        tx_builder
+            .unspendable(unspendable)
+            .add_foreign_utxo_with_sequence(
+                seller_outpoint,
+                seller_input.clone(),
+                satisfaction_weight,
+                seller_tx.input[0].sequence,
+            )
+            .map_err(|_| invalid_psbt())?
+            .add_recipient(seller_output.script_pubkey, seller_output.value)
+            .add_recipient(seller_tx.output[1].script_pubkey.clone(), BdkAmount::ZERO)
+            .add_recipient(buyer_script, BdkAmount::from_sat(buyer_amount))
+            .version(seller_tx.version.0)
+            .nlocktime(seller_tx.lock_time)
+            .fee_rate(fee_rate_checked)
+            .ordering(bdk_wallet::tx_builder::TxOrdering::Untouched);
+        let mut psbt = tx_builder.finish().map_err(|e| match e {
+            bdk_wallet::error::CreateTxError::CoinSelection(InsufficientFunds {
+                needed,
+                available,
+            }) => Error::InsufficientBitcoins {
+                needed: needed.to_sat(),
+                available: available.to_sat(),
+            },
+            bdk_wallet::error::CreateTxError::OutputBelowDustLimit(_) => {
+                Error::OutputBelowDustLimit
+            }
+            _ => Error::Internal {
+                details: e.to_string(),
+            },
+        })?;
+        drop(bdk_wallet);
+        if psbt.unsigned_tx.input[0].previous_output != seller_outpoint
+            || psbt.unsigned_tx.output[..2] != seller_tx.output[..]
+        {
+            return Err(Error::Internal {
+                details: s!("unexpected sell TX layout"),
+            });
+        }
+        psbt.inputs[0] = seller_input;
+
+        // point the consignment to the completed TX and check what the seller pays
+        let invalid_consignment = || Error::InvalidSellOrder {
+            details: s!("the seller consignment is invalid"),
+        };
+        let mut consignment = general_purpose::STANDARD
+            .decode(&payload.consignment)
+            .ok()
+            .and_then(|c| RgbTransfer::load(&c[..]).ok())
+            .ok_or_else(invalid_consignment)?;
+        let pub_witness = PubWitness::with(psbt.unsigned_tx.clone());
+        if !consignment.modify_bundle(seller_psbt.get_txid(), |wb| {
+            wb.pub_witness = pub_witness.clone()
+        }) {
+            return Err(invalid_consignment());
+        }
+        let mut consignment_bytes = vec![];
+        consignment.save(&mut consignment_bytes)?;
+        let txid = psbt.get_txid().to_string();
+        if !self.validate_unsigned_consignment(
+            txn,
+            &receive_batch_transfer,
+            &consignment_bytes,
+            &txid,
+            seller_tx.output.len() as u32,
+        )? {
+            return Err(invalid_consignment());
+        }
 
+        Ok((receive_batch_transfer, psbt))
+    }
+
+    fn sell_order_take_end_impl(
+        &mut self,
+        txn: &DbTxn,
+        receive_batch_transfer: DbBatchTransfer,
+        signed_psbt: &Psbt,
+    ) -> Result<OperationResult, Error> {
+        let tx = self.broadcast_psbt(txn, signed_psbt)?;
+        self.finalize_vanilla_wallet_transaction(txn, signed_psbt, WalletTransactionType::SendBtc)?;
+        let batch_transfer_idx = receive_batch_transfer.idx;
+        self.set_batch_transfer_status(
+            txn,
+            receive_batch_transfer,
+            TransferStatus::WaitingConfirmations,
+        )?;
         Ok(OperationResult {
-            txid,
+            txid: tx.compute_txid().to_string(),
             batch_transfer_idx,
-            entropy: info_contents.entropy,
+            entropy: signed_psbt.outputs[1].mpc_entropy().unwrap_or_default(),
         })
     }
+
+    // the seller learns about the sell TX, completed and broadcast by the buyer, only from the
+    // indexer; once found, the transfer is moved from the partial TX to the final one
+    fn find_sell_order_fill(
+        &mut self,
+        txn: &DbTxn,
+        batch_transfer: &DbBatchTransfer,
+    ) -> Result<Option<DbBatchTransfer>, Error> {
+        // the TXID is the one of the partial TX until the sell order is found to be filled
+        let partial_txid = batch_transfer
+            .txid
+            .clone()
+            .expect("batch transfer should have a TXID");
+        let partial_transfer_dir = self.get_transfer_dir(&partial_txid);
+        let psbt_str = fs::read_to_string(partial_transfer_dir.join(UNSIGNED_PSBT_FILE))?;
+        let partial_psbt = Psbt::from_str(&psbt_str)?;
+        let seller_outpoint = partial_psbt.unsigned_tx.input[0].previous_output;
+        self.sync_wallet(
+            txn,
+            SyncOptions {
+                keychain: SyncKeychain::Colored,
+                strategy: SyncStrategy::FastSync,
+            },
+            false,
+        )?;
+        let Some(tx) = self
+            .bdk_wallet()
+            .transactions()
+            .map(|t| t.tx_node.tx.as_ref().clone())
+            .find(|tx| {
+                tx.input
+                    .iter()
+                    .any(|i| i.previous_output == seller_outpoint)
+            })
+        else {
+            return Ok(None);
+        };
+        let buyer_vout = partial_psbt.unsigned_tx.output.len();
+        if tx.input[0].previous_output != seller_outpoint
+            || tx.output.len() <= buyer_vout
+            || tx.output[..buyer_vout] != partial_psbt.unsigned_tx.output[..]
+        {
+            warn!(
+                self.logger(),
+                "Sell order UTXO spent by an unrelated TX, failing the transfer"
+            );
+            return Ok(Some(self.fail_batch_transfer(txn, batch_transfer)?));
+        }
+        let witness_txid = tx.compute_txid();
+        let txid = witness_txid.to_string();
+        if txid == partial_txid {
+            // already moved to the final TX by a previous refresh
+            return Ok(Some(batch_transfer.clone()));
+        }
+        debug!(self.logger(), "Sell order filled by TX {txid}");
+
+        // move the transfer data to the final TX
+        let transfer_dir = self.get_transfer_dir(&txid);
+        fs::rename(&partial_transfer_dir, &transfer_dir)?;
+        let (_, _, mut info_contents, mut fascia) = self.get_transfer_dir_data(txid.clone())?;
+        fascia.update_pub_witness(PubWitness::with(tx.clone()));
+        let buyer_output = &tx.output[buyer_vout];
+        let recipient_id = recipient_id_from_script_buf(
+            buyer_output.script_pubkey.clone(),
+            self.bitcoin_network(),
+        );
+        for transfer_info in info_contents.transfers.values_mut() {
+            for recipient in transfer_info.recipients.iter_mut() {
+                recipient.recipient_id = recipient_id.clone();
+                if let LocalRecipientData::Witness(witness_data) =
+                    &mut recipient.local_recipient_data
+                {
+                    witness_data.amount_sat = buyer_output.value.to_sat();
+                }
+            }
+            transfer_info.beneficiaries_witness =
+                vec![ExplicitSeal::with(witness_txid, buyer_vout as u32)];
+        }
+        let serialized_info = serde_json::to_string(&info_contents).map_err(InternalError::from)?;
+        fs::write(transfer_dir.join(TRANSFER_DATA_FILE), serialized_info)?;
+        let serialized_fascia = serde_json::to_string(&fascia).map_err(InternalError::from)?;
+        fs::write(transfer_dir.join(FASCIA_FILE), serialized_fascia)?;
+        self.gen_consignments(&fascia, &info_contents.transfers, &transfer_dir)?;
+        self.rgb_runtime()?.consume_fascia(fascia, None)?;
+
+        // the change allocations move from the seller output of the partial TX to the final one,
+        // which may have already been synced as a new TXO
+        if let Some(change_txo) = txn.get_txo(&Outpoint {
+            txid: partial_txid,
+            vout: 0,
+        })? {
+            match txn.get_txo(&Outpoint {
+                txid: txid.clone(),
+                vout: 0,
+            })? {
+                Some(synced_txo) => {
+                    txn.update_colorings_txo(change_txo.idx, synced_txo.idx)?;
+                    txn.del_txo(change_txo.idx)?;
+                }
+                None => {
+                    let mut change_txo: DbTxoActMod = change_txo.into();
+                    change_txo.txid = ActiveValue::Set(txid.clone());
+                    txn.update_txo(change_txo)?;
+                }
+            }
+        }
+        self.set_inputs_spent(txn, &tx)?;
+        self.set_sell_order_recipient_id(txn, batch_transfer, Some(recipient_id))?;
+        let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
+        updated_batch_transfer.txid = ActiveValue::Set(Some(txid));
+        Ok(Some(
+            txn.update_batch_transfer(&mut updated_batch_transfer)?,
+        ))
+    }
 }
 
 /// Online operations for a wallet.
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub trait RgbWalletOpsOnline: RgbWalletOpsOffline + WalletOnline {
     /// Set the status for eligible transfers to [`TransferStatus::Failed`] and return true if any
     /// transfer has changed.
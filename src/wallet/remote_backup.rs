@@ -0,0 +1,389 @@
+use super::*;
+
+// key the index of the wallet backup is stored with, in the store named after the wallet directory
+const REMOTE_BACKUP_KEY: &str = "rgb_lib_backup";
+// incremental backups pushed on top of a full one before a full one is pushed again
+const REMOTE_BACKUP_MAX_INCREMENTS: u32 = 20;
+const REMOTE_BACKUP_NONCE_LENGTH: usize = 24;
+const REMOTE_BACKUP_VERSION: u8 = 1;
+
+/// An object stored in a [`BackupTarget`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionedObject {
+    /// Key the object is stored with
+    pub key: String,
+    /// Version of the stored object, 0 if it's not stored yet
+    pub version: u64,
+    /// Object content
+    pub value: Vec<u8>,
+}
+
+/// A remote key/value store the wallet backups can be pushed to.
+///
+/// Objects are grouped in stores, one per wallet. Writes are versioned: storing an object requires
+/// the version of the one it replaces (0 if there is none) and must fail if it doesn't match, so
+/// concurrent writers can't silently overwrite each other. A successful write increments the
+/// version by one.
+///
+/// Backups are encrypted before reaching the target.
+pub trait BackupTarget: Send + Sync {
+    /// Return the object stored with the given key in the given store, if any
+    fn get_object(&self, store_id: &str, key: &str) -> Result<Option<VersionedObject>, Error>;
+
+    /// Store the given object in the given store, replacing the one with the object version
+    fn put_object(&self, store_id: &str, object: VersionedObject) -> Result<(), Error>;
+}
+
+// Content of the object a backup is stored as, with the public data needed to decrypt it.
+#[derive(Debug, Deserialize, Serialize)]
+struct RemoteBackupData {
+    version: u8,
+    scrypt_params: ScryptParams,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+// Content of the object the backup index is stored as. The backup is the chain of the objects
+// stored with the keys returned by remote_backup_key, from position 0 (a full backup) to the
+// number of increments, each increment only including the files changed since the previous push.
+// Full backups alternate between 2 slots, so a push failing half way never touches the chain the
+// stored index refers to.
+#[derive(Debug, Deserialize, Serialize)]
+struct RemoteBackupIndex {
+    version: u8,
+    slot: u8,
+    increments: u32,
+}
+
+fn remote_backup_key(slot: u8, position: u32) -> String {
+    format!("{REMOTE_BACKUP_KEY}_{slot}_{position}")
+}
+
+// Chain of backups the stored index refers to, as pushed by this wallet.
+struct RemoteBackupChain {
+    slot: u8,
+    increments: u32,
+    // manifest of the last pushed backup, to base the next incremental one on
+    manifest: BackupManifest,
+}
+
+#[derive(Default)]
+struct RemoteBackupState {
+    // versions of the stored objects, retrieved from the target before writing them the first time
+    object_versions: HashMap<String, u64>,
+    // unknown until the first successful push, which is a full one
+    chain: Option<RemoteBackupChain>,
+}
+
+// Pushes encrypted backups of a wallet to a BackupTarget. The encryption key is derived once, when
+// remote backups are enabled, so pushes after operations stay cheap.
+pub(crate) struct RemoteBackup {
+    target: Arc<dyn BackupTarget>,
+    database: Weak<RgbLibDatabase>,
+    wallet_dir: PathBuf,
+    store_id: String,
+    logger: Logger,
+    scrypt_params: ScryptParams,
+    salt: String,
+    aead: XChaCha20Poly1305,
+    state: Mutex<RemoteBackupState>,
+    // set when an operation has outdated the stored backup
+    pending: Mutex<bool>,
+}
+
+impl RemoteBackup {
+    pub(crate) fn new(
+        target: Arc<dyn BackupTarget>,
+        database: &Arc<RgbLibDatabase>,
+        wallet_dir: &Path,
+        logger: &Logger,
+        password: &str,
+    ) -> Result<Self, Error> {
+        let store_id = wallet_dir
+            .file_name()
+            .ok_or(InternalError::Unexpected)?
+            .to_string_lossy()
+            .to_string();
+        let scrypt_params = ScryptParams::default();
+        let salt = generate_salt();
+        let key = derive_key(password, &salt, &scrypt_params)?;
+        Ok(Self {
+            target,
+            database: Arc::downgrade(database),
+            wallet_dir: wallet_dir.to_path_buf(),
+            store_id,
+            logger: logger.clone(),
+            scrypt_params,
+            salt,
+            aead: XChaCha20Poly1305::new(&key),
+            state: Mutex::new(RemoteBackupState::default()),
+            pending: Mutex::new(false),
+        })
+    }
+
+    pub(crate) fn mark_pending(&self) {
+        *self.pending.lock().expect("lock poisoned") = true;
+    }
+
+    // Push the backup if an operation has outdated it, unless the current thread is still in the
+    // middle of an operation. Failures are only logged, as the operation itself has succeeded, and
+    // the backup is left marked as required so it gets pushed again after the next operation.
+    pub(crate) fn push_pending(&self) {
+        let Some(database) = self.database.upgrade() else {
+            return;
+        };
+        if database.in_operation() {
+            return;
+        }
+        if !std::mem::take(&mut *self.pending.lock().expect("lock poisoned")) {
+            return;
+        }
+        if let Err(e) = self.push() {
+            error!(self.logger, "Error pushing remote backup: {e:?}");
+        }
+    }
+
+    pub(crate) fn push(&self) -> Result<(), Error> {
+        info!(self.logger, "pushing remote backup...");
+        let database = self.database.upgrade().ok_or(InternalError::Unexpected)?;
+        // keep other threads from changing the wallet while it's being archived
+        let _operation_guard = database.lock_operations();
+        let txn = database.begin_transaction()?;
+        let prev_backup_info = record_remote_backup_info(&txn)?;
+        txn.commit()?;
+        match self.upload() {
+            Ok(()) => {
+                info!(self.logger, "remote backup pushed");
+                Ok(())
+            }
+            Err(e) => {
+                let txn = database.begin_transaction()?;
+                revert_backup_info(&txn, prev_backup_info)?;
+                txn.commit()?;
+                Err(e)
+            }
+        }
+    }
+
+    fn upload(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().expect("lock poisoned");
+        let res = self.upload_with_state(&mut state);
+        // on failure the stored index is retrieved again and a full backup is pushed next time
+        if res.is_err() {
+            *state = RemoteBackupState::default();
+        }
+        res
+    }
+
+    fn upload_with_state(&self, state: &mut RemoteBackupState) -> Result<(), Error> {
+        let (slot, increments, base_manifest) = match state.chain.take() {
+            Some(chain) if chain.increments < REMOTE_BACKUP_MAX_INCREMENTS => {
+                (chain.slot, chain.increments + 1, Some(chain.manifest))
+            }
+            Some(chain) => (1 - chain.slot, 0, None),
+            None => {
+                let index = self.target.get_object(&self.store_id, REMOTE_BACKUP_KEY)?;
+                let slot = index.as_ref().map_or(0, |o| {
+                    state
+                        .object_versions
+                        .insert(REMOTE_BACKUP_KEY.to_string(), o.version);
+                    serde_json::from_slice::<RemoteBackupIndex>(&o.value)
+                        .map_or(0, |i| 1 - i.slot % 2)
+                });
+                (slot, 0, None)
+            }
+        };
+
+        let tmp_base_path = self.wallet_dir.parent().ok_or(InternalError::Unexpected)?;
+        let files = get_backup_paths(tmp_base_path)?;
+        // incremental backups only include the files whose content changed since the last push
+        let manifest = zip_dir_with_manifest(
+            &self.wallet_dir,
+            &files.zip,
+            true,
+            Some(base_manifest.as_ref()),
+            &self.logger,
+        )?;
+        let cleartext = fs::read(&files.zip)?;
+        let mut nonce = [0u8; REMOTE_BACKUP_NONCE_LENGTH];
+        rand::rng().fill(&mut nonce[..]);
+        let ciphertext = self
+            .aead
+            .encrypt(&XNonce::from(nonce), cleartext.as_slice())
+            .map_err(|_| InternalError::Unexpected)?;
+        let backup_data = RemoteBackupData {
+            version: REMOTE_BACKUP_VERSION,
+            scrypt_params: self.scrypt_params.clone(),
+            salt: self.salt.clone(),
+            nonce: hex::encode(nonce),
+            data: general_purpose::STANDARD.encode(ciphertext),
+        };
+        let value = serde_json::to_vec(&backup_data).map_err(InternalError::from)?;
+        self.put_object(state, &remote_backup_key(slot, increments), value)?;
+
+        // only the index update makes the pushed backup part of the stored one
+        let index = RemoteBackupIndex {
+            version: REMOTE_BACKUP_VERSION,
+            slot,
+            increments,
+        };
+        let value = serde_json::to_vec(&index).map_err(InternalError::from)?;
+        self.put_object(state, REMOTE_BACKUP_KEY, value)?;
+        state.chain = Some(RemoteBackupChain {
+            slot,
+            increments,
+            manifest,
+        });
+        Ok(())
+    }
+
+    fn put_object(
+        &self,
+        state: &mut RemoteBackupState,
+        key: &str,
+        value: Vec<u8>,
+    ) -> Result<(), Error> {
+        let version = match state.object_versions.get(key) {
+            Some(version) => *version,
+            None => self
+                .target
+                .get_object(&self.store_id, key)?
+                .map(|o| o.version)
+                .unwrap_or_default(),
+        };
+        debug!(self.logger, "replacing version {version} of {key}");
+        self.target.put_object(
+            &self.store_id,
+            VersionedObject {
+                key: key.to_string(),
+                version,
+                value,
+            },
+        )?;
+        state.object_versions.insert(key.to_string(), version + 1);
+        Ok(())
+    }
+}
+
+// Decrypt the given backup object, reusing the cipher of the previous object if it has been
+// encrypted with the same key, as deriving it is expensive.
+fn decrypt_remote_backup(
+    value: &[u8],
+    password: &str,
+    cipher: &mut Option<(String, XChaCha20Poly1305)>,
+) -> Result<Vec<u8>, Error> {
+    let backup_data: RemoteBackupData =
+        serde_json::from_slice(value).map_err(|e| Error::RemoteBackup {
+            details: format!("invalid backup: {e}"),
+        })?;
+    if backup_data.version != REMOTE_BACKUP_VERSION {
+        return Err(Error::UnsupportedBackupVersion {
+            version: backup_data.version.to_string(),
+        });
+    }
+    if cipher
+        .as_ref()
+        .is_none_or(|(salt, _)| *salt != backup_data.salt)
+    {
+        let key = derive_key(password, &backup_data.salt, &backup_data.scrypt_params)?;
+        *cipher = Some((backup_data.salt.clone(), XChaCha20Poly1305::new(&key)));
+    }
+    let (_, aead) = cipher.as_ref().expect("cipher has just been set");
+    let nonce: [u8; REMOTE_BACKUP_NONCE_LENGTH] = hex::decode(&backup_data.nonce)
+        .ok()
+        .and_then(|n| n.try_into().ok())
+        .ok_or_else(|| Error::RemoteBackup {
+            details: s!("invalid backup nonce"),
+        })?;
+    let ciphertext = general_purpose::STANDARD
+        .decode(&backup_data.data)
+        .map_err(InternalError::from)?;
+    aead.decrypt(&XNonce::from(nonce), ciphertext.as_slice())
+        .map_err(|_| Error::WrongPassword)
+}
+
+/// Restore the backup of the wallet with the provided master fingerprint from the provided
+/// [`BackupTarget`], decrypting it with the provided password, to the provided target directory.
+pub fn restore_backup_remote(
+    backup_target: &dyn BackupTarget,
+    fingerprint: &str,
+    password: &str,
+    target_dir: &str,
+) -> Result<(), Error> {
+    // setup
+    fs::create_dir_all(target_dir)?;
+    let log_dir = Path::new(&target_dir);
+    let log_name = format!("restore_{}", now().unix_timestamp());
+    let (logger, _logger_guard) = setup_logger(log_dir, Some(&log_name))?;
+    info!(logger, "starting remote restore...");
+    let target_dir_path = PathBuf::from(&target_dir);
+    let wallet_dir = target_dir_path.join(fingerprint);
+    if wallet_dir.exists() {
+        return Err(Error::WalletDirAlreadyExists {
+            path: wallet_dir.to_string_lossy().to_string(),
+        });
+    }
+
+    // retrieve the backup index
+    let object = backup_target
+        .get_object(fingerprint, REMOTE_BACKUP_KEY)?
+        .ok_or(Error::NoRemoteBackup)?;
+    debug!(logger, "retrieved version {} of the backup", object.version);
+    let index: RemoteBackupIndex =
+        serde_json::from_slice(&object.value).map_err(|e| Error::RemoteBackup {
+            details: format!("invalid backup index: {e}"),
+        })?;
+    if index.version != REMOTE_BACKUP_VERSION {
+        return Err(Error::UnsupportedBackupVersion {
+            version: index.version.to_string(),
+        });
+    }
+
+    // replay the backup chain, not leaving a partially restored wallet behind on failure
+    let mut cipher = None;
+    let mut replay = || -> Result<(), Error> {
+        for position in 0..=index.increments {
+            let key = remote_backup_key(index.slot, position);
+            let object = backup_target
+                .get_object(fingerprint, &key)?
+                .ok_or_else(|| Error::RemoteBackup {
+                    details: format!("missing backup {key}"),
+                })?;
+            let cleartext = decrypt_remote_backup(&object.value, password, &mut cipher)?;
+            let files = get_backup_paths(&target_dir_path)?;
+            fs::write(&files.zip, cleartext)?;
+            if get_fingerprint_from_zip(&files.zip)? != fingerprint {
+                return Err(Error::RemoteBackup {
+                    details: s!("backup belongs to another wallet"),
+                });
+            }
+            info!(
+                logger.clone(),
+                "unzipping {:?} to {:?}", &files.zip, &target_dir_path
+            );
+            unzip(&files.zip, &target_dir_path, &logger)?;
+            // the manifest is only needed to replay deletions, as the restored wallet pushes a
+            // full backup first
+            let manifest_path = target_dir_path.join(BACKUP_MANIFEST_FILE);
+            let manifest =
+                read_backup_manifest(&manifest_path)?.ok_or_else(|| Error::RemoteBackup {
+                    details: format!("backup {key} has no manifest"),
+                })?;
+            fs::remove_file(manifest_path)?;
+            if position > 0 {
+                remove_deleted_entries(&target_dir_path, &wallet_dir, &manifest, &logger)?;
+            }
+        }
+        Ok(())
+    };
+    if let Err(e) = replay() {
+        if wallet_dir.exists() {
+            fs::remove_dir_all(&wallet_dir)?;
+        }
+        return Err(e);
+    }
+
+    info!(logger, "remote restore completed");
+    Ok(())
+}
@@ -29,34 +29,48 @@ pub type AssetBeneficiariesMap = BTreeMap<ContractId, Vec<BuilderSeal<GraphSeal>
 
 /// Indexer protocol
 #[derive(Debug, Clone)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 pub enum IndexerProtocol {
+    /// A bitcoind node, queried via its JSON-RPC API
+    #[cfg(feature = "bitcoind")]
+    Bitcoind,
     /// An indexer implementing the electrum protocol
     Electrum,
     /// An indexer implementing the esplora protocol
     Esplora,
 }
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl fmt::Display for IndexerProtocol {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{self:?}")
     }
 }
 
-/// Return the indexer protocol for the provided URL.
+/// Return the indexer protocol for the provided URL, connecting as configured by the provided
+/// [`NetworkOptions`].
 /// An error is raised if the provided indexer URL is invalid or if the service is for the wrong
 /// network or doesn't have the required functionality.
 ///
 /// <div class="warning">This method is meant for special usage and is normally not needed, use
 /// it only if you know what you're doing</div>
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
+#[cfg_attr(
+    not(any(feature = "electrum", feature = "esplora", feature = "bitcoind")),
+    allow(unreachable_code, unused_variables)
+)]
 pub fn check_indexer_url(
     indexer_url: &str,
     bitcoin_network: BitcoinNetwork,
+    network_options: &NetworkOptions,
 ) -> Result<IndexerProtocol, Error> {
-    let (indexer, _) = get_indexer_and_resolver(indexer_url, bitcoin_network)?;
+    let (indexer, _) = get_indexer_and_resolver(indexer_url, bitcoin_network, network_options)?;
     let indexer_protocol = match indexer {
+        #[cfg(feature = "bitcoind")]
+        Indexer::Bitcoind(_) => IndexerProtocol::Bitcoind,
+        #[cfg(feature = "cbf")]
+        Indexer::Cbf(_) => unreachable!("CBF indexers are not built from URLs"),
+        Indexer::Multi(_) => unreachable!("a single URL builds a single indexer"),
         #[cfg(feature = "electrum")]
         Indexer::Electrum(_) => IndexerProtocol::Electrum,
         #[cfg(feature = "esplora")]
@@ -66,15 +80,17 @@ pub fn check_indexer_url(
     Ok(indexer_protocol)
 }
 
-/// Check whether the provided URL points to a valid proxy.
+/// Check whether the provided URL points to a valid proxy, connecting as configured by the provided
+/// [`NetworkOptions`].
 /// An error is raised if the provided proxy URL is invalid or if the service is running an
 /// unsupported protocol version.
 ///
 /// <div class="warning">This method is meant for special usage and is normally not needed, use
 /// it only if you know what you're doing</div>
-#[cfg(any(feature = "electrum", feature = "esplora"))]
-pub fn check_proxy_url(proxy_url: &str) -> Result<(), Error> {
-    check_proxy(proxy_url)
+#[cfg(online)]
+pub fn check_proxy_url(proxy_url: &str, network_options: &NetworkOptions) -> Result<(), Error> {
+    let socks5_proxy = network_options.socks5_proxy(NetworkService::Proxy, None);
+    check_proxy(proxy_url, socks5_proxy.as_ref(), &network_options.policy)
 }
 
 /// Rust-only APIs of the wallet.
@@ -324,7 +340,7 @@ impl Wallet {
     ///
     /// <div class="warning">This method is meant for special usage and is normally not needed, use
     /// it only if you know what you're doing</div>
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub fn accept_transfer_consignment(
         &mut self,
         online: Online,
@@ -427,7 +443,7 @@ impl Wallet {
     ///
     /// <div class="warning">This method is meant for special usage and is normally not needed, use
     /// it only if you know what you're doing</div>
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub fn get_tx_height(&self, online: Online, txid: String) -> Result<Option<u32>, Error> {
         info!(self.logger(), "Getting TX height...");
         self.check_online(online)?;
@@ -440,7 +456,7 @@ impl Wallet {
     ///
     /// <div class="warning">This method is meant for special usage and is normally not needed, use
     /// it only if you know what you're doing</div>
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub fn update_witnesses(
         &self,
         online: Online,
@@ -476,7 +492,7 @@ impl Wallet {
     ///
     /// <div class="warning">This method is meant for special usage and is normally not needed, use
     /// it only if you know what you're doing</div>
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub fn save_new_asset(
         &self,
         online: Online,
@@ -529,7 +545,7 @@ impl Wallet {
     ///
     /// <div class="warning">This method is meant for special usage, for most cases the method
     /// <code>list_unspents</code> is sufficient</div>
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub fn list_unspents_vanilla(
         &mut self,
         online: Online,
@@ -623,7 +639,7 @@ impl Wallet {
     ///
     /// <div class="warning">This method is meant for special usage and is normally not needed, use
     /// it only if you know what you're doing</div>
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     pub fn send_end_db_update_only(
         &mut self,
         online: Online,
@@ -660,11 +676,11 @@ impl Wallet {
 }
 
 #[cfg(test)]
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 mod tests {
     use super::*;
 
-    #[cfg(any(feature = "electrum", feature = "esplora"))]
+    #[cfg(online)]
     #[test]
     fn display_indexer_protocol() {
         assert_eq!(IndexerProtocol::Electrum.to_string(), "Electrum");
@@ -97,6 +97,8 @@ impl SinglesigKeys {
 pub struct Wallet {
     pub(crate) internals: WalletInternals,
     pub(crate) keys: SinglesigKeys,
+    #[cfg(online)]
+    pub(crate) auto_refresh: Option<AutoRefreshHandle>,
 }
 
 impl WalletCore for Wallet {
@@ -113,7 +115,7 @@ impl WalletBackup for Wallet {}
 
 impl WalletOffline for Wallet {}
 
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl WalletOnline for Wallet {
     fn wallet_specific_consistency_checks(&mut self, txn: &DbTxn) -> Result<(), Error> {
         self.sync_wallet(
@@ -161,14 +163,14 @@ impl WalletOnline for Wallet {
 impl RgbWalletOpsOffline for Wallet {}
 
 /// Common online APIs of the wallet.
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl RgbWalletOpsOnline for Wallet {}
 
 /// Offline APIs of the wallet.
 impl Wallet {
     /// Create a new RGB singlesig wallet based on the provided [`WalletData`] and
     /// [`SinglesigKeys`].
-    pub fn new(wallet_data: WalletData, keys: SinglesigKeys) -> Result<Self, Error> {
+    pub fn new(mut wallet_data: WalletData, keys: SinglesigKeys) -> Result<Self, Error> {
         let wdata = wallet_data.clone();
 
         // wallet keys
@@ -181,21 +183,25 @@ impl Wallet {
         // reject settings the wallet wasn't created with before any of them reaches the database
         WalletManifest::check_settings_unchanged(&wallet_dir, &wallet_data, &keys)?;
 
+        // database encryption key
+        let key = WalletEncryption::setup(&wallet_dir, &mut wallet_data)?;
+
         // setup the BDK wallet
-        let (bdk_wallet, bdk_database) = setup_bdk(
+        let bdk = setup_bdk(
             &wdata,
             &wallet_dir,
             descs.colored,
             descs.vanilla,
             watch_only,
             BdkNetwork::from(wdata.bitcoin_network),
+            key.as_ref(),
         )?;
 
         // setup RGB
         setup_rgb(&wallet_dir, wdata.supported_schemas, wdata.bitcoin_network)?;
 
         // setup rgb-lib DB
-        let database = setup_db(&wallet_dir)?;
+        let database = setup_db(&wallet_dir, &wdata.database_type, key.as_ref())?;
 
         // persist the settings needed to load the wallet back
         WalletManifest::new(&wallet_data, &keys).write(&wallet_dir)?;
@@ -205,15 +211,17 @@ impl Wallet {
             internals: WalletInternals {
                 wallet_data,
                 logger,
-                _logger_guard,
+                _logger_guard: Arc::new(_logger_guard),
                 database: Arc::new(database),
                 wallet_dir,
-                bdk_wallet,
-                bdk_database,
-                #[cfg(any(feature = "electrum", feature = "esplora"))]
+                bdk: Arc::new(Mutex::new(bdk)),
+                musig_nonces_lock: Arc::new(Mutex::new(())),
+                #[cfg(online)]
                 online_data: None,
             },
             keys,
+            #[cfg(online)]
+            auto_refresh: None,
         })
     }
 
@@ -222,7 +230,8 @@ impl Wallet {
     ///
     /// The settings are read back from the manifest that [`Wallet::new`] wrote in the wallet
     /// directory, so they don't need to be supplied again. Pass the `mnemonic` to load a wallet
-    /// able to sign, or `None` to load it in watch-only mode.
+    /// able to sign, or `None` to load it in watch-only mode. The `password` is required if the
+    /// wallet databases are encrypted (see [`WalletData::encryption_password`]).
     ///
     /// Wallets created before manifest support, or never opened with [`Wallet::new`] since, have
     /// no manifest and cannot be loaded; call [`Wallet::new`] once to write one.
@@ -230,6 +239,7 @@ impl Wallet {
         data_dir: &str,
         master_fingerprint: &str,
         mnemonic: Option<String>,
+        password: Option<String>,
     ) -> Result<Self, Error> {
         let data_dir_path = Path::new(data_dir);
         if !data_dir_path.exists() {
@@ -242,7 +252,7 @@ impl Wallet {
         if manifest.master_fingerprint != master_fingerprint {
             return Err(Error::FingerprintMismatch);
         }
-        let (wallet_data, keys) = manifest.into_parts(data_dir.to_string(), mnemonic);
+        let (wallet_data, keys) = manifest.into_parts(data_dir.to_string(), mnemonic, password);
         Self::new(wallet_data, keys)
     }
 
@@ -259,6 +269,17 @@ impl Wallet {
             .0
     }
 
+    /// Register a listener that will receive the [`WalletEvent`]s emitted by the wallet,
+    /// replacing any previously registered one. Providing None unregisters the current listener.
+    ///
+    /// Events are emitted when a transfer status changes, when an asset is seen for the first
+    /// time, when a colored UTXO is created or spent and when the refresh of a transfer fails.
+    pub fn set_event_listener(&self, listener: Option<Arc<dyn WalletEventListener>>) {
+        info!(self.logger(), "Setting event listener...");
+        self.database().set_event_listener(listener);
+        info!(self.logger(), "Set event listener completed");
+    }
+
     fn sign_psbt_impl(
         &self,
         psbt: &mut Psbt,
@@ -268,9 +289,62 @@ impl Wallet {
         self.bdk_wallet()
             .sign(psbt, sign_options)
             .map_err(InternalError::from)?;
+        if self.keys.mnemonic.is_some() && musig::has_musig_inputs(psbt) {
+            let secret_keys = self.musig_secret_keys()?;
+            let _lock = self
+                .internals
+                .musig_nonces_lock
+                .lock()
+                .expect("lock poisoned");
+            let store = musig::SecNonceStore::new(self.wallet_dir(), &secret_keys);
+            musig::add_partial_sigs(psbt, &secret_keys, |pub_nonce| store.take(pub_nonce))?;
+        }
         Ok(())
     }
 
+    // the keychain-level keys this wallet contributes to a MuSig2 aggregate key
+    fn musig_secret_keys(&self) -> Result<Vec<SecretKey>, Error> {
+        let mnemonic = self.keys.mnemonic.as_ref().ok_or(Error::WatchOnly)?;
+        let bitcoin_network = self.bitcoin_network();
+        let mut secret_keys = vec![];
+        for (rgb, keychain) in [
+            (true, KEYCHAIN_RGB),
+            (false, self.keys.vanilla_keychain.unwrap_or(KEYCHAIN_BTC)),
+        ] {
+            let (account_xprv, _, _) =
+                get_account_data(&bitcoin_network, mnemonic, rgb, self.keys.witness_version)?;
+            let child = ChildNumber::from_normal_idx(keychain as u32).expect("valid keychain");
+            let keychain_xprv = account_xprv.derive_priv(&Secp256k1::new(), &[child])?;
+            secret_keys.push(keychain_xprv.private_key);
+        }
+        Ok(secret_keys)
+    }
+
+    /// Add MuSig2 public nonces to a multisig PSBT, for the inputs to be spent via the taproot key
+    /// path where this wallet is a participant.
+    ///
+    /// This is the first round of a MuSig2 signing session: once the nonces of all the cosigners
+    /// have been collected in the PSBT, [`sign_psbt`](Wallet::sign_psbt) also adds the partial
+    /// signatures. The secret nonces are stored encrypted in the wallet directory until they're
+    /// used, so signing can happen after the wallet has been reloaded, but they're not included in
+    /// backups: a wallet restored from a backup needs to generate new nonces.
+    ///
+    /// A wallet with private keys is required.
+    pub fn musig_generate_nonces(&self, unsigned_psbt: String) -> Result<String, Error> {
+        info!(self.logger(), "Generating MuSig2 nonces...");
+        let mut psbt = Psbt::from_str(&unsigned_psbt)?;
+        let secret_keys = self.musig_secret_keys()?;
+        let sec_nonces = musig::add_pub_nonces(&mut psbt, &secret_keys)?;
+        let _lock = self
+            .internals
+            .musig_nonces_lock
+            .lock()
+            .expect("lock poisoned");
+        musig::SecNonceStore::new(self.wallet_dir(), &secret_keys).add(sec_nonces)?;
+        info!(self.logger(), "Generate MuSig2 nonces completed");
+        Ok(psbt.to_string())
+    }
+
     /// Sign a PSBT, optionally providing BDK sign options.
     pub fn sign_psbt(
         &self,
@@ -491,8 +565,12 @@ impl Wallet {
     /// An optional asset ID can be specified, which will be embedded in the invoice, resulting in
     /// the refusal of the transfer is the asset doesn't match.
     ///
-    /// An optional amount can be specified, which will be embedded in the invoice. It will not be
-    /// checked when accepting the transfer.
+    /// An optional amount can be specified, which will be embedded in the invoice. The provided
+    /// [`ReceivePolicy`] determines how it's checked when accepting the transfer: a consignment
+    /// that doesn't pay it as the policy requires is refused while refreshing and the transfer
+    /// fails. A policy other than [`ReceivePolicy::Any`] requires an amount (or
+    /// [`Assignment::NonFungible`] for UDA assets). Payment request lines are treated as
+    /// [`ReceivePolicy::AtLeast`] unless a stricter policy is provided.
     ///
     /// An expiration UTC timestamp must be specified, which will set the expiration of the
     /// invoice and the transfer.
@@ -516,6 +594,7 @@ impl Wallet {
         &mut self,
         asset_id: Option<String>,
         assignment: Assignment,
+        receive_policy: ReceivePolicy,
         expiration_timestamp: u64,
         transport_endpoints: Vec<String>,
         min_confirmations: u8,
@@ -526,6 +605,7 @@ impl Wallet {
             asset_id,
             expiration_timestamp,
         );
+        self.check_receive_policy(&assignment, receive_policy)?;
         let txn = self.database().begin_transaction()?;
         let receive_data_internal = self.create_receive_data(
             &txn,
@@ -535,8 +615,12 @@ impl Wallet {
             transport_endpoints,
             RecipientType::Blind,
         )?;
-        let batch_transfer_idx =
-            self.store_receive_transfer(&txn, &receive_data_internal, min_confirmations)?;
+        let batch_transfer_idx = self.store_receive_transfer(
+            &txn,
+            &receive_data_internal,
+            receive_policy,
+            min_confirmations,
+        )?;
         self.update_backup_info(&txn, false)?;
         txn.commit()?;
         info!(self.logger(), "Blind receive completed");
@@ -553,8 +637,12 @@ impl Wallet {
     /// An optional asset ID can be specified, which will be embedded in the invoice, resulting in
     /// the refusal of the transfer is the asset doesn't match.
     ///
-    /// An optional amount can be specified, which will be embedded in the invoice. It will not be
-    /// checked when accepting the transfer.
+    /// An optional amount can be specified, which will be embedded in the invoice. The provided
+    /// [`ReceivePolicy`] determines how it's checked when accepting the transfer: a consignment
+    /// that doesn't pay it as the policy requires is refused while refreshing and the transfer
+    /// fails. A policy other than [`ReceivePolicy::Any`] requires an amount (or
+    /// [`Assignment::NonFungible`] for UDA assets). Payment request lines are treated as
+    /// [`ReceivePolicy::AtLeast`] unless a stricter policy is provided.
     ///
     /// An expiration UTC timestamp must be specified, which will set the expiration of the
     /// invoice and the transfer.
@@ -578,6 +666,7 @@ impl Wallet {
         &mut self,
         asset_id: Option<String>,
         assignment: Assignment,
+        receive_policy: ReceivePolicy,
         expiration_timestamp: u64,
         transport_endpoints: Vec<String>,
         min_confirmations: u8,
@@ -588,6 +677,7 @@ impl Wallet {
             asset_id,
             expiration_timestamp,
         );
+        self.check_receive_policy(&assignment, receive_policy)?;
         let txn = self.database().begin_transaction()?;
         let receive_data_internal = self.create_receive_data(
             &txn,
@@ -597,8 +687,12 @@ impl Wallet {
             transport_endpoints,
             RecipientType::Witness,
         )?;
-        let batch_transfer_idx =
-            self.store_receive_transfer(&txn, &receive_data_internal, min_confirmations)?;
+        let batch_transfer_idx = self.store_receive_transfer(
+            &txn,
+            &receive_data_internal,
+            receive_policy,
+            min_confirmations,
+        )?;
         self.update_backup_info(&txn, false)?;
         txn.commit()?;
         info!(self.logger(), "Witness receive completed");
@@ -609,10 +703,34 @@ impl Wallet {
             batch_transfer_idx,
         })
     }
+
+    /// Bundle the provided [`ReceiveData`], obtained from [`blind_receive`](Wallet::blind_receive)
+    /// and [`witness_receive`](Wallet::witness_receive), into a [`PaymentRequest`] that can be paid
+    /// with a single batch transfer (see [`send_payment_request`](Wallet::send_payment_request)).
+    ///
+    /// Each receive becomes a line of the payment request. All receives need to be pending, to
+    /// specify an asset ID and an amount (or [`Assignment::NonFungible`] for UDA assets) and to
+    /// share the same transport endpoints. The payment request expires with the earliest of them.
+    ///
+    /// While refreshing, the wallet will ACK the transfer of a line only once all the lines have
+    /// been paid as requested by the same transaction. If any line can't be satisfied (e.g. its
+    /// consignment is invalid or it has failed), the transfers of all the other lines are refused.
+    pub fn create_payment_request(
+        &self,
+        receive_data: Vec<ReceiveData>,
+    ) -> Result<PaymentRequest, Error> {
+        info!(self.logger(), "Creating payment request...");
+        let txn = self.database().begin_transaction()?;
+        let payment_request = self.create_payment_request_impl(&txn, receive_data)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Create payment request completed");
+        Ok(payment_request)
+    }
 }
 
 /// Online APIs of the wallet.
-#[cfg(any(feature = "electrum", feature = "esplora"))]
+#[cfg(online)]
 impl Wallet {
     pub(crate) fn watch_only(&self) -> bool {
         self.keys.mnemonic.is_none()
@@ -730,11 +848,36 @@ impl Wallet {
     /// See [`OnlineOptions`] for details on the available options.
     pub fn go_online(&mut self, online_options: OnlineOptions) -> Result<Online, Error> {
         info!(self.logger(), "Going online...");
+        self.auto_refresh = None;
         let online = self.go_online_impl(&online_options)?;
+        if let Some(auto_refresh_options) = online_options.auto_refresh {
+            let mut wallet = Wallet {
+                internals: self.internals.share(),
+                keys: self.keys.clone(),
+                auto_refresh: None,
+            };
+            let (wallet_online, online_data) = wallet.get_online_data(&online_options)?;
+            *wallet.online_data_mut() = Some(online_data);
+            self.auto_refresh = Some(AutoRefreshHandle::start(
+                wallet,
+                wallet_online,
+                auto_refresh_options,
+            ));
+        }
         info!(self.logger(), "Go online completed");
         Ok(online)
     }
 
+    /// Stop the background auto-refresh task started by [`go_online`](Wallet::go_online), if
+    /// any.
+    ///
+    /// This waits for a run in progress to complete.
+    pub fn stop_auto_refresh(&mut self) {
+        info!(self.logger(), "Stopping auto-refresh...");
+        self.auto_refresh = None;
+        info!(self.logger(), "Stop auto-refresh completed");
+    }
+
     /// Send bitcoin funds to the provided address.
     ///
     /// This calls [`drain_to_begin`](Wallet::drain_to_begin), signs the resulting PSBT and finally
@@ -816,6 +959,134 @@ impl Wallet {
         Ok(tx.compute_txid().to_string())
     }
 
+    /// Migrate all RGB assets and bitcoin funds to another wallet.
+    ///
+    /// This calls [`migrate_to_begin`](Wallet::migrate_to_begin), signs the resulting PSBT and
+    /// finally calls [`migrate_to_end`](Wallet::migrate_to_end).
+    ///
+    /// A wallet with private keys is required.
+    pub fn migrate_to(
+        &mut self,
+        online: Online,
+        invoices: Vec<String>,
+        address: String,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<OperationResult, Error> {
+        info!(self.logger(), "Migrating to '{}'...", address);
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let mut begin_op_data =
+            self.migrate_to_begin_impl(&txn, invoices, address, fee_rate, min_confirmations, true)?;
+        self.sign_psbt_impl(&mut begin_op_data.psbt, None)?;
+        let res = self.migrate_to_end_impl(&txn, &begin_op_data.psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Migrate completed");
+        Ok(res)
+    }
+
+    /// Prepare the PSBT to migrate all RGB assets and bitcoin funds to another wallet, with the
+    /// provided `fee_rate` (in sat/vB).
+    ///
+    /// <div class="warning">Warning: migrating is meant to move the wallet funds to a new wallet
+    /// of the same owner (e.g. to switch to a different witness version or to rotate a mnemonic).
+    /// After migrating the wallet will be left without funds.</div>
+    ///
+    /// The `invoices`, generated by the destination wallet, need to specify an asset ID and
+    /// should not specify an amount. All spendable settled allocations of each asset are sent to
+    /// the invoice for that asset, with an additional invoice with an
+    /// [`Assignment::InflationRight`] assignment needed for assets with inflation rights. Invoices
+    /// for assets with nothing to migrate are ignored. Allocations that are not spendable yet
+    /// (e.g. pending incoming ones) are left in the wallet.
+    ///
+    /// All assets are moved with a single batch transfer which also spends all the other
+    /// spendable UTXOs, sending the remaining bitcoins to the provided `address`. The transfer is
+    /// a donation, so [`migrate_to_end`](Wallet::migrate_to_end) broadcasts it right away and the
+    /// consignments are posted to the invoices' transport endpoints or, for out-of-band invoices,
+    /// left in the transfer directory to be provided to the destination wallet.
+    ///
+    /// The `min_confirmations` number has the same meaning as in
+    /// [`send_begin`](Wallet::send_begin). The transfer expiration is set to the earliest one of
+    /// the provided invoices.
+    ///
+    /// If `dry_run` is true, the wallet does not persist the transfer in
+    /// [`TransferStatus::Initiated`] and does not reserve the spent vanilla TXOs. The returned
+    /// [`SendBeginResult::batch_transfer_idx`] is None in that case.
+    ///
+    /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
+    /// to be fed to the [`migrate_to_end`](Wallet::migrate_to_end) function.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a PSBT ready to be signed and operation details.
+    pub fn migrate_to_begin(
+        &mut self,
+        online: Online,
+        invoices: Vec<String>,
+        address: String,
+        fee_rate: u64,
+        min_confirmations: u8,
+        dry_run: bool,
+    ) -> Result<SendBeginResult, Error> {
+        info!(self.logger(), "Migrating (begin) to '{}'...", address);
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let begin_op_data = self.migrate_to_begin_impl(
+            &txn,
+            invoices,
+            address,
+            fee_rate,
+            min_confirmations,
+            dry_run,
+        )?;
+        if !dry_run {
+            self.update_backup_info(&txn, false)?;
+        }
+        txn.commit()?;
+        info!(self.logger(), "Migrate (begin) completed");
+        Ok(SendBeginResult {
+            psbt: begin_op_data.psbt.to_string(),
+            batch_transfer_idx: begin_op_data.batch_transfer_idx,
+            details: SendDetails {
+                fascia_path: begin_op_data
+                    .transfer_dir
+                    .join(FASCIA_FILE)
+                    .to_string_lossy()
+                    .to_string(),
+                min_confirmations,
+                entropy: begin_op_data.info_batch_transfer.entropy,
+                is_donation: true,
+            },
+        })
+    }
+
+    /// Complete the migrate operation by saving the PSBT to disk, POSTing consignments to the RGB
+    /// proxy server, saving the transfer to DB and broadcasting the provided PSBT.
+    ///
+    /// The provided PSBT, prepared with the [`migrate_to_begin`](Wallet::migrate_to_begin)
+    /// function, needs to have already been signed.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a [`OperationResult`].
+    pub fn migrate_to_end(
+        &mut self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<OperationResult, Error> {
+        info!(self.logger(), "Migrating (end)...");
+        self.check_online(online)?;
+        let psbt = Psbt::from_str(&signed_psbt)?;
+        let txn = self.database().begin_transaction()?;
+        let res = self.migrate_to_end_impl(&txn, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Migrate (end) completed");
+        Ok(res)
+    }
+
     /// Send RGB assets.
     ///
     /// This calls [`send_begin`](Wallet::send_begin), signs the resulting PSBT and finally calls
@@ -842,6 +1113,8 @@ impl Wallet {
             fee_rate,
             min_confirmations,
             Some(expiration_timestamp as i64),
+            CoinSelectionOptions::default(),
+            None,
             true,
         )?;
         self.sign_psbt_impl(&mut begin_op_data.psbt, None)?;
@@ -878,6 +1151,10 @@ impl Wallet {
     /// received. In case of a batch transfer, set it to the minimum (earliest) expiration across
     /// the recipients' invoices.
     ///
+    /// The optional `coin_selection` drives which UTXOs hosting the assets to be spent get selected
+    /// as inputs: a [`CoinSelectionStrategy`] (defaulting to
+    /// [`CoinSelectionStrategy::SmallestFirst`]) plus outpoints that must or must not be spent.
+    ///
     /// If `dry_run` is true, the wallet does not persist the transfer in
     /// [`TransferStatus::Initiated`]. The returned [`SendBeginResult::batch_transfer_idx`] is None
     /// in that case. The PSBT and on-disk transfer data under the wallet directory are still
@@ -901,6 +1178,7 @@ impl Wallet {
         fee_rate: u64,
         min_confirmations: u8,
         expiration_timestamp: u64,
+        coin_selection: Option<CoinSelectionOptions>,
         dry_run: bool,
     ) -> Result<SendBeginResult, Error> {
         info!(self.logger(), "Sending (begin) to: {:?}...", recipient_map);
@@ -913,6 +1191,8 @@ impl Wallet {
             fee_rate,
             min_confirmations,
             Some(expiration_timestamp as i64),
+            coin_selection.unwrap_or_default(),
+            None,
             dry_run,
         )?;
         if !dry_run {
@@ -961,6 +1241,104 @@ impl Wallet {
         Ok(res)
     }
 
+    /// Pay an RGB payment request.
+    ///
+    /// This calls [`send_payment_request_begin`](Wallet::send_payment_request_begin), signs the
+    /// resulting PSBT and finally calls [`send_end`](Wallet::send_end).
+    ///
+    /// A wallet with private keys is required.
+    pub fn send_payment_request(
+        &mut self,
+        online: Online,
+        payment_request: String,
+        donation: bool,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<OperationResult, Error> {
+        info!(self.logger(), "Sending to payment request...");
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let mut begin_op_data = self.send_payment_request_begin_impl(
+            &txn,
+            payment_request,
+            donation,
+            fee_rate,
+            min_confirmations,
+            CoinSelectionOptions::default(),
+            true,
+        )?;
+        self.sign_psbt_impl(&mut begin_op_data.psbt, None)?;
+        let res = self.send_end_impl(&txn, &begin_op_data.psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Send to payment request completed");
+        Ok(res)
+    }
+
+    /// Prepare the PSBT to pay the provided RGB [`PaymentRequest`] string, with the provided
+    /// `fee_rate` (in sat/vB).
+    ///
+    /// All the lines of the payment request are paid with a single batch transfer, sending each
+    /// requested assignment to its recipient. Witness recipients are sent the minimum non-dust
+    /// bitcoin amount for their script. The transfer expiration is set to the one of the payment
+    /// request.
+    ///
+    /// The recipient will ACK the transfer only if all the lines are paid, so a transfer that is
+    /// not a donation is broadcast only in that case.
+    ///
+    /// The `donation`, `min_confirmations`, `coin_selection` and `dry_run` parameters have the
+    /// same meaning as in [`send_begin`](Wallet::send_begin).
+    ///
+    /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
+    /// to be fed to the [`send_end`](Wallet::send_end) function to complete the send operation.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a PSBT ready to be signed and operation details.
+    pub fn send_payment_request_begin(
+        &mut self,
+        online: Online,
+        payment_request: String,
+        donation: bool,
+        fee_rate: u64,
+        min_confirmations: u8,
+        coin_selection: Option<CoinSelectionOptions>,
+        dry_run: bool,
+    ) -> Result<SendBeginResult, Error> {
+        info!(self.logger(), "Sending (begin) to payment request...");
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let begin_op_data = self.send_payment_request_begin_impl(
+            &txn,
+            payment_request,
+            donation,
+            fee_rate,
+            min_confirmations,
+            coin_selection.unwrap_or_default(),
+            dry_run,
+        )?;
+        if !dry_run {
+            self.update_backup_info(&txn, false)?;
+        }
+        txn.commit()?;
+        info!(self.logger(), "Send (begin) to payment request completed");
+        Ok(SendBeginResult {
+            psbt: begin_op_data.psbt.to_string(),
+            batch_transfer_idx: begin_op_data.batch_transfer_idx,
+            details: SendDetails {
+                fascia_path: begin_op_data
+                    .transfer_dir
+                    .join(FASCIA_FILE)
+                    .to_string_lossy()
+                    .to_string(),
+                min_confirmations,
+                entropy: begin_op_data.info_batch_transfer.entropy,
+                is_donation: donation,
+            },
+        })
+    }
+
     /// Receive an RGB transfer whose consignment was exchanged out-of-band, without using automated
     /// transport endpoints.
     ///
@@ -982,6 +1360,12 @@ impl Wallet {
     /// to [`TransferStatus::WaitingConfirmations`], otherwise it moves to
     /// [`TransferStatus::WaitingBroadcast`], leaving the ACK to be communicated to the sender
     /// out-of-band. An invalid consignment fails the transfer locally, without any proxy NACK.
+    ///
+    /// A consignment anchored to a transaction that replaced (see [`bump_fee`](Wallet::bump_fee))
+    /// the one of an incoming transfer already in the [`TransferStatus::WaitingBroadcast`] or
+    /// [`TransferStatus::WaitingConfirmations`] status is also accepted, also for transfers using
+    /// transport endpoints, as long as the replaced transaction has not been confirmed. In this
+    /// case an invalid consignment is rejected without failing the transfer.
     pub fn provide_out_of_band_consignment(
         &mut self,
         online: Online,
@@ -999,87 +1383,271 @@ impl Wallet {
         Ok(res)
     }
 
-    /// Record the out-of-band ACK for an out-of-band recipient of an outgoing
-    /// [`TransferStatus::WaitingCounterparty`] batch transfer, identified by its `recipient_id`,
-    /// after the ACK has been received out-of-band.
-    ///
-    /// This is the out-of-band counterpart of the proxy ACK flow driven by [`refresh`](Wallet::refresh).
-    /// The batch is broadcast only once *every* recipient has ACKed (via this call for the
-    /// out-of-band ones and via `refresh` for the proxy ones), so a batch may freely mix out-of-band
-    /// and proxy recipients. Recipients using the JSON-RPC proxy are rejected here, keeping the
-    /// `refresh` flow authoritative for them.
-    ///
-    /// Returns the broadcast [`OperationResult`] (transfer moved to
-    /// [`TransferStatus::WaitingConfirmations`]) when this ACK completed the batch, or `None` when
-    /// the batch still has recipients waiting to ACK.
-    pub fn provide_out_of_band_ack(
+    /// Create a [`SwapOffer`] to atomically exchange the provided `assignment` of the asset with
+    /// the provided `asset_id` for what the taker pays, described by `taker_leg`.
+    ///
+    /// The offer contains a PSBT spending the maker's asset, with an OP_RETURN output that will
+    /// anchor the RGB transitions of both parties and, if the taker pays with an asset, an output
+    /// receiving it. The outputs of the taker will be appended to the ones of the maker. A
+    /// [`TransferKind::Swap`] transfer receiving the taker's asset is created, refusing anything
+    /// different from what has been requested.
+    ///
+    /// The offer can be taken until `expiration_timestamp`. Until then, the maker's UTXOs are
+    /// reserved by a pending vanilla transaction of type [`WalletTransactionType::SwapOffer`],
+    /// having the TXID of the offer PSBT. The offer can be withdrawn by aborting it with
+    /// [`abort_pending_vanilla_tx`](Wallet::abort_pending_vanilla_tx), after which it can't be
+    /// completed anymore.
+    ///
+    /// The offer then needs to be taken by the counterparty with
+    /// [`swap_offer_take`](Wallet::swap_offer_take).
+    pub fn swap_offer_create(
         &mut self,
         online: Online,
-        recipient_id: String,
-    ) -> Result<Option<OperationResult>, Error> {
-        info!(
-            self.logger(),
-            "Providing out-of-band ACK for recipient {recipient_id}..."
-        );
+        asset_id: String,
+        assignment: Assignment,
+        taker_leg: SwapLeg,
+        expiration_timestamp: u64,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<SwapOffer, Error> {
+        info!(self.logger(), "Creating swap offer...");
+        self.check_xprv()?;
         self.check_online(online)?;
         let txn = self.database().begin_transaction()?;
-        let res = self.provide_out_of_band_ack_impl(&txn, recipient_id)?;
+        let swap_offer = self.swap_offer_create_impl(
+            &txn,
+            asset_id,
+            assignment,
+            taker_leg,
+            expiration_timestamp,
+            fee_rate,
+            min_confirmations,
+        )?;
         self.update_backup_info(&txn, false)?;
         txn.commit()?;
-        info!(self.logger(), "Provide out-of-band ACK completed");
-        Ok(res)
+        info!(self.logger(), "Create swap offer completed");
+        Ok(swap_offer)
     }
 
-    /// Send bitcoins using the vanilla wallet.
+    /// Take the provided [`SwapOffer`] string, in the [`SwapStage::Offered`] stage, completing the
+    /// swap PSBT with the taker's inputs, outputs and RGB transitions, paying with the provided
+    /// `fee_rate` (in sat/vB) the taker's share of the transaction fee.
     ///
-    /// This calls [`send_btc_begin`](Wallet::send_btc_begin), signs the resulting PSBT and finally
-    /// calls [`send_btc_end`](Wallet::send_btc_end).
+    /// A [`TransferKind::Swap`] transfer receiving the maker's asset is created, refusing anything
+    /// different from what has been offered, and the UTXOs spent by the taker are reserved.
     ///
-    /// A wallet with private keys and [`Online`] data are required.
-    pub fn send_btc(
+    /// The returned offer, in the [`SwapStage::Taken`] stage, needs to be sent back to the maker,
+    /// who will complete it with [`swap_offer_complete`](Wallet::swap_offer_complete).
+    pub fn swap_offer_take(
         &mut self,
         online: Online,
-        address: String,
-        amount: u64,
+        swap_offer: String,
         fee_rate: u64,
-        skip_sync: bool,
-    ) -> Result<String, Error> {
-        info!(self.logger(), "Sending BTC...");
+        min_confirmations: u8,
+    ) -> Result<SwapOffer, Error> {
+        info!(self.logger(), "Taking swap offer...");
         self.check_xprv()?;
         self.check_online(online)?;
         let txn = self.database().begin_transaction()?;
-        let mut psbt =
-            self.send_btc_begin_impl(&txn, address, amount, fee_rate, skip_sync, true)?;
-        self.sign_psbt_impl(&mut psbt, None)?;
-        let res = self.send_btc_end_impl(&txn, &psbt)?;
+        let swap_offer =
+            self.swap_offer_take_impl(&txn, swap_offer, fee_rate, min_confirmations)?;
         self.update_backup_info(&txn, false)?;
         txn.commit()?;
-        info!(self.logger(), "Send BTC completed");
-        Ok(res)
+        info!(self.logger(), "Take swap offer completed");
+        Ok(swap_offer)
     }
 
-    /// Prepare the PSBT to send the specified `amount` of bitcoins (in sats) using the vanilla
-    /// wallet to the specified Bitcoin `address` with the specified `fee_rate` (in sat/vB).
+    /// Complete the provided [`SwapOffer`] string, after validating the counterparty's side of the
+    /// swap.
     ///
-    /// If `dry_run` is true, the wallet does not reserve the selected vanilla TXOs. The returned
-    /// PSBT can still be signed and completed with [`send_btc_end`](Wallet::send_btc_end) but
-    /// concurrent vanilla operations may try to spend the same inputs.
-    ///
-    /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
-    /// to be fed to the [`send_btc_end`](Wallet::send_btc_end) function.
-    ///
-    /// This doesn't require the wallet to have private keys.
+    /// Called by the maker on a [`SwapStage::Taken`] offer, it checks the taker has not altered the
+    /// maker's side of the swap and pays what has been requested, then signs the maker's inputs.
+    /// The returned offer, in the [`SwapStage::Signed`] stage, needs to be sent back to the taker.
     ///
-    /// Returns a PSBT ready to be signed.
-    pub fn send_btc_begin(
+    /// Called by the taker on a [`SwapStage::Signed`] offer, it checks the maker's consignment,
+    /// then signs the taker's inputs and broadcasts the swap transaction, returning the offer in
+    /// the [`SwapStage::Broadcast`] stage. The maker will detect the broadcast while refreshing.
+    pub fn swap_offer_complete(
         &mut self,
         online: Online,
-        address: String,
-        amount: u64,
-        fee_rate: u64,
-        skip_sync: bool,
-        dry_run: bool,
-    ) -> Result<String, Error> {
+        swap_offer: String,
+    ) -> Result<SwapOffer, Error> {
+        info!(self.logger(), "Completing swap offer...");
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let (swap_offer, mut psbt) = self.swap_offer_complete_begin_impl(&txn, swap_offer)?;
+        self.sign_psbt_impl(&mut psbt, None)?;
+        let swap_offer = self.swap_offer_complete_end_impl(&txn, swap_offer, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Complete swap offer completed");
+        Ok(swap_offer)
+    }
+
+    /// Create a [`SellOrder`], offering the provided `assignment` of the RGB asset with the
+    /// provided `asset_id` to any buyer paying the provided `price` (in sats).
+    ///
+    /// The sold assignment needs to be held by a single UTXO, which is signed with
+    /// `SIGHASH_SINGLE|ANYONECANPAY` together with the output receiving the input amount plus the
+    /// price. The RGB transition and consignment are prepared for a buyer output that will be added
+    /// when the order is filled, so the buyer can complete the transaction alone, also paying all
+    /// its fees.
+    ///
+    /// Since the signature doesn't cover the OP_RETURN output committing to the RGB transition,
+    /// the UTXO can't hold anything besides the sold assignment, or the buyer could take it as
+    /// well. Sending the assignment to a new UTXO of the wallet first is needed to sell part of an
+    /// allocation.
+    ///
+    /// An outgoing transfer in status [`TransferStatus::WaitingBroadcast`] reserves the UTXO until
+    /// the buyer broadcasts the transaction, which will be detected while refreshing. Since the
+    /// signed input stays valid as long as the UTXO is unspent, the order can be withdrawn only by
+    /// failing the transfer, once expired, and then spending the UTXO.
+    pub fn sell_order_create(
+        &mut self,
+        online: Online,
+        asset_id: String,
+        assignment: Assignment,
+        price: u64,
+        expiration_timestamp: u64,
+        min_confirmations: u8,
+    ) -> Result<SellOrder, Error> {
+        info!(self.logger(), "Creating sell order...");
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let (sell_order, mut psbt) = self.sell_order_create_begin_impl(
+            &txn,
+            asset_id,
+            assignment,
+            price,
+            expiration_timestamp,
+            min_confirmations,
+        )?;
+        self.sign_psbt_impl(
+            &mut psbt,
+            Some(SignOptions {
+                allow_all_sighashes: true,
+                ..Default::default()
+            }),
+        )?;
+        let sell_order = self.sell_order_create_end_impl(sell_order, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Create sell order completed");
+        Ok(sell_order)
+    }
+
+    /// Fill the provided [`SellOrder`] string, paying its price plus the transaction fee, with
+    /// the provided `fee_rate` (in sat/vB), from the vanilla wallet.
+    ///
+    /// The seller's signed transaction is completed with the buyer's inputs and an output receiving
+    /// the sold asset, then the seller's consignment is validated against the completed
+    /// transaction, refusing anything different from what the order offers. The transaction is
+    /// then signed and broadcast, with the incoming transfer moving to
+    /// [`TransferStatus::WaitingConfirmations`].
+    pub fn sell_order_take(
+        &mut self,
+        online: Online,
+        sell_order: String,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<OperationResult, Error> {
+        info!(self.logger(), "Taking sell order...");
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let (receive_batch_transfer, mut psbt) =
+            self.sell_order_take_begin_impl(&txn, sell_order, fee_rate, min_confirmations)?;
+        self.sign_psbt_impl(&mut psbt, None)?;
+        let res = self.sell_order_take_end_impl(&txn, receive_batch_transfer, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Take sell order completed");
+        Ok(res)
+    }
+
+    /// Record the out-of-band ACK for an out-of-band recipient of an outgoing
+    /// [`TransferStatus::WaitingCounterparty`] batch transfer, identified by its `recipient_id`,
+    /// after the ACK has been received out-of-band.
+    ///
+    /// This is the out-of-band counterpart of the proxy ACK flow driven by [`refresh`](Wallet::refresh).
+    /// The batch is broadcast only once *every* recipient has ACKed (via this call for the
+    /// out-of-band ones and via `refresh` for the proxy ones), so a batch may freely mix out-of-band
+    /// and proxy recipients. Recipients using the JSON-RPC proxy are rejected here, keeping the
+    /// `refresh` flow authoritative for them.
+    ///
+    /// Returns the broadcast [`OperationResult`] (transfer moved to
+    /// [`TransferStatus::WaitingConfirmations`]) when this ACK completed the batch, or `None` when
+    /// the batch still has recipients waiting to ACK.
+    pub fn provide_out_of_band_ack(
+        &mut self,
+        online: Online,
+        recipient_id: String,
+    ) -> Result<Option<OperationResult>, Error> {
+        info!(
+            self.logger(),
+            "Providing out-of-band ACK for recipient {recipient_id}..."
+        );
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let res = self.provide_out_of_band_ack_impl(&txn, recipient_id)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Provide out-of-band ACK completed");
+        Ok(res)
+    }
+
+    /// Send bitcoins using the vanilla wallet.
+    ///
+    /// This calls [`send_btc_begin`](Wallet::send_btc_begin), signs the resulting PSBT and finally
+    /// calls [`send_btc_end`](Wallet::send_btc_end).
+    ///
+    /// A wallet with private keys and [`Online`] data are required.
+    pub fn send_btc(
+        &mut self,
+        online: Online,
+        address: String,
+        amount: u64,
+        fee_rate: u64,
+        skip_sync: bool,
+    ) -> Result<String, Error> {
+        info!(self.logger(), "Sending BTC...");
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let mut psbt =
+            self.send_btc_begin_impl(&txn, address, amount, fee_rate, skip_sync, true)?;
+        self.sign_psbt_impl(&mut psbt, None)?;
+        let res = self.send_btc_end_impl(&txn, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Send BTC completed");
+        Ok(res)
+    }
+
+    /// Prepare the PSBT to send the specified `amount` of bitcoins (in sats) using the vanilla
+    /// wallet to the specified Bitcoin `address` with the specified `fee_rate` (in sat/vB).
+    ///
+    /// If `dry_run` is true, the wallet does not reserve the selected vanilla TXOs. The returned
+    /// PSBT can still be signed and completed with [`send_btc_end`](Wallet::send_btc_end) but
+    /// concurrent vanilla operations may try to spend the same inputs.
+    ///
+    /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
+    /// to be fed to the [`send_btc_end`](Wallet::send_btc_end) function.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a PSBT ready to be signed.
+    pub fn send_btc_begin(
+        &mut self,
+        online: Online,
+        address: String,
+        amount: u64,
+        fee_rate: u64,
+        skip_sync: bool,
+        dry_run: bool,
+    ) -> Result<String, Error> {
         info!(self.logger(), "Sending BTC (begin)...");
         self.check_online(online)?;
         let txn = self.database().begin_transaction()?;
@@ -1112,6 +1680,146 @@ impl Wallet {
         Ok(res)
     }
 
+    /// Accelerate the pending transaction with the provided `txid` by spending one of its outputs
+    /// with a child transaction (CPFP) paying for the whole package at the specified `fee_rate`
+    /// (in sat/vB).
+    ///
+    /// This calls [`cpfp_begin`](Wallet::cpfp_begin), signs the resulting PSBT and finally calls
+    /// [`cpfp_end`](Wallet::cpfp_end).
+    ///
+    /// A wallet with private keys is required.
+    pub fn cpfp(&mut self, online: Online, txid: String, fee_rate: u64) -> Result<String, Error> {
+        info!(self.logger(), "CPFP for TX {}...", txid);
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let mut psbt = self.cpfp_begin_impl(&txn, txid, fee_rate, true)?;
+        self.sign_psbt_impl(&mut psbt, None)?;
+        let res = self.cpfp_end_impl(&txn, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "CPFP completed");
+        Ok(res)
+    }
+
+    /// Accelerate the pending transaction anchoring the transfer with the provided
+    /// `batch_transfer_idx`, like [`cpfp`](Wallet::cpfp) does for a transaction given by its TXID.
+    ///
+    /// A wallet with private keys is required.
+    pub fn cpfp_transfer(
+        &mut self,
+        online: Online,
+        batch_transfer_idx: i32,
+        fee_rate: u64,
+    ) -> Result<String, Error> {
+        info!(self.logger(), "CPFP for transfer {}...", batch_transfer_idx);
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let txid = self.get_cpfp_transfer_txid(&txn, batch_transfer_idx)?;
+        let mut psbt = self.cpfp_begin_impl(&txn, txid, fee_rate, true)?;
+        self.sign_psbt_impl(&mut psbt, None)?;
+        let res = self.cpfp_end_impl(&txn, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "CPFP completed");
+        Ok(res)
+    }
+
+    /// Prepare the PSBT of a child transaction (CPFP) accelerating the pending transaction with
+    /// the provided `txid`, so that parent and child together pay the specified `fee_rate` (in
+    /// sat/vB).
+    ///
+    /// The child spends an unspent wallet output of the parent that holds no RGB allocations (the
+    /// largest one if more are available), adding vanilla inputs if needed and sending the change
+    /// to the vanilla wallet.
+    ///
+    /// If no such output is available (e.g. for a witness receive or a send without BTC change),
+    /// the child spends an output holding RGB allocations and re-assigns all of them to a new
+    /// UTXO of the wallet, adding colored inputs if needed. This colored child is saved as a
+    /// [`TransferKind::Consolidation`] transfer and can be built only once the parent transfer
+    /// is in the [`TransferStatus::WaitingConfirmations`] status (i.e. after the consignment of
+    /// an incoming transfer has been validated with [`refresh`](Wallet::refresh)).
+    ///
+    /// If `dry_run` is true, the wallet does not reserve the selected TXOs. The returned PSBT can
+    /// still be signed and completed with [`cpfp_end`](Wallet::cpfp_end) but concurrent
+    /// operations may try to spend the same inputs.
+    ///
+    /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
+    /// to be fed to the [`cpfp_end`](Wallet::cpfp_end) function.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a PSBT ready to be signed.
+    pub fn cpfp_begin(
+        &mut self,
+        online: Online,
+        txid: String,
+        fee_rate: u64,
+        dry_run: bool,
+    ) -> Result<String, Error> {
+        info!(self.logger(), "CPFP for TX {} (begin)...", txid);
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let res = self.cpfp_begin_impl(&txn, txid, fee_rate, dry_run)?;
+        if !dry_run {
+            self.update_backup_info(&txn, false)?;
+        }
+        txn.commit()?;
+        info!(self.logger(), "CPFP (begin) completed");
+        Ok(res.to_string())
+    }
+
+    /// Prepare the PSBT of a child transaction (CPFP) accelerating the pending transaction
+    /// anchoring the transfer with the provided `batch_transfer_idx`, like
+    /// [`cpfp_begin`](Wallet::cpfp_begin) does for a transaction given by its TXID.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a PSBT ready to be signed.
+    pub fn cpfp_transfer_begin(
+        &mut self,
+        online: Online,
+        batch_transfer_idx: i32,
+        fee_rate: u64,
+        dry_run: bool,
+    ) -> Result<String, Error> {
+        info!(
+            self.logger(),
+            "CPFP for transfer {} (begin)...", batch_transfer_idx
+        );
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let txid = self.get_cpfp_transfer_txid(&txn, batch_transfer_idx)?;
+        let res = self.cpfp_begin_impl(&txn, txid, fee_rate, dry_run)?;
+        if !dry_run {
+            self.update_backup_info(&txn, false)?;
+        }
+        txn.commit()?;
+        info!(self.logger(), "CPFP (begin) completed");
+        Ok(res.to_string())
+    }
+
+    /// Broadcast the provided PSBT of a CPFP child transaction.
+    ///
+    /// The provided PSBT, prepared with the [`cpfp_begin`](Wallet::cpfp_begin) function, needs to
+    /// have already been signed.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns the TXID of the broadcasted child transaction.
+    pub fn cpfp_end(&mut self, online: Online, signed_psbt: String) -> Result<String, Error> {
+        info!(self.logger(), "CPFP (end)...");
+        self.check_online(online)?;
+        let psbt = Psbt::from_str(&signed_psbt)?;
+        let txn = self.database().begin_transaction()?;
+        let res = self.cpfp_end_impl(&txn, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "CPFP (end) completed");
+        Ok(res)
+    }
+
     /// Inflate RGB assets.
     ///
     /// This calls [`inflate_begin`](Wallet::inflate_begin), signs the resulting PSBT and finally
@@ -1139,6 +1847,7 @@ impl Wallet {
             inflation_amounts,
             fee_rate,
             min_confirmations,
+            CoinSelectionOptions::default(),
             true,
         )?;
         self.sign_psbt_impl(&mut begin_op_data.psbt, None)?;
@@ -1160,6 +1869,10 @@ impl Wallet {
     /// the transaction anchoring the transfer for it to be considered final and move (while
     /// refreshing) to the [`TransferStatus::Settled`] status.
     ///
+    /// The optional `coin_selection` drives which UTXOs hosting the assets to be spent get selected
+    /// as inputs: a [`CoinSelectionStrategy`] (defaulting to
+    /// [`CoinSelectionStrategy::SmallestFirst`]) plus outpoints that must or must not be spent.
+    ///
     /// If `dry_run` is true, the wallet does not persist the transfer in
     /// [`TransferStatus::Initiated`]. The returned [`InflateBeginResult::batch_transfer_idx`] is
     /// None in that case. The PSBT and on-disk transfer data under the wallet directory are still
@@ -1179,6 +1892,7 @@ impl Wallet {
         inflation_amounts: Vec<u64>,
         fee_rate: u64,
         min_confirmations: u8,
+        coin_selection: Option<CoinSelectionOptions>,
         dry_run: bool,
     ) -> Result<InflateBeginResult, Error> {
         info!(
@@ -1193,6 +1907,7 @@ impl Wallet {
             inflation_amounts,
             fee_rate,
             min_confirmations,
+            coin_selection.unwrap_or_default(),
             dry_run,
         )?;
         if !dry_run {
@@ -1258,8 +1973,15 @@ impl Wallet {
         self.check_xprv()?;
         self.check_online(online)?;
         let txn = self.database().begin_transaction()?;
-        let mut begin_op_data =
-            self.burn_begin_impl(&txn, asset_id, amount, fee_rate, min_confirmations, true)?;
+        let mut begin_op_data = self.burn_begin_impl(
+            &txn,
+            asset_id,
+            amount,
+            fee_rate,
+            min_confirmations,
+            CoinSelectionOptions::default(),
+            true,
+        )?;
         self.sign_psbt_impl(&mut begin_op_data.psbt, None)?;
         let res = self.burn_end_impl(&txn, &begin_op_data.psbt)?;
         self.update_backup_info(&txn, false)?;
@@ -1273,6 +1995,10 @@ impl Wallet {
     ///
     /// The amount of assets to burn is specified by the `amount` parameter and cannot be zero.
     ///
+    /// The optional `coin_selection` drives which UTXOs hosting the assets to be spent get selected
+    /// as inputs: a [`CoinSelectionStrategy`] (defaulting to
+    /// [`CoinSelectionStrategy::SmallestFirst`]) plus outpoints that must or must not be spent.
+    ///
     /// If `dry_run` is true, the wallet does not persist the transfer in
     /// [`TransferStatus::Initiated`]. The returned [`BurnBeginResult::batch_transfer_idx`] is None
     /// in that case. The PSBT and on-disk transfer data under the wallet directory are still
@@ -1292,13 +2018,21 @@ impl Wallet {
         amount: u64,
         fee_rate: u64,
         min_confirmations: u8,
+        coin_selection: Option<CoinSelectionOptions>,
         dry_run: bool,
     ) -> Result<BurnBeginResult, Error> {
         info!(self.logger(), "Burning (begin) amount: {}...", amount);
         self.check_online(online)?;
         let txn = self.database().begin_transaction()?;
-        let begin_operation_data =
-            self.burn_begin_impl(&txn, asset_id, amount, fee_rate, min_confirmations, dry_run)?;
+        let begin_operation_data = self.burn_begin_impl(
+            &txn,
+            asset_id,
+            amount,
+            fee_rate,
+            min_confirmations,
+            coin_selection.unwrap_or_default(),
+            dry_run,
+        )?;
         if !dry_run {
             self.update_backup_info(&txn, false)?;
         }
@@ -1342,4 +2076,257 @@ impl Wallet {
         info!(self.logger(), "Burn (end) completed");
         Ok(res)
     }
+
+    /// Consolidate the spendable allocations of the wallet's fungible assets onto fewer UTXOs.
+    ///
+    /// This calls [`consolidate_allocations_begin`](Wallet::consolidate_allocations_begin), signs
+    /// the resulting PSBT and finally calls
+    /// [`consolidate_allocations_end`](Wallet::consolidate_allocations_end).
+    ///
+    /// A wallet with private keys is required.
+    pub fn consolidate_allocations(
+        &mut self,
+        online: Online,
+        asset_id: Option<String>,
+        fee_rate: u64,
+        min_confirmations: u8,
+    ) -> Result<OperationResult, Error> {
+        info!(
+            self.logger(),
+            "Consolidating allocations for asset: {:?}...", asset_id
+        );
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let mut begin_op_data = self.consolidate_allocations_begin_impl(
+            &txn,
+            asset_id,
+            fee_rate,
+            min_confirmations,
+            true,
+        )?;
+        self.sign_psbt_impl(&mut begin_op_data.psbt, None)?;
+        let res = self.consolidate_allocations_end_impl(&txn, &begin_op_data.psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Consolidate allocations completed");
+        Ok(res)
+    }
+
+    /// Prepare the PSBT to consolidate the spendable allocations of the wallet's fungible assets
+    /// onto fewer UTXOs, with the provided `fee_rate` (in sat/vB).
+    ///
+    /// If an `asset_id` is provided only allocations of that asset are consolidated, otherwise all
+    /// fungible assets with more than one spendable allocation are. All spendable allocations of
+    /// each involved asset are moved, in a single self-transfer, to new wallet UTXOs, each one
+    /// receiving at most [`max_allocations_per_utxo`](WalletData::max_allocations_per_utxo)
+    /// consolidated allocations. The resulting transfers have kind
+    /// [`TransferKind::Consolidation`].
+    ///
+    /// Only fungible allocations are consolidated: non-fungible allocations, which can't be merged,
+    /// and inflation rights are not counted and stay on their UTXOs, unless they share one with a
+    /// consolidated allocation, in which case they are moved to the change UTXO.
+    ///
+    /// If `dry_run` is true, the wallet does not persist the transfer in
+    /// [`TransferStatus::Initiated`]. The returned
+    /// [`ConsolidateAllocationsBeginResult::batch_transfer_idx`] is None in that case. The PSBT
+    /// and on-disk transfer data under the wallet directory are still produced.
+    /// [`consolidate_allocations_end`](Wallet::consolidate_allocations_end) can still complete
+    /// the operation and will persist the transfer.
+    ///
+    /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
+    /// to be fed to the [`consolidate_allocations_end`](Wallet::consolidate_allocations_end)
+    /// function for broadcasting.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a PSBT ready to be signed and operation details.
+    pub fn consolidate_allocations_begin(
+        &mut self,
+        online: Online,
+        asset_id: Option<String>,
+        fee_rate: u64,
+        min_confirmations: u8,
+        dry_run: bool,
+    ) -> Result<ConsolidateAllocationsBeginResult, Error> {
+        info!(
+            self.logger(),
+            "Consolidating allocations (begin) for asset: {:?}...", asset_id
+        );
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let begin_operation_data = self.consolidate_allocations_begin_impl(
+            &txn,
+            asset_id,
+            fee_rate,
+            min_confirmations,
+            dry_run,
+        )?;
+        if !dry_run {
+            self.update_backup_info(&txn, false)?;
+        }
+        txn.commit()?;
+        info!(self.logger(), "Consolidate allocations (begin) completed");
+        Ok(ConsolidateAllocationsBeginResult {
+            psbt: begin_operation_data.psbt.to_string(),
+            batch_transfer_idx: begin_operation_data.batch_transfer_idx,
+            details: ConsolidateAllocationsDetails {
+                fascia_path: begin_operation_data
+                    .transfer_dir
+                    .join(FASCIA_FILE)
+                    .to_string_lossy()
+                    .to_string(),
+                asset_ids: begin_operation_data
+                    .info_batch_transfer
+                    .transfers
+                    .keys()
+                    .cloned()
+                    .collect(),
+                min_confirmations,
+                entropy: begin_operation_data.info_batch_transfer.entropy,
+            },
+        })
+    }
+
+    /// Complete the consolidate allocations operation by broadcasting the provided PSBT and
+    /// saving the transfer to DB.
+    ///
+    /// The provided PSBT, prepared with the
+    /// [`consolidate_allocations_begin`](Wallet::consolidate_allocations_begin) function, needs to
+    /// have already been signed.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a [`OperationResult`].
+    pub fn consolidate_allocations_end(
+        &mut self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<OperationResult, Error> {
+        info!(self.logger(), "Consolidating allocations (end)...");
+        self.check_online(online)?;
+        let psbt = Psbt::from_str(&signed_psbt)?;
+        let txn = self.database().begin_transaction()?;
+        let res = self.consolidate_allocations_end_impl(&txn, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Consolidate allocations (end) completed");
+        Ok(res)
+    }
+
+    /// Bump the fee of the transaction anchoring the outgoing transfer with the provided
+    /// `batch_transfer_idx`, replacing it (RBF) with one paying the provided `fee_rate` (in
+    /// sat/vB).
+    ///
+    /// This calls [`bump_fee_begin`](Wallet::bump_fee_begin), signs the resulting PSBT and finally
+    /// calls [`bump_fee_end`](Wallet::bump_fee_end).
+    ///
+    /// A wallet with private keys is required.
+    pub fn bump_fee(
+        &mut self,
+        online: Online,
+        batch_transfer_idx: i32,
+        fee_rate: u64,
+    ) -> Result<BumpFeeResult, Error> {
+        info!(
+            self.logger(),
+            "Bumping fee for batch transfer with idx {}...", batch_transfer_idx
+        );
+        self.check_xprv()?;
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let mut begin_op_data = self.bump_fee_begin_impl(&txn, batch_transfer_idx, fee_rate)?;
+        self.sign_psbt_impl(&mut begin_op_data.psbt, None)?;
+        let res = self.bump_fee_end_impl(&txn, &begin_op_data.psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Bump fee completed");
+        Ok(res)
+    }
+
+    /// Prepare the PSBT to replace the transaction anchoring the outgoing transfer with the
+    /// provided `batch_transfer_idx` with one paying the provided `fee_rate` (in sat/vB).
+    ///
+    /// Only transfers in the [`TransferStatus::WaitingConfirmations`] status, whose transaction
+    /// has not been confirmed yet, can be bumped. The replacement transaction keeps all the
+    /// original inputs and outputs and pays the additional fee by spending vanilla UTXOs, which
+    /// are not reserved until [`bump_fee_end`](Wallet::bump_fee_end) is called. The same RGB
+    /// transitions are committed to the replacement transaction.
+    ///
+    /// This API requires to be online since it checks that the transaction is still unconfirmed.
+    ///
+    /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
+    /// to be fed to the [`bump_fee_end`](Wallet::bump_fee_end) function to complete the operation.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a PSBT ready to be signed and operation details.
+    pub fn bump_fee_begin(
+        &mut self,
+        online: Online,
+        batch_transfer_idx: i32,
+        fee_rate: u64,
+    ) -> Result<BumpFeeBeginResult, Error> {
+        info!(
+            self.logger(),
+            "Bumping fee (begin) for batch transfer with idx {}...", batch_transfer_idx
+        );
+        self.check_online(online)?;
+        let txn = self.database().begin_transaction()?;
+        let begin_op_data = self.bump_fee_begin_impl(&txn, batch_transfer_idx, fee_rate)?;
+        txn.commit()?;
+        info!(self.logger(), "Bump fee (begin) completed");
+        Ok(BumpFeeBeginResult {
+            psbt: begin_op_data.psbt.to_string(),
+            batch_transfer_idx,
+            details: BumpFeeDetails {
+                fascia_path: begin_op_data
+                    .transfer_dir
+                    .join(FASCIA_FILE)
+                    .to_string_lossy()
+                    .to_string(),
+                replaced_txid: begin_op_data
+                    .info_batch_transfer
+                    .replaced_txid
+                    .expect("replaced TXID should be set"),
+                entropy: begin_op_data.info_batch_transfer.entropy,
+            },
+        })
+    }
+
+    /// Complete the bump fee operation by broadcasting the provided PSBT and updating the transfer
+    /// to the replacement transaction.
+    ///
+    /// The provided PSBT, prepared with the [`bump_fee_begin`](Wallet::bump_fee_begin) function,
+    /// needs to have already been signed.
+    ///
+    /// Recipients only know the replaced transaction until they receive the updated consignment,
+    /// which embeds the signed replacement transaction. Since the RGB proxy server doesn't allow
+    /// changing an already posted consignment, the updated consignments are not posted: the IDs of
+    /// all the recipients are returned in [`BumpFeeResult::recipient_ids_to_notify`] and the
+    /// consignments for them (see [`get_send_consignment_path`](Wallet::get_send_consignment_path))
+    /// need to be delivered out-of-band (see
+    /// [`provide_out_of_band_consignment`](Wallet::provide_out_of_band_consignment)). Until they
+    /// receive it, recipients keep waiting for the replaced transaction, which will never confirm.
+    ///
+    /// The data of the replaced transfer is deleted.
+    ///
+    /// This doesn't require the wallet to have private keys.
+    ///
+    /// Returns a [`BumpFeeResult`].
+    pub fn bump_fee_end(
+        &mut self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<BumpFeeResult, Error> {
+        info!(self.logger(), "Bumping fee (end)...");
+        self.check_online(online)?;
+        let psbt = Psbt::from_str(&signed_psbt)?;
+        let txn = self.database().begin_transaction()?;
+        let res = self.bump_fee_end_impl(&txn, &psbt)?;
+        self.update_backup_info(&txn, false)?;
+        txn.commit()?;
+        info!(self.logger(), "Bump fee (end) completed");
+        Ok(res)
+    }
 }
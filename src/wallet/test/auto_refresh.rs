@@ -0,0 +1,115 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+fn go_online_auto_refresh(party: &mut SinglesigParty) {
+    let mut online_options = test_go_online_options(None);
+    online_options.auto_refresh = Some(AutoRefreshOptions {
+        sync_interval_secs: 1,
+        refresh_interval_secs: 1,
+        max_backoff_secs: 4,
+    });
+    party.wallet.go_online(online_options).unwrap();
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+    let rcv_collector = Arc::new(EventCollector::default());
+    rcv_party
+        .wallet
+        .set_event_listener(Some(rcv_collector.clone()));
+
+    // start the background task and wait for its first runs
+    go_online_auto_refresh(&mut rcv_party);
+    assert!(wait_for_function(
+        || rcv_collector.contains(&WalletEvent::AutoRefreshCompleted {
+            synced: true,
+            transfers_changed: false,
+        }),
+        10,
+        200,
+    ));
+
+    // send
+    let asset = party.issue_asset_nia(None);
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    party.send(recipient_map, FEE_RATE, None);
+
+    // the receiver's transfer progresses without refreshing manually
+    assert!(wait_for_function(
+        || rcv_collector.contains(&WalletEvent::TransferStatusChanged {
+            batch_transfer_idx: receive_data.batch_transfer_idx,
+            status: TransferStatus::WaitingConfirmations,
+        }),
+        30,
+        500,
+    ));
+    assert!(rcv_collector.contains(&WalletEvent::AutoRefreshCompleted {
+        synced: true,
+        transfers_changed: true,
+    }));
+
+    // operations keep working while the task is running
+    party.refresh_all();
+    mine(false);
+    party.refresh_all();
+    assert!(wait_for_function(
+        || rcv_collector.contains(&WalletEvent::TransferStatusChanged {
+            batch_transfer_idx: receive_data.batch_transfer_idx,
+            status: TransferStatus::Settled,
+        }),
+        30,
+        500,
+    ));
+    assert_eq!(rcv_party.get_asset_balance(&asset.asset_id).settled, amount);
+
+    // no more runs after stopping the task
+    rcv_party.wallet.stop_auto_refresh();
+    rcv_collector.take();
+    std::thread::sleep(Duration::from_secs(3));
+    assert!(!rcv_collector.any_completed());
+
+    // going online again without the option stops it
+    go_online_auto_refresh(&mut rcv_party);
+    assert!(wait_for_function(|| rcv_collector.any_completed(), 10, 200));
+    rcv_party.go_online(true, None);
+    rcv_collector.take();
+    std::thread::sleep(Duration::from_secs(3));
+    assert!(!rcv_collector.any_completed());
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn drop_wallet() {
+    initialize();
+
+    let mut party = get_funded_party!();
+    let collector = Arc::new(EventCollector::default());
+    party.wallet.set_event_listener(Some(collector.clone()));
+    go_online_auto_refresh(&mut party);
+    assert!(wait_for_function(|| collector.any_completed(), 10, 200));
+
+    // dropping the wallet stops the task
+    drop(party);
+    collector.take();
+    std::thread::sleep(Duration::from_secs(3));
+    assert!(!collector.any_completed());
+}
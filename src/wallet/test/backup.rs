@@ -336,3 +336,155 @@ fn backup_info() {
     let backup_required = wallet.backup_info().unwrap();
     assert!(!backup_required);
 }
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn incremental_success() {
+    initialize();
+
+    let amount: u64 = 66;
+    let test_data_dir = get_test_data_dir_path();
+    let full_backup_path = test_data_dir.join("test_backup_incremental_full.rgb-lib_backup");
+    let full_backup = full_backup_path.to_str().unwrap();
+    let incr_backup_path = test_data_dir.join("test_backup_incremental_incr.rgb-lib_backup");
+    let incr_backup = incr_backup_path.to_str().unwrap();
+    let _ = std::fs::remove_file(full_backup);
+    let _ = std::fs::remove_file(incr_backup);
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+    let mut wallet_data = party.get_wallet_data();
+    let keys = party.get_keys();
+    let wallet_dir = party.wallet.get_wallet_dir();
+
+    // full backup
+    let asset = party.issue_asset_nia(None);
+    party.wallet.backup(full_backup, PASSWORD).unwrap();
+
+    // send
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id,
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let txid = party.send_retry(&recipient_map);
+    assert!(!txid.is_empty());
+    rcv_party.wait_for_refresh(None);
+    party.wait_for_refresh(Some(&asset.asset_id));
+    mine(false);
+    rcv_party.wait_for_refresh(Some(&asset.asset_id));
+    party.wait_for_refresh(Some(&asset.asset_id));
+    party.check_test_wallet_data(&asset, None, 1, amount);
+
+    // incremental backup
+    assert!(party.wallet.backup_info().unwrap());
+    party
+        .wallet
+        .backup_incremental(incr_backup, PASSWORD)
+        .unwrap();
+    assert!(!party.wallet.backup_info().unwrap());
+
+    // the incremental backup is smaller than a full one
+    assert!(
+        std::fs::metadata(incr_backup).unwrap().len()
+            < std::fs::metadata(full_backup).unwrap().len()
+    );
+    drop(party);
+
+    // restore the chain
+    let target_dir_path = get_restore_dir_path(Some("incremental_success"));
+    let target_dir = target_dir_path.to_str().unwrap();
+    restore_backup_chain(&[full_backup, incr_backup], PASSWORD, target_dir).unwrap();
+
+    // check original and restored data are the same
+    let restore_wallet_dir = target_dir_path.join(wallet_dir.file_name().unwrap());
+    compare_test_directories(&wallet_dir, &restore_wallet_dir, &["log"]);
+
+    // post-restore wallet data
+    wallet_data.data_dir = target_dir.to_string();
+    let mut party = offline_party!(Wallet::new(wallet_data, keys).unwrap());
+    let online = party.go_online(true, None);
+    let mut party = party!(party.wallet, online);
+    party.check_test_wallet_data(&asset, None, 1, amount);
+    assert!(!party.wallet.backup_info().unwrap());
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn incremental_fail() {
+    // services are unnecessary here but this prevents removal of the test dir during exectution
+    initialize();
+
+    let test_data_dir = get_test_data_dir_path();
+    let full_backup_path = test_data_dir.join("test_backup_incremental_fail_full.rgb-lib_backup");
+    let full_backup = full_backup_path.to_str().unwrap();
+    let incr_backup_path = test_data_dir.join("test_backup_incremental_fail_incr.rgb-lib_backup");
+    let incr_backup = incr_backup_path.to_str().unwrap();
+    let _ = std::fs::remove_file(full_backup);
+    let _ = std::fs::remove_file(incr_backup);
+
+    let mut wallet = get_test_wallet(true, None);
+
+    // incremental backup without a previous backup
+    let result = wallet.backup_incremental(incr_backup, PASSWORD);
+    assert!(matches!(result, Err(Error::NoBaseBackup)));
+
+    // incremental backup without the manifest of the previous backup
+    wallet.backup(full_backup, PASSWORD).unwrap();
+    let manifest_path = wallet.get_wallet_dir().join(BACKUP_MANIFEST_FILE);
+    let manifest = std::fs::read(&manifest_path).unwrap();
+    std::fs::remove_file(&manifest_path).unwrap();
+    let result = wallet.backup_incremental(incr_backup, PASSWORD);
+    assert!(matches!(result, Err(Error::NoBaseBackup)));
+    std::fs::write(&manifest_path, manifest).unwrap();
+
+    // backups
+    let _ = std::fs::remove_file(full_backup);
+    wallet.backup(full_backup, PASSWORD).unwrap();
+    wallet.get_address().unwrap();
+    wallet.backup_incremental(incr_backup, PASSWORD).unwrap();
+
+    // chain not starting with a full backup
+    let target_dir_path = get_restore_dir_path(Some("incremental_fail"));
+    let target_dir = target_dir_path.to_str().unwrap();
+    let result = restore_backup_chain(&[incr_backup, full_backup], PASSWORD, target_dir);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+
+    // incremental backup not based on the previous one
+    let result = restore_backup_chain(&[full_backup, full_backup], PASSWORD, target_dir);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+    let result = restore_backup_chain(
+        &[full_backup, incr_backup, incr_backup],
+        PASSWORD,
+        target_dir,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+
+    // empty chain
+    let result = restore_backup_chain(&[], PASSWORD, target_dir);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+
+    // nothing has been restored
+    let wallet_dir = target_dir_path.join(wallet.get_wallet_dir().file_name().unwrap());
+    assert!(!wallet_dir.exists());
+}
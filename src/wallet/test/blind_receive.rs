@@ -18,6 +18,7 @@ fn success() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             expiration_timestamp,
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -33,7 +34,10 @@ fn success() {
     );
     let transfer = party.get_test_transfer_recipient(&receive_data.recipient_id);
     let (_, batch_transfer) = party.get_test_transfer_related(&transfer);
-    assert_eq!(batch_transfer.min_confirmations, MIN_CONFIRMATIONS);
+    assert_eq!(
+        batch_transfer.min_confirmations,
+        i16::from(MIN_CONFIRMATIONS)
+    );
 
     // asset ID (NIA) + expiration + 0 min confirmations
     let asset_nia = party.issue_asset_nia(None);
@@ -45,6 +49,7 @@ fn success() {
         .blind_receive(
             Some(asset_nia_id.clone()),
             Assignment::Any,
+            ReceivePolicy::Any,
             expiration_timestamp,
             TRANSPORT_ENDPOINTS.clone(),
             min_confirmations,
@@ -53,7 +58,10 @@ fn success() {
     assert_eq!(receive_data.expiration_timestamp, expiration_timestamp);
     let transfer = party.get_test_transfer_recipient(&receive_data.recipient_id);
     let (_, batch_transfer) = party.get_test_transfer_related(&transfer);
-    assert_eq!(batch_transfer.min_confirmations, min_confirmations);
+    assert_eq!(
+        batch_transfer.min_confirmations,
+        i16::from(min_confirmations)
+    );
     let invoice = Invoice::new(receive_data.invoice).unwrap();
     let invoice_data = invoice.invoice_data();
     assert_eq!(invoice_data.asset_schema, Some(AssetSchema::Nia));
@@ -66,6 +74,7 @@ fn success() {
         .blind_receive(
             Some(asset_uda_id.clone()),
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -83,6 +92,7 @@ fn success() {
         .blind_receive(
             Some(asset_cfa_id.clone()),
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -102,6 +112,7 @@ fn success() {
         .blind_receive(
             Some(asset_ifa_id.clone()),
             Assignment::Fungible(amount),
+            ReceivePolicy::Any,
             expiration_timestamp,
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -131,6 +142,7 @@ fn success() {
         .blind_receive(
             Some(asset_nia_id.clone()),
             Assignment::Fungible(amount),
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -155,6 +167,7 @@ fn success() {
         .blind_receive(
             None,
             Assignment::Fungible(amount),
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -179,6 +192,7 @@ fn success() {
         .blind_receive(
             Some(asset_nia_id.clone()),
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -200,6 +214,7 @@ fn success() {
         .blind_receive(
             Some(asset_uda_id.clone()),
             Assignment::NonFungible,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -221,6 +236,7 @@ fn success() {
         .blind_receive(
             Some(asset_uda_id.clone()),
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -242,6 +258,7 @@ fn success() {
         .blind_receive(
             Some(asset_ifa_id.clone()),
             Assignment::InflationRight(amount),
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -266,6 +283,7 @@ fn success() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -282,6 +300,7 @@ fn success() {
     let result = party.wallet.blind_receive(
         Some(asset_ifa_id.clone()),
         Assignment::NonFungible,
+        ReceivePolicy::Any,
         default_rcv_expiration(),
         TRANSPORT_ENDPOINTS.clone(),
         MIN_CONFIRMATIONS,
@@ -301,6 +320,7 @@ fn success() {
     let result = party.wallet.blind_receive(
         None,
         Assignment::Any,
+        ReceivePolicy::Any,
         default_rcv_expiration(),
         transport_endpoints.clone(),
         MIN_CONFIRMATIONS,
@@ -418,6 +438,7 @@ fn fail() {
         wallet.blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             transport_endpoints,
             MIN_CONFIRMATIONS,
@@ -446,6 +467,7 @@ fn fail() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             (now().unix_timestamp() - 1) as u64,
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -457,6 +479,7 @@ fn fail() {
     let result = party.wallet.blind_receive(
         Some(s!("rgb1inexistent")),
         Assignment::Any,
+        ReceivePolicy::Any,
         default_rcv_expiration(),
         TRANSPORT_ENDPOINTS.clone(),
         MIN_CONFIRMATIONS,
@@ -563,6 +586,7 @@ fn wrong_asset_fail() {
         .blind_receive(
             Some(asset_a.asset_id),
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -0,0 +1,225 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_empty_party!();
+
+    // issue
+    let asset = party.issue_asset_nia(None);
+
+    // send and get the TX broadcast without mining it
+    let _guard = stop_mining();
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let txid = party.send_retry(&recipient_map);
+    rcv_party.refresh_all();
+    party.refresh_all();
+    assert!(party.check_test_transfer_status_sender(&txid, TransferStatus::WaitingConfirmations));
+    let balance_before = party.get_asset_balance(&asset.asset_id);
+    assert!(rcv_party.check_test_transfer_status_recipient(
+        &receive_data.recipient_id,
+        TransferStatus::WaitingBroadcast
+    ));
+    let batch_transfer_idx = party
+        .db_batch_transfers_filtered(&txid)
+        .first()
+        .unwrap()
+        .idx;
+
+    // bump fee (begin)
+    let bump_begin = party
+        .wallet
+        .bump_fee_begin(party.online, batch_transfer_idx, FEE_RATE * 2)
+        .unwrap();
+    assert_eq!(bump_begin.batch_transfer_idx, batch_transfer_idx);
+    assert_eq!(bump_begin.details.replaced_txid, txid);
+    assert!(Path::new(&bump_begin.details.fascia_path).exists());
+    let unsigned_psbt = Psbt::from_str(&bump_begin.psbt).unwrap();
+    let new_txid = unsigned_psbt.unsigned_tx.compute_txid().to_string();
+    assert_ne!(new_txid, txid);
+
+    // bump fee (end)
+    let signed_psbt = party.wallet.sign_psbt(bump_begin.psbt, None).unwrap();
+    let bak_info_before = party.db_backup_info();
+    let result = party
+        .wallet
+        .bump_fee_end(party.online, signed_psbt)
+        .unwrap();
+    let bak_info_after = party.db_backup_info();
+    assert!(bak_info_after.last_operation_timestamp > bak_info_before.last_operation_timestamp);
+    assert_eq!(result.txid, new_txid);
+    assert_eq!(result.replaced_txid, txid);
+    assert_eq!(result.batch_transfer_idx, batch_transfer_idx);
+    // the updated consignment is never posted, as the proxy refuses changes
+    assert_eq!(
+        result.recipient_ids_to_notify,
+        vec![receive_data.recipient_id.clone()]
+    );
+
+    // the sender now tracks the replacement TX
+    assert!(party.db_batch_transfers_filtered(&txid).is_empty());
+    assert!(
+        party.check_test_transfer_status_sender(&new_txid, TransferStatus::WaitingConfirmations)
+    );
+    assert!(!party.db_txos().iter().any(|t| t.txid == txid));
+    assert!(!party.wallet.get_transfers_dir().join(&txid).exists());
+
+    // consuming the same transitions again (with the replacement witness) doesn't change the state
+    assert_eq!(party.get_asset_balance(&asset.asset_id), balance_before);
+    let fascia_path = party
+        .wallet
+        .get_transfers_dir()
+        .join(&new_txid)
+        .join(FASCIA_FILE);
+    let fascia: Fascia = serde_json::from_str(&fs::read_to_string(fascia_path).unwrap()).unwrap();
+    party.wallet.consume_fascia(fascia, None).unwrap();
+    assert_eq!(party.get_asset_balance(&asset.asset_id), balance_before);
+
+    // the recipient receives the updated consignment out-of-band
+    let consignment_path = party
+        .wallet
+        .get_send_consignment_path(&asset.asset_id, &new_txid)
+        .to_string_lossy()
+        .to_string();
+    rcv_party
+        .wallet
+        .provide_out_of_band_consignment(rcv_party.online, consignment_path, vec![])
+        .unwrap();
+    assert!(rcv_party.check_test_transfer_status_recipient(
+        &receive_data.recipient_id,
+        TransferStatus::WaitingConfirmations
+    ));
+    let rcv_transfer = rcv_party.get_test_transfer_recipient(&receive_data.recipient_id);
+    let (rcv_transfer_data, _) = rcv_party.get_test_transfer_data(&rcv_transfer);
+    assert_eq!(rcv_transfer_data.txid, Some(new_txid.clone()));
+
+    // settle the transfer
+    drop(_guard);
+    mine_tx(false, &new_txid);
+    rcv_party.wait_for_refresh(None);
+    party.wait_for_refresh(Some(&asset.asset_id));
+    assert!(party.check_test_transfer_status_sender(&new_txid, TransferStatus::Settled));
+    assert!(
+        rcv_party.check_test_transfer_status_recipient(
+            &receive_data.recipient_id,
+            TransferStatus::Settled
+        )
+    );
+    assert_eq!(rcv_party.get_asset_balance(&asset.asset_id).settled, amount);
+    let balance = party.get_asset_balance(&asset.asset_id);
+    assert_eq!(balance.settled, AMOUNT - amount);
+    assert_eq!(balance.spendable, AMOUNT - amount);
+
+    // the change is spendable after the replacement
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let txid = party.send_retry(&recipient_map);
+    rcv_party.wait_for_refresh(None);
+    party.wait_for_refresh(Some(&asset.asset_id));
+    mine(false);
+    rcv_party.wait_for_refresh(None);
+    party.wait_for_refresh(Some(&asset.asset_id));
+    assert!(party.check_test_transfer_status_sender(&txid, TransferStatus::Settled));
+    assert_eq!(
+        rcv_party.get_asset_balance(&asset.asset_id).settled,
+        amount * 2
+    );
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_empty_party!();
+
+    let asset = party.issue_asset_nia(None);
+
+    // unknown batch transfer
+    let result = party.wallet.bump_fee(party.online, 999, FEE_RATE * 2);
+    assert_matches!(result, Err(Error::BatchTransferNotFound { idx: 999 }));
+
+    // transfer not waiting for confirmations
+    let _guard = stop_mining();
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(66),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let txid = party.send_retry(&recipient_map);
+    let batch_transfer_idx = party
+        .db_batch_transfers_filtered(&txid)
+        .first()
+        .unwrap()
+        .idx;
+    let result = party
+        .wallet
+        .bump_fee(party.online, batch_transfer_idx, FEE_RATE * 2);
+    assert!(matches!(
+        result,
+        Err(Error::CannotBumpFee { details: m })
+            if m == "the transfer is not waiting for confirmations"
+    ));
+
+    // incoming transfer
+    rcv_party.refresh_all();
+    let rcv_batch_transfer_idx = rcv_party.db_batch_transfers().first().unwrap().idx;
+    let result = rcv_party
+        .wallet
+        .bump_fee(rcv_party.online, rcv_batch_transfer_idx, FEE_RATE * 2);
+    assert!(matches!(
+        result,
+        Err(Error::CannotBumpFee { details: m })
+            if m == "the transfer is incoming"
+    ));
+
+    // fee rate not higher than the original one
+    party.refresh_all();
+    let result = party
+        .wallet
+        .bump_fee(party.online, batch_transfer_idx, FEE_RATE);
+    assert_matches!(result, Err(Error::InvalidFeeRate { details: _ }));
+
+    // TX already confirmed
+    drop(_guard);
+    mine_tx(false, &txid);
+    let result = party
+        .wallet
+        .bump_fee(party.online, batch_transfer_idx, FEE_RATE * 2);
+    assert!(matches!(
+        result,
+        Err(Error::CannotBumpFee { details: m })
+            if m == "the transfer TX is already confirmed"
+    ));
+}
@@ -444,6 +444,7 @@ fn fail() {
         0,
         FEE_RATE,
         MIN_CONFIRMATIONS,
+        None,
         false,
     );
     assert_matches!(result, Err(Error::Offline));
@@ -490,6 +491,7 @@ fn fail() {
         10,
         0,
         MIN_CONFIRMATIONS,
+        None,
         false,
     );
     assert_matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == FEE_MSG_LOW);
@@ -500,6 +502,7 @@ fn fail() {
         10,
         u64::MAX,
         MIN_CONFIRMATIONS,
+        None,
         false,
     );
     assert_matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == FEE_MSG_OVER);
@@ -519,6 +522,7 @@ fn fail() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: vec![AssetSchema::Nia, AssetSchema::Ifa],
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys, None),
     )
@@ -560,6 +564,7 @@ fn fail() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: vec![AssetSchema::Nia],
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys, None),
     )
@@ -639,6 +644,7 @@ fn begin_end() {
             AMOUNT,
             FEE_RATE,
             MIN_CONFIRMATIONS,
+            None,
             true,
         )
         .unwrap();
@@ -658,6 +664,7 @@ fn begin_end() {
             AMOUNT,
             FEE_RATE,
             MIN_CONFIRMATIONS,
+            None,
             false,
         )
         .unwrap();
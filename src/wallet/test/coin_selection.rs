@@ -0,0 +1,354 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+fn get_input_outpoints(
+    party: &mut SinglesigParty,
+    recipient_map: &HashMap<String, Vec<Recipient>>,
+    coin_selection: Option<CoinSelectionOptions>,
+) -> Result<HashSet<Outpoint>, Error> {
+    let begin = party.wallet.send_begin(
+        party.online,
+        recipient_map.clone(),
+        false,
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+        default_send_expiration(),
+        coin_selection,
+        true,
+    )?;
+    Ok(Psbt::from_str(&begin.psbt)
+        .unwrap()
+        .unsigned_tx
+        .input
+        .iter()
+        .map(|i| Outpoint {
+            txid: i.previous_output.txid.to_string(),
+            vout: i.previous_output.vout,
+        })
+        .collect())
+}
+
+#[cfg(feature = "electrum")]
+fn get_recipient_map(
+    rcv_party: &mut SinglesigParty,
+    asset_id: &str,
+    amount: u64,
+) -> HashMap<String, Vec<Recipient>> {
+    let receive_data = rcv_party.blind_receive();
+    HashMap::from([(
+        asset_id.to_string(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id,
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )])
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // issue
+    let asset = party.issue_asset_nia(Some(&[100, 200, 300]));
+    let unspents = party.list_unspents(false);
+    let outpoint_for = |amount: u64| {
+        unspents
+            .iter()
+            .find(|u| {
+                u.rgb_allocations
+                    .iter()
+                    .any(|a| a.assignment == Assignment::Fungible(amount))
+            })
+            .unwrap()
+            .utxo
+            .outpoint
+            .clone()
+    };
+    let (outpoint_100, outpoint_200, outpoint_300) =
+        (outpoint_for(100), outpoint_for(200), outpoint_for(300));
+    let options = |strategy: CoinSelectionStrategy| {
+        Some(CoinSelectionOptions {
+            strategy,
+            ..Default::default()
+        })
+    };
+
+    // smallest-first (default)
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset.asset_id, 50);
+    let inputs = get_input_outpoints(&mut party, &recipient_map, None).unwrap();
+    assert_eq!(inputs, HashSet::from([outpoint_100.clone()]));
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset.asset_id, 250);
+    let inputs = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        options(CoinSelectionStrategy::SmallestFirst),
+    )
+    .unwrap();
+    assert_eq!(
+        inputs,
+        HashSet::from([outpoint_100.clone(), outpoint_200.clone()])
+    );
+
+    // largest-first
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset.asset_id, 50);
+    let inputs = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        options(CoinSelectionStrategy::LargestFirst),
+    )
+    .unwrap();
+    assert_eq!(inputs, HashSet::from([outpoint_300.clone()]));
+
+    // minimize inputs
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset.asset_id, 150);
+    let inputs = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        options(CoinSelectionStrategy::MinimizeInputs),
+    )
+    .unwrap();
+    assert_eq!(inputs, HashSet::from([outpoint_200.clone()]));
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset.asset_id, 450);
+    let inputs = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        options(CoinSelectionStrategy::MinimizeInputs),
+    )
+    .unwrap();
+    assert_eq!(
+        inputs,
+        HashSet::from([outpoint_200.clone(), outpoint_300.clone()])
+    );
+
+    // random
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset.asset_id, 600);
+    let inputs = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        options(CoinSelectionStrategy::Random),
+    )
+    .unwrap();
+    assert_eq!(inputs.len(), 3);
+
+    // included outpoints are always spent
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset.asset_id, 50);
+    let inputs = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        Some(CoinSelectionOptions {
+            included_outpoints: vec![outpoint_200.clone(), outpoint_300.clone()],
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        inputs,
+        HashSet::from([outpoint_200.clone(), outpoint_300.clone()])
+    );
+
+    // excluded outpoints are never spent
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset.asset_id, 50);
+    let inputs = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        Some(CoinSelectionOptions {
+            excluded_outpoints: vec![outpoint_100.clone()],
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+    assert_eq!(inputs, HashSet::from([outpoint_200.clone()]));
+
+    // the selection is also applied to burns
+    let asset_ifa = party.issue_asset_ifa(Some(&[100, 200]), None, None);
+    let ifa_outpoint_200 = party
+        .list_unspents(false)
+        .into_iter()
+        .find(|u| {
+            u.rgb_allocations.iter().any(|a| {
+                a.asset_id.as_deref() == Some(&asset_ifa.asset_id)
+                    && a.assignment == Assignment::Fungible(200)
+            })
+        })
+        .unwrap()
+        .utxo
+        .outpoint;
+    let begin = party
+        .wallet
+        .burn_begin(
+            party.online,
+            asset_ifa.asset_id.clone(),
+            50,
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+            options(CoinSelectionStrategy::LargestFirst),
+            true,
+        )
+        .unwrap();
+    let psbt = Psbt::from_str(&begin.psbt).unwrap();
+    assert!(psbt.unsigned_tx.input.iter().any(|i| {
+        i.previous_output.txid.to_string() == ifa_outpoint_200.txid
+            && i.previous_output.vout == ifa_outpoint_200.vout
+    }));
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // issue
+    let asset = party.issue_asset_nia(Some(&[100, 200]));
+    let unspents = party.list_unspents(false);
+    let asset_outpoint = unspents
+        .iter()
+        .find(|u| !u.rgb_allocations.is_empty())
+        .unwrap()
+        .utxo
+        .outpoint
+        .clone();
+    let empty_outpoint = unspents
+        .iter()
+        .find(|u| u.utxo.colorable && u.rgb_allocations.is_empty())
+        .unwrap()
+        .utxo
+        .outpoint
+        .clone();
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset.asset_id, 50);
+
+    // outpoint both included and excluded
+    let result = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        Some(CoinSelectionOptions {
+            included_outpoints: vec![asset_outpoint.clone()],
+            excluded_outpoints: vec![asset_outpoint.clone()],
+            ..Default::default()
+        }),
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidCoinSelection { details: m })
+            if m == format!("outpoint {asset_outpoint} is both included and excluded")
+    ));
+
+    // unknown included outpoint
+    let unknown_outpoint = Outpoint {
+        txid: "0".repeat(64),
+        vout: 0,
+    };
+    let result = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        Some(CoinSelectionOptions {
+            included_outpoints: vec![unknown_outpoint.clone()],
+            ..Default::default()
+        }),
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidCoinSelection { details: m })
+            if m == format!("outpoint {unknown_outpoint} is not available to be spent")
+    ));
+
+    // included outpoint with no allocations of the spent asset
+    let result = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        Some(CoinSelectionOptions {
+            included_outpoints: vec![empty_outpoint.clone()],
+            ..Default::default()
+        }),
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidCoinSelection { details: m })
+            if m == format!("outpoint {empty_outpoint} hosts no spendable allocations")
+    ));
+
+    // all asset outpoints excluded
+    let result = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        Some(CoinSelectionOptions {
+            excluded_outpoints: unspents
+                .iter()
+                .filter(|u| !u.rgb_allocations.is_empty())
+                .map(|u| u.utxo.outpoint.clone())
+                .collect(),
+            ..Default::default()
+        }),
+    );
+    assert_matches!(result, Err(Error::InsufficientAssignments { .. }));
+
+    // UTXOs hosting other assets are never selected when avoiding them
+    let mut party = get_funded_noutxo_party!();
+    party.create_utxos(false, Some(1), None, FEE_RATE, None);
+    let asset_1 = party.issue_asset_nia(Some(&[100]));
+    let asset_2 = party.issue_asset_nia(Some(&[200]));
+    let unspents = party.list_unspents(false);
+    let shared_outpoint = unspents
+        .iter()
+        .find(|u| !u.rgb_allocations.is_empty())
+        .unwrap()
+        .utxo
+        .outpoint
+        .clone();
+    assert_eq!(
+        unspents
+            .iter()
+            .find(|u| u.utxo.outpoint == shared_outpoint)
+            .unwrap()
+            .rgb_allocations
+            .len(),
+        2
+    );
+    let recipient_map = get_recipient_map(&mut rcv_party, &asset_1.asset_id, 50);
+    let result = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        Some(CoinSelectionOptions {
+            strategy: CoinSelectionStrategy::AvoidOtherAssets,
+            ..Default::default()
+        }),
+    );
+    assert_matches!(result, Err(Error::InsufficientAssignments { .. }));
+    // unless they are explicitly included
+    let inputs = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        Some(CoinSelectionOptions {
+            strategy: CoinSelectionStrategy::AvoidOtherAssets,
+            included_outpoints: vec![shared_outpoint.clone()],
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+    assert_eq!(inputs, HashSet::from([shared_outpoint.clone()]));
+    // or both assets are sent in the same batch
+    let mut recipient_map = recipient_map;
+    recipient_map.extend(get_recipient_map(&mut rcv_party, &asset_2.asset_id, 150));
+    let inputs = get_input_outpoints(
+        &mut party,
+        &recipient_map,
+        Some(CoinSelectionOptions {
+            strategy: CoinSelectionStrategy::AvoidOtherAssets,
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+    assert_eq!(inputs, HashSet::from([shared_outpoint]));
+}
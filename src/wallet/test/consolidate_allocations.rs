@@ -0,0 +1,283 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+fn asset_allocation_outpoints(party: &mut SinglesigParty, asset_id: &str) -> Vec<Outpoint> {
+    party
+        .list_unspents(false)
+        .into_iter()
+        .filter(|u| {
+            u.rgb_allocations
+                .iter()
+                .any(|a| a.asset_id.as_deref() == Some(asset_id))
+        })
+        .map(|u| u.utxo.outpoint)
+        .collect()
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+
+    // issue assets with allocations spread over multiple UTXOs
+    let asset_nia = party.issue_asset_nia(Some(&[100, 200, 300]));
+    let asset_cfa = party.issue_asset_cfa(Some(&[AMOUNT, AMOUNT]), None);
+    assert_eq!(
+        asset_allocation_outpoints(&mut party, &asset_nia.asset_id).len(),
+        3
+    );
+    assert_eq!(
+        asset_allocation_outpoints(&mut party, &asset_cfa.asset_id).len(),
+        2
+    );
+
+    // consolidate (begin) with dry run
+    let begin = party
+        .wallet
+        .consolidate_allocations_begin(party.online, None, FEE_RATE, MIN_CONFIRMATIONS, true)
+        .unwrap();
+    assert!(begin.batch_transfer_idx.is_none());
+    let mut asset_ids = vec![asset_nia.asset_id.clone(), asset_cfa.asset_id.clone()];
+    asset_ids.sort();
+    assert_eq!(begin.details.asset_ids, asset_ids);
+    // both assets fit in a single output (OP_RETURN + consolidation + BTC change)
+    let psbt = Psbt::from_str(&begin.psbt).unwrap();
+    assert_eq!(psbt.unsigned_tx.output.len(), 3);
+    assert_eq!(party.list_transfers(Some(&asset_nia.asset_id)).len(), 1);
+
+    // consolidate
+    let bak_info_before = party.db_backup_info();
+    let res = party
+        .wallet
+        .consolidate_allocations(party.online, None, FEE_RATE, MIN_CONFIRMATIONS)
+        .unwrap();
+    let bak_info_after = party.db_backup_info();
+    assert!(bak_info_after.last_operation_timestamp > bak_info_before.last_operation_timestamp);
+
+    // balances are unaffected, allocations are not spendable until confirmed
+    let balance = party.get_asset_balance(&asset_nia.asset_id);
+    assert_eq!(
+        balance,
+        Balance {
+            settled: 600,
+            future: 600,
+            spendable: 0,
+        }
+    );
+
+    // mine and refresh
+    mine(false);
+    assert!(party.refresh_asset(&asset_nia.asset_id));
+
+    // check balances
+    let balance = party.get_asset_balance(&asset_nia.asset_id);
+    assert_eq!(
+        balance,
+        Balance {
+            settled: 600,
+            future: 600,
+            spendable: 600,
+        }
+    );
+    let balance = party.get_asset_balance(&asset_cfa.asset_id);
+    assert_eq!(balance.spendable, AMOUNT * 2);
+
+    // check transfer info
+    let transfers = party.list_transfers(Some(&asset_nia.asset_id));
+    assert_eq!(transfers.len(), 2);
+    let transfer = transfers.last().unwrap();
+    assert_eq!(transfer.kind, TransferKind::Consolidation);
+    assert_eq!(transfer.status, TransferStatus::Settled);
+    assert_eq!(transfer.txid.as_ref().unwrap(), &res.txid);
+    assert_eq!(
+        transfer.requested_assignment.as_ref().unwrap(),
+        &Assignment::Fungible(600)
+    );
+    assert_eq!(transfer.assignments, vec![Assignment::Fungible(600)]);
+    assert!(transfer.recipient_id.is_some());
+    let receive_utxo = transfer.receive_utxo.clone().unwrap();
+    assert_eq!(receive_utxo.txid, res.txid);
+    assert!(transfer.transport_endpoints.is_empty());
+    let transfers = party.list_transfers(Some(&asset_cfa.asset_id));
+    let transfer_cfa = transfers.last().unwrap();
+    assert_eq!(transfer_cfa.kind, TransferKind::Consolidation);
+    assert_eq!(transfer_cfa.receive_utxo.as_ref().unwrap(), &receive_utxo);
+
+    // all allocations are now on a single UTXO
+    assert_eq!(
+        asset_allocation_outpoints(&mut party, &asset_nia.asset_id),
+        vec![receive_utxo.clone()]
+    );
+    assert_eq!(
+        asset_allocation_outpoints(&mut party, &asset_cfa.asset_id),
+        vec![receive_utxo]
+    );
+
+    // nothing left to consolidate
+    let result =
+        party
+            .wallet
+            .consolidate_allocations(party.online, None, FEE_RATE, MIN_CONFIRMATIONS);
+    assert_matches!(result, Err(Error::CannotConsolidate { details: _ }));
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn single_asset() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+
+    // issue
+    let asset_1 = party.issue_asset_nia(Some(&[AMOUNT, AMOUNT]));
+    let asset_2 = party.issue_asset_nia(Some(&[AMOUNT_SMALL, AMOUNT_SMALL]));
+
+    // consolidate only the first asset (begin + end)
+    let begin = party
+        .wallet
+        .consolidate_allocations_begin(
+            party.online,
+            Some(asset_1.asset_id.clone()),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+            false,
+        )
+        .unwrap();
+    assert!(begin.batch_transfer_idx.is_some());
+    assert_eq!(begin.details.asset_ids, vec![asset_1.asset_id.clone()]);
+    let signed_psbt = party.wallet.sign_psbt(begin.psbt, None).unwrap();
+    party
+        .wallet
+        .consolidate_allocations_end(party.online, signed_psbt)
+        .unwrap();
+    mine(false);
+    party.refresh_all();
+
+    // first asset is consolidated, second one is untouched
+    assert_eq!(
+        asset_allocation_outpoints(&mut party, &asset_1.asset_id).len(),
+        1
+    );
+    assert_eq!(
+        party.get_asset_balance(&asset_1.asset_id).spendable,
+        AMOUNT * 2
+    );
+    assert_eq!(
+        asset_allocation_outpoints(&mut party, &asset_2.asset_id).len(),
+        2
+    );
+    let transfers = party.list_transfers(Some(&asset_2.asset_id));
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].kind, TransferKind::Issuance);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn non_fungible_and_inflation() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+
+    // issue
+    let asset_uda = party.issue_asset_uda(None, None, vec![]);
+    let asset_ifa = party.issue_asset_ifa(Some(&[AMOUNT, AMOUNT]), None, None);
+    let uda_outpoints = asset_allocation_outpoints(&mut party, &asset_uda.asset_id);
+
+    // non-fungible allocations can't be consolidated
+    let result = party.wallet.consolidate_allocations(
+        party.online,
+        Some(asset_uda.asset_id.clone()),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert_matches!(result, Err(Error::CannotConsolidate { details: _ }));
+
+    // only the fungible allocations of the IFA asset are consolidated
+    let begin = party
+        .wallet
+        .consolidate_allocations_begin(party.online, None, FEE_RATE, MIN_CONFIRMATIONS, true)
+        .unwrap();
+    assert_eq!(begin.details.asset_ids, vec![asset_ifa.asset_id.clone()]);
+    party
+        .wallet
+        .consolidate_allocations(party.online, None, FEE_RATE, MIN_CONFIRMATIONS)
+        .unwrap();
+    mine(false);
+    party.refresh_all();
+
+    // the UDA is untouched and the inflation right is still available
+    assert_eq!(
+        asset_allocation_outpoints(&mut party, &asset_uda.asset_id),
+        uda_outpoints
+    );
+    assert_eq!(
+        party.get_asset_balance(&asset_ifa.asset_id).spendable,
+        AMOUNT * 2
+    );
+    let inflation_rights: Vec<Assignment> = party
+        .list_unspents(false)
+        .into_iter()
+        .flat_map(|u| u.rgb_allocations)
+        .filter(|a| a.asset_id.as_ref() == Some(&asset_ifa.asset_id))
+        .map(|a| a.assignment)
+        .filter(|a| matches!(a, Assignment::InflationRight(_)))
+        .collect();
+    assert_eq!(
+        inflation_rights,
+        vec![Assignment::InflationRight(AMOUNT_INFLATION)]
+    );
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    let mut party = get_funded_party!();
+
+    // unknown asset
+    let result = party.wallet.consolidate_allocations_begin(
+        party.online,
+        Some(s!("rgb1inexistent")),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+        false,
+    );
+    assert_matches!(result, Err(Error::AssetNotFound { asset_id: _ }));
+
+    // no assets
+    let result =
+        party
+            .wallet
+            .consolidate_allocations(party.online, None, FEE_RATE, MIN_CONFIRMATIONS);
+    assert_matches!(result, Err(Error::CannotConsolidate { details: _ }));
+
+    // single allocation
+    let asset = party.issue_asset_nia(Some(&[AMOUNT]));
+    let result = party.wallet.consolidate_allocations(
+        party.online,
+        Some(asset.asset_id),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert_matches!(result, Err(Error::CannotConsolidate { details: _ }));
+
+    // invalid fee rate
+    let asset = party.issue_asset_nia(Some(&[AMOUNT, AMOUNT]));
+    let result = party.wallet.consolidate_allocations(
+        party.online,
+        Some(asset.asset_id),
+        0,
+        MIN_CONFIRMATIONS,
+    );
+    assert_matches!(result, Err(Error::InvalidFeeRate { details: _ }));
+}
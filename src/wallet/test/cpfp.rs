@@ -0,0 +1,287 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 1000;
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_empty_party!();
+
+    // send BTC and get the TX broadcast without mining it
+    let _guard = stop_mining();
+    let parent_txid = party.send_btc(&rcv_party.get_address(), amount);
+
+    // CPFP (begin)
+    let unsigned_psbt_str = party
+        .wallet
+        .cpfp_begin(party.online, parent_txid.clone(), FEE_RATE * 5, false)
+        .unwrap();
+    let unsigned_psbt = Psbt::from_str(&unsigned_psbt_str).unwrap();
+    let child_txid = unsigned_psbt.unsigned_tx.compute_txid().to_string();
+    assert!(
+        unsigned_psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .any(|i| i.previous_output.txid.to_string() == parent_txid)
+    );
+    let pending = party.wallet.list_pending_vanilla_txs().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].txid, child_txid);
+    assert_eq!(pending[0].r#type, WalletTransactionType::Cpfp);
+
+    // CPFP (end)
+    let signed_psbt = party.wallet.sign_psbt(unsigned_psbt_str, None).unwrap();
+    let bak_info_before = party.db_backup_info();
+    let txid = party.wallet.cpfp_end(party.online, signed_psbt).unwrap();
+    let bak_info_after = party.db_backup_info();
+    assert!(bak_info_after.last_operation_timestamp > bak_info_before.last_operation_timestamp);
+    assert_eq!(txid, child_txid);
+    assert!(party.db_reserved_txos().is_empty());
+
+    // the child pays for the parent too
+    let transactions = party.list_transactions_with_sync();
+    let parent = transactions.iter().find(|t| t.txid == parent_txid).unwrap();
+    let child = transactions.iter().find(|t| t.txid == child_txid).unwrap();
+    assert_eq!(parent.transaction_type, TransactionType::SendBtc);
+    assert_eq!(child.transaction_type, TransactionType::Cpfp);
+    assert!(child.fee > parent.fee * 5);
+
+    // both TXs get mined
+    drop(_guard);
+    mine_tx(false, &child_txid);
+    let transactions = party.list_transactions_with_sync();
+    assert!(
+        transactions
+            .iter()
+            .filter(|t| [&parent_txid, &child_txid].contains(&&t.txid))
+            .all(|t| t.confirmation_time.is_some())
+    );
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success_colored() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // issue
+    let asset = party.issue_asset_nia(None);
+
+    // send to a witness recipient and get the TX broadcast without mining it
+    let _guard = stop_mining();
+    let receive_data = rcv_party.witness_receive();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: Some(WitnessData {
+                amount_sat: 1000,
+                blinding: None,
+            }),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let parent_txid = party.send_retry(&recipient_map);
+    rcv_party.refresh_all();
+    party.refresh_all();
+    assert!(rcv_party.check_test_transfer_status_recipient(
+        &receive_data.recipient_id,
+        TransferStatus::WaitingConfirmations
+    ));
+
+    // the transfer can also be accelerated by its index, a dry run leaving no trace
+    let rcv_transfer = rcv_party
+        .list_transfers(Some(&asset.asset_id))
+        .into_iter()
+        .find(|t| t.txid.as_ref() == Some(&parent_txid))
+        .unwrap();
+    let dry_run_psbt_str = rcv_party
+        .wallet
+        .cpfp_transfer_begin(
+            rcv_party.online,
+            rcv_transfer.batch_transfer_idx,
+            FEE_RATE * 5,
+            true,
+        )
+        .unwrap();
+    let dry_run_psbt = Psbt::from_str(&dry_run_psbt_str).unwrap();
+    assert!(
+        dry_run_psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .any(|i| i.previous_output.txid.to_string() == parent_txid)
+    );
+    assert!(rcv_party.db_reserved_txos().is_empty());
+
+    // the recipient only owns the output holding the received allocation
+    let unsigned_psbt_str = rcv_party
+        .wallet
+        .cpfp_begin(rcv_party.online, parent_txid.clone(), FEE_RATE * 5, false)
+        .unwrap();
+    let unsigned_psbt = Psbt::from_str(&unsigned_psbt_str).unwrap();
+    let child_txid = unsigned_psbt.unsigned_tx.compute_txid().to_string();
+    assert!(
+        unsigned_psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .any(|i| i.previous_output.txid.to_string() == parent_txid)
+    );
+    assert!(
+        unsigned_psbt.unsigned_tx.output[0]
+            .script_pubkey
+            .is_op_return()
+    );
+    let signed_psbt = rcv_party.wallet.sign_psbt(unsigned_psbt_str, None).unwrap();
+    let txid = rcv_party
+        .wallet
+        .cpfp_end(rcv_party.online, signed_psbt)
+        .unwrap();
+    assert_eq!(txid, child_txid);
+
+    // the child re-assigns the received allocation to a new UTXO
+    let transfers = rcv_party.list_transfers(Some(&asset.asset_id));
+    let child_transfer = transfers
+        .iter()
+        .find(|t| t.txid.as_ref() == Some(&child_txid))
+        .unwrap();
+    assert_eq!(child_transfer.kind, TransferKind::Consolidation);
+    assert_eq!(child_transfer.status, TransferStatus::WaitingConfirmations);
+    assert_eq!(
+        child_transfer.requested_assignment,
+        Some(Assignment::Fungible(amount))
+    );
+
+    // the child pays for the parent too
+    let transactions = rcv_party.list_transactions_with_sync();
+    let child = transactions.iter().find(|t| t.txid == child_txid).unwrap();
+    let parent_fee = party
+        .list_transactions_with_sync()
+        .into_iter()
+        .find(|t| t.txid == parent_txid)
+        .unwrap()
+        .fee;
+    assert!(child.fee > parent_fee * 4);
+
+    // both TXs get mined and the allocation ends up on the child output
+    drop(_guard);
+    mine_tx(false, &child_txid);
+    rcv_party.wait_for_refresh(None);
+    party.wait_for_refresh(Some(&asset.asset_id));
+    assert!(
+        rcv_party.check_test_transfer_status_recipient(
+            &receive_data.recipient_id,
+            TransferStatus::Settled
+        )
+    );
+    rcv_party.refresh_all();
+    let transfers = rcv_party.list_transfers(Some(&asset.asset_id));
+    assert!(
+        transfers
+            .iter()
+            .all(|t| t.status == TransferStatus::Settled)
+    );
+    assert_eq!(rcv_party.get_asset_balance(&asset.asset_id).settled, amount);
+    let unspents = rcv_party.list_unspents(true);
+    let unspent = unspents
+        .iter()
+        .find(|u| {
+            u.rgb_allocations
+                .iter()
+                .any(|a| a.asset_id.as_ref() == Some(&asset.asset_id))
+        })
+        .unwrap();
+    assert_eq!(unspent.utxo.outpoint.txid, child_txid);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // invalid TXID
+    let result = party.wallet.cpfp(party.online, s!("invalid"), FEE_RATE);
+    assert_matches!(result, Err(Error::InvalidTxid));
+
+    // unknown transfer
+    let result = party
+        .wallet
+        .cpfp_transfer(party.online, UNKNOWN_IDX, FEE_RATE);
+    assert!(matches!(
+        result,
+        Err(Error::BatchTransferNotFound { idx }) if idx == UNKNOWN_IDX
+    ));
+
+    // unknown TX
+    let result = party.wallet.cpfp(party.online, "0".repeat(64), FEE_RATE);
+    assert!(matches!(
+        result,
+        Err(Error::CannotCpfp { details: m }) if m == "the TX is unknown to the wallet"
+    ));
+
+    let _guard = stop_mining();
+    let asset = party.issue_asset_nia(None);
+    let txid = party.send_btc(&rcv_party.get_address(), 1000);
+
+    // invalid fee rate
+    let result = party.wallet.cpfp(party.online, txid.clone(), 0);
+    assert_matches!(result, Err(Error::InvalidFeeRate { details: _ }));
+
+    // fee rate already paid by the TX
+    let result = party.wallet.cpfp(party.online, txid.clone(), FEE_RATE);
+    assert!(matches!(
+        result,
+        Err(Error::CannotCpfp { details: m }) if m == "the TX already pays the requested fee rate"
+    ));
+
+    // the only output of the recipient holds RGB allocations whose consignment is still missing
+    let receive_data = rcv_party.witness_receive();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(66),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: Some(WitnessData {
+                amount_sat: 1000,
+                blinding: None,
+            }),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let rgb_txid = party.send_retry(&recipient_map);
+    let result = rcv_party
+        .wallet
+        .cpfp(rcv_party.online, rgb_txid.clone(), FEE_RATE * 5);
+    assert!(matches!(
+        result,
+        Err(Error::CannotCpfp { details: m })
+            if m == "the TX output holding RGB allocations cannot be spent yet"
+    ));
+
+    // TX already confirmed
+    drop(_guard);
+    mine_tx(false, &txid);
+    let result = party.wallet.cpfp(party.online, txid, FEE_RATE * 5);
+    assert!(matches!(
+        result,
+        Err(Error::CannotCpfp { details: m }) if m == "the TX is already confirmed"
+    ));
+}
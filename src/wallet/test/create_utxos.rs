@@ -398,7 +398,7 @@ fn begin_reservation_interactions() {
     assert!(reservations.iter().all(|r| r.reserved_for == Some(wt.idx)));
     let reserved_set: HashSet<(String, u32)> = reservations
         .iter()
-        .map(|r| (r.txid.clone(), r.vout))
+        .map(|r| (r.txid.clone(), r.vout as u32))
         .collect();
     assert_eq!(reserved_set, psbt_inputs);
 
@@ -428,7 +428,7 @@ fn begin_reservation_interactions() {
     let reserved_set: HashSet<(String, u32)> = party
         .db_reserved_txos()
         .iter()
-        .map(|r| (r.txid.clone(), r.vout))
+        .map(|r| (r.txid.clone(), r.vout as u32))
         .collect();
     assert_eq!(reserved_set.len(), 1);
 
@@ -101,6 +101,7 @@ fn success() {
             FEE_RATE,
             MIN_CONFIRMATIONS,
             default_send_expiration(),
+            None,
             false,
         )
         .unwrap();
@@ -140,6 +141,7 @@ fn success() {
             FEE_RATE,
             MIN_CONFIRMATIONS,
             default_send_expiration(),
+            None,
             false,
         )
         .unwrap();
@@ -166,6 +168,7 @@ fn success() {
         .blind_receive(
             Some(asset.asset_id),
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -275,6 +278,7 @@ fn fail() {
         .blind_receive(
             Some(asset.asset_id),
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -0,0 +1,168 @@
+use super::*;
+
+fn get_encrypted_wallet_data(password: Option<&str>) -> WalletData {
+    let test_data_dir = create_test_data_dir();
+    let mut wallet_data = get_test_wallet_data(&test_data_dir.to_string_lossy());
+    wallet_data.encryption_password = password.map(|p| p.to_string());
+    wallet_data
+}
+
+fn file_contains(path: &Path, needle: &[u8]) -> bool {
+    let content = fs::read(path).unwrap();
+    content.windows(needle.len()).any(|w| w == needle)
+}
+
+#[test]
+#[parallel]
+fn success() {
+    let keys = generate_keys(BitcoinNetwork::Regtest, WitnessVersion::Taproot);
+    let wallet_data = get_encrypted_wallet_data(Some(PASSWORD));
+    let mut wallet =
+        Wallet::new(wallet_data.clone(), SinglesigKeys::from_keys(&keys, None)).unwrap();
+    let wallet_dir = wallet.get_wallet_dir();
+    let address = wallet.get_address().unwrap();
+    drop(wallet);
+
+    // databases are not readable without the key
+    let rgb_lib_db = wallet_dir.join(RGB_LIB_DB_NAME);
+    assert!(!file_contains(&rgb_lib_db, b"SQLite format 3"));
+    assert!(!file_contains(&rgb_lib_db, b"backup_info"));
+    let bdk_db = wallet_dir.join(BDK_DB_NAME);
+    assert!(!file_contains(
+        &bdk_db,
+        keys.account_xpub_colored.as_bytes()
+    ));
+    assert!(!file_contains(&bdk_db, b"descriptor"));
+
+    // the wallet loads back with the same password
+    let mut loaded = Wallet::load(
+        &wallet_data.data_dir,
+        &keys.master_fingerprint,
+        Some(keys.mnemonic.clone()),
+        Some(PASSWORD.to_string()),
+    )
+    .unwrap();
+    // the password is never kept, serialized nor printed
+    assert_eq!(loaded.get_wallet_data().encryption_password, None);
+    assert!(
+        !serde_json::to_string(&wallet_data)
+            .unwrap()
+            .contains(PASSWORD)
+    );
+    assert!(!format!("{wallet_data:?}").contains(PASSWORD));
+    assert_ne!(loaded.get_address().unwrap(), address);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn operations_success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let keys = generate_keys(BitcoinNetwork::Regtest, WitnessVersion::Taproot);
+    let wallet_data = get_encrypted_wallet_data(Some(PASSWORD));
+    let mut wallet =
+        Wallet::new(wallet_data.clone(), SinglesigKeys::from_keys(&keys, None)).unwrap();
+    let online = wallet.go_online(test_go_online_options(None)).unwrap();
+    let mut party = party!(wallet, online);
+    fund_wallet(party.get_address());
+    party.create_utxos_default();
+    let mut rcv_party = get_funded_party!();
+
+    // issue and send
+    let asset = party.issue_asset_nia(None);
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id,
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let txid = party.send_retry(&recipient_map);
+    assert!(!txid.is_empty());
+    rcv_party.wait_for_refresh(None);
+    party.wait_for_refresh(Some(&asset.asset_id));
+    mine(false);
+    rcv_party.wait_for_refresh(Some(&asset.asset_id));
+    party.wait_for_refresh(Some(&asset.asset_id));
+    let btc_balance = party.get_btc_balance();
+    drop(party);
+
+    // all data is back after loading the wallet again
+    let wallet = Wallet::load(
+        &wallet_data.data_dir,
+        &keys.master_fingerprint,
+        Some(keys.mnemonic.clone()),
+        Some(PASSWORD.to_string()),
+    )
+    .unwrap();
+    let mut party = offline_party!(wallet);
+    let online = party.go_online(true, None);
+    let mut party = party!(party.wallet, online);
+    assert_eq!(
+        party.get_asset_balance(&asset.asset_id).settled,
+        AMOUNT - amount
+    );
+    assert_eq!(party.list_transfers(Some(&asset.asset_id)).len(), 2);
+    assert_eq!(party.get_btc_balance(), btc_balance);
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    let keys = generate_keys(BitcoinNetwork::Regtest, WitnessVersion::Taproot);
+    let wallet_data = get_encrypted_wallet_data(Some(PASSWORD));
+    let mut wallet =
+        Wallet::new(wallet_data.clone(), SinglesigKeys::from_keys(&keys, None)).unwrap();
+    wallet.get_address().unwrap();
+    drop(wallet);
+
+    // wrong password
+    let result = Wallet::load(
+        &wallet_data.data_dir,
+        &keys.master_fingerprint,
+        Some(keys.mnemonic.clone()),
+        Some("wrong password".to_string()),
+    );
+    assert!(matches!(result, Err(Error::WrongPassword)));
+
+    // missing password
+    let result = Wallet::load(
+        &wallet_data.data_dir,
+        &keys.master_fingerprint,
+        Some(keys.mnemonic.clone()),
+        None,
+    );
+    assert!(matches!(result, Err(Error::PasswordRequired)));
+
+    // a plaintext wallet can't be switched to encryption
+    let keys = generate_keys(BitcoinNetwork::Regtest, WitnessVersion::Taproot);
+    let wallet_data = get_encrypted_wallet_data(None);
+    let wallet = Wallet::new(wallet_data.clone(), SinglesigKeys::from_keys(&keys, None)).unwrap();
+    drop(wallet);
+    let result = Wallet::load(
+        &wallet_data.data_dir,
+        &keys.master_fingerprint,
+        Some(keys.mnemonic.clone()),
+        Some(PASSWORD.to_string()),
+    );
+    assert!(
+        matches!(result, Err(Error::WalletSettingMismatch { setting, .. }) if setting == "encryption_password")
+    );
+
+    // encryption is not supported for PostgreSQL databases
+    let keys = generate_keys(BitcoinNetwork::Regtest, WitnessVersion::Taproot);
+    let mut wallet_data = get_encrypted_wallet_data(Some(PASSWORD));
+    wallet_data.database_type = DatabaseType::Postgres {
+        url: "postgres://postgres@127.0.0.1:5432/postgres".to_string(),
+        schema: format!("wallet_{}", keys.master_fingerprint),
+    };
+    let result = Wallet::new(wallet_data, SinglesigKeys::from_keys(&keys, None));
+    assert!(matches!(result, Err(Error::UnsupportedDatabaseEncryption)));
+}
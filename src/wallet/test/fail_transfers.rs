@@ -287,6 +287,7 @@ fn fail() {
         .blind_receive(
             Some(asset.asset_id),
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -650,6 +651,7 @@ fn waiting_safe_height() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             (now().unix_timestamp() + DURATION_RCV_TRANSFER as i64) as u64,
             TRANSPORT_ENDPOINTS.clone(),
             2,
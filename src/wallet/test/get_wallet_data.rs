@@ -15,6 +15,7 @@ fn success() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: 1,
             supported_schemas: AssetSchema::VALUES.to_vec(),
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys, Some(2)),
     )
@@ -41,6 +42,7 @@ fn success() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: 5,
             supported_schemas: AssetSchema::VALUES.to_vec(),
+            encryption_password: None,
         },
         SinglesigKeys::from_keys_no_mnemonic(&keys_2, None),
     )
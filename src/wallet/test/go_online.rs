@@ -165,17 +165,14 @@ fn consistency_check_fail_bitcoins() {
     let mut party_empty = offline_party!(Wallet::new(wallet_data_empty, keys.clone()).unwrap());
     let online_empty = party_empty.go_online(false, None);
     let mut party_empty = party!(party_empty.wallet, online_empty);
-    let request = party_empty.wallet.bdk_wallet().start_full_scan();
+    let request = || party_empty.wallet.bdk_wallet().start_full_scan();
     let update = party_empty.wallet.indexer().full_scan(request).unwrap();
     party_empty
         .wallet
         .bdk_wallet_mut()
         .apply_update(update)
         .unwrap();
-    {
-        let (bdk_wallet, bdk_database) = party_empty.wallet.bdk_wallet_db_mut();
-        bdk_wallet.persist(bdk_database).unwrap();
-    }
+    party_empty.wallet.bdk().persist().unwrap();
     let mut rcv_party = get_funded_party!();
     party_empty.drain_to(&rcv_party.get_address());
 
@@ -257,17 +254,14 @@ fn consistency_check_fail_utxos() {
     let mut party_empty = offline_party!(Wallet::new(wallet_data_empty, keys.clone()).unwrap());
     let online_empty = party_empty.go_online(false, None);
     let mut party_empty = party!(party_empty.wallet, online_empty);
-    let request = party_empty.wallet.bdk_wallet().start_full_scan();
+    let request = || party_empty.wallet.bdk_wallet().start_full_scan();
     let update = party_empty.wallet.indexer().full_scan(request).unwrap();
     party_empty
         .wallet
         .bdk_wallet_mut()
         .apply_update(update)
         .unwrap();
-    {
-        let (bdk_wallet, bdk_database) = party_empty.wallet.bdk_wallet_db_mut();
-        bdk_wallet.persist(bdk_database).unwrap();
-    }
+    party_empty.wallet.bdk().persist().unwrap();
     let mut rcv_party = get_funded_party!();
     party_empty.drain_to(&rcv_party.get_address());
 
@@ -421,6 +415,160 @@ fn consistency_check_fail_media() {
     assert!(matches!(result, Err(Error::Inconsistency { details: e }) if e == err));
 }
 
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn multiple_indexers() {
+    initialize();
+
+    let unreachable_url = "127.0.0.1:50099";
+    let mut party = offline_party!(get_test_wallet(true, None));
+
+    // cannot go online with a quorum greater than the number of indexers
+    let online_options = OnlineOptions {
+        fallback_indexer_urls: vec![ELECTRUM_2_URL.to_string()],
+        indexer_quorum: Some(3),
+        ..test_go_online_options(None)
+    };
+    let result = party.wallet.go_online(online_options);
+    let details = "indexer quorum must be between 2 and the number of indexers (2)";
+    assert!(matches!(result, Err(Error::InvalidIndexer { details: m }) if m == details));
+
+    // cannot go online if fewer indexers than the quorum are reachable
+    let online_options = OnlineOptions {
+        fallback_indexer_urls: vec![ELECTRUM_URL.to_string()],
+        indexer_quorum: Some(2),
+        ..test_go_online_options(Some(unreachable_url))
+    };
+    let result = party.wallet.go_online(online_options);
+    assert!(matches!(result, Err(Error::InvalidIndexer { .. })));
+
+    // unreachable indexers are skipped in favor of the fallback ones
+    let online_options = OnlineOptions {
+        fallback_indexer_urls: vec![ELECTRUM_URL.to_string()],
+        ..test_go_online_options(Some(unreachable_url))
+    };
+    let online = party.wallet.go_online(online_options).unwrap();
+    let mut party = party!(party.wallet, online);
+    fund_wallet(party.wallet.get_address().unwrap());
+    party.create_utxos_default();
+
+    // can go online with a quorum of indexers
+    let online_options = OnlineOptions {
+        fallback_indexer_urls: vec![ELECTRUM_2_URL.to_string()],
+        indexer_quorum: Some(2),
+        ..test_go_online_options(None)
+    };
+    party.online = party.wallet.go_online(online_options).unwrap();
+    let unspents = party.list_unspents_with_sync(false);
+    assert!(!unspents.is_empty());
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn socks5_proxy() {
+    initialize();
+
+    let unreachable_proxy = s!("127.0.0.1:9099");
+
+    // proxy address and stream isolation credentials
+    let network = NetworkOptions {
+        socks5_proxy: Some(unreachable_proxy.clone()),
+        service_proxies: vec![ServiceProxy {
+            service: NetworkService::Indexer,
+            socks5_proxy: None,
+        }],
+        stream_isolation: true,
+        ..Default::default()
+    };
+    assert!(
+        network
+            .socks5_proxy(NetworkService::Indexer, None)
+            .is_none()
+    );
+    let socks5_proxy = network.socks5_proxy(NetworkService::Proxy, None).unwrap();
+    assert_eq!(socks5_proxy.url(), format!("socks5h://{unreachable_proxy}"));
+    let url_1 = network
+        .socks5_proxy(NetworkService::Proxy, Some("recipient_1"))
+        .unwrap()
+        .url();
+    let url_2 = network
+        .socks5_proxy(NetworkService::Proxy, Some("recipient_2"))
+        .unwrap()
+        .url();
+    assert!(url_1.starts_with("socks5h://rgb-lib:"));
+    assert_ne!(url_1, url_2);
+
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+    let asset = party.issue_asset_nia(None);
+    let receive_data = rcv_party.blind_receive();
+
+    // cannot reach the indexer through an unreachable proxy
+    let online_options = OnlineOptions {
+        network: NetworkOptions {
+            socks5_proxy: Some(unreachable_proxy.clone()),
+            ..Default::default()
+        },
+        ..test_go_online_options(None)
+    };
+    let result = party.wallet.go_online(online_options);
+    assert!(matches!(result, Err(Error::InvalidIndexer { .. })));
+
+    // indexer connections bypass the proxy, RGB proxy connections go through it
+    let online_options = OnlineOptions {
+        network,
+        ..test_go_online_options(None)
+    };
+    party.online = party.wallet.go_online(online_options).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(AMOUNT),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let result = party.send_begin_result(&recipient_map);
+    let msg = s!("no valid transport endpoints");
+    assert!(matches!(
+        result,
+        Err(Error::InvalidTransportEndpoints { details: m }) if m == msg
+    ));
+
+    // going online without the proxy restores direct connections
+    party.online = party
+        .wallet
+        .go_online(test_go_online_options(None))
+        .unwrap();
+    party.send_begin_result(&recipient_map).unwrap();
+}
+
+#[cfg(all(feature = "electrum", feature = "cbf"))]
+#[test]
+#[parallel]
+fn socks5_proxy_cbf() {
+    initialize();
+
+    let mut party = offline_party!(get_test_wallet(true, None));
+    let online_options = OnlineOptions {
+        network: NetworkOptions {
+            socks5_proxy: Some(s!("127.0.0.1:9099")),
+            ..Default::default()
+        },
+        cbf: Some(CbfOptions {
+            peers: vec![CBF_PEER.to_string()],
+            start_height: 0,
+        }),
+        ..test_go_online_options(None)
+    };
+    let result = party.wallet.go_online(online_options);
+    let details = "compact block filters cannot be used with a SOCKS5 proxy";
+    assert!(matches!(result, Err(Error::InvalidIndexer { details: m }) if m == details));
+}
+
 #[cfg(feature = "electrum")]
 #[test]
 #[parallel]
@@ -348,6 +348,7 @@ fn fail() {
         vec![],
         FEE_RATE,
         MIN_CONFIRMATIONS,
+        None,
         false,
     );
     assert_matches!(result, Err(Error::Offline));
@@ -408,6 +409,7 @@ fn fail() {
         vec![1],
         0,
         MIN_CONFIRMATIONS,
+        None,
         false,
     );
     assert_matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == FEE_MSG_LOW);
@@ -418,6 +420,7 @@ fn fail() {
         vec![1],
         u64::MAX,
         MIN_CONFIRMATIONS,
+        None,
         false,
     );
     assert_matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == FEE_MSG_OVER);
@@ -437,6 +440,7 @@ fn fail() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: vec![AssetSchema::Nia, AssetSchema::Ifa],
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys, None),
     )
@@ -478,6 +482,7 @@ fn fail() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: vec![AssetSchema::Nia],
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys, None),
     )
@@ -560,6 +565,7 @@ fn begin_end() {
             vec![1000],
             FEE_RATE,
             MIN_CONFIRMATIONS,
+            None,
             true,
         )
         .unwrap();
@@ -579,6 +585,7 @@ fn begin_end() {
             vec![1000],
             FEE_RATE,
             MIN_CONFIRMATIONS,
+            None,
             false,
         )
         .unwrap();
@@ -19,7 +19,8 @@ fn check_unspents_derivation(wallet: &Wallet, unspents: &[Unspent]) {
             .unwrap()
             .script_pubkey();
         let txid: bdk_wallet::bitcoin::Txid = unspent.utxo.outpoint.txid.parse().unwrap();
-        let tx = wallet.bdk_wallet().get_tx(txid).unwrap();
+        let bdk_wallet = wallet.bdk_wallet();
+        let tx = bdk_wallet.get_tx(txid).unwrap();
         let actual_spk = tx.tx_node.tx.output[unspent.utxo.outpoint.vout as usize]
             .script_pubkey
             .clone();
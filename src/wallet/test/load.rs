@@ -15,6 +15,7 @@ fn success() {
         database_type: DatabaseType::Sqlite,
         max_allocations_per_utxo: 1,
         supported_schemas: vec![AssetSchema::Nia, AssetSchema::Cfa],
+        encryption_password: None,
     };
     let wallet_keys = SinglesigKeys::from_keys(&keys, Some(2));
     let wallet = Wallet::new(wallet_data.clone(), wallet_keys.clone()).unwrap();
@@ -27,6 +28,7 @@ fn success() {
         &test_data_dir_str,
         &keys.master_fingerprint,
         Some(keys.mnemonic.clone()),
+        None,
     )
     .unwrap();
 
@@ -67,7 +69,7 @@ fn watch_only_success() {
     drop(wallet);
 
     // omitting the mnemonic loads the wallet in watch-only mode
-    let loaded = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None).unwrap();
+    let loaded = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None, None).unwrap();
 
     let loaded_keys = loaded.get_keys();
     assert!(loaded_keys.mnemonic.is_none());
@@ -90,11 +92,12 @@ fn new_updates_manifest_success() {
         database_type: DatabaseType::Sqlite,
         max_allocations_per_utxo: 1,
         supported_schemas: vec![AssetSchema::Nia],
+        encryption_password: None,
     };
     let wallet = Wallet::new(wallet_data.clone(), SinglesigKeys::from_keys(&keys, None)).unwrap();
     drop(wallet);
 
-    let loaded = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None).unwrap();
+    let loaded = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None, None).unwrap();
     let loaded_data = loaded.get_wallet_data();
     assert_eq!(
         loaded_data.max_allocations_per_utxo,
@@ -117,7 +120,7 @@ fn new_updates_manifest_success() {
     .unwrap();
     drop(wallet);
 
-    let loaded = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None).unwrap();
+    let loaded = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None, None).unwrap();
     let loaded_data = loaded.get_wallet_data();
     assert_eq!(
         loaded_data.max_allocations_per_utxo,
@@ -146,7 +149,8 @@ fn watch_only_toggle_success() {
     // the mnemonic is the one setting that is meant to come and go across opens, so it's neither
     // recorded in the manifest nor checked against it: signing and watch-only opens keep separate
     // BDK stores and address the same scripts
-    let watch_only = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None).unwrap();
+    let watch_only =
+        Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None, None).unwrap();
     assert!(watch_only.get_keys().mnemonic.is_none());
     drop(watch_only);
 
@@ -154,6 +158,7 @@ fn watch_only_toggle_success() {
         &test_data_dir_str,
         &keys.master_fingerprint,
         Some(keys.mnemonic.clone()),
+        None,
     )
     .unwrap();
     assert_eq!(signing.get_keys().mnemonic, Some(keys.mnemonic.clone()));
@@ -172,6 +177,7 @@ fn watch_only_toggle_success() {
         &test_data_dir_str,
         &keys_2.master_fingerprint,
         Some(keys_2.mnemonic.clone()),
+        None,
     )
     .unwrap();
     assert_eq!(signing.get_keys().mnemonic, Some(keys_2.mnemonic));
@@ -229,6 +235,7 @@ fn new_immutable_settings_fail() {
         &test_data_dir_str,
         &keys.master_fingerprint,
         Some(keys.mnemonic.clone()),
+        None,
     )
     .unwrap();
     assert_eq!(
@@ -305,7 +312,7 @@ fn new_mutable_settings_success() {
     assert!(wallet.get_keys().mnemonic.is_none());
     drop(wallet);
 
-    let loaded = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None).unwrap();
+    let loaded = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None, None).unwrap();
     assert_eq!(
         loaded.get_wallet_data().max_allocations_per_utxo,
         wallet_data.max_allocations_per_utxo
@@ -343,7 +350,13 @@ fn restored_backup_success() {
 
     // the manifest lives in the wallet directory, so it rides along in the backup and the wallet
     // can be loaded from where it was restored, not just from where it was created
-    let loaded = Wallet::load(target_dir, &keys.master_fingerprint, Some(keys.mnemonic)).unwrap();
+    let loaded = Wallet::load(
+        target_dir,
+        &keys.master_fingerprint,
+        Some(keys.mnemonic),
+        None,
+    )
+    .unwrap();
     assert_eq!(loaded.get_descriptors(), descriptors);
     assert_eq!(loaded.get_wallet_data().data_dir, target_dir);
 }
@@ -351,7 +364,7 @@ fn restored_backup_success() {
 #[test]
 #[parallel]
 fn inexistent_data_dir_fail() {
-    let err = Wallet::load("/inexistent/data/dir", "deadbeef", None)
+    let err = Wallet::load("/inexistent/data/dir", "deadbeef", None, None)
         .err()
         .unwrap();
     assert_matches!(err, Error::InexistentDataDir);
@@ -375,7 +388,7 @@ fn inexistent_manifest_fail() {
     // a wallet directory from before manifest support looks like one with the manifest removed
     fs::remove_file(wallet_dir.join(WALLET_MANIFEST_FILE)).unwrap();
 
-    let err = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None)
+    let err = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None, None)
         .err()
         .unwrap();
     assert_matches!(err, Error::InexistentWalletManifest { .. });
@@ -386,7 +399,7 @@ fn inexistent_manifest_fail() {
         SinglesigKeys::from_keys(&keys, None),
     )
     .unwrap();
-    Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None).unwrap();
+    Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None, None).unwrap();
 }
 
 #[test]
@@ -409,7 +422,7 @@ fn unsupported_manifest_version_fail() {
     manifest["version"] = Value::from(u8::MAX);
     fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
 
-    let err = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None)
+    let err = Wallet::load(&test_data_dir_str, &keys.master_fingerprint, None, None)
         .err()
         .unwrap();
     assert_matches!(
@@ -438,6 +451,7 @@ fn wrong_mnemonic_fail() {
         &test_data_dir_str,
         &keys.master_fingerprint,
         Some(other_keys.mnemonic),
+        None,
     )
     .err()
     .unwrap();
@@ -473,7 +487,7 @@ fn manifest_fingerprint_mismatch_fail() {
     // a second, unrelated wallet at the fingerprint the manifest names
     fs::copy(manifest_1, &manifest_2).unwrap();
 
-    let err = Wallet::load(&test_data_dir_str, &keys_2.master_fingerprint, None)
+    let err = Wallet::load(&test_data_dir_str, &keys_2.master_fingerprint, None, None)
         .err()
         .unwrap();
     assert_matches!(err, Error::FingerprintMismatch);
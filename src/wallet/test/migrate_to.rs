@@ -0,0 +1,306 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+fn migration_invoice(party: &mut SinglesigParty, asset_id: &str, assignment: Assignment) -> String {
+    party
+        .wallet
+        .witness_receive(
+            Some(asset_id.to_string()),
+            assignment,
+            ReceivePolicy::Any,
+            default_rcv_expiration(),
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap()
+        .invoice
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut dest_party = get_empty_party!();
+
+    // issue assets, with allocations spread over multiple UTXOs
+    let asset_nia = party.issue_asset_nia(Some(&[100, 200]));
+    let asset_ifa = party.issue_asset_ifa(None, None, None);
+
+    // destination invoices, leaving the amounts open
+    let invoices = vec![
+        migration_invoice(&mut dest_party, &asset_nia.asset_id, Assignment::Any),
+        migration_invoice(&mut dest_party, &asset_ifa.asset_id, Assignment::Any),
+        migration_invoice(
+            &mut dest_party,
+            &asset_ifa.asset_id,
+            Assignment::InflationRight(0),
+        ),
+    ];
+    let address = dest_party.get_address();
+
+    // migrate (begin) with dry run: a single transaction spends all UTXOs
+    let num_unspents = party.list_unspents_with_sync(false).len();
+    let begin = party
+        .wallet
+        .migrate_to_begin(
+            party.online,
+            invoices.clone(),
+            address.clone(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+            true,
+        )
+        .unwrap();
+    assert!(begin.batch_transfer_idx.is_none());
+    assert!(begin.details.is_donation);
+    let psbt = Psbt::from_str(&begin.psbt).unwrap();
+    assert_eq!(psbt.unsigned_tx.input.len(), num_unspents);
+    // OP_RETURN + 3 witness recipients + BTC drain
+    assert_eq!(psbt.unsigned_tx.output.len(), 5);
+
+    // migrate
+    let bak_info_before = party.db_backup_info();
+    let res = party
+        .wallet
+        .migrate_to(party.online, invoices, address, FEE_RATE, MIN_CONFIRMATIONS)
+        .unwrap();
+    assert!(!res.txid.is_empty());
+    let bak_info_after = party.db_backup_info();
+    assert!(bak_info_after.last_operation_timestamp > bak_info_before.last_operation_timestamp);
+    let transactions = party.list_transactions();
+    let migration_tx = transactions.iter().find(|t| t.txid == res.txid).unwrap();
+    assert_eq!(migration_tx.transaction_type, TransactionType::Drain);
+
+    // settle the transfers
+    dest_party.wait_for_refresh(None);
+    mine(false);
+    dest_party.wait_for_refresh(Some(&asset_nia.asset_id));
+    dest_party.wait_for_refresh(Some(&asset_ifa.asset_id));
+    party.wait_for_refresh(Some(&asset_nia.asset_id));
+    party.wait_for_refresh(Some(&asset_ifa.asset_id));
+
+    // the destination wallet holds all assets and bitcoins
+    assert_eq!(
+        dest_party.get_asset_balance(&asset_nia.asset_id).settled,
+        300
+    );
+    assert_eq!(
+        dest_party.get_asset_balance(&asset_ifa.asset_id).settled,
+        AMOUNT
+    );
+    let dest_inflation: u64 = dest_party
+        .list_unspents(false)
+        .iter()
+        .flat_map(|u| &u.rgb_allocations)
+        .filter(|a| a.asset_id.as_deref() == Some(&asset_ifa.asset_id))
+        .map(|a| match a.assignment {
+            Assignment::InflationRight(amt) => amt,
+            _ => 0,
+        })
+        .sum();
+    assert_eq!(dest_inflation, AMOUNT_INFLATION);
+    assert!(dest_party.get_btc_balance_with_sync().vanilla.settled > 0);
+
+    // the migrated wallet is left empty
+    party.wait_for_unspents(false, 0);
+    assert_eq!(party.get_asset_balance(&asset_nia.asset_id).settled, 0);
+    assert_eq!(party.get_asset_balance(&asset_ifa.asset_id).settled, 0);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn begin_end_success() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut dest_party = get_empty_party!();
+
+    // issue
+    let asset = party.issue_asset_nia(None);
+
+    // migrate (begin) reserves the spent vanilla TXOs
+    let invoices = vec![migration_invoice(
+        &mut dest_party,
+        &asset.asset_id,
+        Assignment::Any,
+    )];
+    let begin = party
+        .wallet
+        .migrate_to_begin(
+            party.online,
+            invoices,
+            dest_party.get_address(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+            false,
+        )
+        .unwrap();
+    assert!(begin.batch_transfer_idx.is_some());
+    let result = party.wallet.send_btc_begin(
+        party.online,
+        dest_party.get_address(),
+        1000,
+        FEE_RATE,
+        false,
+        true,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InsufficientBitcoins {
+            needed: _,
+            available: _
+        })
+    ));
+
+    // sign and complete
+    let signed_psbt = party.wallet.sign_psbt(begin.psbt, None).unwrap();
+    let res = party
+        .wallet
+        .migrate_to_end(party.online, signed_psbt)
+        .unwrap();
+    assert_eq!(Some(res.batch_transfer_idx), begin.batch_transfer_idx);
+
+    // settle the transfer
+    dest_party.wait_for_refresh(None);
+    mine(false);
+    dest_party.wait_for_refresh(Some(&asset.asset_id));
+    assert_eq!(
+        dest_party.get_asset_balance(&asset.asset_id).settled,
+        AMOUNT
+    );
+    party.wait_for_unspents(false, 0);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut dest_party = get_empty_party!();
+    let address = dest_party.get_address();
+
+    // nothing to migrate
+    let result = party.wallet.migrate_to(
+        party.online,
+        vec![],
+        address.clone(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::CannotMigrate { details: _ })));
+
+    // issue
+    let asset_nia = party.issue_asset_nia(None);
+    let asset_ifa = party.issue_asset_ifa(None, None, None);
+    let invoice_nia = migration_invoice(&mut dest_party, &asset_nia.asset_id, Assignment::Any);
+    let invoice_ifa = migration_invoice(&mut dest_party, &asset_ifa.asset_id, Assignment::Any);
+    let invoice_ifa_inflation = migration_invoice(
+        &mut dest_party,
+        &asset_ifa.asset_id,
+        Assignment::InflationRight(0),
+    );
+
+    // bad address
+    let result = party.wallet.migrate_to(
+        party.online,
+        vec![invoice_nia.clone()],
+        s!("invalid address"),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidAddress { details: _ })));
+
+    // invalid invoice
+    let result = party.wallet.migrate_to(
+        party.online,
+        vec![s!("invalid invoice")],
+        address.clone(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidInvoice { details: _ })));
+
+    // invoice without an asset ID
+    let invoice = dest_party.witness_receive().invoice;
+    let result = party.wallet.migrate_to(
+        party.online,
+        vec![invoice],
+        address.clone(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidInvoice { details: m }) if m == "missing asset ID"));
+
+    // multiple invoices for the same asset
+    let result = party.wallet.migrate_to(
+        party.online,
+        vec![
+            invoice_nia.clone(),
+            migration_invoice(&mut dest_party, &asset_nia.asset_id, Assignment::Any),
+        ],
+        address.clone(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidInvoice { details: _ })));
+
+    // invoice requesting a different amount
+    let result = party.wallet.migrate_to(
+        party.online,
+        vec![
+            migration_invoice(
+                &mut dest_party,
+                &asset_nia.asset_id,
+                Assignment::Fungible(AMOUNT - 1),
+            ),
+            invoice_ifa.clone(),
+            invoice_ifa_inflation.clone(),
+        ],
+        address.clone(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidInvoice { details: _ })));
+
+    // missing invoice for an asset
+    let result = party.wallet.migrate_to(
+        party.online,
+        vec![invoice_nia.clone(), invoice_ifa.clone()],
+        address.clone(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(
+        matches!(result, Err(Error::CannotMigrate { details: m }) if m.contains("inflation rights"))
+    );
+    let result = party.wallet.migrate_to(
+        party.online,
+        vec![invoice_ifa, invoice_ifa_inflation],
+        address.clone(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(
+        matches!(result, Err(Error::CannotMigrate { details: m }) if m.contains(&asset_nia.asset_id))
+    );
+
+    // no private keys
+    let mut wo_party = get_funded_noutxo_party(false, None);
+    let result = wo_party.wallet.migrate_to(
+        wo_party.online,
+        vec![invoice_nia],
+        address,
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::WatchOnly)));
+}
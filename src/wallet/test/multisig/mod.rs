@@ -1225,11 +1225,25 @@ fn fail() {
             MultisigOnlineOptions {
                 hub_url: s!("invalid"),
                 hub_token: cosigner_tokens[0].to_string(),
+                cosigner_mnemonic: None,
             },
         )
         .unwrap_err();
     assert_matches!(err, Error::MultisigHubService { details: d } if d == "URL must be valid and start with http:// or https://");
 
+    // mnemonic not belonging to the hub user
+    let err = wlt_badtoken_multisig
+        .go_online(
+            test_go_online_options(None),
+            MultisigOnlineOptions {
+                hub_url: MULTISIG_HUB_URL.to_string(),
+                hub_token: cosigner_tokens[0].to_string(),
+                cosigner_mnemonic: Some(wlt_2_keys.mnemonic.clone()),
+            },
+        )
+        .unwrap_err();
+    assert_matches!(err, Error::InvalidCosigner { details: d } if d == "mnemonic doesn't belong to the hub user");
+
     // respond with PSBT that has no signatures
     send_sats_to_address(wlt_1.get_address(), Some(10_000));
     mine(false);
@@ -1244,11 +1258,18 @@ fn fail() {
         Error::InvalidPsbt { details: d } if d == "PSBT has no signatures"
     );
 
-    // cannot initiate a new operation if another is pending
+    // cannot spend the inputs reserved by a pending operation
     let err = wlt_1
         .create_utxos_init_res(false, None, None, FEE_RATE)
         .unwrap_err();
-    assert_matches!(err, Error::MultisigOperationInProgress);
+    assert_matches!(err, Error::InsufficientBitcoins { available: 0, .. });
+
+    // the cancellation needs to be signed with the cosigner mnemonic
+    let err = wlt_1
+        .multisig
+        .cancel_operation(wlt_1.online, op_idx_1)
+        .unwrap_err();
+    assert_matches!(err, Error::MultisigCannotCancelOperation { details: d } if d == "the cosigner mnemonic is needed to sign the cancellation");
 
     // respond to a non-pending operation
     let signed_psbt = wlt_1_singlesig
@@ -1398,10 +1419,10 @@ fn offline() {
     );
     assert_matches!(result, Err(Error::Offline));
 
-    let result = wallet.burn_init(fake_online, "aid".into(), 0, 0, 0);
+    let result = wallet.burn_init(fake_online, "aid".into(), 0, 0, 0, None);
     assert_matches!(result, Err(Error::Offline));
 
-    let result = wallet.create_utxos_init(fake_online, false, None, None, 0, false);
+    let result = wallet.create_utxos_init(fake_online, false, None, None, 0, false, None);
     assert_matches!(result, Err(Error::Offline));
 
     let result = wallet.fail_transfers(fake_online, None, false, false);
@@ -1413,7 +1434,7 @@ fn offline() {
     let result = wallet.get_fee_estimation(fake_online, 1);
     assert_matches!(result, Err(Error::Offline));
 
-    let result = wallet.inflate_init(fake_online, "aid".into(), vec![1], 0, 0);
+    let result = wallet.inflate_init(fake_online, "aid".into(), vec![1], 0, 0, None);
     assert_matches!(result, Err(Error::Offline));
 
     let result = wallet.issue_asset_cfa(fake_online, "n".into(), None, 0, vec![1], None);
@@ -1439,7 +1460,7 @@ fn offline() {
     let result = wallet.refresh(fake_online, None, vec![], false);
     assert_matches!(result, Err(Error::Offline));
 
-    let result = wallet.send_btc_init(fake_online, "addr".into(), 0, 0, false);
+    let result = wallet.send_btc_init(fake_online, "addr".into(), 0, 0, false, None);
     assert_matches!(result, Err(Error::Offline));
 
     let result = wallet.send_init(
@@ -1449,6 +1470,7 @@ fn offline() {
         0,
         0,
         default_send_expiration(),
+        None,
     );
     assert_matches!(result, Err(Error::Offline));
 
@@ -1752,3 +1774,1018 @@ fn send_to_oneself() {
         (AMOUNT_SMALL, AMOUNT_SMALL, AMOUNT_SMALL),
     );
 }
+
+#[cfg(feature = "electrum")]
+#[test]
+#[serial]
+fn recovery_policy() {
+    initialize();
+
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let random_str: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(6)
+        .map(char::from)
+        .collect();
+
+    // multisig wallet keys
+    let wlt_1_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let wlt_2_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let recovery_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+
+    // cosigners
+    let cosigners = vec![
+        Cosigner::from_keys(&wlt_1_keys, None),
+        Cosigner::from_keys(&wlt_2_keys, None),
+    ];
+    let recovery_cosigner = Cosigner::from_keys(&recovery_keys, None);
+
+    let data_dir = get_test_data_dir_path()
+        .join(format!("{random_str}_recovery_policy"))
+        .to_string_lossy()
+        .to_string();
+    let _ = fs::create_dir_all(&data_dir);
+
+    // without recovery policies descriptors have a single tapleaf
+    let wallet = MultisigWallet::new(
+        get_test_wallet_data(&data_dir),
+        MultisigKeys::new(cosigners.clone(), 2, 2),
+    )
+    .unwrap();
+    let descriptors = wallet.get_descriptors();
+    assert!(!descriptors.colored.contains("older("));
+    assert!(!descriptors.vanilla.contains("older("));
+    assert!(wallet.spending_policy_paths().is_empty());
+    drop(wallet);
+
+    // with recovery policies: a lower threshold of the group and a single recovery key
+    let mut multisig_wlt_keys = MultisigKeys::new(cosigners.clone(), 2, 2);
+    multisig_wlt_keys.recovery_policies = vec![
+        RecoveryPolicy {
+            after_blocks: 144,
+            cosigners: vec![],
+            threshold: 1,
+        },
+        RecoveryPolicy {
+            after_blocks: 4320,
+            cosigners: vec![recovery_cosigner.clone()],
+            threshold: 1,
+        },
+    ];
+    let mut wallet =
+        MultisigWallet::new(get_test_wallet_data(&data_dir), multisig_wlt_keys.clone()).unwrap();
+    assert_eq!(wallet.get_keys(), multisig_wlt_keys);
+    let descriptors = wallet.get_descriptors();
+    for descriptor in [&descriptors.colored, &descriptors.vanilla] {
+        assert!(descriptor.contains(",{multi_a(2,"));
+        assert!(descriptor.contains("{and_v(v:multi_a(1,"));
+        assert!(descriptor.contains("older(144)"));
+        assert!(descriptor.contains("older(4320)"));
+    }
+    assert!(
+        descriptors
+            .colored
+            .contains(&recovery_cosigner.account_xpub_colored)
+    );
+    assert!(
+        descriptors
+            .vanilla
+            .contains(&recovery_cosigner.account_xpub_vanilla)
+    );
+
+    // spending path selection: the regular multisig leaf by default
+    let check_policy_paths = |wallet: &MultisigWallet, item: usize| {
+        let policy_paths = wallet.spending_policy_paths();
+        assert_eq!(policy_paths.len(), 2);
+        for (_, policy_path) in policy_paths {
+            assert_eq!(
+                policy_path.into_values().collect::<Vec<_>>(),
+                vec![vec![item]]
+            );
+        }
+    };
+    check_policy_paths(&wallet, 1);
+    wallet.use_recovery_policy(Some(1)).unwrap();
+    check_policy_paths(&wallet, 3);
+    wallet.use_recovery_policy(Some(0)).unwrap();
+    check_policy_paths(&wallet, 2);
+    wallet.use_recovery_policy(None).unwrap();
+    check_policy_paths(&wallet, 1);
+
+    // unknown recovery policy
+    let result = wallet.use_recovery_policy(Some(2));
+    assert_matches!(result, Err(Error::InvalidRecoveryPolicy { details: d }) if d == "unknown policy 2");
+
+    // invalid recovery policies
+    let invalid_policy_keys = |recovery_policy: RecoveryPolicy| {
+        let mut keys = MultisigKeys::new(cosigners.clone(), 2, 2);
+        keys.recovery_policies = vec![recovery_policy];
+        keys
+    };
+    // - no timelock
+    let res = MultisigWallet::new(
+        get_test_wallet_data(&data_dir),
+        invalid_policy_keys(RecoveryPolicy {
+            after_blocks: 0,
+            cosigners: vec![],
+            threshold: 1,
+        }),
+    );
+    assert_matches!(res.as_ref().err().unwrap(), Error::InvalidRecoveryPolicy { details: d } if d == "policy 0 has a 0 blocks timelock");
+    // - k=0
+    let res = MultisigWallet::new(
+        get_test_wallet_data(&data_dir),
+        invalid_policy_keys(RecoveryPolicy {
+            after_blocks: 144,
+            cosigners: vec![],
+            threshold: 0,
+        }),
+    );
+    assert_matches!(res.as_ref().err().unwrap(), Error::InvalidRecoveryPolicy { details: d } if d == "policy 0 requires 0 signatures but has 2 cosigners");
+    // - threshold higher than the policy cosigners
+    let res = MultisigWallet::new(
+        get_test_wallet_data(&data_dir),
+        invalid_policy_keys(RecoveryPolicy {
+            after_blocks: 144,
+            cosigners: vec![recovery_cosigner.clone()],
+            threshold: 2,
+        }),
+    );
+    assert_matches!(res.as_ref().err().unwrap(), Error::InvalidRecoveryPolicy { details: d } if d == "policy 0 requires 2 signatures but has 1 cosigners");
+    // - invalid cosigner
+    let mut invalid_cosigner = recovery_cosigner.clone();
+    invalid_cosigner.master_fingerprint = s!("invalid");
+    let res = MultisigWallet::new(
+        get_test_wallet_data(&data_dir),
+        invalid_policy_keys(RecoveryPolicy {
+            after_blocks: 144,
+            cosigners: vec![invalid_cosigner],
+            threshold: 1,
+        }),
+    );
+    assert_matches!(res.as_ref().err().unwrap(), Error::InvalidCosigner { details: d } if d == "invalid master_fingerprint 'invalid'");
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[serial]
+fn hubless_recovery_policy() {
+    initialize();
+
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let random_str: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(6)
+        .map(char::from)
+        .collect();
+
+    // multisig wallet keys, with a single recovery key as recovery policy
+    let wlt_1_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let wlt_2_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let recovery_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let cosigners = vec![
+        Cosigner::from_keys(&wlt_1_keys, None),
+        Cosigner::from_keys(&wlt_2_keys, None),
+    ];
+    let recovery_cosigner = Cosigner::from_keys(&recovery_keys, None);
+    let after_blocks = 3;
+    let mut multisig_wlt_keys = MultisigKeys::new(cosigners, 2, 2);
+    multisig_wlt_keys.recovery_policies = vec![RecoveryPolicy {
+        after_blocks,
+        cosigners: vec![recovery_cosigner.clone()],
+        threshold: 1,
+    }];
+
+    // multisig wallets, the recovery cosigner can go online with its mnemonic
+    let mut wlt_1 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_1"));
+    let mut wlt_rec = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_rec"));
+    let wlt_1_online = wlt_1
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_1_keys.mnemonic.clone()),
+        )
+        .unwrap();
+    let wlt_rec_online = wlt_rec
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(recovery_keys.mnemonic.clone()),
+        )
+        .unwrap();
+    let wlt_rec_singlesig = get_test_wallet_with_keys(&recovery_keys);
+    let mut rcv_wlt = get_test_wallet(false, None);
+
+    // fund the wallet and wait for the recovery timelock to expire
+    send_sats_to_address(wlt_1.get_address(wlt_1_online).unwrap(), Some(30_000));
+    mine_blocks(false, after_blocks as u32);
+
+    // the selected recovery policy travels with the operation
+    let packages_dir = get_test_data_dir_path().join(format!("{random_str}_packages"));
+    fs::create_dir_all(&packages_dir).unwrap();
+    let package_path = |name: &str| packages_dir.join(name).to_string_lossy().to_string();
+    wlt_1.use_recovery_policy(Some(0)).unwrap();
+    let op_init = wlt_1
+        .send_btc_init(
+            wlt_1_online,
+            rcv_wlt.get_address().unwrap(),
+            1000,
+            FEE_RATE,
+            false,
+            None,
+        )
+        .unwrap();
+    wlt_1.use_recovery_policy(None).unwrap();
+    wlt_1
+        .export_operation(wlt_1_online, op_init.operation_idx, package_path("op_1"))
+        .unwrap();
+
+    // group cosigners not part of the policy cannot respond
+    let result = wlt_1.respond_to_operation(
+        wlt_1_online,
+        op_init.operation_idx,
+        RespondToOperation::Nack,
+    );
+    assert_matches!(result, Err(Error::MultisigCannotRespondToOperation { details: d }) if d == "not a cosigner of the operation spending policy");
+
+    // the approval threshold follows the policy, so the recovery cosigner completes the operation
+    let op_info = wlt_rec
+        .import_operation(wlt_rec_online, package_path("op_1"))
+        .unwrap();
+    let Operation::SendBtcToReview { psbt, status } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    assert_eq!(status.threshold, 1);
+    let signed = wlt_rec_singlesig.sign_psbt(psbt, None).unwrap();
+    let op_info = wlt_rec
+        .respond_to_operation(
+            wlt_rec_online,
+            op_init.operation_idx,
+            RespondToOperation::Ack(signed),
+        )
+        .unwrap();
+    let Operation::SendBtcCompleted { txid, status } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    assert_eq!(
+        status.acked_by,
+        set![recovery_cosigner.account_xpub_colored.clone()]
+    );
+    wlt_rec
+        .export_operation(
+            wlt_rec_online,
+            op_init.operation_idx,
+            package_path("op_1_final"),
+        )
+        .unwrap();
+
+    // the initiator processes the completed operation
+    let op_info = wlt_1
+        .import_operation(wlt_1_online, package_path("op_1_final"))
+        .unwrap();
+    assert_matches!(op_info.operation, Operation::SendBtcCompleted { txid: t, .. } if t == txid);
+    mine_tx(false, &txid);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[serial]
+fn musig() {
+    initialize();
+
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let random_str: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(6)
+        .map(char::from)
+        .collect();
+
+    // multisig wallet keys
+    let wlt_1_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let wlt_2_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+
+    // cosigners
+    let cosigners = vec![
+        Cosigner::from_keys(&wlt_1_keys, None),
+        Cosigner::from_keys(&wlt_2_keys, None),
+    ];
+
+    let data_dir = get_test_data_dir_path()
+        .join(format!("{random_str}_musig"))
+        .to_string_lossy()
+        .to_string();
+    let _ = fs::create_dir_all(&data_dir);
+
+    // MuSig2 requires n-of-n thresholds
+    let mut multisig_wlt_keys = MultisigKeys::new(cosigners.clone(), 1, 2);
+    multisig_wlt_keys.musig = true;
+    let res = MultisigWallet::new(get_test_wallet_data(&data_dir), multisig_wlt_keys);
+    assert_matches!(res.as_ref().err().unwrap(), Error::InvalidMultisigThreshold { required, total } if *required == 1 && *total == 2);
+    let mut multisig_wlt_keys = MultisigKeys::new(cosigners.clone(), 2, 1);
+    multisig_wlt_keys.musig = true;
+    let res = MultisigWallet::new(get_test_wallet_data(&data_dir), multisig_wlt_keys);
+    assert_matches!(res.as_ref().err().unwrap(), Error::InvalidMultisigThreshold { required, total } if *required == 1 && *total == 2);
+
+    // the internal key is the aggregate of the cosigner keys instead of the NUMS one
+    let mut multisig_wlt_keys = MultisigKeys::new(cosigners.clone(), 2, 2);
+    multisig_wlt_keys.musig = true;
+    let wallet =
+        MultisigWallet::new(get_test_wallet_data(&data_dir), multisig_wlt_keys.clone()).unwrap();
+    assert_eq!(wallet.get_keys(), multisig_wlt_keys);
+    let descriptors = wallet.get_descriptors();
+    let nums_descriptors = MultisigKeys::new(cosigners.clone(), 2, 2)
+        .build_descriptors(bitcoin_network)
+        .unwrap();
+    let internal_key = |descriptor: &str| descriptor.split(',').next().unwrap().to_string();
+    assert_ne!(
+        internal_key(&descriptors.colored),
+        internal_key(&nums_descriptors.colored)
+    );
+    assert_ne!(
+        internal_key(&descriptors.vanilla),
+        internal_key(&nums_descriptors.vanilla)
+    );
+    assert_ne!(
+        internal_key(&descriptors.colored),
+        internal_key(&descriptors.vanilla)
+    );
+    for descriptor in [&descriptors.colored, &descriptors.vanilla] {
+        assert!(descriptor.contains("/*,multi_a(2,"));
+    }
+
+    // the aggregate key doesn't depend on the cosigners order
+    let mut swapped_keys =
+        MultisigKeys::new(vec![cosigners[1].clone(), cosigners[0].clone()], 2, 2);
+    swapped_keys.musig = true;
+    assert_eq!(
+        swapped_keys.build_descriptors(bitcoin_network).unwrap(),
+        descriptors
+    );
+
+    // the aggregate xpub matches the one derived from the cosigners keys
+    let participants = multisig_wlt_keys
+        .musig_participants(bitcoin_network, true)
+        .unwrap();
+    let aggregate_xpub = musig::KeyAggContext::new(&participants)
+        .unwrap()
+        .aggregate_xpub(bitcoin_network.network_kind());
+    assert!(
+        descriptors
+            .colored
+            .starts_with(&format!("tr({aggregate_xpub}/*,"))
+    );
+
+    // nonces can't be posted offline
+    let result = wallet.post_musig_nonces(Online { id: 0 }, 0, s!(""));
+    assert_matches!(result, Err(Error::Offline));
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[serial]
+fn musig_key_path_spend() {
+    initialize();
+
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let random_str: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(6)
+        .map(char::from)
+        .collect();
+
+    // multisig wallet keys
+    let wlt_1_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let wlt_2_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+
+    // cosigners
+    let cosigners = vec![
+        Cosigner::from_keys(&wlt_1_keys, None),
+        Cosigner::from_keys(&wlt_2_keys, None),
+    ];
+    let mut multisig_wlt_keys = MultisigKeys::new(cosigners, 2, 2);
+    multisig_wlt_keys.musig = true;
+
+    // multisig wallets, coordinating without a hub
+    let mut wlt_1 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_1"));
+    let mut wlt_2 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_2"));
+    let wlt_1_online = wlt_1
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_1_keys.mnemonic.clone()),
+        )
+        .unwrap();
+    let wlt_2_online = wlt_2
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_2_keys.mnemonic.clone()),
+        )
+        .unwrap();
+
+    // singlesig wallets (for signing)
+    let wlt_1_singlesig = get_test_wallet_with_keys(&wlt_1_keys);
+    let wlt_2_singlesig = get_test_wallet_with_keys(&wlt_2_keys);
+
+    // fund wallet 1
+    send_sats_to_address(wlt_1.get_address(wlt_1_online).unwrap(), Some(30_000));
+    mine(false);
+
+    // the initiator adds its nonces to the operation
+    let packages_dir = get_test_data_dir_path().join(format!("{random_str}_packages"));
+    fs::create_dir_all(&packages_dir).unwrap();
+    let package_path = |name: &str| packages_dir.join(name).to_string_lossy().to_string();
+    let op_init = wlt_1
+        .create_utxos_init(wlt_1_online, false, None, None, FEE_RATE, false, None)
+        .unwrap();
+    let nonces_1 = wlt_1_singlesig
+        .musig_generate_nonces(op_init.psbt.clone())
+        .unwrap();
+    wlt_1
+        .post_musig_nonces(wlt_1_online, op_init.operation_idx, nonces_1)
+        .unwrap();
+    wlt_1
+        .export_operation(wlt_1_online, 1, package_path("op_1_nonces_1"))
+        .unwrap();
+
+    // the second cosigner adds its nonces, then signs and ACKs the PSBT including all of them
+    let op_info = wlt_2
+        .import_operation(wlt_2_online, package_path("op_1_nonces_1"))
+        .unwrap();
+    let Operation::CreateUtxosToReview { psbt, .. } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    let nonces_2 = wlt_2_singlesig.musig_generate_nonces(psbt).unwrap();
+    wlt_2.post_musig_nonces(wlt_2_online, 1, nonces_2).unwrap();
+    let op_info = wlt_2.sync_with_hub(wlt_2_online).unwrap().unwrap();
+    let Operation::CreateUtxosToReview { psbt, .. } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    let signed_2 = wlt_2_singlesig.sign_psbt(psbt, None).unwrap();
+    wlt_2
+        .respond_to_operation(wlt_2_online, 1, RespondToOperation::Ack(signed_2))
+        .unwrap();
+    wlt_2
+        .export_operation(wlt_2_online, 1, package_path("op_1_ack_2"))
+        .unwrap();
+
+    // the initiator signs after a restart, as secret nonces are persisted, completing the operation
+    drop(wlt_1_singlesig);
+    let wlt_1_singlesig = Wallet::load(
+        &get_test_data_dir_string(),
+        &wlt_1_keys.master_fingerprint,
+        Some(wlt_1_keys.mnemonic.clone()),
+        None,
+    )
+    .unwrap();
+    let op_info = wlt_1
+        .import_operation(wlt_1_online, package_path("op_1_ack_2"))
+        .unwrap();
+    let Operation::CreateUtxosToReview { psbt, .. } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    let signed_1 = wlt_1_singlesig.sign_psbt(psbt.clone(), None).unwrap();
+    // secret nonces are used only once
+    let has_partial_sigs = |psbt: &str| {
+        Psbt::from_str(psbt).unwrap().inputs.iter().all(|i| {
+            i.unknown
+                .keys()
+                .any(|k| k.type_value == musig::PSBT_IN_MUSIG2_PARTIAL_SIG)
+        })
+    };
+    assert!(has_partial_sigs(&signed_1));
+    let resigned_1 = wlt_1_singlesig.sign_psbt(psbt, None).unwrap();
+    assert!(!has_partial_sigs(&resigned_1));
+    let op_info = wlt_1
+        .respond_to_operation(wlt_1_online, 1, RespondToOperation::Ack(signed_1))
+        .unwrap();
+    let Operation::CreateUtxosCompleted { txid, .. } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+
+    // the transaction has been spent via the key path (a single signature in the witness)
+    let bdk_txid: bdk_wallet::bitcoin::Txid = txid.parse().unwrap();
+    let bdk_wallet = wlt_1.bdk_wallet();
+    let tx = bdk_wallet.get_tx(bdk_txid).unwrap();
+    for input in &tx.tx_node.tx.input {
+        assert_eq!(input.witness.len(), 1);
+        assert_eq!(input.witness.nth(0).unwrap().len(), 64);
+    }
+    drop(bdk_wallet);
+    mine_tx(false, &txid);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[serial]
+fn hubless() {
+    initialize();
+
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let random_str: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(6)
+        .map(char::from)
+        .collect();
+
+    // multisig wallet keys
+    let wlt_1_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let wlt_2_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+
+    // cosigners
+    let cosigners = vec![
+        Cosigner::from_keys(&wlt_1_keys, None),
+        Cosigner::from_keys(&wlt_2_keys, None),
+    ];
+    let cosigner_xpubs: Vec<String> = cosigners
+        .iter()
+        .map(|c| c.account_xpub_colored.clone())
+        .collect();
+    let multisig_wlt_keys = MultisigKeys::new(cosigners, 2, 2);
+
+    // multisig wallets
+    let mut wlt_1 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_1"));
+    let mut wlt_2 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_2"));
+    let mut wlt_3 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_3"));
+
+    // the mnemonic needs to belong to a cosigner
+    let unknown_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let result = wlt_1.go_online_without_hub(
+        test_go_online_options(None),
+        Some(unknown_keys.mnemonic.clone()),
+    );
+    assert_matches!(result, Err(Error::InvalidCosigner { details: _ }));
+
+    let wlt_1_online = wlt_1
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_1_keys.mnemonic.clone()),
+        )
+        .unwrap();
+    let wlt_2_online = wlt_2
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_2_keys.mnemonic.clone()),
+        )
+        .unwrap();
+    let wlt_3_online = wlt_3
+        .go_online_without_hub(test_go_online_options(None), None)
+        .unwrap();
+
+    // singlesig wallets (for signing)
+    let wlt_1_singlesig = get_test_wallet_with_keys(&wlt_1_keys);
+    let wlt_2_singlesig = get_test_wallet_with_keys(&wlt_2_keys);
+
+    // hub APIs are not available
+    let result = wlt_1.hub_info(wlt_1_online);
+    assert_matches!(result, Err(Error::MultisigCoordinationMode { details: d }) if d == "a hub is required");
+    assert!(wlt_1.sync_with_hub(wlt_1_online).unwrap().is_none());
+
+    // fund wallet 1
+    send_sats_to_address(wlt_1.get_address(wlt_1_online).unwrap(), Some(30_000));
+    mine(false);
+
+    // initiate the operation and ACK it
+    let packages_dir = get_test_data_dir_path().join(format!("{random_str}_packages"));
+    fs::create_dir_all(&packages_dir).unwrap();
+    let package_path = |name: &str| packages_dir.join(name).to_string_lossy().to_string();
+    let op_init = wlt_1
+        .create_utxos_init(wlt_1_online, false, None, None, FEE_RATE, false, None)
+        .unwrap();
+    assert_eq!(op_init.operation_idx, 1);
+    let result = wlt_1.create_utxos_init(wlt_1_online, false, None, None, FEE_RATE, false, None);
+    assert_matches!(
+        result,
+        Err(Error::InsufficientBitcoins { available: 0, .. })
+    );
+    let signed_1 = wlt_1_singlesig
+        .sign_psbt(op_init.psbt.clone(), None)
+        .unwrap();
+    let op_info = wlt_1
+        .respond_to_operation(wlt_1_online, 1, RespondToOperation::Ack(signed_1))
+        .unwrap();
+    assert_matches!(op_info.operation, Operation::CreateUtxosPending { .. });
+    wlt_1
+        .export_operation(wlt_1_online, 1, package_path("op_1_ack_1"))
+        .unwrap();
+
+    // unknown operations cannot be exported
+    let result = wlt_1.export_operation(wlt_1_online, 2, package_path("op_2"));
+    assert_matches!(
+        result,
+        Err(Error::MultisigOperationNotFound { operation_idx: 2 })
+    );
+
+    // contributions need to be signed by the cosigner they're attributed to
+    let package: serde_json::Value =
+        serde_json::from_slice(&fs::read(package_path("op_1_ack_1")).unwrap()).unwrap();
+    let tamper = |name: &str, f: &dyn Fn(&mut serde_json::Value)| {
+        let mut tampered = package.clone();
+        f(&mut tampered);
+        fs::write(package_path(name), serde_json::to_vec(&tampered).unwrap()).unwrap();
+        package_path(name)
+    };
+    let result = wlt_2.import_operation(
+        wlt_2_online,
+        tamper("op_1_created_at", &|p| p["created_at"] = 0.into()),
+    );
+    assert_matches!(result, Err(Error::InvalidOperationPackage { details: d }) if d == "invalid initiator signature");
+    let result = wlt_2.import_operation(
+        wlt_2_online,
+        tamper("op_1_nack", &|p| {
+            p["nacked_by"][&cosigner_xpubs[1]] = p["initiator_signature"].clone()
+        }),
+    );
+    assert_matches!(result, Err(Error::InvalidOperationPackage { details: d }) if d == format!("invalid NACK signature of cosigner '{}'", cosigner_xpubs[1]));
+    let result = wlt_2.import_operation(
+        wlt_2_online,
+        tamper("op_1_poster", &|p| {
+            let files = p["files"].as_array_mut().unwrap();
+            let response = files.iter_mut().find(|f| f["type"] == "ResponsePsbt");
+            response.unwrap()["posted_by_xpub"] = cosigner_xpubs[1].clone().into();
+            p["acked_by"] = serde_json::json!([cosigner_xpubs[1]]);
+        }),
+    );
+    assert_matches!(result, Err(Error::InvalidOperationPackage { details: d }) if d == format!("invalid file signature of cosigner '{}'", cosigner_xpubs[1]));
+
+    // the second cosigner reviews the operation and completes it
+    let op_info = wlt_2
+        .import_operation(wlt_2_online, package_path("op_1_ack_1"))
+        .unwrap();
+    assert_eq!(op_info.initiator_xpub, cosigner_xpubs[0]);
+    let Operation::CreateUtxosToReview { psbt, status } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    assert_eq!(status.acked_by, set![cosigner_xpubs[0].clone()]);
+    assert_eq!(status.threshold, 2);
+    // ACKs need to be signed by the responding cosigner
+    let result = wlt_2.respond_to_operation(
+        wlt_2_online,
+        1,
+        RespondToOperation::Ack(wlt_1_singlesig.sign_psbt(psbt.clone(), None).unwrap()),
+    );
+    assert_matches!(result, Err(Error::InvalidPsbt { details: d }) if d == format!("PSBT not signed by cosigner '{}'", cosigner_xpubs[1]));
+    let signed_2 = wlt_2_singlesig.sign_psbt(psbt, None).unwrap();
+    let op_info = wlt_2
+        .respond_to_operation(wlt_2_online, 1, RespondToOperation::Ack(signed_2))
+        .unwrap();
+    let Operation::CreateUtxosCompleted { txid, .. } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    assert_eq!(wlt_2.get_local_last_processed_operation_idx().unwrap(), 1);
+    wlt_2
+        .export_operation(wlt_2_online, 1, package_path("op_1_final"))
+        .unwrap();
+
+    // the other parties import the final package
+    for (wlt, online) in [(&mut wlt_1, wlt_1_online), (&mut wlt_3, wlt_3_online)] {
+        let op_info = wlt
+            .import_operation(online, package_path("op_1_final"))
+            .unwrap();
+        assert_matches!(op_info.operation, Operation::CreateUtxosCompleted { txid: t, .. } if t == txid);
+        assert_eq!(wlt.get_local_last_processed_operation_idx().unwrap(), 1);
+        let result = wlt.import_operation(online, package_path("op_1_final"));
+        assert_matches!(result, Err(Error::InvalidOperationPackage { details: d }) if d == "operation 1 already processed");
+    }
+
+    // watch-only parties cannot initiate operations
+    let result = wlt_3.create_utxos_init(wlt_3_online, false, None, None, FEE_RATE, false, None);
+    assert_matches!(result, Err(Error::MultisigUserNotCosigner));
+
+    // packages of a different wallet are rejected
+    let other_keys = MultisigKeys::new(
+        vec![
+            Cosigner::from_keys(&wlt_1_keys, None),
+            Cosigner::from_keys(&unknown_keys, None),
+        ],
+        2,
+        2,
+    );
+    let mut other_wlt = get_test_ms_wallet(&other_keys, format!("{random_str}_other"));
+    let other_online = other_wlt
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_1_keys.mnemonic.clone()),
+        )
+        .unwrap();
+    let result = other_wlt.import_operation(other_online, package_path("op_1_final"));
+    assert_matches!(result, Err(Error::InvalidOperationPackage { details: d }) if d == "package belongs to a different wallet");
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[serial]
+fn hubless_concurrent_operations() {
+    initialize();
+
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let random_str: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(6)
+        .map(char::from)
+        .collect();
+
+    // multisig wallet keys
+    let wlt_1_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let wlt_2_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+
+    // cosigners
+    let cosigners = vec![
+        Cosigner::from_keys(&wlt_1_keys, None),
+        Cosigner::from_keys(&wlt_2_keys, None),
+    ];
+    let multisig_wlt_keys = MultisigKeys::new(cosigners, 2, 2);
+
+    // multisig wallets
+    let mut wlt_1 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_1"));
+    let mut wlt_2 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_2"));
+    let wlt_1_online = wlt_1
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_1_keys.mnemonic.clone()),
+        )
+        .unwrap();
+    let wlt_2_online = wlt_2
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_2_keys.mnemonic.clone()),
+        )
+        .unwrap();
+
+    // singlesig wallets (for signing)
+    let mut wlt_1_singlesig = get_test_wallet_with_keys(&wlt_1_keys);
+    let wlt_2_singlesig = get_test_wallet_with_keys(&wlt_2_keys);
+
+    // fund wallet 1 with 2 UTXOs
+    for _ in 0..2 {
+        send_sats_to_address(wlt_1.get_address(wlt_1_online).unwrap(), Some(30_000));
+    }
+    mine(false);
+
+    // initiate 2 operations spending different UTXOs
+    let packages_dir = get_test_data_dir_path().join(format!("{random_str}_packages"));
+    fs::create_dir_all(&packages_dir).unwrap();
+    let package_path = |name: &str| packages_dir.join(name).to_string_lossy().to_string();
+    let address = wlt_1_singlesig.get_address().unwrap();
+    let op_1 = wlt_1
+        .send_btc_init(wlt_1_online, address.clone(), 5_000, FEE_RATE, false, None)
+        .unwrap();
+    let op_2 = wlt_1
+        .send_btc_init(wlt_1_online, address.clone(), 5_000, FEE_RATE, false, None)
+        .unwrap();
+    assert_eq!(op_1.operation_idx, 1);
+    assert_eq!(op_2.operation_idx, 2);
+    let inputs = |psbt: &str| -> HashSet<_> {
+        Psbt::from_str(psbt)
+            .unwrap()
+            .unsigned_tx
+            .input
+            .iter()
+            .map(|i| i.previous_output)
+            .collect()
+    };
+    assert!(inputs(&op_1.psbt).is_disjoint(&inputs(&op_2.psbt)));
+    // all UTXOs are now reserved
+    let result = wlt_1.send_btc_init(wlt_1_online, address.clone(), 5_000, FEE_RATE, false, None);
+    assert_matches!(result, Err(Error::InsufficientBitcoins { .. }));
+
+    // ACK both operations and export them
+    for (op, name) in [(&op_1, "op_1_ack_1"), (&op_2, "op_2_ack_1")] {
+        let signed = wlt_1_singlesig.sign_psbt(op.psbt.clone(), None).unwrap();
+        let op_info = wlt_1
+            .respond_to_operation(
+                wlt_1_online,
+                op.operation_idx,
+                RespondToOperation::Ack(signed),
+            )
+            .unwrap();
+        assert_matches!(op_info.operation, Operation::SendBtcPending { .. });
+        wlt_1
+            .export_operation(wlt_1_online, op.operation_idx, package_path(name))
+            .unwrap();
+    }
+    // operations already responded to don't need to be reviewed again
+    assert!(wlt_1.sync_with_hub(wlt_1_online).unwrap().is_none());
+
+    // packages need to be imported in order
+    let result = wlt_2.import_operation(wlt_2_online, package_path("op_2_ack_1"));
+    assert_matches!(result, Err(Error::InvalidOperationPackage { details: d }) if d == "operation 1 needs to be imported first");
+    let op_info = wlt_2
+        .import_operation(wlt_2_online, package_path("op_1_ack_1"))
+        .unwrap();
+    assert_eq!(op_info.operation_idx, 1);
+    assert_matches!(op_info.operation, Operation::SendBtcToReview { .. });
+
+    // the second operation can be completed while the first one is still pending
+    let op_info = wlt_2
+        .import_operation(wlt_2_online, package_path("op_2_ack_1"))
+        .unwrap();
+    assert_eq!(op_info.operation_idx, 2);
+    let Operation::SendBtcToReview { psbt, .. } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    let signed = wlt_2_singlesig.sign_psbt(psbt, None).unwrap();
+    let op_info = wlt_2
+        .respond_to_operation(wlt_2_online, 2, RespondToOperation::Ack(signed))
+        .unwrap();
+    let Operation::SendBtcCompleted { txid: txid_2, .. } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    assert_eq!(wlt_2.get_local_last_processed_operation_idx().unwrap(), 0);
+    wlt_2
+        .export_operation(wlt_2_online, 2, package_path("op_2_final"))
+        .unwrap();
+    let op_info = wlt_1
+        .import_operation(wlt_1_online, package_path("op_2_final"))
+        .unwrap();
+    assert_matches!(op_info.operation, Operation::SendBtcCompleted { txid: t, .. } if t == txid_2);
+    assert_eq!(wlt_1.get_local_last_processed_operation_idx().unwrap(), 0);
+
+    // the first operation is still available for review and can be completed
+    let op_info = wlt_2.sync_with_hub(wlt_2_online).unwrap().unwrap();
+    assert_eq!(op_info.operation_idx, 1);
+    let Operation::SendBtcToReview { psbt, .. } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    let signed = wlt_2_singlesig.sign_psbt(psbt, None).unwrap();
+    let op_info = wlt_2
+        .respond_to_operation(wlt_2_online, 1, RespondToOperation::Ack(signed))
+        .unwrap();
+    let Operation::SendBtcCompleted { txid: txid_1, .. } = op_info.operation else {
+        panic!("unexpected operation")
+    };
+    assert_ne!(txid_1, txid_2);
+    assert_eq!(wlt_2.get_local_last_processed_operation_idx().unwrap(), 2);
+    wlt_2
+        .export_operation(wlt_2_online, 1, package_path("op_1_final"))
+        .unwrap();
+    let op_info = wlt_1
+        .import_operation(wlt_1_online, package_path("op_1_final"))
+        .unwrap();
+    assert_matches!(op_info.operation, Operation::SendBtcCompleted { txid: t, .. } if t == txid_1);
+    assert_eq!(wlt_1.get_local_last_processed_operation_idx().unwrap(), 2);
+
+    // nothing left to process
+    assert!(wlt_1.sync_with_hub(wlt_1_online).unwrap().is_none());
+    assert!(wlt_2.sync_with_hub(wlt_2_online).unwrap().is_none());
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[serial]
+fn hubless_cancel_and_expire() {
+    initialize();
+
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let random_str: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(6)
+        .map(char::from)
+        .collect();
+
+    // multisig wallet keys
+    let wlt_1_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+    let wlt_2_keys = generate_keys(bitcoin_network, WitnessVersion::Taproot);
+
+    // cosigners
+    let cosigners = vec![
+        Cosigner::from_keys(&wlt_1_keys, None),
+        Cosigner::from_keys(&wlt_2_keys, None),
+    ];
+    let multisig_wlt_keys = MultisigKeys::new(cosigners, 2, 2);
+
+    // multisig wallets
+    let mut wlt_1 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_1"));
+    let mut wlt_2 = get_test_ms_wallet(&multisig_wlt_keys, format!("{random_str}_2"));
+    let wlt_1_online = wlt_1
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_1_keys.mnemonic.clone()),
+        )
+        .unwrap();
+    let wlt_2_online = wlt_2
+        .go_online_without_hub(
+            test_go_online_options(None),
+            Some(wlt_2_keys.mnemonic.clone()),
+        )
+        .unwrap();
+
+    // singlesig wallets (for signing)
+    let mut wlt_1_singlesig = get_test_wallet_with_keys(&wlt_1_keys);
+
+    // fund wallet 1
+    send_sats_to_address(wlt_1.get_address(wlt_1_online).unwrap(), Some(30_000));
+    mine(false);
+
+    let packages_dir = get_test_data_dir_path().join(format!("{random_str}_packages"));
+    fs::create_dir_all(&packages_dir).unwrap();
+    let package_path = |name: &str| packages_dir.join(name).to_string_lossy().to_string();
+    let address = wlt_1_singlesig.get_address().unwrap();
+
+    // the expiry cannot be in the past
+    let result = wlt_1.send_btc_init(
+        wlt_1_online,
+        address.clone(),
+        5_000,
+        FEE_RATE,
+        false,
+        Some(OperationExpiry::Timestamp(0)),
+    );
+    assert_matches!(result, Err(Error::InvalidExpiration));
+    let height = wlt_1.bdk_wallet().latest_checkpoint().height();
+    let result = wlt_1.send_btc_init(
+        wlt_1_online,
+        address.clone(),
+        5_000,
+        FEE_RATE,
+        false,
+        Some(OperationExpiry::BlockHeight(height)),
+    );
+    assert_matches!(result, Err(Error::InvalidExpiration));
+
+    // initiate an operation and ACK it
+    let op_init = wlt_1
+        .send_btc_init(wlt_1_online, address.clone(), 5_000, FEE_RATE, false, None)
+        .unwrap();
+    assert_eq!(op_init.operation_idx, 1);
+    let signed = wlt_1_singlesig.sign_psbt(op_init.psbt, None).unwrap();
+    wlt_1
+        .respond_to_operation(wlt_1_online, 1, RespondToOperation::Ack(signed))
+        .unwrap();
+    wlt_1
+        .export_operation(wlt_1_online, 1, package_path("op_1_ack_1"))
+        .unwrap();
+    let op_info = wlt_2
+        .import_operation(wlt_2_online, package_path("op_1_ack_1"))
+        .unwrap();
+    assert_matches!(op_info.operation, Operation::SendBtcToReview { .. });
+
+    // only the initiator can cancel the operation
+    let result = wlt_2.cancel_operation(wlt_2_online, 1);
+    assert_matches!(result, Err(Error::MultisigCannotCancelOperation { details: d }) if d == "not the initiator");
+    let result = wlt_1.cancel_operation(wlt_1_online, 2);
+    assert_matches!(
+        result,
+        Err(Error::MultisigOperationNotFound { operation_idx: 2 })
+    );
+
+    // cancel the operation, which gets discarded releasing its inputs
+    let op_info = wlt_1.cancel_operation(wlt_1_online, 1).unwrap();
+    assert_eq!(op_info.operation_idx, 1);
+    assert_matches!(op_info.operation, Operation::SendBtcDiscarded { .. });
+    assert_eq!(wlt_1.get_local_last_processed_operation_idx().unwrap(), 1);
+    let result = wlt_1.cancel_operation(wlt_1_online, 1);
+    assert_matches!(result, Err(Error::MultisigCannotCancelOperation { details: d }) if d == "not pending");
+    wlt_1
+        .export_operation(wlt_1_online, 1, package_path("op_1_cancelled"))
+        .unwrap();
+
+    // the other cosigner discards the operation as well
+    let op_info = wlt_2
+        .import_operation(wlt_2_online, package_path("op_1_cancelled"))
+        .unwrap();
+    assert_matches!(op_info.operation, Operation::SendBtcDiscarded { .. });
+    assert_eq!(wlt_2.get_local_last_processed_operation_idx().unwrap(), 1);
+    let result = wlt_2.respond_to_operation(wlt_2_online, 1, RespondToOperation::Nack);
+    assert_matches!(result, Err(Error::MultisigCannotRespondToOperation { details: d }) if d == "not pending");
+
+    // initiate an operation, spending the released inputs, that expires shortly
+    let expiration_secs = 10;
+    let expiry_timestamp = (now().unix_timestamp() + expiration_secs) as u64;
+    let op_init = wlt_1
+        .send_btc_init(
+            wlt_1_online,
+            address.clone(),
+            5_000,
+            FEE_RATE,
+            false,
+            Some(OperationExpiry::Timestamp(expiry_timestamp)),
+        )
+        .unwrap();
+    assert_eq!(op_init.operation_idx, 2);
+    wlt_1
+        .export_operation(wlt_1_online, 2, package_path("op_2"))
+        .unwrap();
+    let result = wlt_1.send_btc_init(wlt_1_online, address.clone(), 5_000, FEE_RATE, false, None);
+    assert_matches!(result, Err(Error::InsufficientBitcoins { .. }));
+    std::thread::sleep(Duration::from_secs(expiration_secs as u64 + 1));
+
+    // expired operations cannot be responded to
+    let signed = wlt_1_singlesig.sign_psbt(op_init.psbt, None).unwrap();
+    let result = wlt_1.respond_to_operation(wlt_1_online, 2, RespondToOperation::Ack(signed));
+    assert_matches!(result, Err(Error::MultisigCannotRespondToOperation { details: d }) if d == "expired");
+
+    // expired operations are discarded when syncing or importing them
+    let op_info = wlt_1.sync_with_hub(wlt_1_online).unwrap().unwrap();
+    assert_eq!(op_info.operation_idx, 2);
+    assert_matches!(op_info.operation, Operation::SendBtcDiscarded { .. });
+    assert_eq!(wlt_1.get_local_last_processed_operation_idx().unwrap(), 2);
+    assert!(wlt_1.sync_with_hub(wlt_1_online).unwrap().is_none());
+    let op_info = wlt_2
+        .import_operation(wlt_2_online, package_path("op_2"))
+        .unwrap();
+    assert_matches!(op_info.operation, Operation::SendBtcDiscarded { .. });
+    assert_eq!(wlt_2.get_local_last_processed_operation_idx().unwrap(), 2);
+
+    // the inputs of the expired operation can be spent again
+    let op_init = wlt_1
+        .send_btc_init(wlt_1_online, address, 5_000, FEE_RATE, false, None)
+        .unwrap();
+    assert_eq!(op_init.operation_idx, 3);
+}
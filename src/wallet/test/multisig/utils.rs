@@ -295,6 +295,7 @@ pub(super) fn get_test_ms_wallet(keys: &MultisigKeys, dir: String) -> MultisigWa
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: AssetSchema::VALUES.to_vec(),
+            encryption_password: None,
         },
         keys.clone(),
     )
@@ -309,6 +310,7 @@ pub(super) fn ms_go_online_res(wallet: &mut MultisigWallet, token: &str) -> Resu
         MultisigOnlineOptions {
             hub_url: MULTISIG_HUB_URL.to_string(),
             hub_token: token.to_string(),
+            cosigner_mnemonic: None,
         },
     )
 }
@@ -427,7 +429,7 @@ pub(super) trait MultisigOps: OfflineSigParty {
     ) -> Result<InitOperationResult, Error> {
         let online = self.online();
         self.multisig_mut()
-            .create_utxos_init(online, up_to, num, size, fee_rate, false)
+            .create_utxos_init(online, up_to, num, size, fee_rate, false, None)
     }
 
     fn get_address(&mut self) -> String {
@@ -437,7 +439,6 @@ pub(super) trait MultisigOps: OfflineSigParty {
 
     fn get_op(&self, idx: i32) -> OperationResponse {
         self.multisig_ref()
-            .hub_client()
             .get_operation_by_idx(idx)
             .unwrap()
             .unwrap()
@@ -475,6 +476,7 @@ pub(super) trait MultisigOps: OfflineSigParty {
             inflation_amounts.to_vec(),
             FEE_RATE,
             1,
+            None,
         )
     }
 
@@ -491,7 +493,7 @@ pub(super) trait MultisigOps: OfflineSigParty {
     fn burn_init_res(&mut self, asset_id: &str, amount: u64) -> Result<InitOperationResult, Error> {
         let online = self.online();
         self.multisig_mut()
-            .burn_init(online, asset_id.to_string(), amount, FEE_RATE, 1)
+            .burn_init(online, asset_id.to_string(), amount, FEE_RATE, 1, None)
     }
 
     fn issue_asset_cfa(&mut self, amounts: Option<&[u64]>, file_path: Option<String>) -> AssetCFA {
@@ -659,8 +661,14 @@ pub(super) trait MultisigOps: OfflineSigParty {
         amount: u64,
     ) -> Result<InitOperationResult, Error> {
         let online = self.online();
-        self.multisig_mut()
-            .send_btc_init(online, address.to_string(), amount, FEE_RATE, false)
+        self.multisig_mut().send_btc_init(
+            online,
+            address.to_string(),
+            amount,
+            FEE_RATE,
+            false,
+            None,
+        )
     }
 
     fn send_init(&mut self, recipient_map: HashMap<String, Vec<Recipient>>) -> InitOperationResult {
@@ -686,6 +694,7 @@ pub(super) trait MultisigOps: OfflineSigParty {
             FEE_RATE,
             1,
             default_send_expiration(),
+            None,
         )
     }
 
@@ -7,7 +7,8 @@ fn check_wallet(
     network: BitcoinNetwork,
     keychain_vanilla: Option<u8>,
 ) {
-    let keychains: Vec<_> = party.wlt().bdk_wallet().keychains().collect();
+    let bdk_wallet = party.wlt().bdk_wallet();
+    let keychains: Vec<_> = bdk_wallet.keychains().collect();
     assert_eq!(keychains.len(), 2);
     for (keychain_kind, extended_descriptor) in keychains {
         match keychain_kind {
@@ -81,6 +82,7 @@ fn success() {
                 database_type: DatabaseType::Sqlite,
                 max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
                 supported_schemas: AssetSchema::VALUES.to_vec(),
+                encryption_password: None,
             },
             SinglesigKeys::from_keys(&keys, vanilla_keychain),
         )
@@ -151,6 +153,7 @@ fn mainnet_success_electrum() {
                 max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
                 // IFA not supported on mainnet
                 supported_schemas: vec![AssetSchema::Cfa, AssetSchema::Nia, AssetSchema::Uda],
+                encryption_password: None,
             },
             SinglesigKeys::from_keys(&keys, None),
         )
@@ -182,6 +185,7 @@ fn mainnet_success_esplora() {
                 max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
                 // IFA not supported on mainnet
                 supported_schemas: vec![AssetSchema::Cfa, AssetSchema::Nia, AssetSchema::Uda],
+                encryption_password: None,
             },
             SinglesigKeys::from_keys(&keys, None),
         )
@@ -356,6 +360,7 @@ fn watch_only_success() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: AssetSchema::VALUES.to_vec(),
+            encryption_password: None,
         },
         SinglesigKeys::from_keys_no_mnemonic(&keys, None),
     )
@@ -373,6 +378,7 @@ fn watch_only_success() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: AssetSchema::VALUES.to_vec(),
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys, None),
     )
@@ -421,6 +427,7 @@ fn watch_only_fail() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: AssetSchema::VALUES.to_vec(),
+            encryption_password: None,
         },
         SinglesigKeys::from_keys_no_mnemonic(&keys_bad, None),
     );
@@ -493,6 +500,7 @@ fn supported_schemas() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: vec![AssetSchema::Nia],
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys, None),
     )
@@ -522,6 +530,7 @@ fn supported_schemas() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: vec![AssetSchema::Uda],
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys_rcv, None),
     )
@@ -567,6 +576,7 @@ fn supported_schemas() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: vec![AssetSchema::Cfa],
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys, None),
     )
@@ -596,6 +606,7 @@ fn supported_schemas() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: vec![],
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys, None),
     );
@@ -614,6 +625,7 @@ fn supported_schemas() {
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
             supported_schemas: vec![AssetSchema::Nia, AssetSchema::Ifa],
+            encryption_password: None,
         },
         SinglesigKeys::from_keys(&keys_mainnet, None),
     );
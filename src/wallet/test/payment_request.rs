@@ -0,0 +1,382 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+fn request_line(
+    party: &mut SinglesigParty,
+    asset_id: &str,
+    assignment: Assignment,
+    transport_endpoints: Vec<String>,
+    witness: bool,
+) -> ReceiveData {
+    let asset_id = Some(asset_id.to_string());
+    let expiration_timestamp = default_rcv_expiration();
+    if witness {
+        party.wallet.witness_receive(
+            asset_id,
+            assignment,
+            ReceivePolicy::Any,
+            expiration_timestamp,
+            transport_endpoints,
+            MIN_CONFIRMATIONS,
+        )
+    } else {
+        party.wallet.blind_receive(
+            asset_id,
+            assignment,
+            ReceivePolicy::Any,
+            expiration_timestamp,
+            transport_endpoints,
+            MIN_CONFIRMATIONS,
+        )
+    }
+    .unwrap()
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // issue
+    let asset_nia = party.issue_asset_nia(None);
+    let asset_uda = party.issue_asset_uda(None, None, vec![]);
+
+    // bundle a witness and a blind receive into a payment request
+    let receive_data = vec![
+        request_line(
+            &mut rcv_party,
+            &asset_nia.asset_id,
+            Assignment::Fungible(amount),
+            TRANSPORT_ENDPOINTS.clone(),
+            true,
+        ),
+        request_line(
+            &mut rcv_party,
+            &asset_uda.asset_id,
+            Assignment::NonFungible,
+            TRANSPORT_ENDPOINTS.clone(),
+            false,
+        ),
+    ];
+    let bak_info_before = rcv_party.db_backup_info();
+    let payment_request = rcv_party
+        .wallet
+        .create_payment_request(receive_data.clone())
+        .unwrap();
+    let bak_info_after = rcv_party.db_backup_info();
+    assert!(bak_info_after.last_operation_timestamp > bak_info_before.last_operation_timestamp);
+    let payment_request_data = payment_request.payment_request_data();
+    assert_eq!(payment_request_data.lines.len(), 2);
+    assert_eq!(
+        payment_request_data.lines[0].assignment,
+        Assignment::Fungible(amount)
+    );
+    assert_eq!(
+        payment_request_data.lines[1].assignment,
+        Assignment::NonFungible
+    );
+    assert_eq!(
+        payment_request_data.transport_endpoints,
+        TRANSPORT_ENDPOINTS.clone()
+    );
+    assert_eq!(
+        payment_request_data.expiration_timestamp,
+        receive_data.iter().map(|r| r.expiration_timestamp).min()
+    );
+    let batch_transfers = rcv_party.db_batch_transfers();
+    for rcv_data in &receive_data {
+        let batch_transfer = batch_transfers
+            .iter()
+            .find(|bt| bt.idx == rcv_data.batch_transfer_idx)
+            .unwrap();
+        assert_eq!(
+            batch_transfer.payment_request_id,
+            Some(payment_request_data.payment_request_id.clone())
+        );
+    }
+    // the payment request string can be parsed back
+    let parsed = PaymentRequest::new(payment_request.payment_request_string()).unwrap();
+    assert_eq!(parsed.payment_request_data(), payment_request_data);
+
+    // pay the whole payment request with a single batch transfer
+    let res = party
+        .wallet
+        .send_payment_request(
+            party.online,
+            payment_request.payment_request_string(),
+            false,
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    assert!(!res.txid.is_empty());
+    for asset_id in [&asset_nia.asset_id, &asset_uda.asset_id] {
+        let transfers = party.list_transfers(Some(asset_id));
+        let transfer = transfers.last().unwrap();
+        assert_eq!(transfer.kind, TransferKind::Send);
+        assert_eq!(transfer.txid, Some(res.txid.clone()));
+    }
+
+    // a validated line is ACKed only once the other one has been paid too
+    rcv_party.refresh_all();
+    let statuses: Vec<TransferStatus> = rcv_party
+        .db_batch_transfers()
+        .into_iter()
+        .filter(|bt| bt.payment_request_id.is_some())
+        .map(|bt| bt.status)
+        .collect();
+    assert!(statuses.contains(&TransferStatus::WaitingCounterparty));
+    assert!(statuses.contains(&TransferStatus::WaitingBroadcast));
+    party.refresh_all();
+    assert!(
+        party.check_test_transfer_status_sender(&res.txid, TransferStatus::WaitingCounterparty)
+    );
+    rcv_party.refresh_all();
+    for rcv_data in &receive_data {
+        assert!(rcv_party.check_test_transfer_status_recipient(
+            &rcv_data.recipient_id,
+            TransferStatus::WaitingBroadcast
+        ));
+    }
+    party.wait_for_refresh(None);
+    assert!(
+        party.check_test_transfer_status_sender(&res.txid, TransferStatus::WaitingConfirmations)
+    );
+
+    // settle the transfer
+    rcv_party.wait_for_refresh(None);
+    mine(false);
+    rcv_party.wait_for_refresh(Some(&asset_nia.asset_id));
+    rcv_party.wait_for_refresh(Some(&asset_uda.asset_id));
+    party.wait_for_refresh(None);
+    for rcv_data in &receive_data {
+        assert!(
+            rcv_party.check_test_transfer_status_recipient(
+                &rcv_data.recipient_id,
+                TransferStatus::Settled
+            )
+        );
+    }
+    assert!(party.check_test_transfer_status_sender(&res.txid, TransferStatus::Settled));
+    assert_eq!(
+        rcv_party.get_asset_balance(&asset_nia.asset_id).settled,
+        amount
+    );
+    assert_eq!(rcv_party.get_asset_balance(&asset_uda.asset_id).settled, 1);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn unsatisfied_line() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // issue
+    let asset_nia = party.issue_asset_nia(None);
+    let asset_cfa = party.issue_asset_cfa(None, None);
+
+    // payment request
+    let receive_data = vec![
+        request_line(
+            &mut rcv_party,
+            &asset_nia.asset_id,
+            Assignment::Fungible(amount),
+            TRANSPORT_ENDPOINTS.clone(),
+            false,
+        ),
+        request_line(
+            &mut rcv_party,
+            &asset_cfa.asset_id,
+            Assignment::Fungible(amount),
+            TRANSPORT_ENDPOINTS.clone(),
+            false,
+        ),
+    ];
+    rcv_party
+        .wallet
+        .create_payment_request(receive_data.clone())
+        .unwrap();
+
+    // pay the NIA line less than requested
+    let recipient_map = HashMap::from([
+        (
+            asset_nia.asset_id.clone(),
+            vec![Recipient {
+                recipient_id: receive_data[0].recipient_id.clone(),
+                witness_data: None,
+                assignment: Assignment::Fungible(amount - 1),
+                transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+            }],
+        ),
+        (
+            asset_cfa.asset_id.clone(),
+            vec![Recipient {
+                recipient_id: receive_data[1].recipient_id.clone(),
+                witness_data: None,
+                assignment: Assignment::Fungible(amount),
+                transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+            }],
+        ),
+    ]);
+    let txid = party.send(recipient_map, FEE_RATE, None).txid;
+
+    // the receiver refuses all the lines, the sender fails the transfer
+    rcv_party.refresh_all();
+    rcv_party.refresh_all();
+    for rcv_data in &receive_data {
+        assert!(
+            rcv_party.check_test_transfer_status_recipient(
+                &rcv_data.recipient_id,
+                TransferStatus::Failed
+            )
+        );
+    }
+    party.refresh_all();
+    assert!(party.check_test_transfer_status_sender(&txid, TransferStatus::Failed));
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // issue
+    let asset_nia = party.issue_asset_nia(None);
+    let asset_cfa = party.issue_asset_cfa(None, None);
+
+    // invalid payment request string
+    let result = PaymentRequest::new(s!("invalid"));
+    assert!(
+        matches!(result, Err(Error::InvalidPaymentRequest { details: m }) if m == "invalid encoding")
+    );
+
+    // no receive data
+    let result = rcv_party.wallet.create_payment_request(vec![]);
+    assert!(
+        matches!(result, Err(Error::InvalidPaymentRequest { details: m }) if m == "no invoices")
+    );
+
+    // receive without an asset ID
+    let receive_data = rcv_party.blind_receive();
+    let result = rcv_party.wallet.create_payment_request(vec![receive_data]);
+    assert!(
+        matches!(result, Err(Error::InvalidPaymentRequest { details: m }) if m == "missing asset ID")
+    );
+
+    // receive without an amount
+    let receive_data = request_line(
+        &mut rcv_party,
+        &asset_nia.asset_id,
+        Assignment::Any,
+        TRANSPORT_ENDPOINTS.clone(),
+        false,
+    );
+    let result = rcv_party.wallet.create_payment_request(vec![receive_data]);
+    assert!(
+        matches!(result, Err(Error::InvalidPaymentRequest { details: m }) if m.starts_with("missing amount"))
+    );
+
+    // receives with different transport endpoints
+    let receive_data_nia = request_line(
+        &mut rcv_party,
+        &asset_nia.asset_id,
+        Assignment::Fungible(AMOUNT_SMALL),
+        TRANSPORT_ENDPOINTS.clone(),
+        false,
+    );
+    let receive_data_cfa = request_line(
+        &mut rcv_party,
+        &asset_cfa.asset_id,
+        Assignment::Fungible(AMOUNT_SMALL),
+        vec![format!("rpc://{PROXY_HOST_MOD_API}")],
+        true,
+    );
+    let result = rcv_party
+        .wallet
+        .create_payment_request(vec![receive_data_nia.clone(), receive_data_cfa]);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidPaymentRequest { details: m })
+            if m == "invoices with different transport endpoints"
+    ));
+
+    // duplicate receive
+    let result = rcv_party
+        .wallet
+        .create_payment_request(vec![receive_data_nia.clone(), receive_data_nia.clone()]);
+    assert!(
+        matches!(result, Err(Error::InvalidPaymentRequest { details: m }) if m == "duplicate recipient ID")
+    );
+
+    // receive of another wallet
+    let other_receive_data = request_line(
+        &mut party,
+        &asset_cfa.asset_id,
+        Assignment::Fungible(AMOUNT_SMALL),
+        TRANSPORT_ENDPOINTS.clone(),
+        false,
+    );
+    let result = rcv_party
+        .wallet
+        .create_payment_request(vec![receive_data_nia.clone(), other_receive_data]);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidPaymentRequest { details: _ })
+    ));
+
+    // receive already bundled in a payment request
+    rcv_party
+        .wallet
+        .create_payment_request(vec![receive_data_nia.clone()])
+        .unwrap();
+    let result = rcv_party
+        .wallet
+        .create_payment_request(vec![receive_data_nia.clone()]);
+    assert!(
+        matches!(result, Err(Error::InvalidPaymentRequest { details: m }) if m.contains("already belongs"))
+    );
+
+    // failed receive
+    let receive_data = request_line(
+        &mut rcv_party,
+        &asset_nia.asset_id,
+        Assignment::Fungible(AMOUNT_SMALL),
+        TRANSPORT_ENDPOINTS.clone(),
+        false,
+    );
+    assert!(rcv_party.fail_transfers_single(receive_data.batch_transfer_idx));
+    let result = rcv_party.wallet.create_payment_request(vec![receive_data]);
+    assert!(
+        matches!(result, Err(Error::InvalidPaymentRequest { details: m }) if m.contains("not a pending receive"))
+    );
+
+    // no private keys
+    let payment_request = PaymentRequest::from_invoices(vec![receive_data_nia.invoice]).unwrap();
+    let mut wo_party = get_funded_noutxo_party(false, None);
+    let result = wo_party.wallet.send_payment_request(
+        wo_party.online,
+        payment_request.payment_request_string(),
+        false,
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::WatchOnly)));
+}
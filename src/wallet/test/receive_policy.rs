@@ -0,0 +1,210 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+fn policy_receive(
+    party: &mut SinglesigParty,
+    asset_id: &str,
+    amount: u64,
+    receive_policy: ReceivePolicy,
+) -> ReceiveData {
+    party
+        .wallet
+        .blind_receive(
+            Some(asset_id.to_string()),
+            Assignment::Fungible(amount),
+            receive_policy,
+            default_rcv_expiration(),
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap()
+}
+
+#[cfg(feature = "electrum")]
+fn blind_recipient(receive_data: &ReceiveData, amount: u64) -> Recipient {
+    Recipient {
+        recipient_id: receive_data.recipient_id.clone(),
+        witness_data: None,
+        assignment: Assignment::Fungible(amount),
+        transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+    }
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // issue
+    let asset = party.issue_asset_nia(None);
+
+    // receives with an exact and an at-least policy
+    let receive_data_exact = policy_receive(
+        &mut rcv_party,
+        &asset.asset_id,
+        amount,
+        ReceivePolicy::Exact,
+    );
+    let receive_data_at_least = policy_receive(
+        &mut rcv_party,
+        &asset.asset_id,
+        amount,
+        ReceivePolicy::AtLeast,
+    );
+    let transfers = rcv_party.db_transfers();
+    for (rcv_data, receive_policy) in [
+        (&receive_data_exact, ReceivePolicy::Exact),
+        (&receive_data_at_least, ReceivePolicy::AtLeast),
+    ] {
+        let transfer = transfers
+            .iter()
+            .find(|t| t.recipient_id.as_ref() == Some(&rcv_data.recipient_id))
+            .unwrap();
+        assert_eq!(transfer.receive_policy, Some(receive_policy));
+    }
+
+    // pay the exact amount and more than the at-least amount
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![
+            blind_recipient(&receive_data_exact, amount),
+            blind_recipient(&receive_data_at_least, amount + 1),
+        ],
+    )]);
+    let txid = party.send(recipient_map, FEE_RATE, None).txid;
+
+    // both transfers are accepted
+    let refresh_result = rcv_party.refresh_result(None, &[]).unwrap();
+    for rcv_data in [&receive_data_exact, &receive_data_at_least] {
+        let refreshed = refresh_result.get(&rcv_data.batch_transfer_idx).unwrap();
+        assert_eq!(
+            refreshed.updated_status,
+            Some(TransferStatus::WaitingBroadcast)
+        );
+        assert!(refreshed.failure.is_none());
+    }
+    party.refresh_all();
+    assert!(party.check_test_transfer_status_sender(&txid, TransferStatus::WaitingConfirmations));
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn refuse() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // issue
+    let asset = party.issue_asset_nia(None);
+
+    // receives with an exact and an at-least policy
+    let receive_data_exact = policy_receive(
+        &mut rcv_party,
+        &asset.asset_id,
+        amount,
+        ReceivePolicy::Exact,
+    );
+    let receive_data_at_least = policy_receive(
+        &mut rcv_party,
+        &asset.asset_id,
+        amount,
+        ReceivePolicy::AtLeast,
+    );
+
+    // overpay the exact amount and underpay the at-least amount
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![
+            blind_recipient(&receive_data_exact, amount + 1),
+            blind_recipient(&receive_data_at_least, amount - 1),
+        ],
+    )]);
+    let txid = party.send(recipient_map, FEE_RATE, None).txid;
+
+    // both transfers are refused, reporting the policy violation
+    let refresh_result = rcv_party.refresh_result(None, &[]).unwrap();
+    for (rcv_data, received) in [
+        (&receive_data_exact, amount + 1),
+        (&receive_data_at_least, amount - 1),
+    ] {
+        let refreshed = refresh_result.get(&rcv_data.batch_transfer_idx).unwrap();
+        assert_eq!(refreshed.updated_status, Some(TransferStatus::Failed));
+        assert!(matches!(
+            &refreshed.failure,
+            Some(Error::ReceivePolicyNotSatisfied { details })
+                if details.starts_with(&format!("received {received} but {amount} was requested"))
+        ));
+        assert!(
+            rcv_party.check_test_transfer_status_recipient(
+                &rcv_data.recipient_id,
+                TransferStatus::Failed
+            )
+        );
+    }
+    party.refresh_all();
+    assert!(party.check_test_transfer_status_sender(&txid, TransferStatus::Failed));
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    // wallets
+    let mut rcv_party = get_funded_party!();
+
+    // receive without an amount
+    for receive_policy in [ReceivePolicy::Exact, ReceivePolicy::AtLeast] {
+        let result = rcv_party.wallet.blind_receive(
+            None,
+            Assignment::Any,
+            receive_policy,
+            default_rcv_expiration(),
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::InvalidReceivePolicy { details: m })
+                if m == "the receive doesn't request an amount"
+        ));
+    }
+    let result = rcv_party.wallet.witness_receive(
+        None,
+        Assignment::Fungible(0),
+        ReceivePolicy::Exact,
+        default_rcv_expiration(),
+        TRANSPORT_ENDPOINTS.clone(),
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidReceivePolicy { details: m })
+            if m == "the receive doesn't request an amount"
+    ));
+    // the any policy can always be used
+    rcv_party
+        .wallet
+        .blind_receive(
+            None,
+            Assignment::Any,
+            ReceivePolicy::Any,
+            default_rcv_expiration(),
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+}
@@ -0,0 +1,415 @@
+use super::*;
+
+use std::sync::Mutex;
+#[cfg(online)]
+use std::{
+    io::{BufRead, BufReader},
+    net::TcpListener,
+};
+
+// key the backup index is stored with
+const INDEX_KEY: &str = "rgb_lib_backup";
+
+// In-memory stand-in for a remote backup server
+#[derive(Default)]
+struct MemoryBackupTarget {
+    objects: Mutex<HashMap<(String, String), VersionedObject>>,
+    unreachable: Mutex<bool>,
+}
+
+impl MemoryBackupTarget {
+    fn set_unreachable(&self, unreachable: bool) {
+        *self.unreachable.lock().unwrap() = unreachable;
+    }
+
+    fn check_reachable(&self) -> Result<(), Error> {
+        if *self.unreachable.lock().unwrap() {
+            return Err(Error::RemoteBackup {
+                details: s!("unreachable"),
+            });
+        }
+        Ok(())
+    }
+
+    fn version(&self, store_id: &str) -> u64 {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(&(store_id.to_string(), INDEX_KEY.to_string()))
+            .map(|o| o.version)
+            .unwrap_or_default()
+    }
+
+    // simulate a write from another device
+    fn bump_version(&self, store_id: &str) {
+        for ((s, _), object) in self.objects.lock().unwrap().iter_mut() {
+            if s == store_id {
+                object.version += 1;
+            }
+        }
+    }
+}
+
+#[cfg(online)]
+type VssObjects = Arc<Mutex<HashMap<(String, String), (u64, String)>>>;
+
+// Local stand-in for a VSS-style server, handling each connection in its own thread
+#[cfg(online)]
+struct VssServer {
+    url: String,
+    objects: VssObjects,
+}
+
+#[cfg(online)]
+impl VssServer {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let objects = VssObjects::default();
+        let server_objects = objects.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    return;
+                };
+                let objects = server_objects.clone();
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    loop {
+                        let mut request_line = String::new();
+                        if reader.read_line(&mut request_line).unwrap_or_default() == 0 {
+                            return;
+                        }
+                        let mut content_length = 0;
+                        loop {
+                            let mut header = String::new();
+                            reader.read_line(&mut header).unwrap();
+                            let header = header.trim_end();
+                            if header.is_empty() {
+                                break;
+                            }
+                            if let Some((name, value)) = header.split_once(':')
+                                && name.eq_ignore_ascii_case("content-length")
+                            {
+                                content_length = value.trim().parse().unwrap();
+                            }
+                        }
+                        let mut body = vec![0u8; content_length];
+                        reader.read_exact(&mut body).unwrap();
+                        let request: Value = serde_json::from_slice(&body).unwrap();
+                        let (status, body) = Self::handle(&objects, &request_line, &request);
+                        write!(
+                            stream,
+                            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                            body.len()
+                        )
+                        .unwrap();
+                    }
+                });
+            }
+        });
+        Self { url, objects }
+    }
+
+    fn handle(objects: &VssObjects, request_line: &str, request: &Value) -> (&'static str, String) {
+        let mut objects = objects.lock().unwrap();
+        let store_id = request["store_id"].as_str().unwrap();
+        if request_line.starts_with("POST /getObject ") {
+            let key = request["key"].as_str().unwrap();
+            match objects.get(&(store_id.to_string(), key.to_string())) {
+                Some((version, value)) => (
+                    "200 OK",
+                    serde_json::json!({"value": {"key": key, "version": version, "value": value}})
+                        .to_string(),
+                ),
+                None => ("404 Not Found", s!("")),
+            }
+        } else if request_line.starts_with("POST /putObjects ") {
+            let items = request["transaction_items"].as_array().unwrap();
+            let ids: Vec<_> = items
+                .iter()
+                .map(|i| (store_id.to_string(), i["key"].as_str().unwrap().to_string()))
+                .collect();
+            for (id, item) in ids.iter().zip(items) {
+                let stored_version = objects.get(id).map(|(v, _)| *v).unwrap_or_default();
+                if item["version"].as_u64().unwrap() != stored_version {
+                    return ("409 Conflict", s!(""));
+                }
+            }
+            for (id, item) in ids.into_iter().zip(items) {
+                let version = item["version"].as_u64().unwrap() + 1;
+                let value = item["value"].as_str().unwrap().to_string();
+                objects.insert(id, (version, value));
+            }
+            ("200 OK", s!(""))
+        } else {
+            ("400 Bad Request", s!(""))
+        }
+    }
+
+    fn has_object(&self, store_id: &str, key: &str) -> bool {
+        self.objects
+            .lock()
+            .unwrap()
+            .contains_key(&(store_id.to_string(), key.to_string()))
+    }
+}
+
+impl BackupTarget for MemoryBackupTarget {
+    fn get_object(&self, store_id: &str, key: &str) -> Result<Option<VersionedObject>, Error> {
+        self.check_reachable()?;
+        let objects = self.objects.lock().unwrap();
+        Ok(objects
+            .get(&(store_id.to_string(), key.to_string()))
+            .cloned())
+    }
+
+    fn put_object(&self, store_id: &str, object: VersionedObject) -> Result<(), Error> {
+        self.check_reachable()?;
+        let mut objects = self.objects.lock().unwrap();
+        let id = (store_id.to_string(), object.key.clone());
+        let stored_version = objects.get(&id).map(|o| o.version).unwrap_or_default();
+        if object.version != stored_version {
+            return Err(Error::RemoteBackup {
+                details: s!("version conflict"),
+            });
+        }
+        objects.insert(
+            id,
+            VersionedObject {
+                version: stored_version + 1,
+                ..object
+            },
+        );
+        Ok(())
+    }
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut wallet_data = party.get_wallet_data();
+    let keys = party.get_keys();
+    let wallet_dir = party.wallet.get_wallet_dir();
+    let fingerprint = wallet_dir
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let target = Arc::new(MemoryBackupTarget::default());
+
+    // a backup is pushed right away
+    party
+        .wallet
+        .enable_remote_backup(target.clone(), PASSWORD)
+        .unwrap();
+    assert_eq!(target.version(&fingerprint), 1);
+    assert!(!party.wallet.backup_info().unwrap());
+
+    // operations push a new backup
+    let asset = party.issue_asset_nia(None);
+    let version = target.version(&fingerprint);
+    assert!(version > 1);
+    assert!(!party.wallet.backup_info().unwrap());
+
+    // explicit push
+    party.wallet.backup_remote().unwrap();
+    assert_eq!(target.version(&fingerprint), version + 1);
+    drop(party);
+
+    // restore
+    let target_dir_path = get_restore_dir_path(Some("remote_backup_success"));
+    let target_dir = target_dir_path.to_str().unwrap();
+    restore_backup_remote(target.as_ref(), &fingerprint, PASSWORD, target_dir).unwrap();
+
+    // check original and restored data are the same
+    let restore_wallet_dir = target_dir_path.join(&fingerprint);
+    compare_test_directories(&wallet_dir, &restore_wallet_dir, &["log"]);
+
+    // post-restore wallet data
+    wallet_data.data_dir = target_dir.to_string();
+    let mut party = offline_party!(Wallet::new(wallet_data, keys).unwrap());
+    let online = party.go_online(true, None);
+    let party = party!(party.wallet, online);
+    assert_eq!(party.get_asset_balance(&asset.asset_id).settled, AMOUNT);
+    assert!(!party.wallet.backup_info().unwrap());
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn incremental_chain() {
+    initialize();
+
+    let test_data_dir = get_test_data_dir_path();
+    let full_backup_path = test_data_dir.join("test_remote_backup_chain_full.rgb-lib_backup");
+    let full_backup = full_backup_path.to_str().unwrap();
+    let incr_backup_path = test_data_dir.join("test_remote_backup_chain_incr.rgb-lib_backup");
+    let incr_backup = incr_backup_path.to_str().unwrap();
+    let _ = std::fs::remove_file(full_backup);
+    let _ = std::fs::remove_file(incr_backup);
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut wallet_data = party.get_wallet_data();
+    let keys = party.get_keys();
+    let wallet_dir = party.wallet.get_wallet_dir();
+    let target = Arc::new(MemoryBackupTarget::default());
+
+    // full backup
+    let asset = party.issue_asset_nia(None);
+    party.wallet.backup(full_backup, PASSWORD).unwrap();
+
+    // remote pushes don't move the base of the next incremental backup
+    party
+        .wallet
+        .enable_remote_backup(target.clone(), PASSWORD)
+        .unwrap();
+    party.wallet.backup_remote().unwrap();
+    let asset_2 = party.issue_asset_nia(None);
+
+    // incremental backup
+    party
+        .wallet
+        .backup_incremental(incr_backup, PASSWORD)
+        .unwrap();
+    party.wallet.disable_remote_backup();
+    drop(party);
+
+    // restore the chain
+    let target_dir_path = get_restore_dir_path(Some("remote_backup_incremental_chain"));
+    let target_dir = target_dir_path.to_str().unwrap();
+    restore_backup_chain(&[full_backup, incr_backup], PASSWORD, target_dir).unwrap();
+    let restore_wallet_dir = target_dir_path.join(wallet_dir.file_name().unwrap());
+    compare_test_directories(&wallet_dir, &restore_wallet_dir, &["log"]);
+
+    // post-restore wallet data
+    wallet_data.data_dir = target_dir.to_string();
+    let mut party = offline_party!(Wallet::new(wallet_data, keys).unwrap());
+    let online = party.go_online(true, None);
+    let party = party!(party.wallet, online);
+    assert_eq!(party.get_asset_balance(&asset.asset_id).settled, AMOUNT);
+    assert_eq!(party.get_asset_balance(&asset_2.asset_id).settled, AMOUNT);
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    let test_data_dir = create_test_data_dir();
+    let keys = generate_keys(BitcoinNetwork::Regtest, WitnessVersion::Taproot);
+    let wallet_data = get_test_wallet_data(&test_data_dir.to_string_lossy());
+    let mut wallet = Wallet::new(wallet_data, SinglesigKeys::from_keys(&keys, None)).unwrap();
+    let fingerprint = keys.master_fingerprint.clone();
+    let target = Arc::new(MemoryBackupTarget::default());
+    let target_dir_path = get_restore_dir_path(Some("remote_backup_fail"));
+    let target_dir = target_dir_path.to_str().unwrap();
+
+    // remote backup not enabled
+    let result = wallet.backup_remote();
+    assert!(matches!(result, Err(Error::RemoteBackup { details: _ })));
+
+    // no backup stored
+    let result = restore_backup_remote(target.as_ref(), &fingerprint, PASSWORD, target_dir);
+    assert!(matches!(result, Err(Error::NoRemoteBackup)));
+
+    // unreachable target
+    target.set_unreachable(true);
+    let result = wallet.enable_remote_backup(target.clone(), PASSWORD);
+    assert!(matches!(result, Err(Error::RemoteBackup { details: _ })));
+    target.set_unreachable(false);
+    wallet
+        .enable_remote_backup(target.clone(), PASSWORD)
+        .unwrap();
+    assert_eq!(target.version(&fingerprint), 1);
+
+    // failed automatic pushes leave the backup required, the next operation pushes it
+    target.set_unreachable(true);
+    wallet.get_address().unwrap();
+    assert!(wallet.backup_info().unwrap());
+    target.set_unreachable(false);
+    wallet.get_address().unwrap();
+    assert!(!wallet.backup_info().unwrap());
+    assert_eq!(target.version(&fingerprint), 2);
+
+    // a backup replaced by someone else makes the push fail, the next one overwrites it
+    target.bump_version(&fingerprint);
+    wallet.get_address().unwrap();
+    assert!(wallet.backup_info().unwrap());
+    assert_eq!(target.version(&fingerprint), 3);
+    wallet.get_address().unwrap();
+    assert!(!wallet.backup_info().unwrap());
+    assert_eq!(target.version(&fingerprint), 4);
+
+    // disabled remote backup
+    wallet.disable_remote_backup();
+    wallet.get_address().unwrap();
+    assert!(wallet.backup_info().unwrap());
+    assert_eq!(target.version(&fingerprint), 4);
+
+    // wrong password
+    let result = restore_backup_remote(target.as_ref(), &fingerprint, "wrong", target_dir);
+    assert!(matches!(result, Err(Error::WrongPassword)));
+
+    // existing wallet directory
+    restore_backup_remote(target.as_ref(), &fingerprint, PASSWORD, target_dir).unwrap();
+    let result = restore_backup_remote(target.as_ref(), &fingerprint, PASSWORD, target_dir);
+    assert!(matches!(
+        result,
+        Err(Error::WalletDirAlreadyExists { path: _ })
+    ));
+}
+
+#[cfg(online)]
+#[test]
+#[parallel]
+fn vss() {
+    let test_data_dir = create_test_data_dir();
+    let keys = generate_keys(BitcoinNetwork::Regtest, WitnessVersion::Taproot);
+    let wallet_data = get_test_wallet_data(&test_data_dir.to_string_lossy());
+    let mut wallet = Wallet::new(wallet_data, SinglesigKeys::from_keys(&keys, None)).unwrap();
+    let wallet_dir = wallet.get_wallet_dir();
+    let fingerprint = keys.master_fingerprint.clone();
+    let server = VssServer::start();
+    let target = Arc::new(VssBackupTarget::new(&server.url, Some(s!("token"))).unwrap());
+
+    // a full backup is pushed right away
+    wallet
+        .enable_remote_backup(target.clone(), PASSWORD)
+        .unwrap();
+    assert!(server.has_object(&fingerprint, INDEX_KEY));
+    assert!(server.has_object(&fingerprint, "rgb_lib_backup_0_0"));
+
+    // operations push incremental backups
+    wallet.get_address().unwrap();
+    assert!(server.has_object(&fingerprint, "rgb_lib_backup_0_1"));
+    assert!(!wallet.backup_info().unwrap());
+
+    // deleted files are replayed on restore
+    let extra_file = wallet_dir.join("extra");
+    std::fs::write(&extra_file, "extra").unwrap();
+    wallet.backup_remote().unwrap();
+    std::fs::remove_file(&extra_file).unwrap();
+    wallet.backup_remote().unwrap();
+    let target_dir_path = get_restore_dir_path(Some("remote_backup_vss"));
+    let target_dir = target_dir_path.to_str().unwrap();
+    restore_backup_remote(target.as_ref(), &fingerprint, PASSWORD, target_dir).unwrap();
+    let restore_wallet_dir = target_dir_path.join(&fingerprint);
+    assert!(!restore_wallet_dir.join("extra").exists());
+    compare_test_directories(&wallet_dir, &restore_wallet_dir, &["log"]);
+
+    // a full backup is pushed again, to the other slot, after enough increments
+    for _ in 0..20 {
+        wallet.backup_remote().unwrap();
+    }
+    assert!(server.has_object(&fingerprint, "rgb_lib_backup_1_0"));
+    let target_dir_path = get_restore_dir_path(Some("remote_backup_vss_2"));
+    let target_dir = target_dir_path.to_str().unwrap();
+    restore_backup_remote(target.as_ref(), &fingerprint, PASSWORD, target_dir).unwrap();
+    let restore_wallet_dir = target_dir_path.join(&fingerprint);
+    compare_test_directories(&wallet_dir, &restore_wallet_dir, &["log"]);
+}
@@ -22,7 +22,8 @@ fn success() {
 
     // prepare PSBT
     let address = BdkAddress::from_str(&recv_party.get_address()).unwrap();
-    let mut tx_builder = party_send.wallet.bdk_wallet_mut().build_tx();
+    let mut bdk_wallet = party_send.wallet.bdk_wallet_mut();
+    let mut tx_builder = bdk_wallet.build_tx();
     tx_builder
         .add_recipient(
             address.assume_checked().script_pubkey(),
@@ -30,6 +31,7 @@ fn success() {
         )
         .fee_rate(FeeRate::from_sat_per_vb_u32(FEE_RATE as u32));
     let mut psbt = tx_builder.finish().unwrap();
+    drop(bdk_wallet);
     let mut psbt_copy = psbt.clone();
     assert!(
         !psbt
@@ -253,7 +255,7 @@ fn save_new_asset_success() {
     assert_eq!(asset_model.id, nia_asset.asset_id);
     assert_eq!(asset_model.initial_supply, AMOUNT.to_string());
     assert_eq!(asset_model.name, NAME);
-    assert_eq!(asset_model.precision, PRECISION);
+    assert_eq!(asset_model.precision, i16::from(PRECISION));
     assert_eq!(asset_model.ticker.unwrap(), TICKER);
     assert_eq!(asset_model.schema, AssetSchema::Nia);
 
@@ -269,7 +271,7 @@ fn save_new_asset_success() {
     assert_eq!(asset_model.id, cfa_asset.asset_id);
     assert_eq!(asset_model.initial_supply, AMOUNT.to_string());
     assert_eq!(asset_model.name, NAME);
-    assert_eq!(asset_model.precision, PRECISION);
+    assert_eq!(asset_model.precision, i16::from(PRECISION));
     assert!(asset_model.ticker.is_none());
     assert_eq!(asset_model.schema, AssetSchema::Cfa);
 
@@ -284,7 +286,7 @@ fn save_new_asset_success() {
     assert_eq!(asset_model.id, uda_asset.asset_id);
     assert_eq!(asset_model.initial_supply, 1.to_string());
     assert_eq!(asset_model.name, NAME);
-    assert_eq!(asset_model.precision, PRECISION);
+    assert_eq!(asset_model.precision, i16::from(PRECISION));
     assert_eq!(asset_model.ticker.unwrap(), TICKER);
     assert_eq!(asset_model.schema, AssetSchema::Uda);
 }
@@ -326,12 +328,14 @@ fn color_psbt_uda() {
         .address;
 
     // prepare PSBT: drain all wallet UTXOs to the p2wpkh address (no p2tr outputs, no change)
-    let mut tx_builder = party_send.wallet.bdk_wallet_mut().build_tx();
+    let mut bdk_wallet = party_send.wallet.bdk_wallet_mut();
+    let mut tx_builder = bdk_wallet.build_tx();
     tx_builder
         .drain_wallet()
         .drain_to(p2wpkh_addr.script_pubkey())
         .fee_rate(FeeRate::from_sat_per_vb_u32(FEE_RATE as u32));
     let mut psbt = tx_builder.finish().unwrap();
+    drop(bdk_wallet);
     assert!(
         !psbt
             .unsigned_tx
@@ -430,7 +434,8 @@ fn color_psbt_fail() {
 
     // prepare PSBT
     let address = BdkAddress::from_str(&recv_party.get_address()).unwrap();
-    let mut tx_builder = party_send.wallet.bdk_wallet_mut().build_tx();
+    let mut bdk_wallet = party_send.wallet.bdk_wallet_mut();
+    let mut tx_builder = bdk_wallet.build_tx();
     tx_builder
         .add_recipient(
             address.assume_checked().script_pubkey(),
@@ -438,6 +443,7 @@ fn color_psbt_fail() {
         )
         .fee_rate(FeeRate::from_sat_per_vb_u32(FEE_RATE as u32));
     let mut psbt = tx_builder.finish().unwrap();
+    drop(bdk_wallet);
 
     // prepare coloring data
     assert_eq!(psbt.unsigned_tx.input.len(), 1);
@@ -531,7 +537,8 @@ fn color_psbt_overflow_fail() {
     // total amount in output_map overflows u64: two valid vouts whose amounts sum to
     // more than u64::MAX (the checked sum must error before reaching the available check)
     let address = BdkAddress::from_str(&recv_party.get_address()).unwrap();
-    let mut tx_builder = party_send.wallet.bdk_wallet_mut().build_tx();
+    let mut bdk_wallet = party_send.wallet.bdk_wallet_mut();
+    let mut tx_builder = bdk_wallet.build_tx();
     tx_builder
         .add_recipient(
             address.assume_checked().script_pubkey(),
@@ -539,6 +546,7 @@ fn color_psbt_overflow_fail() {
         )
         .fee_rate(FeeRate::from_sat_per_vb_u32(FEE_RATE as u32));
     let mut psbt = tx_builder.finish().unwrap();
+    drop(bdk_wallet);
     let output_map: HashMap<u32, u64> = HashMap::from_iter([(0, u64::MAX), (1, 1)]);
     let asset_coloring_info = AssetColoringInfo {
         output_map,
@@ -559,7 +567,8 @@ fn color_psbt_overflow_fail() {
 
     // vout in output_map overflows u32 when shifted by 1 for the OP_RETURN output
     let address = BdkAddress::from_str(&recv_party.get_address()).unwrap();
-    let mut tx_builder = party_send.wallet.bdk_wallet_mut().build_tx();
+    let mut bdk_wallet = party_send.wallet.bdk_wallet_mut();
+    let mut tx_builder = bdk_wallet.build_tx();
     tx_builder
         .add_recipient(
             address.assume_checked().script_pubkey(),
@@ -567,6 +576,7 @@ fn color_psbt_overflow_fail() {
         )
         .fee_rate(FeeRate::from_sat_per_vb_u32(FEE_RATE as u32));
     let mut psbt = tx_builder.finish().unwrap();
+    drop(bdk_wallet);
     let output_map: HashMap<u32, u64> = HashMap::from_iter([(u32::MAX, AMOUNT)]);
     let asset_coloring_info = AssetColoringInfo {
         output_map,
@@ -586,16 +596,53 @@ fn color_psbt_overflow_fail() {
     assert!(matches!(result, Err(Error::InvalidColoringInfo { details: m }) if m == msg));
 }
 
+#[cfg(all(feature = "bitcoind", feature = "electrum"))]
+#[test]
+#[parallel]
+fn check_indexer_url_bitcoind_success() {
+    initialize();
+
+    let result = check_indexer_url(
+        BITCOIND_URL,
+        BitcoinNetwork::Regtest,
+        &NetworkOptions::default(),
+    );
+    assert_matches!(result, Ok(IndexerProtocol::Bitcoind));
+}
+
+#[cfg(all(feature = "bitcoind", feature = "electrum"))]
+#[test]
+#[parallel]
+fn check_indexer_url_bitcoind_fail() {
+    initialize();
+
+    // the wallet birthday is required
+    let result = check_indexer_url(
+        BITCOIND_URL.split('?').next().unwrap(),
+        BitcoinNetwork::Regtest,
+        &NetworkOptions::default(),
+    );
+    assert_matches!(result, Err(Error::InvalidIndexer { details: m }) if m.contains("birthday"));
+}
+
 #[cfg(feature = "electrum")]
 #[test]
 #[parallel]
 fn check_indexer_url_electrum_success() {
     initialize();
 
-    let result = check_indexer_url(ELECTRUM_URL, BitcoinNetwork::Regtest);
+    let result = check_indexer_url(
+        ELECTRUM_URL,
+        BitcoinNetwork::Regtest,
+        &NetworkOptions::default(),
+    );
     assert_matches!(result, Ok(IndexerProtocol::Electrum));
 
-    let result = check_indexer_url(ELECTRUM_2_URL, BitcoinNetwork::Regtest);
+    let result = check_indexer_url(
+        ELECTRUM_2_URL,
+        BitcoinNetwork::Regtest,
+        &NetworkOptions::default(),
+    );
     assert_matches!(result, Ok(IndexerProtocol::Electrum));
 }
 
@@ -605,7 +652,11 @@ fn check_indexer_url_electrum_success() {
 fn check_indexer_url_electrum_fail() {
     initialize();
 
-    let result = check_indexer_url(ELECTRUM_BLOCKSTREAM_URL, BitcoinNetwork::Regtest);
+    let result = check_indexer_url(
+        ELECTRUM_BLOCKSTREAM_URL,
+        BitcoinNetwork::Regtest,
+        &NetworkOptions::default(),
+    );
     let verbose_unsupported =
         "verbose transactions are unsupported by the provided electrum service";
     assert_matches!(result, Err(Error::InvalidIndexer { details: m }) if m.contains(verbose_unsupported));
@@ -617,7 +668,11 @@ fn check_indexer_url_electrum_fail() {
 fn check_indexer_url_esplora_success() {
     initialize();
 
-    let result = check_indexer_url(ESPLORA_URL, BitcoinNetwork::Regtest);
+    let result = check_indexer_url(
+        ESPLORA_URL,
+        BitcoinNetwork::Regtest,
+        &NetworkOptions::default(),
+    );
     assert_matches!(result, Ok(IndexerProtocol::Esplora));
 }
 
@@ -627,7 +682,11 @@ fn check_indexer_url_esplora_success() {
 fn check_indexer_url_esplora_fail() {
     initialize();
 
-    let result = check_indexer_url(PROXY_URL, BitcoinNetwork::Regtest);
+    let result = check_indexer_url(
+        PROXY_URL,
+        BitcoinNetwork::Regtest,
+        &NetworkOptions::default(),
+    );
     let invalid_indexer = s!("not a valid electrum nor esplora server");
     assert_matches!(result, Err(Error::InvalidIndexer { details: m }) if m == invalid_indexer);
 }
@@ -638,7 +697,7 @@ fn check_indexer_url_esplora_fail() {
 fn check_proxy_url_success() {
     initialize();
 
-    assert!(check_proxy_url(PROXY_URL).is_ok());
+    assert!(check_proxy_url(PROXY_URL, &NetworkOptions::default()).is_ok());
 }
 
 #[cfg(feature = "electrum")]
@@ -647,7 +706,7 @@ fn check_proxy_url_success() {
 fn check_proxy_url_fail() {
     initialize();
 
-    let result = check_proxy_url(PROXY_URL_MOD_PROTO);
+    let result = check_proxy_url(PROXY_URL_MOD_PROTO, &NetworkOptions::default());
     assert_matches!(result, Err(Error::InvalidProxyProtocol { version: _ }));
 }
 
@@ -860,6 +919,7 @@ fn send_end_db_update_only_success() {
             FEE_RATE,
             MIN_CONFIRMATIONS,
             default_send_expiration(),
+            None,
             false,
         )
         .unwrap();
@@ -0,0 +1,299 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+fn sell_order_transfer(party: &SinglesigParty, asset_id: &str, incoming: bool) -> Transfer {
+    let transfers = party.list_transfers(Some(asset_id));
+    let transfer = transfers.last().unwrap();
+    let batch_transfer = party
+        .db_batch_transfers()
+        .into_iter()
+        .find(|bt| bt.idx == transfer.batch_transfer_idx)
+        .unwrap();
+    assert_eq!(batch_transfer.incoming, incoming);
+    assert!(batch_transfer.sell_order_id.is_some());
+    transfer.clone()
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let price: u64 = 10_000;
+
+    // wallets
+    let mut seller = get_funded_party!();
+    let mut buyer = get_funded_party!();
+
+    // issue
+    let asset_uda = seller.issue_asset_uda(None, None, vec![]);
+
+    // seller creates the sell order
+    let btc_balance_before = seller.get_btc_balance_with_sync();
+    let bak_info_before = seller.db_backup_info();
+    let sell_order = seller
+        .wallet
+        .sell_order_create(
+            seller.online,
+            asset_uda.asset_id.clone(),
+            Assignment::NonFungible,
+            price,
+            default_rcv_expiration(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let bak_info_after = seller.db_backup_info();
+    assert!(bak_info_after.last_operation_timestamp > bak_info_before.last_operation_timestamp);
+    let sell_order_data = sell_order.sell_order_data();
+    assert_eq!(sell_order_data.asset_id, asset_uda.asset_id);
+    assert_eq!(sell_order_data.assignment, Assignment::NonFungible);
+    assert_eq!(sell_order_data.price, price);
+    // the sell order string can be parsed back
+    let parsed = SellOrder::new(sell_order.sell_order_string()).unwrap();
+    assert_eq!(parsed.sell_order_data(), sell_order_data);
+    // the seller transfer has no recipient until the order is filled
+    let transfer = sell_order_transfer(&seller, &asset_uda.asset_id, false);
+    assert_eq!(transfer.kind, TransferKind::Send);
+    assert_eq!(transfer.status, TransferStatus::WaitingBroadcast);
+    assert!(transfer.recipient_id.is_none());
+    let partial_txid = transfer.txid.unwrap();
+
+    // nothing changes for the seller while the order is not filled
+    seller.refresh_all();
+    let transfer = sell_order_transfer(&seller, &asset_uda.asset_id, false);
+    assert_eq!(transfer.status, TransferStatus::WaitingBroadcast);
+
+    // buyer fills the order
+    let result = buyer
+        .wallet
+        .sell_order_take(
+            buyer.online,
+            sell_order.sell_order_string(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let txid = result.txid;
+    assert_ne!(txid, partial_txid);
+    let transfer = sell_order_transfer(&buyer, &asset_uda.asset_id, true);
+    assert_eq!(transfer.kind, TransferKind::ReceiveWitness);
+    assert_eq!(transfer.status, TransferStatus::WaitingConfirmations);
+    assert_eq!(transfer.txid, Some(txid.clone()));
+
+    // seller detects the filled order
+    seller.refresh_all();
+    let transfer = sell_order_transfer(&seller, &asset_uda.asset_id, false);
+    assert_eq!(transfer.status, TransferStatus::WaitingConfirmations);
+    assert_eq!(transfer.txid, Some(txid.clone()));
+    assert!(transfer.recipient_id.is_some());
+
+    // settle the sale
+    mine(false);
+    seller.wait_for_refresh(Some(&asset_uda.asset_id));
+    buyer.wait_for_refresh(Some(&asset_uda.asset_id));
+    for (party, incoming) in [(&seller, false), (&buyer, true)] {
+        let transfer = sell_order_transfer(party, &asset_uda.asset_id, incoming);
+        assert_eq!(transfer.status, TransferStatus::Settled);
+        assert_eq!(transfer.txid, Some(txid.clone()));
+    }
+    assert_eq!(seller.get_asset_balance(&asset_uda.asset_id).settled, 0);
+    assert_eq!(buyer.get_asset_balance(&asset_uda.asset_id).settled, 1);
+    let btc_balance_after = seller.get_btc_balance_with_sync();
+    assert_eq!(
+        btc_balance_after.colored.settled,
+        btc_balance_before.colored.settled + price
+    );
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success_multiple_utxos() {
+    initialize();
+
+    let amount: u64 = 66;
+    let price: u64 = 5_000;
+
+    // wallets
+    let mut seller = get_funded_party!();
+    let mut buyer = get_funded_party!();
+
+    // issue, allocating the sold amount to its own UTXO
+    let asset_nia = seller.issue_asset_nia(Some(&[amount, AMOUNT]));
+
+    // sell the UTXO holding exactly the sold amount, the other one stays with the seller
+    let sell_order = seller
+        .wallet
+        .sell_order_create(
+            seller.online,
+            asset_nia.asset_id.clone(),
+            Assignment::Fungible(amount),
+            price,
+            default_rcv_expiration(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let txid = buyer
+        .wallet
+        .sell_order_take(
+            buyer.online,
+            sell_order.sell_order_string(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap()
+        .txid;
+
+    // settle the sale
+    seller.refresh_all();
+    mine(false);
+    seller.wait_for_refresh(Some(&asset_nia.asset_id));
+    buyer.wait_for_refresh(Some(&asset_nia.asset_id));
+    let transfer = sell_order_transfer(&seller, &asset_nia.asset_id, false);
+    assert_eq!(transfer.status, TransferStatus::Settled);
+    assert_eq!(transfer.txid, Some(txid.clone()));
+    let transfer = sell_order_transfer(&buyer, &asset_nia.asset_id, true);
+    assert_eq!(transfer.status, TransferStatus::Settled);
+    assert_eq!(transfer.txid, Some(txid));
+    assert_eq!(
+        seller.get_asset_balance(&asset_nia.asset_id).settled,
+        AMOUNT
+    );
+    assert_eq!(buyer.get_asset_balance(&asset_nia.asset_id).settled, amount);
+
+    // the seller can spend the rest
+    let mut rcv_party = get_funded_party!();
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset_nia.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id,
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    seller.send(recipient_map, FEE_RATE, None);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    let amount: u64 = 66;
+    let price: u64 = 5_000;
+
+    // wallets
+    let mut seller = get_funded_party!();
+    let mut buyer = get_funded_party!();
+
+    // issue
+    let asset_nia = seller.issue_asset_nia(Some(&[amount]));
+
+    // invalid sell order string
+    let result = SellOrder::new(s!("invalid"));
+    assert!(
+        matches!(result, Err(Error::InvalidSellOrder { details: m }) if m == "invalid encoding")
+    );
+
+    // expired order
+    let result = seller.wallet.sell_order_create(
+        seller.online,
+        asset_nia.asset_id.clone(),
+        Assignment::Fungible(amount),
+        price,
+        0,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidExpiration)));
+
+    // zero price
+    let result = seller.wallet.sell_order_create(
+        seller.online,
+        asset_nia.asset_id.clone(),
+        Assignment::Fungible(amount),
+        0,
+        default_rcv_expiration(),
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidAmountZero)));
+
+    // zero amount
+    let result = seller.wallet.sell_order_create(
+        seller.online,
+        asset_nia.asset_id.clone(),
+        Assignment::Fungible(0),
+        price,
+        default_rcv_expiration(),
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidAmountZero)));
+
+    // UTXO holding more than the sold assignment
+    let result = seller.wallet.sell_order_create(
+        seller.online,
+        asset_nia.asset_id.clone(),
+        Assignment::Fungible(amount - 1),
+        price,
+        default_rcv_expiration(),
+        MIN_CONFIRMATIONS,
+    );
+    assert!(
+        matches!(result, Err(Error::InvalidSellOrder { details: m }) if m == "no UTXO holds exactly the sold assignment")
+    );
+
+    // no private keys
+    let sell_order = seller
+        .wallet
+        .sell_order_create(
+            seller.online,
+            asset_nia.asset_id.clone(),
+            Assignment::Fungible(amount),
+            price,
+            default_rcv_expiration(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let mut wo_party = get_funded_noutxo_party(false, None);
+    let result = wo_party.wallet.sell_order_take(
+        wo_party.online,
+        sell_order.sell_order_string(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::WatchOnly)));
+
+    // taking an order created by the same wallet
+    let result = seller.wallet.sell_order_take(
+        seller.online,
+        sell_order.sell_order_string(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(
+        matches!(result, Err(Error::InvalidSellOrder { details: m }) if m == "the sell order is already known to this wallet")
+    );
+
+    // taking an order twice
+    buyer
+        .wallet
+        .sell_order_take(
+            buyer.online,
+            sell_order.sell_order_string(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let result = buyer.wallet.sell_order_take(
+        buyer.online,
+        sell_order.sell_order_string(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(
+        matches!(result, Err(Error::InvalidSellOrder { details: m }) if m == "the sell order is already known to this wallet")
+    );
+}
@@ -2604,6 +2604,7 @@ fn fail() {
         FEE_RATE,
         MIN_CONFIRMATIONS,
         default_send_expiration(),
+        None,
         false,
     );
     assert_matches!(result, Err(Error::Offline));
@@ -2775,6 +2776,7 @@ fn fail() {
         0,
         MIN_CONFIRMATIONS,
         default_send_expiration(),
+        None,
         false,
     );
     assert!(matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == FEE_MSG_LOW));
@@ -2787,6 +2789,7 @@ fn fail() {
         u64::MAX,
         MIN_CONFIRMATIONS,
         default_send_expiration(),
+        None,
         false,
     );
     assert!(matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == FEE_MSG_OVER));
@@ -3610,6 +3613,7 @@ fn send_to_oneself_crash() {
             FEE_RATE,
             MIN_CONFIRMATIONS,
             default_send_expiration(),
+            None,
             true,
         )
         .unwrap();
@@ -4531,6 +4535,7 @@ fn min_confirmations_common(
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             min_confirmations,
@@ -4565,8 +4570,14 @@ fn min_confirmations_common(
     let (transfer, _, _) = party.get_test_transfer_sender(&txid);
     let (transfer_data, _) = party.get_test_transfer_data(&transfer);
     let (_, batch_transfer) = party.get_test_transfer_related(&transfer);
-    assert_eq!(rcv_batch_transfer.min_confirmations, min_confirmations);
-    assert_eq!(batch_transfer.min_confirmations, min_confirmations);
+    assert_eq!(
+        rcv_batch_transfer.min_confirmations,
+        i16::from(min_confirmations)
+    );
+    assert_eq!(
+        batch_transfer.min_confirmations,
+        i16::from(min_confirmations)
+    );
     assert_eq!(
         rcv_transfer_data.status,
         TransferStatus::WaitingCounterparty
@@ -4620,6 +4631,7 @@ fn min_confirmations_common(
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             min_confirmations,
@@ -4654,8 +4666,14 @@ fn min_confirmations_common(
     let (transfer, _, _) = party.get_test_transfer_sender(&txid);
     let (transfer_data, _) = party.get_test_transfer_data(&transfer);
     let (_, batch_transfer) = party.get_test_transfer_related(&transfer);
-    assert_eq!(rcv_batch_transfer.min_confirmations, min_confirmations);
-    assert_eq!(batch_transfer.min_confirmations, min_confirmations);
+    assert_eq!(
+        rcv_batch_transfer.min_confirmations,
+        i16::from(min_confirmations)
+    );
+    assert_eq!(
+        batch_transfer.min_confirmations,
+        i16::from(min_confirmations)
+    );
     assert_eq!(
         rcv_transfer_data.status,
         TransferStatus::WaitingCounterparty
@@ -4724,6 +4742,63 @@ fn min_confirmations_electrum() {
     min_confirmations_common(&mut party, &mut rcv_party, false);
 }
 
+#[cfg(all(feature = "bitcoind", feature = "electrum"))]
+#[test]
+#[parallel]
+fn min_confirmations_bitcoind() {
+    initialize();
+
+    // wallets
+    let mut party = get_funded_party!(BITCOIND_URL.to_string());
+    let mut rcv_party = get_funded_party!(BITCOIND_URL.to_string());
+
+    min_confirmations_common(&mut party, &mut rcv_party, false);
+}
+
+#[cfg(all(feature = "cbf", feature = "electrum"))]
+#[test]
+#[parallel]
+fn min_confirmations_cbf() {
+    initialize();
+
+    // wallets, funded via electrum and then switched to the CBF light client
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+    for party in [&mut party, &mut rcv_party] {
+        let online_options = OnlineOptions {
+            cbf: Some(CbfOptions {
+                peers: vec![CBF_PEER.to_string()],
+                start_height: 0,
+            }),
+            ..test_go_online_options(None)
+        };
+        party.online = party.wallet.go_online(online_options).unwrap();
+    }
+
+    min_confirmations_common(&mut party, &mut rcv_party, false);
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn min_confirmations_quorum() {
+    initialize();
+
+    // wallets, with TX confirmations cross-checked by 2 indexers
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+    for party in [&mut party, &mut rcv_party] {
+        let online_options = OnlineOptions {
+            fallback_indexer_urls: vec![ELECTRUM_2_URL.to_string()],
+            indexer_quorum: Some(2),
+            ..test_go_online_options(None)
+        };
+        party.online = party.wallet.go_online(online_options).unwrap();
+    }
+
+    min_confirmations_common(&mut party, &mut rcv_party, false);
+}
+
 #[cfg(feature = "esplora")]
 #[test]
 #[parallel]
@@ -5281,6 +5356,7 @@ fn min_fee_rate() {
             fee_rate,
             MIN_CONFIRMATIONS,
             default_send_expiration(),
+            None,
             false,
         )
         .unwrap();
@@ -5445,6 +5521,7 @@ fn min_relay_fee_common(
             fee_rate,
             MIN_CONFIRMATIONS,
             default_send_expiration(),
+            None,
             false,
         )
         .unwrap();
@@ -6579,7 +6656,7 @@ fn pending_witness_txo() {
     assert!(rcv_txo.pending_witness);
     let rcv_outpoint = Outpoint {
         txid,
-        vout: rcv_txo.vout,
+        vout: rcv_txo.vout as u32,
     };
 
     // check the recipient still has the pending witness script
@@ -6692,7 +6769,7 @@ fn pending_witness_txo() {
     assert!(rcv_txo.pending_witness);
     let rcv_outpoint = Outpoint {
         txid,
-        vout: rcv_txo.vout,
+        vout: rcv_txo.vout as u32,
     };
 
     // check pending witness script has been deleted
@@ -6757,6 +6834,7 @@ fn blinded_change_failed_xfer() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             (now().unix_timestamp() + 1) as u64, // expire early so can fail
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -6899,6 +6977,7 @@ fn blinded_change_send_begin_only() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             (now().unix_timestamp() + 1) as u64, // expire early so can fail
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -7043,6 +7122,7 @@ fn donation_recipient_nack() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             (now().unix_timestamp() + 1) as u64, // expire early so can fail
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -7207,7 +7287,7 @@ fn allocations() {
             let outpoint = &u.utxo.outpoint;
             let db_txo = db_txos
                 .iter()
-                .find(|t| t.txid == outpoint.txid && t.vout == outpoint.vout)
+                .find(|t| t.txid == outpoint.txid && t.vout == i64::from(outpoint.vout))
                 .unwrap();
             let txo_colorings: Vec<&DbColoring> = db_colorings
                 .iter()
@@ -7834,6 +7914,7 @@ fn unsafe_history_waits_for_safe_height() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             (now().unix_timestamp() + DURATION_RCV_TRANSFER as i64) as u64,
             vec![],
             2,
@@ -7951,6 +8032,7 @@ fn begin_end() {
             FEE_RATE,
             MIN_CONFIRMATIONS,
             default_send_expiration(),
+            None,
             true,
         )
         .unwrap();
@@ -7971,6 +8053,7 @@ fn begin_end() {
             FEE_RATE,
             MIN_CONFIRMATIONS,
             default_send_expiration(),
+            None,
             false,
         )
         .unwrap();
@@ -8008,6 +8091,7 @@ fn out_of_band_success() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8018,6 +8102,7 @@ fn out_of_band_success() {
         .witness_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8154,6 +8239,7 @@ fn out_of_band_fail() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8264,6 +8350,7 @@ fn out_of_band_fail() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8333,6 +8420,7 @@ fn out_of_band_fail() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8408,6 +8496,7 @@ fn out_of_band_media() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8418,6 +8507,7 @@ fn out_of_band_media() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8428,6 +8518,7 @@ fn out_of_band_media() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8543,6 +8634,7 @@ fn out_of_band_media_cleanup() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8721,6 +8813,7 @@ fn out_of_band_mixed_batch() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8842,6 +8935,7 @@ fn out_of_band_consignment_multiple_receives() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -8852,6 +8946,7 @@ fn out_of_band_consignment_multiple_receives() {
         .blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             vec![],
             MIN_CONFIRMATIONS,
@@ -242,7 +242,7 @@ fn begin_reservation_interactions() {
     // reservation set exactly matches PSBT inputs
     let reserved_set: HashSet<(String, u32)> = reservations
         .iter()
-        .map(|r| (r.txid.clone(), r.vout))
+        .map(|r| (r.txid.clone(), r.vout as u32))
         .collect();
     let input_set: HashSet<(String, u32)> = unsigned_psbt
         .unsigned_tx
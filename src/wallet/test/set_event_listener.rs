@@ -0,0 +1,153 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+fn status_changes(events: &[WalletEvent]) -> Vec<(i32, TransferStatus)> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            WalletEvent::TransferStatusChanged {
+                batch_transfer_idx,
+                status,
+            } => Some((*batch_transfer_idx, *status)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut party = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+    let collector = Arc::new(EventCollector::default());
+    let rcv_collector = Arc::new(EventCollector::default());
+    party.wallet.set_event_listener(Some(collector.clone()));
+    rcv_party
+        .wallet
+        .set_event_listener(Some(rcv_collector.clone()));
+
+    // issue
+    let asset = party.issue_asset_nia(None);
+    let events = collector.take();
+    assert!(events.contains(&WalletEvent::AssetAdded {
+        asset_id: asset.asset_id.clone(),
+    }));
+
+    // send
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let txid = party.send(recipient_map, FEE_RATE, None).txid;
+    let (_, _, batch_transfer) = party.get_test_transfer_sender(&txid);
+    let events = collector.take();
+    assert_eq!(
+        status_changes(&events),
+        vec![(batch_transfer.idx, TransferStatus::WaitingCounterparty)]
+    );
+    // spent inputs are reported
+    assert!(
+        events
+            .iter()
+            .any(|e| matches!(e, WalletEvent::UtxoSpent { .. }))
+    );
+
+    // the recipient sees the asset for the first time and moves to WaitingConfirmations
+    rcv_collector.take();
+    rcv_party.refresh_all();
+    let rcv_events = rcv_collector.take();
+    assert!(rcv_events.contains(&WalletEvent::AssetAdded {
+        asset_id: asset.asset_id.clone(),
+    }));
+    assert_eq!(
+        status_changes(&rcv_events)
+            .into_iter()
+            .map(|(_, s)| s)
+            .collect::<Vec<_>>(),
+        vec![TransferStatus::WaitingConfirmations]
+    );
+
+    // the sender moves to WaitingConfirmations
+    party.refresh_all();
+    assert_eq!(
+        status_changes(&collector.take()),
+        vec![(batch_transfer.idx, TransferStatus::WaitingConfirmations)]
+    );
+
+    // both transfers settle
+    mine(false);
+    rcv_party.refresh_all();
+    party.refresh_all();
+    assert_eq!(
+        status_changes(&rcv_collector.take())
+            .into_iter()
+            .map(|(_, s)| s)
+            .collect::<Vec<_>>(),
+        vec![TransferStatus::Settled]
+    );
+    assert_eq!(
+        status_changes(&collector.take()),
+        vec![(batch_transfer.idx, TransferStatus::Settled)]
+    );
+
+    // creating and failing a transfer are reported
+    let receive_data = rcv_party.blind_receive();
+    let batch_transfer_idx = receive_data.batch_transfer_idx;
+    assert_eq!(
+        status_changes(&rcv_collector.take()),
+        vec![(batch_transfer_idx, TransferStatus::WaitingCounterparty)]
+    );
+    assert!(rcv_party.fail_transfers_single(batch_transfer_idx));
+    assert_eq!(
+        status_changes(&rcv_collector.take()),
+        vec![(batch_transfer_idx, TransferStatus::Failed)]
+    );
+
+    // no events are received after unregistering the listener
+    party.wallet.set_event_listener(None);
+    party.issue_asset_nia(None);
+    assert!(collector.take().is_empty());
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn no_events_on_failure() {
+    initialize();
+
+    let mut party = get_funded_party!();
+    let collector = Arc::new(EventCollector::default());
+    party.wallet.set_event_listener(Some(collector.clone()));
+
+    // an operation that fails doesn't emit events for the rolled back changes
+    let result = party.wallet.burn(
+        party.online,
+        s!("rgb1inexistent"),
+        AMOUNT,
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert_matches!(result, Err(Error::AssetNotFound { asset_id: _ }));
+    assert!(collector.take().is_empty());
+
+    // colored UTXO creation is reported
+    party.create_utxos_default();
+    assert!(
+        collector
+            .take()
+            .iter()
+            .any(|e| matches!(e, WalletEvent::UtxoCreated { .. }))
+    );
+}
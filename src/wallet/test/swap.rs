@@ -0,0 +1,447 @@
+use super::*;
+
+#[cfg(feature = "electrum")]
+fn swap_transfer(party: &SinglesigParty, asset_id: &str, incoming: bool) -> Transfer {
+    let transfers = party.list_transfers(Some(asset_id));
+    let transfer = transfers.last().unwrap();
+    assert_eq!(transfer.kind, TransferKind::Swap);
+    let batch_transfer = party
+        .db_batch_transfers()
+        .into_iter()
+        .find(|bt| bt.idx == transfer.batch_transfer_idx)
+        .unwrap();
+    assert_eq!(batch_transfer.incoming, incoming);
+    assert!(batch_transfer.swap_id.is_some());
+    transfer.clone()
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount_maker: u64 = 66;
+    let amount_taker: u64 = 42;
+
+    // wallets
+    let mut maker = get_funded_party!();
+    let mut taker = get_funded_party!();
+
+    // issue
+    let asset_nia = maker.issue_asset_nia(None);
+    let asset_cfa = taker.issue_asset_cfa(None, None);
+
+    // maker offers NIA in exchange for CFA
+    let taker_leg = SwapLeg::Asset {
+        asset_id: asset_cfa.asset_id.clone(),
+        assignment: Assignment::Fungible(amount_taker),
+    };
+    let bak_info_before = maker.db_backup_info();
+    let swap_offer = maker
+        .wallet
+        .swap_offer_create(
+            maker.online,
+            asset_nia.asset_id.clone(),
+            Assignment::Fungible(amount_maker),
+            taker_leg.clone(),
+            default_rcv_expiration(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let bak_info_after = maker.db_backup_info();
+    assert!(bak_info_after.last_operation_timestamp > bak_info_before.last_operation_timestamp);
+    let swap_offer_data = swap_offer.swap_offer_data();
+    assert_eq!(swap_offer_data.stage, SwapStage::Offered);
+    assert_eq!(swap_offer_data.maker_asset_id, asset_nia.asset_id);
+    assert_eq!(
+        swap_offer_data.maker_assignment,
+        Assignment::Fungible(amount_maker)
+    );
+    assert_eq!(swap_offer_data.taker_leg, taker_leg);
+    assert!(swap_offer_data.txid.is_none());
+    // the swap offer string can be parsed back
+    let parsed = SwapOffer::new(swap_offer.swap_offer_string()).unwrap();
+    assert_eq!(parsed.swap_offer_data(), swap_offer_data);
+
+    // taker completes the offer with its side of the swap
+    let swap_offer = taker
+        .wallet
+        .swap_offer_take(
+            taker.online,
+            swap_offer.swap_offer_string(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let swap_offer_data = swap_offer.swap_offer_data();
+    assert_eq!(swap_offer_data.stage, SwapStage::Taken);
+    let txid = swap_offer_data.txid.clone().unwrap();
+
+    // maker validates the taker's side and signs
+    let swap_offer = maker
+        .wallet
+        .swap_offer_complete(maker.online, swap_offer.swap_offer_string())
+        .unwrap();
+    assert_eq!(swap_offer.swap_offer_data().stage, SwapStage::Signed);
+    let transfer = swap_transfer(&maker, &asset_nia.asset_id, false);
+    assert_eq!(transfer.status, TransferStatus::WaitingBroadcast);
+    assert_eq!(transfer.txid, Some(txid.clone()));
+    let transfer = swap_transfer(&maker, &asset_cfa.asset_id, true);
+    assert_eq!(transfer.status, TransferStatus::WaitingBroadcast);
+    assert_eq!(transfer.txid, Some(txid.clone()));
+
+    // taker validates the maker's side, signs and broadcasts
+    let swap_offer = taker
+        .wallet
+        .swap_offer_complete(taker.online, swap_offer.swap_offer_string())
+        .unwrap();
+    assert_eq!(swap_offer.swap_offer_data().stage, SwapStage::Broadcast);
+    assert_eq!(swap_offer.swap_offer_data().txid, Some(txid.clone()));
+    let transfer = swap_transfer(&taker, &asset_cfa.asset_id, false);
+    assert_eq!(transfer.status, TransferStatus::WaitingConfirmations);
+    let transfer = swap_transfer(&taker, &asset_nia.asset_id, true);
+    assert_eq!(transfer.status, TransferStatus::WaitingConfirmations);
+
+    // maker detects the broadcast
+    maker.refresh_all();
+    let transfer = swap_transfer(&maker, &asset_nia.asset_id, false);
+    assert_eq!(transfer.status, TransferStatus::WaitingConfirmations);
+
+    // settle the swap
+    mine(false);
+    maker.wait_for_refresh(Some(&asset_nia.asset_id));
+    maker.wait_for_refresh(Some(&asset_cfa.asset_id));
+    taker.wait_for_refresh(Some(&asset_nia.asset_id));
+    taker.wait_for_refresh(Some(&asset_cfa.asset_id));
+    for (party, asset_id, incoming) in [
+        (&maker, &asset_nia.asset_id, false),
+        (&maker, &asset_cfa.asset_id, true),
+        (&taker, &asset_cfa.asset_id, false),
+        (&taker, &asset_nia.asset_id, true),
+    ] {
+        let transfer = swap_transfer(party, asset_id, incoming);
+        assert_eq!(transfer.status, TransferStatus::Settled);
+        assert_eq!(transfer.txid, Some(txid.clone()));
+    }
+    assert_eq!(
+        maker.get_asset_balance(&asset_nia.asset_id).settled,
+        AMOUNT - amount_maker
+    );
+    assert_eq!(
+        maker.get_asset_balance(&asset_cfa.asset_id).settled,
+        amount_taker
+    );
+    assert_eq!(
+        taker.get_asset_balance(&asset_cfa.asset_id).settled,
+        AMOUNT - amount_taker
+    );
+    assert_eq!(
+        taker.get_asset_balance(&asset_nia.asset_id).settled,
+        amount_maker
+    );
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn success_btc() {
+    initialize();
+
+    let amount: u64 = 66;
+    let amount_btc: u64 = 10_000;
+
+    // wallets
+    let mut maker = get_funded_party!();
+    let mut taker = get_funded_party!();
+
+    // issue
+    let asset_nia = maker.issue_asset_nia(None);
+
+    // maker offers NIA in exchange for bitcoins
+    let swap_offer = maker
+        .wallet
+        .swap_offer_create(
+            maker.online,
+            asset_nia.asset_id.clone(),
+            Assignment::Fungible(amount),
+            SwapLeg::Bitcoin { amount: amount_btc },
+            default_rcv_expiration(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let swap_offer = taker
+        .wallet
+        .swap_offer_take(
+            taker.online,
+            swap_offer.swap_offer_string(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let txid = swap_offer.swap_offer_data().txid.unwrap();
+    let swap_offer = maker
+        .wallet
+        .swap_offer_complete(maker.online, swap_offer.swap_offer_string())
+        .unwrap();
+    let btc_balance_before = maker.get_btc_balance_with_sync();
+    taker
+        .wallet
+        .swap_offer_complete(taker.online, swap_offer.swap_offer_string())
+        .unwrap();
+
+    // settle the swap
+    maker.refresh_all();
+    mine(false);
+    maker.wait_for_refresh(Some(&asset_nia.asset_id));
+    taker.wait_for_refresh(Some(&asset_nia.asset_id));
+    let transfer = swap_transfer(&maker, &asset_nia.asset_id, false);
+    assert_eq!(transfer.status, TransferStatus::Settled);
+    assert_eq!(transfer.txid, Some(txid.clone()));
+    let transfer = swap_transfer(&taker, &asset_nia.asset_id, true);
+    assert_eq!(transfer.status, TransferStatus::Settled);
+    assert_eq!(transfer.txid, Some(txid));
+    assert_eq!(
+        maker.get_asset_balance(&asset_nia.asset_id).settled,
+        AMOUNT - amount
+    );
+    assert_eq!(taker.get_asset_balance(&asset_nia.asset_id).settled, amount);
+    let btc_balance_after = maker.get_btc_balance_with_sync();
+    assert_eq!(
+        btc_balance_after.vanilla.settled,
+        btc_balance_before.vanilla.settled + amount_btc
+    );
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn reserved_inputs() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut maker = get_funded_party!();
+    let mut taker = get_funded_party!();
+    let mut rcv_party = get_funded_party!();
+
+    // issue
+    let asset_nia = maker.issue_asset_nia(None);
+
+    // maker offers NIA in exchange for bitcoins
+    let swap_offer = maker
+        .wallet
+        .swap_offer_create(
+            maker.online,
+            asset_nia.asset_id.clone(),
+            Assignment::Fungible(amount),
+            SwapLeg::Bitcoin { amount: 10_000 },
+            default_rcv_expiration(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let offer_psbt = Psbt::from_str(&swap_offer.payload.psbt).unwrap();
+    let offer_txid = offer_psbt.unsigned_tx.compute_txid().to_string();
+    let pending = maker.wallet.list_pending_vanilla_txs().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].txid, offer_txid);
+    assert_eq!(pending[0].r#type, WalletTransactionType::SwapOffer);
+
+    // the offered allocation can't be spent while the offer is open
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset_nia.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let result = maker.send_result(&recipient_map);
+    assert_matches!(result, Err(Error::InsufficientAssignments { asset_id: t, available: a }) if t == asset_nia.asset_id && a == AssignmentsCollection::default());
+
+    // aborting the offer releases the allocation and prevents completing it
+    let taken = taker
+        .wallet
+        .swap_offer_take(
+            taker.online,
+            swap_offer.swap_offer_string(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    maker.abort_pending_vanilla_tx(&offer_txid);
+    assert!(maker.wallet.list_pending_vanilla_txs().unwrap().is_empty());
+    let result = maker
+        .wallet
+        .swap_offer_complete(maker.online, taken.swap_offer_string());
+    assert!(
+        matches!(result, Err(Error::InvalidSwapOffer { details: m }) if m == "the swap offer has been aborted")
+    );
+    let send_result = maker.send_result(&recipient_map).unwrap();
+    assert!(!send_result.txid.is_empty());
+
+    // an expired offer doesn't reserve the allocation anymore
+    let asset_nia = maker.issue_asset_nia(None);
+    let expiration = (now().unix_timestamp() + 3) as u64;
+    maker
+        .wallet
+        .swap_offer_create(
+            maker.online,
+            asset_nia.asset_id.clone(),
+            Assignment::Fungible(amount),
+            SwapLeg::Bitcoin { amount: 10_000 },
+            expiration,
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let receive_data = rcv_party.blind_receive();
+    let recipient_map = HashMap::from([(
+        asset_nia.asset_id.clone(),
+        vec![Recipient {
+            assignment: Assignment::Fungible(amount),
+            recipient_id: receive_data.recipient_id.clone(),
+            witness_data: None,
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let result = maker.send_result(&recipient_map);
+    assert_matches!(result, Err(Error::InsufficientAssignments { .. }));
+    std::thread::sleep(Duration::from_secs(4));
+    let send_result = maker.send_result(&recipient_map).unwrap();
+    assert!(!send_result.txid.is_empty());
+    assert!(maker.wallet.list_pending_vanilla_txs().unwrap().is_empty());
+}
+
+#[cfg(feature = "electrum")]
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let mut maker = get_funded_party!();
+    let mut taker = get_funded_party!();
+
+    // issue
+    let asset_nia = maker.issue_asset_nia(None);
+    let asset_cfa = taker.issue_asset_cfa(None, None);
+    let taker_leg = SwapLeg::Asset {
+        asset_id: asset_cfa.asset_id.clone(),
+        assignment: Assignment::Fungible(amount),
+    };
+
+    // invalid swap offer string
+    let result = SwapOffer::new(s!("invalid"));
+    assert!(
+        matches!(result, Err(Error::InvalidSwapOffer { details: m }) if m == "invalid encoding")
+    );
+
+    // expired offer
+    let result = maker.wallet.swap_offer_create(
+        maker.online,
+        asset_nia.asset_id.clone(),
+        Assignment::Fungible(amount),
+        taker_leg.clone(),
+        0,
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidExpiration)));
+
+    // zero amount
+    let result = maker.wallet.swap_offer_create(
+        maker.online,
+        asset_nia.asset_id.clone(),
+        Assignment::Fungible(0),
+        taker_leg.clone(),
+        default_rcv_expiration(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::InvalidAmountZero)));
+
+    // same asset on both legs
+    let result = maker.wallet.swap_offer_create(
+        maker.online,
+        asset_nia.asset_id.clone(),
+        Assignment::Fungible(amount),
+        SwapLeg::Asset {
+            asset_id: asset_nia.asset_id.clone(),
+            assignment: Assignment::Fungible(amount),
+        },
+        default_rcv_expiration(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidSwapOffer { details: _ })
+    ));
+
+    // taking an offer twice
+    let swap_offer = maker
+        .wallet
+        .swap_offer_create(
+            maker.online,
+            asset_nia.asset_id.clone(),
+            Assignment::Fungible(amount),
+            taker_leg.clone(),
+            default_rcv_expiration(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let taken = taker
+        .wallet
+        .swap_offer_take(
+            taker.online,
+            swap_offer.swap_offer_string(),
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let result = taker.wallet.swap_offer_take(
+        taker.online,
+        taken.swap_offer_string(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(
+        matches!(result, Err(Error::InvalidSwapOffer { details: m }) if m.contains("already been taken"))
+    );
+
+    // completing an offer that has not been taken
+    let result = maker
+        .wallet
+        .swap_offer_complete(maker.online, swap_offer.swap_offer_string());
+    assert!(
+        matches!(result, Err(Error::InvalidSwapOffer { details: m }) if m.contains("needs to be taken"))
+    );
+
+    // completing an offer created by another wallet
+    let result = taker
+        .wallet
+        .swap_offer_complete(taker.online, taken.swap_offer_string());
+    assert!(
+        matches!(result, Err(Error::InvalidSwapOffer { details: m }) if m == "unknown swap offer")
+    );
+
+    // no private keys
+    let mut wo_party = get_funded_noutxo_party(false, None);
+    let result = wo_party.wallet.swap_offer_take(
+        wo_party.online,
+        swap_offer.swap_offer_string(),
+        FEE_RATE,
+        MIN_CONFIRMATIONS,
+    );
+    assert!(matches!(result, Err(Error::WatchOnly)));
+}
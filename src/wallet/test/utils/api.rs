@@ -680,7 +680,7 @@ pub(crate) trait OfflineSigParty {
             let outpoint = unspent.utxo.outpoint;
             let db_txo = db_txos
                 .iter()
-                .find(|t| t.txid == outpoint.txid && t.vout == outpoint.vout)
+                .find(|t| t.txid == outpoint.txid && t.vout == i64::from(outpoint.vout))
                 .unwrap();
             let txo_pending_blind_transfers = pending_blind_transfers.iter().filter(|t| {
                 if let Some(txo) = &t.receive_utxo {
@@ -1074,6 +1074,7 @@ impl<T: OfflineSigParty<W = Wallet>> SinglesigWalletParty for T {
             .blind_receive(
                 asset_id,
                 Assignment::Any,
+                ReceivePolicy::Any,
                 expiration.unwrap_or_else(default_rcv_expiration),
                 TRANSPORT_ENDPOINTS.clone(),
                 MIN_CONFIRMATIONS,
@@ -1086,6 +1087,7 @@ impl<T: OfflineSigParty<W = Wallet>> SinglesigWalletParty for T {
         self.wlt_mut().blind_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             default_rcv_expiration(),
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -1102,6 +1104,7 @@ impl<T: OfflineSigParty<W = Wallet>> SinglesigWalletParty for T {
             .blind_receive(
                 None,
                 Assignment::Any,
+                ReceivePolicy::Any,
                 expiration.unwrap_or_else(default_rcv_expiration),
                 transport_endpoints,
                 MIN_CONFIRMATIONS,
@@ -1241,6 +1244,7 @@ impl<T: OfflineSigParty<W = Wallet>> SinglesigWalletParty for T {
             .witness_receive(
                 None,
                 Assignment::Any,
+                ReceivePolicy::Any,
                 default_rcv_expiration(),
                 TRANSPORT_ENDPOINTS.clone(),
                 MIN_CONFIRMATIONS,
@@ -1278,6 +1282,7 @@ impl SinglesigParty {
             amount,
             FEE_RATE,
             MIN_CONFIRMATIONS,
+            None,
             true,
         )
     }
@@ -1372,6 +1377,7 @@ impl SinglesigParty {
             inflation_amounts.to_vec(),
             FEE_RATE,
             MIN_CONFIRMATIONS,
+            None,
             true,
         )
     }
@@ -1522,6 +1528,7 @@ impl SinglesigParty {
             FEE_RATE,
             MIN_CONFIRMATIONS,
             default_send_expiration(),
+            None,
             false,
         )
     }
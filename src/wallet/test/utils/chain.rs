@@ -245,7 +245,7 @@ pub fn get_tx_height(esplora: bool, txid: &str) -> Option<u64> {
             panic!("electrum indexer unavailable without electrum feature")
         }
     };
-    let indexer = build_indexer(indexer_url).expect("cannot get indexer");
+    let indexer = build_indexer(indexer_url, None).expect("cannot get indexer");
     indexer.get_tx_confirmations(txid).unwrap()
 }
 
@@ -429,7 +429,7 @@ pub(crate) fn wait_indexers_sync() {
 
         for indexer_url in indexer_urls {
             let err_msg = format!("cannot get indexer {indexer_url}");
-            let indexer = build_indexer(indexer_url).expect(&err_msg);
+            let indexer = build_indexer(indexer_url, None).expect(&err_msg);
             if indexer.block_hash(max_blockcount as usize).is_err() {
                 all_synced = false;
             }
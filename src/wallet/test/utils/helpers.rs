@@ -21,6 +21,39 @@ macro_rules! assert_matches {
     }};
 }
 
+/// Wallet event listener collecting the received events
+#[cfg(feature = "electrum")]
+#[derive(Default)]
+pub(crate) struct EventCollector {
+    events: std::sync::Mutex<Vec<WalletEvent>>,
+}
+
+#[cfg(feature = "electrum")]
+impl EventCollector {
+    pub(crate) fn take(&self) -> Vec<WalletEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+
+    pub(crate) fn contains(&self, event: &WalletEvent) -> bool {
+        self.events.lock().unwrap().contains(event)
+    }
+
+    pub(crate) fn any_completed(&self) -> bool {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|e| matches!(e, WalletEvent::AutoRefreshCompleted { .. }))
+    }
+}
+
+#[cfg(feature = "electrum")]
+impl WalletEventListener for EventCollector {
+    fn on_event(&self, event: WalletEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
 pub(crate) fn join_with_sep(parts: &[&str]) -> String {
     parts.join(MAIN_SEPARATOR_STR)
 }
@@ -68,6 +101,7 @@ pub(crate) fn get_test_wallet_data(data_dir: &str) -> WalletData {
         database_type: DatabaseType::Sqlite,
         max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
         supported_schemas: AssetSchema::VALUES.to_vec(),
+        encryption_password: None,
     }
 }
 
@@ -107,6 +141,7 @@ pub(crate) fn get_test_wallet_raw(
             database_type: DatabaseType::Sqlite,
             max_allocations_per_utxo: max_allocations_per_utxo.unwrap_or(MAX_ALLOCATIONS_PER_UTXO),
             supported_schemas: AssetSchema::VALUES.to_vec(),
+            encryption_password: None,
         },
         wallet_keys.clone(),
     )
@@ -290,7 +325,12 @@ pub(crate) fn write_opouts_to_reject_list(filename: &str, opouts: &[String]) {
 
 #[cfg(feature = "electrum")]
 pub(crate) fn get_proxy_client(proxy_url: Option<&str>) -> ProxyClient {
-    ProxyClient::new(proxy_url.unwrap_or(PROXY_URL)).unwrap()
+    ProxyClient::new(
+        proxy_url.unwrap_or(PROXY_URL),
+        None,
+        &NetworkPolicy::default(),
+    )
+    .unwrap()
 }
 
 #[cfg(any(feature = "electrum", feature = "esplora"))]
@@ -299,6 +339,12 @@ pub(crate) fn test_go_online_options(indexer_url: Option<&str>) -> OnlineOptions
         indexer_url: indexer_url.unwrap_or(DEFAULT_INDEXER_URL).to_string(),
         skip_consistency_check: true,
         vanilla_sync_lookback: INDEXER_SYNC_LOOKBACK as u32,
+        auto_refresh: None,
+        fallback_indexer_urls: vec![],
+        indexer_quorum: None,
+        network: NetworkOptions::default(),
+        #[cfg(feature = "cbf")]
+        cbf: None,
     }
 }
 
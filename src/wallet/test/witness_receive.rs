@@ -18,6 +18,7 @@ fn success() {
         .witness_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             expiration_timestamp,
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -33,7 +34,10 @@ fn success() {
     );
     let transfer = party.get_test_transfer_recipient(&receive_data.recipient_id);
     let (_, batch_transfer) = party.get_test_transfer_related(&transfer);
-    assert_eq!(batch_transfer.min_confirmations, MIN_CONFIRMATIONS);
+    assert_eq!(
+        batch_transfer.min_confirmations,
+        i16::from(MIN_CONFIRMATIONS)
+    );
 
     // asset ID + expiration + 0 min confirmations
     let asset = party.issue_asset_cfa(None, None);
@@ -45,6 +49,7 @@ fn success() {
         .witness_receive(
             Some(asset_id.clone()),
             Assignment::Fungible(amount),
+            ReceivePolicy::Any,
             expiration_timestamp,
             TRANSPORT_ENDPOINTS.clone(),
             min_confirmations,
@@ -53,7 +58,10 @@ fn success() {
     assert_eq!(receive_data.expiration_timestamp, expiration_timestamp);
     let transfer = party.get_test_transfer_recipient(&receive_data.recipient_id);
     let (_, batch_transfer) = party.get_test_transfer_related(&transfer);
-    assert_eq!(batch_transfer.min_confirmations, min_confirmations);
+    assert_eq!(
+        batch_transfer.min_confirmations,
+        i16::from(min_confirmations)
+    );
     let invoice = Invoice::new(receive_data.invoice.clone()).unwrap();
     let invoice_data = invoice.invoice_data();
     assert_eq!(invoice_data.asset_schema, Some(AssetSchema::Cfa));
@@ -88,6 +96,7 @@ fn success() {
     let result = party.wallet.witness_receive(
         None,
         Assignment::Any,
+        ReceivePolicy::Any,
         default_rcv_expiration(),
         transport_endpoints.clone(),
         MIN_CONFIRMATIONS,
@@ -111,6 +120,7 @@ fn fail() {
         .witness_receive(
             None,
             Assignment::Any,
+            ReceivePolicy::Any,
             (now().unix_timestamp() - 1) as u64,
             TRANSPORT_ENDPOINTS.clone(),
             MIN_CONFIRMATIONS,
@@ -44,7 +44,7 @@ LISTS_DIR="${CWD}/lists"
 HUB_DIR="${CWD}/hub"
 COMPOSE_FPATH="${CWD}/compose.yaml"
 COMPOSE="$COMPOSE -f ${COMPOSE_FPATH}"
-EXPOSED_PORTS=(3000 50001) # see compose.yaml for the exposed ports
+EXPOSED_PORTS=(3000 18443 18444 50001) # see compose.yaml for the exposed ports
 TIMEOUT=100
 
 BCLI="$COMPOSE exec -T -u blits bitcoind bitcoin-cli -regtest"
@@ -124,6 +124,21 @@ _wait_for_proxy() {
     done
 }
 
+_wait_for_postgres() {
+    # wait for postgres to accept connections
+    postgres_service_name="$1"
+    start_time=$(date +%s)
+    until $COMPOSE exec -T $postgres_service_name pg_isready -U postgres >/dev/null 2>&1; do
+        current_time=$(date +%s)
+        if [ $((current_time - start_time)) -gt $TIMEOUT ]; then
+            echo "Timeout waiting for $postgres_service_name to start"
+            $COMPOSE logs $postgres_service_name
+            exit 1
+        fi
+        sleep 1
+    done
+}
+
 stop_services() {
     # cleanly stop the version 0.1.0 RGB proxy server
     local proxy_mod_proto
@@ -154,7 +169,7 @@ prepare_tests_environment() {
     TESTS=1
 
     COMPOSE="$COMPOSE --profile tests"
-    EXPOSED_PORTS+=(3001 3002 50002 50003 50004 50005 8094 8140 8141)
+    EXPOSED_PORTS+=(3001 3002 5432 50002 50003 50004 50005 8094 8140 8141)
 
     PROXY_MOD_PROTO="proxy-mod-proto"
     PROXY_MOD_API="proxy-mod-api"
@@ -190,6 +205,8 @@ prepare_tests_environment() {
     _wait_for_proxy $PROXY_MOD_PROTO
 
     _wait_for_proxy $PROXY_MOD_API
+
+    _wait_for_postgres postgres
 }
 
 prepare_bindings_examples_environment() {